@@ -0,0 +1,189 @@
+/*
+ * ============================================================================
+ * ACCOUNT MODES
+ * ============================================================================
+ *
+ * A user account's `mode` decides which side of a transfer it may take:
+ * - Normal: sends and receives
+ * - ReceiveOnly: receives only, e.g. a cold savings account
+ * - SendOnly: sends only, e.g. an account being wound down
+ * - Locked: neither; what `deactivate_user` sets
+ * - Deactivating: neither, but open obligations still settle; what
+ *   `deactivate_user` sets until they have (see `deactivation`)
+ *
+ * The authority picks the mode with `set_account_mode`, except while the
+ * account is deactivated, which only `reactivate_user` undoes. The namespace's
+ * compliance authority can impose one with `compliance_set_account_mode`,
+ * which places a hold the user cannot lift; setting Normal releases it.
+ *
+ * The mode replaced an `is_active` flag in place: `Locked` and `Normal`
+ * encode as the false and true bytes, so existing accounts read back as
+ * Locked or Normal unchanged.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount,
+};
+
+/// Which transfer directions a user account accepts
+/// Variant order is part of the account layout; append only
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AccountMode {
+    Locked,       // No transfers (encodes like `is_active = false`)
+    Normal,       // Sends and receives (encodes like `is_active = true`)
+    ReceiveOnly,  // Inbound transfers only
+    SendOnly,     // Outbound transfers only
+    Deactivating, // Settling open obligations before Locked
+}
+
+impl AccountMode {
+    /// Whether an account in this mode may be debited by a transfer
+    pub const fn can_send(self) -> bool {
+        matches!(self, AccountMode::Normal | AccountMode::SendOnly)
+    }
+
+    /// Whether an account in this mode may be credited by a transfer
+    pub const fn can_receive(self) -> bool {
+        matches!(self, AccountMode::Normal | AccountMode::ReceiveOnly)
+    }
+
+    /// The mode settling an already open obligation is checked in
+    ///
+    /// A deactivating account still pays and collects what it committed to
+    /// before deactivation; every other mode applies as usual.
+    pub const fn for_settlement(self) -> AccountMode {
+        match self {
+            AccountMode::Deactivating => AccountMode::Normal,
+            mode => mode,
+        }
+    }
+}
+
+/// Check both parties' modes allow a transfer, sender first
+pub fn require_transfer_modes(sender: AccountMode, receiver: AccountMode) -> Result<()> {
+    match sender {
+        AccountMode::Locked => return err!(CustomError::SenderLocked),
+        AccountMode::ReceiveOnly => return err!(CustomError::SenderReceiveOnly),
+        AccountMode::Deactivating => return err!(CustomError::AccountDeactivating),
+        AccountMode::Normal | AccountMode::SendOnly => {}
+    }
+    match receiver {
+        AccountMode::Locked => err!(CustomError::ReceiverLocked),
+        AccountMode::SendOnly => err!(CustomError::ReceiverSendOnly),
+        AccountMode::Deactivating => err!(CustomError::AccountDeactivating),
+        AccountMode::Normal | AccountMode::ReceiveOnly => Ok(()),
+    }
+}
+
+/// Context for a user choosing their own account mode
+#[derive(Accounts)]
+pub struct SetAccountMode<'info> {
+    /// The user account changing mode
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for the compliance authority imposing a mode
+#[derive(Accounts)]
+pub struct ComplianceSetAccountMode<'info> {
+    /// The user account being overridden
+    #[account(
+        mut,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Namespace config naming the compliance authority
+    #[account(
+        has_one = compliance @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Compliance authority
+    pub compliance: Signer<'info>,
+}
+
+/// Event emitted whenever a user account's mode changes
+#[event]
+pub struct AccountModeChangedEvent {
+    pub user: Pubkey,          // Affected user account
+    pub previous: AccountMode, // Mode before the change
+    pub current: AccountMode,  // Mode after the change
+    pub compliance_hold: bool, // Whether compliance now holds the mode
+    pub tag: InstructionTag,   // Emitting instruction
+}
+
+/// Set the caller's own mode, unless compliance holds it
+pub fn handle_set_account_mode(ctx: Context<SetAccountMode>, mode: AccountMode) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SET_ACCOUNT_MODE)?;
+
+    let user = &mut ctx.accounts.user;
+    require!(!user.compliance_hold, CustomError::ModeHeldByCompliance);
+    require!(user.deactivated_at == 0, CustomError::AccountInactive); // Cooldown applies
+    require!(
+        mode != AccountMode::Deactivating,
+        CustomError::InvalidAccountMode
+    );
+    let previous = user.mode;
+    user.mode = mode;
+
+    emit!(AccountModeChangedEvent {
+        user: user.key(),
+        previous,
+        current: mode,
+        compliance_hold: false,
+        tag: InstructionTag::SetAccountMode,
+    });
+
+    msg!("Account mode: {:?} -> {:?}", previous, mode);
+    Ok(())
+}
+
+/// Impose `mode` on a user; anything but Normal is held against the user
+pub fn handle_compliance_set_account_mode(
+    ctx: Context<ComplianceSetAccountMode>,
+    mode: AccountMode,
+) -> Result<()> {
+    require!(
+        mode != AccountMode::Deactivating,
+        CustomError::InvalidAccountMode
+    );
+    let user = &mut ctx.accounts.user;
+    let previous = user.mode;
+    user.mode = mode;
+    user.compliance_hold = mode != AccountMode::Normal;
+    if mode != AccountMode::Locked {
+        user.deactivated_at = 0; // Compliance lifts a deactivation outright
+    }
+
+    emit!(AccountModeChangedEvent {
+        user: user.key(),
+        previous,
+        current: mode,
+        compliance_hold: user.compliance_hold,
+        tag: InstructionTag::ComplianceSetAccountMode,
+    });
+
+    msg!("Compliance set account mode: {:?} -> {:?}", previous, mode);
+    Ok(())
+}
@@ -0,0 +1,245 @@
+/*
+ * ============================================================================
+ * ACTIVITY LOG
+ * ============================================================================
+ *
+ * Events disappear once RPC nodes prune their logs, so users may keep an
+ * on-chain history instead: an `ActivityLog` (`[b"activity_log", user]`)
+ * holding the last `ACTIVITY_LOG_CAPACITY` operations on the account in a ring
+ * buffer. `init_activity_log` creates it with an `Opened` entry marking where
+ * the history starts; `update_user`, `deactivate_user`
+ * and every instruction that changes a balance append to it, for each side
+ * whose balance moves.
+ *
+ * Like a transfer policy, the log sits at a fixed address those
+ * instructions always receive, so a caller cannot leave it out to keep an
+ * operation off the record; for an account without a log the address is
+ * simply empty. Logs of accounts passed as remaining accounts, such as
+ * batch receivers, follow them there and are checked with
+ * `require_log_address`. Deposits, withdrawals and payouts from the
+ * treasury have no counterparty and log the default key.
+ *
+ * The log is zero-copy, so an append touches one entry rather than
+ * deserializing and re-serializing the whole buffer.
+ */
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{CustomError, GlobalConfig, InstructionTag, UserAccount, ACTIVITY_LOG_CAPACITY};
+
+/// Kind of operation an activity entry records
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum ActivityKind {
+    Sent = 1,        // Balance sent to the counterparty
+    Received = 2,    // Balance received from the counterparty
+    Updated = 3,     // Profile fields changed
+    Deactivated = 4, // Account locked by its authority
+    Opened = 5,      // Log created, nothing earlier is on record
+}
+
+impl ActivityKind {
+    /// Kind stored as `kind`, if any
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            1 => Some(Self::Sent),
+            2 => Some(Self::Received),
+            3 => Some(Self::Updated),
+            4 => Some(Self::Deactivated),
+            5 => Some(Self::Opened),
+            _ => None, // Empty slot
+        }
+    }
+}
+
+/// One logged operation
+#[zero_copy]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ActivityEntry {
+    pub counterparty: Pubkey, // Other user account, default when none (32 bytes)
+    pub amount: u64,          // Amount moved, zero when none (8 bytes)
+    pub timestamp: i64,       // When it happened (8 bytes)
+    pub kind: u8,             // `ActivityKind`, zero for an empty slot (1 byte)
+    pub tag: u8,              // `InstructionTag` of the logging instruction (1 byte)
+    pub _padding: [u8; 6],    // Keeps the entry 8-byte aligned (6 bytes)
+}
+
+impl ActivityEntry {
+    /// Entry for `kind` logged by `tag`
+    pub fn new(
+        kind: ActivityKind,
+        tag: InstructionTag,
+        counterparty: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            counterparty,
+            amount,
+            timestamp,
+            kind: kind as u8,
+            tag: tag as u8,
+            _padding: [0; 6],
+        }
+    }
+}
+
+/// Ring buffer of a user's most recent operations
+#[account(zero_copy)]
+pub struct ActivityLog {
+    pub user: Pubkey, // Owning user account (32 bytes)
+    pub count: u64,   // Entries appended since creation (8 bytes)
+    pub entries: [ActivityEntry; ACTIVITY_LOG_CAPACITY], // Ring buffer (56 * N bytes)
+    pub bump: u8,     // PDA bump seed (1 byte)
+    pub _padding: [u8; 7], // Keeps the log 8-byte aligned (7 bytes)
+}
+
+impl ActivityLog {
+    /// Space required for the log including the discriminator
+    pub const LEN: usize = 8 + std::mem::size_of::<ActivityLog>();
+
+    /// Overwrite the oldest entry with `entry`
+    pub fn append(&mut self, entry: ActivityEntry) {
+        let slot = (self.count % ACTIVITY_LOG_CAPACITY as u64) as usize;
+        self.entries[slot] = entry;
+        self.count += 1;
+    }
+
+    /// Logged entries, newest first
+    pub fn recent(&self) -> impl Iterator<Item = &ActivityEntry> {
+        let len = self.count.min(ACTIVITY_LOG_CAPACITY as u64) as usize;
+        (1..=len).map(move |back| {
+            let slot = (self.count - back as u64) % ACTIVITY_LOG_CAPACITY as u64;
+            &self.entries[slot as usize]
+        })
+    }
+
+    /// True if the log holds every operation from `since` on: it was opened
+    /// before then and no entry of the range has been overwritten yet
+    pub fn covers_since(&self, since: i64) -> bool {
+        match self.recent().last() {
+            // Either the opening entry or the oldest one the buffer kept
+            Some(oldest)
+                if self.count > ACTIVITY_LOG_CAPACITY as u64
+                    || ActivityKind::from_u8(oldest.kind) == Some(ActivityKind::Opened) =>
+            {
+                oldest.timestamp < since
+            }
+            _ => false, // No opening entry to date the history from
+        }
+    }
+}
+
+/// Append `entry` to `user`'s activity log at `info`, if the user keeps one
+///
+/// `info` must be the user's log address, checked by the caller's context
+/// or with `require_log_address`.
+pub fn log_activity(info: &AccountInfo, user: Pubkey, entry: ActivityEntry) -> Result<()> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(()); // The user keeps no on-chain history
+    }
+    require!(info.is_writable, ErrorCode::AccountNotMutable);
+    let mut data = info.try_borrow_mut_data()?;
+    require!(
+        data.len() >= ActivityLog::LEN && data[..8] == ActivityLog::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let log: &mut ActivityLog = bytemuck::try_from_bytes_mut(&mut data[8..ActivityLog::LEN])
+        .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+    require_keys_eq!(log.user, user, CustomError::ActivityLogMismatch);
+    log.append(entry);
+    Ok(())
+}
+
+/// Ordering marker of a transfer event, unique per sending account
+///
+/// Consumers reading events from several RPC sources de-duplicate on
+/// `(sender, seq)` and order by it; `slot` places the event in the chain.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EventOrder {
+    pub slot: u64, // Slot the transfer landed in
+    pub seq: u64,  // Sender's `UserAccount::sequence` after the transfer
+}
+
+/// Log `sent` leaving `sender` and `received` of it reaching `receiver`,
+/// each at its own log, and count the transfer on both accounts
+pub fn log_transfer(
+    (sender_log, sender_key, sender): (&AccountInfo, Pubkey, &mut UserAccount),
+    (receiver_log, receiver_key, receiver): (&AccountInfo, Pubkey, &mut UserAccount),
+    sent: u64,
+    received: u64,
+    tag: InstructionTag,
+    now: i64,
+) -> Result<EventOrder> {
+    let seq = sender.next_sequence()?;
+    receiver.next_sequence()?;
+
+    let sent = ActivityEntry::new(ActivityKind::Sent, tag, receiver_key, sent, now);
+    log_activity(sender_log, sender_key, sent)?;
+    let received = ActivityEntry::new(ActivityKind::Received, tag, sender_key, received, now);
+    log_activity(receiver_log, receiver_key, received)?;
+    Ok(EventOrder {
+        slot: Clock::get()?.slot,
+        seq,
+    })
+}
+
+/// Fail unless `info` is at `user`'s activity log address
+pub fn require_log_address(info: &AccountInfo, user: &Pubkey) -> Result<()> {
+    let (address, _) = Pubkey::find_program_address(&[b"activity_log", user.as_ref()], &crate::ID);
+    require_keys_eq!(*info.key, address, CustomError::ActivityLogMismatch);
+    Ok(())
+}
+
+/// Context for creating a user's activity log
+#[derive(Accounts)]
+pub struct InitActivityLog<'info> {
+    /// Log being created
+    #[account(
+        init,
+        payer = authority,
+        space = ActivityLog::LEN,
+        seeds = [b"activity_log", user.key().as_ref()], // One log per user
+        bump
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Create an empty activity log for the caller's account
+pub fn handle_init_activity_log(ctx: Context<InitActivityLog>) -> Result<()> {
+    let mut log = ctx.accounts.activity_log.load_init()?; // Zero-initialized by the runtime
+    log.user = ctx.accounts.user.key();
+    log.bump = ctx.bumps.activity_log;
+    log.append(ActivityEntry::new(
+        ActivityKind::Opened,
+        InstructionTag::InitActivityLog,
+        Pubkey::default(),
+        0,
+        Clock::get()?.unix_timestamp,
+    ));
+
+    msg!("Activity log created for {}", log.user);
+    Ok(())
+}
@@ -0,0 +1,311 @@
+/*
+ * ============================================================================
+ * ALLOWANCES
+ * ============================================================================
+ *
+ * ERC20-style approvals: an owner lets a delegate move up to `amount` of
+ * their balance with `transfer_from`, which the delegate signs instead of
+ * the owner. Each approval is an `Allowance` PDA per owner account and
+ * delegate; every transfer draws it down with checked math, and
+ * `revoke_delegate` closes it and refunds the rent.
+ *
+ * `approve_delegate` creates the allowance. To change an existing one, revoke
+ * and approve again in the same transaction; the delegate cannot spend in
+ * between, which avoids the classic approve front-running race.
+ *
+ * Every change is emitted as an `AllowanceChangedEvent`, so indexers can track
+ * remaining approvals without reading the accounts.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::guards::{self, Guard};
+use crate::{
+    apply_transfer, instruction_flags, load_policy, log_transfer, record_transfer_volume,
+    signer_keys, CustomError, EpochStats, GlobalConfig, InstructionTag, Ledger, LedgerAccount,
+    PolicyInput, TokenTransferEvent, UserAccount,
+};
+
+/// A delegate's remaining right to spend from an owner's account
+#[account]
+pub struct Allowance {
+    pub owner: Pubkey,     // Owner user account (32 bytes)
+    pub authority: Pubkey, // Owner authority that approved and paid (32 bytes)
+    pub delegate: Pubkey,  // Key allowed to spend (32 bytes)
+    pub amount: u64,       // Remaining spendable amount (8 bytes)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl Allowance {
+    /// Space required for the allowance including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // owner: Pubkey
+        32 +                     // authority: Pubkey
+        32 +                     // delegate: Pubkey
+        8 +                      // amount: u64
+        1; // bump: u8
+
+    /// Draw `amount` from the allowance, returning what remains
+    pub fn spend(&mut self, amount: u64) -> Result<u64> {
+        self.amount = self
+            .amount
+            .checked_sub(amount)
+            .ok_or(CustomError::AllowanceExceeded)?;
+        Ok(self.amount)
+    }
+}
+
+/// Context for approving a delegate on the caller's account
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct ApproveDelegate<'info> {
+    /// Allowance being created
+    #[account(
+        init,
+        payer = authority,
+        space = Allowance::LEN,
+        seeds = [b"allowance", owner.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    /// The owner's user account
+    #[account(
+        has_one = authority,
+        constraint = owner.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub owner: Account<'info, UserAccount>,
+
+    /// Owner authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revoking a delegate, refunding the allowance's rent
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    /// Allowance being closed
+    #[account(
+        mut,
+        close = authority,
+        has_one = owner,
+        has_one = authority,
+        seeds = [b"allowance", owner.key().as_ref(), allowance.delegate.as_ref()],
+        bump = allowance.bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    /// The owner's user account
+    #[account(
+        constraint = owner.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub owner: Account<'info, UserAccount>,
+
+    /// Owner authority, receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the owner belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for a delegate transferring out of an owner's account
+#[derive(Accounts)]
+pub struct TransferFrom<'info> {
+    /// Allowance being drawn down
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = delegate,
+        seeds = [b"allowance", owner.key().as_ref(), delegate.key().as_ref()],
+        bump = allowance.bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    /// The owner's user account, debited
+    #[account(
+        mut,
+        constraint = owner.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), owner.authority.as_ref()],
+        bump
+    )]
+    pub owner: Account<'info, UserAccount>,
+
+    /// Receiver's user account, credited
+    #[account(
+        mut,
+        constraint = receiver.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), receiver.authority.as_ref()],
+        bump
+    )]
+    pub receiver: Account<'info, UserAccount>,
+
+    /// Delegate spending the allowance
+    pub delegate: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the transfer entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Owner's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the owner's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", owner.key().as_ref()], bump)]
+    pub owner_policy: UncheckedAccount<'info>,
+
+    /// Owner's activity log address, appended to when the owner keeps one
+    /// CHECK: address is the owner's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", owner.key().as_ref()], bump)]
+    pub owner_activity: UncheckedAccount<'info>,
+
+    /// Receiver's activity log address, appended to when the receiver keeps one
+    /// CHECK: address is the receiver's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", receiver.key().as_ref()], bump)]
+    pub receiver_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted whenever an allowance is approved, spent or revoked
+#[event]
+pub struct AllowanceChangedEvent {
+    pub owner: Pubkey,       // Owner user account
+    pub delegate: Pubkey,    // Delegate key
+    pub previous: u64,       // Allowance before the change
+    pub current: u64,        // Allowance after the change (0 once revoked)
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Let `delegate` transfer up to `amount` out of the caller's account
+pub fn handle_approve_delegate(
+    ctx: Context<ApproveDelegate>,
+    delegate: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::APPROVE_DELEGATE),
+        Guard::Positive(amount), // Revoke instead of approving zero
+    ])?;
+
+    let allowance = &mut ctx.accounts.allowance;
+    allowance.owner = ctx.accounts.owner.key();
+    allowance.authority = ctx.accounts.authority.key();
+    allowance.delegate = delegate;
+    allowance.amount = amount;
+    allowance.bump = ctx.bumps.allowance;
+
+    emit!(AllowanceChangedEvent {
+        owner: allowance.owner,
+        delegate,
+        previous: 0,
+        current: amount,
+        tag: InstructionTag::ApproveDelegate,
+    });
+
+    msg!("Delegate {} approved for {}", delegate, amount);
+    Ok(())
+}
+
+/// Revoke a delegate's allowance
+pub fn handle_revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+    let allowance = &ctx.accounts.allowance;
+
+    emit!(AllowanceChangedEvent {
+        owner: allowance.owner,
+        delegate: allowance.delegate,
+        previous: allowance.amount,
+        current: 0,
+        tag: InstructionTag::RevokeDelegate,
+    });
+
+    msg!("Delegate {} revoked", allowance.delegate);
+    Ok(())
+}
+
+/// Transfer `amount` from the owner to the receiver as the owner's delegate
+pub fn handle_transfer_from(ctx: Context<TransferFrom>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let owner = &ctx.accounts.owner;
+    let policy = load_policy(&ctx.accounts.owner_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: ctx.accounts.receiver.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::TRANSFER_FROM),
+        Guard::Distinct(&[owner.key(), ctx.accounts.receiver.key()]),
+        Guard::NotJoint(owner), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(owner, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(owner, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input), // Binds delegates too
+    ])?;
+
+    let owner = &mut ctx.accounts.owner;
+    let receiver = &mut ctx.accounts.receiver;
+    owner.record_daily_spend(amount, now)?;
+
+    let allowance = &mut ctx.accounts.allowance;
+    let previous = allowance.amount;
+    let current = allowance.spend(amount)?;
+
+    apply_transfer(owner, receiver, amount, now)?;
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        amount,
+        InstructionTag::TransferFrom,
+    )?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Users, amount)?;
+    let order = log_transfer(
+        (&ctx.accounts.owner_activity, owner.key(), owner),
+        (&ctx.accounts.receiver_activity, receiver.key(), receiver),
+        amount,
+        amount,
+        InstructionTag::TransferFrom,
+        now,
+    )?;
+
+    emit!(TokenTransferEvent {
+        from: owner.authority,
+        to: receiver.authority,
+        amount,
+        fee: 0, // Fee-free
+        timestamp: now,
+        order,
+        tag: InstructionTag::TransferFrom,
+    });
+    emit!(AllowanceChangedEvent {
+        owner: owner.key(),
+        delegate: allowance.delegate,
+        previous,
+        current,
+        tag: InstructionTag::TransferFrom,
+    });
+
+    msg!(
+        "Delegate {} transferred {} tokens",
+        allowance.delegate,
+        amount
+    );
+    Ok(())
+}
@@ -0,0 +1,313 @@
+/*
+ * ============================================================================
+ * PROFILE ATTACHMENTS
+ * ============================================================================
+ *
+ * Users anchor off-chain documents (terms, invoices, avatars) to their
+ * account by content hash. Only the 32-byte hash of the IPFS/Arweave CID is
+ * stored, so anyone holding the document can verify it against the chain.
+ *
+ * Attachments live in pages (`[b"attachments", user, page]`) so no single
+ * account grows without bound; a user may hold at most
+ * `MAX_ATTACHMENT_PAGES` pages of `ATTACHMENTS_PER_PAGE` entries each. A page
+ * is sized to its entries and resized with `resize_with_rent` as documents
+ * are attached or removed, so the authority only pays rent for what it uses.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    compact_to_fit, instruction_flags, require_instruction_enabled, resize_with_rent,
+    AccountCompactedEvent, CustomError, GlobalConfig, InstructionTag, UserAccount,
+    ATTACHMENTS_PER_PAGE, MAX_ATTACHMENT_PAGES,
+};
+
+/// What an attached document is
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DocumentKind {
+    Terms,   // Terms of service or agreement
+    Invoice, // Invoice or receipt
+    Avatar,  // Profile image
+    Other,   // Anything else
+}
+
+/// One anchored document
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Attachment {
+    pub kind: DocumentKind, // Document kind (1 byte)
+    pub cid_hash: [u8; 32], // Hash of the content identifier (32 bytes)
+    pub attached_at: i64,   // When the document was attached (8 bytes)
+}
+
+impl Attachment {
+    /// Serialized size of an attachment entry
+    pub const LEN: usize = 1 + 32 + 8;
+}
+
+/// A page of a user's attachments
+#[account]
+pub struct AttachmentPage {
+    pub user: Pubkey,             // Owning user account (32 bytes)
+    pub page: u16,                // Page index (2 bytes)
+    pub entries: Vec<Attachment>, // Attached documents (4 + 41 * N bytes)
+    pub bump: u8,                 // PDA bump seed (1 byte)
+}
+
+impl AttachmentPage {
+    /// Space required for a full page including the discriminator
+    pub const LEN: usize = Self::space(ATTACHMENTS_PER_PAGE);
+
+    /// Space required for a page holding `entries` attachments
+    pub const fn space(entries: usize) -> usize {
+        8 +                      // Anchor discriminator
+        32 +                     // user: Pubkey
+        2 +                      // page: u16
+        4 + Attachment::LEN * entries + // entries: Vec<Attachment>
+        1 // bump: u8
+    }
+}
+
+/// Context for creating an attachment page
+#[derive(Accounts)]
+#[instruction(page: u16)]
+pub struct CreateAttachmentPage<'info> {
+    /// Page being created
+    #[account(
+        init,
+        payer = authority,
+        space = AttachmentPage::space(0), // Grows as documents are attached
+        seeds = [b"attachments", user.key().as_ref(), &page.to_le_bytes()],
+        bump
+    )]
+    pub attachments: Account<'info, AttachmentPage>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for adding or removing an attachment
+#[derive(Accounts)]
+pub struct ManageAttachments<'info> {
+    /// Page being modified
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [b"attachments", user.key().as_ref(), &attachments.page.to_le_bytes()],
+        bump = attachments.bump
+    )]
+    pub attachments: Account<'info, AttachmentPage>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, settles the page's rent as it resizes
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for rent top-ups
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ManageAttachments<'info> {
+    /// Resize the page to fit its current entries
+    fn fit_page(&self) -> Result<()> {
+        resize_with_rent(
+            &self.attachments.to_account_info(),
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+            AttachmentPage::space(self.attachments.entries.len()),
+        )
+    }
+}
+
+/// Context for shrinking an over-allocated attachment page
+#[derive(Accounts)]
+pub struct CompactAccount<'info> {
+    /// Page being compacted
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [b"attachments", user.key().as_ref(), &attachments.page.to_le_bytes()],
+        bump = attachments.bump
+    )]
+    pub attachments: Account<'info, AttachmentPage>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, receives the refunded rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program (unused on shrink, required by the resize helper)
+    pub system_program: Program<'info, System>,
+}
+
+/// Event emitted when a document is attached
+#[event]
+pub struct DocumentAttachedEvent {
+    pub user: Pubkey,        // Owning user account
+    pub page: u16,           // Page holding the entry
+    pub kind: DocumentKind,  // Document kind
+    pub cid_hash: [u8; 32],  // Hash of the content identifier
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a document is removed
+#[event]
+pub struct DocumentRemovedEvent {
+    pub user: Pubkey,        // Owning user account
+    pub page: u16,           // Page that held the entry
+    pub cid_hash: [u8; 32],  // Hash of the removed content identifier
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create an empty attachment page for the caller's account
+pub fn handle_create_attachment_page(ctx: Context<CreateAttachmentPage>, page: u16) -> Result<()> {
+    require!(
+        page < MAX_ATTACHMENT_PAGES,
+        CustomError::AttachmentLimitReached
+    );
+
+    let attachments = &mut ctx.accounts.attachments;
+    attachments.user = ctx.accounts.user.key();
+    attachments.page = page;
+    attachments.entries = Vec::new();
+    attachments.bump = ctx.bumps.attachments;
+
+    msg!("Attachment page {} created for {}", page, attachments.user);
+    Ok(())
+}
+
+/// Anchor a document's content hash to the caller's account
+pub fn handle_attach_document(
+    ctx: Context<ManageAttachments>,
+    kind: DocumentKind,
+    cid_hash: [u8; 32],
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::ATTACH_DOCUMENT)?;
+    require!(ctx.accounts.user.is_active(), CustomError::AccountInactive);
+
+    let attachments = &mut ctx.accounts.attachments;
+    require!(
+        attachments.entries.len() < ATTACHMENTS_PER_PAGE,
+        CustomError::AttachmentLimitReached
+    );
+    require!(
+        !attachments.entries.iter().any(|a| a.cid_hash == cid_hash),
+        CustomError::DuplicateAttachment
+    );
+
+    attachments.entries.push(Attachment {
+        kind,
+        cid_hash,
+        attached_at: Clock::get()?.unix_timestamp,
+    });
+    ctx.accounts.fit_page()?;
+    let attachments = &ctx.accounts.attachments;
+
+    emit!(DocumentAttachedEvent {
+        user: attachments.user,
+        page: attachments.page,
+        kind,
+        cid_hash,
+        tag: InstructionTag::AttachDocument,
+    });
+
+    msg!("Document attached on page {}", attachments.page);
+    Ok(())
+}
+
+/// Remove an attached document by its content hash
+pub fn handle_remove_document(ctx: Context<ManageAttachments>, cid_hash: [u8; 32]) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::REMOVE_DOCUMENT)?;
+
+    let attachments = &mut ctx.accounts.attachments;
+    let index = attachments
+        .entries
+        .iter()
+        .position(|a| a.cid_hash == cid_hash)
+        .ok_or(CustomError::AttachmentNotFound)?;
+    attachments.entries.remove(index); // Keep attachment order stable
+    ctx.accounts.fit_page()?; // Refund the freed entry's rent
+    let attachments = &ctx.accounts.attachments;
+
+    emit!(DocumentRemovedEvent {
+        user: attachments.user,
+        page: attachments.page,
+        cid_hash,
+        tag: InstructionTag::RemoveDocument,
+    });
+
+    msg!("Document removed from page {}", attachments.page);
+    Ok(())
+}
+
+/// Shrink an attachment page to fit its entries, refunding rent to the authority
+/// Pages allocated at full size before on-demand resizing are the main target
+pub fn handle_compact_account(ctx: Context<CompactAccount>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let info = accounts.attachments.to_account_info();
+    let old_len = info.data_len();
+    let new_len = AttachmentPage::space(accounts.attachments.entries.len());
+
+    let refunded = compact_to_fit(
+        &accounts.attachments,
+        &accounts.authority.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(AccountCompactedEvent {
+        account: info.key(),
+        old_len: old_len as u64,
+        new_len: new_len as u64,
+        refunded,
+        tag: InstructionTag::CompactAccount,
+    });
+
+    msg!(
+        "Compacted {} from {} to {} bytes",
+        info.key(),
+        old_len,
+        new_len
+    );
+    Ok(())
+}
@@ -0,0 +1,335 @@
+/*
+ * ============================================================================
+ * ATTESTATIONS
+ * ============================================================================
+ *
+ * A registry of typed claims about users. The admin registers issuers; an
+ * active issuer publishes compact attestation PDAs
+ * (`[b"attestation", issuer, user, claim_type]`) with an optional expiry and
+ * may revoke them at any time.
+ *
+ * Instructions that need a claim take the attestation and issuer record as
+ * accounts and call `require_attestation`, or use `Attestation::is_valid` in
+ * a constraint.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, ClockTolerance, CustomError, GlobalConfig,
+    InstructionTag, UserAccount,
+};
+
+/// Well-known claim types; issuers may define others above `CUSTOM_START`
+pub mod claim_types {
+    pub const AGE_OVER_18: u16 = 1;
+    pub const ACCREDITED_INVESTOR: u16 = 2;
+    pub const DAO_MEMBER: u16 = 3; // `data` holds the DAO address
+    pub const CUSTOM_START: u16 = 1 << 15;
+}
+
+/// A registered attestation issuer
+#[account]
+pub struct IssuerRecord {
+    pub issuer: Pubkey, // Issuer signing key (32 bytes)
+    pub active: bool,   // Cleared on deregistration (1 byte)
+    pub bump: u8,       // PDA bump seed (1 byte)
+}
+
+impl IssuerRecord {
+    /// Space required for an issuer record including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // issuer: Pubkey
+        1 +                      // active: bool
+        1; // bump: u8
+}
+
+/// A claim made by an issuer about a user
+#[account]
+pub struct Attestation {
+    pub issuer: Pubkey,  // Issuing key (32 bytes)
+    pub user: Pubkey,    // User account the claim is about (32 bytes)
+    pub claim_type: u16, // One of `claim_types` (2 bytes)
+    pub data: [u8; 32],  // Claim-specific payload (32 bytes)
+    pub issued_at: i64,  // Issue timestamp (8 bytes)
+    pub expires_at: i64, // Expiry timestamp, 0 = never (8 bytes)
+    pub bump: u8,        // PDA bump seed (1 byte)
+}
+
+impl Attestation {
+    /// Space required for an attestation including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // issuer: Pubkey
+        32 +                     // user: Pubkey
+        2 +                      // claim_type: u16
+        32 +                     // data: [u8; 32]
+        8 +                      // issued_at: i64
+        8 +                      // expires_at: i64
+        1; // bump: u8
+
+    /// Whether this attestation proves `claim_type` about `user` at `now`
+    pub fn is_valid(
+        &self,
+        user: &Pubkey,
+        claim_type: u16,
+        now: i64,
+        tolerance: ClockTolerance,
+    ) -> bool {
+        self.user == *user
+            && self.claim_type == claim_type
+            && (self.expires_at == 0 || tolerance.before(now, self.expires_at))
+    }
+}
+
+/// Require a live attestation of `claim_type` about `user` from an active
+/// issuer, judging its expiry with the namespace's `tolerance`
+pub fn require_attestation(
+    attestation: &Attestation,
+    issuer: &IssuerRecord,
+    user: &Pubkey,
+    claim_type: u16,
+    tolerance: ClockTolerance,
+) -> Result<()> {
+    require_keys_eq!(
+        attestation.issuer,
+        issuer.issuer,
+        CustomError::AttestationInvalid
+    );
+    require!(issuer.active, CustomError::IssuerInactive);
+    require!(
+        attestation.is_valid(user, claim_type, Clock::get()?.unix_timestamp, tolerance),
+        CustomError::AttestationInvalid
+    );
+    Ok(())
+}
+
+/// Context for registering an issuer
+#[derive(Accounts)]
+#[instruction(issuer: Pubkey)]
+pub struct RegisterIssuer<'info> {
+    /// Issuer record being created
+    #[account(
+        init,
+        payer = admin,
+        space = IssuerRecord::LEN,
+        seeds = [b"issuer", config.key().as_ref(), issuer.as_ref()],
+        bump
+    )]
+    pub issuer_record: Account<'info, IssuerRecord>,
+
+    /// Global config; only its admin registers issuers
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for activating or deactivating an issuer
+#[derive(Accounts)]
+pub struct SetIssuerActive<'info> {
+    /// Issuer record being changed
+    #[account(
+        mut,
+        seeds = [b"issuer", config.key().as_ref(), issuer_record.issuer.as_ref()],
+        bump = issuer_record.bump
+    )]
+    pub issuer_record: Account<'info, IssuerRecord>,
+
+    /// Global config; only its admin manages issuers
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+}
+
+/// Context for issuing an attestation
+#[derive(Accounts)]
+#[instruction(claim_type: u16)]
+pub struct IssueAttestation<'info> {
+    /// Attestation being created
+    #[account(
+        init,
+        payer = issuer,
+        space = Attestation::LEN,
+        seeds = [
+            b"attestation",
+            issuer.key().as_ref(),
+            user.key().as_ref(),
+            &claim_type.to_le_bytes()
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Issuer's registry record
+    #[account(
+        constraint = issuer_record.active @ CustomError::IssuerInactive,
+        seeds = [b"issuer", config.key().as_ref(), issuer.key().as_ref()],
+        bump = issuer_record.bump
+    )]
+    pub issuer_record: Account<'info, IssuerRecord>,
+
+    /// User account the claim is about
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Issuer, pays rent
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revoking an attestation
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    /// Attestation being revoked, rent returned to the issuer
+    #[account(mut, close = issuer, has_one = issuer)]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Issuer that published the attestation
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+}
+
+/// Context for checking an attestation (permissionless, read-only)
+#[derive(Accounts)]
+pub struct VerifyAttestation<'info> {
+    /// Attestation being checked
+    pub attestation: Account<'info, Attestation>,
+
+    /// Registry record of the attestation's issuer
+    #[account(seeds = [b"issuer", config.key().as_ref(), attestation.issuer.as_ref()], bump = issuer_record.bump)]
+    pub issuer_record: Account<'info, IssuerRecord>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when an attestation is issued or revoked
+#[event]
+pub struct AttestationChangedEvent {
+    pub issuer: Pubkey,      // Issuing key
+    pub user: Pubkey,        // User account the claim is about
+    pub claim_type: u16,     // Claim type
+    pub expires_at: i64,     // Expiry, 0 = never
+    pub revoked: bool,       // True when revoked
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Register `issuer` as an attestation issuer
+pub fn handle_register_issuer(ctx: Context<RegisterIssuer>, issuer: Pubkey) -> Result<()> {
+    let record = &mut ctx.accounts.issuer_record;
+    record.issuer = issuer;
+    record.active = true;
+    record.bump = ctx.bumps.issuer_record;
+
+    msg!("Attestation issuer registered: {}", issuer);
+    Ok(())
+}
+
+/// Activate or deactivate an issuer; deactivation invalidates its attestations
+pub fn handle_set_issuer_active(ctx: Context<SetIssuerActive>, active: bool) -> Result<()> {
+    let record = &mut ctx.accounts.issuer_record;
+    record.active = active;
+
+    msg!("Issuer {} active: {}", record.issuer, active);
+    Ok(())
+}
+
+/// Publish a claim about a user
+pub fn handle_issue_attestation(
+    ctx: Context<IssueAttestation>,
+    claim_type: u16,
+    data: [u8; 32],
+    expires_at: i64,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::ISSUE_ATTESTATION)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        expires_at == 0 || expires_at > now,
+        CustomError::InvalidAttestationExpiry
+    );
+
+    let attestation = &mut ctx.accounts.attestation;
+    attestation.issuer = ctx.accounts.issuer.key();
+    attestation.user = ctx.accounts.user.key();
+    attestation.claim_type = claim_type;
+    attestation.data = data;
+    attestation.issued_at = now;
+    attestation.expires_at = expires_at;
+    attestation.bump = ctx.bumps.attestation;
+
+    emit!(AttestationChangedEvent {
+        issuer: attestation.issuer,
+        user: attestation.user,
+        claim_type,
+        expires_at,
+        revoked: false,
+        tag: InstructionTag::IssueAttestation,
+    });
+
+    msg!("Attestation {} issued for {}", claim_type, attestation.user);
+    Ok(())
+}
+
+/// Revoke and close an attestation
+pub fn handle_revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+    let attestation = &ctx.accounts.attestation;
+
+    emit!(AttestationChangedEvent {
+        issuer: attestation.issuer,
+        user: attestation.user,
+        claim_type: attestation.claim_type,
+        expires_at: attestation.expires_at,
+        revoked: true,
+        tag: InstructionTag::RevokeAttestation,
+    });
+
+    msg!(
+        "Attestation {} revoked for {}",
+        attestation.claim_type,
+        attestation.user
+    );
+    Ok(())
+}
+
+/// Fail unless the attestation proves `claim_type` about `user`
+pub fn handle_verify_attestation(
+    ctx: Context<VerifyAttestation>,
+    user: Pubkey,
+    claim_type: u16,
+) -> Result<()> {
+    require_attestation(
+        &ctx.accounts.attestation,
+        &ctx.accounts.issuer_record,
+        &user,
+        claim_type,
+        ctx.accounts.config.clock_tolerance(),
+    )
+}
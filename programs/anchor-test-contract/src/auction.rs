@@ -0,0 +1,545 @@
+/*
+ * ============================================================================
+ * SEALED-BID (VICKREY) AUCTIONS
+ * ============================================================================
+ *
+ * A seller opens an auction with a commit window and a reveal window:
+ * - during `[created, commit_end)` bidders commit `hash(amount || salt)`
+ *   through the commit-reveal module and lock a lamport deposit
+ * - during `[commit_end, reveal_end)` bidders reveal; a revealed amount must
+ *   be covered by the deposit
+ * - after `reveal_end` anyone may settle: the highest revealed bidder wins
+ *   and pays the second-highest revealed bid (or the reserve, if higher)
+ *
+ * After settlement every bid is claimed back: the winner's deposit pays the
+ * price to the seller, bidders who never revealed forfeit
+ * `non_reveal_penalty_bps` of their deposit to the seller, and the rest of
+ * each deposit plus the bid account rent returns to the bidder.
+ *
+ * A bid is one of the bidder's open obligations from placement until it
+ * is claimed, so a bidder with bids out cannot finish deactivating. Bidders
+ * therefore bid through their user account of the auction's namespace.
+ *
+ * Settlement and claims are not behind the kill switch, so deposits can
+ * always leave.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
+
+use crate::decimals::Rounding;
+use crate::guards::Guard;
+use crate::math;
+use crate::{
+    auction_transition, instruction_flags, load_policy, require_instruction_enabled,
+    settle_obligation, AuctionAction, AuctionStatus, Bps, Commitment, CustomError, GlobalConfig,
+    InstructionTag, LamportAmount, PolicyInput, UserAccount,
+};
+
+/// A sealed-bid auction
+#[account]
+pub struct Auction {
+    pub seller: Pubkey,              // Receives the price and penalties (32 bytes)
+    pub auction_id: u64,             // Seller-chosen identifier (8 bytes)
+    pub commit_end: i64,             // Bids accepted before this timestamp (8 bytes)
+    pub reveal_end: i64,             // Reveals accepted before this timestamp (8 bytes)
+    pub reserve_price: u64,          // Minimum price in lamports (8 bytes)
+    pub non_reveal_penalty_bps: u64, // Deposit share forfeited by non-revealers (8 bytes)
+    pub highest_bidder: Pubkey,      // Leading revealed bidder (32 bytes)
+    pub highest_bid: u64,            // Leading revealed amount (8 bytes)
+    pub second_bid: u64,             // Runner-up revealed amount (8 bytes)
+    pub bid_count: u32,              // Bids placed (4 bytes)
+    pub revealed_count: u32,         // Bids revealed (4 bytes)
+    pub status: AuctionStatus,       // Lifecycle status (1 byte)
+    pub winner: Pubkey,              // Set at settlement, default if none (32 bytes)
+    pub price: u64,                  // Clearing price set at settlement (8 bytes)
+    pub bump: u8,                    // PDA bump seed (1 byte)
+}
+
+impl Auction {
+    /// Space required for an auction including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // seller: Pubkey
+        8 +                      // auction_id: u64
+        8 +                      // commit_end: i64
+        8 +                      // reveal_end: i64
+        8 +                      // reserve_price: u64
+        8 +                      // non_reveal_penalty_bps: u64
+        32 +                     // highest_bidder: Pubkey
+        8 +                      // highest_bid: u64
+        8 +                      // second_bid: u64
+        4 +                      // bid_count: u32
+        4 +                      // revealed_count: u32
+        1 +                      // status: AuctionStatus
+        32 +                     // winner: Pubkey
+        8 +                      // price: u64
+        1; // bump: u8
+
+    /// Commit-reveal domain of bids on the auction at `key`
+    pub fn commitment_domain(key: &Pubkey) -> u64 {
+        let digest = hashv(&[b"auction", key.as_ref()]).to_bytes();
+        u64::from_le_bytes(digest[..8].try_into().unwrap())
+    }
+
+    /// Commitment preimage for a bid of `amount` with `salt`
+    pub fn bid_preimage(amount: u64, salt: &[u8; 32]) -> Vec<u8> {
+        [amount.to_le_bytes().as_slice(), salt.as_slice()].concat()
+    }
+
+    /// Fold a revealed bid into the running highest and second-highest bids
+    /// Ties keep the earlier revealer in front
+    pub fn record_reveal(&mut self, bidder: Pubkey, amount: u64) {
+        if amount > self.highest_bid {
+            self.second_bid = self.highest_bid;
+            self.highest_bid = amount;
+            self.highest_bidder = bidder;
+        } else if amount > self.second_bid {
+            self.second_bid = amount;
+        }
+        self.revealed_count = self.revealed_count.saturating_add(1); // Bounded by bid_count
+    }
+}
+
+/// A bidder's deposit and revealed bid
+#[account]
+pub struct SealedBid {
+    pub auction: Pubkey, // Auction the bid belongs to (32 bytes)
+    pub bidder: Pubkey,  // Bidder and refund recipient (32 bytes)
+    pub deposit: u64,    // LamportAmount locked on top of rent (8 bytes)
+    pub revealed: bool,  // Whether the bid was revealed (1 byte)
+    pub amount: u64,     // Revealed amount, 0 until revealed (8 bytes)
+    pub bump: u8,        // PDA bump seed (1 byte)
+}
+
+impl SealedBid {
+    /// Space required for a bid including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // auction: Pubkey
+        32 +                     // bidder: Pubkey
+        8 +                      // deposit: u64
+        1 +                      // revealed: bool
+        8 +                      // amount: u64
+        1; // bump: u8
+}
+
+/// Context for opening an auction
+#[derive(Accounts)]
+#[instruction(auction_id: u64)]
+pub struct CreateAuction<'info> {
+    /// Auction being created
+    #[account(
+        init,
+        payer = seller,
+        space = Auction::LEN,
+        seeds = [b"auction", config.key().as_ref(), seller.key().as_ref(), &auction_id.to_le_bytes()],
+        bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Seller, pays rent
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for placing a sealed bid
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    /// Auction receiving the bid, tied to the config whose tolerance applies
+    #[account(
+        mut, // Bid count increases
+        seeds = [b"auction", config.key().as_ref(), auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Bid record holding the deposit
+    #[account(
+        init,
+        payer = bidder,
+        space = SealedBid::LEN,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()], // One bid per bidder
+        bump
+    )]
+    pub bid: Account<'info, SealedBid>,
+
+    /// Bidder's commitment to `hash(amount || salt)` in this auction's domain
+    #[account(
+        seeds = [
+            b"commitment",
+            config.key().as_ref(),
+            bidder.key().as_ref(),
+            &Auction::commitment_domain(&auction.key()).to_le_bytes()
+        ],
+        bump = commitment.bump
+    )]
+    pub commitment: Account<'info, Commitment>,
+
+    /// Bidder, pays rent and the deposit
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// Bidder's user account, which the bid is an obligation of
+    #[account(
+        mut,
+        constraint = bidder_account.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bidder_account: Account<'info, UserAccount>,
+
+    /// Bidder's transfer policy address, enforced on the deposit when it holds a policy
+    /// CHECK: address is the bidder's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", bidder_account.key().as_ref()], bump)]
+    pub bidder_policy: UncheckedAccount<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation and the deposit transfer
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revealing a sealed bid
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    /// Auction the bid belongs to, tied to the config whose tolerance applies
+    #[account(
+        mut, // Leading bids are updated
+        seeds = [b"auction", config.key().as_ref(), auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// The bid being revealed
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, SealedBid>,
+
+    /// Bidder's commitment, marked revealed
+    #[account(
+        mut,
+        seeds = [
+            b"commitment",
+            config.key().as_ref(),
+            bidder.key().as_ref(),
+            &Auction::commitment_domain(&auction.key()).to_le_bytes()
+        ],
+        bump = commitment.bump
+    )]
+    pub commitment: Account<'info, Commitment>,
+
+    /// Bidder
+    pub bidder: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for settling an auction (permissionless)
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// Auction being settled
+    #[account(
+        mut,
+        seeds = [b"auction", config.key().as_ref(), auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Namespace config the auction belongs to, whose clock tolerance applies
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for claiming a bid after settlement (permissionless)
+#[derive(Accounts)]
+pub struct ClaimBid<'info> {
+    /// Settled auction
+    #[account(
+        has_one = seller,
+        seeds = [b"auction", config.key().as_ref(), auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Bid being paid out and closed to the bidder
+    #[account(
+        mut,
+        close = bidder,                          // Refund rest of deposit and rent
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, SealedBid>,
+
+    /// Bidder receiving the refund
+    #[account(mut)]
+    pub bidder: SystemAccount<'info>,
+
+    /// Seller receiving the price or the penalty
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+
+    /// Bidder's user account, whose obligation the claim settles
+    #[account(
+        mut,
+        constraint = bidder_account.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bidder_account: Account<'info, UserAccount>,
+
+    /// Namespace config the auction belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for cancelling an auction without bids
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+    /// Auction being cancelled, rent returned to the seller
+    #[account(mut, close = seller, has_one = seller)]
+    pub auction: Account<'info, Auction>,
+
+    /// Seller
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+/// Event emitted when an auction is settled
+#[event]
+pub struct AuctionSettledEvent {
+    pub auction: Pubkey,     // Settled auction
+    pub winner: Pubkey,      // Winning bidder, default if no valid bid
+    pub price: u64,          // Clearing price in lamports
+    pub bids: u32,           // Bids placed
+    pub revealed: u32,       // Bids revealed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Open a sealed-bid auction
+pub fn handle_create_auction(
+    ctx: Context<CreateAuction>,
+    auction_id: u64,
+    commit_end: i64,
+    reveal_end: i64,
+    reserve_price: u64,
+    non_reveal_penalty_bps: u64,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::CREATE_AUCTION)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now < commit_end && commit_end < reveal_end,
+        CustomError::InvalidAuctionWindow
+    );
+    require!(
+        Bps(non_reveal_penalty_bps).is_fraction(),
+        CustomError::InvalidPenaltyRate
+    );
+
+    let auction = &mut ctx.accounts.auction;
+    auction.seller = ctx.accounts.seller.key();
+    auction.auction_id = auction_id;
+    auction.commit_end = commit_end;
+    auction.reveal_end = reveal_end;
+    auction.reserve_price = reserve_price;
+    auction.non_reveal_penalty_bps = non_reveal_penalty_bps;
+    auction.highest_bidder = Pubkey::default();
+    auction.highest_bid = 0;
+    auction.second_bid = 0;
+    auction.bid_count = 0;
+    auction.revealed_count = 0;
+    auction.status = AuctionStatus::Open;
+    auction.winner = Pubkey::default();
+    auction.price = 0;
+    auction.bump = ctx.bumps.auction;
+
+    msg!("Auction {} opened by {}", auction_id, auction.seller);
+    Ok(())
+}
+
+/// Place a sealed bid backed by a lamport deposit
+/// The bidder's commitment must already exist and match the auction windows
+pub fn handle_place_bid(ctx: Context<PlaceBid>, deposit: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::PLACE_BID)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+    require!(
+        auction.status == AuctionStatus::Open,
+        CustomError::BiddingClosed
+    );
+    let tolerance = ctx.accounts.config.clock_tolerance();
+    require!(
+        tolerance.before(now, auction.commit_end),
+        CustomError::BiddingClosed
+    );
+    require!(deposit > 0, CustomError::InvalidAmount);
+
+    // The deposit may end up with the seller, so it is held to the bidder's policy
+    let policy = load_policy(&ctx.accounts.bidder_policy)?;
+    let policy_input = PolicyInput {
+        amount: deposit,
+        receiver: auction.seller,
+        now,
+    };
+    Guard::Policy(policy.as_ref(), policy_input).check()?;
+
+    // The commitment must open for reveal exactly when bidding closes
+    let commitment = &ctx.accounts.commitment;
+    require!(
+        commitment.reveal_after == auction.commit_end
+            && commitment.expires_at == auction.reveal_end,
+        CustomError::CommitmentWindowMismatch
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.bidder.to_account_info(),
+                to: ctx.accounts.bid.to_account_info(),
+            },
+        ),
+        deposit,
+    )?;
+
+    ctx.accounts.bidder_account.open_obligation()?; // No new bids while deactivating
+
+    let bid = &mut ctx.accounts.bid;
+    bid.auction = auction.key();
+    bid.bidder = ctx.accounts.bidder.key();
+    bid.deposit = deposit;
+    bid.revealed = false;
+    bid.amount = 0;
+    bid.bump = ctx.bumps.bid;
+
+    auction.bid_count = auction
+        .bid_count
+        .checked_add(1)
+        .ok_or(CustomError::MathOverflow)?;
+
+    msg!("Sealed bid placed on auction {}", auction.key());
+    Ok(())
+}
+
+/// Reveal a sealed bid; the commitment enforces the reveal window
+pub fn handle_reveal_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::REVEAL_BID)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+    let bid = &mut ctx.accounts.bid;
+    require!(
+        auction.status == AuctionStatus::Open,
+        CustomError::BiddingClosed
+    );
+
+    ctx.accounts.commitment.verify_reveal(
+        &Auction::bid_preimage(amount, &salt),
+        now,
+        ctx.accounts.config.clock_tolerance(),
+    )?;
+    require!(amount <= bid.deposit, CustomError::BidExceedsDeposit); // Unbacked bids stay unrevealed
+
+    bid.revealed = true;
+    bid.amount = amount;
+    auction.record_reveal(bid.bidder, amount);
+
+    msg!("Bid revealed on auction {}", auction.key());
+    Ok(())
+}
+
+/// Fix the winner and clearing price once the reveal window has ended
+pub fn handle_settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+    require!(
+        ctx.accounts
+            .config
+            .clock_tolerance()
+            .passed(now, auction.reveal_end),
+        CustomError::AuctionNotEnded
+    );
+    auction.status = auction_transition(auction.status, AuctionAction::Settle)?;
+
+    // Vickrey price: second-highest revealed bid, floored at the reserve
+    if auction.revealed_count > 0 && auction.highest_bid >= auction.reserve_price {
+        auction.winner = auction.highest_bidder;
+        auction.price = auction.second_bid.max(auction.reserve_price);
+    }
+
+    emit!(AuctionSettledEvent {
+        auction: auction.key(),
+        winner: auction.winner,
+        price: auction.price,
+        bids: auction.bid_count,
+        revealed: auction.revealed_count,
+        tag: InstructionTag::SettleAuction,
+    });
+
+    msg!("Auction {} settled at {}", auction.key(), auction.price);
+    Ok(())
+}
+
+/// Pay out one bid of a settled auction and close it to the bidder
+pub fn handle_claim_bid(ctx: Context<ClaimBid>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let bid = &ctx.accounts.bid;
+    require!(
+        auction.status == AuctionStatus::Settled,
+        CustomError::AuctionNotSettled
+    );
+
+    // Seller's share of this deposit; the remainder is refunded on close
+    let to_seller = if bid.revealed && auction.winner == bid.bidder {
+        auction.price
+    } else if !bid.revealed {
+        Bps(auction.non_reveal_penalty_bps).of(bid.deposit, Rounding::Down)?
+    } else {
+        0
+    };
+
+    if to_seller > 0 {
+        math::move_lamports(
+            &ctx.accounts.bid.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            LamportAmount(to_seller),
+        )?;
+    }
+    settle_obligation(&mut ctx.accounts.bidder_account, InstructionTag::ClaimBid)?;
+
+    msg!(
+        "Bid of {} claimed, {} paid to seller",
+        bid.bidder,
+        to_seller
+    );
+    Ok(())
+}
+
+/// Withdraw an auction that has not received any bids
+pub fn handle_cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.bid_count == 0, CustomError::AuctionHasBids);
+    auction.status = auction_transition(auction.status, AuctionAction::Cancel)?;
+
+    msg!("Auction {} cancelled", auction.key());
+    Ok(())
+}
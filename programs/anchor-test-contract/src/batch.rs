@@ -0,0 +1,188 @@
+/*
+ * ============================================================================
+ * BATCH TRANSFERS
+ * ============================================================================
+ *
+ * `batch_transfer(amounts)` pays `amounts[i]` to the `i`-th receiver in
+ * `remaining_accounts`, so airdrops need one transaction per batch instead of
+ * one per receiver. Every receiver goes through `RemainingAccountsValidator`
+ * and each leg through the same checks and fee as `transfer_tokens`; any
+ * failing leg fails the whole instruction. The receivers' activity log
+ * addresses follow the receivers, then guardian signatures when the batch
+ * total needs them.
+ */
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    apply_transfer_with_fee, instruction_flags, load_policy, log_transfer, record_transfer_volume,
+    require_log_address, signer_keys, utilization, CustomError, EpochStats, EventOrder,
+    FeeTreasury, GlobalConfig, InstructionTag, Ledger, LedgerAccount, PolicyInput,
+    RemainingAccountsValidator, UserAccount, MAX_BATCH_RECEIVERS,
+};
+
+/// Context for a batch transfer
+///
+/// Remaining accounts: the receivers' user PDAs, writable, in `amounts` order,
+/// then their activity log addresses, writable, in the same order, then any
+/// guardian signers.
+#[derive(Accounts)]
+pub struct BatchTransfer<'info> {
+    /// Sender's user account
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub sender: Account<'info, UserAccount>,
+
+    /// Sender authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the batch entries
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fees
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Sender's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the sender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", sender.key().as_ref()], bump)]
+    pub sender_policy: UncheckedAccount<'info>,
+
+    /// Sender's activity log address, appended to when the sender keeps one
+    /// CHECK: address is the sender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", sender.key().as_ref()], bump)]
+    pub sender_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted once per batch transfer
+#[event]
+pub struct BatchTransferEvent {
+    pub from: Pubkey,        // Sender's authority
+    pub count: u8,           // Receivers paid
+    pub total: u64,          // Sum of `amounts`, fees included
+    pub fees: u64,           // Protocol fees withheld over all legs
+    pub timestamp: i64,      // When the batch was applied
+    pub order: EventOrder,   // Order of the last leg
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Pay `amounts[i]` to the `i`-th remaining account
+pub fn handle_batch_transfer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchTransfer<'info>>,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    require!(
+        !amounts.is_empty() && 2 * amounts.len() <= ctx.remaining_accounts.len(),
+        CustomError::BatchLengthMismatch
+    );
+    require!(
+        amounts.len() <= MAX_BATCH_RECEIVERS,
+        CustomError::BatchTooLarge
+    );
+
+    let (receiver_infos, rest) = ctx.remaining_accounts.split_at(amounts.len());
+    let (log_infos, guardian_infos) = rest.split_at(amounts.len());
+
+    // The sender cannot appear among the receivers
+    let mut keys = vec![ctx.accounts.sender.key()];
+    keys.extend(receiver_infos.iter().map(|info| *info.key));
+
+    let total = amounts
+        .iter()
+        .try_fold(0, |sum, amount| math::add(sum, *amount))?;
+    let now = Clock::get()?.unix_timestamp;
+    let sender = &ctx.accounts.sender;
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::BATCH_TRANSFER),
+        Guard::Distinct(&keys),
+        Guard::NotJoint(sender), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(sender, total, &signer_keys(guardian_infos)),
+        Guard::DailyLimit(sender, total, now),
+    ])?;
+
+    let sender = &mut ctx.accounts.sender;
+    sender.record_daily_spend(total, now)?;
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        total,
+        InstructionTag::BatchTransfer,
+    )?;
+    let stats = &ctx.accounts.epoch_stats;
+    let rate_utilization = utilization(stats.volume, stats.trailing_average);
+
+    let policy = load_policy(&ctx.accounts.sender_policy)?;
+    let config = ctx.accounts.config.key();
+    let mut receivers = RemainingAccountsValidator::with_capacity(amounts.len());
+    let mut fees: u64 = 0;
+    let mut order = EventOrder::default();
+    for ((info, log), &amount) in receiver_infos.iter().zip(log_infos).zip(&amounts) {
+        require!(info.is_writable, ErrorCode::ConstraintMut);
+        let mut receiver = receivers.load(info, |user: &UserAccount| {
+            vec![
+                b"user".to_vec(),
+                config.to_bytes().to_vec(),
+                user.authority.to_bytes().to_vec(),
+            ]
+        })?;
+        require!(receiver.is_current(), CustomError::MigrationRequired);
+        let policy_input = PolicyInput {
+            amount,
+            receiver: receiver.authority,
+            now,
+        };
+        Guard::Policy(policy.as_ref(), policy_input).check()?; // Every leg, as its own transfer
+
+        let fee = ctx.accounts.config.fee_at(amount, now, rate_utilization)?;
+        apply_transfer_with_fee(sender, &mut receiver, amount, fee, now)?;
+        fees = math::add(fees, fee)?;
+
+        require_log_address(log, info.key)?;
+        order = log_transfer(
+            (&ctx.accounts.sender_activity, sender.key(), sender),
+            (log, *info.key, &mut receiver),
+            amount,
+            amount - fee,
+            InstructionTag::BatchTransfer,
+            now,
+        )?;
+        receiver.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
+
+    ctx.accounts.fee_treasury.collect(fees)?;
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, total - fees)?; // Sender debit, receiver credits
+    if fees > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fees)?; // Withheld for the treasury
+    }
+
+    emit!(BatchTransferEvent {
+        from: sender.authority,
+        count: amounts.len() as u8,
+        total,
+        fees,
+        timestamp: now,
+        order,
+        tag: InstructionTag::BatchTransfer,
+    });
+
+    msg!("Batch transferred {} to {} receivers", total, amounts.len());
+    Ok(())
+}
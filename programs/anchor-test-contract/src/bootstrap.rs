@@ -0,0 +1,216 @@
+/*
+ * ============================================================================
+ * LOCALNET BOOTSTRAP
+ * ============================================================================
+ *
+ * `bootstrap_demo` seeds a fresh local validator in a single transaction:
+ * a namespace config, its epoch stats, ledger, fee treasury, and up to
+ * `MAX_DEMO_USERS` demo users
+ * with deterministic authorities and balances. Demo balances are posted to
+ * the ledger as vault deposits, so the invariant checks hold from the start.
+ *
+ * The handler body is only compiled with the `bootstrap-demo` feature; in
+ * every other build the instruction exists but always fails.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, EpochStats, FeeTreasury, GlobalConfig, Ledger};
+
+/// Deterministic authority of demo user `index`
+///
+/// A program-derived address nobody can sign for: demo users are state for
+/// tools to read, not wallets to drive.
+pub fn demo_authority(index: u8) -> Pubkey {
+    Pubkey::find_program_address(&[b"demo_user", &[index]], &crate::ID).0
+}
+
+/// Context for bootstrapping a local environment
+///
+/// Remaining accounts: the `users` demo user PDAs
+/// (`[b"user", config, demo_authority(i)]`), writable, in index order.
+#[derive(Accounts)]
+#[instruction(namespace: u64)]
+pub struct BootstrapDemo<'info> {
+    /// Namespace config created with the payer as admin
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalConfig::LEN,
+        seeds = [b"config", namespace.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Circuit breaker stats
+    #[account(
+        init,
+        payer = admin,
+        space = EpochStats::LEN,
+        seeds = [b"epoch_stats", config.key().as_ref()],
+        bump
+    )]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger
+    #[account(
+        init,
+        payer = admin,
+        space = Ledger::LEN,
+        seeds = [b"ledger", config.key().as_ref()],
+        bump
+    )]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Transfer fee treasury
+    #[account(
+        init,
+        payer = admin,
+        space = FeeTreasury::LEN,
+        seeds = [b"fee_treasury", config.key().as_ref()],
+        bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Becomes the config admin and pays rent for everything
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Initialize protocol singletons and `users` demo accounts
+#[cfg(feature = "bootstrap-demo")]
+pub fn handle_bootstrap_demo<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BootstrapDemo<'info>>,
+    namespace: u64,
+    users: u8,
+) -> Result<()> {
+    use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+
+    use crate::{
+        emit_config_snapshot, AccountMode, EmissionSchedule, FeeStrategy, InstructionTag,
+        LedgerAccount, RampedParam, UserAccount, DEFAULT_CLOCK_TOLERANCE, DEFAULT_FEE_RATE_BPS,
+        DEFAULT_REACTIVATION_COOLDOWN, DEMO_BALANCE_UNIT, MAX_DEMO_USERS, MAX_GUARDIANS,
+        MAX_VERSIONED_INSTRUCTIONS, USER_ACCOUNT_VERSION,
+    };
+
+    require!(users <= MAX_DEMO_USERS, CustomError::TooManyDemoUsers);
+    require!(
+        ctx.remaining_accounts.len() == users as usize,
+        CustomError::TooManyDemoUsers
+    );
+
+    let clock = Clock::get()?;
+    let admin = ctx.accounts.admin.key();
+
+    let config_key = ctx.accounts.config.key();
+    let config = &mut ctx.accounts.config;
+    config.namespace = namespace;
+    config.admin = admin;
+    config.disabled_instructions = 0;
+    config.bump = ctx.bumps.config;
+    config.fee_rate_bps = RampedParam::fixed(DEFAULT_FEE_RATE_BPS);
+    config.circuit_breaker_multiple_bps = 0;
+    config.max_price_deviation_bps = 0;
+    config.price_deviation_window_secs = 0;
+    config.lookup_table = Pubkey::default();
+    config.fee_strategy = FeeStrategy::Proportional;
+    config.emission_schedule = EmissionSchedule::default();
+    config.compliance = admin;
+    config.paused = false;
+    config.reactivation_cooldown_secs = DEFAULT_REACTIVATION_COOLDOWN.get();
+    config.clock_tolerance_secs = DEFAULT_CLOCK_TOLERANCE.get();
+    config.instruction_sunsets = [0; MAX_VERSIONED_INSTRUCTIONS];
+    emit_config_snapshot(config, InstructionTag::BootstrapDemo)?;
+
+    let stats = &mut ctx.accounts.epoch_stats;
+    stats.epoch = clock.epoch;
+    stats.volume = 0;
+    stats.trailing_average = 0;
+    stats.tripped = false;
+    stats.tripped_at = 0;
+    stats.bump = ctx.bumps.epoch_stats;
+
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.bump = ctx.bumps.ledger;
+
+    let treasury = &mut ctx.accounts.fee_treasury;
+    treasury.balance = 0;
+    treasury.total_collected = 0;
+    treasury.bump = ctx.bumps.fee_treasury;
+
+    let rent = Rent::get()?.minimum_balance(UserAccount::LEN);
+    for (index, info) in ctx.remaining_accounts.iter().enumerate() {
+        let index = index as u8;
+        let authority = demo_authority(index);
+        let (expected, bump) = Pubkey::find_program_address(
+            &[b"user", config_key.as_ref(), authority.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(expected, *info.key, CustomError::InvalidUserAccount);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                &admin,
+                info.key,
+                rent,
+                UserAccount::LEN as u64,
+                &crate::ID,
+            ),
+            &[
+                ctx.accounts.admin.to_account_info(),
+                info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[b"user", config_key.as_ref(), authority.as_ref(), &[bump]]],
+        )?;
+
+        let balance = DEMO_BALANCE_UNIT * (index as u64 + 1);
+        let user = UserAccount {
+            authority,
+            name: format!("demo-{index}"),
+            age: 20 + index,
+            balance,
+            mode: AccountMode::Normal,
+            created_at: clock.unix_timestamp,
+            is_joint: false,
+            vacation_start: 0,
+            vacation_end: 0,
+            compliance_hold: false,
+            deactivated_at: 0,
+            guardians: [Pubkey::default(); MAX_GUARDIANS],
+            guardian_threshold: 0,
+            guardian_limit: 0,
+            version: USER_ACCOUNT_VERSION,
+            daily_limit: 0,
+            spent_today: 0,
+            window_start: 0,
+            pending_daily_limit: 0,
+            pending_limit_at: 0,
+            watchtower: Pubkey::default(),
+            alert_until: 0,
+            open_obligations: 0,
+            sequence: 0,
+            uncounted_obligations: false,
+        };
+        user.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        // Demo balances enter as if deposited into the vault
+        ledger.post(LedgerAccount::Vault, LedgerAccount::Users, balance)?;
+    }
+
+    msg!("Bootstrapped localnet with {} demo users", users);
+    Ok(())
+}
+
+/// Bootstrap is compiled out of this build
+#[cfg(not(feature = "bootstrap-demo"))]
+pub fn handle_bootstrap_demo<'info>(
+    _ctx: Context<'_, '_, 'info, 'info, BootstrapDemo<'info>>,
+    _namespace: u64,
+    _users: u8,
+) -> Result<()> {
+    err!(CustomError::BootstrapDisabled)
+}
@@ -0,0 +1,492 @@
+/*
+ * ============================================================================
+ * CROSS-NAMESPACE BRIDGES
+ * ============================================================================
+ *
+ * Users of two namespaces can transfer to each other once both admins have
+ * agreed on a `NamespaceBridgeConfig`. One admin proposes the terms (total
+ * fee and how it is split) and the other approves them by echoing them
+ * back, so neither side can change the terms after agreeing. Either admin
+ * may close the bridge at any time.
+ *
+ * Each transfer withholds the bridge fee from the receiver's credit. The
+ * sending namespace keeps `source_share_bps` of it and the receiving one
+ * keeps the rest, each collected into its namespace's `FeeTreasury`. Both
+ * ledgers record the value leaving or arriving through their vault, so each
+ * namespace stays balanced on its own.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    apply_transfer_with_fee, instruction_flags, load_policy, log_transfer, record_transfer_volume,
+    signer_keys, Bps, CustomError, EpochStats, EventOrder, FeeTreasury, GlobalConfig,
+    InstructionTag, Ledger, LedgerAccount, PolicyInput, UserAccount,
+};
+
+/// Fee withheld from a bridged transfer and its split
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BridgeSplit {
+    pub net_amount: u64, // Credited to the receiver
+    pub source_fee: u64, // Kept by the sending namespace
+    pub dest_fee: u64,   // Kept by the receiving namespace
+}
+
+/// Terms agreed between two namespaces, keyed by their ordered config addresses
+#[account]
+pub struct NamespaceBridgeConfig {
+    pub config_a: Pubkey,      // Lower config address (32 bytes)
+    pub config_b: Pubkey,      // Higher config address (32 bytes)
+    pub fee_bps: u64,          // Total fee on each transfer (8 bytes)
+    pub source_share_bps: u64, // Fee share kept by the sender's namespace (8 bytes)
+    pub approved_a: bool,      // Admin of `config_a` consented (1 byte)
+    pub approved_b: bool,      // Admin of `config_b` consented (1 byte)
+    pub payer: Pubkey,         // Proposing admin, refunded on close (32 bytes)
+    pub bump: u8,              // PDA bump seed (1 byte)
+}
+
+impl NamespaceBridgeConfig {
+    /// Space required for the bridge including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // config_a: Pubkey
+        32 +                     // config_b: Pubkey
+        8 +                      // fee_bps: u64
+        8 +                      // source_share_bps: u64
+        1 +                      // approved_a: bool
+        1 +                      // approved_b: bool
+        32 +                     // payer: Pubkey
+        1; // bump: u8
+
+    /// True once both admins have consented
+    pub fn is_active(&self) -> bool {
+        self.approved_a && self.approved_b
+    }
+
+    /// True if the bridge connects `source` and `dest` (in either direction)
+    pub fn connects(&self, source: &Pubkey, dest: &Pubkey) -> bool {
+        (self.config_a == *source && self.config_b == *dest)
+            || (self.config_a == *dest && self.config_b == *source)
+    }
+
+    /// Record consent from `admin`, who must administer one of the two configs
+    fn approve(
+        &mut self,
+        admin: &Pubkey,
+        config_a: &GlobalConfig,
+        config_b: &GlobalConfig,
+    ) -> Result<()> {
+        if config_a.admin == *admin {
+            self.approved_a = true;
+        } else if config_b.admin == *admin {
+            self.approved_b = true;
+        } else {
+            return err!(CustomError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Fee withheld from `amount` and its split; the fee rounds up
+    pub fn split(&self, amount: u64) -> Result<BridgeSplit> {
+        let fee = Bps(self.fee_bps).of(amount, Rounding::Up)?;
+        let source_fee = Bps(self.source_share_bps).of(fee, Rounding::Down)?;
+        Ok(BridgeSplit {
+            net_amount: math::sub(amount, fee)?,
+            source_fee,
+            dest_fee: math::sub(fee, source_fee)?,
+        })
+    }
+}
+
+/// Check proposed bridge terms
+fn validate_terms(fee_bps: u64, source_share_bps: u64) -> Result<()> {
+    require!(
+        Bps(fee_bps).is_fraction() && Bps(source_share_bps).is_fraction(),
+        CustomError::InvalidBridgeTerms
+    );
+    Ok(())
+}
+
+/// Context for proposing a bridge between two namespaces
+#[derive(Accounts)]
+pub struct ProposeNamespaceBridge<'info> {
+    /// Bridge being created
+    #[account(
+        init,
+        payer = admin,
+        space = NamespaceBridgeConfig::LEN,
+        seeds = [b"bridge", config_a.key().as_ref(), config_b.key().as_ref()], // One per pair
+        bump
+    )]
+    pub bridge: Account<'info, NamespaceBridgeConfig>,
+
+    /// Namespace config with the lower address
+    #[account(
+        constraint = config_a.key() < config_b.key() @ CustomError::InvalidBridgeTerms,
+        seeds = [b"config", config_a.namespace.to_le_bytes().as_ref()],
+        bump = config_a.bump
+    )]
+    pub config_a: Account<'info, GlobalConfig>,
+
+    /// Namespace config with the higher address
+    #[account(seeds = [b"config", config_b.namespace.to_le_bytes().as_ref()], bump = config_b.bump)]
+    pub config_b: Account<'info, GlobalConfig>,
+
+    /// Admin of either namespace, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for approving or closing an existing bridge
+#[derive(Accounts)]
+pub struct ManageNamespaceBridge<'info> {
+    /// Bridge being approved or closed
+    #[account(
+        mut,
+        seeds = [b"bridge", config_a.key().as_ref(), config_b.key().as_ref()],
+        bump = bridge.bump
+    )]
+    pub bridge: Account<'info, NamespaceBridgeConfig>,
+
+    /// Namespace config with the lower address
+    #[account(seeds = [b"config", config_a.namespace.to_le_bytes().as_ref()], bump = config_a.bump)]
+    pub config_a: Account<'info, GlobalConfig>,
+
+    /// Namespace config with the higher address
+    #[account(seeds = [b"config", config_b.namespace.to_le_bytes().as_ref()], bump = config_b.bump)]
+    pub config_b: Account<'info, GlobalConfig>,
+
+    /// Admin of either namespace
+    pub admin: Signer<'info>,
+
+    /// Proposing admin, receives the rent on close
+    /// CHECK: only receives lamports; must match `bridge.payer`
+    #[account(mut, address = bridge.payer @ CustomError::Unauthorized)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+/// Context for a transfer to a user of another namespace
+///
+/// Remaining accounts: the sender's guardian signers, when needed.
+#[derive(Accounts)]
+pub struct CrossNamespaceTransfer<'info> {
+    /// Sender's user account in the source namespace
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", source_config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub sender: Account<'info, UserAccount>,
+
+    /// Receiver's user account in the destination namespace
+    #[account(
+        mut,
+        constraint = receiver.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", dest_config.key().as_ref(), receiver.authority.as_ref()],
+        bump
+    )]
+    pub receiver: Account<'info, UserAccount>,
+
+    /// Sender's authority
+    pub authority: Signer<'info>,
+
+    /// Agreed terms between the two namespaces
+    #[account(
+        constraint = bridge.connects(&source_config.key(), &dest_config.key())
+            @ CustomError::BridgeNotActive,
+        seeds = [b"bridge", bridge.config_a.as_ref(), bridge.config_b.as_ref()],
+        bump = bridge.bump
+    )]
+    pub bridge: Account<'info, NamespaceBridgeConfig>,
+
+    /// Sender's namespace config
+    #[account(
+        seeds = [b"config", source_config.namespace.to_le_bytes().as_ref()],
+        bump = source_config.bump
+    )]
+    pub source_config: Account<'info, GlobalConfig>,
+
+    /// Receiver's namespace config
+    #[account(
+        seeds = [b"config", dest_config.namespace.to_le_bytes().as_ref()],
+        bump = dest_config.bump
+    )]
+    pub dest_config: Account<'info, GlobalConfig>,
+
+    /// Source namespace volume tracker
+    #[account(
+        mut,
+        seeds = [b"epoch_stats", source_config.key().as_ref()],
+        bump = source_epoch_stats.bump
+    )]
+    pub source_epoch_stats: Account<'info, EpochStats>,
+
+    /// Destination namespace volume tracker
+    #[account(
+        mut,
+        seeds = [b"epoch_stats", dest_config.key().as_ref()],
+        bump = dest_epoch_stats.bump
+    )]
+    pub dest_epoch_stats: Account<'info, EpochStats>,
+
+    /// Source namespace ledger
+    #[account(mut, seeds = [b"ledger", source_config.key().as_ref()], bump = source_ledger.bump)]
+    pub source_ledger: Account<'info, Ledger>,
+
+    /// Destination namespace ledger
+    #[account(mut, seeds = [b"ledger", dest_config.key().as_ref()], bump = dest_ledger.bump)]
+    pub dest_ledger: Account<'info, Ledger>,
+
+    /// Source namespace treasury, collects the source fee
+    #[account(
+        mut,
+        seeds = [b"fee_treasury", source_config.key().as_ref()],
+        bump = source_fee_treasury.bump
+    )]
+    pub source_fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Destination namespace treasury, collects the destination fee
+    #[account(
+        mut,
+        seeds = [b"fee_treasury", dest_config.key().as_ref()],
+        bump = dest_fee_treasury.bump
+    )]
+    pub dest_fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Sender's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the sender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", sender.key().as_ref()], bump)]
+    pub sender_policy: UncheckedAccount<'info>,
+
+    /// Sender's activity log address, appended to when the sender keeps one
+    /// CHECK: address is the sender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", sender.key().as_ref()], bump)]
+    pub sender_activity: UncheckedAccount<'info>,
+
+    /// Receiver's activity log address, appended to when the receiver keeps one
+    /// CHECK: address is the receiver's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", receiver.key().as_ref()], bump)]
+    pub receiver_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted when a bridge is proposed, approved or closed
+#[event]
+pub struct NamespaceBridgeChangedEvent {
+    pub config_a: Pubkey,      // Lower config address
+    pub config_b: Pubkey,      // Higher config address
+    pub fee_bps: u64,          // Total fee
+    pub source_share_bps: u64, // Sender namespace's share
+    pub active: bool,          // Both admins consented and the bridge is open
+    pub tag: InstructionTag,   // Emitting instruction
+}
+
+impl NamespaceBridgeChangedEvent {
+    fn from_bridge(bridge: &NamespaceBridgeConfig, active: bool, tag: InstructionTag) -> Self {
+        Self {
+            config_a: bridge.config_a,
+            config_b: bridge.config_b,
+            fee_bps: bridge.fee_bps,
+            source_share_bps: bridge.source_share_bps,
+            active,
+            tag,
+        }
+    }
+}
+
+/// Event emitted for every cross-namespace transfer
+#[event]
+pub struct CrossNamespaceTransferEvent {
+    pub from: Pubkey,          // Sender's authority
+    pub to: Pubkey,            // Receiver's authority
+    pub source_config: Pubkey, // Sender's namespace config
+    pub dest_config: Pubkey,   // Receiver's namespace config
+    pub amount: u64,           // Debited from the sender
+    pub net_amount: u64,       // Credited to the receiver
+    pub source_fee: u64,       // Kept by the source namespace
+    pub dest_fee: u64,         // Kept by the destination namespace
+    pub timestamp: i64,        // Transfer time
+    pub order: EventOrder,     // Sender's sequence and slot
+    pub tag: InstructionTag,   // Emitting instruction
+}
+
+/// Propose a bridge with the given terms; counts as the proposer's consent
+pub fn handle_propose_namespace_bridge(
+    ctx: Context<ProposeNamespaceBridge>,
+    fee_bps: u64,
+    source_share_bps: u64,
+) -> Result<()> {
+    validate_terms(fee_bps, source_share_bps)?;
+
+    let bridge = &mut ctx.accounts.bridge;
+    bridge.config_a = ctx.accounts.config_a.key();
+    bridge.config_b = ctx.accounts.config_b.key();
+    bridge.fee_bps = fee_bps;
+    bridge.source_share_bps = source_share_bps;
+    bridge.approved_a = false;
+    bridge.approved_b = false;
+    bridge.payer = ctx.accounts.admin.key();
+    bridge.bump = ctx.bumps.bridge;
+    bridge.approve(
+        &ctx.accounts.admin.key(),
+        &ctx.accounts.config_a,
+        &ctx.accounts.config_b,
+    )?;
+
+    emit!(NamespaceBridgeChangedEvent::from_bridge(
+        bridge,
+        false,
+        InstructionTag::ProposeNamespaceBridge,
+    ));
+
+    msg!(
+        "Bridge proposed: {} <-> {}",
+        bridge.config_a,
+        bridge.config_b
+    );
+    Ok(())
+}
+
+/// Consent to a proposed bridge; the terms must match the proposal exactly
+pub fn handle_approve_namespace_bridge(
+    ctx: Context<ManageNamespaceBridge>,
+    fee_bps: u64,
+    source_share_bps: u64,
+) -> Result<()> {
+    let bridge = &mut ctx.accounts.bridge;
+    require!(
+        bridge.fee_bps == fee_bps && bridge.source_share_bps == source_share_bps,
+        CustomError::InvalidBridgeTerms
+    );
+    bridge.approve(
+        &ctx.accounts.admin.key(),
+        &ctx.accounts.config_a,
+        &ctx.accounts.config_b,
+    )?;
+
+    emit!(NamespaceBridgeChangedEvent::from_bridge(
+        bridge,
+        bridge.is_active(),
+        InstructionTag::ApproveNamespaceBridge,
+    ));
+
+    msg!("Bridge approved, active: {}", bridge.is_active());
+    Ok(())
+}
+
+/// Close the bridge on behalf of either namespace, refunding the proposer
+pub fn handle_close_namespace_bridge(ctx: Context<ManageNamespaceBridge>) -> Result<()> {
+    let admin = ctx.accounts.admin.key();
+    require!(
+        ctx.accounts.config_a.admin == admin || ctx.accounts.config_b.admin == admin,
+        CustomError::Unauthorized
+    );
+
+    emit!(NamespaceBridgeChangedEvent::from_bridge(
+        &ctx.accounts.bridge,
+        false,
+        InstructionTag::CloseNamespaceBridge,
+    ));
+
+    msg!("Bridge closed by {}", admin);
+    ctx.accounts
+        .bridge
+        .close(ctx.accounts.payer.to_account_info())
+}
+
+/// Transfer to a user of another namespace over an active bridge
+pub fn handle_cross_namespace_transfer(
+    ctx: Context<CrossNamespaceTransfer>,
+    amount: u64,
+) -> Result<()> {
+    let flag = instruction_flags::CROSS_NAMESPACE_TRANSFER;
+    let now = Clock::get()?.unix_timestamp;
+    let sender = &ctx.accounts.sender;
+    let policy = load_policy(&ctx.accounts.sender_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: ctx.accounts.receiver.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.source_config, flag),
+        Guard::Enabled(&ctx.accounts.dest_config, flag), // Both namespaces must allow it
+        Guard::Distinct(&[sender.key(), ctx.accounts.receiver.key()]),
+        Guard::NotJoint(sender), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(sender, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(sender, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+    require!(
+        ctx.accounts.bridge.is_active(),
+        CustomError::BridgeNotActive
+    );
+
+    let split = ctx.accounts.bridge.split(amount)?;
+    let sender = &mut ctx.accounts.sender;
+    let receiver = &mut ctx.accounts.receiver;
+    sender.record_daily_spend(amount, now)?;
+    let fee = math::add(split.source_fee, split.dest_fee)?;
+    apply_transfer_with_fee(sender, receiver, amount, fee, now)?;
+
+    record_transfer_volume(
+        &mut ctx.accounts.source_epoch_stats,
+        &ctx.accounts.source_config,
+        amount,
+        InstructionTag::CrossNamespaceTransfer,
+    )?;
+    record_transfer_volume(
+        &mut ctx.accounts.dest_epoch_stats,
+        &ctx.accounts.dest_config,
+        amount,
+        InstructionTag::CrossNamespaceTransfer,
+    )?;
+
+    // Source: user balance leaves through the vault, minus the fee it keeps
+    let outflow = math::sub(amount, split.source_fee)?;
+    let source_ledger = &mut ctx.accounts.source_ledger;
+    source_ledger.post(LedgerAccount::Users, LedgerAccount::Vault, outflow)?;
+    source_ledger.post(LedgerAccount::Users, LedgerAccount::Fees, split.source_fee)?;
+    ctx.accounts.source_fee_treasury.collect(split.source_fee)?;
+
+    // Destination: the same value arrives, split between the receiver and its fee
+    let dest_ledger = &mut ctx.accounts.dest_ledger;
+    dest_ledger.post(LedgerAccount::Vault, LedgerAccount::Users, split.net_amount)?;
+    dest_ledger.post(LedgerAccount::Vault, LedgerAccount::Fees, split.dest_fee)?;
+    ctx.accounts.dest_fee_treasury.collect(split.dest_fee)?;
+    let order = log_transfer(
+        (&ctx.accounts.sender_activity, sender.key(), sender),
+        (&ctx.accounts.receiver_activity, receiver.key(), receiver),
+        amount,
+        split.net_amount,
+        InstructionTag::CrossNamespaceTransfer,
+        now,
+    )?;
+
+    emit!(CrossNamespaceTransferEvent {
+        from: sender.authority,
+        to: receiver.authority,
+        source_config: ctx.accounts.source_config.key(),
+        dest_config: ctx.accounts.dest_config.key(),
+        amount,
+        net_amount: split.net_amount,
+        source_fee: split.source_fee,
+        dest_fee: split.dest_fee,
+        timestamp: now,
+        order,
+        tag: InstructionTag::CrossNamespaceTransfer,
+    });
+
+    msg!(
+        "Bridged {} tokens, {} credited across namespaces",
+        amount,
+        split.net_amount
+    );
+    Ok(())
+}
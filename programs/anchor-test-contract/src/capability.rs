@@ -0,0 +1,421 @@
+/*
+ * ============================================================================
+ * CAPABILITY TOKENS
+ * ============================================================================
+ *
+ * Service integrations used to need the authority's own signature, which
+ * grants everything. A capability token instead lets a named off-chain
+ * service act on one account within `capability_scopes`. Each token is a
+ * `CapabilityToken` PDA per user and service key, carrying a label naming
+ * the service, a mandatory expiry and an optional cap on uses. Every use is
+ * counted on the token, so the authority sees what the service did.
+ *
+ * The authority revokes a token at any time and gets its rent back; once
+ * expired anyone can retire it through `expire`. Scopes only cover
+ * operations a service runs on the account's behalf: funds never move on a
+ * capability.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::math;
+use crate::{
+    instruction_flags, require_instruction_enabled, start_invoice_template, ClockTolerance,
+    CustomError, GlobalConfig, InstructionTag, InvoiceTemplate, Seconds, UserAccount,
+    MAX_SERVICE_NAME_LEN,
+};
+
+/// Operations a capability token may allow; combine with bitwise OR
+pub mod capability_scopes {
+    pub const HEARTBEAT: u64 = 1 << 0; // Report the service alive
+    pub const CREATE_INVOICE_TEMPLATE: u64 = 1 << 1; // Start billing as the merchant
+
+    /// Every defined scope
+    pub const ALL: u64 = HEARTBEAT | CREATE_INVOICE_TEMPLATE;
+}
+
+/// A user's grant of some operations to a service
+#[account]
+pub struct CapabilityToken {
+    pub user: Pubkey,      // User account the service acts for (32 bytes)
+    pub authority: Pubkey, // User authority that issued and paid (32 bytes)
+    pub service: Pubkey,   // Service key allowed to act (32 bytes)
+    pub name: String,      // Service label (4 + up to MAX_SERVICE_NAME_LEN bytes)
+    pub scopes: u64,       // Bitmask of `capability_scopes` (8 bytes)
+    pub uses: u64,         // Operations performed so far (8 bytes)
+    pub max_uses: u64,     // Uses allowed, 0 for unlimited (8 bytes)
+    pub expires_at: i64,   // Token lapses at this timestamp (8 bytes)
+    pub issued_at: i64,    // Token creation timestamp (8 bytes)
+    pub last_used_at: i64, // Timestamp of the latest use, 0 if none (8 bytes)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl CapabilityToken {
+    /// Space required at the maximum label size
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        32 +                     // authority: Pubkey
+        32 +                     // service: Pubkey
+        4 + MAX_SERVICE_NAME_LEN + // name: String
+        8 +                      // scopes: u64
+        8 +                      // uses: u64
+        8 +                      // max_uses: u64
+        8 +                      // expires_at: i64
+        8 +                      // issued_at: i64
+        8 +                      // last_used_at: i64
+        1; // bump: u8
+
+    /// Whether the token has lapsed at `now`
+    pub fn is_expired(&self, now: i64, tolerance: ClockTolerance) -> bool {
+        tolerance.passed(now, self.expires_at)
+    }
+
+    /// Check the token is live at `now` and covers `scope`, counting the use
+    pub fn use_scope(&mut self, scope: u64, now: i64, tolerance: ClockTolerance) -> Result<()> {
+        require!(
+            !self.is_expired(now, tolerance),
+            CustomError::CapabilityExpired
+        );
+        require!(
+            self.scopes & scope == scope,
+            CustomError::CapabilityNotPermitted
+        );
+        require!(
+            self.max_uses == 0 || self.uses < self.max_uses,
+            CustomError::CapabilityExhausted
+        );
+        self.uses = math::add(self.uses, 1)?;
+        self.last_used_at = now;
+        Ok(())
+    }
+}
+
+/// Check the terms of a token before it is issued
+pub fn validate_capability(name: &str, scopes: u64, expires_at: i64, now: i64) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_SERVICE_NAME_LEN,
+        CustomError::InvalidCapability
+    );
+    require!(
+        scopes != 0 && scopes & !capability_scopes::ALL == 0,
+        CustomError::InvalidCapability
+    );
+    require!(expires_at > now, CustomError::CapabilityExpired); // Must not be born expired
+    Ok(())
+}
+
+/// Context for issuing a capability token for the caller's account
+#[derive(Accounts)]
+#[instruction(service: Pubkey)]
+pub struct IssueCapability<'info> {
+    /// Token being created
+    #[account(
+        init,
+        payer = authority,
+        space = CapabilityToken::LEN,
+        seeds = [b"capability", user.key().as_ref(), service.as_ref()],
+        bump
+    )]
+    pub token: Account<'info, CapabilityToken>,
+
+    /// The user account the service will act for
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revoking a capability token, refunding its rent
+#[derive(Accounts)]
+pub struct RevokeCapability<'info> {
+    /// Token being closed
+    #[account(
+        mut,
+        close = authority,
+        has_one = user,
+        has_one = authority,
+        seeds = [b"capability", user.key().as_ref(), token.service.as_ref()],
+        bump = token.bump
+    )]
+    pub token: Account<'info, CapabilityToken>,
+
+    /// The user account the service acted for
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the user belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for a service reporting itself alive
+#[derive(Accounts)]
+pub struct ServiceHeartbeat<'info> {
+    /// Token authorizing the service
+    #[account(
+        mut,
+        has_one = user,
+        has_one = service,
+        seeds = [b"capability", user.key().as_ref(), service.key().as_ref()],
+        bump = token.bump
+    )]
+    pub token: Account<'info, CapabilityToken>,
+
+    /// The user account the service acts for
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Service acting on the user's behalf
+    pub service: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for a billing service starting an invoice template as the merchant
+#[derive(Accounts)]
+pub struct CapabilityCreateInvoiceTemplate<'info> {
+    /// Token authorizing the service
+    #[account(
+        mut,
+        has_one = service,
+        constraint = token.user == merchant.key() @ CustomError::CapabilityNotPermitted,
+        seeds = [b"capability", merchant.key().as_ref(), service.key().as_ref()],
+        bump = token.bump
+    )]
+    pub token: Account<'info, CapabilityToken>,
+
+    /// Template being created
+    #[account(
+        init,
+        payer = service,
+        space = InvoiceTemplate::LEN,
+        seeds = [b"invoice_template", merchant.key().as_ref(), payer.key().as_ref()], // One per pair
+        bump
+    )]
+    pub template: Account<'info, InvoiceTemplate>,
+
+    /// Merchant's user account, which the service bills for
+    #[account(
+        constraint = merchant.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), merchant.authority.as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, UserAccount>,
+
+    /// Payer's user account
+    #[account(
+        constraint = payer.key() != merchant.key() @ CustomError::InvalidInvoiceTemplate,
+        constraint = payer.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), payer.authority.as_ref()],
+        bump
+    )]
+    pub payer: Account<'info, UserAccount>,
+
+    /// Service acting for the merchant, pays the template's rent
+    #[account(mut)]
+    pub service: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Event emitted when a capability token is issued
+#[event]
+pub struct CapabilityIssuedEvent {
+    pub user: Pubkey,        // User account the service acts for
+    pub service: Pubkey,     // Service key
+    pub name: String,        // Service label
+    pub scopes: u64,         // Granted `capability_scopes`
+    pub max_uses: u64,       // Uses allowed, 0 for unlimited
+    pub expires_at: i64,     // Token expiry
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a token is revoked by the authority
+#[event]
+pub struct CapabilityRevokedEvent {
+    pub user: Pubkey,        // User account the service acted for
+    pub service: Pubkey,     // Service key
+    pub uses: u64,           // Operations the token performed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted for every operation a service performs on a token
+#[event]
+pub struct CapabilityUsedEvent {
+    pub user: Pubkey,        // User account the service acts for
+    pub service: Pubkey,     // Acting service
+    pub scope: u64,          // `capability_scopes` bit exercised
+    pub uses: u64,           // Uses including this one
+    pub timestamp: i64,      // When it was performed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Count a use of `scope` on `token` and emit it
+fn use_capability(
+    token: &mut Account<CapabilityToken>,
+    config: &GlobalConfig,
+    scope: u64,
+    now: i64,
+    tag: InstructionTag,
+) -> Result<()> {
+    token.use_scope(scope, now, config.clock_tolerance())?;
+    emit!(CapabilityUsedEvent {
+        user: token.user,
+        service: token.service,
+        scope,
+        uses: token.uses,
+        timestamp: now,
+        tag,
+    });
+    Ok(())
+}
+
+/// Let `service`, labelled `name`, perform `scopes` on the caller's account
+/// until `expires_at`, at most `max_uses` times (0 for unlimited)
+pub fn handle_issue_capability(
+    ctx: Context<IssueCapability>,
+    service: Pubkey,
+    name: String,
+    scopes: u64,
+    max_uses: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::ISSUE_CAPABILITY)?;
+    let now = Clock::get()?.unix_timestamp;
+    validate_capability(&name, scopes, expires_at, now)?;
+
+    let token = &mut ctx.accounts.token;
+    token.user = ctx.accounts.user.key();
+    token.authority = ctx.accounts.authority.key();
+    token.service = service;
+    token.name = name;
+    token.scopes = scopes;
+    token.uses = 0;
+    token.max_uses = max_uses;
+    token.expires_at = expires_at;
+    token.issued_at = now;
+    token.last_used_at = 0;
+    token.bump = ctx.bumps.token;
+
+    emit!(CapabilityIssuedEvent {
+        user: token.user,
+        service,
+        name: token.name.clone(),
+        scopes,
+        max_uses,
+        expires_at,
+        tag: InstructionTag::IssueCapability,
+    });
+
+    msg!(
+        "Capability {:#x} issued to {} until {}",
+        scopes,
+        service,
+        expires_at
+    );
+    Ok(())
+}
+
+/// Revoke a service's token before it expires; always available, whatever
+/// the kill switches
+pub fn handle_revoke_capability(ctx: Context<RevokeCapability>) -> Result<()> {
+    let token = &ctx.accounts.token;
+
+    emit!(CapabilityRevokedEvent {
+        user: token.user,
+        service: token.service,
+        uses: token.uses,
+        tag: InstructionTag::RevokeCapability,
+    });
+
+    msg!("Capability of {} revoked", token.service);
+    Ok(())
+}
+
+/// Record that the service is alive
+pub fn handle_service_heartbeat(ctx: Context<ServiceHeartbeat>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::ISSUE_CAPABILITY)?;
+    let now = Clock::get()?.unix_timestamp;
+    use_capability(
+        &mut ctx.accounts.token,
+        &ctx.accounts.config,
+        capability_scopes::HEARTBEAT,
+        now,
+        InstructionTag::ServiceHeartbeat,
+    )?;
+
+    msg!("Heartbeat from {}", ctx.accounts.service.key());
+    Ok(())
+}
+
+/// Start billing `payer` `amount` every `interval` on the merchant's behalf
+pub fn handle_capability_create_invoice_template(
+    ctx: Context<CapabilityCreateInvoiceTemplate>,
+    amount: u64,
+    interval: Seconds,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::ISSUE_CAPABILITY)?;
+    require_instruction_enabled(
+        &ctx.accounts.config,
+        instruction_flags::CREATE_INVOICE_TEMPLATE,
+    )?;
+    let now = Clock::get()?.unix_timestamp;
+    use_capability(
+        &mut ctx.accounts.token,
+        &ctx.accounts.config,
+        capability_scopes::CREATE_INVOICE_TEMPLATE,
+        now,
+        InstructionTag::CapabilityCreateInvoiceTemplate,
+    )?;
+
+    start_invoice_template(
+        &mut ctx.accounts.template,
+        ctx.accounts.merchant.key(),
+        ctx.accounts.payer.key(),
+        amount,
+        interval,
+        now,
+        ctx.bumps.template,
+    )?;
+
+    msg!(
+        "Invoice template created by {}: {} every {}s",
+        ctx.accounts.service.key(),
+        amount,
+        interval.get()
+    );
+    Ok(())
+}
@@ -0,0 +1,398 @@
+/*
+ * ============================================================================
+ * MERCHANT CASHBACK
+ * ============================================================================
+ *
+ * A namespace's `CashbackProgram` (`[b"cashback", config]`) credits payers
+ * `rate` of what they send to a registered merchant, paid out of the fee
+ * treasury. The admin registers merchants with a `CashbackMerchant` PDA
+ * (`[b"cashback_merchant", merchant]`); `transfer_tokens` pays cashback when
+ * both accounts are passed and the receiver is the registered merchant.
+ *
+ * Payouts are capped by a per-epoch budget. The payment that exhausts it is
+ * paid only what remains and suspends the program for the rest of the epoch;
+ * it resumes on the first merchant payment of a later epoch. Payouts never
+ * exceed the treasury balance. Every payout, suspension, resumption and
+ * registration change emits an event, so payouts reconcile against the
+ * ledger's `Fees` account.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::math;
+use crate::{
+    Bps, CustomError, FeeTreasury, GlobalConfig, InstructionTag, Ledger, LedgerAccount, UserAccount,
+};
+
+/// Namespace cashback settings and this epoch's payouts
+#[account]
+pub struct CashbackProgram {
+    pub rate: Bps,         // Share of a merchant payment credited back (8 bytes)
+    pub epoch_budget: u64, // Most paid out per epoch (8 bytes)
+    pub epoch: u64,        // Epoch `spent` accumulates for (8 bytes)
+    pub spent: u64,        // Paid out in `epoch` (8 bytes)
+    pub suspended: bool,   // Budget exhausted for `epoch` (1 byte)
+    pub total_paid: u64,   // Paid out over all epochs (8 bytes)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+/// Outcome of one cashback grant
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct CashbackGrant {
+    pub amount: u64,     // Credited to the payer
+    pub resumed: bool,   // A new epoch lifted an earlier suspension
+    pub suspended: bool, // This grant exhausted the epoch budget
+}
+
+impl CashbackProgram {
+    /// Space required for the program including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        8 +                      // rate: Bps
+        8 +                      // epoch_budget: u64
+        8 +                      // epoch: u64
+        8 +                      // spent: u64
+        1 +                      // suspended: bool
+        8 +                      // total_paid: u64
+        1; // bump: u8
+
+    /// Start accumulating for `epoch`; returns true if that lifted a suspension
+    pub fn roll_to(&mut self, epoch: u64) -> bool {
+        if epoch == self.epoch {
+            return false;
+        }
+        let resumed = self.suspended;
+        self.epoch = epoch;
+        self.spent = 0;
+        self.suspended = false;
+        resumed
+    }
+
+    /// Cashback on a merchant payment of `amount` in `epoch`, with
+    /// `available` left in the treasury to fund it
+    pub fn grant(&mut self, amount: u64, epoch: u64, available: u64) -> Result<CashbackGrant> {
+        let resumed = self.roll_to(epoch);
+        if self.suspended {
+            return Ok(CashbackGrant::default());
+        }
+
+        let due = self.rate.of(amount, Rounding::Down)?; // Rounds in the treasury's favour
+        let remaining = self.epoch_budget.saturating_sub(self.spent);
+        let paid = due.min(remaining).min(available);
+        self.spent = math::add(self.spent, paid)?;
+        self.total_paid = math::add(self.total_paid, paid)?;
+        self.suspended = self.spent >= self.epoch_budget;
+
+        Ok(CashbackGrant {
+            amount: paid,
+            resumed,
+            suspended: self.suspended,
+        })
+    }
+}
+
+/// Registration of a user account as a cashback merchant
+#[account]
+pub struct CashbackMerchant {
+    pub merchant: Pubkey,   // Merchant's user account (32 bytes)
+    pub registered_at: i64, // When the admin registered it (8 bytes)
+    pub bump: u8,           // PDA bump seed (1 byte)
+}
+
+impl CashbackMerchant {
+    /// Space required for the registration including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // merchant: Pubkey
+        8 +                      // registered_at: i64
+        1; // bump: u8
+}
+
+/// Context for creating the namespace's cashback program
+#[derive(Accounts)]
+pub struct InitializeCashback<'info> {
+    /// Program being created
+    #[account(
+        init,
+        payer = admin,
+        space = CashbackProgram::LEN,
+        seeds = [b"cashback", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub cashback: Account<'info, CashbackProgram>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for changing the cashback rate and budget
+#[derive(Accounts)]
+pub struct SetCashback<'info> {
+    /// Program being changed
+    #[account(mut, seeds = [b"cashback", config.key().as_ref()], bump = cashback.bump)]
+    pub cashback: Account<'info, CashbackProgram>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+}
+
+/// Context for registering a cashback merchant
+#[derive(Accounts)]
+pub struct RegisterMerchant<'info> {
+    /// Registration being created
+    #[account(
+        init,
+        payer = admin,
+        space = CashbackMerchant::LEN,
+        seeds = [b"cashback_merchant", merchant.key().as_ref()],
+        bump
+    )]
+    pub registration: Account<'info, CashbackMerchant>,
+
+    /// Merchant's user account
+    #[account(
+        constraint = merchant.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), merchant.authority.as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, UserAccount>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for removing a cashback merchant
+#[derive(Accounts)]
+pub struct DeregisterMerchant<'info> {
+    /// Registration being closed, rent returned to the admin
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"cashback_merchant", merchant.key().as_ref()],
+        bump = registration.bump
+    )]
+    pub registration: Account<'info, CashbackMerchant>,
+
+    /// Merchant's user account
+    #[account(
+        constraint = merchant.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), merchant.authority.as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, UserAccount>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, receives the rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+/// Event emitted when the cashback rate or budget changes
+#[event]
+pub struct CashbackConfiguredEvent {
+    pub rate: Bps,           // Share of merchant payments credited back
+    pub epoch_budget: u64,   // Most paid out per epoch
+    pub suspended: bool,     // Whether this epoch's budget is still exhausted
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a merchant is registered or deregistered
+#[event]
+pub struct CashbackMerchantChangedEvent {
+    pub merchant: Pubkey,    // Merchant's user account
+    pub registered: bool,    // False once deregistered
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted for every cashback payout
+#[event]
+pub struct CashbackPaidEvent {
+    pub payer: Pubkey,       // Credited user account
+    pub merchant: Pubkey,    // Merchant paid
+    pub payment: u64,        // Amount sent to the merchant
+    pub cashback: u64,       // Amount credited back
+    pub epoch: u64,          // Epoch the payout counts against
+    pub epoch_spent: u64,    // Paid out in the epoch so far
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when payouts stop or restart for an epoch
+#[event]
+pub struct CashbackSuspensionEvent {
+    pub epoch: u64,          // Epoch concerned
+    pub suspended: bool,     // True when the budget ran out, false on resumption
+    pub epoch_spent: u64,    // Paid out in the epoch
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Credit `payer` cashback on a payment of `amount` to `merchant`
+///
+/// Funded from the treasury and posted `Fees -> Users`; returns the credit.
+pub fn pay_cashback<'info>(
+    cashback: &mut CashbackProgram,
+    treasury: &mut FeeTreasury,
+    ledger: &mut Ledger,
+    payer: &mut Account<'info, UserAccount>,
+    merchant: Pubkey,
+    amount: u64,
+    tag: InstructionTag,
+) -> Result<u64> {
+    let epoch = Clock::get()?.epoch;
+    let grant = cashback.grant(amount, epoch, treasury.balance)?;
+    if grant.resumed {
+        emit!(CashbackSuspensionEvent {
+            epoch,
+            suspended: false,
+            epoch_spent: 0,
+            tag,
+        });
+    }
+    if grant.amount > 0 {
+        treasury.balance = math::sub(treasury.balance, grant.amount)?; // Capped by the balance above
+        payer.balance = math::add(payer.balance, grant.amount)?;
+        ledger.post(LedgerAccount::Fees, LedgerAccount::Users, grant.amount)?; // Paid out of collected fees
+
+        emit!(CashbackPaidEvent {
+            payer: payer.key(),
+            merchant,
+            payment: amount,
+            cashback: grant.amount,
+            epoch,
+            epoch_spent: cashback.spent,
+            tag,
+        });
+    }
+    if grant.suspended {
+        emit!(CashbackSuspensionEvent {
+            epoch,
+            suspended: true,
+            epoch_spent: cashback.spent,
+            tag,
+        });
+        msg!("Cashback suspended for epoch {}", epoch);
+    }
+    Ok(grant.amount)
+}
+
+/// Create the cashback program with `rate` and `epoch_budget`
+pub fn handle_initialize_cashback(
+    ctx: Context<InitializeCashback>,
+    rate: Bps,
+    epoch_budget: u64,
+) -> Result<()> {
+    require!(rate.is_fraction(), CustomError::InvalidCashbackRate);
+
+    let cashback = &mut ctx.accounts.cashback;
+    cashback.rate = rate;
+    cashback.epoch_budget = epoch_budget;
+    cashback.epoch = Clock::get()?.epoch;
+    cashback.spent = 0;
+    cashback.suspended = false;
+    cashback.total_paid = 0;
+    cashback.bump = ctx.bumps.cashback;
+
+    emit!(CashbackConfiguredEvent {
+        rate,
+        epoch_budget,
+        suspended: false,
+        tag: InstructionTag::InitializeCashback,
+    });
+
+    msg!("Cashback program created at {} bps", rate.get());
+    Ok(())
+}
+
+/// Change the rate and budget; a raised budget lifts this epoch's suspension
+pub fn handle_set_cashback(ctx: Context<SetCashback>, rate: Bps, epoch_budget: u64) -> Result<()> {
+    require!(rate.is_fraction(), CustomError::InvalidCashbackRate);
+
+    let cashback = &mut ctx.accounts.cashback;
+    cashback.roll_to(Clock::get()?.epoch);
+    cashback.rate = rate;
+    cashback.epoch_budget = epoch_budget;
+    cashback.suspended = cashback.spent >= epoch_budget;
+
+    emit!(CashbackConfiguredEvent {
+        rate,
+        epoch_budget,
+        suspended: cashback.suspended,
+        tag: InstructionTag::SetCashback,
+    });
+
+    msg!(
+        "Cashback set to {} bps, budget {}",
+        rate.get(),
+        epoch_budget
+    );
+    Ok(())
+}
+
+/// Register the user account as a cashback merchant
+pub fn handle_register_merchant(ctx: Context<RegisterMerchant>) -> Result<()> {
+    let registration = &mut ctx.accounts.registration;
+    registration.merchant = ctx.accounts.merchant.key();
+    registration.registered_at = Clock::get()?.unix_timestamp;
+    registration.bump = ctx.bumps.registration;
+
+    emit!(CashbackMerchantChangedEvent {
+        merchant: registration.merchant,
+        registered: true,
+        tag: InstructionTag::RegisterMerchant,
+    });
+
+    msg!("Cashback merchant registered: {}", registration.merchant);
+    Ok(())
+}
+
+/// Remove the user account's cashback merchant registration
+pub fn handle_deregister_merchant(ctx: Context<DeregisterMerchant>) -> Result<()> {
+    emit!(CashbackMerchantChangedEvent {
+        merchant: ctx.accounts.merchant.key(),
+        registered: false,
+        tag: InstructionTag::DeregisterMerchant,
+    });
+
+    msg!(
+        "Cashback merchant deregistered: {}",
+        ctx.accounts.merchant.key()
+    );
+    Ok(())
+}
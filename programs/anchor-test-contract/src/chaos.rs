@@ -0,0 +1,180 @@
+/*
+ * ============================================================================
+ * CHAOS INJECTION
+ * ============================================================================
+ *
+ * Deterministic failure injection for the integration suite, reaching error
+ * paths a healthy validator never takes. The namespace admin creates a
+ * `ChaosConfig` and tunes three knobs:
+ * - `fail_on_call`: the Nth hooked call fails with `ChaosInjected`
+ * - `clock_skew_secs`: added to the clock hooked handlers read
+ * - `oracle_delay_secs`: how much older `delay_oracle` makes a mint's
+ *   accepted price look, so the deviation guard treats it as stale
+ *
+ * `transfer_tokens` is hooked when the chaos account is passed; helpers
+ * that only take a timestamp, like `accept_oracle_price`, are driven
+ * through `skewed` and `delay_oracle` directly. Injection is
+ * only compiled with the `chaos` feature, which must never reach a deployed
+ * build: other builds ignore the account and `init_chaos` always fails.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, GlobalConfig, InstructionTag, MintConfig};
+
+/// Knobs of a chaos configuration
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ChaosParams {
+    pub fail_on_call: u64,      // Hooked call that fails, 1-based (0 = never)
+    pub clock_skew_secs: i64,   // Added to the hooked clock, may be negative
+    pub oracle_delay_secs: i64, // Subtracted from accepted price timestamps
+}
+
+/// A namespace's failure injection state
+#[account]
+pub struct ChaosConfig {
+    pub config: Pubkey,      // Namespace config injected into (32 bytes)
+    pub params: ChaosParams, // Current knobs (24 bytes)
+    pub calls: u64,          // Hooked calls since the knobs were set (8 bytes)
+    pub bump: u8,            // PDA bump seed (1 byte)
+}
+
+impl ChaosConfig {
+    /// Space required for the account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // config: Pubkey
+        8 + 8 + 8 +              // params: ChaosParams
+        8 +                      // calls: u64
+        1; // bump: u8
+
+    /// Count a hooked call, failing it when it is the configured one
+    pub fn record_call(&mut self) -> Result<()> {
+        self.calls = self.calls.saturating_add(1);
+        require!(
+            self.calls != self.params.fail_on_call,
+            CustomError::ChaosInjected
+        );
+        Ok(())
+    }
+
+    /// `now` as seen through the configured clock skew
+    pub fn skewed(&self, now: i64) -> i64 {
+        now.saturating_add(self.params.clock_skew_secs)
+    }
+
+    /// Age `mint_config`'s accepted price by the configured oracle delay
+    pub fn delay_oracle(&self, mint_config: &mut MintConfig) {
+        mint_config.last_price_at = mint_config
+            .last_price_at
+            .saturating_sub(self.params.oracle_delay_secs);
+    }
+}
+
+/// Context for creating the namespace's chaos configuration
+#[derive(Accounts)]
+pub struct InitChaos<'info> {
+    /// Account being created
+    #[account(
+        init,
+        payer = admin,
+        space = ChaosConfig::LEN,
+        seeds = [b"chaos", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub chaos: Account<'info, ChaosConfig>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for retuning the chaos knobs
+#[derive(Accounts)]
+pub struct SetChaos<'info> {
+    /// Account being changed
+    #[account(mut, seeds = [b"chaos", config.key().as_ref()], bump = chaos.bump)]
+    pub chaos: Account<'info, ChaosConfig>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+}
+
+/// Event emitted when the chaos knobs change
+#[event]
+pub struct ChaosSetEvent {
+    pub params: ChaosParams, // Knobs now in force
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// The clock a hooked handler reads, counting the call against `chaos`
+///
+/// The unskewed clock in builds without the `chaos` feature.
+pub fn chaos_clock(chaos: Option<&mut Account<ChaosConfig>>) -> Result<i64> {
+    let now = Clock::get()?.unix_timestamp;
+    #[cfg(feature = "chaos")]
+    if let Some(chaos) = chaos {
+        chaos.record_call()?;
+        return Ok(chaos.skewed(now));
+    }
+    #[cfg(not(feature = "chaos"))]
+    let _ = chaos;
+    Ok(now)
+}
+
+/// Create the chaos configuration with `params`
+#[cfg(feature = "chaos")]
+pub fn handle_init_chaos(ctx: Context<InitChaos>, params: ChaosParams) -> Result<()> {
+    let chaos = &mut ctx.accounts.chaos;
+    chaos.config = ctx.accounts.config.key();
+    chaos.params = params;
+    chaos.calls = 0;
+    chaos.bump = ctx.bumps.chaos;
+
+    emit!(ChaosSetEvent {
+        params,
+        tag: InstructionTag::InitChaos,
+    });
+
+    msg!("Chaos injection initialized");
+    Ok(())
+}
+
+/// Chaos injection is compiled out of this build
+#[cfg(not(feature = "chaos"))]
+pub fn handle_init_chaos(_ctx: Context<InitChaos>, _params: ChaosParams) -> Result<()> {
+    err!(CustomError::ChaosDisabled)
+}
+
+/// Replace the knobs and restart the call count
+pub fn handle_set_chaos(ctx: Context<SetChaos>, params: ChaosParams) -> Result<()> {
+    let chaos = &mut ctx.accounts.chaos;
+    chaos.params = params;
+    chaos.calls = 0;
+
+    emit!(ChaosSetEvent {
+        params,
+        tag: InstructionTag::SetChaos,
+    });
+
+    msg!("Chaos knobs set");
+    Ok(())
+}
@@ -0,0 +1,206 @@
+/*
+ * ============================================================================
+ * VOLUME CIRCUIT BREAKER
+ * ============================================================================
+ *
+ * Transfer volume is accumulated per Solana epoch in the `EpochStats` PDA,
+ * alongside an exponential trailing average of past epochs. When the current
+ * epoch's volume exceeds `GlobalConfig::circuit_breaker_multiple_bps` times the
+ * trailing average, the breaker trips and further transfers fail until the
+ * admin resets it.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    ConfigSnapshotEvent, CustomError, GlobalConfig, InstructionTag, BPS_DENOMINATOR,
+    TRAILING_EPOCHS,
+};
+
+/// Per-epoch transfer volume and circuit breaker state
+#[account]
+pub struct EpochStats {
+    pub epoch: u64,            // Epoch currently being accumulated (8 bytes)
+    pub volume: u64,           // Transfer volume in `epoch` (8 bytes)
+    pub trailing_average: u64, // Smoothed volume of past epochs (8 bytes)
+    pub tripped: bool,         // Breaker state; blocks transfers while set (1 byte)
+    pub tripped_at: i64,       // When the breaker last tripped (8 bytes)
+    pub bump: u8,              // PDA bump seed (1 byte)
+}
+
+impl EpochStats {
+    /// Space required for the stats account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        8 +                      // epoch: u64
+        8 +                      // volume: u64
+        8 +                      // trailing_average: u64
+        1 +                      // tripped: bool
+        8 +                      // tripped_at: i64
+        1; // bump: u8
+
+    /// Fold the finished epoch into the trailing average and start `epoch`
+    ///
+    /// Returns true if a new epoch was started.
+    fn roll_to(&mut self, epoch: u64) -> bool {
+        if epoch <= self.epoch {
+            return false; // Same epoch, keep accumulating
+        }
+
+        let mut average = if self.trailing_average == 0 {
+            self.volume as u128 // First completed epoch seeds the average
+        } else {
+            ema(self.trailing_average as u128, self.volume as u128)
+        };
+
+        // Epochs without any transfer count as zero volume (epoch > self.epoch here)
+        let idle_epochs = (epoch - self.epoch - 1).min(TRAILING_EPOCHS);
+        for _ in 0..idle_epochs {
+            average = ema(average, 0);
+        }
+
+        self.trailing_average = average as u64;
+        self.epoch = epoch;
+        self.volume = 0;
+        true
+    }
+
+    /// Ceiling on current-epoch volume, or None when the breaker is inactive
+    pub fn volume_limit(&self, multiple_bps: u64) -> Option<u128> {
+        if multiple_bps == 0 || self.trailing_average == 0 {
+            return None; // Disabled, or no history to compare against
+        }
+        Some(self.trailing_average as u128 * multiple_bps as u128 / BPS_DENOMINATOR as u128)
+    }
+}
+
+/// One smoothing step of the exponential trailing average
+fn ema(average: u128, sample: u128) -> u128 {
+    (average * (TRAILING_EPOCHS as u128 - 1) + sample) / TRAILING_EPOCHS as u128
+}
+
+/// Event emitted when abnormal volume trips the breaker
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    pub epoch: u64,            // Epoch in which the breaker tripped
+    pub volume: u64,           // Volume that triggered the trip
+    pub trailing_average: u64, // Trailing average at the time
+    pub multiple_bps: u64,     // Configured multiple
+    pub timestamp: i64,        // When the breaker tripped
+    pub tag: InstructionTag,   // Emitting instruction
+}
+
+/// Account a transfer against the breaker, tripping it on abnormal volume
+///
+/// The transfer that crosses the limit still completes; the breaker blocks
+/// every transfer after it until `reset_circuit_breaker` is called. The first
+/// transfer of an epoch also emits the epoch's `ConfigSnapshotEvent`.
+pub fn record_transfer_volume(
+    stats: &mut EpochStats,
+    config: &GlobalConfig,
+    amount: u64,
+    tag: InstructionTag,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    if stats.roll_to(clock.epoch) {
+        emit!(ConfigSnapshotEvent::from_config(config, &clock, tag));
+    }
+
+    require!(!stats.tripped, CustomError::CircuitBreakerTripped); // Blocked until reset
+
+    stats.volume = stats
+        .volume
+        .checked_add(amount)
+        .ok_or(CustomError::MathOverflow)?;
+
+    let multiple_bps = config.circuit_breaker_multiple_bps;
+    if let Some(limit) = stats.volume_limit(multiple_bps) {
+        if stats.volume as u128 > limit {
+            stats.tripped = true;
+            stats.tripped_at = clock.unix_timestamp;
+
+            emit!(CircuitBreakerTrippedEvent {
+                epoch: stats.epoch,
+                volume: stats.volume,
+                trailing_average: stats.trailing_average,
+                multiple_bps,
+                timestamp: clock.unix_timestamp,
+                tag,
+            });
+            msg!("Circuit breaker tripped at volume {}", stats.volume);
+        }
+    }
+
+    Ok(())
+}
+
+/// Context for creating the epoch stats account
+#[derive(Accounts)]
+pub struct InitializeEpochStats<'info> {
+    /// Stats account being created
+    #[account(
+        init,
+        payer = admin,
+        space = EpochStats::LEN,
+        seeds = [b"epoch_stats", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for resetting a tripped breaker
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    /// Stats account holding the breaker state
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+}
+
+/// Create the epoch stats account starting at the current epoch
+pub fn handle_initialize_epoch_stats(ctx: Context<InitializeEpochStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.epoch_stats;
+    stats.epoch = Clock::get()?.epoch;
+    stats.volume = 0;
+    stats.trailing_average = 0;
+    stats.tripped = false;
+    stats.tripped_at = 0;
+    stats.bump = ctx.bumps.epoch_stats;
+
+    msg!("Epoch stats initialized at epoch {}", stats.epoch);
+    Ok(())
+}
+
+/// Clear a tripped breaker so transfers can resume
+pub fn handle_reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+    let stats = &mut ctx.accounts.epoch_stats;
+    require!(stats.tripped, CustomError::CircuitBreakerNotTripped);
+    stats.tripped = false;
+
+    msg!("Circuit breaker reset by {}", ctx.accounts.admin.key());
+    Ok(())
+}
@@ -0,0 +1,52 @@
+/*
+ * ============================================================================
+ * CLOCK TOLERANCE
+ * ============================================================================
+ *
+ * `Clock::unix_timestamp` is a stake-weighted estimate and drifts from wall
+ * time by tens of seconds, while clients set deadlines from their own
+ * clocks. A transaction signed just before an expiry can therefore land
+ * just after it. Each namespace sets a `clock_tolerance_secs`, and every
+ * deadline comparison goes through `ClockTolerance`, which judges a
+ * deadline as falling that much later:
+ *
+ *     tolerance.before(now, deadline)  // still in time: now < deadline + tolerance
+ *     tolerance.passed(now, deadline)  // too late:      now >= deadline + tolerance
+ *
+ * The same shifted instant decides both sides of a deadline, so no moment
+ * belongs to two phases. An attestation or token stays usable for the
+ * tolerance after its expiry, and `expire` cannot retire it until then.
+ * Sealed-bid reveals open only when late bids no longer can.
+ *
+ * Creation-time checks such as "the expiry must lie in the future" read the
+ * raw clock, and so do delays that protect the account, like cooldowns
+ * and limit increases. Tolerance only ever extends a deadline.
+ */
+
+use crate::{GlobalConfig, Seconds};
+
+/// How much later than stated a namespace judges its deadlines
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ClockTolerance(pub Seconds);
+
+impl ClockTolerance {
+    /// Deadlines judged at exactly their timestamp
+    pub const NONE: ClockTolerance = ClockTolerance(Seconds(0));
+
+    /// Whether `now` is still in time for `deadline`
+    pub fn before(self, now: i64, deadline: i64) -> bool {
+        now < self.0.after(deadline)
+    }
+
+    /// Whether `deadline` is over at `now`
+    pub fn passed(self, now: i64, deadline: i64) -> bool {
+        !self.before(now, deadline)
+    }
+}
+
+impl GlobalConfig {
+    /// Tolerance the namespace applies to deadline comparisons
+    pub fn clock_tolerance(&self) -> ClockTolerance {
+        ClockTolerance(Seconds(self.clock_tolerance_secs))
+    }
+}
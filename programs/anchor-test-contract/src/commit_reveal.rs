@@ -0,0 +1,193 @@
+/*
+ * ============================================================================
+ * COMMIT-REVEAL
+ * ============================================================================
+ *
+ * Generic two-phase commitments shared by raffles, sealed-bid auctions and
+ * secret voting. A committer stores `hash(owner || domain || preimage)` in a
+ * `Commitment` PDA, then reveals the preimage inside `[reveal_after,
+ * expires_at)`:
+ * - binding the owner and domain into the hash stops one commitment being
+ *   replayed by another user or in another domain
+ * - `reveal_after` stops a reveal before every participant has committed
+ * - `revealed` stops the same commitment being revealed twice
+ *
+ * `domain` identifies the consumer instance (e.g. one auction); features read
+ * the revealed commitment instead of re-implementing the checks.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, ClockTolerance, CustomError, GlobalConfig,
+    InstructionTag, MAX_PREIMAGE_LEN,
+};
+
+/// A hashed commitment awaiting its reveal
+#[account]
+pub struct Commitment {
+    pub owner: Pubkey,     // Committer (32 bytes)
+    pub domain: u64,       // Consumer instance the commitment belongs to (8 bytes)
+    pub hash: [u8; 32],    // hash(owner || domain || preimage) (32 bytes)
+    pub committed_at: i64, // When the commitment was made (8 bytes)
+    pub reveal_after: i64, // Earliest reveal timestamp (8 bytes)
+    pub expires_at: i64,   // Reveals rejected from this timestamp (8 bytes)
+    pub revealed: bool,    // Set once the preimage is verified (1 byte)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl Commitment {
+    /// Space required for a commitment including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // owner: Pubkey
+        8 +                      // domain: u64
+        32 +                     // hash: [u8; 32]
+        8 +                      // committed_at: i64
+        8 +                      // reveal_after: i64
+        8 +                      // expires_at: i64
+        1 +                      // revealed: bool
+        1; // bump: u8
+
+    /// Commitment hash a client must submit for `preimage`
+    pub fn hash_for(owner: &Pubkey, domain: u64, preimage: &[u8]) -> [u8; 32] {
+        hashv(&[owner.as_ref(), &domain.to_le_bytes(), preimage]).to_bytes()
+    }
+
+    /// Verify `preimage` against the commitment at `now` and mark it revealed
+    ///
+    /// Both ends of the reveal window move by `tolerance`, so reveals never
+    /// open while the phase before them is still in time.
+    pub fn verify_reveal(
+        &mut self,
+        preimage: &[u8],
+        now: i64,
+        tolerance: ClockTolerance,
+    ) -> Result<()> {
+        require!(!self.revealed, CustomError::CommitmentAlreadyRevealed);
+        require!(
+            tolerance.passed(now, self.reveal_after),
+            CustomError::RevealTooEarly
+        );
+        require!(
+            tolerance.before(now, self.expires_at),
+            CustomError::CommitmentExpired
+        );
+        require!(
+            preimage.len() <= MAX_PREIMAGE_LEN,
+            CustomError::PreimageTooLong
+        );
+        require!(
+            Self::hash_for(&self.owner, self.domain, preimage) == self.hash,
+            CustomError::CommitmentMismatch
+        );
+        self.revealed = true;
+        Ok(())
+    }
+}
+
+/// Context for creating a commitment
+#[derive(Accounts)]
+#[instruction(domain: u64)]
+pub struct Commit<'info> {
+    /// Commitment being created
+    #[account(
+        init,
+        payer = owner,
+        space = Commitment::LEN,
+        seeds = [b"commitment", config.key().as_ref(), owner.key().as_ref(), &domain.to_le_bytes()], // One per owner and domain
+        bump
+    )]
+    pub commitment: Account<'info, Commitment>,
+
+    /// Committer, pays rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revealing a commitment
+#[derive(Accounts)]
+pub struct Reveal<'info> {
+    /// Commitment being revealed
+    #[account(
+        mut,                                     // `revealed` flag will be set
+        has_one = owner,                         // Only the committer reveals
+        seeds = [b"commitment", config.key().as_ref(), owner.key().as_ref(), &commitment.domain.to_le_bytes()],
+        bump = commitment.bump
+    )]
+    pub commitment: Account<'info, Commitment>,
+
+    /// Committer
+    pub owner: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a commitment is revealed
+#[event]
+pub struct CommitmentRevealedEvent {
+    pub owner: Pubkey,       // Committer
+    pub domain: u64,         // Consumer instance
+    pub preimage: Vec<u8>,   // Revealed preimage
+    pub timestamp: i64,      // When the reveal happened
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Store a commitment revealable within `[reveal_after, expires_at)`
+pub fn handle_commit(
+    ctx: Context<Commit>,
+    domain: u64,
+    hash: [u8; 32],
+    reveal_after: i64,
+    expires_at: i64,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::COMMIT)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now <= reveal_after && reveal_after < expires_at,
+        CustomError::InvalidRevealWindow
+    );
+
+    let commitment = &mut ctx.accounts.commitment;
+    commitment.owner = ctx.accounts.owner.key();
+    commitment.domain = domain;
+    commitment.hash = hash;
+    commitment.committed_at = now;
+    commitment.reveal_after = reveal_after;
+    commitment.expires_at = expires_at;
+    commitment.revealed = false;
+    commitment.bump = ctx.bumps.commitment;
+
+    msg!("Commitment stored for domain {}", domain);
+    Ok(())
+}
+
+/// Reveal the preimage of the caller's commitment
+pub fn handle_reveal(ctx: Context<Reveal>, preimage: Vec<u8>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::REVEAL)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let commitment = &mut ctx.accounts.commitment;
+    commitment.verify_reveal(&preimage, now, ctx.accounts.config.clock_tolerance())?;
+
+    emit!(CommitmentRevealedEvent {
+        owner: commitment.owner,
+        domain: commitment.domain,
+        preimage,
+        timestamp: now,
+        tag: InstructionTag::Reveal,
+    });
+
+    msg!("Commitment revealed for domain {}", commitment.domain);
+    Ok(())
+}
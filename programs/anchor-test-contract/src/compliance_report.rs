@@ -0,0 +1,176 @@
+/*
+ * ============================================================================
+ * COMPLIANCE REPORTS
+ * ============================================================================
+ *
+ * `compile_compliance_report(from, to)` aggregates a user account's activity
+ * between two timestamps into a `ComplianceReport` an auditor can be handed
+ * instead of the raw history: how many distinct counterparties, the totals
+ * received and sent, and which `report_flags` the range triggered.
+ *
+ * The source is the user's `ActivityLog`, walked once; every balance change
+ * appends to it, including deposits, withdrawals and treasury payouts,
+ * which count towards the totals but have no counterparty to count. When
+ * the range starts before the log was opened, or the log has overwritten
+ * entries the range may have held, the report carries `INCOMPLETE_HISTORY`
+ * rather than silently under-reporting.
+ *
+ * Like account statements, the report comes back as return data tagged with
+ * the program id, and is emitted with its digest in a
+ * `ComplianceReportCompiledEvent`; anyone may compile one, since everything
+ * it summarizes is already public on chain.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::math;
+use crate::{
+    ActivityKind, ActivityLog, CustomError, GlobalConfig, InstructionTag, UserAccount,
+    REPORTABLE_TRANSFER_THRESHOLD,
+};
+
+/// Domain prefix hashed before every report, versioned with the format
+pub const COMPLIANCE_REPORT_DOMAIN: &[u8] = b"anchor-test-contract:compliance-report:v1\n";
+
+/// Bits set in `ComplianceReport::flags`
+pub mod report_flags {
+    pub const LARGE_TRANSFER: u64 = 1 << 0; // A transfer of at least `REPORTABLE_TRANSFER_THRESHOLD`
+    pub const DEACTIVATED: u64 = 1 << 1; // The account was deactivated in range
+    pub const COMPLIANCE_HOLD: u64 = 1 << 2; // A compliance hold is in place now
+    pub const INCOMPLETE_HISTORY: u64 = 1 << 3; // The log does not cover the whole range
+}
+
+/// Aggregate of one user account's activity over a time range
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct ComplianceReport {
+    pub user: Pubkey,        // User account reported on
+    pub from: i64,           // First second of the range
+    pub to: i64,             // First second after the range
+    pub counterparties: u32, // Distinct user accounts transacted with
+    pub transfers: u32,      // Transfers in either direction
+    pub total_in: u64,       // Total received
+    pub total_out: u64,      // Total sent
+    pub flags: u64,          // `report_flags` triggered
+}
+
+impl ComplianceReport {
+    /// Aggregate `log`'s entries in `[from, to)` for `user`
+    pub fn compile(log: &ActivityLog, user: &UserAccount, from: i64, to: i64) -> Result<Self> {
+        let mut report = Self {
+            user: log.user,
+            from,
+            to,
+            ..Self::default()
+        };
+        let mut counterparties: Vec<Pubkey> = Vec::new();
+
+        for entry in log.recent() {
+            if entry.timestamp < from || entry.timestamp >= to {
+                continue;
+            }
+            match ActivityKind::from_u8(entry.kind) {
+                Some(kind @ (ActivityKind::Sent | ActivityKind::Received)) => {
+                    if kind == ActivityKind::Sent {
+                        report.total_out = math::add(report.total_out, entry.amount)?;
+                    } else {
+                        report.total_in = math::add(report.total_in, entry.amount)?;
+                    }
+                    report.transfers += 1;
+                    if entry.amount >= REPORTABLE_TRANSFER_THRESHOLD {
+                        report.flags |= report_flags::LARGE_TRANSFER;
+                    }
+                    if entry.counterparty != Pubkey::default()
+                        && !counterparties.contains(&entry.counterparty)
+                    {
+                        counterparties.push(entry.counterparty);
+                    }
+                }
+                Some(ActivityKind::Deactivated) => report.flags |= report_flags::DEACTIVATED,
+                Some(ActivityKind::Updated | ActivityKind::Opened) | None => {}
+            }
+        }
+        report.counterparties = counterparties.len() as u32;
+
+        if user.compliance_hold {
+            report.flags |= report_flags::COMPLIANCE_HOLD;
+        }
+        if !log.covers_since(from) {
+            report.flags |= report_flags::INCOMPLETE_HISTORY;
+        }
+        Ok(report)
+    }
+
+    /// Digest committing to every field, for auditors to reference
+    pub fn digest(&self) -> [u8; 32] {
+        hashv(&[
+            COMPLIANCE_REPORT_DOMAIN,
+            self.user.as_ref(),
+            &self.from.to_le_bytes(),
+            &self.to.to_le_bytes(),
+            &self.counterparties.to_le_bytes(),
+            &self.transfers.to_le_bytes(),
+            &self.total_in.to_le_bytes(),
+            &self.total_out.to_le_bytes(),
+            &self.flags.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+/// Context for compiling a user account's compliance report
+#[derive(Accounts)]
+pub struct CompileComplianceReport<'info> {
+    /// User account reported on
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// The user's activity log, the report's source
+    #[account(seeds = [b"activity_log", user.key().as_ref()], bump)]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    /// Namespace config the user belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event carrying a compiled report and its digest
+#[event]
+pub struct ComplianceReportCompiledEvent {
+    pub report: ComplianceReport, // Aggregated activity
+    pub digest: [u8; 32],         // `ComplianceReport::digest`
+    pub tag: InstructionTag,      // Emitting instruction
+}
+
+/// Report on the user's activity over the ended range `[from, to)`
+pub fn handle_compile_compliance_report(
+    ctx: Context<CompileComplianceReport>,
+    from: i64,
+    to: i64,
+) -> Result<ComplianceReport> {
+    require!(
+        from < to && to <= Clock::get()?.unix_timestamp,
+        CustomError::InvalidReportRange
+    );
+
+    let log = ctx.accounts.activity_log.load()?;
+    let report = ComplianceReport::compile(&log, &ctx.accounts.user, from, to)?;
+
+    emit!(ComplianceReportCompiledEvent {
+        digest: report.digest(),
+        report: report.clone(),
+        tag: InstructionTag::CompileComplianceReport,
+    });
+
+    msg!(
+        "Compliance report for {}: {} transfers, flags {:#x}",
+        report.user,
+        report.transfers,
+        report.flags
+    );
+    Ok(report)
+}
@@ -0,0 +1,598 @@
+/*
+ * ============================================================================
+ * GLOBAL CONFIGURATION AND INSTRUCTION KILL SWITCHES
+ * ============================================================================
+ *
+ * Program-wide settings live in a single `GlobalConfig` PDA controlled by an
+ * admin. The `disabled_instructions` bitmask lets the admin switch off
+ * individual instructions (e.g. only transfers) instead of the whole program.
+ * Every gated handler calls `require_instruction_enabled` before doing work.
+ * The `paused` switch (`set_paused`) stops every gated instruction at once
+ * without touching the mask, so unpausing restores exactly what was enabled.
+ *
+ * Each config is a namespace: an independent instance of the protocol
+ * sharing this program binary, created with `create_namespace`. The config
+ * PDA is `[b"config", namespace]`, and every other program PDA that is not
+ * already derived from a namespaced account (users, epoch stats, ledger,
+ * mint registry, ...) includes the config address in its seeds. Accounts
+ * of different namespaces therefore never collide or mix.
+ *
+ * Sensitive rates are stored as `RampedParam`s: a change moves linearly from
+ * the old to the new value over a configured duration and the effective value
+ * is computed lazily from the Clock, so there is no single transaction whose
+ * ordering can be exploited around a step change.
+ *
+ * Every change to a config value is followed by a `ConfigSnapshotEvent`
+ * carrying all of them, and the first transfer of each epoch emits one too,
+ * so the parameter regime in force over any slot range can be rebuilt from
+ * logs alone, without replaying individual change events.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    authority_change_summary, fee_for, require_digest, validate_sunset, Bps, CustomError,
+    EmissionSchedule, FeeInputs, FeeStrategy, InstructionTag, Seconds, DEFAULT_CLOCK_TOLERANCE,
+    DEFAULT_FEE_RATE_BPS, DEFAULT_REACTIVATION_COOLDOWN, MAX_CLOCK_TOLERANCE,
+    MAX_VERSIONED_INSTRUCTIONS,
+};
+
+/// Bit flags identifying instructions in `GlobalConfig::disabled_instructions`
+pub mod instruction_flags {
+    pub const INITIALIZE_USER: u64 = 1 << 0;
+    pub const UPDATE_USER: u64 = 1 << 1;
+    pub const TRANSFER_TOKENS: u64 = 1 << 2;
+    pub const DEACTIVATE_USER: u64 = 1 << 3;
+    pub const CONVERT_TO_JOINT: u64 = 1 << 4;
+    pub const JOINT_TRANSFER: u64 = 1 << 5;
+    pub const SCHEDULE_DEACTIVATION: u64 = 1 << 6;
+    pub const COMMIT: u64 = 1 << 7;
+    pub const REVEAL: u64 = 1 << 8;
+    pub const CREATE_AUCTION: u64 = 1 << 9;
+    pub const PLACE_BID: u64 = 1 << 10;
+    pub const REVEAL_BID: u64 = 1 << 11;
+    pub const ATTACH_DOCUMENT: u64 = 1 << 12;
+    pub const REMOVE_DOCUMENT: u64 = 1 << 13;
+    pub const ISSUE_ATTESTATION: u64 = 1 << 14;
+    pub const CROSS_NAMESPACE_TRANSFER: u64 = 1 << 15;
+    pub const EXPIRE: u64 = 1 << 16;
+    pub const SET_ACCOUNT_MODE: u64 = 1 << 17;
+    pub const GRANT_MANAGER: u64 = 1 << 18;
+    pub const DEPOSIT_TOKENS: u64 = 1 << 19;
+    pub const WITHDRAW_TOKENS: u64 = 1 << 20;
+    pub const APPROVE_DELEGATE: u64 = 1 << 21;
+    pub const TRANSFER_FROM: u64 = 1 << 22;
+    pub const REACTIVATE_USER: u64 = 1 << 23;
+    pub const CLOSE_USER: u64 = 1 << 24;
+    pub const BATCH_TRANSFER: u64 = 1 << 25;
+    pub const ROUTED_TRANSFER: u64 = 1 << 26;
+    pub const SET_GUARDIANS: u64 = 1 << 27;
+    pub const RECORD_OBLIGATION: u64 = 1 << 28;
+    pub const SETTLE_NET: u64 = 1 << 29;
+    pub const MIGRATE_USER: u64 = 1 << 30;
+    pub const CREATE_INVOICE_TEMPLATE: u64 = 1 << 31;
+    pub const ISSUE_INVOICE: u64 = 1 << 32;
+    pub const PAY_INVOICE: u64 = 1 << 33;
+    pub const PROMO_TRANSFER: u64 = 1 << 34;
+    pub const CREATE_ESCROW: u64 = 1 << 35;
+    pub const CLAIM_ESCROW: u64 = 1 << 36;
+    pub const OPEN_CREDIT_LINE: u64 = 1 << 37;
+    pub const DRAW_CREDIT: u64 = 1 << 38;
+    pub const REPAY_CREDIT: u64 = 1 << 39;
+    pub const SET_DAILY_LIMIT: u64 = 1 << 40;
+    pub const UPDATE_PROFILE: u64 = 1 << 41;
+    pub const FACTOR_INVOICE: u64 = 1 << 42;
+    pub const WATCHTOWER: u64 = 1 << 43;
+    pub const SET_POLICY: u64 = 1 << 44;
+    pub const ISSUE_CAPABILITY: u64 = 1 << 45;
+}
+
+/// A parameter moving linearly from `old` to `new` between `start` and `end`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RampedParam {
+    pub old: u64,   // Value before the ramp (8 bytes)
+    pub new: u64,   // Value once the ramp completes (8 bytes)
+    pub start: i64, // Ramp start timestamp (8 bytes)
+    pub end: i64,   // Ramp end timestamp (8 bytes)
+}
+
+impl RampedParam {
+    /// Serialized size of a ramped parameter
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    /// A parameter fixed at `value` with no ramp in progress
+    pub fn fixed(value: u64) -> Self {
+        Self {
+            old: value,
+            new: value,
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Effective value at `now`, interpolated linearly during the ramp
+    pub fn value_at(&self, now: i64) -> u64 {
+        if now >= self.end || self.end <= self.start {
+            return self.new; // Ramp finished (or never started)
+        }
+        if now <= self.start {
+            return self.old; // Ramp not started yet
+        }
+
+        // Differences are taken in i128 so extreme timestamps cannot overflow,
+        // and both factors then fit comfortably in u128
+        let elapsed = (now as i128 - self.start as i128) as u128;
+        let duration = (self.end as i128 - self.start as i128) as u128;
+        if self.new >= self.old {
+            let delta = (self.new - self.old) as u128;
+            self.old + (delta * elapsed / duration) as u64
+        } else {
+            let delta = (self.old - self.new) as u128;
+            self.old - (delta * elapsed / duration) as u64
+        }
+    }
+
+    /// Start a new ramp towards `target` from the value effective at `now`
+    pub fn ramp_to(&mut self, target: u64, now: i64, duration: Seconds) {
+        self.old = self.value_at(now);
+        self.new = target;
+        self.start = now;
+        self.end = duration.after(now);
+    }
+}
+
+/// Per-namespace configuration account
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,              // Authority allowed to change config (32 bytes)
+    pub disabled_instructions: u64, // Bitmask of `instruction_flags` (8 bytes)
+    pub bump: u8,                   // PDA bump seed (1 byte)
+    pub fee_rate_bps: RampedParam,  // Protocol fee rate in bps (32 bytes)
+    pub circuit_breaker_multiple_bps: u64, // Volume multiple that trips the breaker (8 bytes)
+    pub max_price_deviation_bps: u64, // Oracle move tolerated within the window (8 bytes)
+    pub price_deviation_window_secs: i64, // Lifetime of the reference price (8 bytes)
+    pub lookup_table: Pubkey,       // Registered address lookup table (32 bytes)
+    pub namespace: u64,             // Namespace identifier, part of the PDA seeds (8 bytes)
+    pub fee_strategy: FeeStrategy,  // How transfer fees are computed (65 bytes)
+    pub emission_schedule: EmissionSchedule, // Staking reward curve and supply cap (25 bytes)
+    pub compliance: Pubkey,         // Authority allowed to impose account modes (32 bytes)
+    pub paused: bool,               // Every gated instruction stopped (1 byte)
+    pub reactivation_cooldown_secs: i64, // Wait before a deactivated account reactivates (8 bytes)
+    pub clock_tolerance_secs: i64,  // Slack on deadline comparisons, see `clock` (8 bytes)
+    pub instruction_sunsets: [i64; MAX_VERSIONED_INSTRUCTIONS], // Sunset per registry slot, 0 = none (64 bytes)
+}
+
+impl GlobalConfig {
+    /// Space required for the config account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // admin: Pubkey
+        8 +                      // disabled_instructions: u64
+        1 +                      // bump: u8
+        RampedParam::LEN +       // fee_rate_bps: RampedParam
+        8 +                      // circuit_breaker_multiple_bps: u64
+        8 +                      // max_price_deviation_bps: u64
+        8 +                      // price_deviation_window_secs: i64
+        32 +                     // lookup_table: Pubkey
+        8 +                      // namespace: u64
+        FeeStrategy::LEN +       // fee_strategy: FeeStrategy
+        EmissionSchedule::LEN +  // emission_schedule: EmissionSchedule
+        32 +                     // compliance: Pubkey
+        1 +                      // paused: bool
+        8 +                      // reactivation_cooldown_secs: i64
+        8 +                      // clock_tolerance_secs: i64
+        8 * MAX_VERSIONED_INSTRUCTIONS; // instruction_sunsets: [i64; N]
+
+    /// Returns true if the instruction identified by `flag` is enabled
+    ///
+    /// Ignores `paused`; `require_instruction_enabled` checks both.
+    pub fn is_enabled(&self, flag: u64) -> bool {
+        self.disabled_instructions & flag == 0
+    }
+
+    /// Fee rate in basis points effective at `now`
+    pub fn fee_rate_at(&self, now: i64) -> u64 {
+        self.fee_rate_bps.value_at(now)
+    }
+
+    /// What the fee on `amount` at `now` is based on
+    pub fn fee_inputs(&self, amount: u64, now: i64, utilization: Bps) -> FeeInputs {
+        FeeInputs {
+            amount,
+            base_rate: Bps(self.fee_rate_at(now)),
+            utilization,
+        }
+    }
+
+    /// Fee the namespace's strategy charges on `amount` at `now`
+    pub fn fee_at(&self, amount: u64, now: i64, utilization: Bps) -> Result<u64> {
+        fee_for(
+            &self.fee_strategy,
+            &self.fee_inputs(amount, now, utilization),
+        )
+    }
+}
+
+/// Shared guard called at the top of every gated handler
+pub fn require_instruction_enabled(config: &GlobalConfig, flag: u64) -> Result<()> {
+    require!(!config.paused, CustomError::ProgramPaused);
+    require!(config.is_enabled(flag), CustomError::InstructionDisabled);
+    Ok(())
+}
+
+/// Context for creating a namespace (its config account)
+#[derive(Accounts)]
+#[instruction(namespace: u64)]
+pub struct CreateNamespace<'info> {
+    /// The namespace config account being created
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalConfig::LEN,
+        seeds = [b"config", namespace.to_le_bytes().as_ref()], // One config per namespace
+        bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Initial admin, pays for the account
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for admin-only config changes
+#[derive(Accounts)]
+pub struct AdminConfig<'info> {
+    /// The config account being modified
+    #[account(
+        mut,
+        has_one = admin @ CustomError::Unauthorized, // Only the admin may change config
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Current admin
+    pub admin: Signer<'info>,
+}
+
+/// Event carrying every config value, see the module docs for when it fires
+#[event]
+pub struct ConfigSnapshotEvent {
+    pub namespace: u64,                                         // Namespace identifier
+    pub admin: Pubkey,                                          // Config admin
+    pub compliance: Pubkey,                                     // Compliance authority
+    pub disabled_instructions: u64,                             // Kill-switch bitmask
+    pub paused: bool,                                           // Pause switch
+    pub fee_rate_bps: RampedParam,                              // Protocol fee rate and any ramp
+    pub fee_strategy: FeeStrategy,                              // How transfer fees are computed
+    pub emission_schedule: EmissionSchedule, // Staking reward curve and supply cap
+    pub circuit_breaker_multiple_bps: u64,   // Volume multiple that trips the breaker
+    pub max_price_deviation_bps: u64,        // Oracle move tolerated within the window
+    pub price_deviation_window_secs: i64,    // Lifetime of the reference price
+    pub reactivation_cooldown_secs: i64,     // Wait before a deactivated account reactivates
+    pub clock_tolerance_secs: i64,           // Slack on deadline comparisons
+    pub instruction_sunsets: [i64; MAX_VERSIONED_INSTRUCTIONS], // Sunsets of superseded entrypoints
+    pub lookup_table: Pubkey,                // Registered address lookup table
+    pub slot: u64,                           // Slot the snapshot was taken in
+    pub epoch: u64,                          // Epoch the snapshot was taken in
+    pub tag: InstructionTag,                 // Emitting instruction
+}
+
+impl ConfigSnapshotEvent {
+    /// Snapshot of `config` as of `clock`
+    pub fn from_config(config: &GlobalConfig, clock: &Clock, tag: InstructionTag) -> Self {
+        Self {
+            namespace: config.namespace,
+            admin: config.admin,
+            compliance: config.compliance,
+            disabled_instructions: config.disabled_instructions,
+            paused: config.paused,
+            fee_rate_bps: config.fee_rate_bps,
+            fee_strategy: config.fee_strategy,
+            emission_schedule: config.emission_schedule,
+            circuit_breaker_multiple_bps: config.circuit_breaker_multiple_bps,
+            max_price_deviation_bps: config.max_price_deviation_bps,
+            price_deviation_window_secs: config.price_deviation_window_secs,
+            reactivation_cooldown_secs: config.reactivation_cooldown_secs,
+            clock_tolerance_secs: config.clock_tolerance_secs,
+            instruction_sunsets: config.instruction_sunsets,
+            lookup_table: config.lookup_table,
+            slot: clock.slot,
+            epoch: clock.epoch,
+            tag,
+        }
+    }
+}
+
+/// Emit a `ConfigSnapshotEvent` of `config` at the current slot
+pub fn emit_config_snapshot(config: &GlobalConfig, tag: InstructionTag) -> Result<()> {
+    emit!(ConfigSnapshotEvent::from_config(
+        config,
+        &Clock::get()?,
+        tag
+    ));
+    Ok(())
+}
+
+/// Event emitted when the kill-switch bitmask changes
+#[event]
+pub struct InstructionsToggledEvent {
+    pub previous: u64,       // Bitmask before the change
+    pub current: u64,        // Bitmask after the change
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a parameter ramp is scheduled
+#[event]
+pub struct ParameterRampScheduledEvent {
+    pub parameter: String,   // Parameter name, e.g. "fee_rate_bps"
+    pub old: u64,            // Value at the start of the ramp
+    pub new: u64,            // Value at the end of the ramp
+    pub start: i64,          // Ramp start timestamp
+    pub end: i64,            // Ramp end timestamp
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create namespace `namespace` with the signer as admin and everything enabled
+pub fn handle_create_namespace(ctx: Context<CreateNamespace>, namespace: u64) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.namespace = namespace;
+    config.admin = ctx.accounts.admin.key();
+    config.disabled_instructions = 0;
+    config.bump = ctx.bumps.config;
+    config.fee_rate_bps = RampedParam::fixed(DEFAULT_FEE_RATE_BPS);
+    config.circuit_breaker_multiple_bps = 0; // Breaker disabled until configured
+    config.max_price_deviation_bps = 0; // Deviation guard disabled until configured
+    config.price_deviation_window_secs = 0;
+    config.lookup_table = Pubkey::default(); // Registered later by the admin
+    config.fee_strategy = FeeStrategy::Proportional;
+    config.emission_schedule = EmissionSchedule::default(); // No emissions until configured
+    config.compliance = config.admin; // Delegated later by the admin
+    config.paused = false;
+    config.reactivation_cooldown_secs = DEFAULT_REACTIVATION_COOLDOWN.get();
+    config.clock_tolerance_secs = DEFAULT_CLOCK_TOLERANCE.get();
+    config.instruction_sunsets = [0; MAX_VERSIONED_INSTRUCTIONS]; // Every entrypoint served
+    emit_config_snapshot(config, InstructionTag::CreateNamespace)?;
+
+    msg!("Namespace {} created, admin: {}", namespace, config.admin);
+    Ok(())
+}
+
+/// Replace the disabled-instruction bitmask
+pub fn handle_set_disabled_instructions(ctx: Context<AdminConfig>, mask: u64) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous = config.disabled_instructions;
+    config.disabled_instructions = mask;
+
+    emit!(InstructionsToggledEvent {
+        previous,
+        current: mask,
+        tag: InstructionTag::SetDisabledInstructions,
+    });
+    emit_config_snapshot(config, InstructionTag::SetDisabledInstructions)?;
+
+    msg!("Disabled instructions: {:#x} -> {:#x}", previous, mask);
+    Ok(())
+}
+
+/// Event emitted when the pause switch is flipped
+#[event]
+pub struct PauseToggledEvent {
+    pub paused: bool,        // Switch state after the change
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Pause or unpause every gated instruction
+pub fn handle_set_paused(ctx: Context<AdminConfig>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+
+    emit!(PauseToggledEvent {
+        paused,
+        tag: InstructionTag::SetPaused,
+    });
+    emit_config_snapshot(&ctx.accounts.config, InstructionTag::SetPaused)?;
+
+    msg!("Paused: {}", paused);
+    Ok(())
+}
+
+/// Ramp the protocol fee rate to `new_bps` over `duration` seconds
+pub fn handle_ramp_fee_rate(ctx: Context<AdminConfig>, new_bps: u64, duration: i64) -> Result<()> {
+    let (new_rate, duration) = (Bps(new_bps), Seconds(duration));
+    require!(new_rate.is_fraction(), CustomError::InvalidFeeRate); // At most 100%
+    require!(duration >= Seconds(0), CustomError::InvalidRampDuration); // Zero means immediate
+
+    let now = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+    config.fee_rate_bps.ramp_to(new_rate.get(), now, duration);
+    let ramp = config.fee_rate_bps;
+
+    emit!(ParameterRampScheduledEvent {
+        parameter: "fee_rate_bps".to_string(),
+        old: ramp.old,
+        new: ramp.new,
+        start: ramp.start,
+        end: ramp.end,
+        tag: InstructionTag::RampFeeRate,
+    });
+    emit_config_snapshot(config, InstructionTag::RampFeeRate)?;
+
+    msg!("Fee rate ramping {} -> {} bps", ramp.old, ramp.new);
+    Ok(())
+}
+
+/// Event emitted when the fee strategy is switched
+#[event]
+pub struct FeeStrategyChangedEvent {
+    pub previous: FeeStrategy, // Strategy before the change
+    pub current: FeeStrategy,  // Strategy after the change
+    pub tag: InstructionTag,   // Emitting instruction
+}
+
+/// Switch how transfer fees are computed
+pub fn handle_set_fee_strategy(ctx: Context<AdminConfig>, strategy: FeeStrategy) -> Result<()> {
+    strategy.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    let previous = config.fee_strategy;
+    config.fee_strategy = strategy;
+
+    emit!(FeeStrategyChangedEvent {
+        previous,
+        current: strategy,
+        tag: InstructionTag::SetFeeStrategy,
+    });
+    emit_config_snapshot(config, InstructionTag::SetFeeStrategy)?;
+
+    msg!("Fee strategy: {:?} -> {:?}", previous, strategy);
+    Ok(())
+}
+
+/// Event emitted when the emission schedule is replaced
+#[event]
+pub struct EmissionScheduleChangedEvent {
+    pub previous: EmissionSchedule, // Schedule before the change
+    pub current: EmissionSchedule,  // Schedule after the change
+    pub tag: InstructionTag,        // Emitting instruction
+}
+
+/// Replace the staking reward curve and supply cap
+pub fn handle_set_emission_schedule(
+    ctx: Context<AdminConfig>,
+    schedule: EmissionSchedule,
+) -> Result<()> {
+    schedule.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    let previous = config.emission_schedule;
+    config.emission_schedule = schedule;
+
+    emit!(EmissionScheduleChangedEvent {
+        previous,
+        current: schedule,
+        tag: InstructionTag::SetEmissionSchedule,
+    });
+    emit_config_snapshot(config, InstructionTag::SetEmissionSchedule)?;
+
+    msg!("Emission schedule: {:?} -> {:?}", previous, schedule);
+    Ok(())
+}
+
+/// Event emitted when the compliance authority is replaced
+#[event]
+pub struct ComplianceAuthorityChangedEvent {
+    pub previous: Pubkey,    // Authority before the change
+    pub current: Pubkey,     // Authority after the change
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Hand the compliance role to `compliance`, confirmed by its summary digest
+pub fn handle_set_compliance_authority(
+    ctx: Context<AdminConfig>,
+    compliance: Pubkey,
+    digest: [u8; 32],
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous = config.compliance;
+    let summary = authority_change_summary(config.namespace, "compliance", &previous, &compliance);
+    require_digest(&summary, Some(digest))?;
+    config.compliance = compliance;
+
+    emit!(ComplianceAuthorityChangedEvent {
+        previous,
+        current: compliance,
+        tag: InstructionTag::SetComplianceAuthority,
+    });
+    emit_config_snapshot(config, InstructionTag::SetComplianceAuthority)?;
+
+    msg!("Compliance authority: {} -> {}", previous, compliance);
+    Ok(())
+}
+
+/// Set the wait between deactivating and reactivating a user account
+pub fn handle_set_reactivation_cooldown(
+    ctx: Context<AdminConfig>,
+    cooldown_secs: i64,
+) -> Result<()> {
+    require!(cooldown_secs >= 0, CustomError::InvalidCooldown);
+    ctx.accounts.config.reactivation_cooldown_secs = cooldown_secs;
+    emit_config_snapshot(
+        &ctx.accounts.config,
+        InstructionTag::SetReactivationCooldown,
+    )?;
+
+    msg!("Reactivation cooldown set to {}s", cooldown_secs);
+    Ok(())
+}
+
+/// Set how much later than stated the namespace judges its deadlines
+pub fn handle_set_clock_tolerance(ctx: Context<AdminConfig>, tolerance_secs: i64) -> Result<()> {
+    require!(
+        (0..=MAX_CLOCK_TOLERANCE.get()).contains(&tolerance_secs),
+        CustomError::InvalidClockTolerance
+    );
+    ctx.accounts.config.clock_tolerance_secs = tolerance_secs;
+    emit_config_snapshot(&ctx.accounts.config, InstructionTag::SetClockTolerance)?;
+
+    msg!("Clock tolerance set to {}s", tolerance_secs);
+    Ok(())
+}
+
+/// Stop serving a superseded entrypoint from `sunset_at`, or keep serving
+/// it with 0
+pub fn handle_set_instruction_sunset(
+    ctx: Context<AdminConfig>,
+    instruction: InstructionTag,
+    sunset_at: i64,
+) -> Result<()> {
+    let slot = validate_sunset(instruction, sunset_at)?;
+    ctx.accounts.config.instruction_sunsets[slot] = sunset_at;
+    emit_config_snapshot(&ctx.accounts.config, InstructionTag::SetInstructionSunset)?;
+
+    msg!("{:?} sunset set to {}", instruction, sunset_at);
+    Ok(())
+}
+
+/// Set the multiple of trailing volume that trips the circuit breaker
+pub fn handle_set_circuit_breaker_multiple(
+    ctx: Context<AdminConfig>,
+    multiple_bps: u64,
+) -> Result<()> {
+    // Anything at or below 1x would trip on ordinary activity
+    let multiple = Bps(multiple_bps);
+    require!(
+        multiple == Bps(0) || multiple > Bps::ONE_HUNDRED_PERCENT,
+        CustomError::InvalidCircuitBreakerMultiple
+    );
+    ctx.accounts.config.circuit_breaker_multiple_bps = multiple_bps;
+    emit_config_snapshot(
+        &ctx.accounts.config,
+        InstructionTag::SetCircuitBreakerMultiple,
+    )?;
+
+    msg!("Circuit breaker multiple set to {} bps", multiple_bps);
+    Ok(())
+}
+
+/// Set the oracle price deviation guard limits
+pub fn handle_set_price_deviation_limits(
+    ctx: Context<AdminConfig>,
+    max_deviation_bps: u64,
+    window_secs: i64,
+) -> Result<()> {
+    require!(window_secs >= 0, CustomError::InvalidDeviationWindow);
+
+    let config = &mut ctx.accounts.config;
+    config.max_price_deviation_bps = max_deviation_bps;
+    config.price_deviation_window_secs = window_secs;
+    emit_config_snapshot(config, InstructionTag::SetPriceDeviationLimits)?;
+
+    msg!(
+        "Price deviation limits: {} bps within {}s",
+        max_deviation_bps,
+        window_secs
+    );
+    Ok(())
+}
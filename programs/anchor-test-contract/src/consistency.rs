@@ -0,0 +1,151 @@
+/*
+ * ============================================================================
+ * LINKED PDA CONSISTENCY CHECKS
+ * ============================================================================
+ *
+ * `check_user_consistency` validates the cross-references between a user
+ * account and the PDAs hanging off it (joint record, recent-operations
+ * buffer, attachment pages). Unlike `assert_invariants` it never reverts on
+ * a finding: every check is reported as a bit in a structured event, so
+ * monitors can sweep users without a failing transaction per problem.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    AttachmentPage, CustomError, GlobalConfig, InstructionTag, JointAccount, RecentOperations,
+    RemainingAccountsValidator, UserAccount, ATTACHMENTS_PER_PAGE, RECENT_OPERATIONS_CAPACITY,
+};
+
+/// Bits set in `UserConsistencyReportEvent::failed`
+pub mod consistency_checks {
+    pub const NAME_LENGTH: u64 = 1 << 0; // Name longer than the allocated space
+    pub const VACATION_WINDOW: u64 = 1 << 1; // Window ends before it starts
+    pub const JOINT_FLAG: u64 = 1 << 2; // `is_joint` disagrees with the joint record
+    pub const JOINT_BACKREF: u64 = 1 << 3; // Joint record points elsewhere
+    pub const RECENT_OPS_BACKREF: u64 = 1 << 4; // Buffer points at another user
+    pub const RECENT_OPS_HEAD: u64 = 1 << 5; // Ring head out of range
+    pub const ATTACHMENT_BACKREF: u64 = 1 << 6; // Page points at another user or index
+    pub const ATTACHMENT_OVERFLOW: u64 = 1 << 7; // Page holds more than its cap
+}
+
+/// Context for the consistency check; attachment pages go in `remaining_accounts`
+#[derive(Accounts)]
+pub struct CheckUserConsistency<'info> {
+    /// User account being checked
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Joint record address; may be uninitialized
+    /// CHECK: address is the user's joint PDA; contents are decoded in the handler
+    #[account(seeds = [b"joint", user.key().as_ref()], bump)]
+    pub joint: UncheckedAccount<'info>,
+
+    /// Recent-operations buffer address; may be uninitialized
+    /// CHECK: address is the user's buffer PDA; contents are decoded in the handler
+    #[account(seeds = [b"recent_ops", user.key().as_ref()], bump)]
+    pub recent_ops: UncheckedAccount<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Structured result of a consistency check
+#[event]
+pub struct UserConsistencyReportEvent {
+    pub user: Pubkey,        // User account checked
+    pub checked: u64,        // Bits of `consistency_checks` that were evaluated
+    pub failed: u64,         // Bits of `consistency_checks` that failed
+    pub timestamp: i64,      // When the check ran
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Decode a program account of type `T` if `info` holds one
+fn load_optional<T: AccountDeserialize>(info: &AccountInfo) -> Result<Option<T>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(None); // Never created (or closed)
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Some(T::try_deserialize(&mut &data[..])?)) // Checks the discriminator
+}
+
+/// Check a user's linked PDAs and emit a report of every failed check
+pub fn handle_check_user_consistency<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CheckUserConsistency<'info>>,
+) -> Result<()> {
+    use consistency_checks::*;
+
+    let user = &ctx.accounts.user;
+    let user_key = user.key();
+    let mut checked = NAME_LENGTH | VACATION_WINDOW | JOINT_FLAG;
+    let mut failed = 0u64;
+
+    if user.name.len() > 32 {
+        failed |= NAME_LENGTH;
+    }
+    if user.vacation_start > user.vacation_end {
+        failed |= VACATION_WINDOW;
+    }
+
+    match load_optional::<JointAccount>(&ctx.accounts.joint)? {
+        Some(joint) => {
+            checked |= JOINT_BACKREF;
+            if !user.is_joint {
+                failed |= JOINT_FLAG;
+            }
+            if joint.user != user_key || joint.primary != user.authority {
+                failed |= JOINT_BACKREF;
+            }
+        }
+        None if user.is_joint => failed |= JOINT_FLAG,
+        None => {}
+    }
+
+    if let Some(recent_ops) = load_optional::<RecentOperations>(&ctx.accounts.recent_ops)? {
+        checked |= RECENT_OPS_BACKREF | RECENT_OPS_HEAD;
+        if recent_ops.user != user_key {
+            failed |= RECENT_OPS_BACKREF;
+        }
+        if recent_ops.head as usize >= RECENT_OPERATIONS_CAPACITY {
+            failed |= RECENT_OPS_HEAD;
+        }
+    }
+
+    let mut pages = RemainingAccountsValidator::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        let page = pages.load(info, |page: &AttachmentPage| {
+            vec![
+                b"attachments".to_vec(),
+                page.user.to_bytes().to_vec(),
+                page.page.to_le_bytes().to_vec(),
+            ]
+        })?;
+        checked |= ATTACHMENT_BACKREF | ATTACHMENT_OVERFLOW;
+        if page.user != user_key {
+            failed |= ATTACHMENT_BACKREF; // A genuine page, but of another user
+        }
+        if page.entries.len() > ATTACHMENTS_PER_PAGE {
+            failed |= ATTACHMENT_OVERFLOW;
+        }
+    }
+
+    emit!(UserConsistencyReportEvent {
+        user: user_key,
+        checked,
+        failed,
+        timestamp: Clock::get()?.unix_timestamp,
+        tag: InstructionTag::CheckUserConsistency,
+    });
+
+    msg!(
+        "Consistency check for {}: failed mask {:#x}",
+        user_key,
+        failed
+    );
+    Ok(())
+}
@@ -0,0 +1,240 @@
+/*
+ * ============================================================================
+ * PROTOCOL CONSTANTS AND UNITS
+ * ============================================================================
+ *
+ * Every tunable number the program relies on lives here with its unit and
+ * rationale. Rates, durations and lamport amounts are wrapped in newtypes
+ * (`Bps`, `Seconds`, `LamportAmount`) so a percentage cannot be passed where
+ * basis points are expected, or a slot count where seconds are. Handlers
+ * wrap raw instruction arguments at the boundary and work with the types.
+ *
+ * Account layouts keep plain integers: each newtype serializes exactly like
+ * the integer it wraps, so adopting one never changes on-chain data.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::math;
+
+/*
+ * Units
+ */
+
+/// A rate in basis points (1 bps = 0.01%, 10_000 bps = 100%)
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default,
+)]
+pub struct Bps(pub u64);
+
+impl Bps {
+    /// The whole amount
+    pub const ONE_HUNDRED_PERCENT: Bps = Bps(10_000);
+
+    /// `percent`% expressed in basis points
+    pub const fn from_percent(percent: u64) -> Self {
+        Self(percent * 100)
+    }
+
+    /// Raw basis points
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Whether the rate is a share of the whole (0% to 100%)
+    pub const fn is_fraction(self) -> bool {
+        self.0 <= Self::ONE_HUNDRED_PERCENT.0
+    }
+
+    /// This rate of `amount`, rounded as requested
+    pub fn of(self, amount: u64, rounding: Rounding) -> Result<u64> {
+        math::bps_of(amount, self.0, rounding)
+    }
+}
+
+/// A duration in seconds of unix time
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default,
+)]
+pub struct Seconds(pub i64);
+
+impl Seconds {
+    pub const MINUTE: Seconds = Seconds(60);
+    pub const HOUR: Seconds = Seconds(60 * 60);
+    pub const DAY: Seconds = Seconds(24 * 60 * 60);
+    pub const YEAR: Seconds = Seconds(365 * 24 * 60 * 60);
+
+    /// Raw seconds
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+
+    /// Timestamp this long after `timestamp`, saturating at the end of time
+    pub const fn after(self, timestamp: i64) -> i64 {
+        timestamp.saturating_add(self.0)
+    }
+}
+
+/// An amount of lamports (native SOL units)
+/// Not `Lamports`, which Anchor's prelude already uses for a trait
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default,
+)]
+pub struct LamportAmount(pub u64);
+
+impl LamportAmount {
+    /// Raw lamports
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+/*
+ * Rates
+ */
+
+/// Basis-point denominator (100% = 10_000 bps)
+pub const BPS_DENOMINATOR: u64 = Bps::ONE_HUNDRED_PERCENT.get();
+
+/// Fee rate applied when a namespace is first created
+pub const DEFAULT_FEE_RATE: Bps = Bps::from_percent(1);
+
+/// `DEFAULT_FEE_RATE` in raw basis points, as stored in `GlobalConfig`
+pub const DEFAULT_FEE_RATE_BPS: u64 = DEFAULT_FEE_RATE.get();
+
+/*
+ * Sizes and limits
+ */
+
+/// Longest user display name in bytes; `UserAccount::LEN` reserves this much
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Longest namespace display name in bytes
+pub const MAX_NAMESPACE_NAME_LEN: usize = 32;
+
+/// Longest profile avatar URI in bytes
+pub const MAX_AVATAR_URI_LEN: usize = 128;
+
+/// Longest free-form profile metadata in bytes
+pub const MAX_PROFILE_METADATA_LEN: usize = 256;
+
+/// URI schemes a profile avatar may use
+pub const ALLOWED_URI_SCHEMES: &[&str] = &["https://", "ipfs://", "ar://"];
+
+/// Longest preimage accepted by `reveal`; bounds the hashing cost
+pub const MAX_PREIMAGE_LEN: usize = 64;
+
+/// Entries held by one attachment page
+pub const ATTACHMENTS_PER_PAGE: usize = 8;
+
+/// Pages a single user may create
+pub const MAX_ATTACHMENT_PAGES: u16 = 4;
+
+/// Number of recent idempotency keys remembered per user
+pub const RECENT_OPERATIONS_CAPACITY: usize = 8;
+
+/// Number of recent operations kept in a user's activity log
+pub const ACTIVITY_LOG_CAPACITY: usize = 32;
+
+/// Lots held by one tax lot page
+pub const TAX_LOTS_PER_PAGE: usize = 16;
+
+/// Decimals used by internal accounting for every mint
+pub const INTERNAL_DECIMALS: u8 = 9;
+
+/// External programs a namespace's CPI registry can allow at once
+pub const MAX_CPI_PROGRAMS: usize = 16;
+
+/// Receivers one `batch_transfer` may pay
+pub const MAX_BATCH_RECEIVERS: usize = 16;
+
+/// Intermediate hops one `routed_transfer` may pass through
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+/// Guardians a user account may name
+pub const MAX_GUARDIANS: usize = 3;
+
+/// Bytes of bytecode a transfer policy may hold
+pub const MAX_POLICY_CODE_LEN: usize = 64;
+
+/// Values a transfer policy's evaluation stack may hold
+pub const MAX_POLICY_STACK: usize = 8;
+
+/// Receiver authorities a transfer policy's allowlist may hold
+pub const MAX_POLICY_ALLOWLIST: usize = 8;
+
+/// Sunset slots in `GlobalConfig`, one per `INSTRUCTION_VERSIONS` entry
+pub const MAX_VERSIONED_INSTRUCTIONS: usize = 8;
+
+/// Longest `transfer_tokens_v2` memo in bytes
+pub const MAX_MEMO_LEN: usize = 64;
+
+/// Longest capability token service label in bytes
+pub const MAX_SERVICE_NAME_LEN: usize = 32;
+
+/// Layout version of newly created user accounts, bumped on every field addition
+pub const USER_ACCOUNT_VERSION: u8 = 6;
+
+/// Outstanding invoices at which a recurring template cancels itself
+pub const MAX_UNPAID_INVOICES: u64 = 3;
+
+/// Withdrawals of at least this many internal units (1,000 whole tokens)
+/// must carry an operation digest
+pub const LARGE_WITHDRAWAL_THRESHOLD: u64 = 1_000 * 10u64.pow(INTERNAL_DECIMALS as u32);
+
+/// Transfers of at least this many internal units (10,000 whole tokens)
+/// are flagged in compliance reports
+pub const REPORTABLE_TRANSFER_THRESHOLD: u64 = 10_000 * 10u64.pow(INTERNAL_DECIMALS as u32);
+
+/*
+ * Windows
+ */
+
+/// How long a recorded idempotency key is considered a duplicate
+pub const IDEMPOTENCY_WINDOW: Seconds = Seconds::DAY;
+
+/// Shortest interval a recurring invoice template may bill at
+pub const MIN_INVOICE_INTERVAL: Seconds = Seconds::HOUR;
+
+/// How long an issued invoice stays payable before `expire` may retire it
+pub const INVOICE_PAYMENT_WINDOW: Seconds = Seconds(30 * Seconds::DAY.get());
+
+/// How long an unlocked escrow waits for its claim before `expire` refunds it
+pub const ESCROW_CLAIM_WINDOW: Seconds = Seconds(90 * Seconds::DAY.get());
+
+/// Length of the window a daily spending limit applies to
+pub const DAILY_LIMIT_WINDOW: Seconds = Seconds::DAY;
+
+/// Wait before a raised or removed daily spending limit takes effect
+pub const DAILY_LIMIT_INCREASE_DELAY: Seconds = Seconds::DAY;
+
+/// Default wait between `deactivate_user` and `reactivate_user`, so a
+/// compromised account cannot be re-enabled straight away
+pub const DEFAULT_REACTIVATION_COOLDOWN: Seconds = Seconds::DAY;
+
+/// Default slack on deadline comparisons, a few slots' worth of cluster
+/// clock drift
+pub const DEFAULT_CLOCK_TOLERANCE: Seconds = Seconds(30);
+
+/// Most slack a namespace may put on its deadlines
+pub const MAX_CLOCK_TOLERANCE: Seconds = Seconds(10 * 60);
+
+/// Length of the period an account statement summarizes
+pub const STATEMENT_PERIOD: Seconds = Seconds(30 * Seconds::DAY.get());
+
+/// How long a watchtower alert freezes outbound transfers
+pub const WATCHTOWER_ALERT_DURATION: Seconds = Seconds(6 * Seconds::HOUR.get());
+
+/// Number of epochs the circuit breaker's trailing average is smoothed over
+pub const TRAILING_EPOCHS: u64 = 7;
+
+/*
+ * Localnet demo
+ */
+
+/// Upper bound on demo users per bootstrap transaction
+pub const MAX_DEMO_USERS: u8 = 8;
+
+/// Balance of the first demo user; user `i` holds `(i + 1)` times this
+pub const DEMO_BALANCE_UNIT: u64 = 1_000;
@@ -0,0 +1,172 @@
+/*
+ * ============================================================================
+ * CPI TARGET REGISTRY
+ * ============================================================================
+ *
+ * Instructions that CPI into an external program (token programs, metadata,
+ * AMMs, compression) receive that program as an account, so a caller could
+ * hand in a look-alike program of their own. Each namespace keeps a
+ * `CpiProgramRegistry` PDA next to its config listing the program ids its
+ * admin trusts, and every such CPI site checks its target with
+ * `assert_allowed_program` before invoking it.
+ *
+ * Native programs with a fixed address (system, address lookup table) are
+ * pinned with `address = ..` constraints instead and need no entry.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, GlobalConfig, InstructionTag, MAX_CPI_PROGRAMS};
+
+/// Program ids a namespace allows as CPI targets
+#[account]
+pub struct CpiProgramRegistry {
+    pub config: Pubkey,        // Namespace config this belongs to (32 bytes)
+    pub programs: Vec<Pubkey>, // Allowed program ids (4 + 32 * MAX_CPI_PROGRAMS bytes)
+    pub bump: u8,              // PDA bump seed (1 byte)
+}
+
+impl CpiProgramRegistry {
+    /// Space required for a full registry including the discriminator
+    pub const LEN: usize = 8 +      // Anchor discriminator
+        32 +                         // config: Pubkey
+        4 + 32 * MAX_CPI_PROGRAMS +  // programs: Vec<Pubkey>
+        1; // bump: u8
+
+    /// Whether `program_id` may be invoked
+    pub fn is_allowed(&self, program_id: &Pubkey) -> bool {
+        self.programs.contains(program_id)
+    }
+}
+
+/// Check `program` is an executable the namespace allows as a CPI target
+pub fn assert_allowed_program(registry: &CpiProgramRegistry, program: &AccountInfo) -> Result<()> {
+    require!(
+        program.executable && registry.is_allowed(program.key),
+        CustomError::ProgramNotAllowed
+    );
+    Ok(())
+}
+
+/// Context for creating a namespace's CPI registry
+#[derive(Accounts)]
+pub struct CreateCpiRegistry<'info> {
+    /// Registry being created, empty
+    #[account(
+        init,
+        payer = admin,
+        space = CpiProgramRegistry::LEN,
+        seeds = [b"cpi_registry", config.key().as_ref()], // One per namespace
+        bump
+    )]
+    pub registry: Account<'info, CpiProgramRegistry>,
+
+    /// Namespace config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for allowing or revoking a CPI target
+#[derive(Accounts)]
+pub struct UpdateCpiRegistry<'info> {
+    /// Registry being edited
+    #[account(
+        mut,
+        seeds = [b"cpi_registry", config.key().as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, CpiProgramRegistry>,
+
+    /// Namespace config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace admin
+    pub admin: Signer<'info>,
+}
+
+/// Event emitted when a program is allowed or revoked as a CPI target
+#[event]
+pub struct CpiProgramUpdatedEvent {
+    pub config: Pubkey,      // Namespace config
+    pub program: Pubkey,     // Program id
+    pub allowed: bool,       // Whether it is now allowed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create an empty CPI registry for the admin's namespace
+pub fn handle_create_cpi_registry(ctx: Context<CreateCpiRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    registry.config = ctx.accounts.config.key();
+    registry.programs = Vec::new();
+    registry.bump = ctx.bumps.registry;
+
+    msg!(
+        "CPI registry created for namespace {}",
+        ctx.accounts.config.namespace
+    );
+    Ok(())
+}
+
+/// Allow `program_id` as a CPI target; allowing it twice is a no-op
+pub fn handle_allow_cpi_program(ctx: Context<UpdateCpiRegistry>, program_id: Pubkey) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    if registry.is_allowed(&program_id) {
+        msg!("Program {} is already allowed", program_id);
+        return Ok(());
+    }
+    require!(
+        registry.programs.len() < MAX_CPI_PROGRAMS,
+        CustomError::CpiRegistryFull
+    );
+    registry.programs.push(program_id);
+
+    emit!(CpiProgramUpdatedEvent {
+        config: registry.config,
+        program: program_id,
+        allowed: true,
+        tag: InstructionTag::AllowCpiProgram,
+    });
+
+    msg!("Program {} allowed as CPI target", program_id);
+    Ok(())
+}
+
+/// Stop allowing `program_id` as a CPI target
+pub fn handle_revoke_cpi_program(
+    ctx: Context<UpdateCpiRegistry>,
+    program_id: Pubkey,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    let index = registry
+        .programs
+        .iter()
+        .position(|p| *p == program_id)
+        .ok_or(CustomError::ProgramNotAllowed)?;
+    registry.programs.swap_remove(index);
+
+    emit!(CpiProgramUpdatedEvent {
+        config: registry.config,
+        program: program_id,
+        allowed: false,
+        tag: InstructionTag::RevokeCpiProgram,
+    });
+
+    msg!("Program {} revoked as CPI target", program_id);
+    Ok(())
+}
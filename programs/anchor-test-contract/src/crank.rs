@@ -0,0 +1,124 @@
+/*
+ * ============================================================================
+ * CRANK CONTENTION
+ * ============================================================================
+ *
+ * Permissionless cranks race: several keepers may see the same invoice
+ * template come due and submit `issue_invoice` in the same slot. Queue
+ * items therefore carry a `version`, bumped by every write, and a crank
+ * names the version it read. The first crank to land wins; every other one
+ * fails on an account constraint with `StaleVersion`, before any account is
+ * created or any rent moves, so losing a race costs only the signature fee.
+ *
+ * A failed transaction cannot write state, so lost races are counted off
+ * chain from `StaleVersion` failures. The namespace's `CrankMetrics`
+ * records the winning side: how many cranks landed and how long after the
+ * item came due, which shows whether keepers keep up. Keepers pass the
+ * metrics account optionally; it is a shared writable account, and leaving
+ * it out keeps cranks on different items parallel.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::math;
+use crate::{CustomError, GlobalConfig, InstructionTag};
+
+/// A namespace's crank counters
+#[account]
+pub struct CrankMetrics {
+    pub config: Pubkey,        // Namespace config measured (32 bytes)
+    pub cranks: u64,           // Cranks that won their item (8 bytes)
+    pub total_delay_secs: u64, // Summed time from due to landed (8 bytes)
+    pub max_delay_secs: u64,   // Longest time from due to landed (8 bytes)
+    pub last_crank_at: i64,    // When the last winning crank landed (8 bytes)
+    pub bump: u8,              // PDA bump seed (1 byte)
+}
+
+impl CrankMetrics {
+    /// Space required for the account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // config: Pubkey
+        8 +                      // cranks: u64
+        8 +                      // total_delay_secs: u64
+        8 +                      // max_delay_secs: u64
+        8 +                      // last_crank_at: i64
+        1; // bump: u8
+
+    /// Count a winning crank on an item due at `due`, landed at `now`
+    pub fn record(&mut self, due: i64, now: i64) -> Result<()> {
+        let delay = now.saturating_sub(due).max(0) as u64;
+        self.cranks = math::add(self.cranks, 1)?;
+        self.total_delay_secs = math::add(self.total_delay_secs, delay)?;
+        self.max_delay_secs = self.max_delay_secs.max(delay);
+        self.last_crank_at = now;
+        Ok(())
+    }
+}
+
+/// Next version of a queue item after a write
+pub fn next_version(version: u64) -> Result<u64> {
+    math::add(version, 1)
+}
+
+/// Context for creating the namespace's crank metrics
+#[derive(Accounts)]
+pub struct InitCrankMetrics<'info> {
+    /// Account being created
+    #[account(
+        init,
+        payer = admin,
+        space = CrankMetrics::LEN,
+        seeds = [b"crank_metrics", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub crank_metrics: Account<'info, CrankMetrics>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Event emitted when a namespace starts measuring its cranks
+#[event]
+pub struct CrankMetricsInitializedEvent {
+    pub config: Pubkey,      // Namespace config measured
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Count a winning crank when the metrics account is passed
+pub fn record_crank(metrics: Option<&mut Account<CrankMetrics>>, due: i64, now: i64) -> Result<()> {
+    match metrics {
+        Some(metrics) => metrics.record(due, now),
+        None => Ok(()),
+    }
+}
+
+/// Create the crank metrics with every counter at zero
+pub fn handle_init_crank_metrics(ctx: Context<InitCrankMetrics>) -> Result<()> {
+    let metrics = &mut ctx.accounts.crank_metrics;
+    metrics.config = ctx.accounts.config.key();
+    metrics.cranks = 0;
+    metrics.total_delay_secs = 0;
+    metrics.max_delay_secs = 0;
+    metrics.last_crank_at = 0;
+    metrics.bump = ctx.bumps.crank_metrics;
+
+    emit!(CrankMetricsInitializedEvent {
+        config: metrics.config,
+        tag: InstructionTag::InitCrankMetrics,
+    });
+
+    msg!("Crank metrics initialized");
+    Ok(())
+}
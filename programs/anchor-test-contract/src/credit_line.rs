@@ -0,0 +1,532 @@
+/*
+ * ============================================================================
+ * CREDIT LINES
+ * ============================================================================
+ *
+ * An organization's user account (the lender) can extend a credit line to
+ * another (the borrower) with `open_credit_line(limit, rate)`. The line is a
+ * `CreditLine` PDA at `[b"credit_line", lender, borrower]`.
+ *
+ * The borrower draws against it with `draw_credit`, which moves balance from
+ * the lender to the borrower while the drawn principal stays within `limit`.
+ * Drawn principal accrues simple interest at the annual `rate`, applied
+ * lazily whenever the line is drawn on or repaid. `repay_credit` pays the
+ * accrued interest first, then principal. The lender can freeze the line at
+ * any time; a frozen line accepts repayments but no new draws.
+ *
+ * A line commits the lender's balance, so opening one passes the lender's
+ * guardian approval on the whole limit. Each draw is an outflow of the
+ * lender's and each repayment one of the borrower's: the paying account
+ * must not be frozen, needs its guardians above their limit and counts the
+ * amount against its daily limit. The paying account's guardians sign in
+ * the remaining accounts.
+ *
+ * A borrower owing anything on a line has an open obligation: the draw that
+ * takes the debt off zero opens it and the repayment that clears the debt
+ * settles it. Repayments go through while the borrower is deactivating.
+ *
+ * Every draw and repayment emits a `CreditLineUtilizationEvent` with the
+ * updated debt and utilization.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    instruction_flags, load_policy, log_transfer, require_instruction_enabled, settle_obligation,
+    signer_keys, AccountMode, Bps, CustomError, EventOrder, GlobalConfig, InstructionTag, Ledger,
+    LedgerAccount, PolicyInput, Seconds, TransferPolicy, UserAccount, BPS_DENOMINATOR,
+};
+
+/// Credit extended by one user account to another
+#[account]
+pub struct CreditLine {
+    pub lender: Pubkey,   // Lender's user account (32 bytes)
+    pub borrower: Pubkey, // Borrower's user account (32 bytes)
+    pub limit: u64,       // Most principal drawn at once (8 bytes)
+    pub rate: Bps,        // Annual simple interest on drawn principal (8 bytes)
+    pub drawn: u64,       // Outstanding principal (8 bytes)
+    pub interest: u64,    // Accrued, unpaid interest (8 bytes)
+    pub accrued_at: i64,  // When interest was last accrued (8 bytes)
+    pub frozen: bool,     // Lender has stopped new draws (1 byte)
+    pub bump: u8,         // PDA bump seed (1 byte)
+}
+
+impl CreditLine {
+    /// Space required for the credit line including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // lender: Pubkey
+        32 +                     // borrower: Pubkey
+        8 +                      // limit: u64
+        8 +                      // rate: Bps
+        8 +                      // drawn: u64
+        8 +                      // interest: u64
+        8 +                      // accrued_at: i64
+        1 +                      // frozen: bool
+        1; // bump: u8
+
+    /// Principal and interest owed
+    pub fn outstanding(&self) -> Result<u64> {
+        math::add(self.drawn, self.interest)
+    }
+
+    /// Share of the limit currently drawn
+    pub fn utilization(&self) -> Result<Bps> {
+        math::mul_div(self.drawn, BPS_DENOMINATOR, self.limit, Rounding::Down).map(Bps)
+    }
+
+    /// Accrue interest on drawn principal up to `now`
+    pub fn accrue(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.accrued_at).max(0) as u64;
+        let rate_time = self
+            .rate
+            .get()
+            .checked_mul(elapsed)
+            .ok_or(CustomError::MathOverflow)?;
+        let interest = math::mul_div(
+            self.drawn,
+            rate_time,
+            BPS_DENOMINATOR * Seconds::YEAR.get() as u64,
+            Rounding::Up, // Rounds in the lender's favour
+        )?;
+        self.interest = math::add(self.interest, interest)?;
+        self.accrued_at = now;
+        Ok(())
+    }
+
+    /// Draw `amount` of principal at `now`
+    pub fn draw(&mut self, amount: u64, now: i64) -> Result<()> {
+        require!(!self.frozen, CustomError::CreditLineFrozen);
+        self.accrue(now)?;
+        let drawn = math::add(self.drawn, amount)?;
+        require!(drawn <= self.limit, CustomError::CreditLimitExceeded);
+        self.drawn = drawn;
+        Ok(())
+    }
+
+    /// Repay `amount` at `now`, interest first; returns `(interest, principal)` paid
+    pub fn repay(&mut self, amount: u64, now: i64) -> Result<(u64, u64)> {
+        self.accrue(now)?;
+        require!(
+            amount <= self.outstanding()?,
+            CustomError::RepaymentExceedsDebt
+        );
+        let interest = amount.min(self.interest);
+        let principal = amount - interest;
+        self.interest -= interest;
+        self.drawn -= principal; // Bounded by the outstanding check above
+        Ok((interest, principal))
+    }
+}
+
+/// Preconditions of `lender` committing `limit` to `borrower` at `now`
+pub fn open_guards<'a>(
+    lender: &'a UserAccount,
+    borrower: &'a UserAccount,
+    limit: u64,
+    signers: &'a [Pubkey],
+    now: i64,
+) -> [Guard<'a>; 4] {
+    [
+        Guard::NotJoint(lender), // Joint accounts move balance through joint_transfer
+        Guard::NotJoint(borrower),
+        Guard::NotFrozen(lender, now),
+        Guard::GuardianApproved(lender, limit, signers), // The whole limit may be drawn
+    ]
+}
+
+/// Preconditions of `lender` paying out a draw of `amount` to `borrower` at
+/// `now`, under the lender's `policy`
+pub fn draw_guards<'a>(
+    lender: &'a UserAccount,
+    borrower: &'a UserAccount,
+    amount: u64,
+    signers: &'a [Pubkey],
+    policy: Option<&'a TransferPolicy>,
+    now: i64,
+) -> [Guard<'a>; 7] {
+    let modes = (lender.mode, borrower.mode);
+    outflow_guards(lender, borrower, modes, amount, signers, policy, now)
+}
+
+/// Preconditions of `borrower` repaying `amount` to `lender` at `now`, under
+/// the borrower's `policy`
+pub fn repay_guards<'a>(
+    borrower: &'a UserAccount,
+    lender: &'a UserAccount,
+    amount: u64,
+    signers: &'a [Pubkey],
+    policy: Option<&'a TransferPolicy>,
+    now: i64,
+) -> [Guard<'a>; 7] {
+    // Repaying settles an open obligation, see `AccountMode::for_settlement`
+    let modes = (borrower.mode.for_settlement(), lender.mode.for_settlement());
+    outflow_guards(borrower, lender, modes, amount, signers, policy, now)
+}
+
+fn outflow_guards<'a>(
+    payer: &'a UserAccount,
+    payee: &UserAccount,
+    (payer_mode, payee_mode): (AccountMode, AccountMode),
+    amount: u64,
+    signers: &'a [Pubkey],
+    policy: Option<&'a TransferPolicy>,
+    now: i64,
+) -> [Guard<'a>; 7] {
+    let policy_input = PolicyInput {
+        amount,
+        receiver: payee.authority,
+        now,
+    };
+    [
+        Guard::Positive(amount),
+        Guard::Modes(payer_mode, payee_mode),
+        Guard::NotFrozen(payer, now),
+        Guard::Funded(payer, amount),
+        Guard::GuardianApproved(payer, amount, signers),
+        Guard::DailyLimit(payer, amount, now),
+        Guard::Policy(policy, policy_input),
+    ]
+}
+
+/// Context for a lender opening a credit line
+///
+/// Remaining accounts: the lender's guardian signers, when the limit needs them.
+#[derive(Accounts)]
+pub struct OpenCreditLine<'info> {
+    /// Credit line being opened
+    #[account(
+        init,
+        payer = authority,
+        space = CreditLine::LEN,
+        seeds = [b"credit_line", lender.key().as_ref(), borrower.key().as_ref()],
+        bump
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Lender's user account
+    #[account(
+        has_one = authority,
+        constraint = lender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub lender: Account<'info, UserAccount>,
+
+    /// Borrower's user account
+    #[account(
+        constraint = borrower.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), borrower.authority.as_ref()],
+        bump
+    )]
+    pub borrower: Account<'info, UserAccount>,
+
+    /// Lender authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the borrower drawing on or repaying a credit line
+///
+/// Remaining accounts: the paying account's guardian signers, when needed.
+#[derive(Accounts)]
+pub struct UseCreditLine<'info> {
+    /// Credit line drawn on or repaid
+    #[account(
+        mut,
+        has_one = lender,
+        has_one = borrower,
+        seeds = [b"credit_line", lender.key().as_ref(), borrower.key().as_ref()],
+        bump = credit_line.bump
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Lender's user account
+    #[account(
+        mut,
+        constraint = lender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), lender.authority.as_ref()],
+        bump
+    )]
+    pub lender: Account<'info, UserAccount>,
+
+    /// Borrower's user account
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = borrower.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub borrower: Account<'info, UserAccount>,
+
+    /// Borrower authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Double-entry ledger receiving the transfer entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Lender's activity log address, appended to when the lender keeps one
+    /// CHECK: address is the lender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", lender.key().as_ref()], bump)]
+    pub lender_activity: UncheckedAccount<'info>,
+
+    /// Borrower's activity log address, appended to when the borrower keeps one
+    /// CHECK: address is the borrower's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", borrower.key().as_ref()], bump)]
+    pub borrower_activity: UncheckedAccount<'info>,
+
+    /// Lender's transfer policy address, enforced on draws when it holds a policy
+    /// CHECK: address is the lender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", lender.key().as_ref()], bump)]
+    pub lender_policy: UncheckedAccount<'info>,
+
+    /// Borrower's transfer policy address, enforced on repayments when it holds a policy
+    /// CHECK: address is the borrower's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", borrower.key().as_ref()], bump)]
+    pub borrower_policy: UncheckedAccount<'info>,
+}
+
+/// Context for the lender freezing or unfreezing a credit line
+#[derive(Accounts)]
+pub struct SetCreditLineFrozen<'info> {
+    /// Credit line being frozen or unfrozen
+    #[account(
+        mut,
+        has_one = lender,
+        seeds = [b"credit_line", lender.key().as_ref(), credit_line.borrower.as_ref()],
+        bump = credit_line.bump
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Lender's user account
+    #[account(
+        has_one = authority,
+        constraint = lender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub lender: Account<'info, UserAccount>,
+
+    /// Lender authority
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a credit line is opened
+#[event]
+pub struct CreditLineOpenedEvent {
+    pub credit_line: Pubkey, // New credit line
+    pub lender: Pubkey,      // Lender's user account
+    pub borrower: Pubkey,    // Borrower's user account
+    pub limit: u64,          // Most principal drawn at once
+    pub rate: Bps,           // Annual simple interest
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted after every draw and repayment
+#[event]
+pub struct CreditLineUtilizationEvent {
+    pub credit_line: Pubkey, // Credit line concerned
+    pub amount: u64,         // Drawn or repaid by this instruction
+    pub drawn: u64,          // Outstanding principal afterwards
+    pub interest: u64,       // Unpaid interest afterwards
+    pub utilization: Bps,    // Share of the limit drawn afterwards
+    pub order: EventOrder,   // Order of the draw or repayment
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when the lender freezes or unfreezes a credit line
+#[event]
+pub struct CreditLineFrozenEvent {
+    pub credit_line: Pubkey, // Credit line concerned
+    pub frozen: bool,        // Whether new draws are blocked
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Emit the credit line's state after moving `amount`
+fn emit_utilization(
+    credit_line: &Account<CreditLine>,
+    amount: u64,
+    order: EventOrder,
+    tag: InstructionTag,
+) -> Result<()> {
+    emit!(CreditLineUtilizationEvent {
+        credit_line: credit_line.key(),
+        amount,
+        drawn: credit_line.drawn,
+        interest: credit_line.interest,
+        utilization: credit_line.utilization()?,
+        order,
+        tag,
+    });
+    Ok(())
+}
+
+/// Open a credit line of `limit` at annual `rate` to the borrower
+pub fn handle_open_credit_line(ctx: Context<OpenCreditLine>, limit: u64, rate: Bps) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::OPEN_CREDIT_LINE)?;
+    require!(
+        limit > 0 && rate.is_fraction(),
+        CustomError::InvalidCreditLine
+    );
+    let lender = &ctx.accounts.lender;
+    let borrower = &ctx.accounts.borrower;
+    require_keys_neq!(lender.key(), borrower.key(), CustomError::InvalidCreditLine);
+    let now = Clock::get()?.unix_timestamp;
+    let signers = signer_keys(ctx.remaining_accounts);
+    guards::run(&open_guards(lender, borrower, limit, &signers, now))?;
+
+    let credit_line = &mut ctx.accounts.credit_line;
+    credit_line.lender = lender.key();
+    credit_line.borrower = borrower.key();
+    credit_line.limit = limit;
+    credit_line.rate = rate;
+    credit_line.drawn = 0;
+    credit_line.interest = 0;
+    credit_line.accrued_at = now;
+    credit_line.frozen = false;
+    credit_line.bump = ctx.bumps.credit_line;
+
+    emit!(CreditLineOpenedEvent {
+        credit_line: credit_line.key(),
+        lender: credit_line.lender,
+        borrower: credit_line.borrower,
+        limit,
+        rate,
+        tag: InstructionTag::OpenCreditLine,
+    });
+
+    msg!("Credit line of {} opened at {} bps", limit, rate.get());
+    Ok(())
+}
+
+/// Draw `amount` from the lender's balance against the credit line
+pub fn handle_draw_credit(ctx: Context<UseCreditLine>, amount: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::DRAW_CREDIT)?;
+    let now = Clock::get()?.unix_timestamp;
+    let signers = signer_keys(ctx.remaining_accounts);
+    let policy = load_policy(&ctx.accounts.lender_policy)?;
+    guards::run(&draw_guards(
+        &ctx.accounts.lender,
+        &ctx.accounts.borrower,
+        amount,
+        &signers,
+        policy.as_ref(),
+        now,
+    ))?;
+
+    let credit_line = &mut ctx.accounts.credit_line;
+    let was_clear = credit_line.outstanding()? == 0;
+    credit_line.draw(amount, now)?;
+
+    let lender = &mut ctx.accounts.lender;
+    let borrower = &mut ctx.accounts.borrower;
+    if was_clear {
+        borrower.open_obligation()?; // The borrower now owes on the line
+    }
+    lender.record_daily_spend(amount, now)?;
+    lender.balance = math::sub(lender.balance, amount)?;
+    borrower.balance = math::add(borrower.balance, amount)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Users, amount)?;
+    let order = log_transfer(
+        (&ctx.accounts.lender_activity, lender.key(), lender),
+        (&ctx.accounts.borrower_activity, borrower.key(), borrower),
+        amount,
+        amount,
+        InstructionTag::DrawCredit,
+        now,
+    )?;
+
+    emit_utilization(credit_line, amount, order, InstructionTag::DrawCredit)?;
+
+    msg!("Drew {} on credit line {}", amount, credit_line.key());
+    Ok(())
+}
+
+/// Repay `amount` of the credit line's interest, then principal
+pub fn handle_repay_credit(ctx: Context<UseCreditLine>, amount: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::REPAY_CREDIT)?;
+    let now = Clock::get()?.unix_timestamp;
+    let signers = signer_keys(ctx.remaining_accounts);
+    let policy = load_policy(&ctx.accounts.borrower_policy)?;
+    guards::run(&repay_guards(
+        &ctx.accounts.borrower,
+        &ctx.accounts.lender,
+        amount,
+        &signers,
+        policy.as_ref(),
+        now,
+    ))?;
+
+    let credit_line = &mut ctx.accounts.credit_line;
+    let (interest, principal) = credit_line.repay(amount, now)?;
+
+    let lender = &mut ctx.accounts.lender;
+    let borrower = &mut ctx.accounts.borrower;
+    borrower.record_daily_spend(amount, now)?;
+    borrower.balance = math::sub(borrower.balance, amount)?;
+    if credit_line.outstanding()? == 0 {
+        settle_obligation(borrower, InstructionTag::RepayCredit)?;
+    }
+    lender.balance = math::add(lender.balance, amount)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Users, amount)?;
+    let order = log_transfer(
+        (&ctx.accounts.borrower_activity, borrower.key(), borrower),
+        (&ctx.accounts.lender_activity, lender.key(), lender),
+        amount,
+        amount,
+        InstructionTag::RepayCredit,
+        now,
+    )?;
+
+    emit_utilization(credit_line, amount, order, InstructionTag::RepayCredit)?;
+
+    msg!(
+        "Repaid {} interest and {} principal on credit line {}",
+        interest,
+        principal,
+        credit_line.key()
+    );
+    Ok(())
+}
+
+/// Block or re-allow new draws on the credit line
+/// Not behind a kill switch, so lenders can always stop new draws
+pub fn handle_set_credit_line_frozen(
+    ctx: Context<SetCreditLineFrozen>,
+    frozen: bool,
+) -> Result<()> {
+    let credit_line = &mut ctx.accounts.credit_line;
+    credit_line.frozen = frozen;
+
+    emit!(CreditLineFrozenEvent {
+        credit_line: credit_line.key(),
+        frozen,
+        tag: InstructionTag::SetCreditLineFrozen,
+    });
+
+    msg!("Credit line {} frozen: {}", credit_line.key(), frozen);
+    Ok(())
+}
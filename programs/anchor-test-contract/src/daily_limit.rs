@@ -0,0 +1,147 @@
+/*
+ * ============================================================================
+ * DAILY SPENDING LIMITS
+ * ============================================================================
+ *
+ * A user account may cap how much leaves it per day. Outflows are counted in
+ * `spent_today` over a 24-hour window that starts with the first outflow
+ * after the previous window ended, and an outflow that would take the count
+ * past `daily_limit` fails with `DailyLimitExceeded`. A limit of zero means
+ * unlimited. Every outflow that checks guardians also counts towards the
+ * limit.
+ *
+ * Lowering the limit takes effect at once. Raising or removing it only takes
+ * effect after `DAILY_LIMIT_INCREASE_DELAY`, so a compromised key cannot lift
+ * the limit and drain the account straight away; the authority can cancel a
+ * pending increase by setting a lower limit.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::math;
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount, DAILY_LIMIT_INCREASE_DELAY, DAILY_LIMIT_WINDOW,
+};
+
+impl UserAccount {
+    /// Apply a pending limit increase once its delay has passed
+    pub fn apply_pending_daily_limit(&mut self, now: i64) {
+        if self.pending_limit_at != 0 && now >= self.pending_limit_at {
+            self.daily_limit = self.pending_daily_limit;
+            self.pending_daily_limit = 0;
+            self.pending_limit_at = 0;
+        }
+    }
+
+    /// The limit in force at `now` and what counts against it
+    fn daily_window(&self, now: i64) -> (u64, u64) {
+        let limit = if self.pending_limit_at != 0 && now >= self.pending_limit_at {
+            self.pending_daily_limit // Increase due, applied by the next spend
+        } else {
+            self.daily_limit
+        };
+        let spent = if now >= DAILY_LIMIT_WINDOW.after(self.window_start) {
+            0 // Window over, the next spend starts a new one
+        } else {
+            self.spent_today
+        };
+        (limit, spent)
+    }
+
+    /// What may still leave the account at `now`, or None when unlimited
+    pub fn daily_remaining(&self, now: i64) -> Option<u64> {
+        match self.daily_window(now) {
+            (0, _) => None,
+            (limit, spent) => Some(limit.saturating_sub(spent)), // Lowered below the spend
+        }
+    }
+
+    /// Check an outflow of `amount` at `now` fits the daily limit, without
+    /// counting it
+    pub fn require_daily_headroom(&self, amount: u64, now: i64) -> Result<()> {
+        let (limit, spent) = self.daily_window(now);
+        let spent = math::add(spent, amount)?;
+        require!(
+            limit == 0 || spent <= limit,
+            CustomError::DailyLimitExceeded
+        );
+        Ok(())
+    }
+
+    /// Count an outflow of `amount` at `now` against the daily limit
+    pub fn record_daily_spend(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.require_daily_headroom(amount, now)?;
+        self.apply_pending_daily_limit(now);
+        if now >= DAILY_LIMIT_WINDOW.after(self.window_start) {
+            self.window_start = now; // Previous window over, start a new one
+            self.spent_today = 0;
+        }
+        self.spent_today = math::add(self.spent_today, amount)?;
+        Ok(())
+    }
+
+    /// Set the daily limit at `now`; returns when the new limit takes effect
+    pub fn set_daily_limit(&mut self, limit: u64, now: i64) -> i64 {
+        self.apply_pending_daily_limit(now);
+        let increase = self.daily_limit != 0 && (limit == 0 || limit > self.daily_limit);
+        if increase {
+            self.pending_daily_limit = limit;
+            self.pending_limit_at = DAILY_LIMIT_INCREASE_DELAY.after(now);
+            self.pending_limit_at
+        } else {
+            self.daily_limit = limit; // Lowering, or setting a first limit
+            self.pending_daily_limit = 0;
+            self.pending_limit_at = 0;
+            now
+        }
+    }
+}
+
+/// Context for changing a user account's daily limit
+#[derive(Accounts)]
+pub struct SetDailyLimit<'info> {
+    /// Account whose limit changes
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a daily limit is set or an increase is scheduled
+#[event]
+pub struct DailyLimitChangedEvent {
+    pub user: Pubkey,        // User account concerned
+    pub daily_limit: u64,    // Requested limit, 0 = unlimited
+    pub effective_at: i64,   // When it takes effect
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Set the caller's daily limit, delaying increases
+pub fn handle_set_daily_limit(ctx: Context<SetDailyLimit>, daily_limit: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SET_DAILY_LIMIT)?;
+
+    let user = &mut ctx.accounts.user;
+    let effective_at = user.set_daily_limit(daily_limit, Clock::get()?.unix_timestamp);
+
+    emit!(DailyLimitChangedEvent {
+        user: user.key(),
+        daily_limit,
+        effective_at,
+        tag: InstructionTag::SetDailyLimit,
+    });
+
+    msg!("Daily limit {} effective at {}", daily_limit, effective_at);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+/*
+ * ============================================================================
+ * DEACTIVATION GRACE PERIOD
+ * ============================================================================
+ *
+ * Deactivation does not strand commitments made before it. Each user
+ * account counts its `open_obligations`, everything it still owes or has
+ * locked: escrows it created, invoices issued to it, netting debts it
+ * recorded, credit-line debt it drew and sealed bids it placed.
+ * `deactivate_user` locks an account without any open obligations outright;
+ * otherwise the
+ * account enters `Deactivating`, which refuses new transfers and new
+ * obligations but lets the open ones settle:
+ * - escrows are claimed, cancelled or expired
+ * - issued invoices are paid, rejected or expired
+ * - netting pairs settle and credit lines are repaid
+ * - bids are claimed once their auction settles
+ *
+ * Settling the last one moves the account on to `Locked` and emits a
+ * `DeactivationFinalizedEvent`. The reactivation cooldown runs from the
+ * original deactivation either way, and `reactivate_user` also calls off a
+ * deactivation still in its grace period.
+ *
+ * Obligations opened before layout version 4 were never counted, so
+ * accounts migrated from below it carry `uncounted_obligations` and settling
+ * one with the count at zero leaves it there. Any other account settling
+ * more obligations than it opened fails with `NoOpenObligation`.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{AccountMode, CustomError, InstructionTag, UserAccount};
+
+impl UserAccount {
+    /// Returns true while open obligations hold up a deactivation
+    pub fn is_deactivating(&self) -> bool {
+        self.mode == AccountMode::Deactivating
+    }
+
+    /// Deactivate at `now`: Locked at once with nothing open, Deactivating otherwise
+    pub fn begin_deactivation(&mut self, now: i64) {
+        self.mode = if self.open_obligations == 0 {
+            AccountMode::Locked
+        } else {
+            AccountMode::Deactivating
+        };
+        self.deactivated_at = now;
+    }
+
+    /// Count a newly opened obligation; none open while deactivating
+    pub fn open_obligation(&mut self) -> Result<()> {
+        require!(!self.is_deactivating(), CustomError::AccountDeactivating);
+        self.open_obligations = self
+            .open_obligations
+            .checked_add(1)
+            .ok_or(CustomError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Count a settled obligation; returns true if it finalized a deactivation
+    pub fn close_obligation(&mut self) -> Result<bool> {
+        self.open_obligations = match self.open_obligations.checked_sub(1) {
+            Some(open) => open,
+            None if self.uncounted_obligations => 0, // Opened before version 4
+            None => return err!(CustomError::NoOpenObligation),
+        };
+        if self.is_deactivating() && self.open_obligations == 0 {
+            self.mode = AccountMode::Locked;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// Event emitted when a deactivating account settles its last obligation
+#[event]
+pub struct DeactivationFinalizedEvent {
+    pub user: Pubkey,        // Now locked user account
+    pub deactivated_at: i64, // When deactivation began
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Close one of `user`'s obligations, finalizing its deactivation with the last
+pub fn settle_obligation(user: &mut Account<UserAccount>, tag: InstructionTag) -> Result<()> {
+    let key = user.key();
+    settle_obligation_of(user, key, tag)
+}
+
+/// Like `settle_obligation`, for a user account at `key` decoded by hand
+pub fn settle_obligation_of(
+    user: &mut UserAccount,
+    key: Pubkey,
+    tag: InstructionTag,
+) -> Result<()> {
+    if user.close_obligation()? {
+        emit!(DeactivationFinalizedEvent {
+            user: key,
+            deactivated_at: user.deactivated_at,
+            tag,
+        });
+        msg!("Deactivation finalized: {}", user.authority);
+    }
+    Ok(())
+}
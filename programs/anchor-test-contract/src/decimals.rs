@@ -0,0 +1,204 @@
+/*
+ * ============================================================================
+ * DECIMAL NORMALIZATION ACROSS MINTS
+ * ============================================================================
+ *
+ * Internal accounting represents every mint with `INTERNAL_DECIMALS` (9)
+ * decimals so balances, caps and cross-mint features compare like with like.
+ * Raw token amounts are only converted through the helpers in this module.
+ *
+ * The two scales have distinct types: `RawTokenUnits` for amounts in a
+ * mint's own decimals (what SPL transfers move) and `InternalUnits` for
+ * normalized amounts (what balances and caps hold). The conversions below
+ * are the only functions taking one and returning the other, so a raw
+ * amount cannot be credited as internal by accident, or vice versa.
+ *
+ * Rounding policy: conversions always favor the protocol.
+ * - Amounts credited to or paid out to users round down
+ * - Amounts owed to the protocol (fees, debts) round up
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, INTERNAL_DECIMALS};
+
+/// Direction to round when a conversion loses precision
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Down, // Toward zero, used for amounts the protocol pays out
+    Up,   // Away from zero, used for amounts owed to the protocol
+}
+
+/// An amount in a mint's own decimals, as moved by token transfers
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default,
+)]
+pub struct RawTokenUnits(u64);
+
+impl RawTokenUnits {
+    /// Wrap an amount read from a token account or instruction argument
+    pub const fn new(amount: u64) -> Self {
+        Self(amount)
+    }
+
+    /// Raw token amount, e.g. for a token transfer
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+/// An amount normalized to `INTERNAL_DECIMALS`, as held by internal accounting
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default,
+)]
+pub struct InternalUnits(u64);
+
+impl InternalUnits {
+    /// Wrap an amount read from internal accounting
+    pub const fn new(amount: u64) -> Self {
+        Self(amount)
+    }
+
+    /// Internal amount, e.g. for a balance or cap
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+/// 10^exp, or None if it does not fit in a u64
+const fn pow10(exp: u8) -> Option<u64> {
+    10u64.checked_pow(exp as u32)
+}
+
+/// Integer division with an explicit rounding direction
+const fn div_rounded(numerator: u64, denominator: u64, rounding: Rounding) -> Option<u64> {
+    let quotient = numerator / denominator;
+    let exact = quotient * denominator == numerator; // Cannot overflow: product <= numerator
+    if matches!(rounding, Rounding::Up) && !exact {
+        quotient.checked_add(1)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// Convert a raw token amount with `decimals` into internal units
+/// Returns None on overflow
+const fn normalize(raw: u64, decimals: u8, rounding: Rounding) -> Option<u64> {
+    if decimals <= INTERNAL_DECIMALS {
+        match pow10(INTERNAL_DECIMALS - decimals) {
+            Some(factor) => raw.checked_mul(factor), // Scaling up is exact
+            None => None,
+        }
+    } else {
+        match pow10(decimals - INTERNAL_DECIMALS) {
+            Some(factor) => div_rounded(raw, factor, rounding),
+            None => None,
+        }
+    }
+}
+
+/// Convert internal units into a raw token amount with `decimals`
+/// Returns None on overflow
+const fn denormalize(internal: u64, decimals: u8, rounding: Rounding) -> Option<u64> {
+    if decimals <= INTERNAL_DECIMALS {
+        match pow10(INTERNAL_DECIMALS - decimals) {
+            Some(factor) => div_rounded(internal, factor, rounding),
+            None => None,
+        }
+    } else {
+        match pow10(decimals - INTERNAL_DECIMALS) {
+            Some(factor) => internal.checked_mul(factor), // Scaling up is exact
+            None => None,
+        }
+    }
+}
+
+fn to_internal(raw: RawTokenUnits, decimals: u8, rounding: Rounding) -> Result<InternalUnits> {
+    normalize(raw.0, decimals, rounding)
+        .map(InternalUnits)
+        .ok_or_else(|| error!(CustomError::MathOverflow))
+}
+
+fn to_raw(internal: InternalUnits, decimals: u8, rounding: Rounding) -> Result<RawTokenUnits> {
+    denormalize(internal.0, decimals, rounding)
+        .map(RawTokenUnits)
+        .ok_or_else(|| error!(CustomError::MathOverflow))
+}
+
+/// Internal units to credit for a deposit of `raw` tokens (rounds down)
+pub fn deposit_to_internal(raw: RawTokenUnits, decimals: u8) -> Result<InternalUnits> {
+    to_internal(raw, decimals, Rounding::Down)
+}
+
+/// Raw tokens to pay out for a withdrawal of `internal` units (rounds down)
+pub fn withdrawal_to_raw(internal: InternalUnits, decimals: u8) -> Result<RawTokenUnits> {
+    to_raw(internal, decimals, Rounding::Down)
+}
+
+/// Internal units owed to the protocol for a raw fee amount (rounds up)
+pub fn owed_to_internal(raw: RawTokenUnits, decimals: u8) -> Result<InternalUnits> {
+    to_internal(raw, decimals, Rounding::Up)
+}
+
+/// Raw tokens to collect for `internal` units owed to the protocol (rounds up)
+pub fn owed_to_raw(internal: InternalUnits, decimals: u8) -> Result<RawTokenUnits> {
+    to_raw(internal, decimals, Rounding::Up)
+}
+
+/*
+ * Compile-time checks against 0, 6 and 9-decimal mints (plus one above 9).
+ * A change to the conversion or rounding rules fails the build here.
+ */
+
+// 0 decimals: every whole token is 10^9 internal units
+const _: () = assert!(matches!(
+    normalize(5, 0, Rounding::Down),
+    Some(5_000_000_000)
+));
+const _: () = assert!(matches!(
+    denormalize(1_500_000_000, 0, Rounding::Down),
+    Some(1)
+));
+const _: () = assert!(matches!(
+    denormalize(1_500_000_000, 0, Rounding::Up),
+    Some(2)
+));
+const _: () = assert!(normalize(u64::MAX, 0, Rounding::Down).is_none());
+
+// 6 decimals (USDC-style): internal units are 1000x raw units
+const _: () = assert!(matches!(
+    normalize(1_000_000, 6, Rounding::Down),
+    Some(1_000_000_000)
+));
+const _: () = assert!(matches!(
+    denormalize(1_234_567_891, 6, Rounding::Down),
+    Some(1_234_567)
+));
+const _: () = assert!(matches!(
+    denormalize(1_234_567_891, 6, Rounding::Up),
+    Some(1_234_568)
+));
+const _: () = assert!(matches!(
+    denormalize(1_234_567_000, 6, Rounding::Up),
+    Some(1_234_567)
+));
+
+// 9 decimals: conversion is the identity in both directions
+const _: () = assert!(matches!(
+    normalize(123_456_789, 9, Rounding::Up),
+    Some(123_456_789)
+));
+const _: () = assert!(matches!(
+    denormalize(u64::MAX, 9, Rounding::Down),
+    Some(u64::MAX)
+));
+
+// 12 decimals: precision beyond 9 places is rounded per policy
+const _: () = assert!(matches!(
+    normalize(1_000_000_000_999, 12, Rounding::Down),
+    Some(1_000_000_000)
+));
+const _: () = assert!(matches!(
+    normalize(1_000_000_000_999, 12, Rounding::Up),
+    Some(1_000_000_001)
+));
@@ -0,0 +1,88 @@
+/*
+ * ============================================================================
+ * OPERATION DIGESTS
+ * ============================================================================
+ *
+ * A hardware wallet signing a transaction sees opaque instruction data. For
+ * high-value operations the client therefore also passes the digest of a
+ * canonical, human-readable summary of what it is asking for, e.g.
+ *
+ *   Withdraw 1500.25 tokens of mint <mint> to <token account> (namespace 7)
+ *
+ * The signing device shows that line and hashes it; the program rebuilds the
+ * same line from the instruction's real accounts and arguments and rejects
+ * the instruction with `DigestMismatch` unless the hashes agree. Whatever
+ * the device displayed is exactly what executes.
+ *
+ * Summaries are plain ASCII built only from the functions below, so clients
+ * and signing devices can reproduce them byte for byte. Amounts are shown in
+ * whole tokens with the mint's decimals, trailing zeros trimmed.
+ *
+ * Digests are required for:
+ * - `withdraw_tokens` of at least `LARGE_WITHDRAWAL_THRESHOLD`
+ * - `set_compliance_authority`
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::CustomError;
+
+/// Domain prefix hashed before every summary, versioned with the format
+pub const DIGEST_DOMAIN: &[u8] = b"anchor-test-contract:operation-digest:v1\n";
+
+/// Digest of a canonical operation summary
+pub fn operation_digest(summary: &str) -> [u8; 32] {
+    hashv(&[DIGEST_DOMAIN, summary.as_bytes()]).to_bytes()
+}
+
+/// Check `provided` is the digest of `summary`
+pub fn require_digest(summary: &str, provided: Option<[u8; 32]>) -> Result<()> {
+    require!(
+        provided == Some(operation_digest(summary)),
+        CustomError::DigestMismatch
+    );
+    Ok(())
+}
+
+/// `raw` tokens of a mint with `decimals` decimals, written in whole tokens
+pub fn format_amount(raw: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", raw, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+/// Summary of a `withdraw_tokens` paying `raw_amount` to `destination`
+pub fn withdrawal_summary(
+    namespace: u64,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    raw_amount: u64,
+    decimals: u8,
+) -> String {
+    format!(
+        "Withdraw {} tokens of mint {} to {} (namespace {})",
+        format_amount(raw_amount, decimals),
+        mint,
+        destination,
+        namespace
+    )
+}
+
+/// Summary of handing the `role` authority from `previous` to `new`
+pub fn authority_change_summary(
+    namespace: u64,
+    role: &str,
+    previous: &Pubkey,
+    new: &Pubkey,
+) -> String {
+    format!(
+        "Change {} authority from {} to {} (namespace {})",
+        role, previous, new, namespace
+    )
+}
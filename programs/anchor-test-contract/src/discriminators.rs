@@ -0,0 +1,764 @@
+/*
+ * ============================================================================
+ * ACCOUNT AND EVENT DISCRIMINATORS
+ * ============================================================================
+ *
+ * The 8-byte Anchor discriminators of every account and event type, exported
+ * as plain constants so Rust indexers and Geyser plugins can filter account
+ * updates and decode logs without loading the IDL at runtime.
+ *
+ * Each constant is taken from the type itself, and the checks at the bottom
+ * of this file lock it against the expected bytes: renaming a type (which
+ * changes its discriminator and breaks existing data) fails the build.
+ */
+
+use anchor_lang::Discriminator;
+
+use crate::*;
+
+/// Discriminators prefixed to account data (`sha256("account:<Name>")[..8]`)
+pub mod accounts {
+    use super::*;
+
+    pub const ACTIVITY_LOG: [u8; 8] = <ActivityLog as Discriminator>::DISCRIMINATOR;
+    pub const ALLOWANCE: [u8; 8] = <Allowance as Discriminator>::DISCRIMINATOR;
+    pub const ATTACHMENT_PAGE: [u8; 8] = <AttachmentPage as Discriminator>::DISCRIMINATOR;
+    pub const ATTESTATION: [u8; 8] = <Attestation as Discriminator>::DISCRIMINATOR;
+    pub const AUCTION: [u8; 8] = <Auction as Discriminator>::DISCRIMINATOR;
+    pub const CAPABILITY_TOKEN: [u8; 8] = <CapabilityToken as Discriminator>::DISCRIMINATOR;
+    pub const CASHBACK_MERCHANT: [u8; 8] = <CashbackMerchant as Discriminator>::DISCRIMINATOR;
+    pub const CASHBACK_PROGRAM: [u8; 8] = <CashbackProgram as Discriminator>::DISCRIMINATOR;
+    pub const CHAOS_CONFIG: [u8; 8] = <ChaosConfig as Discriminator>::DISCRIMINATOR;
+    pub const COMMITMENT: [u8; 8] = <Commitment as Discriminator>::DISCRIMINATOR;
+    pub const CPI_PROGRAM_REGISTRY: [u8; 8] = <CpiProgramRegistry as Discriminator>::DISCRIMINATOR;
+    pub const CRANK_METRICS: [u8; 8] = <CrankMetrics as Discriminator>::DISCRIMINATOR;
+    pub const CREDIT_LINE: [u8; 8] = <CreditLine as Discriminator>::DISCRIMINATOR;
+    pub const EPOCH_STATS: [u8; 8] = <EpochStats as Discriminator>::DISCRIMINATOR;
+    pub const ESCROW: [u8; 8] = <Escrow as Discriminator>::DISCRIMINATOR;
+    pub const FEE_TREASURY: [u8; 8] = <FeeTreasury as Discriminator>::DISCRIMINATOR;
+    pub const GLOBAL_CONFIG: [u8; 8] = <GlobalConfig as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE: [u8; 8] = <Invoice as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_TEMPLATE: [u8; 8] = <InvoiceTemplate as Discriminator>::DISCRIMINATOR;
+    pub const ISSUER_RECORD: [u8; 8] = <IssuerRecord as Discriminator>::DISCRIMINATOR;
+    pub const JOINT_ACCOUNT: [u8; 8] = <JointAccount as Discriminator>::DISCRIMINATOR;
+    pub const LEDGER: [u8; 8] = <Ledger as Discriminator>::DISCRIMINATOR;
+    pub const MANAGER_GRANT: [u8; 8] = <ManagerGrant as Discriminator>::DISCRIMINATOR;
+    pub const MINT_CONFIG: [u8; 8] = <MintConfig as Discriminator>::DISCRIMINATOR;
+    pub const NAMESPACE_BRIDGE_CONFIG: [u8; 8] =
+        <NamespaceBridgeConfig as Discriminator>::DISCRIMINATOR;
+    pub const NAMESPACE_METADATA: [u8; 8] = <NamespaceMetadata as Discriminator>::DISCRIMINATOR;
+    pub const OBLIGATION: [u8; 8] = <Obligation as Discriminator>::DISCRIMINATOR;
+    pub const PROMO_CLAIM: [u8; 8] = <PromoClaim as Discriminator>::DISCRIMINATOR;
+    pub const PROMO_CODE: [u8; 8] = <PromoCode as Discriminator>::DISCRIMINATOR;
+    pub const RECENT_OPERATIONS: [u8; 8] = <RecentOperations as Discriminator>::DISCRIMINATOR;
+    pub const RESERVES_ATTESTATION: [u8; 8] = <ReservesAttestation as Discriminator>::DISCRIMINATOR;
+    pub const SEALED_BID: [u8; 8] = <SealedBid as Discriminator>::DISCRIMINATOR;
+    pub const SHADOW_FEES: [u8; 8] = <ShadowFees as Discriminator>::DISCRIMINATOR;
+    pub const SPEND_GUARD: [u8; 8] = <SpendGuard as Discriminator>::DISCRIMINATOR;
+    pub const TAX_LOT_BOOK: [u8; 8] = <TaxLotBook as Discriminator>::DISCRIMINATOR;
+    pub const TAX_LOT_PAGE: [u8; 8] = <TaxLotPage as Discriminator>::DISCRIMINATOR;
+    pub const TRANSFER_POLICY: [u8; 8] = <TransferPolicy as Discriminator>::DISCRIMINATOR;
+    pub const USER_ACCOUNT: [u8; 8] = <UserAccount as Discriminator>::DISCRIMINATOR;
+    pub const USER_PROFILE: [u8; 8] = <UserProfile as Discriminator>::DISCRIMINATOR;
+    pub const VAULT_POSITION: [u8; 8] = <VaultPosition as Discriminator>::DISCRIMINATOR;
+}
+
+/// Discriminators prefixed to event data (`sha256("event:<Name>")[..8]`)
+pub mod events {
+    use super::*;
+
+    pub const ACCOUNT_COMPACTED_EVENT: [u8; 8] =
+        <AccountCompactedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ACCOUNT_EXPIRED_EVENT: [u8; 8] =
+        <AccountExpiredEvent as Discriminator>::DISCRIMINATOR;
+    pub const ACCOUNT_MODE_CHANGED_EVENT: [u8; 8] =
+        <AccountModeChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ALERT_DISMISSED_EVENT: [u8; 8] =
+        <AlertDismissedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ALERT_RAISED_EVENT: [u8; 8] = <AlertRaisedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ALLOWANCE_CHANGED_EVENT: [u8; 8] =
+        <AllowanceChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ATTESTATION_CHANGED_EVENT: [u8; 8] =
+        <AttestationChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const AUCTION_SETTLED_EVENT: [u8; 8] =
+        <AuctionSettledEvent as Discriminator>::DISCRIMINATOR;
+    pub const BATCH_TRANSFER_EVENT: [u8; 8] = <BatchTransferEvent as Discriminator>::DISCRIMINATOR;
+    pub const CAPABILITY_ISSUED_EVENT: [u8; 8] =
+        <CapabilityIssuedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CAPABILITY_REVOKED_EVENT: [u8; 8] =
+        <CapabilityRevokedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CAPABILITY_USED_EVENT: [u8; 8] =
+        <CapabilityUsedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CASHBACK_CONFIGURED_EVENT: [u8; 8] =
+        <CashbackConfiguredEvent as Discriminator>::DISCRIMINATOR;
+    pub const CASHBACK_MERCHANT_CHANGED_EVENT: [u8; 8] =
+        <CashbackMerchantChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CASHBACK_PAID_EVENT: [u8; 8] = <CashbackPaidEvent as Discriminator>::DISCRIMINATOR;
+    pub const CASHBACK_SUSPENSION_EVENT: [u8; 8] =
+        <CashbackSuspensionEvent as Discriminator>::DISCRIMINATOR;
+    pub const CHAOS_SET_EVENT: [u8; 8] = <ChaosSetEvent as Discriminator>::DISCRIMINATOR;
+    pub const CIRCUIT_BREAKER_TRIPPED_EVENT: [u8; 8] =
+        <CircuitBreakerTrippedEvent as Discriminator>::DISCRIMINATOR;
+    pub const COMMITMENT_REVEALED_EVENT: [u8; 8] =
+        <CommitmentRevealedEvent as Discriminator>::DISCRIMINATOR;
+    pub const COMPLIANCE_AUTHORITY_CHANGED_EVENT: [u8; 8] =
+        <ComplianceAuthorityChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const COMPLIANCE_REPORT_COMPILED_EVENT: [u8; 8] =
+        <ComplianceReportCompiledEvent as Discriminator>::DISCRIMINATOR;
+    pub const CONFIG_SNAPSHOT_EVENT: [u8; 8] =
+        <ConfigSnapshotEvent as Discriminator>::DISCRIMINATOR;
+    pub const CPI_PROGRAM_UPDATED_EVENT: [u8; 8] =
+        <CpiProgramUpdatedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CRANK_METRICS_INITIALIZED_EVENT: [u8; 8] =
+        <CrankMetricsInitializedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CREDIT_LINE_FROZEN_EVENT: [u8; 8] =
+        <CreditLineFrozenEvent as Discriminator>::DISCRIMINATOR;
+    pub const CREDIT_LINE_OPENED_EVENT: [u8; 8] =
+        <CreditLineOpenedEvent as Discriminator>::DISCRIMINATOR;
+    pub const CREDIT_LINE_UTILIZATION_EVENT: [u8; 8] =
+        <CreditLineUtilizationEvent as Discriminator>::DISCRIMINATOR;
+    pub const CROSS_NAMESPACE_TRANSFER_EVENT: [u8; 8] =
+        <CrossNamespaceTransferEvent as Discriminator>::DISCRIMINATOR;
+    pub const DAILY_LIMIT_CHANGED_EVENT: [u8; 8] =
+        <DailyLimitChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const DEACTIVATION_FINALIZED_EVENT: [u8; 8] =
+        <DeactivationFinalizedEvent as Discriminator>::DISCRIMINATOR;
+    pub const DEACTIVATION_SCHEDULED_EVENT: [u8; 8] =
+        <DeactivationScheduledEvent as Discriminator>::DISCRIMINATOR;
+    pub const DOCUMENT_ATTACHED_EVENT: [u8; 8] =
+        <DocumentAttachedEvent as Discriminator>::DISCRIMINATOR;
+    pub const DOCUMENT_REMOVED_EVENT: [u8; 8] =
+        <DocumentRemovedEvent as Discriminator>::DISCRIMINATOR;
+    pub const DONATIONS_SKIMMED_EVENT: [u8; 8] =
+        <DonationsSkimmedEvent as Discriminator>::DISCRIMINATOR;
+    pub const EMISSION_SCHEDULE_CHANGED_EVENT: [u8; 8] =
+        <EmissionScheduleChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ESCROW_CANCELLED_EVENT: [u8; 8] =
+        <EscrowCancelledEvent as Discriminator>::DISCRIMINATOR;
+    pub const ESCROW_CLAIMED_EVENT: [u8; 8] = <EscrowClaimedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ESCROW_CREATED_EVENT: [u8; 8] = <EscrowCreatedEvent as Discriminator>::DISCRIMINATOR;
+    pub const FEE_STRATEGY_CHANGED_EVENT: [u8; 8] =
+        <FeeStrategyChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const GUARDIANS_CHANGED_EVENT: [u8; 8] =
+        <GuardiansChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const INSTRUCTION_DEPRECATED_EVENT: [u8; 8] =
+        <InstructionDeprecatedEvent as Discriminator>::DISCRIMINATOR;
+    pub const INSTRUCTIONS_TOGGLED_EVENT: [u8; 8] =
+        <InstructionsToggledEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVARIANTS_ATTESTED_EVENT: [u8; 8] =
+        <InvariantsAttestedEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_ISSUED_EVENT: [u8; 8] = <InvoiceIssuedEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_LISTED_EVENT: [u8; 8] = <InvoiceListedEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_PAID_EVENT: [u8; 8] = <InvoicePaidEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_REJECTED_EVENT: [u8; 8] =
+        <InvoiceRejectedEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_SOLD_EVENT: [u8; 8] = <InvoiceSoldEvent as Discriminator>::DISCRIMINATOR;
+    pub const INVOICE_TEMPLATE_CANCELLED_EVENT: [u8; 8] =
+        <InvoiceTemplateCancelledEvent as Discriminator>::DISCRIMINATOR;
+    pub const LAMPORTS_SWEPT_EVENT: [u8; 8] = <LamportsSweptEvent as Discriminator>::DISCRIMINATOR;
+    pub const LEDGER_VERIFIED_EVENT: [u8; 8] =
+        <LedgerVerifiedEvent as Discriminator>::DISCRIMINATOR;
+    pub const LOOKUP_TABLE_REGISTERED_EVENT: [u8; 8] =
+        <LookupTableRegisteredEvent as Discriminator>::DISCRIMINATOR;
+    pub const MANAGER_ACTION_EVENT: [u8; 8] = <ManagerActionEvent as Discriminator>::DISCRIMINATOR;
+    pub const MANAGER_GRANTED_EVENT: [u8; 8] =
+        <ManagerGrantedEvent as Discriminator>::DISCRIMINATOR;
+    pub const MANAGER_REVOKED_EVENT: [u8; 8] =
+        <ManagerRevokedEvent as Discriminator>::DISCRIMINATOR;
+    pub const MINT_CONFIG_CHANGED_EVENT: [u8; 8] =
+        <MintConfigChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const NAMESPACE_BRIDGE_CHANGED_EVENT: [u8; 8] =
+        <NamespaceBridgeChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const NAMESPACE_METADATA_CHANGED_EVENT: [u8; 8] =
+        <NamespaceMetadataChangedEvent as Discriminator>::DISCRIMINATOR;
+    pub const NET_SETTLED_EVENT: [u8; 8] = <NetSettledEvent as Discriminator>::DISCRIMINATOR;
+    pub const OBLIGATION_RECORDED_EVENT: [u8; 8] =
+        <ObligationRecordedEvent as Discriminator>::DISCRIMINATOR;
+    pub const ORACLE_PRICE_OVERRIDDEN_EVENT: [u8; 8] =
+        <OraclePriceOverriddenEvent as Discriminator>::DISCRIMINATOR;
+    pub const PARAMETER_RAMP_SCHEDULED_EVENT: [u8; 8] =
+        <ParameterRampScheduledEvent as Discriminator>::DISCRIMINATOR;
+    pub const PAUSE_TOGGLED_EVENT: [u8; 8] = <PauseToggledEvent as Discriminator>::DISCRIMINATOR;
+    pub const POLICY_CLOSED_EVENT: [u8; 8] = <PolicyClosedEvent as Discriminator>::DISCRIMINATOR;
+    pub const POLICY_SET_EVENT: [u8; 8] = <PolicySetEvent as Discriminator>::DISCRIMINATOR;
+    pub const PROFILE_UPDATED_EVENT: [u8; 8] =
+        <ProfileUpdatedEvent as Discriminator>::DISCRIMINATOR;
+    pub const PROGRAM_FINGERPRINT_EVENT: [u8; 8] =
+        <ProgramFingerprintEvent as Discriminator>::DISCRIMINATOR;
+    pub const PROMO_REDEEMED_EVENT: [u8; 8] = <PromoRedeemedEvent as Discriminator>::DISCRIMINATOR;
+    pub const PROOF_OF_RESERVES_EVENT: [u8; 8] =
+        <ProofOfReservesEvent as Discriminator>::DISCRIMINATOR;
+    pub const ROUTED_TRANSFER_EVENT: [u8; 8] =
+        <RoutedTransferEvent as Discriminator>::DISCRIMINATOR;
+    pub const SHADOW_FEE_EVENT: [u8; 8] = <ShadowFeeEvent as Discriminator>::DISCRIMINATOR;
+    pub const SHADOW_FEE_STRATEGY_SET_EVENT: [u8; 8] =
+        <ShadowFeeStrategySetEvent as Discriminator>::DISCRIMINATOR;
+    pub const STATEMENT_GENERATED_EVENT: [u8; 8] =
+        <StatementGeneratedEvent as Discriminator>::DISCRIMINATOR;
+    pub const TAX_LOTS_REALIZED_EVENT: [u8; 8] =
+        <TaxLotsRealizedEvent as Discriminator>::DISCRIMINATOR;
+    pub const TOKEN_TRANSFER_EVENT: [u8; 8] = <TokenTransferEvent as Discriminator>::DISCRIMINATOR;
+    pub const TOKENS_DEPOSITED_EVENT: [u8; 8] =
+        <TokensDepositedEvent as Discriminator>::DISCRIMINATOR;
+    pub const TOKENS_WITHDRAWN_EVENT: [u8; 8] =
+        <TokensWithdrawnEvent as Discriminator>::DISCRIMINATOR;
+    pub const TRANSFER_MEMO_EVENT: [u8; 8] = <TransferMemoEvent as Discriminator>::DISCRIMINATOR;
+    pub const TREASURY_WITHDRAWN_EVENT: [u8; 8] =
+        <TreasuryWithdrawnEvent as Discriminator>::DISCRIMINATOR;
+    pub const USER_CONSISTENCY_REPORT_EVENT: [u8; 8] =
+        <UserConsistencyReportEvent as Discriminator>::DISCRIMINATOR;
+    pub const USER_MIGRATED_EVENT: [u8; 8] = <UserMigratedEvent as Discriminator>::DISCRIMINATOR;
+    pub const WATCHTOWER_SET_EVENT: [u8; 8] = <WatchtowerSetEvent as Discriminator>::DISCRIMINATOR;
+}
+
+/// Byte-wise equality usable in constant expressions
+const fn bytes_eq(a: [u8; 8], b: [u8; 8]) -> bool {
+    let mut i = 0;
+    while i < 8 {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Locked account discriminators
+const _: () = assert!(bytes_eq(
+    accounts::ACTIVITY_LOG,
+    [25, 244, 19, 242, 176, 113, 202, 61]
+));
+const _: () = assert!(bytes_eq(
+    accounts::ALLOWANCE,
+    [144, 8, 184, 213, 49, 248, 73, 131]
+));
+const _: () = assert!(bytes_eq(
+    accounts::ATTACHMENT_PAGE,
+    [34, 111, 25, 244, 89, 3, 114, 192]
+));
+const _: () = assert!(bytes_eq(
+    accounts::ATTESTATION,
+    [152, 125, 183, 86, 36, 146, 121, 73]
+));
+const _: () = assert!(bytes_eq(
+    accounts::AUCTION,
+    [218, 94, 247, 242, 126, 233, 131, 81]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CAPABILITY_TOKEN,
+    [145, 178, 87, 19, 62, 64, 238, 252]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CASHBACK_MERCHANT,
+    [25, 242, 188, 15, 106, 1, 26, 183]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CASHBACK_PROGRAM,
+    [247, 226, 225, 29, 202, 231, 200, 12]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CHAOS_CONFIG,
+    [97, 153, 143, 131, 159, 175, 46, 164]
+));
+const _: () = assert!(bytes_eq(
+    accounts::COMMITMENT,
+    [61, 112, 129, 128, 24, 147, 77, 87]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CPI_PROGRAM_REGISTRY,
+    [199, 56, 48, 153, 27, 195, 208, 183]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CRANK_METRICS,
+    [120, 62, 136, 163, 130, 21, 54, 14]
+));
+const _: () = assert!(bytes_eq(
+    accounts::CREDIT_LINE,
+    [220, 226, 205, 24, 220, 151, 129, 104]
+));
+const _: () = assert!(bytes_eq(
+    accounts::EPOCH_STATS,
+    [118, 2, 81, 207, 154, 225, 238, 179]
+));
+const _: () = assert!(bytes_eq(
+    accounts::ESCROW,
+    [31, 213, 123, 187, 186, 22, 218, 155]
+));
+const _: () = assert!(bytes_eq(
+    accounts::FEE_TREASURY,
+    [85, 11, 82, 13, 148, 202, 81, 5]
+));
+const _: () = assert!(bytes_eq(
+    accounts::GLOBAL_CONFIG,
+    [149, 8, 156, 202, 160, 252, 176, 217]
+));
+const _: () = assert!(bytes_eq(
+    accounts::INVOICE,
+    [51, 194, 250, 114, 6, 104, 18, 164]
+));
+const _: () = assert!(bytes_eq(
+    accounts::INVOICE_TEMPLATE,
+    [68, 217, 220, 2, 138, 245, 201, 146]
+));
+const _: () = assert!(bytes_eq(
+    accounts::ISSUER_RECORD,
+    [234, 211, 177, 53, 123, 223, 42, 82]
+));
+const _: () = assert!(bytes_eq(
+    accounts::JOINT_ACCOUNT,
+    [62, 198, 87, 86, 226, 196, 1, 36]
+));
+const _: () = assert!(bytes_eq(
+    accounts::LEDGER,
+    [43, 41, 21, 213, 180, 176, 95, 32]
+));
+const _: () = assert!(bytes_eq(
+    accounts::MANAGER_GRANT,
+    [191, 209, 178, 255, 58, 89, 67, 65]
+));
+const _: () = assert!(bytes_eq(
+    accounts::MINT_CONFIG,
+    [168, 252, 88, 182, 219, 205, 39, 53]
+));
+const _: () = assert!(bytes_eq(
+    accounts::NAMESPACE_BRIDGE_CONFIG,
+    [252, 89, 197, 133, 24, 230, 3, 191]
+));
+const _: () = assert!(bytes_eq(
+    accounts::NAMESPACE_METADATA,
+    [244, 254, 189, 118, 113, 211, 113, 240]
+));
+const _: () = assert!(bytes_eq(
+    accounts::OBLIGATION,
+    [168, 206, 141, 106, 88, 76, 172, 167]
+));
+const _: () = assert!(bytes_eq(
+    accounts::PROMO_CLAIM,
+    [90, 128, 154, 245, 44, 32, 216, 12]
+));
+const _: () = assert!(bytes_eq(
+    accounts::PROMO_CODE,
+    [253, 157, 67, 176, 230, 182, 64, 61]
+));
+const _: () = assert!(bytes_eq(
+    accounts::RECENT_OPERATIONS,
+    [173, 68, 113, 217, 235, 147, 25, 79]
+));
+const _: () = assert!(bytes_eq(
+    accounts::RESERVES_ATTESTATION,
+    [226, 225, 185, 191, 164, 82, 140, 34]
+));
+const _: () = assert!(bytes_eq(
+    accounts::SEALED_BID,
+    [199, 9, 212, 151, 48, 136, 163, 226]
+));
+const _: () = assert!(bytes_eq(
+    accounts::SHADOW_FEES,
+    [181, 97, 189, 47, 61, 8, 173, 31]
+));
+const _: () = assert!(bytes_eq(
+    accounts::SPEND_GUARD,
+    [136, 210, 126, 32, 191, 15, 37, 250]
+));
+const _: () = assert!(bytes_eq(
+    accounts::TAX_LOT_BOOK,
+    [154, 180, 74, 136, 101, 226, 57, 129]
+));
+const _: () = assert!(bytes_eq(
+    accounts::TAX_LOT_PAGE,
+    [25, 236, 254, 217, 194, 150, 238, 8]
+));
+const _: () = assert!(bytes_eq(
+    accounts::TRANSFER_POLICY,
+    [249, 156, 46, 211, 45, 191, 50, 69]
+));
+const _: () = assert!(bytes_eq(
+    accounts::USER_ACCOUNT,
+    [211, 33, 136, 16, 186, 110, 242, 127]
+));
+const _: () = assert!(bytes_eq(
+    accounts::USER_PROFILE,
+    [32, 37, 119, 205, 179, 180, 13, 194]
+));
+const _: () = assert!(bytes_eq(
+    accounts::VAULT_POSITION,
+    [122, 109, 193, 63, 69, 148, 90, 237]
+));
+
+// Locked event discriminators
+const _: () = assert!(bytes_eq(
+    events::ACCOUNT_COMPACTED_EVENT,
+    [173, 187, 251, 121, 146, 135, 210, 84]
+));
+const _: () = assert!(bytes_eq(
+    events::ACCOUNT_EXPIRED_EVENT,
+    [98, 10, 65, 192, 40, 240, 227, 42]
+));
+const _: () = assert!(bytes_eq(
+    events::ACCOUNT_MODE_CHANGED_EVENT,
+    [103, 176, 66, 18, 136, 136, 65, 225]
+));
+const _: () = assert!(bytes_eq(
+    events::ALERT_DISMISSED_EVENT,
+    [247, 136, 75, 185, 118, 168, 249, 41]
+));
+const _: () = assert!(bytes_eq(
+    events::ALERT_RAISED_EVENT,
+    [188, 248, 3, 188, 95, 215, 114, 131]
+));
+const _: () = assert!(bytes_eq(
+    events::ALLOWANCE_CHANGED_EVENT,
+    [148, 230, 253, 124, 185, 121, 76, 150]
+));
+const _: () = assert!(bytes_eq(
+    events::ATTESTATION_CHANGED_EVENT,
+    [19, 156, 136, 146, 111, 131, 211, 25]
+));
+const _: () = assert!(bytes_eq(
+    events::AUCTION_SETTLED_EVENT,
+    [74, 119, 213, 57, 119, 67, 141, 138]
+));
+const _: () = assert!(bytes_eq(
+    events::BATCH_TRANSFER_EVENT,
+    [202, 232, 243, 201, 129, 230, 84, 235]
+));
+const _: () = assert!(bytes_eq(
+    events::CAPABILITY_ISSUED_EVENT,
+    [170, 244, 142, 169, 160, 43, 169, 45]
+));
+const _: () = assert!(bytes_eq(
+    events::CAPABILITY_REVOKED_EVENT,
+    [100, 23, 175, 205, 170, 140, 221, 239]
+));
+const _: () = assert!(bytes_eq(
+    events::CAPABILITY_USED_EVENT,
+    [134, 42, 146, 20, 170, 113, 47, 2]
+));
+const _: () = assert!(bytes_eq(
+    events::CASHBACK_CONFIGURED_EVENT,
+    [64, 120, 94, 89, 87, 214, 104, 21]
+));
+const _: () = assert!(bytes_eq(
+    events::CASHBACK_MERCHANT_CHANGED_EVENT,
+    [161, 131, 240, 194, 239, 35, 211, 137]
+));
+const _: () = assert!(bytes_eq(
+    events::CASHBACK_PAID_EVENT,
+    [113, 251, 83, 28, 210, 184, 196, 90]
+));
+const _: () = assert!(bytes_eq(
+    events::CASHBACK_SUSPENSION_EVENT,
+    [99, 158, 249, 154, 168, 37, 76, 171]
+));
+const _: () = assert!(bytes_eq(
+    events::CHAOS_SET_EVENT,
+    [126, 252, 251, 19, 245, 22, 210, 124]
+));
+const _: () = assert!(bytes_eq(
+    events::CIRCUIT_BREAKER_TRIPPED_EVENT,
+    [112, 68, 182, 85, 54, 184, 4, 134]
+));
+const _: () = assert!(bytes_eq(
+    events::COMMITMENT_REVEALED_EVENT,
+    [105, 102, 60, 237, 186, 170, 187, 79]
+));
+const _: () = assert!(bytes_eq(
+    events::COMPLIANCE_AUTHORITY_CHANGED_EVENT,
+    [116, 243, 38, 113, 73, 30, 209, 236]
+));
+const _: () = assert!(bytes_eq(
+    events::COMPLIANCE_REPORT_COMPILED_EVENT,
+    [69, 90, 190, 158, 83, 114, 149, 247]
+));
+const _: () = assert!(bytes_eq(
+    events::CONFIG_SNAPSHOT_EVENT,
+    [101, 58, 218, 72, 243, 161, 28, 25]
+));
+const _: () = assert!(bytes_eq(
+    events::CPI_PROGRAM_UPDATED_EVENT,
+    [143, 218, 225, 37, 30, 234, 51, 15]
+));
+const _: () = assert!(bytes_eq(
+    events::CRANK_METRICS_INITIALIZED_EVENT,
+    [173, 128, 119, 15, 72, 93, 22, 20]
+));
+const _: () = assert!(bytes_eq(
+    events::CREDIT_LINE_FROZEN_EVENT,
+    [237, 233, 217, 79, 174, 109, 152, 74]
+));
+const _: () = assert!(bytes_eq(
+    events::CREDIT_LINE_OPENED_EVENT,
+    [166, 75, 169, 23, 248, 11, 31, 242]
+));
+const _: () = assert!(bytes_eq(
+    events::CREDIT_LINE_UTILIZATION_EVENT,
+    [54, 30, 101, 73, 177, 149, 196, 50]
+));
+const _: () = assert!(bytes_eq(
+    events::CROSS_NAMESPACE_TRANSFER_EVENT,
+    [22, 33, 109, 152, 186, 208, 71, 61]
+));
+const _: () = assert!(bytes_eq(
+    events::DAILY_LIMIT_CHANGED_EVENT,
+    [89, 253, 167, 114, 228, 69, 27, 25]
+));
+const _: () = assert!(bytes_eq(
+    events::DEACTIVATION_FINALIZED_EVENT,
+    [56, 253, 114, 120, 18, 42, 130, 10]
+));
+const _: () = assert!(bytes_eq(
+    events::DEACTIVATION_SCHEDULED_EVENT,
+    [60, 253, 28, 51, 173, 105, 179, 82]
+));
+const _: () = assert!(bytes_eq(
+    events::DOCUMENT_ATTACHED_EVENT,
+    [187, 101, 92, 222, 151, 35, 247, 224]
+));
+const _: () = assert!(bytes_eq(
+    events::DOCUMENT_REMOVED_EVENT,
+    [4, 148, 182, 92, 196, 228, 2, 243]
+));
+const _: () = assert!(bytes_eq(
+    events::DONATIONS_SKIMMED_EVENT,
+    [175, 123, 7, 16, 165, 112, 77, 245]
+));
+const _: () = assert!(bytes_eq(
+    events::EMISSION_SCHEDULE_CHANGED_EVENT,
+    [9, 168, 92, 107, 129, 102, 78, 224]
+));
+const _: () = assert!(bytes_eq(
+    events::ESCROW_CANCELLED_EVENT,
+    [185, 105, 9, 145, 0, 228, 166, 60]
+));
+const _: () = assert!(bytes_eq(
+    events::ESCROW_CLAIMED_EVENT,
+    [102, 255, 155, 116, 206, 56, 249, 241]
+));
+const _: () = assert!(bytes_eq(
+    events::ESCROW_CREATED_EVENT,
+    [79, 14, 137, 123, 229, 161, 84, 149]
+));
+const _: () = assert!(bytes_eq(
+    events::FEE_STRATEGY_CHANGED_EVENT,
+    [167, 184, 113, 83, 92, 218, 19, 133]
+));
+const _: () = assert!(bytes_eq(
+    events::GUARDIANS_CHANGED_EVENT,
+    [225, 10, 101, 116, 145, 244, 250, 67]
+));
+const _: () = assert!(bytes_eq(
+    events::INSTRUCTION_DEPRECATED_EVENT,
+    [198, 39, 164, 1, 28, 200, 23, 27]
+));
+const _: () = assert!(bytes_eq(
+    events::INSTRUCTIONS_TOGGLED_EVENT,
+    [208, 62, 201, 29, 171, 248, 1, 158]
+));
+const _: () = assert!(bytes_eq(
+    events::INVARIANTS_ATTESTED_EVENT,
+    [179, 251, 162, 243, 51, 169, 101, 75]
+));
+const _: () = assert!(bytes_eq(
+    events::INVOICE_ISSUED_EVENT,
+    [218, 180, 237, 180, 112, 150, 15, 12]
+));
+const _: () = assert!(bytes_eq(
+    events::INVOICE_LISTED_EVENT,
+    [227, 188, 35, 85, 200, 130, 252, 23]
+));
+const _: () = assert!(bytes_eq(
+    events::INVOICE_PAID_EVENT,
+    [201, 31, 158, 13, 50, 47, 66, 53]
+));
+const _: () = assert!(bytes_eq(
+    events::INVOICE_REJECTED_EVENT,
+    [231, 191, 240, 24, 18, 222, 163, 98]
+));
+const _: () = assert!(bytes_eq(
+    events::INVOICE_SOLD_EVENT,
+    [155, 92, 54, 93, 244, 190, 150, 207]
+));
+const _: () = assert!(bytes_eq(
+    events::INVOICE_TEMPLATE_CANCELLED_EVENT,
+    [210, 189, 96, 63, 127, 175, 96, 136]
+));
+const _: () = assert!(bytes_eq(
+    events::LAMPORTS_SWEPT_EVENT,
+    [82, 224, 169, 46, 184, 36, 240, 255]
+));
+const _: () = assert!(bytes_eq(
+    events::LEDGER_VERIFIED_EVENT,
+    [192, 92, 248, 252, 237, 247, 7, 170]
+));
+const _: () = assert!(bytes_eq(
+    events::LOOKUP_TABLE_REGISTERED_EVENT,
+    [21, 163, 241, 209, 5, 66, 247, 97]
+));
+const _: () = assert!(bytes_eq(
+    events::MANAGER_ACTION_EVENT,
+    [171, 253, 174, 78, 35, 185, 176, 248]
+));
+const _: () = assert!(bytes_eq(
+    events::MANAGER_GRANTED_EVENT,
+    [136, 190, 232, 41, 48, 29, 80, 40]
+));
+const _: () = assert!(bytes_eq(
+    events::MANAGER_REVOKED_EVENT,
+    [103, 191, 204, 231, 197, 215, 225, 151]
+));
+const _: () = assert!(bytes_eq(
+    events::MINT_CONFIG_CHANGED_EVENT,
+    [53, 161, 139, 94, 144, 148, 26, 5]
+));
+const _: () = assert!(bytes_eq(
+    events::NAMESPACE_BRIDGE_CHANGED_EVENT,
+    [105, 107, 87, 227, 47, 250, 155, 189]
+));
+const _: () = assert!(bytes_eq(
+    events::NAMESPACE_METADATA_CHANGED_EVENT,
+    [5, 57, 21, 232, 16, 109, 29, 33]
+));
+const _: () = assert!(bytes_eq(
+    events::NET_SETTLED_EVENT,
+    [120, 202, 138, 46, 166, 114, 238, 227]
+));
+const _: () = assert!(bytes_eq(
+    events::OBLIGATION_RECORDED_EVENT,
+    [226, 220, 20, 91, 137, 98, 66, 84]
+));
+const _: () = assert!(bytes_eq(
+    events::ORACLE_PRICE_OVERRIDDEN_EVENT,
+    [234, 146, 153, 233, 56, 98, 115, 180]
+));
+const _: () = assert!(bytes_eq(
+    events::PARAMETER_RAMP_SCHEDULED_EVENT,
+    [29, 89, 106, 157, 55, 128, 180, 173]
+));
+const _: () = assert!(bytes_eq(
+    events::PAUSE_TOGGLED_EVENT,
+    [210, 185, 198, 169, 200, 181, 119, 167]
+));
+const _: () = assert!(bytes_eq(
+    events::POLICY_CLOSED_EVENT,
+    [18, 255, 220, 116, 184, 71, 198, 217]
+));
+const _: () = assert!(bytes_eq(
+    events::POLICY_SET_EVENT,
+    [178, 215, 143, 28, 109, 45, 199, 33]
+));
+const _: () = assert!(bytes_eq(
+    events::PROFILE_UPDATED_EVENT,
+    [241, 132, 13, 233, 46, 80, 12, 50]
+));
+const _: () = assert!(bytes_eq(
+    events::PROGRAM_FINGERPRINT_EVENT,
+    [192, 201, 233, 73, 216, 141, 21, 128]
+));
+const _: () = assert!(bytes_eq(
+    events::PROMO_REDEEMED_EVENT,
+    [96, 47, 200, 59, 60, 137, 135, 98]
+));
+const _: () = assert!(bytes_eq(
+    events::PROOF_OF_RESERVES_EVENT,
+    [50, 52, 67, 57, 190, 123, 116, 186]
+));
+const _: () = assert!(bytes_eq(
+    events::ROUTED_TRANSFER_EVENT,
+    [131, 71, 174, 141, 247, 59, 51, 47]
+));
+const _: () = assert!(bytes_eq(
+    events::SHADOW_FEE_EVENT,
+    [42, 201, 108, 241, 156, 232, 177, 167]
+));
+const _: () = assert!(bytes_eq(
+    events::SHADOW_FEE_STRATEGY_SET_EVENT,
+    [41, 168, 166, 20, 139, 44, 187, 217]
+));
+const _: () = assert!(bytes_eq(
+    events::STATEMENT_GENERATED_EVENT,
+    [143, 43, 196, 117, 43, 11, 144, 75]
+));
+const _: () = assert!(bytes_eq(
+    events::TAX_LOTS_REALIZED_EVENT,
+    [113, 112, 120, 58, 56, 245, 214, 55]
+));
+const _: () = assert!(bytes_eq(
+    events::TOKEN_TRANSFER_EVENT,
+    [238, 159, 246, 89, 230, 8, 89, 117]
+));
+const _: () = assert!(bytes_eq(
+    events::TOKENS_DEPOSITED_EVENT,
+    [36, 116, 231, 91, 66, 193, 12, 20]
+));
+const _: () = assert!(bytes_eq(
+    events::TOKENS_WITHDRAWN_EVENT,
+    [226, 188, 19, 166, 84, 192, 103, 214]
+));
+const _: () = assert!(bytes_eq(
+    events::TRANSFER_MEMO_EVENT,
+    [154, 164, 252, 133, 26, 77, 139, 223]
+));
+const _: () = assert!(bytes_eq(
+    events::TREASURY_WITHDRAWN_EVENT,
+    [29, 130, 89, 213, 225, 104, 229, 154]
+));
+const _: () = assert!(bytes_eq(
+    events::USER_CONSISTENCY_REPORT_EVENT,
+    [192, 135, 211, 105, 213, 171, 187, 221]
+));
+const _: () = assert!(bytes_eq(
+    events::USER_MIGRATED_EVENT,
+    [185, 61, 81, 228, 81, 172, 201, 84]
+));
+const _: () = assert!(bytes_eq(
+    events::WATCHTOWER_SET_EVENT,
+    [249, 203, 8, 81, 153, 243, 40, 84]
+));
+
+/// Returns the account type name for a data prefix, if it belongs to this program
+pub fn account_name(data: &[u8]) -> Option<&'static str> {
+    let prefix: [u8; 8] = data.get(..8)?.try_into().ok()?;
+    match prefix {
+        accounts::ACTIVITY_LOG => Some("ActivityLog"),
+        accounts::ALLOWANCE => Some("Allowance"),
+        accounts::ATTACHMENT_PAGE => Some("AttachmentPage"),
+        accounts::ATTESTATION => Some("Attestation"),
+        accounts::AUCTION => Some("Auction"),
+        accounts::CAPABILITY_TOKEN => Some("CapabilityToken"),
+        accounts::CASHBACK_MERCHANT => Some("CashbackMerchant"),
+        accounts::CASHBACK_PROGRAM => Some("CashbackProgram"),
+        accounts::CHAOS_CONFIG => Some("ChaosConfig"),
+        accounts::COMMITMENT => Some("Commitment"),
+        accounts::CPI_PROGRAM_REGISTRY => Some("CpiProgramRegistry"),
+        accounts::CRANK_METRICS => Some("CrankMetrics"),
+        accounts::CREDIT_LINE => Some("CreditLine"),
+        accounts::EPOCH_STATS => Some("EpochStats"),
+        accounts::ESCROW => Some("Escrow"),
+        accounts::FEE_TREASURY => Some("FeeTreasury"),
+        accounts::GLOBAL_CONFIG => Some("GlobalConfig"),
+        accounts::INVOICE => Some("Invoice"),
+        accounts::INVOICE_TEMPLATE => Some("InvoiceTemplate"),
+        accounts::ISSUER_RECORD => Some("IssuerRecord"),
+        accounts::JOINT_ACCOUNT => Some("JointAccount"),
+        accounts::LEDGER => Some("Ledger"),
+        accounts::MANAGER_GRANT => Some("ManagerGrant"),
+        accounts::MINT_CONFIG => Some("MintConfig"),
+        accounts::NAMESPACE_BRIDGE_CONFIG => Some("NamespaceBridgeConfig"),
+        accounts::NAMESPACE_METADATA => Some("NamespaceMetadata"),
+        accounts::OBLIGATION => Some("Obligation"),
+        accounts::PROMO_CLAIM => Some("PromoClaim"),
+        accounts::PROMO_CODE => Some("PromoCode"),
+        accounts::RECENT_OPERATIONS => Some("RecentOperations"),
+        accounts::RESERVES_ATTESTATION => Some("ReservesAttestation"),
+        accounts::SEALED_BID => Some("SealedBid"),
+        accounts::SHADOW_FEES => Some("ShadowFees"),
+        accounts::SPEND_GUARD => Some("SpendGuard"),
+        accounts::TAX_LOT_BOOK => Some("TaxLotBook"),
+        accounts::TAX_LOT_PAGE => Some("TaxLotPage"),
+        accounts::TRANSFER_POLICY => Some("TransferPolicy"),
+        accounts::USER_ACCOUNT => Some("UserAccount"),
+        accounts::USER_PROFILE => Some("UserProfile"),
+        accounts::VAULT_POSITION => Some("VaultPosition"),
+        _ => None,
+    }
+}
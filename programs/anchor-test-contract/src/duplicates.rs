@@ -0,0 +1,42 @@
+/*
+ * ============================================================================
+ * DUPLICATE ACCOUNT DETECTION
+ * ============================================================================
+ *
+ * Anchor deserializes every account role independently and writes each one
+ * back on exit, so the same account passed as both sender and receiver is
+ * debited in one copy, credited in the other, and the last write wins:
+ * value is double-counted. Instructions with more than one mutable role
+ * call these helpers before touching any balance.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::CustomError;
+
+/// First key that appears more than once in `keys`
+pub fn find_duplicate(keys: &[Pubkey]) -> Option<Pubkey> {
+    keys.iter()
+        .enumerate()
+        .find(|(index, key)| keys[..*index].contains(key))
+        .map(|(_, key)| *key)
+}
+
+/// Reject mutable roles that are filled by the same account
+pub fn require_distinct_accounts(keys: &[Pubkey]) -> Result<()> {
+    match find_duplicate(keys) {
+        Some(key) => Err(error!(CustomError::DuplicateAccount).with_pubkeys((key, key))),
+        None => Ok(()),
+    }
+}
+
+/// Reject `accounts` (e.g. `remaining_accounts`) that repeat a writable account
+/// Read-only accounts may repeat freely
+pub fn require_distinct_writable(accounts: &[AccountInfo]) -> Result<()> {
+    let writable: Vec<Pubkey> = accounts
+        .iter()
+        .filter(|info| info.is_writable)
+        .map(|info| *info.key)
+        .collect();
+    require_distinct_accounts(&writable)
+}
@@ -0,0 +1,128 @@
+/*
+ * ============================================================================
+ * REWARD EMISSION CURVES
+ * ============================================================================
+ *
+ * Staking rewards are released per period along an `EmissionCurve` stored
+ * in the namespace config and switched by the admin with
+ * `set_emission_schedule`:
+ * - Constant: the same amount every period
+ * - LinearDecay: an initial amount that shrinks by a fixed step per period
+ * - Halving: an initial amount halved every `halving_periods` periods
+ * - TargetApr: whatever pays stakers `apr_bps` a year on the total staked
+ *
+ * The schedule's `supply_cap` bounds everything ever emitted: `emissions_for`
+ * trims the last period to what remains and emits nothing once it is
+ * reached. Like the fee strategies, the curves are pure functions of their
+ * inputs so each can be checked in isolation.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::math;
+use crate::{CustomError, BPS_DENOMINATOR};
+
+/// How many reward tokens a period releases before the supply cap
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EmissionCurve {
+    Constant { per_period: u64 },                 // Same amount each period
+    LinearDecay { initial: u64, decrement: u64 }, // initial - decrement * period
+    Halving { initial: u64, halving_periods: u64 }, // initial / 2^(period / halving_periods)
+    TargetApr { apr_bps: u64, periods_per_year: u64 }, // staked * apr / periods_per_year
+}
+
+impl Default for EmissionCurve {
+    fn default() -> Self {
+        EmissionCurve::Constant { per_period: 0 } // Nothing until configured
+    }
+}
+
+impl EmissionCurve {
+    /// Serialized size of the largest variant
+    pub const LEN: usize = 1 + // Variant index
+        8 + 8; // Two u64 parameters
+
+    /// Amount released in `period` with `staked` tokens staked, ignoring any cap
+    pub fn emission_at(&self, period: u64, staked: u64) -> Result<u64> {
+        let amount = match *self {
+            EmissionCurve::Constant { per_period } => per_period,
+            EmissionCurve::LinearDecay { initial, decrement } => {
+                initial.saturating_sub(decrement.saturating_mul(period)) // Floors at zero
+            }
+            EmissionCurve::Halving {
+                initial,
+                halving_periods,
+            } => {
+                let halvings = period
+                    .checked_div(halving_periods)
+                    .ok_or(CustomError::InvalidEmissionSchedule)?;
+                if halvings >= u64::BITS as u64 {
+                    0 // Halved away entirely
+                } else {
+                    initial >> halvings
+                }
+            }
+            EmissionCurve::TargetApr {
+                apr_bps,
+                periods_per_year,
+            } => {
+                let per_year = math::mul_div(staked, apr_bps, BPS_DENOMINATOR, Rounding::Down)?;
+                per_year
+                    .checked_div(periods_per_year)
+                    .ok_or(CustomError::InvalidEmissionSchedule)?
+            }
+        };
+        Ok(amount)
+    }
+}
+
+/// A curve and the cap on everything it may ever emit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EmissionSchedule {
+    pub curve: EmissionCurve, // Per-period release curve
+    pub supply_cap: u64,      // Total ever emitted, across all periods
+}
+
+impl EmissionSchedule {
+    /// Serialized size of the schedule
+    pub const LEN: usize = EmissionCurve::LEN + 8;
+
+    /// Check the curve's parameters before the schedule is stored
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self.curve {
+            EmissionCurve::Constant { .. } | EmissionCurve::LinearDecay { .. } => true,
+            EmissionCurve::Halving {
+                halving_periods, ..
+            } => halving_periods > 0,
+            EmissionCurve::TargetApr {
+                periods_per_year, ..
+            } => periods_per_year > 0,
+        };
+        require!(valid, CustomError::InvalidEmissionSchedule);
+        Ok(())
+    }
+}
+
+/// Emission state a schedule is evaluated against
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EmissionInputs {
+    pub emitted: u64, // Emitted in all earlier periods
+    pub staked: u64,  // Total staked during the period
+}
+
+/// Amount `schedule` releases in `period`, trimmed to the remaining supply
+pub fn emissions_for(
+    schedule: &EmissionSchedule,
+    period: u64,
+    inputs: &EmissionInputs,
+) -> Result<u64> {
+    let remaining = schedule.supply_cap.saturating_sub(inputs.emitted);
+    if remaining == 0 {
+        return Ok(0);
+    }
+    Ok(schedule
+        .curve
+        .emission_at(period, inputs.staked)?
+        .min(remaining))
+}
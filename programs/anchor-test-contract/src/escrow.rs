@@ -0,0 +1,412 @@
+/*
+ * ============================================================================
+ * TIME-LOCKED ESCROW
+ * ============================================================================
+ *
+ * `create_escrow(amount, unlock_at, beneficiary)` moves `amount` out of the
+ * creator's balance into an `Escrow` PDA
+ * (`[b"escrow", config, creator, beneficiary, unlock_at]`). From `unlock_at`
+ * on, the beneficiary authority can `claim_escrow` it into their user
+ * account; before then, the creator can `cancel_escrow` it back. Either
+ * closes the escrow and returns its rent to the creator's authority.
+ *
+ * Escrowed amounts are still owed to users, so they stay in the ledger's
+ * `Users` account while locked; `assert_invariants` counts escrows passed
+ * alongside the user accounts.
+ *
+ * An escrow is one of the creator's open obligations until claimed or
+ * cancelled, so a deactivating creator only locks once its escrows are
+ * gone. Escrowing to oneself is refused: claiming would pass the same user
+ * account as both creator and beneficiary.
+ *
+ * An escrow left unclaimed for `ESCROW_CLAIM_WINDOW` after it unlocks is
+ * refunded to the creator by the `expire` crank, which settles the
+ * obligation as a cancellation would.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    escrow_transition, instruction_flags, load_policy, log_activity, require_distinct_accounts,
+    require_instruction_enabled, require_transfer_modes, settle_obligation, signer_keys,
+    AccountMode, ActivityEntry, ActivityKind, CustomError, EscrowAction, EscrowStatus,
+    GlobalConfig, InstructionTag, Ledger, LedgerAccount, PolicyInput, UserAccount,
+    ESCROW_CLAIM_WINDOW,
+};
+
+/// Balance locked for a beneficiary until `unlock_at`
+#[account]
+pub struct Escrow {
+    pub creator: Pubkey,           // Creator's user account (32 bytes)
+    pub creator_authority: Pubkey, // Paid the rent, refunded on close (32 bytes)
+    pub beneficiary: Pubkey,       // Authority allowed to claim (32 bytes)
+    pub amount: u64,               // Locked amount (8 bytes)
+    pub unlock_at: i64,            // Claimable from (8 bytes)
+    pub status: EscrowStatus,      // Lifecycle status (1 byte)
+    pub bump: u8,                  // PDA bump seed (1 byte)
+}
+
+impl Escrow {
+    /// Space required for the escrow including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // creator: Pubkey
+        32 +                     // creator_authority: Pubkey
+        32 +                     // beneficiary: Pubkey
+        8 +                      // amount: u64
+        8 +                      // unlock_at: i64
+        1 +                      // status: EscrowStatus
+        1; // bump: u8
+
+    /// Returns true once the beneficiary may claim at `now`
+    pub fn is_unlocked(&self, now: i64) -> bool {
+        now >= self.unlock_at
+    }
+
+    /// When `expire` may refund the escrow to its creator if still unclaimed
+    pub fn claim_deadline(&self) -> i64 {
+        ESCROW_CLAIM_WINDOW.after(self.unlock_at)
+    }
+}
+
+/// Context for locking balance in an escrow
+///
+/// Remaining accounts: the creator's guardian signers, when needed.
+#[derive(Accounts)]
+#[instruction(amount: u64, unlock_at: i64, beneficiary: Pubkey)]
+pub struct CreateEscrow<'info> {
+    /// Escrow being created
+    #[account(
+        init,
+        payer = authority,
+        space = Escrow::LEN,
+        seeds = [
+            b"escrow",
+            config.key().as_ref(),
+            creator.key().as_ref(),
+            beneficiary.as_ref(),
+            unlock_at.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Creator's user account, debited
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = creator.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub creator: Account<'info, UserAccount>,
+
+    /// Creator authority, pays the escrow's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Double-entry ledger receiving the escrow entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Creator's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the creator's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", creator.key().as_ref()], bump)]
+    pub creator_policy: UncheckedAccount<'info>,
+
+    /// Creator's activity log address, appended to when the creator keeps one
+    /// CHECK: address is the creator's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", creator.key().as_ref()], bump)]
+    pub creator_activity: UncheckedAccount<'info>,
+}
+
+/// Context for the beneficiary claiming an unlocked escrow
+#[derive(Accounts)]
+pub struct ClaimEscrow<'info> {
+    /// Escrow being claimed, rent returned to the creator's authority
+    #[account(
+        mut,
+        close = creator_authority,
+        seeds = [
+            b"escrow",
+            config.key().as_ref(),
+            escrow.creator.as_ref(),
+            escrow.beneficiary.as_ref(),
+            escrow.unlock_at.to_le_bytes().as_ref()
+        ],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Beneficiary's user account, credited
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = beneficiary.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub beneficiary: Account<'info, UserAccount>,
+
+    /// Beneficiary authority
+    #[account(constraint = authority.key() == escrow.beneficiary @ CustomError::NotBeneficiary)]
+    pub authority: Signer<'info>,
+
+    /// Creator's user account, whose obligation the claim settles
+    #[account(
+        mut,
+        address = escrow.creator,
+        constraint = creator.is_current() @ CustomError::MigrationRequired
+    )]
+    pub creator: Account<'info, UserAccount>,
+
+    /// Creator's authority, receives the rent
+    /// CHECK: matched against the escrow's creator authority
+    #[account(mut, address = escrow.creator_authority)]
+    pub creator_authority: UncheckedAccount<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Double-entry ledger receiving the release entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Beneficiary's activity log address, appended to when the beneficiary keeps one
+    /// CHECK: address is the beneficiary's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", beneficiary.key().as_ref()], bump)]
+    pub beneficiary_activity: UncheckedAccount<'info>,
+}
+
+/// Context for the creator reclaiming an escrow before it unlocks
+#[derive(Accounts)]
+pub struct CancelEscrow<'info> {
+    /// Escrow being cancelled, rent returned to the creator's authority
+    #[account(
+        mut,
+        has_one = creator,
+        close = authority,
+        seeds = [
+            b"escrow",
+            config.key().as_ref(),
+            creator.key().as_ref(),
+            escrow.beneficiary.as_ref(),
+            escrow.unlock_at.to_le_bytes().as_ref()
+        ],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Creator's user account, refunded
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = creator.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub creator: Account<'info, UserAccount>,
+
+    /// Creator authority, receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Double-entry ledger receiving the refund entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Creator's activity log address, appended to when the creator keeps one
+    /// CHECK: address is the creator's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", creator.key().as_ref()], bump)]
+    pub creator_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted when balance is locked in an escrow
+#[event]
+pub struct EscrowCreatedEvent {
+    pub escrow: Pubkey,      // New escrow
+    pub creator: Pubkey,     // Creator's user account
+    pub beneficiary: Pubkey, // Authority allowed to claim
+    pub amount: u64,         // Locked amount
+    pub unlock_at: i64,      // Claimable from
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when the beneficiary claims an escrow
+#[event]
+pub struct EscrowClaimedEvent {
+    pub escrow: Pubkey,      // Claimed, now closed escrow
+    pub beneficiary: Pubkey, // Credited user account
+    pub amount: u64,         // Released amount
+    pub timestamp: i64,      // When it was claimed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when the creator cancels an escrow
+#[event]
+pub struct EscrowCancelledEvent {
+    pub escrow: Pubkey,      // Cancelled, now closed escrow
+    pub creator: Pubkey,     // Refunded user account
+    pub amount: u64,         // Refunded amount
+    pub timestamp: i64,      // When it was cancelled
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Lock `amount` of the creator's balance for `beneficiary` until `unlock_at`
+pub fn handle_create_escrow(
+    ctx: Context<CreateEscrow>,
+    amount: u64,
+    unlock_at: i64,
+    beneficiary: Pubkey,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::CREATE_ESCROW)?;
+    require!(amount > 0, CustomError::InvalidAmount);
+    let now = Clock::get()?.unix_timestamp;
+    require!(unlock_at > now, CustomError::InvalidEscrow);
+    require!(beneficiary != Pubkey::default(), CustomError::InvalidEscrow);
+    require!(
+        beneficiary != ctx.accounts.authority.key(),
+        CustomError::InvalidEscrow
+    );
+
+    let creator = &ctx.accounts.creator;
+    let policy = load_policy(&ctx.accounts.creator_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: beneficiary,
+        now,
+    };
+    guards::run(&[
+        Guard::NotJoint(creator), // Joint accounts use joint_transfer
+        Guard::Modes(creator.mode, AccountMode::Normal), // Locking is an outflow
+        Guard::NotFrozen(creator, now),
+        Guard::GuardianApproved(creator, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(creator, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+
+    let creator = &mut ctx.accounts.creator;
+    creator.record_daily_spend(amount, now)?;
+    creator.open_obligation()?;
+    creator.balance = creator
+        .balance
+        .checked_sub(amount)
+        .ok_or(CustomError::InsufficientFunds)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Users, amount)?; // Still owed to users while locked
+    let tag = InstructionTag::CreateEscrow;
+    let entry = ActivityEntry::new(ActivityKind::Sent, tag, Pubkey::default(), amount, now); // No one holds it yet
+    log_activity(&ctx.accounts.creator_activity, creator.key(), entry)?;
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.creator = creator.key();
+    escrow.creator_authority = ctx.accounts.authority.key();
+    escrow.beneficiary = beneficiary;
+    escrow.amount = amount;
+    escrow.unlock_at = unlock_at;
+    escrow.status = EscrowStatus::Locked;
+    escrow.bump = ctx.bumps.escrow;
+
+    emit!(EscrowCreatedEvent {
+        escrow: escrow.key(),
+        creator: escrow.creator,
+        beneficiary,
+        amount,
+        unlock_at,
+        tag: InstructionTag::CreateEscrow,
+    });
+
+    msg!("Escrowed {} until {}", amount, unlock_at);
+    Ok(())
+}
+
+/// Release an unlocked escrow to the beneficiary
+pub fn handle_claim_escrow(ctx: Context<ClaimEscrow>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::CLAIM_ESCROW)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let escrow = &mut ctx.accounts.escrow;
+    require!(escrow.is_unlocked(now), CustomError::EscrowLocked);
+    escrow.status = escrow_transition(escrow.status, EscrowAction::Claim)?;
+
+    require_distinct_accounts(&[ctx.accounts.creator.key(), ctx.accounts.beneficiary.key()])?;
+    settle_obligation(&mut ctx.accounts.creator, InstructionTag::ClaimEscrow)?;
+
+    let beneficiary = &mut ctx.accounts.beneficiary;
+    require_transfer_modes(AccountMode::Normal, beneficiary.mode.for_settlement())?; // Claiming is an inflow
+    beneficiary.balance = math::add(beneficiary.balance, escrow.amount)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Users, escrow.amount)?;
+    let tag = InstructionTag::ClaimEscrow;
+    let entry = ActivityEntry::new(
+        ActivityKind::Received,
+        tag,
+        escrow.creator,
+        escrow.amount,
+        now,
+    );
+    log_activity(&ctx.accounts.beneficiary_activity, beneficiary.key(), entry)?;
+
+    emit!(EscrowClaimedEvent {
+        escrow: escrow.key(),
+        beneficiary: beneficiary.key(),
+        amount: escrow.amount,
+        timestamp: now,
+        tag: InstructionTag::ClaimEscrow,
+    });
+
+    msg!("Escrow of {} claimed", escrow.amount);
+    Ok(())
+}
+
+/// Refund a still-locked escrow to its creator
+pub fn handle_cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::CREATE_ESCROW)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let escrow = &mut ctx.accounts.escrow;
+    require!(!escrow.is_unlocked(now), CustomError::EscrowUnlocked);
+    escrow.status = escrow_transition(escrow.status, EscrowAction::Cancel)?;
+
+    let creator = &mut ctx.accounts.creator;
+    creator.balance = math::add(creator.balance, escrow.amount)?; // The creator's own funds, in any mode
+    settle_obligation(creator, InstructionTag::CancelEscrow)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Users, escrow.amount)?;
+    let tag = InstructionTag::CancelEscrow;
+    let entry = ActivityEntry::new(
+        ActivityKind::Received,
+        tag,
+        Pubkey::default(),
+        escrow.amount,
+        now,
+    );
+    log_activity(&ctx.accounts.creator_activity, creator.key(), entry)?;
+
+    emit!(EscrowCancelledEvent {
+        escrow: escrow.key(),
+        creator: creator.key(),
+        amount: escrow.amount,
+        timestamp: now,
+        tag: InstructionTag::CancelEscrow,
+    });
+
+    msg!("Escrow of {} cancelled", escrow.amount);
+    Ok(())
+}
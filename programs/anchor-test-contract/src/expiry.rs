@@ -0,0 +1,455 @@
+/*
+ * ============================================================================
+ * EXPIRY SWEEPING
+ * ============================================================================
+ *
+ * Time-bound accounts outlive their deadline until someone retires them.
+ * `expire` is a permissionless crank that moves any expired account to its
+ * terminal state and returns its rent to whoever paid it. Each expirable
+ * type registers a handler in `EXPIRY_HANDLERS`, keyed by discriminator;
+ * the handler validates the account, decides whether it has expired, and
+ * names the rent recipient. New time-bound types only add an entry.
+ *
+ * A retired account may leave state behind in other accounts: the open
+ * obligation an invoice counts against its payer, say. Those accounts
+ * follow as remaining accounts, in the order the handler documents, and
+ * the handler validates and updates them before the account is closed.
+ *
+ * Registered today:
+ * - `Commitment`: unrevealed past `expires_at`; rent to the committer
+ * - `Attestation`: past a non-zero `expires_at`; rent to the issuer
+ * - `Auction`: still open with no bids past `reveal_end`, cancelled; rent
+ *   to the seller (auctions with bids are settled instead)
+ * - `ManagerGrant`: past `expires_at`; rent to the granting authority
+ * - `PromoCode`: past `expires_at`; rent to the creating admin
+ * - `CapabilityToken`: past `expires_at`; rent to the issuing authority
+ * - `Invoice`: unpaid past `INVOICE_PAYMENT_WINDOW`; settles the payer's
+ *   obligation and the template's unpaid count; rent to the issuing crank
+ * - `Escrow`: unclaimed past `ESCROW_CLAIM_WINDOW`; refunds the creator,
+ *   logging it at the creator's activity log address, and settles its
+ *   obligation; rent to the creator's authority
+ */
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+use crate::math;
+use crate::{
+    auction_transition, escrow_transition, instruction_flags, invoice_transition, log_activity,
+    require_instruction_enabled, require_log_address, settle_obligation_of, ActivityEntry,
+    ActivityKind, Attestation, Auction, AuctionAction, AuctionStatus, CapabilityToken,
+    ClockTolerance, Commitment, CustomError, Escrow, EscrowAction, EscrowCancelledEvent,
+    GlobalConfig, InstructionTag, Invoice, InvoiceAction, InvoiceTemplate, LamportAmount, Ledger,
+    LedgerAccount, ManagerGrant, PromoCode, RemainingAccountsValidator, UserAccount,
+};
+
+/// Time-bound account types `expire` can retire
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExpirableKind {
+    Commitment,      // Lapsed commit-reveal commitment
+    Attestation,     // Attestation past its expiry
+    Auction,         // Auction that ended without bids
+    ManagerGrant,    // Lapsed manager delegation
+    PromoCode,       // Promo code past its expiry
+    CapabilityToken, // Lapsed service capability
+    Invoice,         // Invoice left unpaid
+    Escrow,          // Escrow left unclaimed
+}
+
+/// Outcome of an expiry handler for an expired account
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Expiry {
+    pub kind: ExpirableKind,    // Type of the retired account
+    pub rent_recipient: Pubkey, // Who paid the account's rent
+}
+
+/// Validate an account of one type under `config` and check it has expired
+/// at `now`, judging its deadline with the namespace's clock tolerance, then
+/// settle what it leaves behind in the remaining accounts
+pub type ExpiryHandler =
+    fn(&AccountInfo, &[AccountInfo], &Pubkey, i64, ClockTolerance) -> Result<Expiry>;
+
+/// Registered expiry handlers keyed by account discriminator
+pub const EXPIRY_HANDLERS: [([u8; 8], ExpiryHandler); 8] = [
+    (Commitment::DISCRIMINATOR, expire_commitment),
+    (Attestation::DISCRIMINATOR, expire_attestation),
+    (Auction::DISCRIMINATOR, expire_auction),
+    (ManagerGrant::DISCRIMINATOR, expire_manager_grant),
+    (PromoCode::DISCRIMINATOR, expire_promo_code),
+    (CapabilityToken::DISCRIMINATOR, expire_capability_token),
+    (Invoice::DISCRIMINATOR, expire_invoice),
+    (Escrow::DISCRIMINATOR, expire_escrow),
+];
+
+/// Handler registered for the account type of `data`
+pub fn expiry_handler(data: &[u8]) -> Option<ExpiryHandler> {
+    let discriminator = data.get(..8)?;
+    EXPIRY_HANDLERS
+        .iter()
+        .find(|(registered, _)| registered == discriminator)
+        .map(|(_, handler)| *handler)
+}
+
+fn expire_commitment(
+    info: &AccountInfo,
+    _settled: &[AccountInfo],
+    config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    let commitment = RemainingAccountsValidator::default().load(info, |c: &Commitment| {
+        vec![
+            b"commitment".to_vec(),
+            config.to_bytes().to_vec(),
+            c.owner.to_bytes().to_vec(),
+            c.domain.to_le_bytes().to_vec(),
+        ]
+    })?;
+    // Revealed commitments are read by their consumer and are not retired here
+    require!(
+        !commitment.revealed && tolerance.passed(now, commitment.expires_at),
+        CustomError::NotExpired
+    );
+    Ok(Expiry {
+        kind: ExpirableKind::Commitment,
+        rent_recipient: commitment.owner,
+    })
+}
+
+fn expire_attestation(
+    info: &AccountInfo,
+    _settled: &[AccountInfo],
+    _config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    // The user in the seeds is itself a PDA of the namespace
+    let attestation = RemainingAccountsValidator::default().load(info, |a: &Attestation| {
+        vec![
+            b"attestation".to_vec(),
+            a.issuer.to_bytes().to_vec(),
+            a.user.to_bytes().to_vec(),
+            a.claim_type.to_le_bytes().to_vec(),
+        ]
+    })?;
+    require!(
+        attestation.expires_at != 0 && tolerance.passed(now, attestation.expires_at),
+        CustomError::NotExpired
+    );
+    Ok(Expiry {
+        kind: ExpirableKind::Attestation,
+        rent_recipient: attestation.issuer,
+    })
+}
+
+fn expire_auction(
+    info: &AccountInfo,
+    _settled: &[AccountInfo],
+    config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    let auction = RemainingAccountsValidator::default().load(info, |a: &Auction| {
+        vec![
+            b"auction".to_vec(),
+            config.to_bytes().to_vec(),
+            a.seller.to_bytes().to_vec(),
+            a.auction_id.to_le_bytes().to_vec(),
+        ]
+    })?;
+    require!(
+        auction.status == AuctionStatus::Open
+            && auction.bid_count == 0
+            && tolerance.passed(now, auction.reveal_end),
+        CustomError::NotExpired
+    );
+    auction_transition(auction.status, AuctionAction::Cancel)?; // Same terminal state as cancel_auction
+    Ok(Expiry {
+        kind: ExpirableKind::Auction,
+        rent_recipient: auction.seller,
+    })
+}
+
+fn expire_manager_grant(
+    info: &AccountInfo,
+    _settled: &[AccountInfo],
+    _config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    // The user in the seeds is itself a PDA of the namespace
+    let grant = RemainingAccountsValidator::default().load(info, |g: &ManagerGrant| {
+        vec![
+            b"manager_grant".to_vec(),
+            g.user.to_bytes().to_vec(),
+            g.manager.to_bytes().to_vec(),
+        ]
+    })?;
+    require!(
+        tolerance.passed(now, grant.expires_at),
+        CustomError::NotExpired
+    );
+    Ok(Expiry {
+        kind: ExpirableKind::ManagerGrant,
+        rent_recipient: grant.authority,
+    })
+}
+
+fn expire_promo_code(
+    info: &AccountInfo,
+    _settled: &[AccountInfo],
+    config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    let promo_code = RemainingAccountsValidator::default().load(info, |p: &PromoCode| {
+        vec![
+            b"promo_code".to_vec(),
+            config.to_bytes().to_vec(),
+            p.code_hash.to_vec(),
+        ]
+    })?;
+    require!(
+        tolerance.passed(now, promo_code.expires_at),
+        CustomError::NotExpired
+    );
+    Ok(Expiry {
+        kind: ExpirableKind::PromoCode,
+        rent_recipient: promo_code.creator,
+    })
+}
+
+fn expire_capability_token(
+    info: &AccountInfo,
+    _settled: &[AccountInfo],
+    _config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    // The user in the seeds is itself a PDA of the namespace
+    let token = RemainingAccountsValidator::default().load(info, |t: &CapabilityToken| {
+        vec![
+            b"capability".to_vec(),
+            t.user.to_bytes().to_vec(),
+            t.service.to_bytes().to_vec(),
+        ]
+    })?;
+    require!(token.is_expired(now, tolerance), CustomError::NotExpired);
+    Ok(Expiry {
+        kind: ExpirableKind::CapabilityToken,
+        rent_recipient: token.authority,
+    })
+}
+
+/// Remaining accounts: the payer's user account, then the invoice's template
+fn expire_invoice(
+    info: &AccountInfo,
+    settled: &[AccountInfo],
+    config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    // The template in the seeds ties the invoice to the namespace
+    let mut validator = RemainingAccountsValidator::with_capacity(3);
+    let invoice = validator.load(info, |i: &Invoice| {
+        vec![
+            b"invoice".to_vec(),
+            i.template.to_bytes().to_vec(),
+            i.period.to_le_bytes().to_vec(),
+        ]
+    })?;
+    require!(
+        tolerance.passed(now, invoice.expires_at()),
+        CustomError::NotExpired
+    );
+
+    invoice_transition(invoice.status, InvoiceAction::Expire)?; // Closed below
+
+    let [payer_info, template_info] = settled else {
+        return err!(ErrorCode::AccountNotEnoughKeys);
+    };
+    require_keys_eq!(*payer_info.key, invoice.payer, ErrorCode::ConstraintHasOne);
+    require_keys_eq!(
+        *template_info.key,
+        invoice.template,
+        ErrorCode::ConstraintHasOne
+    );
+    let mut payer = validator.load(payer_info, |u: &UserAccount| {
+        vec![
+            b"user".to_vec(),
+            config.to_bytes().to_vec(),
+            u.authority.to_bytes().to_vec(),
+        ]
+    })?;
+    let mut template = validator.load(template_info, |t: &InvoiceTemplate| {
+        vec![
+            b"invoice_template".to_vec(),
+            t.merchant.to_bytes().to_vec(),
+            t.payer.to_bytes().to_vec(),
+        ]
+    })?;
+
+    settle_obligation_of(&mut payer, invoice.payer, InstructionTag::Expire)?;
+    template.record_paid()?; // No longer outstanding
+    store(payer_info, &payer)?;
+    store(template_info, &template)?;
+
+    Ok(Expiry {
+        kind: ExpirableKind::Invoice,
+        rent_recipient: invoice.rent_payer,
+    })
+}
+
+/// Remaining accounts: the creator's user account, then the ledger
+fn expire_escrow(
+    info: &AccountInfo,
+    settled: &[AccountInfo],
+    config: &Pubkey,
+    now: i64,
+    tolerance: ClockTolerance,
+) -> Result<Expiry> {
+    let mut validator = RemainingAccountsValidator::with_capacity(3);
+    let escrow = validator.load(info, |e: &Escrow| {
+        vec![
+            b"escrow".to_vec(),
+            config.to_bytes().to_vec(),
+            e.creator.to_bytes().to_vec(),
+            e.beneficiary.to_bytes().to_vec(),
+            e.unlock_at.to_le_bytes().to_vec(),
+        ]
+    })?;
+    require!(
+        tolerance.passed(now, escrow.claim_deadline()),
+        CustomError::NotExpired
+    );
+
+    escrow_transition(escrow.status, EscrowAction::Expire)?; // Closed below
+
+    let [creator_info, ledger_info, log_info] = settled else {
+        return err!(ErrorCode::AccountNotEnoughKeys);
+    };
+    require_keys_eq!(
+        *creator_info.key,
+        escrow.creator,
+        ErrorCode::ConstraintHasOne
+    );
+    let mut creator = validator.load(creator_info, |u: &UserAccount| {
+        vec![
+            b"user".to_vec(),
+            config.to_bytes().to_vec(),
+            u.authority.to_bytes().to_vec(),
+        ]
+    })?;
+    let mut ledger = validator.load(ledger_info, |_: &Ledger| {
+        vec![b"ledger".to_vec(), config.to_bytes().to_vec()]
+    })?;
+
+    creator.balance = math::add(creator.balance, escrow.amount)?; // The creator's own funds, in any mode
+    settle_obligation_of(&mut creator, escrow.creator, InstructionTag::Expire)?;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, escrow.amount)?;
+    store(creator_info, &creator)?;
+    store(ledger_info, &ledger)?;
+    require_log_address(log_info, &escrow.creator)?;
+    let entry = ActivityEntry::new(
+        ActivityKind::Received,
+        InstructionTag::Expire,
+        Pubkey::default(),
+        escrow.amount,
+        now,
+    );
+    log_activity(log_info, escrow.creator, entry)?;
+
+    emit!(EscrowCancelledEvent {
+        escrow: *info.key,
+        creator: escrow.creator,
+        amount: escrow.amount,
+        timestamp: now,
+        tag: InstructionTag::Expire,
+    });
+
+    Ok(Expiry {
+        kind: ExpirableKind::Escrow,
+        rent_recipient: escrow.creator_authority,
+    })
+}
+
+/// Write a settled remaining account back
+fn store<T: AccountSerialize>(info: &AccountInfo, account: &T) -> Result<()> {
+    require!(info.is_writable, ErrorCode::ConstraintMut);
+    account.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+/// Context for expiring a time-bound account
+///
+/// Remaining accounts: whatever the account's handler settles.
+#[derive(Accounts)]
+pub struct Expire<'info> {
+    /// Account past its deadline
+    /// CHECK: type, address and expiry are verified by its registered handler
+    #[account(mut, owner = crate::ID @ CustomError::NotExpirable)]
+    pub account: UncheckedAccount<'info>,
+
+    /// Namespace config the account belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Receives the reclaimed rent
+    /// CHECK: must be the recipient named by the handler
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+}
+
+/// Event emitted when an expired account is retired
+#[event]
+pub struct AccountExpiredEvent {
+    pub account: Pubkey,        // Retired account
+    pub kind: ExpirableKind,    // Type of the account
+    pub rent_recipient: Pubkey, // Who received the rent
+    pub refunded: u64,          // LamportAmount returned
+    pub timestamp: i64,         // When it was retired
+    pub tag: InstructionTag,    // Emitting instruction
+}
+
+/// Retire an expired time-bound account through its registered handler
+pub fn handle_expire(ctx: Context<Expire>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::EXPIRE)?;
+
+    let info = ctx.accounts.account.to_account_info();
+    let now = Clock::get()?.unix_timestamp;
+    let handler = expiry_handler(&info.try_borrow_data()?).ok_or(CustomError::NotExpirable)?;
+    let config = &ctx.accounts.config;
+    let expiry = handler(
+        &info,
+        ctx.remaining_accounts,
+        &config.key(),
+        now,
+        config.clock_tolerance(),
+    )?;
+
+    let recipient = ctx.accounts.rent_recipient.to_account_info();
+    require_keys_eq!(
+        expiry.rent_recipient,
+        recipient.key(),
+        CustomError::WrongRentRecipient
+    );
+
+    // Close: hand back every lamport and return the account to the system program
+    let refunded = info.lamports();
+    math::move_lamports(&info, &recipient, LamportAmount(refunded))?;
+    info.assign(&system_program::ID);
+    info.realloc(0, false)?;
+
+    emit!(AccountExpiredEvent {
+        account: info.key(),
+        kind: expiry.kind,
+        rent_recipient: expiry.rent_recipient,
+        refunded,
+        timestamp: now,
+        tag: InstructionTag::Expire,
+    });
+
+    msg!("Expired {:?} {}", expiry.kind, info.key());
+    Ok(())
+}
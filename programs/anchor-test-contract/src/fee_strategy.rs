@@ -0,0 +1,129 @@
+/*
+ * ============================================================================
+ * FEE STRATEGIES
+ * ============================================================================
+ *
+ * How a namespace prices a transfer is a `FeeStrategy` stored in its config
+ * and switched by the admin with `set_fee_strategy`:
+ * - Proportional: the config's (possibly ramping) `fee_rate_bps`
+ * - Flat: the same fee on every transfer, capped at the amount
+ * - Tiered: a rate chosen by the largest threshold the amount reaches
+ * - Utilization: a rate interpolated between two bounds as the namespace's
+ *   epoch volume approaches its trailing average
+ * - Discounted: the proportional rate less a fixed share of it
+ *
+ * `fee_for` is a pure function of the strategy and `FeeInputs`, so handlers
+ * only gather inputs and every strategy can be checked in isolation.
+ * Fees never exceed the amount they are charged on.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::math;
+use crate::{Bps, CustomError};
+
+/// Number of rate tiers a tiered strategy holds
+pub const FEE_TIERS: usize = 4;
+
+/// One step of a tiered fee schedule
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeTier {
+    pub min_amount: u64, // Smallest amount the tier applies to (u64::MAX = unused)
+    pub fee_bps: u64,    // Rate charged within the tier
+}
+
+impl FeeTier {
+    /// Placeholder for tiers a schedule does not use
+    pub const UNUSED: FeeTier = FeeTier {
+        min_amount: u64::MAX,
+        fee_bps: 0,
+    };
+}
+
+/// How transfer fees are computed in a namespace
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FeeStrategy {
+    #[default]
+    Proportional, // Config `fee_rate_bps`
+    Flat {
+        fee: u64,
+    }, // Fixed fee per transfer
+    Tiered {
+        tiers: [FeeTier; FEE_TIERS],
+    }, // Ascending `min_amount` thresholds
+    Utilization {
+        min_bps: u64,
+        max_bps: u64,
+    }, // Rate at 0% and 100% utilization
+    Discounted {
+        discount_bps: u64,
+    }, // Share of the proportional rate waived
+}
+
+/// What a strategy may base a fee on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeInputs {
+    pub amount: u64,      // Amount being transferred
+    pub base_rate: Bps,   // Config fee rate effective now
+    pub utilization: Bps, // Epoch volume over trailing average, capped at 100%
+}
+
+impl FeeStrategy {
+    /// Serialized size of the largest variant
+    pub const LEN: usize = 1 + // Variant index
+        FEE_TIERS * (8 + 8); // tiers: [FeeTier; FEE_TIERS]
+
+    /// Check the strategy's parameters before it is stored
+    pub fn validate(&self) -> Result<()> {
+        let valid = match *self {
+            FeeStrategy::Proportional | FeeStrategy::Flat { .. } => true,
+            FeeStrategy::Tiered { tiers } => {
+                tiers.iter().all(|tier| Bps(tier.fee_bps).is_fraction())
+                    && tiers.windows(2).all(|w| w[0].min_amount <= w[1].min_amount)
+            }
+            FeeStrategy::Utilization { min_bps, max_bps } => {
+                min_bps <= max_bps && Bps(max_bps).is_fraction()
+            }
+            FeeStrategy::Discounted { discount_bps } => Bps(discount_bps).is_fraction(),
+        };
+        require!(valid, CustomError::InvalidFeeStrategy);
+        Ok(())
+    }
+}
+
+/// Utilization of `capacity` by `volume`, capped at 100% (0 capacity = idle)
+pub fn utilization(volume: u64, capacity: u64) -> Bps {
+    if capacity == 0 {
+        return Bps(0);
+    }
+    let ratio = volume as u128 * Bps::ONE_HUNDRED_PERCENT.get() as u128 / capacity as u128;
+    Bps(ratio.min(Bps::ONE_HUNDRED_PERCENT.get() as u128) as u64)
+}
+
+/// Fee `strategy` charges on `inputs.amount`, never more than the amount
+pub fn fee_for(strategy: &FeeStrategy, inputs: &FeeInputs) -> Result<u64> {
+    let rate = match *strategy {
+        FeeStrategy::Flat { fee } => return Ok(fee.min(inputs.amount)),
+        FeeStrategy::Proportional => inputs.base_rate,
+        FeeStrategy::Tiered { tiers } => tiers
+            .iter()
+            .rev()
+            .filter(|tier| **tier != FeeTier::UNUSED)
+            .find(|tier| tier.min_amount <= inputs.amount)
+            .map_or(Bps(0), |tier| Bps(tier.fee_bps)), // Below every tier is free
+        FeeStrategy::Utilization { min_bps, max_bps } => {
+            let spread = math::sub(max_bps, min_bps)?;
+            let extra = inputs
+                .utilization
+                .min(Bps::ONE_HUNDRED_PERCENT)
+                .of(spread, Rounding::Down)?;
+            Bps(math::add(min_bps, extra)?)
+        }
+        FeeStrategy::Discounted { discount_bps } => {
+            let waived = Bps(discount_bps).of(inputs.base_rate.get(), Rounding::Up)?;
+            Bps(inputs.base_rate.get().saturating_sub(waived))
+        }
+    };
+    Ok(rate.of(inputs.amount, Rounding::Down)?.min(inputs.amount))
+}
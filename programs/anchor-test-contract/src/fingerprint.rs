@@ -0,0 +1,143 @@
+/*
+ * ============================================================================
+ * CONFIGURATION FINGERPRINT
+ * ============================================================================
+ *
+ * `program_fingerprint` hashes everything governance can change about a
+ * namespace into one digest, returned as return data and emitted in a
+ * `ProgramFingerprintEvent`. An integrator pins the digest of a
+ * configuration it has reviewed and re-checks it before relying on the
+ * namespace; any admin change shows up as a different digest without the
+ * integrator tracking individual fields. The digest covers:
+ * - the whole `GlobalConfig`, including the `disabled_instructions` and
+ *   `paused` kill switches, the fee strategy and the clock tolerance
+ * - the `build_features` compiled into the program
+ * - the CPI target registry, order-independent, or its absence
+ *
+ * The hash is SHA-256 over `FINGERPRINT_DOMAIN`, the feature bits, the
+ * borsh-encoded config and the sorted registry, so clients can reproduce
+ * it from the accounts with `config_fingerprint`. Growing the config
+ * changes every digest, which is intended: a new field is a new setting to
+ * review.
+ *
+ * The view reads only and is not behind the kill switch; anyone may call
+ * it, since everything in it is already public on chain.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{CpiProgramRegistry, GlobalConfig, InstructionTag};
+
+/// Domain prefix hashed before every fingerprint, versioned with the format
+pub const FINGERPRINT_DOMAIN: &[u8] = b"anchor-test-contract:config-fingerprint:v1\n";
+
+/// Bits of optional cargo features compiled into the program
+pub mod build_features {
+    pub const SHADOW_FEES: u64 = 1 << 0; // `shadow-fees`
+    pub const CHAOS: u64 = 1 << 1; // `chaos`, never deployed
+    pub const BOOTSTRAP_DEMO: u64 = 1 << 2; // `bootstrap-demo`, localnet only
+    pub const FIXTURES: u64 = 1 << 3; // `fixtures`, test only
+}
+
+/// `build_features` this build was compiled with
+pub fn compiled_features() -> u64 {
+    [
+        (build_features::SHADOW_FEES, cfg!(feature = "shadow-fees")),
+        (build_features::CHAOS, cfg!(feature = "chaos")),
+        (
+            build_features::BOOTSTRAP_DEMO,
+            cfg!(feature = "bootstrap-demo"),
+        ),
+        (build_features::FIXTURES, cfg!(feature = "fixtures")),
+    ]
+    .into_iter()
+    .filter(|&(_, compiled)| compiled)
+    .fold(0, |features, (feature, _)| features | feature)
+}
+
+/// Digest of `config`, the `features` compiled in and the allowed CPI
+/// targets, `None` when the namespace has no registry
+pub fn config_fingerprint(
+    config: &GlobalConfig,
+    features: u64,
+    cpi_programs: Option<&[Pubkey]>,
+) -> [u8; 32] {
+    let mut config_bytes = Vec::with_capacity(GlobalConfig::LEN);
+    config
+        .serialize(&mut config_bytes)
+        .expect("serializing into a Vec cannot fail");
+
+    let registry: Vec<u8> = match cpi_programs {
+        None => vec![0], // No registry, distinct from an empty one
+        Some(programs) => {
+            // Revoking swaps entries around, so the order carries no meaning
+            let mut sorted = programs.to_vec();
+            sorted.sort();
+            std::iter::once(1)
+                .chain(sorted.into_iter().flat_map(Pubkey::to_bytes))
+                .collect()
+        }
+    };
+
+    hashv(&[
+        FINGERPRINT_DOMAIN,
+        &features.to_le_bytes(),
+        &config_bytes,
+        &registry,
+    ])
+    .to_bytes()
+}
+
+/// Context for fingerprinting a namespace's configuration
+#[derive(Accounts)]
+pub struct ProgramFingerprint<'info> {
+    /// Namespace config fingerprinted
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// The namespace's CPI registry, omitted only when it has none
+    #[account(seeds = [b"cpi_registry", config.key().as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, CpiProgramRegistry>>,
+}
+
+/// Event carrying a namespace's configuration fingerprint
+#[event]
+pub struct ProgramFingerprintEvent {
+    pub config: Pubkey,             // Namespace config fingerprinted
+    pub fingerprint: [u8; 32],      // `config_fingerprint` digest
+    pub build_features: u64,        // `build_features` compiled in
+    pub disabled_instructions: u64, // Kill switches at the time
+    pub paused: bool,               // Whether the namespace was paused
+    pub cpi_programs: u32,          // Allowed CPI targets, 0 without a registry
+    pub tag: InstructionTag,        // Emitting instruction
+}
+
+/// Fingerprint the namespace's configuration, emitting and returning it
+pub fn handle_program_fingerprint(ctx: Context<ProgramFingerprint>) -> Result<[u8; 32]> {
+    let config = &ctx.accounts.config;
+    let features = compiled_features();
+    let cpi_programs = ctx
+        .accounts
+        .registry
+        .as_ref()
+        .map(|registry| registry.programs.as_slice());
+    let fingerprint = config_fingerprint(config, features, cpi_programs);
+
+    emit!(ProgramFingerprintEvent {
+        config: config.key(),
+        fingerprint,
+        build_features: features,
+        disabled_instructions: config.disabled_instructions,
+        paused: config.paused,
+        cpi_programs: cpi_programs.map_or(0, |programs| programs.len() as u32),
+        tag: InstructionTag::ProgramFingerprint,
+    });
+
+    let hex: String = fingerprint
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    msg!("Fingerprint of namespace {}: {}", config.namespace, hex);
+    Ok(fingerprint)
+}
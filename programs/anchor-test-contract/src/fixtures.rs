@@ -0,0 +1,271 @@
+/*
+ * ============================================================================
+ * TEST FIXTURES
+ * ============================================================================
+ *
+ * Deterministic account fixtures for `program-test` and fuzzing harnesses.
+ * Every fixture is derived from a small integer seed, lives at its real PDA
+ * with the canonical bump, and is serialized exactly as the program would
+ * write it (discriminator, Borsh body, zero padding up to `LEN`).
+ *
+ * Only compiled with the `fixtures` feature; never enable it for deployed
+ * builds.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{
+    AccountMode, EmissionSchedule, EpochStats, FeeStrategy, FeeTreasury, GlobalConfig,
+    JointAccount, JointMode, Ledger, LedgerAccount, MintConfig, RampedParam, RecentOperations,
+    UserAccount, BPS_DENOMINATOR, DEFAULT_CLOCK_TOLERANCE, DEFAULT_FEE_RATE_BPS,
+    DEFAULT_REACTIVATION_COOLDOWN, MAX_GUARDIANS, MAX_VERSIONED_INSTRUCTIONS,
+    RECENT_OPERATIONS_CAPACITY, USER_ACCOUNT_VERSION,
+};
+
+/// Timestamp all fixtures treat as "now"
+pub const FIXTURE_NOW: i64 = 1_700_000_000;
+
+/// Namespace every fixture belongs to
+pub const FIXTURE_NAMESPACE: u64 = 0;
+
+/// A ready-to-load program account
+pub struct Fixture {
+    pub address: Pubkey, // Account address (PDA for program accounts)
+    pub lamports: u64,   // Rent-exempt minimum for `data`
+    pub data: Vec<u8>,   // Serialized account data
+    pub owner: Pubkey,   // Always this program
+}
+
+/// Deterministic pubkey for `label` and `seed`
+pub fn fixture_key(label: &str, seed: u64) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[label.as_bytes(), &seed.to_le_bytes()]).to_bytes())
+}
+
+/// Serialize `account` into a buffer of exactly `space` bytes
+pub fn serialize_account<T: AccountSerialize>(account: &T, space: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(space);
+    account
+        .try_serialize(&mut data)
+        .expect("fixture serialization cannot fail");
+    assert!(data.len() <= space, "fixture exceeds its account space");
+    data.resize(space, 0); // Unused tail is zeroed like a fresh allocation
+    data
+}
+
+fn fixture<T: AccountSerialize>(address: Pubkey, account: &T, space: usize) -> Fixture {
+    Fixture {
+        address,
+        lamports: Rent::default().minimum_balance(space),
+        data: serialize_account(account, space),
+        owner: crate::ID,
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &crate::ID)
+}
+
+/// Config PDA of the fixture namespace
+pub fn config_address() -> Pubkey {
+    pda(&[b"config", &FIXTURE_NAMESPACE.to_le_bytes()]).0
+}
+
+/*
+ * Users
+ */
+
+/// Lifecycle states a fixture user can be generated in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserState {
+    Active,     // Plain active account
+    Inactive,   // Deactivated account
+    Joint,      // Converted to a joint account
+    OnVacation, // Inside a scheduled vacation window
+}
+
+/// Authority of fixture user `seed`
+pub fn user_authority(seed: u64) -> Pubkey {
+    fixture_key("user", seed)
+}
+
+/// User account for `seed` in `state` holding `balance`
+pub fn user(seed: u64, state: UserState, balance: u64) -> Fixture {
+    let authority = user_authority(seed);
+    let (address, _) = pda(&[b"user", config_address().as_ref(), authority.as_ref()]);
+    let on_vacation = state == UserState::OnVacation;
+    let account = UserAccount {
+        authority,
+        name: format!("user-{seed}"),
+        age: 18 + (seed % 60) as u8,
+        balance,
+        mode: if state == UserState::Inactive {
+            AccountMode::Locked
+        } else {
+            AccountMode::Normal
+        },
+        created_at: FIXTURE_NOW - 86_400,
+        is_joint: state == UserState::Joint,
+        vacation_start: if on_vacation { FIXTURE_NOW - 3_600 } else { 0 },
+        vacation_end: if on_vacation { FIXTURE_NOW + 3_600 } else { 0 },
+        compliance_hold: false,
+        deactivated_at: 0,
+        guardians: [Pubkey::default(); MAX_GUARDIANS],
+        guardian_threshold: 0,
+        guardian_limit: 0,
+        version: USER_ACCOUNT_VERSION,
+        daily_limit: 0,
+        spent_today: 0,
+        window_start: 0,
+        pending_daily_limit: 0,
+        pending_limit_at: 0,
+        watchtower: Pubkey::default(),
+        alert_until: 0,
+        open_obligations: 0,
+        sequence: 0,
+        uncounted_obligations: false,
+    };
+    fixture(address, &account, UserAccount::LEN)
+}
+
+/// Joint record for fixture user `seed` shared with user `co_seed`
+pub fn joint(seed: u64, co_seed: u64, threshold: u64, mode: JointMode) -> Fixture {
+    let (user, _) = pda(&[
+        b"user",
+        config_address().as_ref(),
+        user_authority(seed).as_ref(),
+    ]);
+    let (address, bump) = pda(&[b"joint", user.as_ref()]);
+    let account = JointAccount {
+        user,
+        primary: user_authority(seed),
+        secondary: user_authority(co_seed),
+        threshold,
+        mode,
+        bump,
+    };
+    fixture(address, &account, JointAccount::LEN)
+}
+
+/// Recent-operations buffer for fixture user `seed` with `recorded` keys filled in
+pub fn recent_operations(seed: u64, recorded: usize) -> Fixture {
+    let (user, _) = pda(&[
+        b"user",
+        config_address().as_ref(),
+        user_authority(seed).as_ref(),
+    ]);
+    let (address, bump) = pda(&[b"recent_ops", user.as_ref()]);
+    let recorded = recorded.min(RECENT_OPERATIONS_CAPACITY);
+    let mut account = RecentOperations {
+        user,
+        head: (recorded % RECENT_OPERATIONS_CAPACITY) as u8,
+        keys: [[0; 16]; RECENT_OPERATIONS_CAPACITY],
+        recorded_at: [0; RECENT_OPERATIONS_CAPACITY],
+        bump,
+    };
+    for slot in 0..recorded {
+        account.keys[slot] = [slot as u8 + 1; 16];
+        account.recorded_at[slot] = FIXTURE_NOW - slot as i64;
+    }
+    fixture(address, &account, RecentOperations::LEN)
+}
+
+/*
+ * Protocol singletons
+ */
+
+/// Admin key used by the config fixture
+pub fn admin() -> Pubkey {
+    fixture_key("admin", 0)
+}
+
+/// Global config with default parameters and the given disabled mask
+pub fn config(disabled_instructions: u64) -> Fixture {
+    let (address, bump) = pda(&[b"config", &FIXTURE_NAMESPACE.to_le_bytes()]);
+    let account = GlobalConfig {
+        admin: admin(),
+        disabled_instructions,
+        bump,
+        fee_rate_bps: RampedParam::fixed(DEFAULT_FEE_RATE_BPS),
+        circuit_breaker_multiple_bps: 0,
+        max_price_deviation_bps: 0,
+        price_deviation_window_secs: 0,
+        lookup_table: Pubkey::default(),
+        namespace: FIXTURE_NAMESPACE,
+        fee_strategy: FeeStrategy::Proportional,
+        emission_schedule: EmissionSchedule::default(),
+        compliance: admin(),
+        paused: false,
+        reactivation_cooldown_secs: DEFAULT_REACTIVATION_COOLDOWN.get(),
+        clock_tolerance_secs: DEFAULT_CLOCK_TOLERANCE.get(),
+        instruction_sunsets: [0; MAX_VERSIONED_INSTRUCTIONS],
+    };
+    fixture(address, &account, GlobalConfig::LEN)
+}
+
+/// Epoch stats for `epoch`, optionally with the breaker tripped
+pub fn epoch_stats(epoch: u64, volume: u64, trailing_average: u64, tripped: bool) -> Fixture {
+    let (address, bump) = pda(&[b"epoch_stats", config_address().as_ref()]);
+    let account = EpochStats {
+        epoch,
+        volume,
+        trailing_average,
+        tripped,
+        tripped_at: if tripped { FIXTURE_NOW } else { 0 },
+        bump,
+    };
+    fixture(address, &account, EpochStats::LEN)
+}
+
+/// Balanced ledger with `user_volume` posted between user accounts
+pub fn ledger(user_volume: u64, entries: u64) -> Fixture {
+    let (address, bump) = pda(&[b"ledger", config_address().as_ref()]);
+    let mut account = Ledger {
+        debits: Default::default(),
+        credits: Default::default(),
+        entries,
+        bump,
+    };
+    account.debits[LedgerAccount::Users as usize] = user_volume;
+    account.credits[LedgerAccount::Users as usize] = user_volume;
+    fixture(address, &account, Ledger::LEN)
+}
+
+/// Fee treasury holding `collected` in unwithdrawn fees
+///
+/// A consistent ledger has `collected` posted from Users to Fees.
+pub fn fee_treasury(collected: u64) -> Fixture {
+    let (address, bump) = pda(&[b"fee_treasury", config_address().as_ref()]);
+    let account = FeeTreasury {
+        balance: collected,
+        total_collected: collected,
+        bump,
+    };
+    fixture(address, &account, FeeTreasury::LEN)
+}
+
+/// Mint registry entry for fixture mint `seed` filled to `utilization_bps` of its cap
+pub fn mint_config(
+    seed: u64,
+    decimals: u8,
+    max_total_deposits: u64,
+    utilization_bps: u64,
+) -> Fixture {
+    let mint = fixture_key("mint", seed);
+    let (address, bump) = pda(&[b"mint_config", config_address().as_ref(), mint.as_ref()]);
+    let total_deposited =
+        (max_total_deposits as u128 * utilization_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+    let account = MintConfig {
+        mint,
+        decimals,
+        deposit_enabled: true,
+        withdraw_enabled: true,
+        max_total_deposits,
+        total_deposited,
+        oracle_feed: fixture_key("oracle", seed),
+        bump,
+        last_price: 0,
+        last_price_at: 0,
+    };
+    fixture(address, &account, MintConfig::LEN)
+}
@@ -0,0 +1,205 @@
+/*
+ * ============================================================================
+ * GUARDIANS
+ * ============================================================================
+ *
+ * A user account may name up to `MAX_GUARDIANS` guardians and a threshold.
+ * From then on, moving more than `guardian_limit` out of the account needs
+ * the authority's signature plus `guardian_threshold` distinct guardian
+ * signatures, passed as extra signer accounts at the end of
+ * `remaining_accounts`. Every handler running `Guard::GuardianApproved`
+ * enforces it: `transfer_tokens`, `batch_transfer`, `routed_transfer` (for
+ * the total moved), `promo_transfer`, `transfer_from`, `create_escrow`,
+ * `cross_namespace_transfer`, `withdraw_tokens`, `settle_net`, invoice
+ * payment and factoring, and credit lines (the limit when opened, then each
+ * draw and repayment).
+ *
+ * Guardians also protect themselves: once set, the guardian set can only be
+ * changed with the current guardians' approval, and a guarded account
+ * cannot be converted to a joint account. A threshold of guardians may lock
+ * the account with `guardian_deactivate_user` when the authority is
+ * compromised. Reactivating a guarded account takes a guardian quorum as
+ * well, so a compromised authority cannot undo the lock, and
+ * `reactivate_user`'s cooldown applies as usual.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount, MAX_GUARDIANS,
+};
+
+/// Number of distinct `guardians` among `signers`
+pub fn guardian_approvals(guardians: &[Pubkey], signers: &[Pubkey]) -> usize {
+    guardians
+        .iter()
+        .filter(|guardian| signers.contains(guardian))
+        .count() // Counting guardians, not signers, ignores repeated signers
+}
+
+/// Keys of the accounts in `accounts` that signed the transaction
+pub fn signer_keys(accounts: &[AccountInfo]) -> Vec<Pubkey> {
+    accounts
+        .iter()
+        .filter(|info| info.is_signer)
+        .map(|info| *info.key)
+        .collect()
+}
+
+impl UserAccount {
+    /// The account's guardians, without unused slots
+    pub fn guardian_keys(&self) -> &[Pubkey] {
+        &self.guardians[..self.guardian_count()]
+    }
+
+    /// Number of guardians set
+    pub fn guardian_count(&self) -> usize {
+        self.guardians
+            .iter()
+            .take_while(|guardian| **guardian != Pubkey::default())
+            .count()
+    }
+
+    /// Fail unless `signers` include a threshold of the account's guardians
+    pub fn require_guardian_quorum(&self, signers: &[Pubkey]) -> Result<()> {
+        require!(
+            self.guardian_threshold > 0
+                && guardian_approvals(self.guardian_keys(), signers)
+                    >= self.guardian_threshold as usize,
+            CustomError::GuardianApprovalRequired
+        );
+        Ok(())
+    }
+
+    /// Fail if reactivating the account needs guardian approval `signers` lack
+    pub fn require_reactivation_approval(&self, signers: &[Pubkey]) -> Result<()> {
+        if self.guardian_threshold == 0 {
+            return Ok(()); // Unguarded, the authority alone may reactivate
+        }
+        self.require_guardian_quorum(signers)
+    }
+
+    /// Fail if moving `amount` out needs guardian approval `signers` lack
+    pub fn require_guardian_approval(&self, amount: u64, signers: &[Pubkey]) -> Result<()> {
+        if self.guardian_threshold == 0 || amount <= self.guardian_limit {
+            return Ok(()); // Unguarded, or small enough for the authority alone
+        }
+        self.require_guardian_quorum(signers)
+    }
+}
+
+/// Check a guardian set before it is stored
+pub fn validate_guardians(authority: &Pubkey, guardians: &[Pubkey], threshold: u8) -> Result<()> {
+    let mut distinct = guardians.to_vec();
+    distinct.sort();
+    distinct.dedup();
+
+    let valid = guardians.len() <= MAX_GUARDIANS
+        && distinct.len() == guardians.len()
+        && !guardians.contains(&Pubkey::default())
+        && !guardians.contains(authority) // The authority cannot vouch for itself
+        && threshold as usize <= guardians.len()
+        && (threshold == 0) == guardians.is_empty();
+    require!(valid, CustomError::InvalidGuardianSet);
+    Ok(())
+}
+
+/// Context for replacing an account's guardian set
+///
+/// Remaining accounts: current guardians' signatures, once guardians are set.
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    /// Account whose guardians change
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for guardians locking an account
+///
+/// Remaining accounts: a threshold of the account's guardians, as signers.
+#[derive(Accounts)]
+pub struct GuardianDeactivateUser<'info> {
+    /// Account being locked
+    #[account(
+        mut,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when an account's guardian set changes
+#[event]
+pub struct GuardiansChangedEvent {
+    pub user: Pubkey,           // User account
+    pub guardians: Vec<Pubkey>, // New guardian set, empty when cleared
+    pub threshold: u8,          // Guardian signatures required
+    pub limit: u64,             // Outflow allowed without guardians
+    pub tag: InstructionTag,    // Emitting instruction
+}
+
+/// Replace the guardian set, with the current guardians' approval if any
+pub fn handle_set_guardians(
+    ctx: Context<SetGuardians>,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    limit: u64,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SET_GUARDIANS)?;
+
+    let user = &mut ctx.accounts.user;
+    require!(!user.is_joint, CustomError::InvalidGuardianSet); // Joint accounts have co-owners
+    validate_guardians(&user.authority, &guardians, threshold)?;
+    if user.guardian_threshold > 0 {
+        user.require_guardian_quorum(&signer_keys(ctx.remaining_accounts))?;
+    }
+
+    user.guardians = [Pubkey::default(); MAX_GUARDIANS];
+    user.guardians[..guardians.len()].copy_from_slice(&guardians);
+    user.guardian_threshold = threshold;
+    user.guardian_limit = limit;
+
+    emit!(GuardiansChangedEvent {
+        user: user.key(),
+        guardians,
+        threshold,
+        limit,
+        tag: InstructionTag::SetGuardians,
+    });
+
+    msg!("Guardians of {} set, threshold {}", user.key(), threshold);
+    Ok(())
+}
+
+/// Lock the account on behalf of a threshold of its guardians
+pub fn handle_guardian_deactivate_user(ctx: Context<GuardianDeactivateUser>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::DEACTIVATE_USER)?;
+
+    let user = &mut ctx.accounts.user;
+    user.require_guardian_quorum(&signer_keys(ctx.remaining_accounts))?;
+    require!(user.is_active(), CustomError::AccountAlreadyInactive);
+
+    user.begin_deactivation(Clock::get()?.unix_timestamp); // Starts the cooldown
+
+    msg!("User account deactivated by guardians: {}", user.authority);
+    Ok(())
+}
@@ -0,0 +1,183 @@
+/*
+ * ============================================================================
+ * ACCOUNT HEALTH
+ * ============================================================================
+ *
+ * `get_account_health` gathers everything a wallet's status screen shows
+ * about one user account into a single `AccountHealth`, returned as return
+ * data, so a wallet renders it from one simulated call instead of decoding
+ * half a dozen accounts:
+ * - status: the mode and `health_flags`
+ * - obligations: how many escrows and invoices are still open
+ * - loans: what the account owes and is owed on the credit lines passed as
+ *   remaining accounts, with interest accrued to the current time
+ * - limits: what may still leave the account in the current daily window,
+ *   and the amount above which guardians must co-sign
+ * - timers: when a vacation, watchtower alert, reactivation cooldown or
+ *   pending limit increase ends, zero for those not running
+ *
+ * Credit lines are keyed by both parties, so the program cannot find them on
+ * its own; the caller passes the ones it knows of, and each is checked to be
+ * a genuine line of this account. The namespace has no staking accounts yet,
+ * so there is no stake to report.
+ *
+ * The view reads only and is not behind the kill switch; anyone may call it,
+ * since everything in it is already public on chain.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::math;
+use crate::{
+    AccountMode, CreditLine, CustomError, GlobalConfig, RemainingAccountsValidator, Seconds,
+    UserAccount,
+};
+
+/// Bits set in `AccountHealth::flags`
+pub mod health_flags {
+    pub const ACTIVE: u64 = 1 << 0; // Neither locked nor deactivating
+    pub const DEACTIVATING: u64 = 1 << 1; // Settling open obligations before locking
+    pub const COMPLIANCE_HOLD: u64 = 1 << 2; // Mode imposed by compliance
+    pub const JOINT: u64 = 1 << 3; // Moves funds with `joint_transfer`
+    pub const ON_VACATION: u64 = 1 << 4; // Outflows blocked by a vacation window
+    pub const ALERTED: u64 = 1 << 5; // Outflows frozen by a watchtower alert
+    pub const GUARDED: u64 = 1 << 6; // Guardians co-sign large outflows
+    pub const CAN_REACTIVATE: u64 = 1 << 7; // Deactivated and past the cooldown
+}
+
+/// One user account's status at a point in time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct AccountHealth {
+    pub user: Pubkey,                 // User account described
+    pub now: i64,                     // Time the view was taken at
+    pub balance: u64,                 // Spendable balance
+    pub mode: AccountMode,            // Transfer directions allowed
+    pub flags: u64,                   // `health_flags` that hold
+    pub open_obligations: u32,        // Escrows and invoices still to settle
+    pub borrowed: u64,                // Owed on the credit lines passed
+    pub lent: u64,                    // Owed to the account on the credit lines passed
+    pub daily_remaining: Option<u64>, // Left in the daily window, None when unlimited
+    pub guardian_limit: u64,          // Largest outflow without guardians, when guarded
+    pub vacation_end: i64,            // End of a current or upcoming vacation
+    pub alert_until: i64,             // End of a watchtower alert
+    pub reactivation_at: i64,         // Earliest `reactivate_user` after a deactivation
+    pub pending_limit_at: i64,        // When a raised daily limit applies
+}
+
+impl AccountHealth {
+    /// Describe `account` at `key` at `now`, without loans, under the
+    /// namespace's reactivation `cooldown`
+    pub fn of(account: &UserAccount, key: Pubkey, cooldown: Seconds, now: i64) -> Self {
+        let reactivation_at = if account.deactivated_at == 0 {
+            0
+        } else {
+            cooldown.after(account.deactivated_at)
+        };
+        let flags = [
+            (health_flags::ACTIVE, account.is_active()),
+            (health_flags::DEACTIVATING, account.is_deactivating()),
+            (health_flags::COMPLIANCE_HOLD, account.compliance_hold),
+            (health_flags::JOINT, account.is_joint),
+            (health_flags::ON_VACATION, account.is_on_vacation(now)),
+            (health_flags::ALERTED, account.is_alerted(now)),
+            (health_flags::GUARDED, account.guardian_threshold > 0),
+            (
+                health_flags::CAN_REACTIVATE,
+                !account.is_active() && reactivation_at != 0 && now >= reactivation_at,
+            ),
+        ]
+        .into_iter()
+        .filter(|&(_, holds)| holds)
+        .fold(0, |flags, (flag, _)| flags | flag);
+
+        Self {
+            user: key,
+            now,
+            balance: account.balance,
+            mode: account.mode,
+            flags,
+            open_obligations: account.open_obligations,
+            borrowed: 0,
+            lent: 0,
+            daily_remaining: account.daily_remaining(now),
+            guardian_limit: account.guardian_limit,
+            vacation_end: if now < account.vacation_end {
+                account.vacation_end
+            } else {
+                0
+            },
+            alert_until: if account.is_alerted(now) {
+                account.alert_until
+            } else {
+                0
+            },
+            reactivation_at,
+            pending_limit_at: account.pending_limit_at,
+        }
+    }
+
+    /// Count `line`'s debt, accrued to `now`, on the side the account is on
+    pub fn add_credit_line(&mut self, line: &CreditLine) -> Result<()> {
+        let mut line = line.clone();
+        line.accrue(self.now)?;
+        let outstanding = line.outstanding()?;
+        if line.borrower == self.user {
+            self.borrowed = math::add(self.borrowed, outstanding)?;
+        } else if line.lender == self.user {
+            self.lent = math::add(self.lent, outstanding)?;
+        } else {
+            return err!(CustomError::InvalidCreditLine); // Another account's line
+        }
+        Ok(())
+    }
+
+    /// True while `flag` holds
+    pub fn has(&self, flag: u64) -> bool {
+        self.flags & flag != 0
+    }
+}
+
+/// Context for viewing a user account's health
+///
+/// Remaining accounts: credit lines the account lends or borrows on.
+#[derive(Accounts)]
+pub struct GetAccountHealth<'info> {
+    /// User account described
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Namespace config the user belongs to, for the reactivation cooldown
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Describe the user account, counting the credit lines passed
+pub fn handle_get_account_health(ctx: Context<GetAccountHealth>) -> Result<AccountHealth> {
+    let now = Clock::get()?.unix_timestamp;
+    let cooldown = Seconds(ctx.accounts.config.reactivation_cooldown_secs);
+    let mut health = AccountHealth::of(&ctx.accounts.user, ctx.accounts.user.key(), cooldown, now);
+
+    let mut validator = RemainingAccountsValidator::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts {
+        let line = validator.load::<CreditLine>(info, |line| {
+            vec![
+                b"credit_line".to_vec(),
+                line.lender.to_bytes().to_vec(),
+                line.borrower.to_bytes().to_vec(),
+            ]
+        })?;
+        health.add_credit_line(&line)?;
+    }
+
+    msg!(
+        "Health of {}: flags {:#x}, {} open obligations",
+        health.user,
+        health.flags,
+        health.open_obligations
+    );
+    Ok(health)
+}
@@ -0,0 +1,117 @@
+/*
+ * ============================================================================
+ * IDEMPOTENCY KEYS
+ * ============================================================================
+ *
+ * Clients may attach a 16-byte idempotency key to balance-moving operations.
+ * The program remembers the most recent keys per user in a small ring buffer
+ * and rejects a key seen again within `IDEMPOTENCY_WINDOW`, so client
+ * retry storms cannot execute the same operation twice.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    CustomError, GlobalConfig, UserAccount, IDEMPOTENCY_WINDOW, RECENT_OPERATIONS_CAPACITY,
+};
+
+/// Client-chosen idempotency key
+pub type IdempotencyKey = [u8; 16];
+
+/// Ring buffer of the most recent idempotency keys for a user
+#[account]
+pub struct RecentOperations {
+    pub user: Pubkey, // Owning user account (32 bytes)
+    pub head: u8,     // Next slot to overwrite (1 byte)
+    pub keys: [IdempotencyKey; RECENT_OPERATIONS_CAPACITY], // Recorded keys (16 * N bytes)
+    pub recorded_at: [i64; RECENT_OPERATIONS_CAPACITY], // Record timestamps (8 * N bytes)
+    pub bump: u8,     // PDA bump seed (1 byte)
+}
+
+impl RecentOperations {
+    /// Space required for the ring buffer including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        1 +                      // head: u8
+        16 * RECENT_OPERATIONS_CAPACITY + // keys
+        8 * RECENT_OPERATIONS_CAPACITY +  // recorded_at
+        1; // bump: u8
+
+    /// Reject `key` if it was recorded within the window, otherwise record it
+    pub fn check_and_record(&mut self, key: IdempotencyKey, now: i64) -> Result<()> {
+        let duplicate = self
+            .keys
+            .iter()
+            .zip(self.recorded_at.iter())
+            .any(|(recorded, at)| {
+                *recorded == key && *at != 0 && now.saturating_sub(*at) < IDEMPOTENCY_WINDOW.get()
+            });
+        require!(!duplicate, CustomError::DuplicateOperation);
+
+        let slot = self.head as usize % RECENT_OPERATIONS_CAPACITY;
+        self.keys[slot] = key;
+        self.recorded_at[slot] = now;
+        self.head = ((slot + 1) % RECENT_OPERATIONS_CAPACITY) as u8; // Overwrite oldest next
+        Ok(())
+    }
+}
+
+/// Record an optional idempotency key against the optional ring buffer
+/// A key without its ring buffer account is rejected rather than ignored
+pub fn record_idempotency_key(
+    recent_ops: Option<&mut Account<RecentOperations>>,
+    key: Option<IdempotencyKey>,
+) -> Result<()> {
+    let Some(key) = key else {
+        return Ok(()); // No key attached, nothing to record
+    };
+    let recent_ops = recent_ops.ok_or(CustomError::RecentOperationsRequired)?;
+    recent_ops.check_and_record(key, Clock::get()?.unix_timestamp)
+}
+
+/// Context for creating a user's ring buffer of recent operations
+#[derive(Accounts)]
+pub struct InitRecentOperations<'info> {
+    /// Ring buffer being created
+    #[account(
+        init,
+        payer = authority,
+        space = RecentOperations::LEN,
+        seeds = [b"recent_ops", user.key().as_ref()], // One buffer per user
+        bump
+    )]
+    pub recent_ops: Account<'info, RecentOperations>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Create an empty ring buffer for the caller's account
+pub fn handle_init_recent_operations(ctx: Context<InitRecentOperations>) -> Result<()> {
+    let recent_ops = &mut ctx.accounts.recent_ops;
+    recent_ops.user = ctx.accounts.user.key();
+    recent_ops.head = 0;
+    recent_ops.keys = [[0; 16]; RECENT_OPERATIONS_CAPACITY];
+    recent_ops.recorded_at = [0; RECENT_OPERATIONS_CAPACITY];
+    recent_ops.bump = ctx.bumps.recent_ops;
+
+    msg!("Recent operations buffer created for {}", recent_ops.user);
+    Ok(())
+}
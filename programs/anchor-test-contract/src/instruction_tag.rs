@@ -0,0 +1,157 @@
+/*
+ * ============================================================================
+ * INSTRUCTION TAGS
+ * ============================================================================
+ *
+ * Every event carries a one-byte `InstructionTag` naming the instruction
+ * that emitted it, so analytics pipelines can attribute volume and activity
+ * per feature without maintaining their own discriminator maps. The tag is
+ * Borsh-encoded as its variant index: variants are append-only and must
+ * never be reordered.
+ */
+
+use anchor_lang::prelude::*;
+
+/// Instruction that emitted an event (append new variants at the end)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InstructionTag {
+    CreateNamespace,
+    SetDisabledInstructions,
+    RampFeeRate,
+    SetCircuitBreakerMultiple,
+    InitializeEpochStats,
+    ResetCircuitBreaker,
+    InitializeLedger,
+    VerifyLedger,
+    AssertInvariants,
+    ListMint,
+    UpdateMintConfig,
+    DelistMint,
+    SetPriceDeviationLimits,
+    OverrideOraclePrice,
+    CreateLookupTable,
+    ExtendLookupTable,
+    InitializeUser,
+    UpdateUser,
+    TransferTokens,
+    DeactivateUser,
+    ConvertToJoint,
+    JointTransfer,
+    InitRecentOperations,
+    ScheduleDeactivation,
+    BootstrapDemo,
+    Commit,
+    Reveal,
+    CreateAuction,
+    PlaceBid,
+    RevealBid,
+    SettleAuction,
+    ClaimBid,
+    CancelAuction,
+    CreateAttachmentPage,
+    AttachDocument,
+    RemoveDocument,
+    RegisterIssuer,
+    SetIssuerActive,
+    IssueAttestation,
+    RevokeAttestation,
+    VerifyAttestation,
+    CheckUserConsistency,
+    CreateNamespaceMetadata,
+    UpdateNamespaceMetadata,
+    ProposeNamespaceBridge,
+    ApproveNamespaceBridge,
+    CloseNamespaceBridge,
+    CrossNamespaceTransfer,
+    CompactAccount,
+    SweepExcessLamports,
+    Expire,
+    CreateCpiRegistry,
+    AllowCpiProgram,
+    RevokeCpiProgram,
+    SetFeeStrategy,
+    SetEmissionSchedule,
+    SetAccountMode,
+    ComplianceSetAccountMode,
+    SetComplianceAuthority,
+    GrantManager,
+    RevokeManager,
+    ManagerUpdateUser,
+    CreateVault,
+    DepositTokens,
+    WithdrawTokens,
+    SetPaused,
+    ApproveDelegate,
+    RevokeDelegate,
+    TransferFrom,
+    ReactivateUser,
+    CloseUser,
+    SetReactivationCooldown,
+    AssertBalance,
+    AssertVaultSolvency,
+    InitializeFeeTreasury,
+    WithdrawTreasury,
+    BeginGuard,
+    EndGuard,
+    BatchTransfer,
+    RoutedTransfer,
+    SetGuardians,
+    GuardianDeactivateUser,
+    OpenObligation,
+    RecordObligation,
+    SettleNet,
+    MigrateUser,
+    CreateInvoiceTemplate,
+    IssueInvoice,
+    PayInvoice,
+    CancelInvoiceTemplate,
+    CreatePromoCode,
+    PromoTransfer,
+    CreateEscrow,
+    ClaimEscrow,
+    CancelEscrow,
+    InitializeCashback,
+    SetCashback,
+    RegisterMerchant,
+    DeregisterMerchant,
+    OpenCreditLine,
+    DrawCredit,
+    RepayCredit,
+    SetCreditLineFrozen,
+    SetDailyLimit,
+    CreateProfile,
+    UpdateProfile,
+    SellInvoice,
+    BuyInvoice,
+    GenerateStatement,
+    InitTaxLots,
+    AddTaxLotPage,
+    SetLotMethod,
+    CloseTaxLotPage,
+    CompileComplianceReport,
+    SetWatchtower,
+    RaiseAlert,
+    DismissAlert,
+    InitShadowFees,
+    SetShadowFeeStrategy,
+    InitChaos,
+    SetChaos,
+    CreatePolicy,
+    UpdatePolicy,
+    ClosePolicy,
+    InitCrankMetrics,
+    IssueCapability,
+    RevokeCapability,
+    ServiceHeartbeat,
+    CapabilityCreateInvoiceTemplate,
+    SetClockTolerance,
+    TransferTokensV2,
+    SetInstructionSunset,
+    ProgramFingerprint,
+    SkimDonations,
+    RejectInvoice,
+    InitActivityLog,
+    OpenVaultPosition,
+    InitReservesAttestation,
+    AttestReserves,
+}
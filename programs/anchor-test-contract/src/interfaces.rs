@@ -0,0 +1,119 @@
+/*
+ * ============================================================================
+ * CALLBACK INTERFACES
+ * ============================================================================
+ *
+ * Stable instruction interfaces third parties implement to plug into the
+ * protocol:
+ * - `transfer_hook`: called around a transfer, may veto it by failing
+ * - `price_adapter`: reports a price for a feed through return data
+ *
+ * Each interface pins an instruction discriminator, an argument layout and
+ * an account order. An Anchor program conforms by exposing an instruction of
+ * the same name with the same arguments and the accounts in the same order
+ * (Anchor discriminators are `sha256("global:<name>")[..8]`); programs
+ * written without Anchor match the bytes directly.
+ *
+ * These definitions are append-only: new arguments or accounts mean a new
+ * instruction name, never a change to an existing one. The reference hook
+ * program in `programs/reference-hook` is checked against them.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+use crate::{CustomError, InstructionTag};
+
+/// Transfer hook: sees every hooked transfer and fails to veto it
+///
+/// Accounts, in order:
+/// 0. `[]` namespace config
+/// 1. `[]` sender user account
+/// 2. `[]` receiver user account
+/// 3. .. `[]` any extra accounts the hook needs, passed through unchanged
+pub mod transfer_hook {
+    use super::*;
+
+    /// `sha256("global:on_transfer")[..8]`
+    pub const ON_TRANSFER: [u8; 8] = [109, 85, 228, 126, 32, 128, 206, 77];
+
+    /// Arguments of `on_transfer`, after the discriminator
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct OnTransferArgs {
+        pub from: Pubkey,        // Sender authority
+        pub to: Pubkey,          // Receiver authority
+        pub amount: u64,         // Amount moved
+        pub tag: InstructionTag, // Instruction performing the transfer
+    }
+
+    /// `on_transfer` for `hook_program` with the interface's account order
+    pub fn on_transfer(
+        hook_program: Pubkey,
+        config: Pubkey,
+        sender: Pubkey,
+        receiver: Pubkey,
+        args: &OnTransferArgs,
+        extra: &[AccountMeta],
+    ) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(sender, false),
+            AccountMeta::new_readonly(receiver, false),
+        ];
+        accounts.extend_from_slice(extra);
+        Instruction {
+            program_id: hook_program,
+            accounts,
+            data: encode(ON_TRANSFER, args),
+        }
+    }
+}
+
+/// Price adapter: reports the latest price of a feed it understands
+///
+/// Accounts, in order:
+/// 0. `[]` price feed account
+///
+/// The adapter returns a `PriceReport` through return data and fails if it
+/// has no usable price for the feed.
+pub mod price_adapter {
+    use super::*;
+
+    /// `sha256("global:get_price")[..8]`
+    pub const GET_PRICE: [u8; 8] = [238, 38, 193, 106, 228, 32, 210, 33];
+
+    /// Return data of `get_price`
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct PriceReport {
+        pub price: u64,        // Price in `10^expo` units of the quote
+        pub expo: i32,         // Decimal exponent of `price`
+        pub published_at: i64, // When the source published the price
+    }
+
+    /// `get_price` for `adapter_program` reading `feed`
+    pub fn get_price(adapter_program: Pubkey, feed: Pubkey) -> Instruction {
+        Instruction {
+            program_id: adapter_program,
+            accounts: vec![AccountMeta::new_readonly(feed, false)],
+            data: GET_PRICE.to_vec(), // No arguments
+        }
+    }
+
+    /// Decode the return data `adapter_program` set, rejecting anyone else's
+    pub fn read_report(
+        adapter_program: &Pubkey,
+        return_data: &(Pubkey, Vec<u8>),
+    ) -> Result<PriceReport> {
+        let (program, data) = return_data;
+        require_keys_eq!(*program, *adapter_program, CustomError::InvalidPriceReport);
+        PriceReport::try_from_slice(data).map_err(|_| error!(CustomError::InvalidPriceReport))
+    }
+}
+
+/// Discriminator followed by the borsh-encoded arguments
+fn encode(discriminator: [u8; 8], args: &impl AnchorSerialize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    args.serialize(&mut data)
+        .expect("serializing into a Vec cannot fail");
+    data
+}
@@ -0,0 +1,210 @@
+/*
+ * ============================================================================
+ * PERMISSIONLESS INVARIANT ASSERTIONS
+ * ============================================================================
+ *
+ * Monitoring bots call `assert_invariants` with the ledger, the epoch stats
+ * and a set of user accounts in `remaining_accounts`, plus any escrows, whose
+ * locked amounts are still owed to users. The instruction
+ * recomputes the key invariants from those accounts and reverts if any is
+ * violated, so solvency can be attested on-chain continuously.
+ *
+ * Protocols composing with this program can append the cheaper single-purpose
+ * checks to their own transactions, after their CPIs, to guarantee what they
+ * expect of the resulting state:
+ * - `assert_balance`: a user's balance lies within `[min, max]`
+ * - `assert_vault_solvency`: the ledger is solvent and one mint's vault holds
+ *   at least what its depositors can withdraw
+ *
+ * Both are read-only and do nothing when the expectation holds; they emit
+ * and log nothing, to stay cheap enough to append anywhere.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::TokenAccount;
+
+use crate::decimals::{self, InternalUnits};
+use crate::{
+    CustomError, EpochStats, Escrow, GlobalConfig, InstructionTag, Ledger, LedgerAccount,
+    MintConfig, RemainingAccountsValidator, UserAccount,
+};
+
+/// Context for the invariant check; user accounts and escrows go in `remaining_accounts`
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    /// Ledger holding the expected totals (read-only)
+    #[account(seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Epoch stats checked for consistency (read-only)
+    #[account(seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when every invariant held
+#[event]
+pub struct InvariantsAttestedEvent {
+    pub users_checked: u32,  // User and escrow accounts supplied
+    pub user_balances: u64,  // Sum of supplied balances and escrowed amounts
+    pub ledger_users: u64,   // Users balance recorded in the ledger
+    pub vault_holdings: u64, // Vault balance recorded in the ledger
+    pub complete: bool,      // Whether the caller claimed a complete user set
+    pub timestamp: i64,      // When the check ran
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Recompute invariants over the supplied user accounts and escrows
+///
+/// With `complete` set, the supplied accounts must be every user and escrow
+/// and their balances must equal the ledger exactly; otherwise they must not
+/// exceed it.
+pub fn handle_assert_invariants<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AssertInvariants<'info>>,
+    complete: bool,
+) -> Result<()> {
+    let ledger = &ctx.accounts.ledger;
+    let stats = &ctx.accounts.epoch_stats;
+    let clock = Clock::get()?;
+
+    // Ledger-level invariants: balanced entries, no negative accounts
+    ledger.check_invariants()?;
+
+    // Solvency: the vault covers everything owed to users and the protocol
+    require!(ledger.is_solvent(), CustomError::InvariantViolated);
+
+    // Stats consistency: never ahead of the cluster clock
+    require!(stats.epoch <= clock.epoch, CustomError::InvariantViolated);
+    require!(
+        !stats.tripped || stats.tripped_at <= clock.unix_timestamp,
+        CustomError::InvariantViolated
+    );
+
+    // Sum supplied user balances and escrows, rejecting duplicates and look-alike accounts
+    let config = ctx.accounts.config.key();
+    let mut users = RemainingAccountsValidator::with_capacity(ctx.remaining_accounts.len());
+    let mut user_balances: u128 = 0;
+    for info in ctx.remaining_accounts.iter() {
+        let is_escrow = info.try_borrow_data()?.starts_with(&Escrow::DISCRIMINATOR);
+        let owed = if is_escrow {
+            let escrow = users.load(info, |escrow: &Escrow| {
+                vec![
+                    b"escrow".to_vec(),
+                    config.to_bytes().to_vec(),
+                    escrow.creator.to_bytes().to_vec(),
+                    escrow.beneficiary.to_bytes().to_vec(),
+                    escrow.unlock_at.to_le_bytes().to_vec(),
+                ]
+            })?;
+            escrow.amount
+        } else {
+            let user = users.load(info, |user: &UserAccount| {
+                vec![
+                    b"user".to_vec(),
+                    config.to_bytes().to_vec(),
+                    user.authority.to_bytes().to_vec(),
+                ]
+            })?;
+            user.balance
+        };
+        user_balances += owed as u128;
+    }
+
+    let ledger_users = ledger.balance(LedgerAccount::Users);
+    if complete {
+        require!(
+            user_balances as i128 == ledger_users,
+            CustomError::InvariantViolated
+        );
+    } else {
+        require!(
+            user_balances as i128 <= ledger_users,
+            CustomError::InvariantViolated
+        );
+    }
+
+    emit!(InvariantsAttestedEvent {
+        users_checked: users.count() as u32,
+        user_balances: user_balances as u64, // Bounded by the ledger total above
+        ledger_users: ledger_users as u64,
+        vault_holdings: ledger.balance(LedgerAccount::Vault) as u64,
+        complete,
+        timestamp: clock.unix_timestamp,
+        tag: InstructionTag::AssertInvariants,
+    });
+
+    msg!("Invariants hold over {} user accounts", users.count());
+    Ok(())
+}
+
+/// Context for asserting a user's balance
+#[derive(Accounts)]
+pub struct AssertBalance<'info> {
+    /// User account checked (read-only)
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Namespace config the user belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for asserting the ledger and one mint's vault are solvent
+#[derive(Accounts)]
+pub struct AssertVaultSolvency<'info> {
+    /// Ledger holding the expected totals (read-only)
+    #[account(seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Registry entry of the mint, holds its outstanding deposits (read-only)
+    #[account(
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Vault of the mint (read-only)
+    #[account(seeds = [b"vault", config.key().as_ref(), mint_config.mint.as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Fail unless the user's balance lies within `[min, max]`
+pub fn handle_assert_balance(ctx: Context<AssertBalance>, min: u64, max: u64) -> Result<()> {
+    let balance = ctx.accounts.user.balance;
+    require!(
+        min <= balance && balance <= max,
+        CustomError::BalanceOutOfRange
+    );
+    Ok(())
+}
+
+/// Fail unless the ledger is solvent and the vault covers the mint's deposits
+pub fn handle_assert_vault_solvency(ctx: Context<AssertVaultSolvency>) -> Result<()> {
+    require!(
+        ctx.accounts.ledger.is_solvent(),
+        CustomError::VaultInsolvent
+    );
+
+    let mint_config = &ctx.accounts.mint_config;
+    let withdrawable = decimals::withdrawal_to_raw(
+        InternalUnits::new(mint_config.total_deposited),
+        mint_config.decimals,
+    )?;
+    require!(
+        ctx.accounts.vault.amount >= withdrawable.get(),
+        CustomError::VaultInsolvent
+    );
+    Ok(())
+}
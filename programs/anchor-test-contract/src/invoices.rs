@@ -0,0 +1,933 @@
+/*
+ * ============================================================================
+ * RECURRING INVOICES
+ * ============================================================================
+ *
+ * A merchant bills a payer on a schedule with an `InvoiceTemplate`
+ * (`[b"invoice_template", merchant, payer]`). Once per `interval`, the
+ * permissionless `issue_invoice` crank instantiates the template into a
+ * concrete `Invoice` (`[b"invoice", template, period]`); the crank pays its
+ * rent and gets it back when the invoice is paid. Payers are notified through
+ * `InvoiceIssuedEvent`, which names their user account.
+ *
+ * `pay_invoice` transfers the amount like `transfer_tokens`, fee and
+ * guardian checks included, and closes the invoice. A template stops once
+ * `MAX_UNPAID_INVOICES` of its invoices are outstanding; those invoices stay
+ * payable. The merchant can also cancel a template at any time.
+ *
+ * Unpaid invoices can be factored. The invoice's `owner`, the merchant when
+ * issued, lists it with `sell_invoice(price)` at no more than its amount, and
+ * anyone else except the payer can `buy_invoice` at that price. The buyer
+ * becomes the owner, and `pay_invoice` credits the owner rather than the
+ * merchant. Every event keeps naming the issuing merchant alongside the
+ * owner, so an invoice's provenance survives any number of sales.
+ *
+ * Every write to a template bumps its `version`, and `issue_invoice` names
+ * the version the crank read, so of several keepers racing on one template
+ * exactly one issues the invoice (see `crank`).
+ *
+ * Each unpaid invoice is an open obligation of the payer. The crank issues
+ * none to a deactivating payer, who can still pay the ones already issued.
+ * An invoice left unpaid for `INVOICE_PAYMENT_WINDOW` is retired by the
+ * `expire` crank, which settles the obligation, stops counting it against
+ * the template and refunds the issuing crank's rent.
+ *
+ * Templates are created by the merchant alone, so the payer can refuse to be
+ * billed: `reject_invoice` closes an unpaid invoice without paying it,
+ * settling the obligation like an expiry, and cancels the template so no
+ * more are issued. A buyer of a factored invoice carries that risk.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    apply_settlement_with_fee, apply_transfer_with_fee, instruction_flags, invoice_transition,
+    load_policy, log_transfer, next_version, record_crank, record_transfer_volume,
+    require_instruction_enabled, settle_obligation, signer_keys, utilization, CrankMetrics,
+    CustomError, EpochStats, EventOrder, FeeTreasury, GlobalConfig, InstructionTag, InvoiceAction,
+    InvoiceStatus, Ledger, LedgerAccount, PolicyInput, Seconds, UserAccount,
+    INVOICE_PAYMENT_WINDOW, MAX_UNPAID_INVOICES, MIN_INVOICE_INTERVAL,
+};
+
+/// Schedule billing a payer a fixed amount every interval
+#[account]
+pub struct InvoiceTemplate {
+    pub merchant: Pubkey,   // Merchant's user account, credited (32 bytes)
+    pub payer: Pubkey,      // Payer's user account, debited (32 bytes)
+    pub amount: u64,        // Amount of every invoice (8 bytes)
+    pub interval: Seconds,  // Time between invoices (8 bytes)
+    pub next_issue_at: i64, // When the crank may issue the next invoice (8 bytes)
+    pub issued: u64,        // Invoices issued so far, the next period index (8 bytes)
+    pub unpaid: u64,        // Issued invoices not yet paid (8 bytes)
+    pub active: bool,       // False once cancelled (1 byte)
+    pub bump: u8,           // PDA bump seed (1 byte)
+    pub version: u64,       // Bumped on every write, see `crank` (8 bytes)
+}
+
+impl InvoiceTemplate {
+    /// Space required for the template including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // merchant: Pubkey
+        32 +                     // payer: Pubkey
+        8 +                      // amount: u64
+        8 +                      // interval: Seconds
+        8 +                      // next_issue_at: i64
+        8 +                      // issued: u64
+        8 +                      // unpaid: u64
+        1 +                      // active: bool
+        1 +                      // bump: u8
+        8; // version: u64
+
+    /// True if the crank may issue an invoice at `now`
+    pub fn is_due(&self, now: i64) -> bool {
+        self.active && now >= self.next_issue_at
+    }
+
+    /// Account for a newly issued invoice; returns true if the template
+    /// cancelled itself for too many unpaid invoices
+    pub fn record_issued(&mut self) -> Result<bool> {
+        self.version = next_version(self.version)?;
+        self.issued = math::add(self.issued, 1)?;
+        self.unpaid = math::add(self.unpaid, 1)?;
+        self.next_issue_at = self.interval.after(self.next_issue_at); // Keeps the schedule when the crank is late
+        if self.unpaid >= MAX_UNPAID_INVOICES {
+            self.active = false;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Account for an invoice paid, rejected or expired
+    pub fn record_paid(&mut self) -> Result<()> {
+        self.version = next_version(self.version)?;
+        self.unpaid = math::sub(self.unpaid, 1)?;
+        Ok(())
+    }
+}
+
+/// One period's invoice, open until paid
+#[account]
+pub struct Invoice {
+    pub template: Pubkey,      // Template it was issued from (32 bytes)
+    pub merchant: Pubkey,      // Merchant's user account (32 bytes)
+    pub payer: Pubkey,         // Payer's user account (32 bytes)
+    pub amount: u64,           // Amount due (8 bytes)
+    pub period: u64,           // Index among the template's invoices (8 bytes)
+    pub issued_at: i64,        // When the crank issued it (8 bytes)
+    pub rent_payer: Pubkey,    // Crank that paid the rent, refunded on payment (32 bytes)
+    pub owner: Pubkey, // User account credited on payment, the merchant until sold (32 bytes)
+    pub price: u64,    // Asking price while listed for sale, zero otherwise (8 bytes)
+    pub status: InvoiceStatus, // Lifecycle status (1 byte)
+    pub bump: u8,      // PDA bump seed (1 byte)
+}
+
+impl Invoice {
+    /// Space required for the invoice including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // template: Pubkey
+        32 +                     // merchant: Pubkey
+        32 +                     // payer: Pubkey
+        8 +                      // amount: u64
+        8 +                      // period: u64
+        8 +                      // issued_at: i64
+        32 +                     // rent_payer: Pubkey
+        32 +                     // owner: Pubkey
+        8 +                      // price: u64
+        1 +                      // status: InvoiceStatus
+        1; // bump: u8
+
+    /// When `expire` may retire the invoice if still unpaid
+    pub fn expires_at(&self) -> i64 {
+        INVOICE_PAYMENT_WINDOW.after(self.issued_at)
+    }
+
+    /// List the invoice for sale at `price`, or withdraw it with zero
+    pub fn list(&mut self, price: u64) -> Result<()> {
+        require!(price <= self.amount, CustomError::InvalidInvoicePrice); // Sold at a discount, never a premium
+        self.price = price;
+        Ok(())
+    }
+
+    /// Transfer the right to payment to `buyer`, who expects to pay `price`
+    pub fn sell_to(&mut self, buyer: Pubkey, price: u64) -> Result<()> {
+        require!(
+            self.price > 0 && buyer != self.owner && buyer != self.payer,
+            CustomError::InvoiceNotForSale
+        );
+        require!(price == self.price, CustomError::InvalidInvoicePrice); // Relisted since the buyer looked
+        self.owner = buyer;
+        self.price = 0;
+        Ok(())
+    }
+}
+
+/// Context for creating an invoice template
+#[derive(Accounts)]
+pub struct CreateInvoiceTemplate<'info> {
+    /// Template being created
+    #[account(
+        init,
+        payer = authority,
+        space = InvoiceTemplate::LEN,
+        seeds = [b"invoice_template", merchant.key().as_ref(), payer.key().as_ref()], // One per pair
+        bump
+    )]
+    pub template: Account<'info, InvoiceTemplate>,
+
+    /// Merchant's user account
+    #[account(
+        has_one = authority,
+        constraint = merchant.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, UserAccount>,
+
+    /// Payer's user account
+    #[account(
+        constraint = payer.key() != merchant.key() @ CustomError::InvalidInvoiceTemplate,
+        constraint = payer.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), payer.authority.as_ref()],
+        bump
+    )]
+    pub payer: Account<'info, UserAccount>,
+
+    /// Merchant authority, pays the template's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the crank issuing a template's next invoice
+#[derive(Accounts)]
+#[instruction(expected_version: u64)]
+pub struct IssueInvoice<'info> {
+    /// Template being instantiated, checked before the invoice is created
+    #[account(
+        mut,
+        constraint = template.version == expected_version @ CustomError::StaleVersion,
+        seeds = [b"invoice_template", merchant.key().as_ref(), template.payer.as_ref()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, InvoiceTemplate>,
+
+    /// Invoice being issued
+    #[account(
+        init,
+        payer = cranker,
+        space = Invoice::LEN,
+        seeds = [b"invoice", template.key().as_ref(), template.issued.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    /// Merchant's user account, ties the template to the namespace
+    #[account(
+        constraint = merchant.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), merchant.authority.as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, UserAccount>,
+
+    /// Payer's user account, which the invoice becomes an obligation of
+    #[account(
+        mut,
+        address = template.payer,
+        constraint = payer.is_current() @ CustomError::MigrationRequired
+    )]
+    pub payer: Account<'info, UserAccount>,
+
+    /// Anyone; pays the invoice's rent until it is paid
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace crank metrics, counted when passed
+    #[account(mut, seeds = [b"crank_metrics", config.key().as_ref()], bump = crank_metrics.bump)]
+    pub crank_metrics: Option<Account<'info, CrankMetrics>>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for paying an invoice
+///
+/// Remaining accounts: the payer's guardian signers, when needed.
+#[derive(Accounts)]
+pub struct PayInvoice<'info> {
+    /// Invoice being paid, rent returned to the crank that issued it
+    #[account(
+        mut,
+        has_one = template,
+        has_one = owner,
+        close = rent_payer,
+        seeds = [b"invoice", template.key().as_ref(), invoice.period.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    /// Template the invoice was issued from
+    #[account(
+        mut,
+        seeds = [b"invoice_template", template.merchant.as_ref(), payer.key().as_ref()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, InvoiceTemplate>,
+
+    /// Payer's user account, debited
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = payer.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub payer: Account<'info, UserAccount>,
+
+    /// Invoice owner's user account, credited: the merchant unless sold
+    #[account(
+        mut,
+        constraint = owner.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), owner.authority.as_ref()],
+        bump
+    )]
+    pub owner: Account<'info, UserAccount>,
+
+    /// Payer authority
+    pub authority: Signer<'info>,
+
+    /// Crank that issued the invoice, receives the rent
+    /// CHECK: matched against the invoice's rent payer
+    #[account(mut, address = invoice.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the payment
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fee
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Payer's activity log address, appended to when the payer keeps one
+    /// CHECK: address is the payer's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", payer.key().as_ref()], bump)]
+    pub payer_activity: UncheckedAccount<'info>,
+
+    /// Owner's activity log address, appended to when the owner keeps one
+    /// CHECK: address is the owner's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", owner.key().as_ref()], bump)]
+    pub owner_activity: UncheckedAccount<'info>,
+
+    /// Payer's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the payer's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", payer.key().as_ref()], bump)]
+    pub payer_policy: UncheckedAccount<'info>,
+}
+
+/// Context for the payer rejecting an invoice
+#[derive(Accounts)]
+pub struct RejectInvoice<'info> {
+    /// Invoice being rejected, rent returned to the crank that issued it
+    #[account(
+        mut,
+        has_one = template,
+        close = rent_payer,
+        seeds = [b"invoice", template.key().as_ref(), invoice.period.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    /// Template the invoice was issued from, cancelled
+    #[account(
+        mut,
+        seeds = [b"invoice_template", template.merchant.as_ref(), payer.key().as_ref()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, InvoiceTemplate>,
+
+    /// Payer's user account, whose obligation is settled
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = payer.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub payer: Account<'info, UserAccount>,
+
+    /// Payer authority
+    pub authority: Signer<'info>,
+
+    /// Crank that issued the invoice, receives the rent
+    /// CHECK: matched against the invoice's rent payer
+    #[account(mut, address = invoice.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for the invoice owner listing it for sale
+#[derive(Accounts)]
+pub struct SellInvoice<'info> {
+    /// Invoice being listed
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"invoice", invoice.template.as_ref(), invoice.period.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    /// Owner's user account
+    #[account(
+        has_one = authority,
+        constraint = owner.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub owner: Account<'info, UserAccount>,
+
+    /// Owner authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for buying a listed invoice
+///
+/// Remaining accounts: the buyer's guardian signers, when needed.
+#[derive(Accounts)]
+pub struct BuyInvoice<'info> {
+    /// Invoice being bought
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"invoice", invoice.template.as_ref(), invoice.period.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    /// Buyer's user account, debited the price
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = buyer.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub buyer: Account<'info, UserAccount>,
+
+    /// Current owner's user account, credited the price
+    #[account(
+        mut,
+        constraint = owner.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), owner.authority.as_ref()],
+        bump
+    )]
+    pub owner: Account<'info, UserAccount>,
+
+    /// Buyer authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the payment
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fee
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Buyer's activity log address, appended to when the buyer keeps one
+    /// CHECK: address is the buyer's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", buyer.key().as_ref()], bump)]
+    pub buyer_activity: UncheckedAccount<'info>,
+
+    /// Current owner's activity log address, appended to when the owner keeps one
+    /// CHECK: address is the owner's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", owner.key().as_ref()], bump)]
+    pub owner_activity: UncheckedAccount<'info>,
+
+    /// Buyer's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the buyer's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", buyer.key().as_ref()], bump)]
+    pub buyer_policy: UncheckedAccount<'info>,
+}
+
+/// Context for the merchant cancelling a template
+#[derive(Accounts)]
+pub struct CancelInvoiceTemplate<'info> {
+    /// Template being cancelled
+    #[account(
+        mut,
+        seeds = [b"invoice_template", merchant.key().as_ref(), template.payer.as_ref()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, InvoiceTemplate>,
+
+    /// Merchant's user account
+    #[account(
+        has_one = authority,
+        constraint = merchant.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, UserAccount>,
+
+    /// Merchant authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when the crank issues an invoice, notifying the payer
+#[event]
+pub struct InvoiceIssuedEvent {
+    pub invoice: Pubkey,     // New invoice
+    pub template: Pubkey,    // Template it was issued from
+    pub merchant: Pubkey,    // Merchant's user account
+    pub payer: Pubkey,       // Payer's user account
+    pub amount: u64,         // Amount due
+    pub period: u64,         // Index among the template's invoices
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when an invoice is paid
+#[event]
+pub struct InvoicePaidEvent {
+    pub invoice: Pubkey,     // Paid, now closed invoice
+    pub payer: Pubkey,       // Payer's user account
+    pub merchant: Pubkey,    // Merchant's user account that issued it
+    pub owner: Pubkey,       // User account credited, the merchant unless sold
+    pub amount: u64,         // Debited from the payer
+    pub fee: u64,            // Protocol fee withheld from `amount`
+    pub timestamp: i64,      // When the invoice was paid
+    pub order: EventOrder,   // Payer's sequence and slot
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when the payer rejects an invoice
+#[event]
+pub struct InvoiceRejectedEvent {
+    pub invoice: Pubkey,     // Rejected, now closed invoice
+    pub payer: Pubkey,       // Payer's user account
+    pub merchant: Pubkey,    // Merchant's user account that issued it
+    pub owner: Pubkey,       // User account it would have paid
+    pub amount: u64,         // Amount left unpaid
+    pub timestamp: i64,      // When the invoice was rejected
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when an invoice is listed for sale or withdrawn
+#[event]
+pub struct InvoiceListedEvent {
+    pub invoice: Pubkey,     // Listed invoice
+    pub merchant: Pubkey,    // Merchant's user account that issued it
+    pub owner: Pubkey,       // Owner's user account, the seller
+    pub amount: u64,         // Amount the payer owes
+    pub price: u64,          // Asking price, zero when withdrawn
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when an invoice changes hands
+#[event]
+pub struct InvoiceSoldEvent {
+    pub invoice: Pubkey,     // Sold invoice
+    pub merchant: Pubkey,    // Merchant's user account that issued it
+    pub seller: Pubkey,      // Previous owner's user account
+    pub buyer: Pubkey,       // New owner's user account
+    pub amount: u64,         // Amount the payer owes the new owner
+    pub price: u64,          // Debited from the buyer
+    pub fee: u64,            // Protocol fee withheld from `price`
+    pub timestamp: i64,      // When the invoice was sold
+    pub order: EventOrder,   // Buyer's sequence and slot
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a template stops issuing invoices
+#[event]
+pub struct InvoiceTemplateCancelledEvent {
+    pub template: Pubkey,    // Cancelled template
+    pub issued: u64,         // Invoices issued over its lifetime
+    pub unpaid: u64,         // Invoices still outstanding
+    pub automatic: bool,     // Cancelled for too many unpaid invoices
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Fill a new template with its first invoice due at `now`
+///
+/// Shared by `create_invoice_template` and billing services acting on a
+/// capability.
+pub fn start_invoice_template(
+    template: &mut InvoiceTemplate,
+    merchant: Pubkey,
+    payer: Pubkey,
+    amount: u64,
+    interval: Seconds,
+    now: i64,
+    bump: u8,
+) -> Result<()> {
+    require!(
+        amount > 0 && interval >= MIN_INVOICE_INTERVAL,
+        CustomError::InvalidInvoiceTemplate
+    );
+    template.merchant = merchant;
+    template.payer = payer;
+    template.amount = amount;
+    template.interval = interval;
+    template.next_issue_at = now; // First invoice is due right away
+    template.issued = 0;
+    template.unpaid = 0;
+    template.active = true;
+    template.bump = bump;
+    template.version = 0;
+    Ok(())
+}
+
+/// Create a template billing the payer `amount` every `interval`
+pub fn handle_create_invoice_template(
+    ctx: Context<CreateInvoiceTemplate>,
+    amount: u64,
+    interval: Seconds,
+) -> Result<()> {
+    require_instruction_enabled(
+        &ctx.accounts.config,
+        instruction_flags::CREATE_INVOICE_TEMPLATE,
+    )?;
+    start_invoice_template(
+        &mut ctx.accounts.template,
+        ctx.accounts.merchant.key(),
+        ctx.accounts.payer.key(),
+        amount,
+        interval,
+        Clock::get()?.unix_timestamp,
+        ctx.bumps.template,
+    )?;
+
+    msg!(
+        "Invoice template created: {} every {}s",
+        amount,
+        interval.get()
+    );
+    Ok(())
+}
+
+/// Issue the template's next invoice once its period has begun, if the
+/// template is still at the version the crank read (checked by the context)
+pub fn handle_issue_invoice(ctx: Context<IssueInvoice>, _expected_version: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::ISSUE_INVOICE)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let template = &mut ctx.accounts.template;
+    require!(template.active, CustomError::InvoiceTemplateInactive);
+    require!(template.is_due(now), CustomError::InvoiceNotDue);
+
+    let invoice = &mut ctx.accounts.invoice;
+    invoice.template = template.key();
+    invoice.merchant = template.merchant;
+    invoice.payer = template.payer;
+    invoice.amount = template.amount;
+    invoice.period = template.issued;
+    invoice.issued_at = now;
+    invoice.rent_payer = ctx.accounts.cranker.key();
+    invoice.owner = template.merchant;
+    invoice.price = 0;
+    invoice.status = invoice_transition(invoice.status, InvoiceAction::Issue)?;
+    invoice.bump = ctx.bumps.invoice;
+    let due = template.next_issue_at;
+    let cancelled = template.record_issued()?;
+    record_crank(ctx.accounts.crank_metrics.as_mut(), due, now)?;
+    ctx.accounts.payer.open_obligation()?; // None issued while deactivating
+
+    emit!(InvoiceIssuedEvent {
+        invoice: invoice.key(),
+        template: template.key(),
+        merchant: invoice.merchant,
+        payer: invoice.payer,
+        amount: invoice.amount,
+        period: invoice.period,
+        tag: InstructionTag::IssueInvoice,
+    });
+    if cancelled {
+        emit!(InvoiceTemplateCancelledEvent {
+            template: template.key(),
+            issued: template.issued,
+            unpaid: template.unpaid,
+            automatic: true,
+            tag: InstructionTag::IssueInvoice,
+        });
+    }
+
+    msg!("Invoice {} issued for {}", invoice.period, invoice.amount);
+    Ok(())
+}
+
+/// Pay the invoice in full and close it
+pub fn handle_pay_invoice(ctx: Context<PayInvoice>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let amount = ctx.accounts.invoice.amount;
+    let payer = &ctx.accounts.payer;
+    let policy = load_policy(&ctx.accounts.payer_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: ctx.accounts.owner.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::PAY_INVOICE),
+        Guard::NotJoint(payer), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(payer, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(payer, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+    let invoice = &mut ctx.accounts.invoice;
+    invoice.status = invoice_transition(invoice.status, InvoiceAction::Pay)?;
+
+    let payer = &mut ctx.accounts.payer;
+    payer.record_daily_spend(amount, now)?;
+
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        amount,
+        InstructionTag::PayInvoice,
+    )?;
+    let stats = &ctx.accounts.epoch_stats;
+    let fee = ctx.accounts.config.fee_at(
+        amount,
+        now,
+        utilization(stats.volume, stats.trailing_average),
+    )?;
+    apply_settlement_with_fee(payer, &mut ctx.accounts.owner, amount, fee, now)?; // Deactivating parties still settle
+    ctx.accounts.fee_treasury.collect(fee)?;
+    settle_obligation(payer, InstructionTag::PayInvoice)?;
+
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, amount - fee)?; // Payer debit, owner credit
+    if fee > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fee)?; // Withheld for the treasury
+    }
+    ctx.accounts.template.record_paid()?;
+    let order = log_transfer(
+        (
+            &ctx.accounts.payer_activity,
+            ctx.accounts.payer.key(),
+            &mut ctx.accounts.payer,
+        ),
+        (
+            &ctx.accounts.owner_activity,
+            ctx.accounts.owner.key(),
+            &mut ctx.accounts.owner,
+        ),
+        amount,
+        amount - fee,
+        InstructionTag::PayInvoice,
+        now,
+    )?;
+
+    emit!(InvoicePaidEvent {
+        invoice: ctx.accounts.invoice.key(),
+        payer: ctx.accounts.payer.key(),
+        merchant: ctx.accounts.invoice.merchant,
+        owner: ctx.accounts.owner.key(),
+        amount,
+        fee,
+        timestamp: now,
+        order,
+        tag: InstructionTag::PayInvoice,
+    });
+
+    msg!("Invoice {} paid", ctx.accounts.invoice.period);
+    Ok(())
+}
+
+/// Close the invoice unpaid and cancel its template
+pub fn handle_reject_invoice(ctx: Context<RejectInvoice>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::PAY_INVOICE)?;
+
+    let invoice = &mut ctx.accounts.invoice;
+    invoice.status = invoice_transition(invoice.status, InvoiceAction::Reject)?;
+
+    settle_obligation(&mut ctx.accounts.payer, InstructionTag::RejectInvoice)?;
+    let template = &mut ctx.accounts.template;
+    template.record_paid()?;
+    let cancelled = template.active;
+    template.active = false; // Version already bumped by `record_paid`
+
+    let invoice = &ctx.accounts.invoice;
+    emit!(InvoiceRejectedEvent {
+        invoice: invoice.key(),
+        payer: invoice.payer,
+        merchant: invoice.merchant,
+        owner: invoice.owner,
+        amount: invoice.amount,
+        timestamp: Clock::get()?.unix_timestamp,
+        tag: InstructionTag::RejectInvoice,
+    });
+    if cancelled {
+        emit!(InvoiceTemplateCancelledEvent {
+            template: template.key(),
+            issued: template.issued,
+            unpaid: template.unpaid,
+            automatic: false,
+            tag: InstructionTag::RejectInvoice,
+        });
+    }
+
+    msg!("Invoice {} rejected", invoice.period);
+    Ok(())
+}
+
+/// List the invoice for sale at `price`, or withdraw it with zero
+pub fn handle_sell_invoice(ctx: Context<SellInvoice>, price: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::FACTOR_INVOICE)?;
+
+    let invoice = &mut ctx.accounts.invoice;
+    invoice.list(price)?;
+
+    emit!(InvoiceListedEvent {
+        invoice: invoice.key(),
+        merchant: invoice.merchant,
+        owner: invoice.owner,
+        amount: invoice.amount,
+        price,
+        tag: InstructionTag::SellInvoice,
+    });
+
+    msg!("Invoice {} listed at {}", invoice.period, price);
+    Ok(())
+}
+
+/// Buy a listed invoice at its asking `price`, taking over the right to payment
+pub fn handle_buy_invoice(ctx: Context<BuyInvoice>, price: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let buyer = &ctx.accounts.buyer;
+    let policy = load_policy(&ctx.accounts.buyer_policy)?;
+    let policy_input = PolicyInput {
+        amount: price,
+        receiver: ctx.accounts.owner.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::FACTOR_INVOICE),
+        Guard::NotJoint(buyer), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(buyer, price, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(buyer, price, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+
+    let buyer = &mut ctx.accounts.buyer;
+    ctx.accounts.invoice.sell_to(buyer.key(), price)?;
+    buyer.record_daily_spend(price, now)?;
+
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        price,
+        InstructionTag::BuyInvoice,
+    )?;
+    let stats = &ctx.accounts.epoch_stats;
+    let fee = ctx.accounts.config.fee_at(
+        price,
+        now,
+        utilization(stats.volume, stats.trailing_average),
+    )?;
+    apply_transfer_with_fee(buyer, &mut ctx.accounts.owner, price, fee, now)?;
+    ctx.accounts.fee_treasury.collect(fee)?;
+
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, price - fee)?; // Buyer debit, seller credit
+    if fee > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fee)?; // Withheld for the treasury
+    }
+    let order = log_transfer(
+        (
+            &ctx.accounts.buyer_activity,
+            ctx.accounts.buyer.key(),
+            &mut ctx.accounts.buyer,
+        ),
+        (
+            &ctx.accounts.owner_activity,
+            ctx.accounts.owner.key(),
+            &mut ctx.accounts.owner,
+        ),
+        price,
+        price - fee,
+        InstructionTag::BuyInvoice,
+        now,
+    )?;
+
+    let invoice = &ctx.accounts.invoice;
+    emit!(InvoiceSoldEvent {
+        invoice: invoice.key(),
+        merchant: invoice.merchant,
+        seller: ctx.accounts.owner.key(),
+        buyer: invoice.owner,
+        amount: invoice.amount,
+        price,
+        fee,
+        timestamp: now,
+        order,
+        tag: InstructionTag::BuyInvoice,
+    });
+
+    msg!("Invoice {} sold for {}", invoice.period, price);
+    Ok(())
+}
+
+/// Stop the template from issuing further invoices
+pub fn handle_cancel_invoice_template(ctx: Context<CancelInvoiceTemplate>) -> Result<()> {
+    require_instruction_enabled(
+        &ctx.accounts.config,
+        instruction_flags::CREATE_INVOICE_TEMPLATE,
+    )?;
+
+    let template = &mut ctx.accounts.template;
+    require!(template.active, CustomError::InvoiceTemplateInactive);
+    template.active = false;
+    template.version = next_version(template.version)?;
+
+    emit!(InvoiceTemplateCancelledEvent {
+        template: template.key(),
+        issued: template.issued,
+        unpaid: template.unpaid,
+        automatic: false,
+        tag: InstructionTag::CancelInvoiceTemplate,
+    });
+
+    msg!("Invoice template cancelled: {}", template.key());
+    Ok(())
+}
@@ -0,0 +1,289 @@
+/*
+ * ============================================================================
+ * JOINT ACCOUNTS
+ * ============================================================================
+ *
+ * A joint account is an existing user account that has been converted to
+ * shared ownership between its original authority and a co-owner.
+ * Transfers above the configured threshold require both signatures when the
+ * account is in `RequireBoth` mode; in `EitherParty` mode either owner may
+ * move funds on their own.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::guards::{self, Guard};
+use crate::{
+    apply_transfer_with_fee, instruction_flags, load_policy, log_transfer, record_transfer_volume,
+    require_instruction_enabled, utilization, CustomError, EpochStats, FeeTreasury, GlobalConfig,
+    InstructionTag, Ledger, LedgerAccount, PolicyInput, TokenTransferEvent, UserAccount,
+};
+
+/// Signature policy applied to joint-account transfers above the threshold
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JointMode {
+    RequireBoth, // Both owners must sign above the threshold
+    EitherParty, // Either owner may sign alone
+}
+
+/// Joint ownership record attached to a converted user account
+#[account]
+pub struct JointAccount {
+    pub user: Pubkey,      // The converted user account (32 bytes)
+    pub primary: Pubkey,   // Original authority of the user account (32 bytes)
+    pub secondary: Pubkey, // Co-owner added during conversion (32 bytes)
+    pub threshold: u64,    // Transfers above this amount follow `mode` (8 bytes)
+    pub mode: JointMode,   // Signature policy (1 byte)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl JointAccount {
+    /// Space required for the joint record including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        32 +                     // primary: Pubkey
+        32 +                     // secondary: Pubkey
+        8 +                      // threshold: u64
+        1 +                      // mode: JointMode
+        1; // bump: u8
+
+    /// Returns true if `key` is one of the two owners
+    pub fn is_owner(&self, key: &Pubkey) -> bool {
+        self.primary == *key || self.secondary == *key
+    }
+
+    /// Returns the owner that did not sign as `signer`
+    pub fn other_owner(&self, signer: &Pubkey) -> Pubkey {
+        if self.primary == *signer {
+            self.secondary
+        } else {
+            self.primary
+        }
+    }
+}
+
+/// Context for converting an existing account into a joint account
+#[derive(Accounts)]
+pub struct ConvertToJoint<'info> {
+    /// The user account being converted
+    #[account(
+        mut,                                     // `is_joint` flag will be set
+        has_one = authority,                     // Verify ownership
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()], // Verify PDA
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Joint ownership record created for this user
+    #[account(
+        init,
+        payer = authority,
+        space = JointAccount::LEN,
+        seeds = [b"joint", user.key().as_ref()], // One joint record per user
+        bump
+    )]
+    pub joint: Account<'info, JointAccount>,
+
+    /// Current authority of the account
+    #[account(mut)] // Pays rent for the joint record
+    pub authority: Signer<'info>,
+
+    /// Prospective co-owner, must consent by signing
+    pub co_authority: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for a transfer out of a joint account
+#[derive(Accounts)]
+pub struct JointTransfer<'info> {
+    /// Joint sender account
+    #[account(
+        mut,                                     // Balance decrease
+        constraint = sender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), joint.primary.as_ref()], // PDA is still owned by the primary
+        bump
+    )]
+    pub sender: Account<'info, UserAccount>,
+
+    /// Joint ownership record for the sender
+    #[account(
+        constraint = joint.user == sender.key() @ CustomError::NotJointOwner,
+        seeds = [b"joint", sender.key().as_ref()],
+        bump = joint.bump
+    )]
+    pub joint: Account<'info, JointAccount>,
+
+    /// Receiver's user account
+    #[account(
+        mut,                                     // Balance increase
+        constraint = receiver.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), receiver.authority.as_ref()],
+        bump
+    )]
+    pub receiver: Account<'info, UserAccount>,
+
+    /// Owner initiating the transfer (primary or secondary)
+    pub signer: Signer<'info>,
+
+    /// The other owner, required above the threshold in `RequireBoth` mode
+    pub co_signer: Option<Signer<'info>>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the transfer entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fee
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Sender's activity log address, appended to when the sender keeps one
+    /// CHECK: address is the sender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", sender.key().as_ref()], bump)]
+    pub sender_activity: UncheckedAccount<'info>,
+
+    /// Receiver's activity log address, appended to when the receiver keeps one
+    /// CHECK: address is the receiver's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", receiver.key().as_ref()], bump)]
+    pub receiver_activity: UncheckedAccount<'info>,
+
+    /// Sender's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the sender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", sender.key().as_ref()], bump)]
+    pub sender_policy: UncheckedAccount<'info>,
+}
+
+/// Convert the caller's account into a joint account shared with `co_authority`
+pub fn handle_convert_to_joint(
+    ctx: Context<ConvertToJoint>,
+    threshold: u64,
+    mode: JointMode,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::CONVERT_TO_JOINT)?;
+
+    let user = &mut ctx.accounts.user;
+    let authority = ctx.accounts.authority.key();
+    let co_authority = ctx.accounts.co_authority.key();
+
+    require!(user.is_active(), CustomError::AccountInactive); // Only active accounts convert
+    require!(!user.is_joint, CustomError::AlreadyJointAccount); // One conversion per account
+    require!(
+        user.guardian_threshold == 0,
+        CustomError::GuardianApprovalRequired
+    ); // Co-owners would sidestep the guardians
+    require_keys_neq!(authority, co_authority, CustomError::InvalidCoAuthority);
+
+    let joint = &mut ctx.accounts.joint;
+    joint.user = user.key();
+    joint.primary = authority;
+    joint.secondary = co_authority;
+    joint.threshold = threshold;
+    joint.mode = mode;
+    joint.bump = ctx.bumps.joint;
+
+    user.is_joint = true; // Plain transfers are now rejected
+
+    msg!(
+        "User account {} converted to joint with {}",
+        user.key(),
+        co_authority
+    );
+    Ok(())
+}
+
+/// Transfer out of a joint account, enforcing the co-signature policy
+pub fn handle_joint_transfer(ctx: Context<JointTransfer>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let policy = load_policy(&ctx.accounts.sender_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: ctx.accounts.receiver.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::JOINT_TRANSFER),
+        Guard::Distinct(&[ctx.accounts.sender.key(), ctx.accounts.receiver.key()]),
+        Guard::DailyLimit(&ctx.accounts.sender, amount, now), // Shared by both owners
+        Guard::Policy(policy.as_ref(), policy_input),         // Binds either owner
+    ])?;
+
+    let joint = &ctx.accounts.joint;
+    let signer = ctx.accounts.signer.key();
+
+    require!(joint.is_owner(&signer), CustomError::NotJointOwner); // Signer must be an owner
+
+    // Large transfers in RequireBoth mode need the other owner as well
+    if amount > joint.threshold && joint.mode == JointMode::RequireBoth {
+        let co_signer = ctx
+            .accounts
+            .co_signer
+            .as_ref()
+            .ok_or(CustomError::CoSignatureRequired)?;
+        require_keys_eq!(
+            co_signer.key(),
+            joint.other_owner(&signer),
+            CustomError::CoSignatureRequired
+        );
+    }
+
+    let sender = &mut ctx.accounts.sender;
+    let receiver = &mut ctx.accounts.receiver;
+    sender.record_daily_spend(amount, now)?;
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        amount,
+        InstructionTag::JointTransfer,
+    )?;
+
+    // Protocol fee, withheld from what the receiver gets
+    let stats = &ctx.accounts.epoch_stats;
+    let fee = ctx.accounts.config.fee_at(
+        amount,
+        now,
+        utilization(stats.volume, stats.trailing_average),
+    )?;
+    apply_transfer_with_fee(sender, receiver, amount, fee, now)?;
+    ctx.accounts.fee_treasury.collect(fee)?;
+
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, amount - fee)?;
+    if fee > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fee)?; // Withheld for the treasury
+    }
+    let order = log_transfer(
+        (&ctx.accounts.sender_activity, sender.key(), sender),
+        (&ctx.accounts.receiver_activity, receiver.key(), receiver),
+        amount,
+        amount - fee,
+        InstructionTag::JointTransfer,
+        now,
+    )?;
+
+    emit!(TokenTransferEvent {
+        from: sender.authority,
+        to: receiver.authority,
+        amount,
+        fee,
+        timestamp: now,
+        order,
+        tag: InstructionTag::JointTransfer,
+    });
+
+    msg!("Joint transfer of {} tokens by {}", amount, signer);
+    Ok(())
+}
@@ -0,0 +1,197 @@
+/*
+ * ============================================================================
+ * DOUBLE-ENTRY LEDGER
+ * ============================================================================
+ *
+ * Every balance mutation posts a matching debit and credit against named
+ * ledger accounts. Totals are kept per ledger account in the `Ledger` PDA,
+ * and `verify_ledger` asserts the global invariants:
+ * - Total debits equal total credits
+ * - Every ledger account sits on its normal side (no negative balances)
+ *
+ * Users, Treasury and Fees are credit-normal (what the protocol owes or has
+ * earned); Vault is debit-normal (what the protocol holds).
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, GlobalConfig, InstructionTag};
+
+/// Number of named ledger accounts
+pub const LEDGER_ACCOUNT_COUNT: usize = 4;
+
+/// Named ledger accounts that entries are posted against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LedgerAccount {
+    Users,    // Internal user balances (credit-normal)
+    Vault,    // Tokens held by the program (debit-normal)
+    Treasury, // Protocol-owned funds (credit-normal)
+    Fees,     // Fees earned (credit-normal)
+}
+
+impl LedgerAccount {
+    /// All ledger accounts in storage order
+    pub const ALL: [LedgerAccount; LEDGER_ACCOUNT_COUNT] = [
+        LedgerAccount::Users,
+        LedgerAccount::Vault,
+        LedgerAccount::Treasury,
+        LedgerAccount::Fees,
+    ];
+
+    /// Storage index of the account in `Ledger`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// True for accounts whose balance grows with debits
+    pub fn is_debit_normal(self) -> bool {
+        matches!(self, LedgerAccount::Vault)
+    }
+}
+
+/// Global double-entry ledger
+#[account]
+pub struct Ledger {
+    pub debits: [u64; LEDGER_ACCOUNT_COUNT], // Total debits per account (32 bytes)
+    pub credits: [u64; LEDGER_ACCOUNT_COUNT], // Total credits per account (32 bytes)
+    pub entries: u64,                        // Number of posted entries (8 bytes)
+    pub bump: u8,                            // PDA bump seed (1 byte)
+}
+
+impl Ledger {
+    /// Space required for the ledger including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        8 * LEDGER_ACCOUNT_COUNT + // debits
+        8 * LEDGER_ACCOUNT_COUNT + // credits
+        8 +                      // entries: u64
+        1; // bump: u8
+
+    /// Post one balanced entry moving `amount` from `credit` to `debit`
+    pub fn post(&mut self, debit: LedgerAccount, credit: LedgerAccount, amount: u64) -> Result<()> {
+        let d = &mut self.debits[debit.index()];
+        *d = d.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+
+        let c = &mut self.credits[credit.index()];
+        *c = c.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+
+        self.entries = self
+            .entries
+            .checked_add(1)
+            .ok_or(CustomError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Balance of a ledger account on its normal side (may be negative if corrupt)
+    pub fn balance(&self, account: LedgerAccount) -> i128 {
+        let debits = self.debits[account.index()] as i128;
+        let credits = self.credits[account.index()] as i128;
+        if account.is_debit_normal() {
+            debits - credits
+        } else {
+            credits - debits
+        }
+    }
+
+    /// Assert the global double-entry invariants
+    pub fn check_invariants(&self) -> Result<()> {
+        let total_debits: u128 = self.debits.iter().map(|d| *d as u128).sum();
+        let total_credits: u128 = self.credits.iter().map(|c| *c as u128).sum();
+        require!(total_debits == total_credits, CustomError::LedgerImbalanced);
+
+        for account in LedgerAccount::ALL {
+            require!(
+                self.balance(account) >= 0,
+                CustomError::LedgerNegativeBalance
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the vault covers everything owed to users and the protocol
+    pub fn is_solvent(&self) -> bool {
+        let owed = self.balance(LedgerAccount::Users)
+            + self.balance(LedgerAccount::Treasury)
+            + self.balance(LedgerAccount::Fees);
+        self.balance(LedgerAccount::Vault) >= owed
+    }
+}
+
+/// Context for creating the ledger
+#[derive(Accounts)]
+pub struct InitializeLedger<'info> {
+    /// Ledger being created
+    #[account(
+        init,
+        payer = admin,
+        space = Ledger::LEN,
+        seeds = [b"ledger", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the permissionless ledger check
+#[derive(Accounts)]
+pub struct VerifyLedger<'info> {
+    /// Ledger being verified (read-only)
+    #[account(seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when the ledger passes verification
+#[event]
+pub struct LedgerVerifiedEvent {
+    pub entries: u64,        // Entries posted so far
+    pub user_balances: u64,  // Users ledger balance
+    pub vault_holdings: u64, // Vault ledger balance
+    pub timestamp: i64,      // When the check ran
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create an empty ledger
+pub fn handle_initialize_ledger(ctx: Context<InitializeLedger>) -> Result<()> {
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.debits = [0; LEDGER_ACCOUNT_COUNT];
+    ledger.credits = [0; LEDGER_ACCOUNT_COUNT];
+    ledger.entries = 0;
+    ledger.bump = ctx.bumps.ledger;
+
+    msg!("Ledger initialized");
+    Ok(())
+}
+
+/// Assert the ledger invariants, failing the transaction if any is violated
+pub fn handle_verify_ledger(ctx: Context<VerifyLedger>) -> Result<()> {
+    let ledger = &ctx.accounts.ledger;
+    ledger.check_invariants()?;
+
+    emit!(LedgerVerifiedEvent {
+        entries: ledger.entries,
+        user_balances: ledger.balance(LedgerAccount::Users) as u64, // Non-negative after check
+        vault_holdings: ledger.balance(LedgerAccount::Vault) as u64,
+        timestamp: Clock::get()?.unix_timestamp,
+        tag: InstructionTag::VerifyLedger,
+    });
+
+    msg!("Ledger verified after {} entries", ledger.entries);
+    Ok(())
+}
@@ -19,7 +19,147 @@
  */
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer}; // Import token utilities
+
+pub mod account_mode; // Send/receive permissions of user accounts
+pub mod activity_log; // On-chain ring buffer of recent user activity
+pub mod allowance; // ERC20-style delegate allowances
+pub mod attachments; // Content-addressed profile attachments
+pub mod attestations; // Issuer attestations about users
+pub mod auction; // Sealed-bid auctions
+pub mod batch; // Batch transfers to many receivers
+pub mod bootstrap; // Localnet demo bootstrap
+pub mod bridge; // Cross-namespace transfer bridges
+pub mod capability; // Scoped capability tokens for services
+pub mod cashback; // Treasury-funded merchant cashback
+pub mod chaos; // Test-only failure injection
+pub mod circuit_breaker; // Volume circuit breaker
+pub mod clock; // Tolerance on deadline comparisons
+pub mod commit_reveal; // Generic commit-reveal commitments
+pub mod compliance_report; // Auditor-facing activity reports
+pub mod config; // Global config and instruction kill switches
+pub mod consistency; // Linked PDA consistency reports
+pub mod constants; // Protocol constants and unit newtypes
+pub mod cpi_registry; // Allowed CPI target programs
+pub mod crank; // Optimistic locking and metrics for racing cranks
+pub mod credit_line; // Credit lines between organizations
+pub mod daily_limit; // Daily spending limits
+pub mod deactivation; // Grace period for settling obligations on deactivation
+pub mod decimals; // Decimal normalization across mints
+pub mod digest; // Human-readable operation digests for signing devices
+pub mod discriminators; // Account and event discriminators for indexers
+pub mod duplicates; // Duplicate account detection
+pub mod emissions; // Staking reward emission curves
+pub mod escrow; // Time-locked escrow
+pub mod expiry; // Permissionless expiry of time-bound accounts
+pub mod fee_strategy; // Pluggable transfer fee strategies
+pub mod fingerprint; // Configuration digest integrators pin against
+#[cfg(feature = "fixtures")]
+pub mod fixtures; // Deterministic account fixtures for tests
+pub mod guardians; // Guardian co-signers for high-value operations
+pub mod guards; // Composable instruction preconditions
+pub mod health; // Single-call account status view for wallets
+pub mod idempotency; // Idempotency keys for client retries
+pub mod instruction_tag; // Instruction tags carried by events
+pub mod interfaces; // Transfer hook and price adapter interfaces
+pub mod invariants; // Permissionless invariant assertions
+pub mod invoices; // Recurring invoice templates
+pub mod joint; // Joint (co-owned) accounts
+pub mod ledger; // Double-entry ledger
+pub mod lookup_table; // Address lookup table registration
+pub mod manager; // Delegated account management
+pub mod math; // Checked arithmetic helpers
+pub mod migration; // User account layout migrations
+pub mod mint_config; // Per-mint configuration registry
+pub mod namespace; // Namespace metadata and branding
+pub mod netting; // Bilateral obligation netting
+pub mod oracle_guard; // Oracle price deviation guard
+pub mod policy; // User-composed transfer policies
+pub mod profile; // Extended user profiles
+pub mod promo; // Promo codes and fee waivers
+pub mod rand; // Slot-hash mixed pseudo-randomness
+pub mod remaining_accounts; // Validation of remaining_accounts lists
+pub mod reserves; // Per-epoch proof of reserves of the vaults
+pub mod resize; // Rent-aware account resizing
+pub mod routing; // Multi-hop routed transfers
+pub mod shadow; // Candidate fee strategies rehearsed on live transfers
+pub mod spend_guard; // Transaction-scoped spending guards
+pub mod state_machine; // Status transition rules
+pub mod statements; // Per-period account statements
+pub mod sweep; // Lamport dust sweeper
+pub mod tally; // Vote tally strategies
+pub mod tax_lots; // Acquisition lot tracking for tax reporting
+pub mod treasury; // Transfer fee treasury
+pub mod vacation; // Scheduled deactivation windows
+pub mod vault; // SPL token vaults for deposits and withdrawals
+pub mod versions; // Instruction version registry and sunsets
+pub mod watchtower; // Monitoring services that can freeze outflows
+
+pub use account_mode::*;
+pub use activity_log::*;
+pub use allowance::*;
+pub use attachments::*;
+pub use attestations::*;
+pub use auction::*;
+pub use batch::*;
+pub use bootstrap::*;
+pub use bridge::*;
+pub use capability::*;
+pub use cashback::*;
+pub use chaos::*;
+pub use circuit_breaker::*;
+pub use clock::*;
+pub use commit_reveal::*;
+pub use compliance_report::*;
+pub use config::*;
+pub use consistency::*;
+pub use constants::*;
+pub use cpi_registry::*;
+pub use crank::*;
+pub use credit_line::*;
+pub use daily_limit::*;
+pub use deactivation::*;
+pub use digest::*;
+pub use duplicates::*;
+pub use emissions::*;
+pub use escrow::*;
+pub use expiry::*;
+pub use fee_strategy::*;
+pub use fingerprint::*;
+pub use guardians::*;
+pub use guards::*;
+pub use health::*;
+pub use idempotency::*;
+pub use instruction_tag::*;
+pub use interfaces::*;
+pub use invariants::*;
+pub use invoices::*;
+pub use joint::*;
+pub use ledger::*;
+pub use lookup_table::*;
+pub use manager::*;
+pub use migration::*;
+pub use mint_config::*;
+pub use namespace::*;
+pub use netting::*;
+pub use oracle_guard::*;
+pub use policy::*;
+pub use profile::*;
+pub use promo::*;
+pub use remaining_accounts::*;
+pub use reserves::*;
+pub use resize::*;
+pub use routing::*;
+pub use shadow::*;
+pub use spend_guard::*;
+pub use state_machine::*;
+pub use statements::*;
+pub use sweep::*;
+pub use tax_lots::*;
+pub use treasury::*;
+pub use vacation::*;
+pub use vault::*;
+pub use versions::*;
+pub use watchtower::*;
 
 // Program ID declaration - this is the unique identifier for our program
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
@@ -30,6 +170,154 @@ declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 pub mod anchor_test_contract {
     use super::*; // Import parent scope items
 
+    /*
+     * CONFIGURATION INSTRUCTIONS
+     * ==========================
+     * Program-wide settings controlled by the config admin.
+     */
+
+    /// Create a namespace: an independent config with its own admin
+    /// The signer becomes the admin; all instructions start enabled
+    ///
+    /// # Arguments
+    /// * `namespace` - Identifier of the new namespace, part of every PDA
+    pub fn create_namespace(ctx: Context<CreateNamespace>, namespace: u64) -> Result<()> {
+        config::handle_create_namespace(ctx, namespace)
+    }
+
+    /// Replace the bitmask of disabled instructions (admin only)
+    ///
+    /// # Arguments
+    /// * `mask` - Bitwise OR of `instruction_flags` constants to disable
+    pub fn set_disabled_instructions(ctx: Context<AdminConfig>, mask: u64) -> Result<()> {
+        config::handle_set_disabled_instructions(ctx, mask)
+    }
+
+    /// Linearly ramp the protocol fee rate to a new value (admin only)
+    ///
+    /// # Arguments
+    /// * `new_bps` - Target fee rate in basis points
+    /// * `duration` - Seconds over which the rate moves; zero applies immediately
+    pub fn ramp_fee_rate(ctx: Context<AdminConfig>, new_bps: u64, duration: i64) -> Result<()> {
+        config::handle_ramp_fee_rate(ctx, new_bps, duration)
+    }
+
+    /// Set the volume multiple that trips the circuit breaker (admin only)
+    ///
+    /// # Arguments
+    /// * `multiple_bps` - Multiple of the trailing average in bps; zero disables
+    pub fn set_circuit_breaker_multiple(
+        ctx: Context<AdminConfig>,
+        multiple_bps: u64,
+    ) -> Result<()> {
+        config::handle_set_circuit_breaker_multiple(ctx, multiple_bps)
+    }
+
+    /// Create the epoch volume tracker (admin only)
+    pub fn initialize_epoch_stats(ctx: Context<InitializeEpochStats>) -> Result<()> {
+        circuit_breaker::handle_initialize_epoch_stats(ctx)
+    }
+
+    /// Clear a tripped circuit breaker so transfers resume (admin only)
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+        circuit_breaker::handle_reset_circuit_breaker(ctx)
+    }
+
+    /// Create the double-entry ledger (admin only)
+    pub fn initialize_ledger(ctx: Context<InitializeLedger>) -> Result<()> {
+        ledger::handle_initialize_ledger(ctx)
+    }
+
+    /// Assert the ledger invariants (permissionless)
+    /// Fails if debits and credits diverge or any ledger account is negative
+    pub fn verify_ledger(ctx: Context<VerifyLedger>) -> Result<()> {
+        ledger::handle_verify_ledger(ctx)
+    }
+
+    /// Recompute solvency and consistency invariants (permissionless)
+    ///
+    /// # Arguments
+    /// * `complete` - Whether `remaining_accounts` holds every user account and escrow
+    ///
+    /// User accounts and escrows are passed in `remaining_accounts`; the instruction
+    /// reverts if any invariant does not hold.
+    pub fn assert_invariants<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AssertInvariants<'info>>,
+        complete: bool,
+    ) -> Result<()> {
+        invariants::handle_assert_invariants(ctx, complete)
+    }
+
+    /// Fail unless a user's balance lies within `[min, max]`
+    ///
+    /// Read-only; for composing protocols to append after their CPIs.
+    pub fn assert_balance(ctx: Context<AssertBalance>, min: u64, max: u64) -> Result<()> {
+        invariants::handle_assert_balance(ctx, min, max)
+    }
+
+    /// Fail unless the ledger is solvent and a mint's vault covers its deposits
+    ///
+    /// Read-only; for composing protocols to append after their CPIs.
+    pub fn assert_vault_solvency(ctx: Context<AssertVaultSolvency>) -> Result<()> {
+        invariants::handle_assert_vault_solvency(ctx)
+    }
+
+    /// List a supported mint in the registry (admin only)
+    /// Caches the mint decimals alongside the supplied settings
+    pub fn list_mint(ctx: Context<ListMint>, params: MintConfigParams) -> Result<()> {
+        mint_config::handle_list_mint(ctx, params)
+    }
+
+    /// Update the settings of a listed mint (admin only)
+    pub fn update_mint_config(ctx: Context<ManageMint>, params: MintConfigParams) -> Result<()> {
+        mint_config::handle_update_mint_config(ctx, params)
+    }
+
+    /// Delist a mint and reclaim its registry rent (admin only)
+    pub fn delist_mint(ctx: Context<DelistMint>) -> Result<()> {
+        mint_config::handle_delist_mint(ctx)
+    }
+
+    /// Set the oracle price deviation limits (admin only)
+    ///
+    /// # Arguments
+    /// * `max_deviation_bps` - Largest accepted move from the last price; zero disables
+    /// * `window_secs` - How long an accepted price stays the reference
+    pub fn set_price_deviation_limits(
+        ctx: Context<AdminConfig>,
+        max_deviation_bps: u64,
+        window_secs: i64,
+    ) -> Result<()> {
+        config::handle_set_price_deviation_limits(ctx, max_deviation_bps, window_secs)
+    }
+
+    /// Re-anchor the accepted oracle price of a mint (admin only)
+    /// Used after a genuine price move tripped the deviation guard
+    pub fn override_oracle_price(ctx: Context<ManageMint>, price: u64) -> Result<()> {
+        oracle_guard::handle_override_oracle_price(ctx, price)
+    }
+
+    /// Create the program's address lookup table (admin only)
+    ///
+    /// # Arguments
+    /// * `recent_slot` - Recent slot used to derive the table address
+    ///
+    /// Seeds the table with config, epoch stats, ledger and the mint
+    /// configs passed in `remaining_accounts`.
+    pub fn create_lookup_table<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ManageLookupTable<'info>>,
+        recent_slot: u64,
+    ) -> Result<()> {
+        lookup_table::handle_create_lookup_table(ctx, recent_slot)
+    }
+
+    /// Add program-owned accounts from `remaining_accounts` to the lookup table (admin only)
+    pub fn extend_lookup_table<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ManageLookupTable<'info>>,
+    ) -> Result<()> {
+        lookup_table::handle_extend_lookup_table(ctx)
+    }
+
     /*
      * INITIALIZATION INSTRUCTION
      * ==========================
@@ -41,7 +329,7 @@ pub mod anchor_test_contract {
     ///
     /// # Arguments
     /// * `ctx` - The context containing accounts and program information
-    /// * `name` - The user's display name (max 32 characters)
+    /// * `name` - The user's display name (at most `MAX_NAME_LEN` bytes)
     /// * `age` - The user's age (must be positive)
     ///
     /// # Returns
@@ -51,6 +339,8 @@ pub mod anchor_test_contract {
         name: String,                 // User name parameter
         age: u8,                      // User age parameter
     ) -> Result<()> {
+        require_instruction_enabled(&ctx.accounts.config, instruction_flags::INITIALIZE_USER)?;
+
         // Get mutable reference to user account
         let user_account = &mut ctx.accounts.user;
 
@@ -58,7 +348,7 @@ pub mod anchor_test_contract {
          * Input validation section
          * Ensure the provided parameters meet our requirements
          */
-        require!(name.len() <= 32, CustomError::NameTooLong); // Name length check
+        require!(name.len() <= MAX_NAME_LEN, CustomError::NameTooLong); // Name length check
         require!(age > 0, CustomError::InvalidAge); // Age validation
 
         // Set account fields with provided values
@@ -66,7 +356,14 @@ pub mod anchor_test_contract {
         user_account.name = name; // Set name
         user_account.age = age; // Set age
         user_account.balance = 0; // Initialize balance to zero
-        user_account.is_active = true; // Mark account as active
+        user_account.mode = AccountMode::Normal; // Sends and receives
+        user_account.compliance_hold = false; // No compliance override
+        user_account.deactivated_at = 0; // Never deactivated
+        user_account.guardian_threshold = 0; // No guardians
+        user_account.daily_limit = 0; // No spending limit
+        user_account.open_obligations = 0; // Nothing to settle
+        user_account.uncounted_obligations = false; // Every obligation is counted
+        user_account.version = USER_ACCOUNT_VERSION; // Current layout
         user_account.created_at = Clock::get()?.unix_timestamp; // Set creation timestamp
 
         // Log successful initialization
@@ -82,19 +379,20 @@ pub mod anchor_test_contract {
         new_name: Option<String>, // Optional new name
         new_age: Option<u8>,      // Optional new age
     ) -> Result<()> {
-        let user_account = &mut ctx.accounts.user; // Get mutable user account reference
-
-        // Update name if provided
-        if let Some(name) = new_name {
-            require!(name.len() <= 32, CustomError::NameTooLong); // Validate name length
-            user_account.name = name; // Update name
-        }
+        require_instruction_enabled(&ctx.accounts.config, instruction_flags::UPDATE_USER)?;
 
-        // Update age if provided
-        if let Some(age) = new_age {
-            require!(age > 0, CustomError::InvalidAge); // Validate age
-            user_account.age = age; // Update age
-        }
+        apply_user_update(&mut ctx.accounts.user, new_name, new_age)?; // Validate and apply
+        log_activity(
+            &ctx.accounts.activity_log,
+            ctx.accounts.user.key(),
+            ActivityEntry::new(
+                ActivityKind::Updated,
+                InstructionTag::UpdateUser,
+                Pubkey::default(),
+                0,
+                Clock::get()?.unix_timestamp,
+            ),
+        )?;
 
         msg!("User account updated successfully"); // Log update
         Ok(()) // Return success
@@ -107,64 +405,1069 @@ pub mod anchor_test_contract {
      * It includes comprehensive validation and error handling.
      */
 
-    /// Transfer tokens between user accounts
+    /// Transfer tokens between user accounts
+    ///
+    /// # Security Considerations
+    /// - Validates transfer amount is positive
+    /// - Checks sender has sufficient balance
+    /// - Ensures the sender may send and the receiver may receive
+    /// - Rejects the same account as sender and receiver
+    /// - Updates balances atomically
+    /// - Rejects a repeated `idempotency_key` within the dedup window
+    ///
+    /// Superseded by `transfer_tokens_v2`; fails with `InstructionDeprecated`
+    /// once the namespace sets its sunset.
+    pub fn transfer_tokens(
+        mut ctx: Context<TransferTokens>,
+        amount: u64, // Transfer amount in smallest token units
+        idempotency_key: Option<IdempotencyKey>, // Optional client retry key
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require_instruction_supported(&ctx.accounts.config, InstructionTag::TransferTokens, now)?;
+        execute_transfer_tokens(
+            &mut ctx,
+            amount,
+            idempotency_key,
+            InstructionTag::TransferTokens,
+        )
+    }
+
+    /// Deactivate a user account
+    /// This is a security measure to disable compromised accounts
+    /// Accounts with open obligations stay Deactivating until they settle
+    pub fn deactivate_user(ctx: Context<DeactivateUser>) -> Result<()> {
+        require_instruction_enabled(&ctx.accounts.config, instruction_flags::DEACTIVATE_USER)?;
+
+        let user_account = &mut ctx.accounts.user; // Get user account
+
+        require!(
+            user_account.is_active(),
+            CustomError::AccountAlreadyInactive
+        ); // Must be active
+
+        user_account.begin_deactivation(Clock::get()?.unix_timestamp); // Starts the cooldown
+        log_activity(
+            &ctx.accounts.activity_log,
+            user_account.key(),
+            ActivityEntry::new(
+                ActivityKind::Deactivated,
+                InstructionTag::DeactivateUser,
+                Pubkey::default(),
+                0,
+                user_account.deactivated_at,
+            ),
+        )?;
+
+        msg!(
+            "User account deactivated: {}, mode {:?}",
+            user_account.authority,
+            user_account.mode
+        ); // Log deactivation
+        Ok(()) // Success
+    }
+
+    /// Re-enable a deactivated account once the namespace's cooldown has elapsed
+    pub fn reactivate_user(ctx: Context<ReactivateUser>) -> Result<()> {
+        require_instruction_enabled(&ctx.accounts.config, instruction_flags::REACTIVATE_USER)?;
+
+        let user_account = &mut ctx.accounts.user;
+        require!(!user_account.is_active(), CustomError::AccountAlreadyActive); // Must be deactivated
+        require!(
+            !user_account.compliance_hold,
+            CustomError::ModeHeldByCompliance
+        ); // Compliance locks are lifted by compliance
+
+        let cooldown = Seconds(ctx.accounts.config.reactivation_cooldown_secs);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= cooldown.after(user_account.deactivated_at),
+            CustomError::CooldownNotElapsed
+        );
+        user_account.require_reactivation_approval(&signer_keys(ctx.remaining_accounts))?;
+
+        user_account.mode = AccountMode::Normal; // Sends and receives again
+        user_account.deactivated_at = 0;
+
+        msg!("User account reactivated: {}", user_account.authority);
+        Ok(())
+    }
+
+    /// Close an empty user account and refund its rent to the authority
+    pub fn close_user(ctx: Context<CloseUser>) -> Result<()> {
+        require_instruction_enabled(&ctx.accounts.config, instruction_flags::CLOSE_USER)?;
+        require!(ctx.accounts.user.balance == 0, CustomError::BalanceNotZero); // Never burn funds
+
+        msg!("User account closed: {}", ctx.accounts.user.authority);
+        Ok(()) // `close = authority` moves the lamports
+    }
+
+    /*
+     * JOINT ACCOUNT INSTRUCTIONS
+     * ==========================
+     * Conversion to shared ownership and co-signed transfers.
+     */
+
+    /// Convert an existing account into a joint account
+    ///
+    /// Both the current authority and the prospective co-owner must sign.
+    /// Transfers above `threshold` then follow the signature policy in `mode`.
+    pub fn convert_to_joint(
+        ctx: Context<ConvertToJoint>,
+        threshold: u64,  // Amount above which `mode` applies
+        mode: JointMode, // RequireBoth or EitherParty
+    ) -> Result<()> {
+        joint::handle_convert_to_joint(ctx, threshold, mode)
+    }
+
+    /// Transfer tokens out of a joint account
+    /// Either owner may sign; the other owner co-signs when the policy requires it
+    pub fn joint_transfer(ctx: Context<JointTransfer>, amount: u64) -> Result<()> {
+        joint::handle_joint_transfer(ctx, amount)
+    }
+
+    /// Create the caller's ring buffer of recent idempotency keys
+    /// Required before attaching idempotency keys to operations
+    pub fn init_recent_operations(ctx: Context<InitRecentOperations>) -> Result<()> {
+        idempotency::handle_init_recent_operations(ctx)
+    }
+
+    /// Create the caller's on-chain activity log
+    /// Every later operation on the account appends to it
+    pub fn init_activity_log(ctx: Context<InitActivityLog>) -> Result<()> {
+        activity_log::handle_init_activity_log(ctx)
+    }
+
+    /// Schedule a vacation window blocking outbound transfers
+    ///
+    /// # Arguments
+    /// * `start` - Unix timestamp when transfers become blocked
+    /// * `end` - Unix timestamp when transfers are automatically restored
+    ///
+    /// Passing zero for both clears the schedule.
+    pub fn schedule_deactivation(
+        ctx: Context<ScheduleDeactivation>,
+        start: i64, // Window start
+        end: i64,   // Window end
+    ) -> Result<()> {
+        vacation::handle_schedule_deactivation(ctx, start, end)
+    }
+
+    /// Seed a local validator with config, stats, ledger and demo users
+    ///
+    /// # Arguments
+    /// * `namespace` - Namespace to create and populate
+    /// * `users` - Number of demo users, passed as remaining accounts
+    ///
+    /// Only functional in builds with the `bootstrap-demo` feature.
+    pub fn bootstrap_demo<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BootstrapDemo<'info>>,
+        namespace: u64,
+        users: u8,
+    ) -> Result<()> {
+        bootstrap::handle_bootstrap_demo(ctx, namespace, users)
+    }
+
+    /// Store a hashed commitment for later reveal
+    ///
+    /// # Arguments
+    /// * `domain` - Consumer instance the commitment belongs to
+    /// * `hash` - `Commitment::hash_for(owner, domain, preimage)`
+    /// * `reveal_after` - Earliest reveal timestamp
+    /// * `expires_at` - Reveals are rejected from this timestamp
+    pub fn commit(
+        ctx: Context<Commit>,
+        domain: u64,
+        hash: [u8; 32],
+        reveal_after: i64,
+        expires_at: i64,
+    ) -> Result<()> {
+        commit_reveal::handle_commit(ctx, domain, hash, reveal_after, expires_at)
+    }
+
+    /// Reveal the preimage of a commitment inside its window
+    pub fn reveal(ctx: Context<Reveal>, preimage: Vec<u8>) -> Result<()> {
+        commit_reveal::handle_reveal(ctx, preimage)
+    }
+
+    /// Open a sealed-bid (Vickrey) auction
+    ///
+    /// # Arguments
+    /// * `auction_id` - Seller-chosen identifier, part of the PDA seeds
+    /// * `commit_end` - Bids accepted before this timestamp
+    /// * `reveal_end` - Reveals accepted before this timestamp
+    /// * `reserve_price` - Minimum clearing price in lamports
+    /// * `non_reveal_penalty_bps` - Deposit share forfeited by non-revealers
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        auction_id: u64,
+        commit_end: i64,
+        reveal_end: i64,
+        reserve_price: u64,
+        non_reveal_penalty_bps: u64,
+    ) -> Result<()> {
+        auction::handle_create_auction(
+            ctx,
+            auction_id,
+            commit_end,
+            reveal_end,
+            reserve_price,
+            non_reveal_penalty_bps,
+        )
+    }
+
+    /// Place a sealed bid, locking `deposit` lamports
+    pub fn place_bid(ctx: Context<PlaceBid>, deposit: u64) -> Result<()> {
+        auction::handle_place_bid(ctx, deposit)
+    }
+
+    /// Reveal a sealed bid of `amount` committed with `salt`
+    pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+        auction::handle_reveal_bid(ctx, amount, salt)
+    }
+
+    /// Settle an auction after its reveal window (permissionless)
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        auction::handle_settle_auction(ctx)
+    }
+
+    /// Pay out and close one bid of a settled auction (permissionless)
+    pub fn claim_bid(ctx: Context<ClaimBid>) -> Result<()> {
+        auction::handle_claim_bid(ctx)
+    }
+
+    /// Cancel an auction that has not received bids
+    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+        auction::handle_cancel_auction(ctx)
+    }
+
+    /// Create attachment page `page` for the caller's account
+    pub fn create_attachment_page(ctx: Context<CreateAttachmentPage>, page: u16) -> Result<()> {
+        attachments::handle_create_attachment_page(ctx, page)
+    }
+
+    /// Anchor an off-chain document to the caller's account
+    ///
+    /// # Arguments
+    /// * `kind` - What the document is
+    /// * `cid_hash` - Hash of the document's IPFS/Arweave content identifier
+    pub fn attach_document(
+        ctx: Context<ManageAttachments>,
+        kind: DocumentKind,
+        cid_hash: [u8; 32],
+    ) -> Result<()> {
+        attachments::handle_attach_document(ctx, kind, cid_hash)
+    }
+
+    /// Remove an attached document by content hash
+    pub fn remove_document(ctx: Context<ManageAttachments>, cid_hash: [u8; 32]) -> Result<()> {
+        attachments::handle_remove_document(ctx, cid_hash)
+    }
+
+    /// Register an attestation issuer (admin only)
+    pub fn register_issuer(ctx: Context<RegisterIssuer>, issuer: Pubkey) -> Result<()> {
+        attestations::handle_register_issuer(ctx, issuer)
+    }
+
+    /// Activate or deactivate an attestation issuer (admin only)
+    pub fn set_issuer_active(ctx: Context<SetIssuerActive>, active: bool) -> Result<()> {
+        attestations::handle_set_issuer_active(ctx, active)
+    }
+
+    /// Publish a typed claim about a user
+    ///
+    /// # Arguments
+    /// * `claim_type` - One of `claim_types`, or a custom type
+    /// * `data` - Claim-specific payload
+    /// * `expires_at` - Expiry timestamp, 0 for none
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        claim_type: u16,
+        data: [u8; 32],
+        expires_at: i64,
+    ) -> Result<()> {
+        attestations::handle_issue_attestation(ctx, claim_type, data, expires_at)
+    }
+
+    /// Revoke an attestation (issuer only)
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        attestations::handle_revoke_attestation(ctx)
+    }
+
+    /// Check that an attestation proves `claim_type` about `user`
+    pub fn verify_attestation(
+        ctx: Context<VerifyAttestation>,
+        user: Pubkey,
+        claim_type: u16,
+    ) -> Result<()> {
+        attestations::handle_verify_attestation(ctx, user, claim_type)
+    }
+
+    /// Report on the cross-references between a user and its linked PDAs
+    /// Attachment pages of the user are passed as remaining accounts
+    pub fn check_user_consistency<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckUserConsistency<'info>>,
+    ) -> Result<()> {
+        consistency::handle_check_user_consistency(ctx)
+    }
+
+    /// Publish the namespace's name, metadata hash and default fees (admin only)
+    pub fn create_namespace_metadata(
+        ctx: Context<CreateNamespaceMetadata>,
+        params: NamespaceMetadataParams,
+    ) -> Result<()> {
+        namespace::handle_create_namespace_metadata(ctx, params)
+    }
+
+    /// Replace the namespace's published metadata (admin only)
+    pub fn update_namespace_metadata(
+        ctx: Context<UpdateNamespaceMetadata>,
+        params: NamespaceMetadataParams,
+    ) -> Result<()> {
+        namespace::handle_update_namespace_metadata(ctx, params)
+    }
+
+    /// Propose a bridge to another namespace (admin of either side)
+    ///
+    /// # Arguments
+    /// * `fee_bps` - Total fee withheld from each bridged transfer
+    /// * `source_share_bps` - Share of the fee kept by the sending namespace
+    ///
+    /// Counts as the proposer's consent; the other admin must approve.
+    pub fn propose_namespace_bridge(
+        ctx: Context<ProposeNamespaceBridge>,
+        fee_bps: u64,
+        source_share_bps: u64,
+    ) -> Result<()> {
+        bridge::handle_propose_namespace_bridge(ctx, fee_bps, source_share_bps)
+    }
+
+    /// Approve a proposed bridge, echoing its terms (admin of either side)
+    pub fn approve_namespace_bridge(
+        ctx: Context<ManageNamespaceBridge>,
+        fee_bps: u64,
+        source_share_bps: u64,
+    ) -> Result<()> {
+        bridge::handle_approve_namespace_bridge(ctx, fee_bps, source_share_bps)
+    }
+
+    /// Close a bridge and refund its rent to the proposer (admin of either side)
+    pub fn close_namespace_bridge(ctx: Context<ManageNamespaceBridge>) -> Result<()> {
+        bridge::handle_close_namespace_bridge(ctx)
+    }
+
+    /// Transfer to a user of another namespace over an active bridge
+    /// The bridge fee is withheld from the receiver's credit
+    pub fn cross_namespace_transfer(
+        ctx: Context<CrossNamespaceTransfer>,
+        amount: u64,
+    ) -> Result<()> {
+        bridge::handle_cross_namespace_transfer(ctx, amount)
+    }
+
+    /// Shrink an over-allocated attachment page to fit its entries
+    /// The freed rent is refunded to the authority
+    pub fn compact_account(ctx: Context<CompactAccount>) -> Result<()> {
+        attachments::handle_compact_account(ctx)
+    }
+
+    /// Move lamports above rent exemption from an allowlisted PDA to the
+    /// namespace treasury (permissionless crank)
+    pub fn sweep_excess_lamports(ctx: Context<SweepExcessLamports>) -> Result<()> {
+        sweep::handle_sweep_excess_lamports(ctx)
+    }
+
+    /// Retire an expired time-bound account and return its rent to whoever
+    /// paid it (permissionless crank); see `EXPIRY_HANDLERS`
+    pub fn expire(ctx: Context<Expire>) -> Result<()> {
+        expiry::handle_expire(ctx)
+    }
+
+    /// Create the namespace's empty registry of allowed CPI targets (admin only)
+    pub fn create_cpi_registry(ctx: Context<CreateCpiRegistry>) -> Result<()> {
+        cpi_registry::handle_create_cpi_registry(ctx)
+    }
+
+    /// Allow a program as a CPI target of the namespace (admin only)
+    pub fn allow_cpi_program(ctx: Context<UpdateCpiRegistry>, program_id: Pubkey) -> Result<()> {
+        cpi_registry::handle_allow_cpi_program(ctx, program_id)
+    }
+
+    /// Stop allowing a program as a CPI target of the namespace (admin only)
+    pub fn revoke_cpi_program(ctx: Context<UpdateCpiRegistry>, program_id: Pubkey) -> Result<()> {
+        cpi_registry::handle_revoke_cpi_program(ctx, program_id)
+    }
+
+    /// Switch the strategy transfer fees are computed with (admin only)
+    pub fn set_fee_strategy(ctx: Context<AdminConfig>, strategy: FeeStrategy) -> Result<()> {
+        config::handle_set_fee_strategy(ctx, strategy)
+    }
+
+    /// Replace the staking reward curve and supply cap (admin only)
+    pub fn set_emission_schedule(
+        ctx: Context<AdminConfig>,
+        schedule: EmissionSchedule,
+    ) -> Result<()> {
+        config::handle_set_emission_schedule(ctx, schedule)
+    }
+
+    /// Choose which transfer directions the caller's account accepts
+    pub fn set_account_mode(ctx: Context<SetAccountMode>, mode: AccountMode) -> Result<()> {
+        account_mode::handle_set_account_mode(ctx, mode)
+    }
+
+    /// Impose an account mode the user cannot change (compliance only)
+    /// Setting `Normal` releases the hold
+    pub fn compliance_set_account_mode(
+        ctx: Context<ComplianceSetAccountMode>,
+        mode: AccountMode,
+    ) -> Result<()> {
+        account_mode::handle_compliance_set_account_mode(ctx, mode)
+    }
+
+    /// Hand the compliance role to another key (admin only)
+    ///
+    /// # Arguments
+    /// * `compliance` - New compliance authority
+    /// * `digest` - `operation_digest` of the authority change summary
+    pub fn set_compliance_authority(
+        ctx: Context<AdminConfig>,
+        compliance: Pubkey,
+        digest: [u8; 32],
+    ) -> Result<()> {
+        config::handle_set_compliance_authority(ctx, compliance, digest)
+    }
+
+    /// Let a custodian perform some operations on the caller's account
+    ///
+    /// # Arguments
+    /// * `manager` - Key allowed to act
+    /// * `permissions` - Bitwise OR of `manager_permissions` constants
+    /// * `expires_at` - Timestamp the grant lapses at
+    pub fn grant_manager(
+        ctx: Context<GrantManager>,
+        manager: Pubkey,
+        permissions: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        manager::handle_grant_manager(ctx, manager, permissions, expires_at)
+    }
+
+    /// Revoke a manager's grant and reclaim its rent
+    pub fn revoke_manager(ctx: Context<RevokeManager>) -> Result<()> {
+        manager::handle_revoke_manager(ctx)
+    }
+
+    /// Update a user's profile as their manager
+    pub fn manager_update_user(
+        ctx: Context<ManagerUpdateUser>,
+        new_name: Option<String>,
+        new_age: Option<u8>,
+    ) -> Result<()> {
+        manager::handle_manager_update_user(ctx, new_name, new_age)
+    }
+
+    /// Create the token vault of a listed mint (admin only)
+    pub fn create_vault(ctx: Context<CreateVault>) -> Result<()> {
+        vault::handle_create_vault(ctx)
+    }
+
+    /// Open the caller's position in a mint's vault, needed to deposit it
+    pub fn open_vault_position(ctx: Context<OpenVaultPosition>) -> Result<()> {
+        vault::handle_open_vault_position(ctx)
+    }
+
+    /// Move tokens from the caller's token account into the vault
+    ///
+    /// # Arguments
+    /// * `amount` - Raw tokens to deposit, in the mint's decimals
+    pub fn deposit_tokens(ctx: Context<MoveTokens>, amount: u64) -> Result<()> {
+        vault::handle_deposit_tokens(ctx, amount)
+    }
+
+    /// Pay tokens out of the vault to the caller's token account
+    ///
+    /// # Arguments
+    /// * `amount` - Internal units to debit from the caller's balance and
+    ///   their deposits of the mint
+    /// * `digest` - `operation_digest` of the withdrawal summary; required
+    ///   from `LARGE_WITHDRAWAL_THRESHOLD`
+    pub fn withdraw_tokens(
+        ctx: Context<MoveTokens>,
+        amount: u64,
+        digest: Option<[u8; 32]>,
+    ) -> Result<()> {
+        vault::handle_withdraw_tokens(ctx, amount, digest)
+    }
+
+    /// Create the reserves attestation of a listed mint (admin only)
+    pub fn init_reserves_attestation(ctx: Context<InitReservesAttestation>) -> Result<()> {
+        reserves::handle_init_reserves_attestation(ctx)
+    }
+
+    /// Attest a vault's reserves against its deposits, once per epoch
+    /// Anyone may crank it; the figures are emitted and kept on chain
+    pub fn attest_reserves(ctx: Context<AttestReserves>) -> Result<()> {
+        reserves::handle_attest_reserves(ctx)
+    }
+
+    /// Book tokens sent straight to a vault as treasury fees (admin only)
+    pub fn skim_donations(ctx: Context<SkimDonations>) -> Result<()> {
+        vault::handle_skim_donations(ctx)
+    }
+
+    /// Pause or unpause every gated instruction (admin only)
+    pub fn set_paused(ctx: Context<AdminConfig>, paused: bool) -> Result<()> {
+        config::handle_set_paused(ctx, paused)
+    }
+
+    /// Let a delegate transfer up to `amount` out of the caller's account
+    ///
+    /// # Arguments
+    /// * `delegate` - Key allowed to call `transfer_from`
+    /// * `amount` - Total the delegate may transfer
+    pub fn approve_delegate(
+        ctx: Context<ApproveDelegate>,
+        delegate: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        allowance::handle_approve_delegate(ctx, delegate, amount)
+    }
+
+    /// Revoke a delegate's allowance and reclaim its rent
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        allowance::handle_revoke_delegate(ctx)
+    }
+
+    /// Transfer out of an owner's account as their delegate
+    pub fn transfer_from(ctx: Context<TransferFrom>, amount: u64) -> Result<()> {
+        allowance::handle_transfer_from(ctx, amount)
+    }
+
+    /// Set how long deactivated accounts wait before reactivating (admin only)
+    pub fn set_reactivation_cooldown(ctx: Context<AdminConfig>, cooldown_secs: i64) -> Result<()> {
+        config::handle_set_reactivation_cooldown(ctx, cooldown_secs)
+    }
+
+    /// Set the slack on the namespace's deadline comparisons (admin only)
+    pub fn set_clock_tolerance(ctx: Context<AdminConfig>, tolerance_secs: i64) -> Result<()> {
+        config::handle_set_clock_tolerance(ctx, tolerance_secs)
+    }
+
+    /// Retire a superseded instruction version from `sunset_at` (admin only)
+    ///
+    /// # Arguments
+    /// * `instruction` - Superseded entrypoint in `INSTRUCTION_VERSIONS`
+    /// * `sunset_at` - Unix timestamp calls start failing, 0 to keep serving it
+    pub fn set_instruction_sunset(
+        ctx: Context<AdminConfig>,
+        instruction: InstructionTag,
+        sunset_at: i64,
+    ) -> Result<()> {
+        config::handle_set_instruction_sunset(ctx, instruction, sunset_at)
+    }
+
+    /// Create the namespace's fee treasury (admin only)
+    pub fn initialize_fee_treasury(ctx: Context<InitializeFeeTreasury>) -> Result<()> {
+        treasury::handle_initialize_fee_treasury(ctx)
+    }
+
+    /// Credit collected transfer fees to a user account (admin only)
+    ///
+    /// # Arguments
+    /// * `amount` - Fees to withdraw, at most the treasury balance
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        treasury::handle_withdraw_treasury(ctx, amount)
+    }
+
+    /// Open a spending guard; an `end_guard` must follow in the same transaction
+    ///
+    /// # Arguments
+    /// * `max_outflow` - Largest balance decrease `end_guard` accepts
+    pub fn begin_guard(ctx: Context<BeginGuard>, max_outflow: u64) -> Result<()> {
+        spend_guard::handle_begin_guard(ctx, max_outflow)
+    }
+
+    /// Close the spending guard, reverting if its max outflow was exceeded
+    pub fn end_guard(ctx: Context<EndGuard>) -> Result<()> {
+        spend_guard::handle_end_guard(ctx)
+    }
+
+    /// Transfer to every receiver in `remaining_accounts`, all or nothing
+    ///
+    /// # Arguments
+    /// * `amounts` - Amount for each receiver, in `remaining_accounts` order
+    pub fn batch_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchTransfer<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        batch::handle_batch_transfer(ctx, amounts)
+    }
+
+    /// Transfer through intermediate accounts to the last remaining account
+    ///
+    /// # Arguments
+    /// * `path` - Policy of each intermediate, in `remaining_accounts` order
+    /// * `amount` - Amount debited from the sender
+    pub fn routed_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RoutedTransfer<'info>>,
+        path: Vec<RouteHop>,
+        amount: u64,
+    ) -> Result<()> {
+        routing::handle_routed_transfer(ctx, path, amount)
+    }
+
+    /// Replace the caller's guardian set
+    /// Once guardians are set, their threshold must co-sign any change
+    ///
+    /// # Arguments
+    /// * `guardians` - Up to `MAX_GUARDIANS` distinct keys, empty to clear
+    /// * `threshold` - Guardian signatures required, 0 exactly when clearing
+    /// * `limit` - Outflow the authority may move alone per operation
+    pub fn set_guardians(
+        ctx: Context<SetGuardians>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        limit: u64,
+    ) -> Result<()> {
+        guardians::handle_set_guardians(ctx, guardians, threshold, limit)
+    }
+
+    /// Lock an account on behalf of a threshold of its guardians
+    pub fn guardian_deactivate_user(ctx: Context<GuardianDeactivateUser>) -> Result<()> {
+        guardians::handle_guardian_deactivate_user(ctx)
+    }
+
+    /// Create the obligation account of two user accounts
+    pub fn open_obligation(ctx: Context<OpenObligation>) -> Result<()> {
+        netting::handle_open_obligation(ctx)
+    }
+
+    /// Acknowledge owing the counterparty `amount`, settled later by `settle_net`
+    pub fn record_obligation(ctx: Context<RecordObligation>, amount: u64) -> Result<()> {
+        netting::handle_record_obligation(ctx, amount)
+    }
+
+    /// Net the pair's obligations and transfer only the difference
+    pub fn settle_net(ctx: Context<SettleNet>) -> Result<()> {
+        netting::handle_settle_net(ctx)
+    }
+
+    /// Bring a user account created under an older layout to the current one
+    ///
+    /// Permissionless: the payer only covers the rent of the grown account.
+    pub fn migrate_user(ctx: Context<MigrateUser>) -> Result<()> {
+        migration::handle_migrate_user(ctx)
+    }
+
+    /// Bill the payer `amount` every `interval`, starting now
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of every invoice
+    /// * `interval` - Time between invoices, at least `MIN_INVOICE_INTERVAL`
+    pub fn create_invoice_template(
+        ctx: Context<CreateInvoiceTemplate>,
+        amount: u64,
+        interval: Seconds,
+    ) -> Result<()> {
+        invoices::handle_create_invoice_template(ctx, amount, interval)
+    }
+
+    /// Issue a template's next invoice once due (permissionless crank)
+    pub fn issue_invoice(ctx: Context<IssueInvoice>, expected_version: u64) -> Result<()> {
+        invoices::handle_issue_invoice(ctx, expected_version)
+    }
+
+    /// Pay an invoice in full, refunding its rent to the crank
+    pub fn pay_invoice(ctx: Context<PayInvoice>) -> Result<()> {
+        invoices::handle_pay_invoice(ctx)
+    }
+
+    /// Reject an unpaid invoice as its payer, cancelling the template
+    pub fn reject_invoice(ctx: Context<RejectInvoice>) -> Result<()> {
+        invoices::handle_reject_invoice(ctx)
+    }
+
+    /// List an invoice the caller owns for sale, or withdraw it
+    ///
+    /// # Arguments
+    /// * `price` - Asking price, at most the invoice amount; zero withdraws it
+    pub fn sell_invoice(ctx: Context<SellInvoice>, price: u64) -> Result<()> {
+        invoices::handle_sell_invoice(ctx, price)
+    }
+
+    /// Buy a listed invoice, becoming the account it pays
+    ///
+    /// # Arguments
+    /// * `price` - Asking price the buyer agrees to, guarding against relisting
+    pub fn buy_invoice(ctx: Context<BuyInvoice>, price: u64) -> Result<()> {
+        invoices::handle_buy_invoice(ctx, price)
+    }
+
+    /// Stop a template from issuing further invoices
+    pub fn cancel_invoice_template(ctx: Context<CancelInvoiceTemplate>) -> Result<()> {
+        invoices::handle_cancel_invoice_template(ctx)
+    }
+
+    /// Create a promo code stored by hash (admin only)
+    ///
+    /// # Arguments
+    /// * `code_hash` - `promo_code_hash` of the code
+    /// * `reward` - Fee waiver or bonus credit granted on redemption
+    /// * `max_uses` - Redemptions allowed in total
+    /// * `expires_at` - Redeemable until this timestamp
+    pub fn create_promo_code(
+        ctx: Context<CreatePromoCode>,
+        code_hash: [u8; 32],
+        reward: PromoReward,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        promo::handle_create_promo_code(ctx, code_hash, reward, max_uses, expires_at)
+    }
+
+    /// Transfer tokens, redeeming a promo code for the sender
+    ///
+    /// # Arguments
+    /// * `amount` - Transfer amount in smallest token units
+    /// * `code` - Plaintext promo code, hashed to find the promo
+    pub fn promo_transfer(ctx: Context<PromoTransfer>, amount: u64, code: String) -> Result<()> {
+        promo::handle_promo_transfer(ctx, amount, code)
+    }
+
+    /// Lock balance for a beneficiary until a timestamp
+    ///
+    /// # Arguments
+    /// * `amount` - Balance moved into the escrow
+    /// * `unlock_at` - Timestamp from which the beneficiary may claim
+    /// * `beneficiary` - Authority allowed to claim
+    pub fn create_escrow(
+        ctx: Context<CreateEscrow>,
+        amount: u64,
+        unlock_at: i64,
+        beneficiary: Pubkey,
+    ) -> Result<()> {
+        escrow::handle_create_escrow(ctx, amount, unlock_at, beneficiary)
+    }
+
+    /// Release an unlocked escrow to the beneficiary
+    pub fn claim_escrow(ctx: Context<ClaimEscrow>) -> Result<()> {
+        escrow::handle_claim_escrow(ctx)
+    }
+
+    /// Reclaim an escrow before it unlocks (creator only)
+    pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
+        escrow::handle_cancel_escrow(ctx)
+    }
+
+    /// Create the namespace's merchant cashback program (admin only)
+    ///
+    /// # Arguments
+    /// * `rate` - Share of merchant payments credited back to the payer
+    /// * `epoch_budget` - Most the treasury pays out per epoch
+    pub fn initialize_cashback(
+        ctx: Context<InitializeCashback>,
+        rate: Bps,
+        epoch_budget: u64,
+    ) -> Result<()> {
+        cashback::handle_initialize_cashback(ctx, rate, epoch_budget)
+    }
+
+    /// Change the cashback rate and per-epoch budget (admin only)
+    ///
+    /// # Arguments
+    /// * `rate` - Share of merchant payments credited back to the payer
+    /// * `epoch_budget` - Most the treasury pays out per epoch
+    pub fn set_cashback(ctx: Context<SetCashback>, rate: Bps, epoch_budget: u64) -> Result<()> {
+        cashback::handle_set_cashback(ctx, rate, epoch_budget)
+    }
+
+    /// Register a user account as a cashback merchant (admin only)
+    pub fn register_merchant(ctx: Context<RegisterMerchant>) -> Result<()> {
+        cashback::handle_register_merchant(ctx)
+    }
+
+    /// Remove a cashback merchant registration (admin only)
+    pub fn deregister_merchant(ctx: Context<DeregisterMerchant>) -> Result<()> {
+        cashback::handle_deregister_merchant(ctx)
+    }
+
+    /// Extend a credit line from the caller's account to the borrower's
+    ///
+    /// # Arguments
+    /// * `limit` - Most principal the borrower may have drawn at once
+    /// * `rate` - Annual simple interest on drawn principal
+    pub fn open_credit_line(ctx: Context<OpenCreditLine>, limit: u64, rate: Bps) -> Result<()> {
+        credit_line::handle_open_credit_line(ctx, limit, rate)
+    }
+
+    /// Draw on a credit line extended to the caller (borrower only)
+    pub fn draw_credit(ctx: Context<UseCreditLine>, amount: u64) -> Result<()> {
+        credit_line::handle_draw_credit(ctx, amount)
+    }
+
+    /// Repay a credit line's accrued interest, then principal (borrower only)
+    pub fn repay_credit(ctx: Context<UseCreditLine>, amount: u64) -> Result<()> {
+        credit_line::handle_repay_credit(ctx, amount)
+    }
+
+    /// Block or re-allow new draws on a credit line (lender only)
+    pub fn set_credit_line_frozen(ctx: Context<SetCreditLineFrozen>, frozen: bool) -> Result<()> {
+        credit_line::handle_set_credit_line_frozen(ctx, frozen)
+    }
+
+    /// Cap what the caller's account may send per day
+    /// Raising or removing the limit only takes effect after a day
+    ///
+    /// # Arguments
+    /// * `daily_limit` - Most sent per 24-hour window, 0 for unlimited
+    pub fn set_daily_limit(ctx: Context<SetDailyLimit>, daily_limit: u64) -> Result<()> {
+        daily_limit::handle_set_daily_limit(ctx, daily_limit)
+    }
+
+    /// Create the caller's extended profile
+    ///
+    /// # Arguments
+    /// * `params` - Avatar URI, email hash and free-form metadata
+    pub fn create_profile(ctx: Context<CreateProfile>, params: ProfileParams) -> Result<()> {
+        profile::handle_create_profile(ctx, params)
+    }
+
+    /// Replace the caller's extended profile, resizing it to fit
+    ///
+    /// # Arguments
+    /// * `params` - Avatar URI, email hash and free-form metadata
+    pub fn update_profile(ctx: Context<UpdateProfile>, params: ProfileParams) -> Result<()> {
+        profile::handle_update_profile(ctx, params)
+    }
+
+    /// Summarize a user's activity over an ended statement period, returning
+    /// the statement and emitting it with its digest
+    ///
+    /// # Arguments
+    /// * `epoch` - Statement period index, `STATEMENT_PERIOD`s since the Unix epoch
+    pub fn generate_statement(
+        ctx: Context<GenerateStatement>,
+        epoch: u64,
+    ) -> Result<AccountStatement> {
+        statements::handle_generate_statement(ctx, epoch)
+    }
+
+    /// Start tracking the caller's acquisition lots
+    ///
+    /// # Arguments
+    /// * `method` - Order outgoing transfers consume lots in
+    pub fn init_tax_lots(ctx: Context<InitTaxLots>, method: LotMethod) -> Result<()> {
+        tax_lots::handle_init_tax_lots(ctx, method)
+    }
+
+    /// Add a page after the caller's newest tax lot page
+    pub fn add_tax_lot_page(ctx: Context<AddTaxLotPage>) -> Result<()> {
+        tax_lots::handle_add_tax_lot_page(ctx)
+    }
+
+    /// Change the order the caller's lots are consumed in
+    ///
+    /// # Arguments
+    /// * `method` - FIFO or LIFO
+    pub fn set_lot_method(ctx: Context<SetLotMethod>, method: LotMethod) -> Result<()> {
+        tax_lots::handle_set_lot_method(ctx, method)
+    }
+
+    /// Close an emptied tax lot page, refunding its rent
+    pub fn close_tax_lot_page(ctx: Context<CloseTaxLotPage>) -> Result<()> {
+        tax_lots::handle_close_tax_lot_page(ctx)
+    }
+
+    /// Aggregate a user's activity over an ended time range for auditors,
+    /// returning the report and emitting it with its digest
+    ///
+    /// # Arguments
+    /// * `from` - First second of the range
+    /// * `to` - First second after the range, not in the future
+    pub fn compile_compliance_report(
+        ctx: Context<CompileComplianceReport>,
+        from: i64,
+        to: i64,
+    ) -> Result<ComplianceReport> {
+        compliance_report::handle_compile_compliance_report(ctx, from, to)
+    }
+
+    /// Register a watchtower allowed to freeze the caller's outbound transfers
+    ///
+    /// # Arguments
+    /// * `watchtower` - Monitoring service key, or the default key to remove it
+    pub fn set_watchtower(ctx: Context<SetWatchtower>, watchtower: Pubkey) -> Result<()> {
+        watchtower::handle_set_watchtower(ctx, watchtower)
+    }
+
+    /// Freeze a watched account's outbound transfers for a few hours
+    ///
+    /// # Arguments
+    /// * `reason` - What the watchtower detected
+    pub fn raise_alert(ctx: Context<RaiseAlert>, reason: AlertReason) -> Result<()> {
+        watchtower::handle_raise_alert(ctx, reason)
+    }
+
+    /// Dismiss the caller's watchtower alert, restoring outbound transfers
+    pub fn dismiss_alert(ctx: Context<DismissAlert>) -> Result<()> {
+        watchtower::handle_dismiss_alert(ctx)
+    }
+
+    /// Create the namespace's shadow fee account (admin only)
+    ///
+    /// # Arguments
+    /// * `candidate` - Fee strategy to rehearse on transfers
+    ///
+    /// Only functional in builds with the `shadow-fees` feature.
+    pub fn init_shadow_fees(ctx: Context<InitShadowFees>, candidate: FeeStrategy) -> Result<()> {
+        shadow::handle_init_shadow_fees(ctx, candidate)
+    }
+
+    /// Replace the rehearsed fee strategy, resetting its totals (admin only)
+    ///
+    /// # Arguments
+    /// * `candidate` - Fee strategy to rehearse on transfers
+    pub fn set_shadow_fee_strategy(
+        ctx: Context<SetShadowFeeStrategy>,
+        candidate: FeeStrategy,
+    ) -> Result<()> {
+        shadow::handle_set_shadow_fee_strategy(ctx, candidate)
+    }
+
+    /// Create the namespace's failure injection config (admin only)
+    ///
+    /// # Arguments
+    /// * `params` - Call to fail, clock skew and oracle delay
+    ///
+    /// Only functional in builds with the `chaos` feature.
+    pub fn init_chaos(ctx: Context<InitChaos>, params: ChaosParams) -> Result<()> {
+        chaos::handle_init_chaos(ctx, params)
+    }
+
+    /// Retune failure injection, restarting the call count (admin only)
+    ///
+    /// # Arguments
+    /// * `params` - Call to fail, clock skew and oracle delay
+    pub fn set_chaos(ctx: Context<SetChaos>, params: ChaosParams) -> Result<()> {
+        chaos::handle_set_chaos(ctx, params)
+    }
+
+    /// Attach a transfer policy to the caller's account
+    ///
+    /// # Arguments
+    /// * `code` - Policy bytecode, see `policy_op`
+    /// * `allowlist` - Receiver authorities `ALLOWLISTED` accepts
+    pub fn create_policy(
+        ctx: Context<CreatePolicy>,
+        code: Vec<u8>,
+        allowlist: Vec<Pubkey>,
+    ) -> Result<()> {
+        policy::handle_create_policy(ctx, code, allowlist)
+    }
+
+    /// Replace the caller's transfer policy
+    ///
+    /// # Arguments
+    /// * `code` - Policy bytecode, see `policy_op`
+    /// * `allowlist` - Receiver authorities `ALLOWLISTED` accepts
+    pub fn update_policy(
+        ctx: Context<ManagePolicy>,
+        code: Vec<u8>,
+        allowlist: Vec<Pubkey>,
+    ) -> Result<()> {
+        policy::handle_update_policy(ctx, code, allowlist)
+    }
+
+    /// Remove the caller's transfer policy, refunding its rent
+    pub fn close_policy(ctx: Context<ManagePolicy>) -> Result<()> {
+        policy::handle_close_policy(ctx)
+    }
+
+    /// Describe a user account's status, obligations, loans, limits and
+    /// timers, returning an `AccountHealth`
+    ///
+    /// Remaining accounts: credit lines the account lends or borrows on.
+    pub fn get_account_health(ctx: Context<GetAccountHealth>) -> Result<AccountHealth> {
+        health::handle_get_account_health(ctx)
+    }
+
+    /// Start counting the namespace's winning cranks (admin only)
+    pub fn init_crank_metrics(ctx: Context<InitCrankMetrics>) -> Result<()> {
+        crank::handle_init_crank_metrics(ctx)
+    }
+
+    /// Let a service act on the caller's account within some scopes
     ///
-    /// # Security Considerations
-    /// - Validates transfer amount is positive
-    /// - Checks sender has sufficient balance
-    /// - Ensures both accounts are active
-    /// - Updates balances atomically
-    pub fn transfer_tokens(
-        ctx: Context<TransferTokens>,
-        amount: u64, // Transfer amount in smallest token units
+    /// # Arguments
+    /// * `service` - Service key allowed to act
+    /// * `name` - Service label (at most `MAX_SERVICE_NAME_LEN` bytes)
+    /// * `scopes` - Bitwise OR of `capability_scopes` constants
+    /// * `max_uses` - Operations allowed, 0 for unlimited
+    /// * `expires_at` - Unix timestamp after which the token is void
+    pub fn issue_capability(
+        ctx: Context<IssueCapability>,
+        service: Pubkey,
+        name: String,
+        scopes: u64,
+        max_uses: u64,
+        expires_at: i64,
     ) -> Result<()> {
-        // Extract account references for readability
-        let sender = &mut ctx.accounts.sender; // Sender account
-        let receiver = &mut ctx.accounts.receiver; // Receiver account
+        capability::handle_issue_capability(ctx, service, name, scopes, max_uses, expires_at)
+    }
 
-        /*
-         * Pre-transfer validation checks
-         * These ensure the transfer can be completed safely
-         */
-        require!(amount > 0, CustomError::InvalidAmount); // Amount must be positive
-        require!(sender.balance >= amount, CustomError::InsufficientFunds); // Sufficient balance
-        require!(sender.is_active, CustomError::AccountInactive); // Sender must be active
-        require!(receiver.is_active, CustomError::AccountInactive); // Receiver must be active
-
-        // Perform atomic balance updates
-        sender.balance = sender
-            .balance
-            .checked_sub(amount) // Safe subtraction to prevent underflow
-            .ok_or(CustomError::MathOverflow)?; // Handle overflow error
-
-        receiver.balance = receiver
-            .balance
-            .checked_add(amount) // Safe addition to prevent overflow
-            .ok_or(CustomError::MathOverflow)?; // Handle overflow error
-
-        // Emit transfer event for off-chain monitoring
-        emit!(TokenTransferEvent {
-            from: sender.authority,                  // Sender's authority
-            to: receiver.authority,                  // Receiver's authority
-            amount,                                  // Transfer amount
-            timestamp: Clock::get()?.unix_timestamp, // Current timestamp
-        });
+    /// Revoke a service's capability token and reclaim its rent
+    pub fn revoke_capability(ctx: Context<RevokeCapability>) -> Result<()> {
+        capability::handle_revoke_capability(ctx)
+    }
 
-        msg!("Transferred {} tokens successfully", amount); // Log successful transfer
-        Ok(()) // Return success
+    /// Report a service alive on its capability token
+    pub fn service_heartbeat(ctx: Context<ServiceHeartbeat>) -> Result<()> {
+        capability::handle_service_heartbeat(ctx)
     }
 
-    /// Deactivate a user account
-    /// This is a security measure to disable compromised accounts
-    pub fn deactivate_user(ctx: Context<DeactivateUser>) -> Result<()> {
-        let user_account = &mut ctx.accounts.user; // Get user account
+    /// Create an invoice template as the merchant's billing service
+    pub fn capability_create_invoice_template(
+        ctx: Context<CapabilityCreateInvoiceTemplate>,
+        amount: u64,
+        interval: Seconds,
+    ) -> Result<()> {
+        capability::handle_capability_create_invoice_template(ctx, amount, interval)
+    }
 
-        require!(user_account.is_active, CustomError::AccountAlreadyInactive); // Must be active
+    /// Transfer tokens between user accounts with a memo
+    ///
+    /// Version 2 of `transfer_tokens`, with the same accounts and checks. The
+    /// memo (at most `MAX_MEMO_LEN` bytes) follows in a `TransferMemoEvent`.
+    pub fn transfer_tokens_v2(
+        mut ctx: Context<TransferTokens>,
+        amount: u64,
+        idempotency_key: Option<IdempotencyKey>,
+        memo: Vec<u8>,
+    ) -> Result<()> {
+        require!(memo.len() <= MAX_MEMO_LEN, CustomError::MemoTooLong);
+        execute_transfer_tokens(
+            &mut ctx,
+            amount,
+            idempotency_key,
+            InstructionTag::TransferTokensV2,
+        )?;
 
-        user_account.is_active = false; // Mark as inactive
+        emit!(TransferMemoEvent {
+            from: ctx.accounts.sender.authority,
+            to: ctx.accounts.receiver.authority,
+            memo,
+            tag: InstructionTag::TransferTokensV2,
+        });
+        Ok(())
+    }
 
-        msg!("User account deactivated: {}", user_account.authority); // Log deactivation
-        Ok(()) // Success
+    /// Digest the namespace's config, compiled features and CPI registry,
+    /// emitting and returning it
+    ///
+    /// Integrators pin the digest of a reviewed configuration to detect
+    /// governance changes.
+    pub fn program_fingerprint(ctx: Context<ProgramFingerprint>) -> Result<[u8; 32]> {
+        fingerprint::handle_program_fingerprint(ctx)
     }
 }
 
@@ -175,18 +1478,42 @@ pub mod anchor_test_contract {
  *
  * This section defines the account structures and their validation rules.
  * Each context specifies which accounts are required and their constraints.
+ *
+ * Write-lock policy: an account is only marked `mut` when the handler writes
+ * to it or it pays/receives lamports. The global config is read-only in every
+ * user-facing context so those transactions can run in parallel; only the
+ * ledger and epoch stats are shared writable accounts on the transfer path.
  */
 
 /// User account data structure
 /// Contains all user-related information stored on-chain
 #[account]
 pub struct UserAccount {
-    pub authority: Pubkey, // Account owner/authority (32 bytes)
-    pub name: String,      // User display name (variable length, max 32)
-    pub age: u8,           // User age (1 byte)
-    pub balance: u64,      // Token balance (8 bytes)
-    pub is_active: bool,   // Account status flag (1 byte)
-    pub created_at: i64,   // Account creation timestamp (8 bytes)
+    pub authority: Pubkey,                  // Account owner/authority (32 bytes)
+    pub name: String,                       // User display name (variable length, max 32)
+    pub age: u8,                            // User age (1 byte)
+    pub balance: u64,                       // Token balance (8 bytes)
+    pub mode: AccountMode,                  // Transfer directions allowed (1 byte)
+    pub created_at: i64,                    // Account creation timestamp (8 bytes)
+    pub is_joint: bool,                     // Converted to a joint account (1 byte)
+    pub vacation_start: i64,                // Outbound transfers blocked from (8 bytes)
+    pub vacation_end: i64,                  // Outbound transfers restored at (8 bytes)
+    pub compliance_hold: bool, // Mode imposed by compliance, not user-changeable (1 byte)
+    pub deactivated_at: i64,   // Last `deactivate_user`, 0 when active (8 bytes)
+    pub guardians: [Pubkey; MAX_GUARDIANS], // Guardian keys, unused slots default (96 bytes)
+    pub guardian_threshold: u8, // Guardian signatures required, 0 = unguarded (1 byte)
+    pub guardian_limit: u64,   // Outflow allowed without guardians (8 bytes)
+    pub version: u8,           // Layout version, see `migrate_user` (1 byte)
+    pub daily_limit: u64,      // Most sent per window, 0 = unlimited (8 bytes)
+    pub spent_today: u64,      // Sent in the current window (8 bytes)
+    pub window_start: i64,     // Start of the current window (8 bytes)
+    pub pending_daily_limit: u64, // Raised limit waiting out its delay (8 bytes)
+    pub pending_limit_at: i64, // When it applies, 0 when none (8 bytes)
+    pub watchtower: Pubkey,    // May raise alerts, default when none (32 bytes)
+    pub alert_until: i64,      // Outbound transfers frozen until (8 bytes)
+    pub open_obligations: u32, // Escrows and invoices still to settle (4 bytes)
+    pub sequence: u64,         // Transfers sent or received, orders their events (8 bytes)
+    pub uncounted_obligations: bool, // Migrated from before version 4, may hold uncounted obligations (1 byte)
 }
 
 // Calculate account size for rent calculation
@@ -195,11 +1522,51 @@ impl UserAccount {
     /// Used for rent calculation during account creation
     pub const LEN: usize = 8 +  // Anchor discriminator
         32 +                     // authority: Pubkey
-        4 + 32 +                // name: String (length + max content)
+        4 + MAX_NAME_LEN +      // name: String (length + max content)
         1 +                     // age: u8
         8 +                     // balance: u64
-        1 +                     // is_active: bool
-        8; // created_at: i64
+        1 +                     // mode: AccountMode
+        8 +                     // created_at: i64
+        1 +                     // is_joint: bool
+        8 +                     // vacation_start: i64
+        8 +                     // vacation_end: i64
+        1 +                     // compliance_hold: bool
+        8 +                     // deactivated_at: i64
+        32 * MAX_GUARDIANS +    // guardians: [Pubkey; MAX_GUARDIANS]
+        1 +                     // guardian_threshold: u8
+        8 +                     // guardian_limit: u64
+        1 +                     // version: u8
+        8 +                     // daily_limit: u64
+        8 +                     // spent_today: u64
+        8 +                     // window_start: i64
+        8 +                     // pending_daily_limit: u64
+        8 +                     // pending_limit_at: i64
+        32 +                    // watchtower: Pubkey
+        8 +                     // alert_until: i64
+        4 +                     // open_obligations: u32
+        8 +                     // sequence: u64
+        1; // uncounted_obligations: bool
+
+    /// Returns true unless the account is locked or deactivating
+    pub fn is_active(&self) -> bool {
+        !matches!(self.mode, AccountMode::Locked | AccountMode::Deactivating)
+    }
+
+    /// Returns true once the account is at the current layout version
+    pub fn is_current(&self) -> bool {
+        self.version == USER_ACCOUNT_VERSION
+    }
+
+    /// Returns true while a scheduled vacation window covers `now`
+    pub fn is_on_vacation(&self, now: i64) -> bool {
+        self.vacation_start <= now && now < self.vacation_end
+    }
+
+    /// Count one more transfer on the account, returning its sequence number
+    pub fn next_sequence(&mut self) -> Result<u64> {
+        self.sequence = math::add(self.sequence, 1)?;
+        Ok(self.sequence)
+    }
 }
 
 /// Context for initializing a new user account
@@ -210,7 +1577,7 @@ pub struct InitializeUser<'info> {
         init,                                    // Initialize new account
         payer = authority,                       // Authority pays for account creation
         space = UserAccount::LEN,               // Required space for account
-        seeds = [b"user", authority.key().as_ref()], // Deterministic address generation
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()], // Deterministic address generation
         bump                                     // Find valid bump seed
     )]
     pub user: Account<'info, UserAccount>, // The user account
@@ -221,6 +1588,10 @@ pub struct InitializeUser<'info> {
 
     /// System program for account creation
     pub system_program: Program<'info, System>, // Required for account initialization
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
 }
 
 /// Context for updating user information
@@ -230,23 +1601,37 @@ pub struct UpdateUser<'info> {
     #[account(
         mut,                                     // Account data will be modified
         has_one = authority,                     // Verify authority ownership
-        seeds = [b"user", authority.key().as_ref()], // Verify PDA derivation
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()], // Verify PDA derivation
         bump                                     // Verify bump seed
     )]
     pub user: Account<'info, UserAccount>, // The user account
 
     /// The authority/owner of the account
     pub authority: Signer<'info>, // Must sign to authorize changes
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// User's activity log address, appended to when the user keeps one
+    /// CHECK: address is the user's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", user.key().as_ref()], bump)]
+    pub activity_log: UncheckedAccount<'info>,
 }
 
 /// Context for token transfers between users
+///
+/// Remaining accounts, in any order: the sender's guardian signers, when
+/// needed, and the tax lot pages of either side that passes its book.
 #[derive(Accounts)]
 pub struct TransferTokens<'info> {
     /// Sender's user account
     #[account(
         mut,                                     // Will be modified (balance decrease)
         has_one = authority,                     // Verify ownership
-        seeds = [b"user", authority.key().as_ref()], // Verify PDA
+        constraint = sender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()], // Verify PDA
         bump
     )]
     pub sender: Account<'info, UserAccount>, // Sender account
@@ -254,13 +1639,77 @@ pub struct TransferTokens<'info> {
     /// Receiver's user account
     #[account(
         mut,                                     // Will be modified (balance increase)
-        seeds = [b"user", receiver.authority.as_ref()], // Verify receiver PDA
+        constraint = receiver.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), receiver.authority.as_ref()], // Verify receiver PDA
         bump
     )]
     pub receiver: Account<'info, UserAccount>, // Receiver account
 
     /// Transaction authority (must be sender)
     pub authority: Signer<'info>, // Must sign transaction
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Sender's recent idempotency keys, required when a key is attached
+    #[account(
+        mut,
+        seeds = [b"recent_ops", sender.key().as_ref()],
+        bump = recent_ops.bump
+    )]
+    pub recent_ops: Option<Account<'info, RecentOperations>>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the transfer entry
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fee and funding cashback
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Namespace cashback program, passed with `merchant` to earn cashback
+    #[account(mut, seeds = [b"cashback", config.key().as_ref()], bump = cashback.bump)]
+    pub cashback: Option<Account<'info, CashbackProgram>>,
+
+    /// Receiver's cashback merchant registration
+    #[account(seeds = [b"cashback_merchant", receiver.key().as_ref()], bump = merchant.bump)]
+    pub merchant: Option<Account<'info, CashbackMerchant>>,
+
+    /// Sender's activity log address, appended to when the sender keeps one
+    /// CHECK: address is the sender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", sender.key().as_ref()], bump)]
+    pub sender_activity: UncheckedAccount<'info>,
+
+    /// Receiver's activity log address, appended to when the receiver keeps one
+    /// CHECK: address is the receiver's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", receiver.key().as_ref()], bump)]
+    pub receiver_activity: UncheckedAccount<'info>,
+
+    /// Sender's tax lot book, consumed from when passed
+    #[account(mut)]
+    pub sender_lots: Option<Account<'info, TaxLotBook>>,
+
+    /// Receiver's tax lot book, added to when passed
+    #[account(mut)]
+    pub receiver_lots: Option<Account<'info, TaxLotBook>>,
+
+    /// Candidate fee strategy, rehearsed on this transfer when passed
+    #[account(mut, seeds = [b"shadow_fees", config.key().as_ref()], bump = shadow_fees.bump)]
+    pub shadow_fees: Option<Account<'info, ShadowFees>>,
+
+    /// Failure injection, consulted when passed to `chaos` builds
+    #[account(mut, seeds = [b"chaos", config.key().as_ref()], bump = chaos.bump)]
+    pub chaos: Option<Account<'info, ChaosConfig>>,
+
+    /// Sender's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the sender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", sender.key().as_ref()], bump)]
+    pub sender_policy: UncheckedAccount<'info>,
 }
 
 /// Context for deactivating a user account
@@ -270,13 +1719,70 @@ pub struct DeactivateUser<'info> {
     #[account(
         mut,                                     // Account status will change
         has_one = authority,                     // Verify ownership
-        seeds = [b"user", authority.key().as_ref()], // Verify PDA
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()], // Verify PDA
         bump
     )]
     pub user: Account<'info, UserAccount>, // The user account
 
     /// Account authority
     pub authority: Signer<'info>, // Must sign for deactivation
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// User's activity log address, appended to when the user keeps one
+    /// CHECK: address is the user's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", user.key().as_ref()], bump)]
+    pub activity_log: UncheckedAccount<'info>,
+}
+
+/// Context for reactivating a user account
+///
+/// Remaining accounts: a threshold of the account's guardians, as signers,
+/// once guardians are set.
+#[derive(Accounts)]
+pub struct ReactivateUser<'info> {
+    /// The user account being reactivated
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Global config, holds the cooldown
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for closing a user account
+#[derive(Accounts)]
+pub struct CloseUser<'info> {
+    /// The user account being closed
+    #[account(
+        mut,
+        close = authority, // Rent returns to the authority
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
 }
 
 /*
@@ -291,10 +1797,13 @@ pub struct DeactivateUser<'info> {
 /// Event emitted when tokens are transferred
 #[event]
 pub struct TokenTransferEvent {
-    pub from: Pubkey,   // Sender's public key
-    pub to: Pubkey,     // Receiver's public key
-    pub amount: u64,    // Transfer amount
-    pub timestamp: i64, // When transfer occurred
+    pub from: Pubkey,        // Sender's public key
+    pub to: Pubkey,          // Receiver's public key
+    pub amount: u64,         // Transfer amount, fee included
+    pub fee: u64,            // Protocol fee withheld from `amount`
+    pub timestamp: i64,      // When transfer occurred
+    pub order: EventOrder,   // Sender's sequence and slot, to order and dedupe events
+    pub tag: InstructionTag, // Emitting instruction
 }
 
 /// Custom error codes for better error handling
@@ -320,6 +1829,447 @@ pub enum CustomError {
 
     #[msg("Mathematical operation resulted in overflow.")]
     MathOverflow, // Error code: 6006
+
+    #[msg("Account is already a joint account.")]
+    AlreadyJointAccount, // Error code: 6007
+
+    #[msg("Co-authority must differ from the current authority.")]
+    InvalidCoAuthority, // Error code: 6008
+
+    #[msg("Signer is not an owner of this joint account.")]
+    NotJointOwner, // Error code: 6009
+
+    #[msg("This transfer requires the signature of both joint owners.")]
+    CoSignatureRequired, // Error code: 6010
+
+    #[msg("Joint accounts must transfer through joint_transfer.")]
+    JointTransferRequired, // Error code: 6011
+
+    #[msg("Vacation window must be non-empty and end in the future.")]
+    InvalidVacationWindow, // Error code: 6012
+
+    #[msg("Outbound transfers are blocked by a scheduled vacation window.")]
+    VacationModeActive, // Error code: 6013
+
+    #[msg("Signer is not authorized for this operation.")]
+    Unauthorized, // Error code: 6014
+
+    #[msg("This instruction is currently disabled.")]
+    InstructionDisabled, // Error code: 6015
+
+    #[msg("Fee rate must not exceed 10000 basis points.")]
+    InvalidFeeRate, // Error code: 6016
+
+    #[msg("Ramp duration must not be negative.")]
+    InvalidRampDuration, // Error code: 6017
+
+    #[msg("Mint cannot be delisted while deposits are outstanding.")]
+    MintHasOutstandingDeposits, // Error code: 6018
+
+    #[msg("This operation was already submitted with the same idempotency key.")]
+    DuplicateOperation, // Error code: 6019
+
+    #[msg("An idempotency key requires the recent operations account.")]
+    RecentOperationsRequired, // Error code: 6020
+
+    #[msg("Transfers are halted by the volume circuit breaker.")]
+    CircuitBreakerTripped, // Error code: 6021
+
+    #[msg("The circuit breaker is not tripped.")]
+    CircuitBreakerNotTripped, // Error code: 6022
+
+    #[msg("Circuit breaker multiple must be zero or above 10000 bps.")]
+    InvalidCircuitBreakerMultiple, // Error code: 6023
+
+    #[msg("Oracle price must be greater than 0.")]
+    InvalidOraclePrice, // Error code: 6024
+
+    #[msg("Oracle price deviates too far from the last accepted price.")]
+    OraclePriceDeviation, // Error code: 6025
+
+    #[msg("Price deviation window must not be negative.")]
+    InvalidDeviationWindow, // Error code: 6026
+
+    #[msg("Ledger debits and credits do not balance.")]
+    LedgerImbalanced, // Error code: 6027
+
+    #[msg("A ledger account has a negative balance.")]
+    LedgerNegativeBalance, // Error code: 6028
+
+    #[msg("A program invariant does not hold.")]
+    InvariantViolated, // Error code: 6029
+
+    #[msg("The same account was supplied more than once.")]
+    DuplicateAccount, // Error code: 6030
+
+    #[msg("Account is not a valid user account PDA.")]
+    InvalidUserAccount, // Error code: 6031
+
+    #[msg("Account cannot be added to the program lookup table.")]
+    InvalidLookupTableEntry, // Error code: 6032
+
+    #[msg("The program lookup table is already registered.")]
+    LookupTableAlreadyRegistered, // Error code: 6033
+
+    #[msg("Demo bootstrap is not compiled into this build.")]
+    BootstrapDisabled, // Error code: 6034
+
+    #[msg("Demo user count exceeds the limit or does not match the supplied accounts.")]
+    TooManyDemoUsers, // Error code: 6035
+
+    #[msg("The slot hashes sysvar account is missing or malformed.")]
+    InvalidSlotHashes, // Error code: 6036
+
+    #[msg("Reveal window must start now or later and end after it starts.")]
+    InvalidRevealWindow, // Error code: 6037
+
+    #[msg("The commitment has already been revealed.")]
+    CommitmentAlreadyRevealed, // Error code: 6038
+
+    #[msg("The reveal window has not opened yet.")]
+    RevealTooEarly, // Error code: 6039
+
+    #[msg("The commitment has expired.")]
+    CommitmentExpired, // Error code: 6040
+
+    #[msg("The preimage exceeds the maximum length.")]
+    PreimageTooLong, // Error code: 6041
+
+    #[msg("The preimage does not match the commitment.")]
+    CommitmentMismatch, // Error code: 6042
+
+    #[msg("This status transition is not allowed.")]
+    InvalidStatusTransition, // Error code: 6043
+
+    #[msg("Auction windows must satisfy now < commit_end < reveal_end.")]
+    InvalidAuctionWindow, // Error code: 6044
+
+    #[msg("Penalty rate cannot exceed 100%.")]
+    InvalidPenaltyRate, // Error code: 6045
+
+    #[msg("The auction is no longer accepting bids.")]
+    BiddingClosed, // Error code: 6046
+
+    #[msg("The commitment does not match the auction's reveal window.")]
+    CommitmentWindowMismatch, // Error code: 6047
+
+    #[msg("Revealed bid exceeds the locked deposit.")]
+    BidExceedsDeposit, // Error code: 6048
+
+    #[msg("The reveal window has not ended yet.")]
+    AuctionNotEnded, // Error code: 6049
+
+    #[msg("The auction has not been settled.")]
+    AuctionNotSettled, // Error code: 6050
+
+    #[msg("An auction with bids cannot be cancelled.")]
+    AuctionHasBids, // Error code: 6051
+
+    #[msg("Attachment page or page index limit reached.")]
+    AttachmentLimitReached, // Error code: 6052
+
+    #[msg("This document is already attached on the page.")]
+    DuplicateAttachment, // Error code: 6053
+
+    #[msg("No attachment with this content hash on the page.")]
+    AttachmentNotFound, // Error code: 6054
+
+    #[msg("The attestation issuer is not active.")]
+    IssuerInactive, // Error code: 6055
+
+    #[msg("Attestation expiry must be zero or in the future.")]
+    InvalidAttestationExpiry, // Error code: 6056
+
+    #[msg("The attestation does not prove the required claim.")]
+    AttestationInvalid, // Error code: 6057
+
+    #[msg("Fee schedule rate or bounds are invalid.")]
+    InvalidFeeSchedule, // Error code: 6058
+
+    #[msg("Bridge terms are invalid or do not match the proposal.")]
+    InvalidBridgeTerms, // Error code: 6059
+
+    #[msg("No active bridge connects these namespaces.")]
+    BridgeNotActive, // Error code: 6060
+
+    #[msg("The account cannot shrink below its live data.")]
+    CompactionWouldTruncate, // Error code: 6061
+
+    #[msg("The account is not a sweepable account of this namespace.")]
+    NotSweepable, // Error code: 6062
+
+    #[msg("The account is not of an expirable type.")]
+    NotExpirable, // Error code: 6063
+
+    #[msg("The account has not expired.")]
+    NotExpired, // Error code: 6064
+
+    #[msg("The rent recipient does not match the account.")]
+    WrongRentRecipient, // Error code: 6065
+
+    #[msg("The program is not an allowed CPI target of this namespace.")]
+    ProgramNotAllowed, // Error code: 6066
+
+    #[msg("The CPI registry is full.")]
+    CpiRegistryFull, // Error code: 6067
+
+    #[msg("The fee strategy parameters are invalid.")]
+    InvalidFeeStrategy, // Error code: 6068
+
+    #[msg("The emission schedule parameters are invalid.")]
+    InvalidEmissionSchedule, // Error code: 6069
+
+    #[msg("The sender's account is locked.")]
+    SenderLocked, // Error code: 6070
+
+    #[msg("The sender's account only receives transfers.")]
+    SenderReceiveOnly, // Error code: 6071
+
+    #[msg("The receiver's account is locked.")]
+    ReceiverLocked, // Error code: 6072
+
+    #[msg("The receiver's account only sends transfers.")]
+    ReceiverSendOnly, // Error code: 6073
+
+    #[msg("The account mode is held by compliance.")]
+    ModeHeldByCompliance, // Error code: 6074
+
+    #[msg("Manager permissions must be a non-empty set of known bits.")]
+    InvalidManagerPermissions, // Error code: 6075
+
+    #[msg("The manager grant has expired.")]
+    ManagerGrantExpired, // Error code: 6076
+
+    #[msg("The manager grant does not cover this operation.")]
+    ManagerNotPermitted, // Error code: 6077
+
+    #[msg("Deposits of this mint are disabled.")]
+    DepositsDisabled, // Error code: 6078
+
+    #[msg("The deposit would exceed the mint's cap.")]
+    DepositCapExceeded, // Error code: 6079
+
+    #[msg("Withdrawals of this mint are disabled.")]
+    WithdrawalsDisabled, // Error code: 6080
+
+    #[msg("The withdrawal exceeds the mint's outstanding deposits.")]
+    VaultInsufficient, // Error code: 6081
+
+    #[msg("The program is paused.")]
+    ProgramPaused, // Error code: 6082
+
+    #[msg("The operation digest is missing or does not match the operation.")]
+    DigestMismatch, // Error code: 6083
+
+    #[msg("The transfer exceeds the delegate's allowance.")]
+    AllowanceExceeded, // Error code: 6084
+
+    #[msg("The price adapter returned no valid price report.")]
+    InvalidPriceReport, // Error code: 6085
+
+    #[msg("The account still holds a balance.")]
+    BalanceNotZero, // Error code: 6086
+
+    #[msg("The reactivation cooldown has not elapsed.")]
+    CooldownNotElapsed, // Error code: 6087
+
+    #[msg("Account is already active.")]
+    AccountAlreadyActive, // Error code: 6088
+
+    #[msg("The reactivation cooldown must not be negative.")]
+    InvalidCooldown, // Error code: 6089
+
+    #[msg("The user's balance is outside the asserted range.")]
+    BalanceOutOfRange, // Error code: 6090
+
+    #[msg("The vault does not cover what it owes.")]
+    VaultInsolvent, // Error code: 6091
+
+    #[msg("More left the account than its spending guard allows.")]
+    SpendGuardExceeded, // Error code: 6092
+
+    #[msg("A spending guard must be closed by end_guard in the same transaction.")]
+    GuardNotClosed, // Error code: 6093
+
+    #[msg("A batch needs exactly one amount per receiver account.")]
+    BatchLengthMismatch, // Error code: 6094
+
+    #[msg("Too many receivers in one batch.")]
+    BatchTooLarge, // Error code: 6095
+
+    #[msg("The route's accounts and hop policies do not line up.")]
+    InvalidRoute, // Error code: 6096
+
+    #[msg("Too many intermediate hops in one route.")]
+    RouteTooLong, // Error code: 6097
+
+    #[msg("Guardians must be distinct, at most the maximum, and cover the threshold.")]
+    InvalidGuardianSet, // Error code: 6098
+
+    #[msg("This operation needs a threshold of the account's guardians to co-sign.")]
+    GuardianApprovalRequired, // Error code: 6099
+
+    #[msg("The obligation account does not belong to these two parties.")]
+    InvalidObligationPair, // Error code: 6100
+
+    #[msg("This user account uses an older layout and must be migrated first.")]
+    MigrationRequired, // Error code: 6101
+
+    #[msg("This user account is already at the current layout version.")]
+    AlreadyMigrated, // Error code: 6102
+
+    #[msg("Invoices need a positive amount, a long enough interval and two different accounts.")]
+    InvalidInvoiceTemplate, // Error code: 6103
+
+    #[msg("This invoice template has been cancelled.")]
+    InvoiceTemplateInactive, // Error code: 6104
+
+    #[msg("The template's next invoice is not due yet.")]
+    InvoiceNotDue, // Error code: 6105
+
+    #[msg("Promo codes need at least one use, a future expiry and a positive bonus.")]
+    InvalidPromoCode, // Error code: 6106
+
+    #[msg("This promo code has expired.")]
+    PromoCodeExpired, // Error code: 6107
+
+    #[msg("This promo code has no redemptions left.")]
+    PromoCodeExhausted, // Error code: 6108
+
+    #[msg("Escrows need a beneficiary and an unlock time in the future.")]
+    InvalidEscrow, // Error code: 6109
+
+    #[msg("This escrow is still locked.")]
+    EscrowLocked, // Error code: 6110
+
+    #[msg("This escrow has unlocked and can only be claimed.")]
+    EscrowUnlocked, // Error code: 6111
+
+    #[msg("Only the escrow's beneficiary can claim it.")]
+    NotBeneficiary, // Error code: 6112
+
+    #[msg("Cashback rate cannot exceed 100%.")]
+    InvalidCashbackRate, // Error code: 6113
+
+    #[msg("This activity log belongs to a different user account.")]
+    ActivityLogMismatch, // Error code: 6114
+
+    #[msg("Credit lines need a positive limit, a rate of at most 100% and two distinct accounts.")]
+    InvalidCreditLine, // Error code: 6115
+
+    #[msg("The lender has frozen new draws on this credit line.")]
+    CreditLineFrozen, // Error code: 6116
+
+    #[msg("This draw would exceed the credit line's limit.")]
+    CreditLimitExceeded, // Error code: 6117
+
+    #[msg("Repayment exceeds the credit line's outstanding debt.")]
+    RepaymentExceedsDebt, // Error code: 6118
+
+    #[msg("This transfer exceeds the account's remaining daily limit.")]
+    DailyLimitExceeded, // Error code: 6119
+
+    #[msg("Avatar URI exceeds maximum length of 128 bytes.")]
+    AvatarUriTooLong, // Error code: 6120
+
+    #[msg("Avatar URI must use https://, ipfs:// or ar://.")]
+    UnsupportedUriScheme, // Error code: 6121
+
+    #[msg("Profile metadata exceeds maximum length of 256 bytes.")]
+    ProfileMetadataTooLong, // Error code: 6122
+
+    #[msg("Invoices sell for no more than their amount, at the price they are listed at.")]
+    InvalidInvoicePrice, // Error code: 6123
+
+    #[msg("This invoice is not for sale to this account.")]
+    InvoiceNotForSale, // Error code: 6124
+
+    #[msg("Statements can only be generated for periods that have ended.")]
+    StatementPeriodOpen, // Error code: 6125
+
+    #[msg("This tax lot account belongs to a different user account.")]
+    TaxLotMismatch, // Error code: 6126
+
+    #[msg("The newest tax lot page is full; add a page first.")]
+    TaxLotPageFull, // Error code: 6127
+
+    #[msg("Tax lot pages must be passed in order, up to the one the transfer needs.")]
+    TaxLotPagesMissing, // Error code: 6128
+
+    #[msg("Only emptied pages the book has moved past can be closed.")]
+    TaxLotPageInUse, // Error code: 6129
+
+    #[msg("Report ranges must be non-empty and already over.")]
+    InvalidReportRange, // Error code: 6130
+
+    #[msg("Outbound transfers are frozen by a watchtower alert.")]
+    WatchtowerAlertActive, // Error code: 6131
+
+    #[msg("The account has no active watchtower alert.")]
+    NoActiveAlert, // Error code: 6132
+
+    #[msg("Shadow fees are not compiled into this build.")]
+    ShadowFeesDisabled, // Error code: 6133
+
+    #[msg("Chaos injection is not compiled into this build.")]
+    ChaosDisabled, // Error code: 6134
+
+    #[msg("Failure injected by the chaos configuration.")]
+    ChaosInjected, // Error code: 6135
+
+    #[msg("Transfer policy bytecode or allowlist is malformed.")]
+    InvalidPolicy, // Error code: 6136
+
+    #[msg("The sender's transfer policy rejects this transfer.")]
+    PolicyRejected, // Error code: 6137
+
+    #[msg("The account is deactivating and only settles what is already open.")]
+    AccountDeactivating, // Error code: 6138
+
+    #[msg("Deactivating is only entered through deactivation.")]
+    InvalidAccountMode, // Error code: 6139
+
+    #[msg("The item changed since it was read; another crank got there first.")]
+    StaleVersion, // Error code: 6140
+
+    #[msg("Capability tokens need a label, known scopes and a future expiry.")]
+    InvalidCapability, // Error code: 6141
+
+    #[msg("The capability token has expired.")]
+    CapabilityExpired, // Error code: 6142
+
+    #[msg("The capability token does not cover this operation.")]
+    CapabilityNotPermitted, // Error code: 6143
+
+    #[msg("The capability token has used up its allowed uses.")]
+    CapabilityExhausted, // Error code: 6144
+
+    #[msg("The clock tolerance must lie between zero and MAX_CLOCK_TOLERANCE.")]
+    InvalidClockTolerance, // Error code: 6145
+
+    #[msg("This instruction version has been sunset; see the logged replacement.")]
+    InstructionDeprecated, // Error code: 6146
+
+    #[msg("Only superseded instructions take a sunset, which must not be negative.")]
+    InvalidSunset, // Error code: 6147
+
+    #[msg("Memo exceeds maximum length.")]
+    MemoTooLong, // Error code: 6148
+
+    #[msg("Withdrawal exceeds the user's deposits of this mint.")]
+    CrossMintWithdrawal, // Error code: 6149
+
+    #[msg("The vault's reserves were already attested this epoch.")]
+    ReservesAlreadyAttested, // Error code: 6150
+
+    #[msg("Spending guards must be opened and closed by top-level instructions.")]
+    GuardNotTopLevel, // Error code: 6151
+
+    #[msg("The account has no open obligation to settle.")]
+    NoOpenObligation, // Error code: 6152
+
+    #[msg("Accounts at the pre-namespace address cannot be migrated; create a new account.")]
+    LegacyUserAddress, // Error code: 6153
 }
 
 /*
@@ -333,16 +2283,242 @@ pub enum CustomError {
 /// Utility function to validate user account state
 /// Returns true if account is valid for operations
 pub fn is_account_valid(account: &UserAccount) -> bool {
-    account.is_active &&           // Must be active
+    account.is_active() &&         // Must not be locked
     !account.name.is_empty() &&    // Must have a name
     account.age > 0 // Must have valid age
 }
 
+/// Validate and apply a profile update; `None` leaves a field unchanged
+/// Shared by `update_user` and managers acting for the user
+pub fn apply_user_update(
+    user_account: &mut UserAccount,
+    new_name: Option<String>,
+    new_age: Option<u8>,
+) -> Result<()> {
+    // Update name if provided
+    if let Some(name) = new_name {
+        require!(name.len() <= MAX_NAME_LEN, CustomError::NameTooLong); // Validate name length
+        user_account.name = name; // Update name
+    }
+
+    // Update age if provided
+    if let Some(age) = new_age {
+        require!(age > 0, CustomError::InvalidAge); // Validate age
+        user_account.age = age; // Update age
+    }
+
+    Ok(())
+}
+
+/// Body of `transfer_tokens` and `transfer_tokens_v2`, emitting under `tag`
+fn execute_transfer_tokens(
+    ctx: &mut Context<TransferTokens>,
+    amount: u64,
+    idempotency_key: Option<IdempotencyKey>,
+    tag: InstructionTag,
+) -> Result<()> {
+    let now = chaos_clock(ctx.accounts.chaos.as_mut())?; // Skewed under chaos testing
+
+    /*
+     * Pre-transfer validation checks
+     * These ensure the transfer can be completed safely
+     */
+    let sender = &ctx.accounts.sender;
+    let policy = load_policy(&ctx.accounts.sender_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: ctx.accounts.receiver.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::TRANSFER_TOKENS),
+        Guard::Distinct(&[sender.key(), ctx.accounts.receiver.key()]),
+        Guard::NotJoint(sender), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(sender, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(sender, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+    record_idempotency_key(ctx.accounts.recent_ops.as_mut(), idempotency_key)?;
+
+    // Extract account references for readability
+    let sender = &mut ctx.accounts.sender; // Sender account
+    let receiver = &mut ctx.accounts.receiver; // Receiver account
+    sender.record_daily_spend(amount, now)?;
+
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        amount,
+        tag,
+    )?;
+
+    // Protocol fee, withheld from what the receiver gets
+    let stats = &ctx.accounts.epoch_stats;
+    let fee_inputs = ctx.accounts.config.fee_inputs(
+        amount,
+        now,
+        utilization(stats.volume, stats.trailing_average),
+    );
+    let fee = fee_for(&ctx.accounts.config.fee_strategy, &fee_inputs)?;
+    rehearse_fee(ctx.accounts.shadow_fees.as_mut(), &fee_inputs, fee, tag)?;
+
+    // Validate and perform atomic balance updates
+    apply_transfer_with_fee(sender, receiver, amount, fee, now)?;
+    ctx.accounts.fee_treasury.collect(fee)?;
+
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, amount - fee)?; // Sender debit, receiver credit
+    if fee > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fee)?; // Withheld for the treasury
+    }
+
+    // Cashback when paying a registered merchant
+    let cashback = match (ctx.accounts.cashback.as_mut(), &ctx.accounts.merchant) {
+        (Some(cashback), Some(_)) => pay_cashback(
+            cashback,
+            &mut ctx.accounts.fee_treasury,
+            ledger,
+            sender,
+            receiver.key(),
+            amount,
+            tag,
+        )?,
+        _ => 0,
+    };
+
+    // On-chain history for whichever side keeps one
+    let order = log_transfer(
+        (&ctx.accounts.sender_activity, sender.key(), sender),
+        (&ctx.accounts.receiver_activity, receiver.key(), receiver),
+        amount,
+        amount - fee,
+        tag,
+        now,
+    )?;
+    if cashback > 0 {
+        let entry = ActivityEntry::new(
+            ActivityKind::Received,
+            tag,
+            Pubkey::default(),
+            cashback,
+            now,
+        );
+        log_activity(&ctx.accounts.sender_activity, sender.key(), entry)?; // Paid by the treasury
+    }
+
+    // Tax lots for whichever side tracks them
+    track_tax_lots(
+        ctx.remaining_accounts,
+        ctx.accounts.sender_lots.as_mut(),
+        ctx.accounts.receiver_lots.as_mut(),
+        LotTransfer {
+            sender: sender.key(),
+            receiver: receiver.key(),
+            sent: amount,
+            received: amount - fee,
+            timestamp: now,
+        },
+        tag,
+    )?;
+
+    // Emit transfer event for off-chain monitoring
+    emit!(TokenTransferEvent {
+        from: sender.authority, // Sender's authority
+        to: receiver.authority, // Receiver's authority
+        amount,                 // Transfer amount
+        fee,                    // Protocol fee withheld
+        timestamp: now,         // Current timestamp
+        order,                  // Sender's sequence and slot
+        tag,                    // Emitting instruction
+    });
+
+    msg!("Transferred {} tokens successfully", amount); // Log successful transfer
+    Ok(()) // Return success
+}
+
+/// Validate and apply a balance transfer between two user accounts at `now`
+/// Shared by every instruction that moves internal balance
+///
+/// `now` is the clock the rest of the instruction reads, so the freeze
+/// check agrees with its other time-based checks.
+pub fn apply_transfer(
+    sender: &mut UserAccount,
+    receiver: &mut UserAccount,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    apply_transfer_with_fee(sender, receiver, amount, 0, now)
+}
+
+/// Like `apply_transfer`, but the receiver is credited `amount - fee`
+/// The caller accounts for the withheld `fee`
+pub fn apply_transfer_with_fee(
+    sender: &mut UserAccount,
+    receiver: &mut UserAccount,
+    amount: u64,
+    fee: u64,
+    now: i64,
+) -> Result<()> {
+    let modes = (sender.mode, receiver.mode);
+    apply_transfer_in_modes(sender, receiver, amount, fee, modes, now)
+}
+
+/// Like `apply_transfer_with_fee`, for paying an obligation already open
+/// Deactivating parties still settle, see `AccountMode::for_settlement`
+pub fn apply_settlement_with_fee(
+    sender: &mut UserAccount,
+    receiver: &mut UserAccount,
+    amount: u64,
+    fee: u64,
+    now: i64,
+) -> Result<()> {
+    let modes = (sender.mode.for_settlement(), receiver.mode.for_settlement());
+    apply_transfer_in_modes(sender, receiver, amount, fee, modes, now)
+}
+
+/// Move `amount - fee` at `now` with the parties' modes taken as `modes`
+fn apply_transfer_in_modes(
+    sender: &mut UserAccount,
+    receiver: &mut UserAccount,
+    amount: u64,
+    fee: u64,
+    (sender_mode, receiver_mode): (AccountMode, AccountMode),
+    now: i64,
+) -> Result<()> {
+    guards::run(&[
+        Guard::Positive(amount),
+        Guard::Funded(sender, amount),
+        Guard::Modes(sender_mode, receiver_mode), // Sender may send, receiver may receive
+        Guard::NotFrozen(sender, now),            // Vacation or watchtower alert
+    ])?;
+    require!(fee <= amount, CustomError::InvalidAmount); // Fee is taken from the amount
+
+    sender.balance = sender
+        .balance
+        .checked_sub(amount) // Safe subtraction to prevent underflow
+        .ok_or(CustomError::MathOverflow)?; // Handle overflow error
+
+    receiver.balance = receiver
+        .balance
+        .checked_add(amount - fee) // Safe addition to prevent overflow
+        .ok_or(CustomError::MathOverflow)?; // Handle overflow error
+
+    Ok(())
+}
+
 /// Calculate transaction fee based on amount
-/// Simple fee calculation for demonstration
-pub fn calculate_fee(amount: u64) -> u64 {
-    let fee_rate = 100; // 1% fee (100 basis points)
-    amount / fee_rate // Simple division for fee calculation
+/// The fee `transfer_tokens` charges under a freshly created namespace's
+/// config; configured namespaces use `GlobalConfig::fee_at`
+pub fn calculate_fee(amount: u64) -> Result<u64> {
+    // 1% fee, checked, never panics
+    fee_for(
+        &FeeStrategy::Proportional,
+        &FeeInputs {
+            amount,
+            base_rate: DEFAULT_FEE_RATE,
+            utilization: Bps(0),
+        },
+    )
 }
 
 // End of file - Total lines include extensive comments for SLOC testing
@@ -0,0 +1,189 @@
+/*
+ * ============================================================================
+ * ADDRESS LOOKUP TABLE REGISTRATION
+ * ============================================================================
+ *
+ * Admin helper that creates an Address Lookup Table (ALT) holding the
+ * program's common PDAs (config, epoch stats, ledger and listed mint
+ * configs) via CPI to the address-lookup-table program. The table authority
+ * is a program PDA, so the table can only be extended through this program.
+ * Clients use the emitted address to keep large batched transactions under
+ * the size limit.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::address_lookup_table::{self, instruction as alt_instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+use crate::{emit_config_snapshot, CustomError, GlobalConfig, InstructionTag};
+
+/// Seed of the PDA that owns the program's lookup table
+pub const ALT_AUTHORITY_SEED: &[u8] = b"alt_authority";
+
+/// Context for creating or extending the program lookup table
+#[derive(Accounts)]
+pub struct ManageLookupTable<'info> {
+    /// Global config; records the table address
+    #[account(
+        mut,
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays for the table
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The lookup table account
+    /// CHECK: address is derived and validated in the handler, owned by the ALT program
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+
+    /// PDA acting as the table authority
+    /// CHECK: seeds are verified; holds no data
+    #[account(seeds = [ALT_AUTHORITY_SEED, config.key().as_ref()], bump)]
+    pub alt_authority: UncheckedAccount<'info>,
+
+    /// Address lookup table program
+    /// CHECK: address is verified against the native program id
+    #[account(address = address_lookup_table::program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+
+    /// System program for table creation and realloc
+    pub system_program: Program<'info, System>,
+}
+
+/// Event emitted whenever the program lookup table changes
+#[event]
+pub struct LookupTableRegisteredEvent {
+    pub lookup_table: Pubkey, // Table address
+    pub added: u32,           // Addresses added by this instruction
+    pub tag: InstructionTag,  // Emitting instruction
+}
+
+/// Collect program-owned accounts from `remaining_accounts` (e.g. mint configs)
+fn program_owned_addresses(accounts: &[AccountInfo]) -> Result<Vec<Pubkey>> {
+    accounts
+        .iter()
+        .map(|info| {
+            require_keys_eq!(*info.owner, crate::ID, CustomError::InvalidLookupTableEntry);
+            Ok(*info.key)
+        })
+        .collect()
+}
+
+/// Extend the table with `addresses`, signing as the PDA authority
+fn extend_table(ctx: &Context<ManageLookupTable>, addresses: Vec<Pubkey>) -> Result<()> {
+    let ix = alt_instruction::extend_lookup_table(
+        ctx.accounts.lookup_table.key(),
+        ctx.accounts.alt_authority.key(),
+        Some(ctx.accounts.admin.key()),
+        addresses,
+    );
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.lookup_table.to_account_info(),
+            ctx.accounts.alt_authority.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[&[
+            ALT_AUTHORITY_SEED,
+            ctx.accounts.config.key().as_ref(),
+            &[ctx.bumps.alt_authority],
+        ]],
+    )?;
+    Ok(())
+}
+
+/// Create the lookup table and seed it with the program's common PDAs
+/// Listed `MintConfig` accounts are passed in `remaining_accounts`
+pub fn handle_create_lookup_table<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ManageLookupTable<'info>>,
+    recent_slot: u64,
+) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.config.lookup_table,
+        Pubkey::default(),
+        CustomError::LookupTableAlreadyRegistered
+    );
+
+    let (create_ix, table) = alt_instruction::create_lookup_table_signed(
+        ctx.accounts.alt_authority.key(),
+        ctx.accounts.admin.key(),
+        recent_slot,
+    );
+    require_keys_eq!(
+        table,
+        ctx.accounts.lookup_table.key(),
+        CustomError::InvalidLookupTableEntry
+    );
+    invoke_signed(
+        &create_ix,
+        &[
+            ctx.accounts.lookup_table.to_account_info(),
+            ctx.accounts.alt_authority.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[&[
+            ALT_AUTHORITY_SEED,
+            ctx.accounts.config.key().as_ref(),
+            &[ctx.bumps.alt_authority],
+        ]],
+    )?;
+
+    // Namespace singletons used by most instructions
+    let config = ctx.accounts.config.key();
+    let mut addresses = vec![
+        config,
+        Pubkey::find_program_address(&[b"epoch_stats", config.as_ref()], &crate::ID).0,
+        Pubkey::find_program_address(&[b"ledger", config.as_ref()], &crate::ID).0,
+        crate::ID,
+        ctx.accounts.system_program.key(),
+    ];
+    addresses.extend(program_owned_addresses(ctx.remaining_accounts)?);
+    let added = addresses.len() as u32;
+    extend_table(&ctx, addresses)?;
+
+    ctx.accounts.config.lookup_table = table;
+
+    emit!(LookupTableRegisteredEvent {
+        lookup_table: table,
+        added,
+        tag: InstructionTag::CreateLookupTable,
+    });
+    emit_config_snapshot(&ctx.accounts.config, InstructionTag::CreateLookupTable)?;
+
+    msg!("Lookup table registered: {}", table);
+    Ok(())
+}
+
+/// Append further program-owned accounts (from `remaining_accounts`) to the table
+pub fn handle_extend_lookup_table<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ManageLookupTable<'info>>,
+) -> Result<()> {
+    let table = ctx.accounts.config.lookup_table;
+    require_keys_eq!(
+        table,
+        ctx.accounts.lookup_table.key(),
+        CustomError::InvalidLookupTableEntry
+    );
+
+    let addresses = program_owned_addresses(ctx.remaining_accounts)?;
+    require!(!addresses.is_empty(), CustomError::InvalidLookupTableEntry);
+    let added = addresses.len() as u32;
+    extend_table(&ctx, addresses)?;
+
+    emit!(LookupTableRegisteredEvent {
+        lookup_table: table,
+        added,
+        tag: InstructionTag::ExtendLookupTable,
+    });
+
+    msg!("Lookup table {} extended by {}", table, added);
+    Ok(())
+}
@@ -0,0 +1,280 @@
+/*
+ * ============================================================================
+ * DELEGATED ACCOUNT MANAGEMENT
+ * ============================================================================
+ *
+ * A custodian can act on a user's behalf within the bits the user grants
+ * (`manager_permissions`). Each grant is a `ManagerGrant` PDA per user and
+ * manager with a mandatory expiry; the authority can revoke it at any time,
+ * and once expired anyone can retire it through `expire`. Every grant,
+ * revocation and delegated action is emitted for audit.
+ *
+ * Managers never get the authority's full powers: transfers, deactivation
+ * and mode changes remain the authority's alone.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    apply_user_update, instruction_flags, require_instruction_enabled, ClockTolerance, CustomError,
+    GlobalConfig, InstructionTag, UserAccount,
+};
+
+/// Operations a manager may be granted; combine with bitwise OR
+pub mod manager_permissions {
+    pub const UPDATE_PROFILE: u64 = 1 << 0; // Change name and age
+
+    /// Every defined permission
+    pub const ALL: u64 = UPDATE_PROFILE;
+}
+
+/// A user's delegation of some operations to a manager
+#[account]
+pub struct ManagerGrant {
+    pub user: Pubkey,      // User account managed (32 bytes)
+    pub authority: Pubkey, // User authority that granted and paid (32 bytes)
+    pub manager: Pubkey,   // Key allowed to act (32 bytes)
+    pub permissions: u64,  // Bitmask of `manager_permissions` (8 bytes)
+    pub expires_at: i64,   // Grant lapses at this timestamp (8 bytes)
+    pub granted_at: i64,   // Grant creation timestamp (8 bytes)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl ManagerGrant {
+    /// Space required for the grant including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        32 +                     // authority: Pubkey
+        32 +                     // manager: Pubkey
+        8 +                      // permissions: u64
+        8 +                      // expires_at: i64
+        8 +                      // granted_at: i64
+        1; // bump: u8
+
+    /// Check the grant is live at `now` and covers `permission`
+    pub fn require_permission(
+        &self,
+        permission: u64,
+        now: i64,
+        tolerance: ClockTolerance,
+    ) -> Result<()> {
+        require!(
+            tolerance.before(now, self.expires_at),
+            CustomError::ManagerGrantExpired
+        );
+        require!(
+            self.permissions & permission == permission,
+            CustomError::ManagerNotPermitted
+        );
+        Ok(())
+    }
+}
+
+/// Context for granting a manager access to the caller's account
+#[derive(Accounts)]
+#[instruction(manager: Pubkey)]
+pub struct GrantManager<'info> {
+    /// Grant being created
+    #[account(
+        init,
+        payer = authority,
+        space = ManagerGrant::LEN,
+        seeds = [b"manager_grant", user.key().as_ref(), manager.as_ref()],
+        bump
+    )]
+    pub grant: Account<'info, ManagerGrant>,
+
+    /// The user account being delegated
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revoking a manager, refunding the grant's rent
+#[derive(Accounts)]
+pub struct RevokeManager<'info> {
+    /// Grant being closed
+    #[account(
+        mut,
+        close = authority,
+        has_one = user,
+        has_one = authority,
+        seeds = [b"manager_grant", user.key().as_ref(), grant.manager.as_ref()],
+        bump = grant.bump
+    )]
+    pub grant: Account<'info, ManagerGrant>,
+
+    /// The managed user account
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the user belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for a manager updating a user's profile
+#[derive(Accounts)]
+pub struct ManagerUpdateUser<'info> {
+    /// Grant authorizing the manager
+    #[account(
+        has_one = user,
+        has_one = manager,
+        seeds = [b"manager_grant", user.key().as_ref(), manager.key().as_ref()],
+        bump = grant.bump
+    )]
+    pub grant: Account<'info, ManagerGrant>,
+
+    /// The user account being updated
+    #[account(
+        mut,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Manager acting on the user's behalf
+    pub manager: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a manager is granted access
+#[event]
+pub struct ManagerGrantedEvent {
+    pub user: Pubkey,        // Managed user account
+    pub manager: Pubkey,     // Manager key
+    pub permissions: u64,    // Granted `manager_permissions`
+    pub expires_at: i64,     // Grant expiry
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a grant is revoked by the authority
+#[event]
+pub struct ManagerRevokedEvent {
+    pub user: Pubkey,        // Managed user account
+    pub manager: Pubkey,     // Manager key
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted for every operation a manager performs
+#[event]
+pub struct ManagerActionEvent {
+    pub user: Pubkey,        // Managed user account
+    pub manager: Pubkey,     // Acting manager
+    pub permission: u64,     // `manager_permissions` bit exercised
+    pub timestamp: i64,      // When it was performed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Let `manager` perform `permissions` on the caller's account until `expires_at`
+pub fn handle_grant_manager(
+    ctx: Context<GrantManager>,
+    manager: Pubkey,
+    permissions: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::GRANT_MANAGER)?;
+    require!(
+        permissions != 0 && permissions & !manager_permissions::ALL == 0,
+        CustomError::InvalidManagerPermissions
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, CustomError::ManagerGrantExpired); // Must not be born expired
+
+    let grant = &mut ctx.accounts.grant;
+    grant.user = ctx.accounts.user.key();
+    grant.authority = ctx.accounts.authority.key();
+    grant.manager = manager;
+    grant.permissions = permissions;
+    grant.expires_at = expires_at;
+    grant.granted_at = now;
+    grant.bump = ctx.bumps.grant;
+
+    emit!(ManagerGrantedEvent {
+        user: grant.user,
+        manager,
+        permissions,
+        expires_at,
+        tag: InstructionTag::GrantManager,
+    });
+
+    msg!(
+        "Manager {} granted {:#x} until {}",
+        manager,
+        permissions,
+        expires_at
+    );
+    Ok(())
+}
+
+/// Revoke a manager's grant before it expires
+pub fn handle_revoke_manager(ctx: Context<RevokeManager>) -> Result<()> {
+    let grant = &ctx.accounts.grant;
+
+    emit!(ManagerRevokedEvent {
+        user: grant.user,
+        manager: grant.manager,
+        tag: InstructionTag::RevokeManager,
+    });
+
+    msg!("Manager {} revoked", grant.manager);
+    Ok(())
+}
+
+/// Update a user's profile on their behalf
+pub fn handle_manager_update_user(
+    ctx: Context<ManagerUpdateUser>,
+    new_name: Option<String>,
+    new_age: Option<u8>,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::UPDATE_USER)?;
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.grant.require_permission(
+        manager_permissions::UPDATE_PROFILE,
+        now,
+        ctx.accounts.config.clock_tolerance(),
+    )?;
+
+    apply_user_update(&mut ctx.accounts.user, new_name, new_age)?;
+
+    emit!(ManagerActionEvent {
+        user: ctx.accounts.user.key(),
+        manager: ctx.accounts.manager.key(),
+        permission: manager_permissions::UPDATE_PROFILE,
+        timestamp: now,
+        tag: InstructionTag::ManagerUpdateUser,
+    });
+
+    msg!(
+        "User account updated by manager {}",
+        ctx.accounts.manager.key()
+    );
+    Ok(())
+}
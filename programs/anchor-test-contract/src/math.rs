@@ -0,0 +1,56 @@
+/*
+ * ============================================================================
+ * CHECKED ARITHMETIC
+ * ============================================================================
+ *
+ * Proportional amounts (fees, penalties, shares) are computed through these
+ * helpers rather than ad-hoc casts. Products are taken in u128, so any
+ * u64 × u64 fits, and a result that does not fit back into a u64 — or a
+ * zero denominator — is reported as `MathOverflow` instead of wrapping,
+ * truncating or panicking.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::{CustomError, LamportAmount, BPS_DENOMINATOR};
+
+/// `value * numerator / denominator`, rounded as requested
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    require!(denominator != 0, CustomError::MathOverflow);
+    let product = value as u128 * numerator as u128; // Cannot overflow: both factors < 2^64
+    let denominator = denominator as u128;
+    let quotient = match rounding {
+        Rounding::Down => product / denominator,
+        Rounding::Up => product.div_ceil(denominator),
+    };
+    u64::try_from(quotient).map_err(|_| error!(CustomError::MathOverflow))
+}
+
+/// `bps` basis points of `amount`, rounded as requested
+pub fn bps_of(amount: u64, bps: u64, rounding: Rounding) -> Result<u64> {
+    mul_div(amount, bps, BPS_DENOMINATOR, rounding)
+}
+
+/// `a + b`, or `MathOverflow`
+pub fn add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b)
+        .ok_or_else(|| error!(CustomError::MathOverflow))
+}
+
+/// `a - b`, or `MathOverflow` when `b > a`
+pub fn sub(a: u64, b: u64) -> Result<u64> {
+    a.checked_sub(b)
+        .ok_or_else(|| error!(CustomError::MathOverflow))
+}
+
+/// Move `amount` lamports between two accounts without wrapping either balance
+/// `from` must be owned by this program
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: LamportAmount) -> Result<()> {
+    let amount = amount.get();
+    let mut from_lamports = from.try_borrow_mut_lamports()?;
+    let mut to_lamports = to.try_borrow_mut_lamports()?;
+    **from_lamports = sub(**from_lamports, amount)?;
+    **to_lamports = add(**to_lamports, amount)?;
+    Ok(())
+}
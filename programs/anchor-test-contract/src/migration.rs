@@ -0,0 +1,153 @@
+/*
+ * ============================================================================
+ * USER ACCOUNT MIGRATIONS
+ * ============================================================================
+ *
+ * `UserAccount::version` records the layout an account was written with.
+ * Every other instruction rejects accounts below `USER_ACCOUNT_VERSION` with
+ * `MigrationRequired`; `migrate_user` brings them up to date.
+ *
+ * An older account is decoded by zero-extending its data to
+ * `UserAccount::LEN`, so the fields it predates read as zero and accounts
+ * written before the version field existed read as version 0. Each layout
+ * change must keep that decoding sound, backfilling in `migrate_step` what
+ * zero does not cover; `is_active` becoming `mode` kept its encoding.
+ * Migration reallocs the account to the current size, with the payer
+ * topping up rent, and applies one step per version until it is current.
+ *
+ * Only accounts at the namespaced address `[b"user", config, authority]`
+ * can be migrated. Accounts created before namespacing, at
+ * `[b"user", authority]`, are not supported: a program cannot move an
+ * account to a new address, and the namespaced PDAs keyed by the user
+ * account (activity log, policy, guards) would not follow it. `migrate_user`
+ * rejects them with `LegacyUserAddress`; their owners create a new account
+ * with `initialize_user` instead.
+ */
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{
+    derive_address, instruction_flags, require_instruction_enabled, resize_with_rent, CustomError,
+    GlobalConfig, InstructionTag, UserAccount, USER_ACCOUNT_VERSION,
+};
+
+/// Decode a user account of any layout version, zero-filling missing fields
+pub fn decode_user_account(info: &AccountInfo) -> Result<UserAccount> {
+    if *info.owner != crate::ID {
+        return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+            .with_pubkeys((*info.owner, crate::ID)));
+    }
+
+    let data = info.try_borrow_data()?;
+    match data.get(..8) {
+        Some(discriminator) if discriminator == UserAccount::DISCRIMINATOR => {}
+        Some(_) => return err!(ErrorCode::AccountDiscriminatorMismatch),
+        None => return err!(ErrorCode::AccountDiscriminatorNotFound),
+    }
+
+    let mut padded = data.to_vec();
+    if padded.len() < UserAccount::LEN {
+        padded.resize(UserAccount::LEN, 0); // Fields appended since read as zero
+    }
+    UserAccount::try_deserialize(&mut &padded[..])
+}
+
+/// Backfill the fields the account's next layout version introduces
+///
+/// Version 1 only added `version` itself: every field appended to the
+/// original layout defaults to zero, and `AccountMode` encodes like the old
+/// `is_active` flag. Version 2 added the daily limit fields, whose zero
+/// defaults mean no limit. Version 3 added the watchtower fields, zero meaning
+/// none registered and no alert. Version 4 added `open_obligations`, zero
+/// since obligations opened earlier were never counted. Version 5 added
+/// `sequence`, which simply starts counting at zero. Version 6 added
+/// `uncounted_obligations`, set for accounts from before version 4 since the
+/// zero count may leave out obligations they still settle. Versions whose new
+/// fields need other defaults set them here before the version is bumped.
+pub fn migrate_step(user: &mut UserAccount) {
+    if user.version < 4 {
+        user.uncounted_obligations = true; // Opened obligations were not counted
+    }
+    user.version += 1;
+}
+
+/// Context for migrating a user account
+#[derive(Accounts)]
+pub struct MigrateUser<'info> {
+    /// Account being migrated, checked and decoded by the handler
+    /// CHECK: owner, discriminator and address checked in the handler
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    /// Pays the rent of the grown account; anyone may migrate
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global config the account belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for the rent top-up
+    pub system_program: Program<'info, System>,
+}
+
+/// Event emitted when a user account is migrated
+#[event]
+pub struct UserMigratedEvent {
+    pub user: Pubkey,        // Migrated user account
+    pub from_version: u8,    // Layout version before migration
+    pub to_version: u8,      // Layout version after migration
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Grow the account to the current layout and backfill its new fields
+pub fn handle_migrate_user(ctx: Context<MigrateUser>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::MIGRATE_USER)?;
+
+    let info = ctx.accounts.user.to_account_info();
+    let mut user = decode_user_account(&info)?;
+    let expected = derive_address(&[
+        b"user".to_vec(),
+        ctx.accounts.config.key().to_bytes().to_vec(),
+        user.authority.to_bytes().to_vec(),
+    ]);
+    if expected != *info.key {
+        let legacy = derive_address(&[b"user".to_vec(), user.authority.to_bytes().to_vec()]);
+        require_keys_neq!(legacy, *info.key, CustomError::LegacyUserAddress); // Pre-namespace, unsupported
+        return Err(Error::from(ErrorCode::ConstraintSeeds).with_pubkeys((*info.key, expected)));
+    }
+    require!(
+        user.version < USER_ACCOUNT_VERSION,
+        CustomError::AlreadyMigrated
+    );
+
+    let from_version = user.version;
+    while user.version < USER_ACCOUNT_VERSION {
+        migrate_step(&mut user);
+    }
+
+    resize_with_rent(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        UserAccount::LEN,
+    )?;
+    user.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    emit!(UserMigratedEvent {
+        user: info.key(),
+        from_version,
+        to_version: user.version,
+        tag: InstructionTag::MigrateUser,
+    });
+
+    msg!(
+        "User account {} migrated from version {} to {}",
+        info.key(),
+        from_version,
+        user.version
+    );
+    Ok(())
+}
@@ -0,0 +1,230 @@
+/*
+ * ============================================================================
+ * PER-MINT CONFIGURATION REGISTRY
+ * ============================================================================
+ *
+ * Each supported SPL mint has a `MintConfig` PDA managed by the config admin.
+ * It caches the mint decimals and holds the switches, caps and oracle feed
+ * for that mint, so mints can be listed and delisted without code changes.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{CustomError, GlobalConfig, InstructionTag};
+
+/// Admin-supplied settings for a listed mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MintConfigParams {
+    pub deposit_enabled: bool,   // Allow deposits of this mint
+    pub withdraw_enabled: bool,  // Allow withdrawals of this mint
+    pub max_total_deposits: u64, // Cap on outstanding deposits (0 = unlimited)
+    pub oracle_feed: Pubkey,     // Price feed account (default = none)
+}
+
+/// Registry entry for a supported mint
+#[account]
+pub struct MintConfig {
+    pub mint: Pubkey,            // The SPL mint (32 bytes)
+    pub decimals: u8,            // Cached mint decimals (1 byte)
+    pub deposit_enabled: bool,   // Deposits allowed (1 byte)
+    pub withdraw_enabled: bool,  // Withdrawals allowed (1 byte)
+    pub max_total_deposits: u64, // Deposit cap, 0 = unlimited (8 bytes)
+    pub total_deposited: u64,    // Outstanding deposits (8 bytes)
+    pub oracle_feed: Pubkey,     // Price feed account (32 bytes)
+    pub bump: u8,                // PDA bump seed (1 byte)
+    pub last_price: u64,         // Last accepted oracle price (8 bytes)
+    pub last_price_at: i64,      // When `last_price` was accepted (8 bytes)
+}
+
+impl MintConfig {
+    /// Space required for a registry entry including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // mint: Pubkey
+        1 +                      // decimals: u8
+        1 +                      // deposit_enabled: bool
+        1 +                      // withdraw_enabled: bool
+        8 +                      // max_total_deposits: u64
+        8 +                      // total_deposited: u64
+        32 +                     // oracle_feed: Pubkey
+        1 +                      // bump: u8
+        8 +                      // last_price: u64
+        8; // last_price_at: i64
+
+    /// Copy admin-controlled settings onto the entry
+    fn apply(&mut self, params: &MintConfigParams) {
+        self.deposit_enabled = params.deposit_enabled;
+        self.withdraw_enabled = params.withdraw_enabled;
+        self.max_total_deposits = params.max_total_deposits;
+        self.oracle_feed = params.oracle_feed;
+    }
+
+    /// Returns true if depositing `amount` more stays within the cap
+    pub fn has_capacity_for(&self, amount: u64) -> bool {
+        self.max_total_deposits == 0
+            || self
+                .total_deposited
+                .checked_add(amount)
+                .is_some_and(|total| total <= self.max_total_deposits)
+    }
+}
+
+/// Context for listing a new mint
+#[derive(Accounts)]
+pub struct ListMint<'info> {
+    /// Registry entry being created
+    #[account(
+        init,
+        payer = admin,
+        space = MintConfig::LEN,
+        seeds = [b"mint_config", config.key().as_ref(), mint.key().as_ref()], // One entry per mint
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// The mint being listed
+    pub mint: Account<'info, Mint>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays for the entry
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for updating an existing mint
+#[derive(Accounts)]
+pub struct ManageMint<'info> {
+    /// Registry entry being modified
+    #[account(
+        mut,
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin (read-only, nothing is paid or refunded)
+    pub admin: Signer<'info>,
+}
+
+/// Context for delisting a mint
+#[derive(Accounts)]
+pub struct DelistMint<'info> {
+    /// Registry entry being closed
+    #[account(
+        mut,
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, receives the entry's rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+/// Event emitted whenever a mint is listed, updated or delisted
+#[event]
+pub struct MintConfigChangedEvent {
+    pub mint: Pubkey,            // Affected mint
+    pub listed: bool,            // False once delisted
+    pub deposit_enabled: bool,   // Current deposit switch
+    pub withdraw_enabled: bool,  // Current withdraw switch
+    pub max_total_deposits: u64, // Current deposit cap
+    pub tag: InstructionTag,     // Emitting instruction
+}
+
+impl MintConfigChangedEvent {
+    fn from_config(config: &MintConfig, listed: bool, tag: InstructionTag) -> Self {
+        Self {
+            mint: config.mint,
+            listed,
+            deposit_enabled: config.deposit_enabled,
+            withdraw_enabled: config.withdraw_enabled,
+            max_total_deposits: config.max_total_deposits,
+            tag,
+        }
+    }
+}
+
+/// List a mint with the given settings
+pub fn handle_list_mint(ctx: Context<ListMint>, params: MintConfigParams) -> Result<()> {
+    let mint_config = &mut ctx.accounts.mint_config;
+    mint_config.mint = ctx.accounts.mint.key();
+    mint_config.decimals = ctx.accounts.mint.decimals; // Cache decimals for conversions
+    mint_config.total_deposited = 0;
+    mint_config.bump = ctx.bumps.mint_config;
+    mint_config.last_price = 0; // No accepted price until the first oracle read
+    mint_config.last_price_at = 0;
+    mint_config.apply(&params);
+
+    emit!(MintConfigChangedEvent::from_config(
+        mint_config,
+        true,
+        InstructionTag::ListMint,
+    ));
+
+    msg!("Mint listed: {}", mint_config.mint);
+    Ok(())
+}
+
+/// Update the settings of a listed mint
+pub fn handle_update_mint_config(ctx: Context<ManageMint>, params: MintConfigParams) -> Result<()> {
+    let mint_config = &mut ctx.accounts.mint_config;
+    mint_config.apply(&params);
+
+    emit!(MintConfigChangedEvent::from_config(
+        mint_config,
+        true,
+        InstructionTag::UpdateMintConfig,
+    ));
+
+    msg!("Mint config updated: {}", mint_config.mint);
+    Ok(())
+}
+
+/// Delist a mint, closing its registry entry
+/// Refused while deposits of the mint are still outstanding
+pub fn handle_delist_mint(ctx: Context<DelistMint>) -> Result<()> {
+    let mint_config = &ctx.accounts.mint_config;
+    require!(
+        mint_config.total_deposited == 0,
+        CustomError::MintHasOutstandingDeposits
+    );
+
+    emit!(MintConfigChangedEvent::from_config(
+        mint_config,
+        false,
+        InstructionTag::DelistMint,
+    ));
+
+    msg!("Mint delisted: {}", mint_config.mint);
+    ctx.accounts
+        .mint_config
+        .close(ctx.accounts.admin.to_account_info())
+}
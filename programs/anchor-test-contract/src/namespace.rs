@@ -0,0 +1,200 @@
+/*
+ * ============================================================================
+ * NAMESPACE METADATA
+ * ============================================================================
+ *
+ * Operators running a namespace can publish a `NamespaceMetadata` PDA next
+ * to its config: a display name, the hash of an off-chain metadata document
+ * (logo, website, terms) and the fee schedule the operator applies by
+ * default. Only the namespace admin can edit it. Every change is emitted in
+ * full, so explorers can label activity per operator from the event stream.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{Bps, CustomError, GlobalConfig, InstructionTag, MAX_NAMESPACE_NAME_LEN};
+
+/// Fees an operator charges unless a more specific rate applies
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct FeeSchedule {
+    pub fee_rate_bps: u64, // Proportional fee in basis points
+    pub min_fee: u64,      // Floor applied to each charge
+    pub max_fee: u64,      // Cap applied to each charge (0 = uncapped)
+}
+
+impl FeeSchedule {
+    /// Serialized size of the schedule
+    pub const LEN: usize = 8 + 8 + 8;
+
+    /// Check the rate and bounds are consistent
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            Bps(self.fee_rate_bps).is_fraction(),
+            CustomError::InvalidFeeSchedule
+        );
+        require!(
+            self.max_fee == 0 || self.min_fee <= self.max_fee,
+            CustomError::InvalidFeeSchedule
+        );
+        Ok(())
+    }
+}
+
+/// Admin-supplied metadata for a namespace
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct NamespaceMetadataParams {
+    pub name: String,              // Display name, at most 32 bytes
+    pub uri_hash: [u8; 32],        // Hash of the off-chain metadata document
+    pub default_fees: FeeSchedule, // Operator's default fee schedule
+}
+
+/// Branding and default fees of a namespace
+#[account]
+pub struct NamespaceMetadata {
+    pub config: Pubkey,            // Namespace config this describes (32 bytes)
+    pub name: String,              // Display name (4 + 32 bytes max)
+    pub uri_hash: [u8; 32],        // Hash of the off-chain metadata document (32 bytes)
+    pub default_fees: FeeSchedule, // Default fee schedule (24 bytes)
+    pub updated_at: i64,           // Last edit timestamp (8 bytes)
+    pub bump: u8,                  // PDA bump seed (1 byte)
+}
+
+impl NamespaceMetadata {
+    /// Space required for the metadata including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // config: Pubkey
+        4 + MAX_NAMESPACE_NAME_LEN + // name: String
+        32 +                     // uri_hash: [u8; 32]
+        FeeSchedule::LEN +       // default_fees: FeeSchedule
+        8 +                      // updated_at: i64
+        1; // bump: u8
+
+    /// Validate and copy admin-controlled fields onto the account
+    fn apply(&mut self, params: NamespaceMetadataParams, now: i64) -> Result<()> {
+        require!(
+            params.name.len() <= MAX_NAMESPACE_NAME_LEN,
+            CustomError::NameTooLong
+        );
+        params.default_fees.validate()?;
+
+        self.name = params.name;
+        self.uri_hash = params.uri_hash;
+        self.default_fees = params.default_fees;
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+/// Context for publishing a namespace's metadata
+#[derive(Accounts)]
+pub struct CreateNamespaceMetadata<'info> {
+    /// Metadata account being created
+    #[account(
+        init,
+        payer = admin,
+        space = NamespaceMetadata::LEN,
+        seeds = [b"namespace_metadata", config.key().as_ref()], // One per namespace
+        bump
+    )]
+    pub metadata: Account<'info, NamespaceMetadata>,
+
+    /// Namespace config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for editing a namespace's metadata
+#[derive(Accounts)]
+pub struct UpdateNamespaceMetadata<'info> {
+    /// Metadata account being edited
+    #[account(
+        mut,
+        seeds = [b"namespace_metadata", config.key().as_ref()],
+        bump = metadata.bump
+    )]
+    pub metadata: Account<'info, NamespaceMetadata>,
+
+    /// Namespace config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace admin
+    pub admin: Signer<'info>,
+}
+
+/// Event carrying the full metadata whenever it is published or edited
+#[event]
+pub struct NamespaceMetadataChangedEvent {
+    pub config: Pubkey,            // Namespace config
+    pub namespace: u64,            // Namespace identifier
+    pub name: String,              // Display name
+    pub uri_hash: [u8; 32],        // Off-chain metadata document hash
+    pub default_fees: FeeSchedule, // Default fee schedule
+    pub tag: InstructionTag,       // Emitting instruction
+}
+
+impl NamespaceMetadataChangedEvent {
+    fn from_metadata(metadata: &NamespaceMetadata, namespace: u64, tag: InstructionTag) -> Self {
+        Self {
+            config: metadata.config,
+            namespace,
+            name: metadata.name.clone(),
+            uri_hash: metadata.uri_hash,
+            default_fees: metadata.default_fees,
+            tag,
+        }
+    }
+}
+
+/// Publish metadata for the admin's namespace
+pub fn handle_create_namespace_metadata(
+    ctx: Context<CreateNamespaceMetadata>,
+    params: NamespaceMetadataParams,
+) -> Result<()> {
+    let metadata = &mut ctx.accounts.metadata;
+    metadata.config = ctx.accounts.config.key();
+    metadata.bump = ctx.bumps.metadata;
+    metadata.apply(params, Clock::get()?.unix_timestamp)?;
+
+    emit!(NamespaceMetadataChangedEvent::from_metadata(
+        metadata,
+        ctx.accounts.config.namespace,
+        InstructionTag::CreateNamespaceMetadata,
+    ));
+
+    msg!("Namespace metadata published: {}", metadata.name);
+    Ok(())
+}
+
+/// Replace the metadata of the admin's namespace
+pub fn handle_update_namespace_metadata(
+    ctx: Context<UpdateNamespaceMetadata>,
+    params: NamespaceMetadataParams,
+) -> Result<()> {
+    let metadata = &mut ctx.accounts.metadata;
+    metadata.apply(params, Clock::get()?.unix_timestamp)?;
+
+    emit!(NamespaceMetadataChangedEvent::from_metadata(
+        metadata,
+        ctx.accounts.config.namespace,
+        InstructionTag::UpdateNamespaceMetadata,
+    ));
+
+    msg!("Namespace metadata updated: {}", metadata.name);
+    Ok(())
+}
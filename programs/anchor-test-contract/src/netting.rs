@@ -0,0 +1,440 @@
+/*
+ * ============================================================================
+ * BILATERAL NETTING
+ * ============================================================================
+ *
+ * Two user accounts that owe each other repeatedly record their obligations
+ * in an `Obligation` PDA per pair (`[b"obligation", party_a, party_b]`, the
+ * user accounts in ascending address order, like bridges) and settle only
+ * the difference:
+ *
+ * - `open_obligation`: either party creates the pair's account
+ * - `record_obligation(amount)`: a party acknowledges owing the other `amount`
+ * - `settle_net()`: either party settles; the net debtor pays the difference
+ *   as a fee-charging transfer and both sides reset to zero
+ *
+ * Only the debtor can record a debt, so nobody can be made to owe. A party
+ * with a recorded, unsettled debt has an open obligation, so it cannot
+ * finish deactivating before the pair settles; settling pays the net debt
+ * even while the debtor is deactivating. Events
+ * carry every recorded amount and the gross totals each settlement netted,
+ * so auditors can rebuild the gross flows from the log.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    apply_settlement_with_fee, instruction_flags, load_policy, log_transfer,
+    record_transfer_volume, require_instruction_enabled, settle_obligation, signer_keys,
+    utilization, CustomError, EpochStats, EventOrder, FeeTreasury, GlobalConfig, InstructionTag,
+    Ledger, LedgerAccount, PolicyInput, UserAccount,
+};
+
+/// Outstanding mutual obligations of two user accounts
+#[account]
+pub struct Obligation {
+    pub party_a: Pubkey,  // User account with the lower address (32 bytes)
+    pub party_b: Pubkey,  // User account with the higher address (32 bytes)
+    pub a_owes_b: u64,    // Recorded by `party_a` since the last settlement (8 bytes)
+    pub b_owes_a: u64,    // Recorded by `party_b` since the last settlement (8 bytes)
+    pub settlements: u64, // Settlements so far (8 bytes)
+    pub bump: u8,         // PDA bump seed (1 byte)
+}
+
+/// Net position of an obligation pair
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NetPosition {
+    pub debtor: Pubkey,   // Party paying the difference
+    pub creditor: Pubkey, // Party receiving it
+    pub amount: u64,      // Difference of the two gross amounts
+}
+
+impl Obligation {
+    /// Space required for the obligation including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // party_a: Pubkey
+        32 +                     // party_b: Pubkey
+        8 +                      // a_owes_b: u64
+        8 +                      // b_owes_a: u64
+        8 +                      // settlements: u64
+        1; // bump: u8
+
+    /// True if the pair is `party` and `counterparty` (in either order)
+    pub fn connects(&self, party: &Pubkey, counterparty: &Pubkey) -> bool {
+        (self.party_a == *party && self.party_b == *counterparty)
+            || (self.party_a == *counterparty && self.party_b == *party)
+    }
+
+    /// Add `amount` to what `debtor` owes the other party
+    pub fn record(&mut self, debtor: &Pubkey, amount: u64) -> Result<u64> {
+        let owed = if *debtor == self.party_a {
+            &mut self.a_owes_b
+        } else {
+            &mut self.b_owes_a
+        };
+        *owed = math::add(*owed, amount)?;
+        Ok(*owed)
+    }
+
+    /// Who pays whom how much once the two sides are netted, if anything
+    pub fn net(&self) -> Option<NetPosition> {
+        let (debtor, creditor) = match self.a_owes_b.cmp(&self.b_owes_a) {
+            std::cmp::Ordering::Greater => (self.party_a, self.party_b),
+            std::cmp::Ordering::Less => (self.party_b, self.party_a),
+            std::cmp::Ordering::Equal => return None, // Fully offset
+        };
+        Some(NetPosition {
+            debtor,
+            creditor,
+            amount: self.a_owes_b.abs_diff(self.b_owes_a),
+        })
+    }
+}
+
+/// Context for creating a pair's obligation account
+#[derive(Accounts)]
+pub struct OpenObligation<'info> {
+    /// Obligation account being created
+    #[account(
+        init,
+        payer = payer,
+        space = Obligation::LEN,
+        seeds = [b"obligation", party_a.key().as_ref(), party_b.key().as_ref()], // One per pair
+        bump
+    )]
+    pub obligation: Account<'info, Obligation>,
+
+    /// User account with the lower address
+    #[account(
+        constraint = party_a.key() < party_b.key() @ CustomError::InvalidObligationPair,
+        constraint = party_a.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), party_a.authority.as_ref()],
+        bump
+    )]
+    pub party_a: Account<'info, UserAccount>,
+
+    /// User account with the higher address
+    #[account(
+        constraint = party_b.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), party_b.authority.as_ref()],
+        bump
+    )]
+    pub party_b: Account<'info, UserAccount>,
+
+    /// Authority of either party, pays rent
+    #[account(
+        mut,
+        constraint = payer.key() == party_a.authority || payer.key() == party_b.authority
+            @ CustomError::Unauthorized
+    )]
+    pub payer: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for acknowledging a debt to the counterparty
+#[derive(Accounts)]
+pub struct RecordObligation<'info> {
+    /// Pair's obligation account
+    #[account(
+        mut,
+        constraint = obligation.connects(&debtor.key(), &creditor.key())
+            @ CustomError::InvalidObligationPair,
+        seeds = [b"obligation", obligation.party_a.as_ref(), obligation.party_b.as_ref()],
+        bump = obligation.bump
+    )]
+    pub obligation: Account<'info, Obligation>,
+
+    /// Debtor's user account, opening an obligation with its first debt
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = debtor.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub debtor: Account<'info, UserAccount>,
+
+    /// Creditor's user account
+    #[account(
+        constraint = creditor.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), creditor.authority.as_ref()],
+        bump
+    )]
+    pub creditor: Account<'info, UserAccount>,
+
+    /// Debtor authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for settling a pair's net position
+///
+/// Remaining accounts: the net debtor's guardian signers, when needed.
+#[derive(Accounts)]
+pub struct SettleNet<'info> {
+    /// Pair's obligation account
+    #[account(
+        mut,
+        constraint = obligation.connects(&party.key(), &counterparty.key())
+            @ CustomError::InvalidObligationPair,
+        seeds = [b"obligation", obligation.party_a.as_ref(), obligation.party_b.as_ref()],
+        bump = obligation.bump
+    )]
+    pub obligation: Account<'info, Obligation>,
+
+    /// Settling party's user account
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = party.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub party: Account<'info, UserAccount>,
+
+    /// Other party's user account
+    #[account(
+        mut,
+        constraint = counterparty.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), counterparty.authority.as_ref()],
+        bump
+    )]
+    pub counterparty: Account<'info, UserAccount>,
+
+    /// Settling party's authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the settlement
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fee
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Signing party's activity log address, appended to when the party keeps one
+    /// CHECK: address is the party's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", party.key().as_ref()], bump)]
+    pub party_activity: UncheckedAccount<'info>,
+
+    /// Counterparty's activity log address, appended to when the counterparty keeps one
+    /// CHECK: address is the counterparty's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", counterparty.key().as_ref()], bump)]
+    pub counterparty_activity: UncheckedAccount<'info>,
+
+    /// Signing party's transfer policy address, enforced when the party pays
+    /// CHECK: address is the party's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", party.key().as_ref()], bump)]
+    pub party_policy: UncheckedAccount<'info>,
+
+    /// Counterparty's transfer policy address, enforced when the counterparty pays
+    /// CHECK: address is the counterparty's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", counterparty.key().as_ref()], bump)]
+    pub counterparty_policy: UncheckedAccount<'info>,
+}
+
+/// Event emitted when a party acknowledges a debt
+#[event]
+pub struct ObligationRecordedEvent {
+    pub obligation: Pubkey,  // Pair's obligation account
+    pub debtor: Pubkey,      // Debtor user account
+    pub creditor: Pubkey,    // Creditor user account
+    pub amount: u64,         // Amount acknowledged
+    pub outstanding: u64,    // Debtor's gross total since the last settlement
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a pair settles
+#[event]
+pub struct NetSettledEvent {
+    pub obligation: Pubkey,  // Pair's obligation account
+    pub a_owed_b: u64,       // Gross owed by `party_a`, now netted
+    pub b_owed_a: u64,       // Gross owed by `party_b`, now netted
+    pub payer: Pubkey,       // Net debtor, default if fully offset
+    pub net: u64,            // Amount actually transferred
+    pub fee: u64,            // Protocol fee withheld from `net`
+    pub timestamp: i64,      // When the pair settled
+    pub order: EventOrder,   // Ordering marker of the net transfer
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create the obligation account of a pair
+pub fn handle_open_obligation(ctx: Context<OpenObligation>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::RECORD_OBLIGATION)?;
+
+    let obligation = &mut ctx.accounts.obligation;
+    obligation.party_a = ctx.accounts.party_a.key();
+    obligation.party_b = ctx.accounts.party_b.key();
+    obligation.a_owes_b = 0;
+    obligation.b_owes_a = 0;
+    obligation.settlements = 0;
+    obligation.bump = ctx.bumps.obligation;
+
+    msg!("Obligation opened: {}", obligation.key());
+    Ok(())
+}
+
+/// Acknowledge owing the creditor `amount`
+pub fn handle_record_obligation(ctx: Context<RecordObligation>, amount: u64) -> Result<()> {
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::RECORD_OBLIGATION),
+        Guard::Positive(amount),
+    ])?;
+
+    let debtor = ctx.accounts.debtor.key();
+    let obligation = &mut ctx.accounts.obligation;
+    let outstanding = obligation.record(&debtor, amount)?;
+    if outstanding == amount {
+        ctx.accounts.debtor.open_obligation()?; // First debt since the last settlement
+    }
+
+    emit!(ObligationRecordedEvent {
+        obligation: obligation.key(),
+        debtor,
+        creditor: ctx.accounts.creditor.key(),
+        amount,
+        outstanding,
+        tag: InstructionTag::RecordObligation,
+    });
+
+    msg!("Obligation of {} recorded", amount);
+    Ok(())
+}
+
+/// Transfer the net difference from the net debtor and reset the pair
+pub fn handle_settle_net(ctx: Context<SettleNet>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SETTLE_NET)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let obligation = &ctx.accounts.obligation;
+    let (payer, net, fee, order) = match obligation.net() {
+        Some(position) => {
+            let (debtor, creditor, debtor_log, creditor_log, debtor_policy) =
+                if position.debtor == ctx.accounts.party.key() {
+                    (
+                        &mut ctx.accounts.party,
+                        &mut ctx.accounts.counterparty,
+                        &ctx.accounts.party_activity,
+                        &ctx.accounts.counterparty_activity,
+                        &ctx.accounts.party_policy,
+                    )
+                } else {
+                    (
+                        &mut ctx.accounts.counterparty,
+                        &mut ctx.accounts.party,
+                        &ctx.accounts.counterparty_activity,
+                        &ctx.accounts.party_activity,
+                        &ctx.accounts.counterparty_policy,
+                    )
+                };
+            let policy = load_policy(debtor_policy)?;
+            let policy_input = PolicyInput {
+                amount: position.amount,
+                receiver: creditor.authority,
+                now,
+            };
+            guards::run(&[
+                Guard::NotJoint(debtor), // Joint accounts use joint_transfer
+                Guard::GuardianApproved(
+                    debtor,
+                    position.amount,
+                    &signer_keys(ctx.remaining_accounts),
+                ),
+                Guard::DailyLimit(debtor, position.amount, now),
+                Guard::Policy(policy.as_ref(), policy_input),
+            ])?;
+            debtor.record_daily_spend(position.amount, now)?;
+
+            record_transfer_volume(
+                &mut ctx.accounts.epoch_stats,
+                &ctx.accounts.config,
+                position.amount,
+                InstructionTag::SettleNet,
+            )?;
+            let stats = &ctx.accounts.epoch_stats;
+            let fee = ctx.accounts.config.fee_at(
+                position.amount,
+                now,
+                utilization(stats.volume, stats.trailing_average),
+            )?;
+            apply_settlement_with_fee(debtor, creditor, position.amount, fee, now)?;
+            ctx.accounts.fee_treasury.collect(fee)?;
+
+            let ledger = &mut ctx.accounts.ledger;
+            ledger.post(
+                LedgerAccount::Users,
+                LedgerAccount::Users,
+                position.amount - fee,
+            )?;
+            if fee > 0 {
+                ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fee)?; // Withheld for the treasury
+            }
+            let order = log_transfer(
+                (debtor_log, debtor.key(), debtor),
+                (creditor_log, creditor.key(), creditor),
+                position.amount,
+                position.amount - fee,
+                InstructionTag::SettleNet,
+                now,
+            )?;
+            (position.debtor, position.amount, fee, order)
+        }
+        None => (Pubkey::default(), 0, 0, EventOrder::default()), // Fully offset, nothing moves
+    };
+
+    let obligation = &mut ctx.accounts.obligation;
+    let (a_owed_b, b_owed_a) = (obligation.a_owes_b, obligation.b_owes_a);
+    obligation.a_owes_b = 0;
+    obligation.b_owes_a = 0;
+    obligation.settlements = math::add(obligation.settlements, 1)?;
+
+    // Each side with a recorded debt had an obligation open
+    for (owed, party) in [
+        (a_owed_b, obligation.party_a),
+        (b_owed_a, obligation.party_b),
+    ] {
+        if owed == 0 {
+            continue;
+        }
+        let user = if party == ctx.accounts.party.key() {
+            &mut ctx.accounts.party
+        } else {
+            &mut ctx.accounts.counterparty
+        };
+        settle_obligation(user, InstructionTag::SettleNet)?;
+    }
+
+    emit!(NetSettledEvent {
+        obligation: obligation.key(),
+        a_owed_b,
+        b_owed_a,
+        payer,
+        net,
+        fee,
+        timestamp: now,
+        order,
+        tag: InstructionTag::SettleNet,
+    });
+
+    msg!("Settled {} net of {} and {}", net, a_owed_b, b_owed_a);
+    Ok(())
+}
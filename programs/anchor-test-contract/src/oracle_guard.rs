@@ -0,0 +1,87 @@
+/*
+ * ============================================================================
+ * ORACLE PRICE DEVIATION GUARD
+ * ============================================================================
+ *
+ * USD-denominated features must pass every oracle price through
+ * `accept_oracle_price` before using it. A price that moves more than
+ * `max_price_deviation_bps` away from the last accepted price for the mint
+ * within `price_deviation_window_secs` is rejected, so a glitching or
+ * manipulated feed cannot be acted on. The admin can re-anchor the accepted
+ * price with `override_oracle_price` after verifying a genuine move.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, GlobalConfig, InstructionTag, ManageMint, MintConfig, BPS_DENOMINATOR};
+
+/// Deviation between two prices in basis points of the reference price
+pub fn price_deviation_bps(reference: u64, price: u64) -> u64 {
+    if reference == 0 {
+        return 0; // No reference to deviate from
+    }
+    let diff = reference.abs_diff(price) as u128;
+    let bps = diff * BPS_DENOMINATOR as u128 / reference as u128;
+    bps.min(u64::MAX as u128) as u64
+}
+
+/// Validate `price` against the last accepted price and record it
+///
+/// Prices outside the window are accepted unconditionally because the
+/// reference is stale; the window only protects against sudden jumps.
+pub fn accept_oracle_price(
+    mint_config: &mut MintConfig,
+    config: &GlobalConfig,
+    price: u64,
+    now: i64,
+) -> Result<()> {
+    require!(price > 0, CustomError::InvalidOraclePrice);
+
+    let within_window = mint_config.last_price != 0
+        && now.saturating_sub(mint_config.last_price_at) <= config.price_deviation_window_secs;
+    if within_window && config.max_price_deviation_bps > 0 {
+        let deviation = price_deviation_bps(mint_config.last_price, price);
+        require!(
+            deviation <= config.max_price_deviation_bps,
+            CustomError::OraclePriceDeviation
+        );
+    }
+
+    mint_config.last_price = price;
+    mint_config.last_price_at = now;
+    Ok(())
+}
+
+/// Event emitted when the admin re-anchors a mint's accepted price
+#[event]
+pub struct OraclePriceOverriddenEvent {
+    pub mint: Pubkey,        // Affected mint
+    pub previous_price: u64, // Last accepted price before the override
+    pub price: u64,          // New accepted price
+    pub admin: Pubkey,       // Admin who performed the override
+    pub timestamp: i64,      // When the override happened
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Admin override: set the accepted price for a mint directly
+pub fn handle_override_oracle_price(ctx: Context<ManageMint>, price: u64) -> Result<()> {
+    require!(price > 0, CustomError::InvalidOraclePrice);
+
+    let now = Clock::get()?.unix_timestamp;
+    let mint_config = &mut ctx.accounts.mint_config;
+    let previous_price = mint_config.last_price;
+    mint_config.last_price = price;
+    mint_config.last_price_at = now;
+
+    emit!(OraclePriceOverriddenEvent {
+        mint: mint_config.mint,
+        previous_price,
+        price,
+        admin: ctx.accounts.admin.key(),
+        timestamp: now,
+        tag: InstructionTag::OverrideOraclePrice,
+    });
+
+    msg!("Oracle price overridden: {} -> {}", previous_price, price);
+    Ok(())
+}
@@ -0,0 +1,326 @@
+/*
+ * ============================================================================
+ * TRANSFER POLICIES
+ * ============================================================================
+ *
+ * A user may attach a `TransferPolicy` to their account: a short program
+ * over predefined predicates that every outflow from the account must
+ * satisfy: `transfer_tokens`, batch, routed, promo, joint and bridged
+ * transfers, `transfer_from` by a delegate, `create_escrow`,
+ * `withdraw_tokens`, invoice payments and purchases, credit draws and
+ * repayments, net settlements and auction bid deposits. Batch and routed
+ * transfers check each receiver in turn, an escrow its beneficiary, a
+ * withdrawal the account's own authority and a bid the auction's seller.
+ * The program is postfix bytecode evaluated on a small boolean stack:
+ *
+ *     MAX_AMOUNT <u64>        push amount <= operand
+ *     HOURS <start> <end>     push start <= UTC hour < end (wraps midnight
+ *                             when start > end)
+ *     ALLOWLISTED             push receiver authority in the allowlist
+ *     AND / OR / NOT          combine the top of the stack
+ *
+ * and the transfer proceeds when exactly `true` is left. "Up to 500, or any
+ * amount to my allowlist" is `MAX_AMOUNT 500 ALLOWLISTED OR`.
+ *
+ * There are no jumps, so evaluation is one pass over at most
+ * `MAX_POLICY_CODE_LEN` bytes with at most `MAX_POLICY_STACK` values, and
+ * each `ALLOWLISTED` compares at most `MAX_POLICY_ALLOWLIST` keys. Programs
+ * are validated when stored, so a policy that could not evaluate is never
+ * written.
+ *
+ * The policy account sits at a fixed address every one of those
+ * instructions receives, so a sender cannot leave it out. Changes apply at
+ * once: a policy guards against mistakes and automated signers, while
+ * guardians and the daily limit's delay cover a compromised key.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount, MAX_POLICY_ALLOWLIST, MAX_POLICY_CODE_LEN, MAX_POLICY_STACK,
+};
+
+/// Policy bytecode opcodes
+pub mod policy_op {
+    pub const MAX_AMOUNT: u8 = 0x01; // Followed by a little-endian u64
+    pub const HOURS: u8 = 0x02; // Followed by start and end hours, UTC
+    pub const ALLOWLISTED: u8 = 0x03; // Receiver authority in the allowlist
+    pub const AND: u8 = 0x10; // Both of the top two
+    pub const OR: u8 = 0x11; // Either of the top two
+    pub const NOT: u8 = 0x12; // Negate the top
+}
+
+/// What a policy decides on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PolicyInput {
+    pub amount: u64,      // Amount leaving the account
+    pub receiver: Pubkey, // Receiver's authority
+    pub now: i64,         // Current Unix time
+}
+
+/// One decoded instruction
+enum Op {
+    MaxAmount(u64),
+    Hours(u8, u8),
+    Allowlisted,
+    And,
+    Or,
+    Not,
+}
+
+/// Decode the instruction at `code[*pc..]`, advancing `pc` past it
+fn decode(code: &[u8], pc: &mut usize) -> Result<Op> {
+    let operands = |len: usize, pc: &mut usize| -> Result<&[u8]> {
+        let start = *pc + 1;
+        let bytes = code
+            .get(start..start + len)
+            .ok_or(CustomError::InvalidPolicy)?; // Truncated operand
+        *pc = start + len;
+        Ok(bytes)
+    };
+    let op = match code[*pc] {
+        policy_op::MAX_AMOUNT => {
+            let bytes = operands(8, pc)?;
+            Op::MaxAmount(u64::from_le_bytes(bytes.try_into().unwrap()))
+        }
+        policy_op::HOURS => {
+            let bytes = operands(2, pc)?;
+            let (start, end) = (bytes[0], bytes[1]);
+            require!(start < 24 && end <= 24, CustomError::InvalidPolicy);
+            Op::Hours(start, end)
+        }
+        single => {
+            *pc += 1;
+            match single {
+                policy_op::ALLOWLISTED => Op::Allowlisted,
+                policy_op::AND => Op::And,
+                policy_op::OR => Op::Or,
+                policy_op::NOT => Op::Not,
+                _ => return err!(CustomError::InvalidPolicy), // Unknown opcode
+            }
+        }
+    };
+    Ok(op)
+}
+
+/// Run `code` against `input`; `Err` for programs that cannot evaluate
+pub fn evaluate_policy(code: &[u8], allowlist: &[Pubkey], input: &PolicyInput) -> Result<bool> {
+    require!(
+        !code.is_empty() && code.len() <= MAX_POLICY_CODE_LEN,
+        CustomError::InvalidPolicy
+    );
+    let hour = (input.now.rem_euclid(86_400) / 3_600) as u8;
+    let mut stack = [false; MAX_POLICY_STACK];
+    let mut depth = 0usize;
+    let mut pc = 0usize;
+
+    while pc < code.len() {
+        // Values consumed, and the value pushed from them
+        let (pops, value) = match decode(code, &mut pc)? {
+            Op::MaxAmount(max) => (0, input.amount <= max),
+            Op::Hours(start, end) if start <= end => (0, start <= hour && hour < end),
+            Op::Hours(start, end) => (0, hour >= start || hour < end), // Wraps midnight
+            Op::Allowlisted => (0, allowlist.contains(&input.receiver)),
+            Op::Not => (1, depth >= 1 && !stack[depth - 1]),
+            Op::And => (2, depth >= 2 && stack[depth - 2] && stack[depth - 1]),
+            Op::Or => (2, depth >= 2 && (stack[depth - 2] || stack[depth - 1])),
+        };
+        require!(depth >= pops, CustomError::InvalidPolicy); // Stack underflow
+        depth -= pops;
+        require!(depth < MAX_POLICY_STACK, CustomError::InvalidPolicy); // Stack overflow
+        stack[depth] = value;
+        depth += 1;
+    }
+
+    require!(depth == 1, CustomError::InvalidPolicy); // Exactly one verdict
+    Ok(stack[0])
+}
+
+/// Check a policy before it is stored
+pub fn validate_policy(code: &[u8], allowlist: &[Pubkey]) -> Result<()> {
+    require!(
+        allowlist.len() <= MAX_POLICY_ALLOWLIST && !allowlist.contains(&Pubkey::default()),
+        CustomError::InvalidPolicy
+    );
+    // Every input walks the same instructions, so any one proves the shape
+    let probe = PolicyInput {
+        amount: 0,
+        receiver: Pubkey::default(),
+        now: 0,
+    };
+    evaluate_policy(code, allowlist, &probe).map(|_| ())
+}
+
+/// A user's transfer policy
+#[account]
+pub struct TransferPolicy {
+    pub user: Pubkey,           // Governed user account (32 bytes)
+    pub code: Vec<u8>,          // Policy bytecode (4 + up to MAX_POLICY_CODE_LEN bytes)
+    pub allowlist: Vec<Pubkey>, // Authorities `ALLOWLISTED` accepts (4 + 32 * up to 8 bytes)
+    pub bump: u8,               // PDA bump seed (1 byte)
+}
+
+impl TransferPolicy {
+    /// Space required at the maximum code and allowlist sizes
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        4 + MAX_POLICY_CODE_LEN + // code: Vec<u8>
+        4 + 32 * MAX_POLICY_ALLOWLIST + // allowlist: Vec<Pubkey>
+        1; // bump: u8
+
+    /// Whether the policy allows a transfer of `input`
+    pub fn allows(&self, input: &PolicyInput) -> Result<bool> {
+        evaluate_policy(&self.code, &self.allowlist, input)
+    }
+}
+
+/// Context for attaching a policy to the caller's account
+#[derive(Accounts)]
+pub struct CreatePolicy<'info> {
+    /// Policy being created
+    #[account(
+        init,
+        payer = authority,
+        space = TransferPolicy::LEN,
+        seeds = [b"policy", user.key().as_ref()],
+        bump
+    )]
+    pub policy: Account<'info, TransferPolicy>,
+
+    /// Governed user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for replacing or removing the caller's policy
+#[derive(Accounts)]
+pub struct ManagePolicy<'info> {
+    /// Policy being changed
+    #[account(mut, has_one = user, seeds = [b"policy", user.key().as_ref()], bump = policy.bump)]
+    pub policy: Account<'info, TransferPolicy>,
+
+    /// Governed user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, receives the rent on close
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a policy is stored
+#[event]
+pub struct PolicySetEvent {
+    pub user: Pubkey,           // Governed user account
+    pub code: Vec<u8>,          // New bytecode
+    pub allowlist: Vec<Pubkey>, // New allowlist
+    pub tag: InstructionTag,    // Emitting instruction
+}
+
+/// Event emitted when a policy is removed
+#[event]
+pub struct PolicyClosedEvent {
+    pub user: Pubkey,        // Formerly governed user account
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Decode the policy at `info` if the sender has one
+pub fn load_policy(info: &AccountInfo) -> Result<Option<TransferPolicy>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(None); // Never created (or closed)
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Some(TransferPolicy::try_deserialize(&mut &data[..])?))
+}
+
+/// Attach `code` and `allowlist` to the caller's account
+pub fn handle_create_policy(
+    ctx: Context<CreatePolicy>,
+    code: Vec<u8>,
+    allowlist: Vec<Pubkey>,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SET_POLICY)?;
+    validate_policy(&code, &allowlist)?;
+
+    let policy = &mut ctx.accounts.policy;
+    policy.user = ctx.accounts.user.key();
+    policy.bump = ctx.bumps.policy;
+    policy.code = code;
+    policy.allowlist = allowlist;
+
+    emit!(PolicySetEvent {
+        user: policy.user,
+        code: policy.code.clone(),
+        allowlist: policy.allowlist.clone(),
+        tag: InstructionTag::CreatePolicy,
+    });
+
+    msg!("Transfer policy attached");
+    Ok(())
+}
+
+/// Replace the caller's policy
+pub fn handle_update_policy(
+    ctx: Context<ManagePolicy>,
+    code: Vec<u8>,
+    allowlist: Vec<Pubkey>,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SET_POLICY)?;
+    validate_policy(&code, &allowlist)?;
+
+    let policy = &mut ctx.accounts.policy;
+    policy.code = code;
+    policy.allowlist = allowlist;
+
+    emit!(PolicySetEvent {
+        user: policy.user,
+        code: policy.code.clone(),
+        allowlist: policy.allowlist.clone(),
+        tag: InstructionTag::UpdatePolicy,
+    });
+
+    msg!("Transfer policy replaced");
+    Ok(())
+}
+
+/// Remove the caller's policy, refunding its rent
+pub fn handle_close_policy(ctx: Context<ManagePolicy>) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::SET_POLICY)?;
+    ctx.accounts
+        .policy
+        .close(ctx.accounts.authority.to_account_info())?;
+
+    emit!(PolicyClosedEvent {
+        user: ctx.accounts.user.key(),
+        tag: InstructionTag::ClosePolicy,
+    });
+
+    msg!("Transfer policy removed");
+    Ok(())
+}
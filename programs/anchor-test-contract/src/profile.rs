@@ -0,0 +1,215 @@
+/*
+ * ============================================================================
+ * USER PROFILES
+ * ============================================================================
+ *
+ * Profile fields beyond `name` and `age` live in a separate `UserProfile`
+ * PDA (`[b"profile", user]`), so `UserAccount` keeps a fixed size:
+ * - an optional avatar URI, on a whitelisted scheme, of at most
+ *   `MAX_AVATAR_URI_LEN` bytes;
+ * - an optional 32-byte hash of the user's email address, never the address;
+ * - a free-form metadata string of at most `MAX_PROFILE_METADATA_LEN` bytes.
+ *
+ * The account is sized to its contents. `create_profile` allocates it, and
+ * `update_profile` reallocs it to fit the new contents, the authority paying
+ * for growth and receiving the rent freed by shrinking. Every change emits
+ * the full profile in a `ProfileUpdatedEvent`.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount, ALLOWED_URI_SCHEMES, MAX_AVATAR_URI_LEN, MAX_PROFILE_METADATA_LEN,
+};
+
+/// User-supplied profile contents
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct ProfileParams {
+    pub avatar_uri: Option<String>,   // Avatar location
+    pub email_hash: Option<[u8; 32]>, // Hash of the email address
+    pub metadata: String,             // Free-form, at most 256 bytes
+}
+
+impl ProfileParams {
+    /// Check lengths and the avatar URI scheme
+    pub fn validate(&self) -> Result<()> {
+        if let Some(uri) = &self.avatar_uri {
+            require!(
+                uri.len() <= MAX_AVATAR_URI_LEN,
+                CustomError::AvatarUriTooLong
+            ); // Bytes, not characters: a multi-byte UTF-8 URI fits less
+            let scheme_ok = ALLOWED_URI_SCHEMES
+                .iter()
+                .any(|scheme| uri.len() > scheme.len() && uri.starts_with(scheme));
+            require!(scheme_ok, CustomError::UnsupportedUriScheme); // Needs more than the bare scheme
+        }
+        require!(
+            self.metadata.len() <= MAX_PROFILE_METADATA_LEN,
+            CustomError::ProfileMetadataTooLong
+        );
+        Ok(())
+    }
+}
+
+/// Extended profile of a user account
+#[account]
+pub struct UserProfile {
+    pub user: Pubkey,                 // Owning user account (32 bytes)
+    pub avatar_uri: Option<String>,   // Avatar location (1 [+ 4 + len] bytes)
+    pub email_hash: Option<[u8; 32]>, // Hash of the email address (1 [+ 32] bytes)
+    pub metadata: String,             // Free-form metadata (4 + len bytes)
+    pub updated_at: i64,              // Last change (8 bytes)
+    pub bump: u8,                     // PDA bump seed (1 byte)
+}
+
+impl UserProfile {
+    /// Space required to store `params` including the discriminator
+    pub fn space(params: &ProfileParams) -> usize {
+        8 +                                                    // Anchor discriminator
+        32 +                                                   // user: Pubkey
+        1 + params.avatar_uri.as_ref().map_or(0, |uri| 4 + uri.len()) + // avatar_uri
+        1 + params.email_hash.map_or(0, |hash| hash.len()) +   // email_hash
+        4 + params.metadata.len() +                            // metadata: String
+        8 +                                                    // updated_at: i64
+        1 // bump: u8
+    }
+
+    /// Validate and copy `params` onto the profile
+    fn apply(&mut self, params: ProfileParams, now: i64) -> Result<()> {
+        params.validate()?;
+        self.avatar_uri = params.avatar_uri;
+        self.email_hash = params.email_hash;
+        self.metadata = params.metadata;
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+/// Context for creating a user's profile
+#[derive(Accounts)]
+#[instruction(params: ProfileParams)]
+pub struct CreateProfile<'info> {
+    /// Profile being created, sized to `params`
+    #[account(
+        init,
+        payer = authority,
+        space = UserProfile::space(&params),
+        seeds = [b"profile", user.key().as_ref()], // One profile per user
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for replacing a user's profile
+#[derive(Accounts)]
+#[instruction(params: ProfileParams)]
+pub struct UpdateProfile<'info> {
+    /// Profile being replaced, resized to `params`
+    #[account(
+        mut,
+        has_one = user,
+        realloc = UserProfile::space(&params),
+        realloc::payer = authority,
+        realloc::zero = false,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays for growth and receives freed rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for the rent top-up
+    pub system_program: Program<'info, System>,
+}
+
+/// Event carrying the full profile whenever it is created or replaced
+#[event]
+pub struct ProfileUpdatedEvent {
+    pub user: Pubkey,                 // Owning user account
+    pub avatar_uri: Option<String>,   // Avatar location
+    pub email_hash: Option<[u8; 32]>, // Hash of the email address
+    pub metadata: String,             // Free-form metadata
+    pub tag: InstructionTag,          // Emitting instruction
+}
+
+impl ProfileUpdatedEvent {
+    fn from_profile(profile: &UserProfile, tag: InstructionTag) -> Self {
+        Self {
+            user: profile.user,
+            avatar_uri: profile.avatar_uri.clone(),
+            email_hash: profile.email_hash,
+            metadata: profile.metadata.clone(),
+            tag,
+        }
+    }
+}
+
+/// Create the caller's profile from `params`
+pub fn handle_create_profile(ctx: Context<CreateProfile>, params: ProfileParams) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::UPDATE_PROFILE)?;
+
+    let profile = &mut ctx.accounts.profile;
+    profile.user = ctx.accounts.user.key();
+    profile.bump = ctx.bumps.profile;
+    profile.apply(params, Clock::get()?.unix_timestamp)?;
+
+    emit!(ProfileUpdatedEvent::from_profile(
+        profile,
+        InstructionTag::CreateProfile
+    ));
+
+    msg!("Profile created for {}", profile.user);
+    Ok(())
+}
+
+/// Replace the caller's profile with `params`
+pub fn handle_update_profile(ctx: Context<UpdateProfile>, params: ProfileParams) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::UPDATE_PROFILE)?;
+
+    let profile = &mut ctx.accounts.profile;
+    profile.apply(params, Clock::get()?.unix_timestamp)?;
+
+    emit!(ProfileUpdatedEvent::from_profile(
+        profile,
+        InstructionTag::UpdateProfile
+    ));
+
+    msg!("Profile updated for {}", profile.user);
+    Ok(())
+}
@@ -0,0 +1,361 @@
+/*
+ * ============================================================================
+ * PROMO CODES
+ * ============================================================================
+ *
+ * The admin creates promo codes by hash: a `PromoCode` lives at
+ * `[b"promo_code", config, promo_code_hash(code)]`, so codes cannot be read
+ * back from chain state. Payers redeem a code with `promo_transfer`, a
+ * `transfer_tokens` that carries the plaintext code; the program hashes it
+ * to find the promo. Rewards are either
+ * - `FeeWaiver`: the transfer's protocol fee is waived, or
+ * - `BonusCredit`: the payer is credited a bonus out of the fee treasury.
+ *
+ * Each code has a usage cap and an expiry; past it, `expire` retires the
+ * code and returns its rent to the admin who created it. Every user may
+ * redeem a code once, enforced by the `PromoClaim` PDA
+ * (`[b"promo_claim", promo_code, user]`) the redemption creates.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    apply_transfer_with_fee, instruction_flags, load_policy, log_activity, log_transfer,
+    record_transfer_volume, signer_keys, utilization, ActivityEntry, ActivityKind, ClockTolerance,
+    CustomError, EpochStats, FeeTreasury, GlobalConfig, InstructionTag, Ledger, LedgerAccount,
+    PolicyInput, TokenTransferEvent, UserAccount,
+};
+
+/// Domain prefix hashed before every promo code
+pub const PROMO_CODE_DOMAIN: &[u8] = b"anchor-test-contract:promo-code:v1\n";
+
+/// Hash a promo code is stored and looked up under
+pub fn promo_code_hash(code: &str) -> [u8; 32] {
+    hashv(&[PROMO_CODE_DOMAIN, code.as_bytes()]).to_bytes()
+}
+
+/// What redeeming a promo code grants
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PromoReward {
+    FeeWaiver,                   // No protocol fee on the transfer
+    BonusCredit { amount: u64 }, // Credited to the payer from the fee treasury
+}
+
+/// An admin-created promo code, stored by hash
+#[account]
+pub struct PromoCode {
+    pub code_hash: [u8; 32], // `promo_code_hash` of the code (32 bytes)
+    pub reward: PromoReward, // Granted on redemption (9 bytes)
+    pub max_uses: u32,       // Redemptions allowed in total (4 bytes)
+    pub uses: u32,           // Redemptions so far (4 bytes)
+    pub expires_at: i64,     // Redeemable until (8 bytes)
+    pub creator: Pubkey,     // Admin who paid the rent (32 bytes)
+    pub bump: u8,            // PDA bump seed (1 byte)
+}
+
+impl PromoCode {
+    /// Space required for the promo code including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // code_hash: [u8; 32]
+        1 + 8 +                  // reward: PromoReward (variant + largest payload)
+        4 +                      // max_uses: u32
+        4 +                      // uses: u32
+        8 +                      // expires_at: i64
+        32 +                     // creator: Pubkey
+        1; // bump: u8
+
+    /// Count one redemption at `now`, failing once expired or used up
+    pub fn redeem(&mut self, now: i64, tolerance: ClockTolerance) -> Result<()> {
+        require!(
+            tolerance.before(now, self.expires_at),
+            CustomError::PromoCodeExpired
+        );
+        require!(self.uses < self.max_uses, CustomError::PromoCodeExhausted);
+        self.uses += 1;
+        Ok(())
+    }
+}
+
+/// Record of a user having redeemed a promo code
+#[account]
+pub struct PromoClaim {
+    pub promo_code: Pubkey, // Redeemed promo code (32 bytes)
+    pub user: Pubkey,       // Redeeming user account (32 bytes)
+    pub claimed_at: i64,    // When it was redeemed (8 bytes)
+    pub bump: u8,           // PDA bump seed (1 byte)
+}
+
+impl PromoClaim {
+    /// Space required for the claim including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // promo_code: Pubkey
+        32 +                     // user: Pubkey
+        8 +                      // claimed_at: i64
+        1; // bump: u8
+}
+
+/// Context for creating a promo code
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32])]
+pub struct CreatePromoCode<'info> {
+    /// Promo code being created
+    #[account(
+        init,
+        payer = admin,
+        space = PromoCode::LEN,
+        seeds = [b"promo_code", config.key().as_ref(), code_hash.as_ref()],
+        bump
+    )]
+    pub promo_code: Account<'info, PromoCode>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for a transfer redeeming a promo code
+///
+/// Remaining accounts: the sender's guardian signers, when needed.
+#[derive(Accounts)]
+#[instruction(amount: u64, code: String)]
+pub struct PromoTransfer<'info> {
+    /// Sender's user account, redeeming the code
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub sender: Account<'info, UserAccount>,
+
+    /// Receiver's user account
+    #[account(
+        mut,
+        constraint = receiver.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), receiver.authority.as_ref()],
+        bump
+    )]
+    pub receiver: Account<'info, UserAccount>,
+
+    /// Promo code the plaintext `code` hashes to
+    #[account(
+        mut,
+        seeds = [b"promo_code", config.key().as_ref(), promo_code_hash(&code).as_ref()],
+        bump = promo_code.bump
+    )]
+    pub promo_code: Account<'info, PromoCode>,
+
+    /// Sender's claim, whose creation fails on a second redemption
+    #[account(
+        init,
+        payer = authority,
+        space = PromoClaim::LEN,
+        seeds = [b"promo_claim", promo_code.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub promo_claim: Account<'info, PromoClaim>,
+
+    /// Sender authority, pays the claim's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the transfer entries
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the fee and funding bonus credits
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Sender's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the sender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", sender.key().as_ref()], bump)]
+    pub sender_policy: UncheckedAccount<'info>,
+
+    /// Sender's activity log address, appended to when the sender keeps one
+    /// CHECK: address is the sender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", sender.key().as_ref()], bump)]
+    pub sender_activity: UncheckedAccount<'info>,
+
+    /// Receiver's activity log address, appended to when the receiver keeps one
+    /// CHECK: address is the receiver's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", receiver.key().as_ref()], bump)]
+    pub receiver_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted when a promo code is redeemed
+#[event]
+pub struct PromoRedeemedEvent {
+    pub promo_code: Pubkey,  // Redeemed promo code
+    pub user: Pubkey,        // Redeeming user account
+    pub reward: PromoReward, // Reward granted
+    pub uses: u32,           // Redemptions so far, this one included
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create a promo code stored under `code_hash`
+pub fn handle_create_promo_code(
+    ctx: Context<CreatePromoCode>,
+    code_hash: [u8; 32],
+    reward: PromoReward,
+    max_uses: u32,
+    expires_at: i64,
+) -> Result<()> {
+    require!(
+        max_uses > 0 && expires_at > Clock::get()?.unix_timestamp,
+        CustomError::InvalidPromoCode
+    );
+    if let PromoReward::BonusCredit { amount } = reward {
+        require!(amount > 0, CustomError::InvalidPromoCode);
+    }
+
+    let promo_code = &mut ctx.accounts.promo_code;
+    promo_code.code_hash = code_hash;
+    promo_code.reward = reward;
+    promo_code.max_uses = max_uses;
+    promo_code.uses = 0;
+    promo_code.expires_at = expires_at;
+    promo_code.creator = ctx.accounts.admin.key();
+    promo_code.bump = ctx.bumps.promo_code;
+
+    msg!("Promo code created: {}", promo_code.key());
+    Ok(())
+}
+
+/// Transfer `amount` to the receiver, redeeming `code` for the sender
+pub fn handle_promo_transfer(
+    ctx: Context<PromoTransfer>,
+    amount: u64,
+    _code: String,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let sender = &ctx.accounts.sender;
+    let policy = load_policy(&ctx.accounts.sender_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: ctx.accounts.receiver.authority,
+        now,
+    };
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::PROMO_TRANSFER),
+        Guard::Distinct(&[sender.key(), ctx.accounts.receiver.key()]),
+        Guard::NotJoint(sender), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(sender, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(sender, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+
+    let promo_code = &mut ctx.accounts.promo_code;
+    promo_code.redeem(now, ctx.accounts.config.clock_tolerance())?;
+    let reward = promo_code.reward;
+
+    let sender = &mut ctx.accounts.sender;
+    sender.record_daily_spend(amount, now)?;
+
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        amount,
+        InstructionTag::PromoTransfer,
+    )?;
+    let fee = match reward {
+        PromoReward::FeeWaiver => 0,
+        PromoReward::BonusCredit { .. } => {
+            let stats = &ctx.accounts.epoch_stats;
+            ctx.accounts.config.fee_at(
+                amount,
+                now,
+                utilization(stats.volume, stats.trailing_average),
+            )?
+        }
+    };
+
+    let receiver = &mut ctx.accounts.receiver;
+    apply_transfer_with_fee(sender, receiver, amount, fee, now)?;
+    let treasury = &mut ctx.accounts.fee_treasury;
+    treasury.collect(fee)?;
+
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, amount - fee)?; // Sender debit, receiver credit
+    if fee > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fee)?; // Withheld for the treasury
+    }
+    if let PromoReward::BonusCredit { amount: bonus } = reward {
+        treasury.balance = treasury
+            .balance
+            .checked_sub(bonus)
+            .ok_or(CustomError::InsufficientFunds)?;
+        sender.balance = math::add(sender.balance, bonus)?;
+        ledger.post(LedgerAccount::Fees, LedgerAccount::Users, bonus)?; // Paid out of collected fees
+    }
+
+    let tag = InstructionTag::PromoTransfer;
+    let order = log_transfer(
+        (&ctx.accounts.sender_activity, sender.key(), sender),
+        (&ctx.accounts.receiver_activity, receiver.key(), receiver),
+        amount,
+        amount - fee,
+        tag,
+        now,
+    )?;
+    if let PromoReward::BonusCredit { amount: bonus } = reward {
+        let entry = ActivityEntry::new(ActivityKind::Received, tag, Pubkey::default(), bonus, now);
+        log_activity(&ctx.accounts.sender_activity, sender.key(), entry)?; // Paid by the treasury
+    }
+
+    let claim = &mut ctx.accounts.promo_claim;
+    claim.promo_code = ctx.accounts.promo_code.key();
+    claim.user = sender.key();
+    claim.claimed_at = now;
+    claim.bump = ctx.bumps.promo_claim;
+
+    emit!(TokenTransferEvent {
+        from: sender.authority,
+        to: receiver.authority,
+        amount,
+        fee,
+        timestamp: now,
+        order,
+        tag: InstructionTag::PromoTransfer,
+    });
+    emit!(PromoRedeemedEvent {
+        promo_code: claim.promo_code,
+        user: claim.user,
+        reward,
+        uses: ctx.accounts.promo_code.uses,
+        tag: InstructionTag::PromoTransfer,
+    });
+
+    msg!(
+        "Transferred {} with promo code {}",
+        amount,
+        claim.promo_code
+    );
+    Ok(())
+}
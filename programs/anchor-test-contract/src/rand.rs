@@ -0,0 +1,92 @@
+/*
+ * ============================================================================
+ * DETERMINISTIC RANDOMNESS
+ * ============================================================================
+ *
+ * Pseudo-randomness for raffles, tie-breaking and sampling. A seed mixes:
+ * - the most recent entry of the `SlotHashes` sysvar
+ * - a user-revealed commitment preimage (see commit-reveal)
+ * - a per-use counter, so one seed never yields the same stream twice
+ *
+ * Manipulation bounds: the leader of the slot can withhold a block to reroll
+ * the slot hash (one retry per slot it leads), and the revealing user can
+ * refuse to reveal after seeing the slot hash. Callers must therefore
+ * penalize non-reveals and never let a single seed decide more value than a
+ * skipped slot costs. Where a VRF oracle is available, implement
+ * `RandomnessSource` for it and use that instead of `SlotHashSource`.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar;
+
+use crate::CustomError;
+
+/// Anything able to provide 32 bytes of entropy for a seed
+pub trait RandomnessSource {
+    /// Entropy for the current transaction
+    fn entropy(&self) -> Result<[u8; 32]>;
+}
+
+/// Entropy from the most recent `SlotHashes` sysvar entry
+pub struct SlotHashSource<'a, 'info> {
+    pub slot_hashes: &'a AccountInfo<'info>,
+}
+
+impl RandomnessSource for SlotHashSource<'_, '_> {
+    fn entropy(&self) -> Result<[u8; 32]> {
+        require_keys_eq!(
+            *self.slot_hashes.key,
+            sysvar::slot_hashes::ID,
+            CustomError::InvalidSlotHashes
+        );
+
+        // Layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first.
+        // Only the first entry is read; deserializing the whole sysvar exceeds compute.
+        let data = self.slot_hashes.try_borrow_data()?;
+        let hash = data.get(16..48).ok_or(CustomError::InvalidSlotHashes)?;
+        let mut entropy = [0u8; 32];
+        entropy.copy_from_slice(hash);
+        Ok(entropy)
+    }
+}
+
+/// Counter-mode stream generator over a mixed seed
+pub struct Rng {
+    seed: [u8; 32], // Mixed seed (32 bytes)
+    counter: u64,   // Draws taken so far (8 bytes)
+}
+
+impl Rng {
+    /// Mix source entropy, a revealed preimage and a domain counter into a seed
+    pub fn new(source: &impl RandomnessSource, preimage: &[u8], nonce: u64) -> Result<Self> {
+        let entropy = source.entropy()?;
+        Ok(Self::from_seed(
+            hashv(&[&entropy, preimage, &nonce.to_le_bytes()]).to_bytes(),
+        ))
+    }
+
+    /// Generator over an already mixed seed
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self { seed, counter: 0 }
+    }
+
+    /// Next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let block = hashv(&[&self.seed, &self.counter.to_le_bytes()]).to_bytes();
+        self.counter += 1;
+        u64::from_le_bytes(block[..8].try_into().unwrap())
+    }
+
+    /// Uniform value in `0..bound` without modulo bias
+    pub fn below(&mut self, bound: u64) -> Result<u64> {
+        require!(bound > 0, CustomError::InvalidAmount);
+        let zone = u64::MAX - (u64::MAX % bound); // Largest multiple of `bound`
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return Ok(value % bound);
+            }
+        }
+    }
+}
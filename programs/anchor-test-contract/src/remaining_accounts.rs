@@ -0,0 +1,78 @@
+/*
+ * ============================================================================
+ * REMAINING ACCOUNT VALIDATION
+ * ============================================================================
+ *
+ * Batch and report instructions take variable-length account lists through
+ * `remaining_accounts`, which Anchor does not validate. Every such account
+ * goes through `RemainingAccountsValidator::load`, which rejects, in order:
+ *
+ * - an account already seen in this instruction (double counting)
+ * - an owner other than this program (look-alikes from a rogue program)
+ * - a discriminator other than the expected type's (type cosplay)
+ * - an address other than the PDA re-derived from the decoded seeds
+ *
+ * Only the first two checks run before any byte of the account is decoded.
+ */
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::CustomError;
+
+/// Address of this program's PDA for `seeds`
+pub fn derive_address(seeds: &[Vec<u8>]) -> Pubkey {
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::find_program_address(&seeds, &crate::ID).0
+}
+
+/// Validates and decodes the accounts of one `remaining_accounts` list
+#[derive(Default)]
+pub struct RemainingAccountsValidator {
+    seen: Vec<Pubkey>, // Accounts accepted so far
+}
+
+impl RemainingAccountsValidator {
+    /// Validator expecting about `capacity` accounts
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            seen: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of accounts accepted so far
+    pub fn count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Check `info` is a genuine `T` of this program living at its PDA, then decode it
+    /// `seeds` returns the PDA seeds of the decoded account
+    pub fn load<T: AccountDeserialize + Discriminator>(
+        &mut self,
+        info: &AccountInfo,
+        seeds: impl FnOnce(&T) -> Vec<Vec<u8>>,
+    ) -> Result<T> {
+        require!(!self.seen.contains(info.key), CustomError::DuplicateAccount);
+        if *info.owner != crate::ID {
+            return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                .with_pubkeys((*info.owner, crate::ID)));
+        }
+
+        let data = info.try_borrow_data()?;
+        match data.get(..8) {
+            Some(discriminator) if discriminator == T::DISCRIMINATOR => {}
+            Some(_) => return err!(ErrorCode::AccountDiscriminatorMismatch),
+            None => return err!(ErrorCode::AccountDiscriminatorNotFound),
+        }
+
+        let account = T::try_deserialize(&mut &data[..])?;
+        let expected = derive_address(&seeds(&account));
+        if expected != *info.key {
+            return Err(Error::from(ErrorCode::ConstraintSeeds).with_pubkeys((*info.key, expected)));
+        }
+
+        self.seen.push(*info.key);
+        Ok(account)
+    }
+}
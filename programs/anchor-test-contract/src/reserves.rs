@@ -0,0 +1,195 @@
+/*
+ * ============================================================================
+ * PROOF OF RESERVES
+ * ============================================================================
+ *
+ * Once per epoch, anyone may crank `attest_reserves` for a mint's vault: it
+ * reads the vault's token balance and the mint's booked deposits, and emits
+ * a `ProofOfReservesEvent` with the coverage ratio. The event is logged by
+ * the program itself, so a dashboard relies on the program id rather than
+ * on the operator's off-chain reporting.
+ *
+ * Coverage is the vault's raw balance over its `accounted_balance`, in basis
+ * points: donations lift it above `BPS_DENOMINATOR`, a shortfall would drop
+ * it below. A vault owing nothing reports `u64::MAX`.
+ *
+ * The latest figures are kept in the mint's `ReservesAttestation`
+ * (`[b"reserves", mint_config]`), which the admin creates once with
+ * `init_reserves_attestation`; it is also what limits the crank to one
+ * attestation per epoch.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::decimals::Rounding;
+use crate::math;
+use crate::{
+    accounted_balance, CustomError, GlobalConfig, InstructionTag, MintConfig, BPS_DENOMINATOR,
+};
+
+/// Latest proof of reserves of one mint's vault
+#[account]
+pub struct ReservesAttestation {
+    pub mint: Pubkey,       // Mint whose vault is attested (32 bytes)
+    pub epoch: u64,         // Epoch of the latest attestation (8 bytes)
+    pub vault_balance: u64, // Raw tokens the vault held (8 bytes)
+    pub accounted: u64,     // Raw tokens owed to depositors (8 bytes)
+    pub coverage_bps: u64,  // `vault_balance` over `accounted` (8 bytes)
+    pub attested_at: i64,   // When it was attested, 0 before the first (8 bytes)
+    pub bump: u8,           // PDA bump seed (1 byte)
+}
+
+impl ReservesAttestation {
+    /// Space required for the account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // mint: Pubkey
+        8 +                      // epoch: u64
+        8 +                      // vault_balance: u64
+        8 +                      // accounted: u64
+        8 +                      // coverage_bps: u64
+        8 +                      // attested_at: i64
+        1; // bump: u8
+
+    /// True if an attestation may still be made in `epoch`
+    pub fn is_due(&self, epoch: u64) -> bool {
+        self.attested_at == 0 || epoch > self.epoch
+    }
+}
+
+/// Coverage of `owed` raw tokens by `reserves`, in basis points
+pub fn coverage_bps(reserves: u64, owed: u64) -> u64 {
+    if owed == 0 {
+        return u64::MAX; // Nothing owed, covered without bound
+    }
+    // Only overflows when reserves exceed what is owed many times over
+    math::mul_div(reserves, BPS_DENOMINATOR, owed, Rounding::Down).unwrap_or(u64::MAX)
+}
+
+/// Context for creating a mint's reserves attestation
+#[derive(Accounts)]
+pub struct InitReservesAttestation<'info> {
+    /// Attestation being created
+    #[account(
+        init,
+        payer = admin,
+        space = ReservesAttestation::LEN,
+        seeds = [b"reserves", mint_config.key().as_ref()], // One per mint
+        bump
+    )]
+    pub attestation: Account<'info, ReservesAttestation>,
+
+    /// Registry entry proving the mint is listed
+    #[account(
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for attesting a vault's reserves
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    /// Attestation updated with the latest figures
+    #[account(
+        mut,
+        seeds = [b"reserves", mint_config.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, ReservesAttestation>,
+
+    /// Vault whose balance is read
+    #[account(seeds = [b"vault", config.key().as_ref(), mint_config.mint.as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Registry entry of the mint, holds the booked deposits
+    #[account(
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Namespace config the vault belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event carrying one epoch's proof of reserves of a vault
+#[event]
+pub struct ProofOfReservesEvent {
+    pub mint: Pubkey,         // Mint whose vault is attested
+    pub vault: Pubkey,        // Vault token account read
+    pub epoch: u64,           // Epoch attested
+    pub vault_balance: u64,   // Raw tokens the vault held
+    pub total_deposited: u64, // Internal units owed to depositors
+    pub accounted: u64,       // Raw tokens owed to depositors
+    pub coverage_bps: u64,    // `vault_balance` over `accounted`
+    pub tag: InstructionTag,  // Emitting instruction
+}
+
+/// Create the mint's attestation, to be filled by the first crank
+pub fn handle_init_reserves_attestation(ctx: Context<InitReservesAttestation>) -> Result<()> {
+    let attestation = &mut ctx.accounts.attestation;
+    attestation.mint = ctx.accounts.mint_config.mint;
+    attestation.bump = ctx.bumps.attestation;
+
+    msg!("Reserves attestation created for mint {}", attestation.mint);
+    Ok(())
+}
+
+/// Attest the vault's reserves against its deposits for the current epoch
+pub fn handle_attest_reserves(ctx: Context<AttestReserves>) -> Result<()> {
+    let clock = Clock::get()?;
+    let attestation = &mut ctx.accounts.attestation;
+    require!(
+        attestation.is_due(clock.epoch),
+        CustomError::ReservesAlreadyAttested
+    );
+
+    let mint_config = &ctx.accounts.mint_config;
+    let vault_balance = ctx.accounts.vault.amount;
+    let accounted = accounted_balance(mint_config)?;
+    let coverage_bps = coverage_bps(vault_balance, accounted);
+
+    attestation.epoch = clock.epoch;
+    attestation.vault_balance = vault_balance;
+    attestation.accounted = accounted;
+    attestation.coverage_bps = coverage_bps;
+    attestation.attested_at = clock.unix_timestamp;
+
+    emit!(ProofOfReservesEvent {
+        mint: mint_config.mint,
+        vault: ctx.accounts.vault.key(),
+        epoch: clock.epoch,
+        vault_balance,
+        total_deposited: mint_config.total_deposited,
+        accounted,
+        coverage_bps,
+        tag: InstructionTag::AttestReserves,
+    });
+
+    msg!(
+        "Reserves of mint {} in epoch {}: {} of {} ({} bps)",
+        mint_config.mint,
+        clock.epoch,
+        vault_balance,
+        accounted,
+        coverage_bps
+    );
+    Ok(())
+}
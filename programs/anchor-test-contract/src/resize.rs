@@ -0,0 +1,109 @@
+/*
+ * ============================================================================
+ * RENT-AWARE ACCOUNT RESIZING
+ * ============================================================================
+ *
+ * Variable-size accounts (paged lists and the like) are allocated at the
+ * size their contents need and resized as they change. `resize_with_rent`
+ * reallocs an account and keeps it exactly rent-exempt: the payer tops up
+ * what a larger account needs, and the lamports a smaller one no longer
+ * needs are refunded to the payer.
+ *
+ * `compact_to_fit` shrinks over-allocated accounts back to what their data
+ * needs, never below their live serialized size, so nothing is truncated.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::math;
+use crate::{CustomError, InstructionTag};
+
+/// Lamports that must move for an account to be exactly rent-exempt
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RentDelta {
+    TopUp(u64),  // Payer must add this many lamports
+    Refund(u64), // This many lamports are no longer needed
+    Balanced,    // Already exactly rent-exempt
+}
+
+/// Lamport movement needed for an account holding `lamports` to be resized to `new_len`
+pub fn rent_delta(rent: &Rent, lamports: u64, new_len: usize) -> RentDelta {
+    let required = rent.minimum_balance(new_len);
+    match lamports.cmp(&required) {
+        std::cmp::Ordering::Less => RentDelta::TopUp(required - lamports),
+        std::cmp::Ordering::Greater => RentDelta::Refund(lamports - required),
+        std::cmp::Ordering::Equal => RentDelta::Balanced,
+    }
+}
+
+/// Realloc a program-owned `account` to `new_len`, settling rent with `payer`
+/// `payer` must sign when the account grows
+pub fn resize_with_rent<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if new_len == account.data_len() {
+        return Ok(()); // Nothing to do, and donated lamports stay put
+    }
+
+    match rent_delta(&Rent::get()?, account.lamports(), new_len) {
+        RentDelta::TopUp(lamports) => transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            lamports,
+        )?,
+        RentDelta::Refund(lamports) => {
+            // Program-owned, so lamports can be debited directly
+            let mut from = account.try_borrow_mut_lamports()?;
+            **from = from
+                .checked_sub(lamports)
+                .ok_or(CustomError::MathOverflow)?;
+            let mut to = payer.try_borrow_mut_lamports()?;
+            **to = to.checked_add(lamports).ok_or(CustomError::MathOverflow)?;
+        }
+        RentDelta::Balanced => {}
+    }
+
+    account.realloc(new_len, false)?;
+    Ok(())
+}
+
+/// Event emitted when an account is shrunk to fit its data
+#[event]
+pub struct AccountCompactedEvent {
+    pub account: Pubkey,     // Compacted account
+    pub old_len: u64,        // Allocation before compaction
+    pub new_len: u64,        // Allocation after compaction
+    pub refunded: u64,       // Lamports returned to the payer
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Shrink `account` to `needed` bytes, refunding freed rent to `payer`
+/// Refuses to cut below the account's live serialized size; returns the refund
+pub fn compact_to_fit<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+    account: &Account<'info, T>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    needed: usize,
+) -> Result<u64> {
+    let mut live = Vec::new();
+    account.try_serialize(&mut live)?;
+    require!(live.len() <= needed, CustomError::CompactionWouldTruncate);
+
+    let info = account.to_account_info();
+    require!(
+        needed <= info.data_len(),
+        CustomError::CompactionWouldTruncate
+    ); // Shrink only
+    let before = info.lamports();
+    resize_with_rent(&info, payer, system_program, needed)?;
+    math::sub(before, info.lamports())
+}
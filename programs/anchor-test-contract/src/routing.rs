@@ -0,0 +1,281 @@
+/*
+ * ============================================================================
+ * ROUTED TRANSFERS
+ * ============================================================================
+ *
+ * `routed_transfer(path, amount)` moves value from the sender through
+ * intermediate user accounts to a final receiver in one instruction, e.g.
+ * buyer → marketplace → merchant. The hop accounts are passed in
+ * `remaining_accounts`, intermediates first and the final receiver last;
+ * `path[i]` is the policy of the `i`-th intermediate.
+ *
+ * Every hop is a transfer in its own right: it pays the namespace fee and
+ * passes the same mode, vacation and joint-account checks as
+ * `transfer_tokens`, so an intermediate must be able to both receive and
+ * send. Of what reaches it, an intermediate keeps its `commission_bps` and
+ * forwards the rest. Intermediates never end up with less than they
+ * started with, so they need not sign. Their activity log addresses, and
+ * the final receiver's, follow the hops, then guardian signatures when the
+ * sender's amount needs them.
+ */
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+use crate::decimals::Rounding;
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    apply_transfer_with_fee, instruction_flags, load_policy, log_transfer, record_transfer_volume,
+    require_log_address, signer_keys, utilization, Bps, CustomError, EpochStats, EventOrder,
+    FeeTreasury, GlobalConfig, InstructionTag, Ledger, LedgerAccount, PolicyInput,
+    RemainingAccountsValidator, UserAccount, MAX_ROUTE_HOPS,
+};
+
+/// Policy of one intermediate hop
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RouteHop {
+    pub commission_bps: u64, // Share of the arriving amount the hop keeps
+}
+
+/// Amounts of one hop of a planned route
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RouteLeg {
+    pub sent: u64,     // Debited from the previous account
+    pub fee: u64,      // Protocol fee withheld from `sent`
+    pub retained: u64, // Kept by the receiving account
+}
+
+/// Amounts of every hop moving `amount` along `path`
+///
+/// `fee_of` is the protocol fee on a hop's sent amount. The result has one
+/// leg per intermediate, then the final receiver's leg, which keeps
+/// everything that arrives.
+pub fn plan_route(
+    amount: u64,
+    path: &[RouteHop],
+    fee_of: impl Fn(u64) -> Result<u64>,
+) -> Result<Vec<RouteLeg>> {
+    require!(path.len() <= MAX_ROUTE_HOPS, CustomError::RouteTooLong);
+
+    let mut legs = Vec::with_capacity(path.len() + 1);
+    let mut sent = amount;
+    for hop in path {
+        require!(
+            Bps(hop.commission_bps).is_fraction(),
+            CustomError::InvalidRoute
+        );
+        let fee = fee_of(sent)?;
+        let arrived = math::sub(sent, fee)?;
+        let retained = Bps(hop.commission_bps).of(arrived, Rounding::Down)?;
+        legs.push(RouteLeg {
+            sent,
+            fee,
+            retained,
+        });
+        sent = arrived - retained;
+    }
+
+    let fee = fee_of(sent)?;
+    legs.push(RouteLeg {
+        sent,
+        fee,
+        retained: math::sub(sent, fee)?,
+    });
+    Ok(legs)
+}
+
+/// Context for a routed transfer
+///
+/// Remaining accounts: the intermediates' user PDAs in `path` order, then
+/// the final receiver's, all writable; then the activity log addresses of
+/// the same accounts in the same order, writable; then any guardian signers.
+#[derive(Accounts)]
+pub struct RoutedTransfer<'info> {
+    /// Sender's user account
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sender.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub sender: Account<'info, UserAccount>,
+
+    /// Sender authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Epoch volume tracker for the circuit breaker
+    #[account(mut, seeds = [b"epoch_stats", config.key().as_ref()], bump = epoch_stats.bump)]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    /// Double-entry ledger receiving the route entries
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Treasury accruing the protocol fees
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Sender's transfer policy address, enforced when it holds a policy
+    /// CHECK: address is the sender's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", sender.key().as_ref()], bump)]
+    pub sender_policy: UncheckedAccount<'info>,
+
+    /// Sender's activity log address, appended to when the sender keeps one
+    /// CHECK: address is the sender's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", sender.key().as_ref()], bump)]
+    pub sender_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted once per routed transfer
+#[event]
+pub struct RoutedTransferEvent {
+    pub from: Pubkey,        // Sender's authority
+    pub to: Pubkey,          // Final receiver's authority
+    pub amount: u64,         // Debited from the sender
+    pub delivered: u64,      // Credited to the final receiver
+    pub fees: u64,           // Protocol fees withheld over all hops
+    pub commissions: u64,    // Kept by intermediates
+    pub hops: u8,            // Intermediates passed through
+    pub timestamp: i64,      // When the route was applied
+    pub order: EventOrder,   // Order of the sender's leg
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Move `amount` from the sender along `path` to the last remaining account
+pub fn handle_routed_transfer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RoutedTransfer<'info>>,
+    path: Vec<RouteHop>,
+    amount: u64,
+) -> Result<()> {
+    require!(
+        ctx.remaining_accounts.len() >= 2 * (path.len() + 1),
+        CustomError::InvalidRoute
+    );
+    let (hop_infos, rest) = ctx.remaining_accounts.split_at(path.len() + 1);
+    let (log_infos, guardian_infos) = rest.split_at(path.len() + 1);
+
+    // No account may appear twice along the route, the sender included
+    let mut keys = vec![ctx.accounts.sender.key()];
+    keys.extend(hop_infos.iter().map(|info| *info.key));
+    let now = Clock::get()?.unix_timestamp;
+    let sender = &ctx.accounts.sender;
+    guards::run(&[
+        Guard::Enabled(&ctx.accounts.config, instruction_flags::ROUTED_TRANSFER),
+        Guard::Distinct(&keys),
+        Guard::NotJoint(sender), // Joint accounts use joint_transfer
+        Guard::GuardianApproved(sender, amount, &signer_keys(guardian_infos)),
+        Guard::DailyLimit(sender, amount, now),
+    ])?;
+    ctx.accounts.sender.record_daily_spend(amount, now)?;
+
+    record_transfer_volume(
+        &mut ctx.accounts.epoch_stats,
+        &ctx.accounts.config,
+        amount,
+        InstructionTag::RoutedTransfer,
+    )?;
+    let stats = &ctx.accounts.epoch_stats;
+    let rate_utilization = utilization(stats.volume, stats.trailing_average);
+    let config = &ctx.accounts.config;
+    let legs = plan_route(amount, &path, |sent| {
+        config.fee_at(sent, now, rate_utilization)
+    })?;
+
+    let policy = load_policy(&ctx.accounts.sender_policy)?;
+    let config_key = config.key();
+    let mut validator = RemainingAccountsValidator::with_capacity(legs.len());
+    let mut hops = Vec::with_capacity(legs.len());
+    for info in hop_infos {
+        require!(info.is_writable, ErrorCode::ConstraintMut);
+        let hop = validator.load(info, |user: &UserAccount| {
+            vec![
+                b"user".to_vec(),
+                config_key.to_bytes().to_vec(),
+                user.authority.to_bytes().to_vec(),
+            ]
+        })?;
+        require!(hop.is_current(), CustomError::MigrationRequired);
+        let policy_input = PolicyInput {
+            amount,
+            receiver: hop.authority,
+            now,
+        };
+        Guard::Policy(policy.as_ref(), policy_input).check()?; // Every hop, so no detour escapes it
+        hops.push(hop);
+    }
+
+    // Each leg applied, then on the logs of both its ends
+    let sender = &mut ctx.accounts.sender;
+    let sender_key = sender.key();
+    let mut order = EventOrder::default();
+    for (index, leg) in legs.iter().enumerate() {
+        require_log_address(&log_infos[index], hop_infos[index].key)?;
+        let (before, after) = hops.split_at_mut(index);
+        let from = match before.last_mut() {
+            Some(previous) => (&log_infos[index - 1], *hop_infos[index - 1].key, previous),
+            None => (
+                ctx.accounts.sender_activity.as_ref(),
+                sender_key,
+                &mut **sender,
+            ),
+        };
+        Guard::NotJoint(from.2).check()?; // Intermediates forward like any sender
+        apply_transfer_with_fee(from.2, &mut after[0], leg.sent, leg.fee, now)?;
+        let leg_order = log_transfer(
+            from,
+            (&log_infos[index], *hop_infos[index].key, &mut after[0]),
+            leg.sent,
+            leg.sent - leg.fee,
+            InstructionTag::RoutedTransfer,
+            now,
+        )?;
+        if index == 0 {
+            order = leg_order; // The sender's own transfer
+        }
+    }
+    for (info, hop) in hop_infos.iter().zip(&hops) {
+        hop.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
+
+    let fees = legs
+        .iter()
+        .try_fold(0, |sum, leg| math::add(sum, leg.fee))?;
+    let moved = legs
+        .iter()
+        .try_fold(0, |sum, leg| math::add(sum, leg.sent - leg.fee))?;
+    ctx.accounts.fee_treasury.collect(fees)?;
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.post(LedgerAccount::Users, LedgerAccount::Users, moved)?; // Debits and credits along the route
+    if fees > 0 {
+        ledger.post(LedgerAccount::Users, LedgerAccount::Fees, fees)?; // Withheld for the treasury
+    }
+
+    let delivered = legs[path.len()].retained;
+    let commissions = legs[..path.len()].iter().map(|leg| leg.retained).sum();
+    emit!(RoutedTransferEvent {
+        from: sender.authority,
+        to: hops[path.len()].authority,
+        amount,
+        delivered,
+        fees,
+        commissions,
+        hops: path.len() as u8,
+        timestamp: now,
+        order,
+        tag: InstructionTag::RoutedTransfer,
+    });
+
+    msg!(
+        "Routed {} through {} hops, {} delivered",
+        amount,
+        path.len(),
+        delivered
+    );
+    Ok(())
+}
@@ -0,0 +1,200 @@
+/*
+ * ============================================================================
+ * SHADOW FEES
+ * ============================================================================
+ *
+ * Upgrade rehearsal for fee logic. The admin stores a candidate
+ * `FeeStrategy` in the namespace's `ShadowFees` account; every
+ * `transfer_tokens` that passes the account then prices itself under both
+ * strategies. The live fee is charged as usual, while the candidate's fee
+ * only accumulates in the shadow totals and a `ShadowFeeEvent`, so a change
+ * can be validated on real traffic before `set_fee_strategy` flips it.
+ *
+ * Rehearsal is only compiled with the `shadow-fees` feature. Other builds
+ * accept the account and ignore it, so clients need no change between the
+ * two, and `init_shadow_fees` always fails.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::math;
+use crate::{fee_for, CustomError, FeeInputs, FeeStrategy, GlobalConfig, InstructionTag};
+
+/// Candidate fee strategy and how it compares to the live one
+#[account]
+pub struct ShadowFees {
+    pub config: Pubkey,         // Namespace config rehearsed in (32 bytes)
+    pub candidate: FeeStrategy, // Strategy being rehearsed (65 bytes)
+    pub transfers: u64,         // Transfers priced under both (8 bytes)
+    pub live_total: u64,        // Fees charged by the live strategy (8 bytes)
+    pub shadow_total: u64,      // Fees the candidate would have charged (8 bytes)
+    pub diverged: u64,          // Transfers where the two fees differ (8 bytes)
+    pub bump: u8,               // PDA bump seed (1 byte)
+}
+
+impl ShadowFees {
+    /// Space required for the account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // config: Pubkey
+        FeeStrategy::LEN +       // candidate: FeeStrategy
+        8 +                      // transfers: u64
+        8 +                      // live_total: u64
+        8 +                      // shadow_total: u64
+        8 +                      // diverged: u64
+        1; // bump: u8
+
+    /// Price `inputs` under the candidate and fold both fees into the totals,
+    /// returning the candidate's fee
+    pub fn rehearse(&mut self, inputs: &FeeInputs, live_fee: u64) -> Result<u64> {
+        let shadow_fee = fee_for(&self.candidate, inputs)?;
+        self.transfers = math::add(self.transfers, 1)?;
+        self.live_total = math::add(self.live_total, live_fee)?;
+        self.shadow_total = math::add(self.shadow_total, shadow_fee)?;
+        if shadow_fee != live_fee {
+            self.diverged = math::add(self.diverged, 1)?;
+        }
+        Ok(shadow_fee)
+    }
+
+    /// Replace the candidate and start the comparison over
+    pub fn reset(&mut self, candidate: FeeStrategy) {
+        self.candidate = candidate;
+        self.transfers = 0;
+        self.live_total = 0;
+        self.shadow_total = 0;
+        self.diverged = 0;
+    }
+}
+
+/// Context for creating the namespace's shadow fee account
+#[derive(Accounts)]
+pub struct InitShadowFees<'info> {
+    /// Account being created
+    #[account(
+        init,
+        payer = admin,
+        space = ShadowFees::LEN,
+        seeds = [b"shadow_fees", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub shadow_fees: Account<'info, ShadowFees>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for replacing the candidate strategy
+#[derive(Accounts)]
+pub struct SetShadowFeeStrategy<'info> {
+    /// Account being changed
+    #[account(mut, seeds = [b"shadow_fees", config.key().as_ref()], bump = shadow_fees.bump)]
+    pub shadow_fees: Account<'info, ShadowFees>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+}
+
+/// Event comparing a transfer's live fee with the candidate's
+#[event]
+pub struct ShadowFeeEvent {
+    pub amount: u64,         // Amount transferred
+    pub live_fee: u64,       // Fee actually charged
+    pub shadow_fee: u64,     // Fee the candidate would have charged
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when the candidate strategy is stored
+#[event]
+pub struct ShadowFeeStrategySetEvent {
+    pub candidate: FeeStrategy, // Strategy now rehearsed
+    pub tag: InstructionTag,    // Emitting instruction
+}
+
+/// Rehearse the candidate on a transfer when the shadow account is passed
+///
+/// A no-op in builds without the `shadow-fees` feature.
+pub fn rehearse_fee(
+    shadow_fees: Option<&mut Account<ShadowFees>>,
+    inputs: &FeeInputs,
+    live_fee: u64,
+    tag: InstructionTag,
+) -> Result<()> {
+    #[cfg(feature = "shadow-fees")]
+    if let Some(shadow_fees) = shadow_fees {
+        let shadow_fee = shadow_fees.rehearse(inputs, live_fee)?;
+        emit!(ShadowFeeEvent {
+            amount: inputs.amount,
+            live_fee,
+            shadow_fee,
+            tag,
+        });
+    }
+    #[cfg(not(feature = "shadow-fees"))]
+    let _ = (shadow_fees, inputs, live_fee, tag);
+    Ok(())
+}
+
+/// Create the shadow account rehearsing `candidate`
+#[cfg(feature = "shadow-fees")]
+pub fn handle_init_shadow_fees(ctx: Context<InitShadowFees>, candidate: FeeStrategy) -> Result<()> {
+    candidate.validate()?;
+
+    let shadow_fees = &mut ctx.accounts.shadow_fees;
+    shadow_fees.config = ctx.accounts.config.key();
+    shadow_fees.bump = ctx.bumps.shadow_fees;
+    shadow_fees.reset(candidate);
+
+    emit!(ShadowFeeStrategySetEvent {
+        candidate,
+        tag: InstructionTag::InitShadowFees,
+    });
+
+    msg!("Shadow fees initialized");
+    Ok(())
+}
+
+/// Shadow fees are compiled out of this build
+#[cfg(not(feature = "shadow-fees"))]
+pub fn handle_init_shadow_fees(
+    _ctx: Context<InitShadowFees>,
+    _candidate: FeeStrategy,
+) -> Result<()> {
+    err!(CustomError::ShadowFeesDisabled)
+}
+
+/// Rehearse `candidate` instead, discarding the totals gathered so far
+pub fn handle_set_shadow_fee_strategy(
+    ctx: Context<SetShadowFeeStrategy>,
+    candidate: FeeStrategy,
+) -> Result<()> {
+    candidate.validate()?;
+    ctx.accounts.shadow_fees.reset(candidate);
+
+    emit!(ShadowFeeStrategySetEvent {
+        candidate,
+        tag: InstructionTag::SetShadowFeeStrategy,
+    });
+
+    msg!("Shadow fee strategy replaced");
+    Ok(())
+}
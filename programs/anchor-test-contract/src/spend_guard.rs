@@ -0,0 +1,177 @@
+/*
+ * ============================================================================
+ * SPENDING GUARDS
+ * ============================================================================
+ *
+ * `begin_guard(max_outflow)` and `end_guard()` bracket the instructions of a
+ * transaction that compose with untrusted programs. `begin_guard` records the
+ * user's balance in a `SpendGuard` PDA (`[b"spend_guard", user]`);
+ * `end_guard` closes it and reverts the whole transaction if the balance fell
+ * by more than `max_outflow` in between.
+ *
+ * `begin_guard` checks through the instructions sysvar that an `end_guard`
+ * for the same guard follows later in the transaction, so a guard can never
+ * be left open. Outflow is the net decrease of the balance: inflows inside
+ * the bracket offset outflows.
+ *
+ * Both ends must be top-level instructions of the transaction, signed by the
+ * user's authority. A program invoked inside the bracket can therefore
+ * neither close the guard early through CPI nor open one of its own that
+ * the sysvar scan would mistake for the user's.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+
+use crate::{CustomError, GlobalConfig, UserAccount};
+
+/// Balance snapshot of an open spending guard
+#[account]
+pub struct SpendGuard {
+    pub opening_balance: u64, // User balance at `begin_guard` (8 bytes)
+    pub max_outflow: u64,     // Largest decrease `end_guard` accepts (8 bytes)
+    pub bump: u8,             // PDA bump seed (1 byte)
+}
+
+impl SpendGuard {
+    /// Space required for the guard including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        8 +                      // opening_balance: u64
+        8 +                      // max_outflow: u64
+        1; // bump: u8
+
+    /// Fail if `closing_balance` is more than `max_outflow` below the opening balance
+    pub fn check(&self, closing_balance: u64) -> Result<()> {
+        let outflow = self.opening_balance.saturating_sub(closing_balance);
+        require!(outflow <= self.max_outflow, CustomError::SpendGuardExceeded);
+        Ok(())
+    }
+}
+
+/// Context for opening a spending guard
+#[derive(Accounts)]
+pub struct BeginGuard<'info> {
+    /// Guard being opened
+    #[account(
+        init,
+        payer = authority,
+        space = SpendGuard::LEN,
+        seeds = [b"spend_guard", user.key().as_ref()], // One open guard per user
+        bump
+    )]
+    pub guard: Account<'info, SpendGuard>,
+
+    /// Guarded user account
+    #[account(
+        has_one = authority,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump,
+        constraint = user.is_current() @ CustomError::MigrationRequired
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Namespace config the user account belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Account authority, pays the guard's rent until `end_guard`
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Instructions sysvar, to find the closing `end_guard`
+    /// CHECK: address checked against the sysvar id
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for closing a spending guard
+#[derive(Accounts)]
+pub struct EndGuard<'info> {
+    /// Guard being closed, rent returned to the authority
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"spend_guard", user.key().as_ref()],
+        bump = guard.bump
+    )]
+    pub guard: Account<'info, SpendGuard>,
+
+    /// Guarded user account
+    #[account(
+        has_one = authority,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump,
+        constraint = user.is_current() @ CustomError::MigrationRequired
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Namespace config the user account belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Account authority, must sign and receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Open a guard allowing at most `max_outflow` to leave the user's balance
+pub fn handle_begin_guard(ctx: Context<BeginGuard>, max_outflow: u64) -> Result<()> {
+    require_top_level()?;
+    require_closing_instruction(
+        &ctx.accounts.instructions.to_account_info(),
+        &ctx.accounts.guard.key(),
+    )?;
+
+    let guard = &mut ctx.accounts.guard;
+    guard.opening_balance = ctx.accounts.user.balance;
+    guard.max_outflow = max_outflow;
+    guard.bump = ctx.bumps.guard;
+
+    msg!("Spending guard opened with max outflow {}", max_outflow);
+    Ok(())
+}
+
+/// Close the guard, reverting the transaction if the outflow exceeded its max
+pub fn handle_end_guard(ctx: Context<EndGuard>) -> Result<()> {
+    require_top_level()?;
+    ctx.accounts.guard.check(ctx.accounts.user.balance)?;
+
+    msg!("Spending guard closed");
+    Ok(())
+}
+
+/// Fail when invoked through CPI rather than by the transaction itself
+fn require_top_level() -> Result<()> {
+    require_eq!(
+        get_stack_height(),
+        TRANSACTION_LEVEL_STACK_HEIGHT,
+        CustomError::GuardNotTopLevel
+    );
+    Ok(())
+}
+
+/// Require an `end_guard` closing `guard` later in the current transaction
+fn require_closing_instruction(instructions: &AccountInfo, guard: &Pubkey) -> Result<()> {
+    let current = load_current_index_checked(instructions)? as usize;
+    for index in current + 1.. {
+        let Ok(ix) = load_instruction_at_checked(index, instructions) else {
+            break; // Past the last instruction
+        };
+        if ix.program_id == crate::ID
+            && ix
+                .data
+                .starts_with(&crate::instruction::EndGuard::DISCRIMINATOR)
+            && ix.accounts.first().map(|meta| meta.pubkey) == Some(*guard)
+        {
+            return Ok(());
+        }
+    }
+    err!(CustomError::GuardNotClosed)
+}
@@ -0,0 +1,158 @@
+/*
+ * ============================================================================
+ * STATUS STATE MACHINES
+ * ============================================================================
+ *
+ * Every stateful entity keeps its status transitions here as an explicit
+ * `transition(from, action) -> Result<to>` function. Handlers never assign a
+ * status directly; they ask the state machine for the next one, so an invalid
+ * transition cannot be introduced by editing a single handler.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::CustomError;
+
+/// Lifecycle of an auction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuctionStatus {
+    Open,      // Accepting bids or reveals (phase is derived from the clock)
+    Settled,   // Winner and price fixed; bids may be claimed
+    Cancelled, // Withdrawn by the seller before any bid
+}
+
+/// Events that move an auction between statuses
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuctionAction {
+    Settle, // Reveal window has ended
+    Cancel, // Seller withdraws an auction without bids
+}
+
+impl AuctionStatus {
+    pub const ALL: [AuctionStatus; 3] = [
+        AuctionStatus::Open,
+        AuctionStatus::Settled,
+        AuctionStatus::Cancelled,
+    ];
+}
+
+impl AuctionAction {
+    pub const ALL: [AuctionAction; 2] = [AuctionAction::Settle, AuctionAction::Cancel];
+}
+
+/// Next auction status after `action`, or an error if the move is not allowed
+pub fn auction_transition(from: AuctionStatus, action: AuctionAction) -> Result<AuctionStatus> {
+    use AuctionAction::*;
+    use AuctionStatus::*;
+
+    match (from, action) {
+        (Open, Settle) => Ok(Settled),
+        (Open, Cancel) => Ok(Cancelled),
+        (Settled | Cancelled, Settle | Cancel) => err!(CustomError::InvalidStatusTransition),
+    }
+}
+
+/// Lifecycle of an invoice; every status past `Issued` closes the account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvoiceStatus {
+    Pending,  // Allocated by the crank, not yet issued
+    Issued,   // Owed by the payer; may be listed and sold meanwhile
+    Paid,     // Paid to its owner
+    Rejected, // Refused by the payer, cancelling the template
+    Expired,  // Retired unpaid after the payment window
+}
+
+/// Events that move an invoice between statuses
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvoiceAction {
+    Issue,  // Crank issues the template's next invoice
+    Pay,    // Payer pays the owner
+    Reject, // Payer refuses it
+    Expire, // Payment window has passed
+}
+
+impl InvoiceStatus {
+    pub const ALL: [InvoiceStatus; 5] = [
+        InvoiceStatus::Pending,
+        InvoiceStatus::Issued,
+        InvoiceStatus::Paid,
+        InvoiceStatus::Rejected,
+        InvoiceStatus::Expired,
+    ];
+}
+
+impl InvoiceAction {
+    pub const ALL: [InvoiceAction; 4] = [
+        InvoiceAction::Issue,
+        InvoiceAction::Pay,
+        InvoiceAction::Reject,
+        InvoiceAction::Expire,
+    ];
+}
+
+/// Next invoice status after `action`, or an error if the move is not allowed
+pub fn invoice_transition(from: InvoiceStatus, action: InvoiceAction) -> Result<InvoiceStatus> {
+    use InvoiceAction::*;
+    use InvoiceStatus::*;
+
+    match (from, action) {
+        (Pending, Issue) => Ok(Issued),
+        (Issued, Pay) => Ok(Paid),
+        (Issued, Reject) => Ok(Rejected),
+        (Issued, Expire) => Ok(Expired),
+        (Pending, Pay | Reject | Expire)
+        | (Issued, Issue)
+        | (Paid | Rejected | Expired, Issue | Pay | Reject | Expire) => {
+            err!(CustomError::InvalidStatusTransition)
+        }
+    }
+}
+
+/// Lifecycle of an escrow; every status past `Locked` closes the account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscrowStatus {
+    Locked,    // Funds held for the beneficiary
+    Claimed,   // Released to the beneficiary once unlocked
+    Cancelled, // Refunded to the creator before unlocking
+    Expired,   // Refunded to the creator after the claim window
+}
+
+/// Events that move an escrow between statuses
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscrowAction {
+    Claim,  // Beneficiary claims the unlocked funds
+    Cancel, // Creator takes back still-locked funds
+    Expire, // Claim window has passed
+}
+
+impl EscrowStatus {
+    pub const ALL: [EscrowStatus; 4] = [
+        EscrowStatus::Locked,
+        EscrowStatus::Claimed,
+        EscrowStatus::Cancelled,
+        EscrowStatus::Expired,
+    ];
+}
+
+impl EscrowAction {
+    pub const ALL: [EscrowAction; 3] = [
+        EscrowAction::Claim,
+        EscrowAction::Cancel,
+        EscrowAction::Expire,
+    ];
+}
+
+/// Next escrow status after `action`, or an error if the move is not allowed
+pub fn escrow_transition(from: EscrowStatus, action: EscrowAction) -> Result<EscrowStatus> {
+    use EscrowAction::*;
+    use EscrowStatus::*;
+
+    match (from, action) {
+        (Locked, Claim) => Ok(Claimed),
+        (Locked, Cancel) => Ok(Cancelled),
+        (Locked, Expire) => Ok(Expired),
+        (Claimed | Cancelled | Expired, Claim | Cancel | Expire) => {
+            err!(CustomError::InvalidStatusTransition)
+        }
+    }
+}
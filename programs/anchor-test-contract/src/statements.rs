@@ -0,0 +1,163 @@
+/*
+ * ============================================================================
+ * ACCOUNT STATEMENTS
+ * ============================================================================
+ *
+ * `generate_statement(epoch)` summarizes a user account's activity over one
+ * statement period of `STATEMENT_PERIOD` (30 days), numbered from the Unix
+ * epoch. Solana epochs are not used: `EpochStats` only knows the current
+ * one, and activity entries are timestamped, not tagged with an epoch.
+ *
+ * The summary is built from the user's `ActivityLog`, which every balance
+ * change appends to, so it only covers what the log still holds. When the
+ * period started before the log was opened, or older entries of the period
+ * have been overwritten, the statement says so with `complete` unset, and
+ * the accountant falls back to an indexer for that period. Deposits,
+ * withdrawals and treasury payouts count as sent or received like
+ * transfers. Only periods that have ended can be summarized.
+ *
+ * The program returns the `AccountStatement` as return data, which the
+ * runtime tags with the program id, and emits it with its digest in a
+ * `StatementGeneratedEvent`. Anyone can generate a statement, because
+ * everything in it is already public on chain.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::math;
+use crate::{
+    ActivityKind, ActivityLog, CustomError, GlobalConfig, InstructionTag, UserAccount,
+    STATEMENT_PERIOD,
+};
+
+/// Domain prefix hashed before every statement, versioned with the format
+pub const STATEMENT_DOMAIN: &[u8] = b"anchor-test-contract:statement:v1\n";
+
+/// Activity of one user account over one statement period
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct AccountStatement {
+    pub user: Pubkey,        // User account summarized
+    pub epoch: u64,          // Statement period index
+    pub period_start: i64,   // First second of the period
+    pub period_end: i64,     // First second after the period
+    pub sent: u64,           // Total sent, withdrawals included
+    pub sent_count: u32,     // Outgoing transfers
+    pub received: u64,       // Total received, deposits included
+    pub received_count: u32, // Incoming transfers
+    pub other_count: u32,    // Profile updates and deactivations
+    pub complete: bool,      // False when the log no longer covers the period
+}
+
+impl AccountStatement {
+    /// Summarize `log`'s entries falling in period `epoch`
+    pub fn from_log(log: &ActivityLog, epoch: u64) -> Result<Self> {
+        let period = STATEMENT_PERIOD.get();
+        let period_start = i64::try_from(epoch)
+            .ok()
+            .and_then(|epoch| epoch.checked_mul(period))
+            .ok_or(CustomError::MathOverflow)?;
+        let mut statement = Self {
+            user: log.user,
+            epoch,
+            period_start,
+            period_end: STATEMENT_PERIOD.after(period_start),
+            ..Self::default()
+        };
+
+        for entry in log.recent() {
+            if entry.timestamp < statement.period_start || entry.timestamp >= statement.period_end {
+                continue;
+            }
+            match ActivityKind::from_u8(entry.kind) {
+                Some(ActivityKind::Sent) => {
+                    statement.sent = math::add(statement.sent, entry.amount)?;
+                    statement.sent_count += 1;
+                }
+                Some(ActivityKind::Received) => {
+                    statement.received = math::add(statement.received, entry.amount)?;
+                    statement.received_count += 1;
+                }
+                Some(ActivityKind::Opened) | None => {} // Log creation or empty slot
+                Some(_) => statement.other_count += 1,
+            }
+        }
+
+        statement.complete = log.covers_since(statement.period_start);
+        Ok(statement)
+    }
+
+    /// Digest committing to every field, for accountants to reference
+    pub fn digest(&self) -> [u8; 32] {
+        hashv(&[
+            STATEMENT_DOMAIN,
+            self.user.as_ref(),
+            &self.epoch.to_le_bytes(),
+            &self.period_start.to_le_bytes(),
+            &self.period_end.to_le_bytes(),
+            &self.sent.to_le_bytes(),
+            &self.sent_count.to_le_bytes(),
+            &self.received.to_le_bytes(),
+            &self.received_count.to_le_bytes(),
+            &self.other_count.to_le_bytes(),
+            &[self.complete as u8],
+        ])
+        .to_bytes()
+    }
+}
+
+/// Context for generating a user account's statement
+#[derive(Accounts)]
+pub struct GenerateStatement<'info> {
+    /// User account summarized
+    #[account(
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// The user's activity log, the statement's source
+    #[account(seeds = [b"activity_log", user.key().as_ref()], bump)]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    /// Namespace config the user belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event carrying a generated statement and its digest
+#[event]
+pub struct StatementGeneratedEvent {
+    pub statement: AccountStatement, // Summarized activity
+    pub digest: [u8; 32],            // `AccountStatement::digest`
+    pub tag: InstructionTag,         // Emitting instruction
+}
+
+/// Summarize the user's activity over the ended period `epoch`
+pub fn handle_generate_statement(
+    ctx: Context<GenerateStatement>,
+    epoch: u64,
+) -> Result<AccountStatement> {
+    let log = ctx.accounts.activity_log.load()?;
+    let statement = AccountStatement::from_log(&log, epoch)?;
+    require!(
+        statement.period_end <= Clock::get()?.unix_timestamp,
+        CustomError::StatementPeriodOpen
+    );
+
+    emit!(StatementGeneratedEvent {
+        digest: statement.digest(),
+        statement: statement.clone(),
+        tag: InstructionTag::GenerateStatement,
+    });
+
+    msg!(
+        "Statement {} for {}: sent {}, received {}",
+        epoch,
+        statement.user,
+        statement.sent,
+        statement.received
+    );
+    Ok(statement)
+}
@@ -0,0 +1,138 @@
+/*
+ * ============================================================================
+ * LAMPORT DUST SWEEPER
+ * ============================================================================
+ *
+ * Program-owned PDAs accumulate lamports beyond rent exemption from direct
+ * transfers. `sweep_excess_lamports` is a permissionless crank that moves
+ * such dust to the namespace treasury (`[b"treasury", config]`, a plain
+ * lamport-holding address). Like any system account, the treasury must
+ * reach rent exemption with its first deposit.
+ *
+ * Only allowlisted account types can be swept, and each is re-derived from
+ * its own data under the namespace config, so a crank can neither drain a
+ * foreign namespace nor touch accounts whose lamports are owed to someone
+ * (bid escrows, for example, are not on the list). Swept types hold nothing
+ * but rent, so everything above the rent-exempt minimum is excess.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::math;
+use crate::{
+    derive_address, Auction, Commitment, CustomError, GlobalConfig, InstructionTag, IssuerRecord,
+    LamportAmount, MintConfig, NamespaceMetadata, UserAccount,
+};
+
+/// Seed of the namespace treasury that receives swept lamports
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Deserialize `data` as `T` and return its expected PDA under `config`
+fn expected_address<T: AccountDeserialize>(
+    data: &[u8],
+    seeds: impl FnOnce(&T) -> Vec<Vec<u8>>,
+) -> Result<Pubkey> {
+    let account = T::try_deserialize(&mut &data[..])?;
+    Ok(derive_address(&seeds(&account)))
+}
+
+/// PDA an allowlisted account must live at, or `NotSweepable`
+pub fn sweepable_address(config: &Pubkey, data: &[u8]) -> Result<Pubkey> {
+    let config = config.to_bytes().to_vec();
+    let discriminator: [u8; 8] = data
+        .get(..8)
+        .and_then(|d| d.try_into().ok())
+        .ok_or(CustomError::NotSweepable)?;
+
+    match discriminator {
+        d if d == UserAccount::DISCRIMINATOR => expected_address::<UserAccount>(data, |a| {
+            vec![b"user".to_vec(), config, a.authority.to_bytes().to_vec()]
+        }),
+        d if d == MintConfig::DISCRIMINATOR => expected_address::<MintConfig>(data, |a| {
+            vec![b"mint_config".to_vec(), config, a.mint.to_bytes().to_vec()]
+        }),
+        d if d == IssuerRecord::DISCRIMINATOR => expected_address::<IssuerRecord>(data, |a| {
+            vec![b"issuer".to_vec(), config, a.issuer.to_bytes().to_vec()]
+        }),
+        d if d == Commitment::DISCRIMINATOR => expected_address::<Commitment>(data, |a| {
+            vec![
+                b"commitment".to_vec(),
+                config,
+                a.owner.to_bytes().to_vec(),
+                a.domain.to_le_bytes().to_vec(),
+            ]
+        }),
+        d if d == Auction::DISCRIMINATOR => expected_address::<Auction>(data, |a| {
+            vec![
+                b"auction".to_vec(),
+                config,
+                a.seller.to_bytes().to_vec(),
+                a.auction_id.to_le_bytes().to_vec(),
+            ]
+        }),
+        d if d == NamespaceMetadata::DISCRIMINATOR => {
+            expected_address::<NamespaceMetadata>(data, |_| {
+                vec![b"namespace_metadata".to_vec(), config]
+            })
+        }
+        _ => err!(CustomError::NotSweepable),
+    }
+}
+
+/// Context for sweeping excess lamports from a program-owned PDA
+#[derive(Accounts)]
+pub struct SweepExcessLamports<'info> {
+    /// Account holding the dust
+    /// CHECK: owner, type and address are verified in the handler
+    #[account(mut, owner = crate::ID @ CustomError::NotSweepable)]
+    pub account: UncheckedAccount<'info>,
+
+    /// Namespace config the account must belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace treasury receiving the dust
+    /// CHECK: seeds are verified; only receives lamports
+    #[account(mut, seeds = [TREASURY_SEED, config.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+/// Event emitted when dust is swept to the treasury
+#[event]
+pub struct LamportsSweptEvent {
+    pub account: Pubkey,     // Swept account
+    pub treasury: Pubkey,    // Receiving treasury
+    pub amount: u64,         // LamportAmount moved
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Move lamports above rent exemption from an allowlisted PDA to the treasury
+pub fn handle_sweep_excess_lamports(ctx: Context<SweepExcessLamports>) -> Result<()> {
+    let info = ctx.accounts.account.to_account_info();
+    {
+        let data = info.try_borrow_data()?;
+        let expected = sweepable_address(&ctx.accounts.config.key(), &data)?;
+        require_keys_eq!(expected, info.key(), CustomError::NotSweepable);
+    }
+
+    let rent_exempt = Rent::get()?.minimum_balance(info.data_len());
+    let amount = info.lamports().saturating_sub(rent_exempt);
+    if amount == 0 {
+        msg!("Nothing to sweep from {}", info.key());
+        return Ok(());
+    }
+
+    let treasury = ctx.accounts.treasury.to_account_info();
+    math::move_lamports(&info, &treasury, LamportAmount(amount))?;
+
+    emit!(LamportsSweptEvent {
+        account: info.key(),
+        treasury: treasury.key(),
+        amount,
+        tag: InstructionTag::SweepExcessLamports,
+    });
+
+    msg!("Swept {} lamports from {}", amount, info.key());
+    Ok(())
+}
@@ -0,0 +1,179 @@
+/*
+ * ============================================================================
+ * VOTE TALLY STRATEGIES
+ * ============================================================================
+ *
+ * Pure tally functions behind a `TallyStrategy` enum, selected when a
+ * proposal is created:
+ * - OneAccountOneVote: every eligible ballot counts once
+ * - StakeWeighted: ballots count with their full weight
+ * - Quadratic: ballots count with the integer square root of their weight,
+ *   and only ballots backed by at least `sybil_bond` are eligible
+ *
+ * The functions hold no account state so they can be checked in isolation;
+ * the checks at the bottom of this file run at compile time.
+ */
+
+use anchor_lang::prelude::*;
+
+/// How ballots are weighted when a proposal is tallied
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TallyStrategy {
+    OneAccountOneVote,             // 1 account = 1 vote
+    StakeWeighted,                 // Weight = stake
+    Quadratic { sybil_bond: u64 }, // Weight = sqrt(stake), bond required
+}
+
+/// A single voter's ballot as seen by the tally
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ballot {
+    pub stake: u64,    // Voting stake backing the ballot
+    pub bond: u64,     // Sybil bond posted by the voter
+    pub support: bool, // True = for, false = against
+}
+
+/// Aggregated result of a tally
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Tally {
+    pub yes: u64,      // Weighted votes in favor
+    pub no: u64,       // Weighted votes against
+    pub counted: u32,  // Eligible ballots counted
+    pub excluded: u32, // Ballots excluded (e.g. missing sybil bond)
+}
+
+/// Integer square root, rounded down
+pub const fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // Binary search for the largest root with root * root <= value
+    let mut low: u64 = 1;
+    let mut high: u64 = 1 << 32;
+    while low + 1 < high {
+        let mid = low + (high - low) / 2;
+        if (mid as u128) * (mid as u128) <= value as u128 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Weight of one ballot under `strategy`, or None if it is not eligible
+pub const fn ballot_weight(strategy: TallyStrategy, ballot: &Ballot) -> Option<u64> {
+    match strategy {
+        TallyStrategy::OneAccountOneVote => Some(1),
+        TallyStrategy::StakeWeighted => Some(ballot.stake),
+        TallyStrategy::Quadratic { sybil_bond } => {
+            if ballot.bond < sybil_bond {
+                None // Unbonded accounts cannot split stake for extra weight
+            } else {
+                Some(isqrt(ballot.stake))
+            }
+        }
+    }
+}
+
+/// Tally `ballots` under `strategy`; returns None on overflow
+pub const fn tally(strategy: TallyStrategy, ballots: &[Ballot]) -> Option<Tally> {
+    let mut result = Tally {
+        yes: 0,
+        no: 0,
+        counted: 0,
+        excluded: 0,
+    };
+    let mut i = 0;
+    while i < ballots.len() {
+        let ballot = &ballots[i];
+        match ballot_weight(strategy, ballot) {
+            Some(weight) => {
+                if ballot.support {
+                    result.yes = match result.yes.checked_add(weight) {
+                        Some(total) => total,
+                        None => return None,
+                    };
+                } else {
+                    result.no = match result.no.checked_add(weight) {
+                        Some(total) => total,
+                        None => return None,
+                    };
+                }
+                result.counted += 1;
+            }
+            None => result.excluded += 1,
+        }
+        i += 1;
+    }
+    Some(result)
+}
+
+/*
+ * Compile-time checks for each strategy.
+ * One whale (stake 100) against three small holders (stake 9 each, one
+ * without a bond) shows how the modes differ.
+ */
+
+const SAMPLE_BALLOTS: [Ballot; 4] = [
+    Ballot {
+        stake: 100,
+        bond: 10,
+        support: true,
+    },
+    Ballot {
+        stake: 9,
+        bond: 10,
+        support: false,
+    },
+    Ballot {
+        stake: 9,
+        bond: 10,
+        support: false,
+    },
+    Ballot {
+        stake: 9,
+        bond: 0,
+        support: false,
+    },
+];
+
+const fn sample_tally(strategy: TallyStrategy) -> Tally {
+    match tally(strategy, &SAMPLE_BALLOTS) {
+        Some(result) => result,
+        None => panic!("sample tally overflowed"),
+    }
+}
+
+const _: () = assert!(isqrt(0) == 0 && isqrt(1) == 1 && isqrt(15) == 3 && isqrt(16) == 4);
+const _: () = assert!(isqrt(u64::MAX) == u32::MAX as u64);
+
+// 1 account = 1 vote: the three small holders win 3 to 1
+const ONE_VOTE: Tally = sample_tally(TallyStrategy::OneAccountOneVote);
+const _: () = assert!(ONE_VOTE.yes == 1 && ONE_VOTE.no == 3 && ONE_VOTE.excluded == 0);
+
+// Stake-weighted: the whale wins 100 to 27
+const STAKE: Tally = sample_tally(TallyStrategy::StakeWeighted);
+const _: () = assert!(STAKE.yes == 100 && STAKE.no == 27);
+
+// Quadratic with a bond of 10: sqrt(100) = 10 against 3 + 3, unbonded ballot excluded
+const QUADRATIC: Tally = sample_tally(TallyStrategy::Quadratic { sybil_bond: 10 });
+const _: () = assert!(QUADRATIC.yes == 10 && QUADRATIC.no == 6);
+const _: () = assert!(QUADRATIC.counted == 3 && QUADRATIC.excluded == 1);
+
+// Overflow is reported rather than wrapped
+const _: () = assert!(tally(
+    TallyStrategy::StakeWeighted,
+    &[
+        Ballot {
+            stake: u64::MAX,
+            bond: 0,
+            support: true
+        },
+        Ballot {
+            stake: 1,
+            bond: 0,
+            support: true
+        },
+    ]
+)
+.is_none());
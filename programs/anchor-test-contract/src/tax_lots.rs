@@ -0,0 +1,494 @@
+/*
+ * ============================================================================
+ * TAX LOTS
+ * ============================================================================
+ *
+ * Users reporting under rules that depend on holding periods can track the
+ * lots their balance was acquired in. A `TaxLotBook` (`[b"tax_lots", user]`)
+ * holds the consumption method and the range of live pages; the lots
+ * themselves sit in `TaxLotPage`s (`[b"tax_lot_page", user, index]`) of
+ * `TAX_LOTS_PER_PAGE` lots each, created by the authority as they fill up.
+ *
+ * `transfer_tokens` keeps the books of whichever side passes one:
+ * - the receiver's newest page gains a lot of the amount received, dated now,
+ *   with the sender as its source;
+ * - the sender's lots are consumed first-in-first-out or last-in-first-out,
+ *   per the book's `LotMethod`, and every lot consumed is reported with its
+ *   holding period in a `TaxLotsRealizedEvent`.
+ * The pages involved go in `remaining_accounts` alongside the guardian
+ * signers, in any order. Whatever the sender's lots do not cover (balance received
+ * before tracking began, or through other instructions) is reported as
+ * untracked rather than failing the transfer.
+ *
+ * FIFO consumption empties the oldest pages first; the book then moves past
+ * them and the authority can close them to reclaim their rent.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    CustomError, GlobalConfig, InstructionTag, RemainingAccountsValidator, UserAccount,
+    TAX_LOTS_PER_PAGE,
+};
+
+/// Order in which outgoing transfers consume lots
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LotMethod {
+    Fifo, // Oldest lots first
+    Lifo, // Newest lots first
+}
+
+/// Balance acquired in one incoming transfer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TaxLot {
+    pub amount: u64,      // Amount still held from this lot (8 bytes)
+    pub acquired_at: i64, // When it was received (8 bytes)
+    pub source: Pubkey,   // User account it came from (32 bytes)
+}
+
+impl TaxLot {
+    /// Serialized size of a lot entry
+    pub const LEN: usize = 8 + 8 + 32;
+}
+
+/// Part of a lot consumed by an outgoing transfer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LotDisposal {
+    pub amount: u64,         // Amount taken from the lot
+    pub acquired_at: i64,    // When the lot was received
+    pub source: Pubkey,      // User account the lot came from
+    pub holding_period: i64, // Seconds between acquisition and disposal
+}
+
+/// A user's lot tracking settings and live page range
+#[account]
+pub struct TaxLotBook {
+    pub user: Pubkey,      // Owning user account (32 bytes)
+    pub method: LotMethod, // Consumption order (1 byte)
+    pub first_page: u32,   // Oldest live page (4 bytes)
+    pub next_page: u32,    // Index the next page is created at (4 bytes)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl TaxLotBook {
+    /// Space required for the book including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        1 +                      // method: LotMethod
+        4 +                      // first_page: u32
+        4 +                      // next_page: u32
+        1; // bump: u8
+
+    /// Index of the page new lots go to, if any page exists
+    pub fn newest_page(&self) -> Option<u32> {
+        self.next_page
+            .checked_sub(1)
+            .filter(|_| self.next_page > self.first_page)
+    }
+
+    /// Record `lot` on `page`, which must be the newest page
+    pub fn acquire(&self, page: &mut TaxLotPage, lot: TaxLot) -> Result<()> {
+        require_keys_eq!(page.user, self.user, CustomError::TaxLotMismatch);
+        require!(
+            Some(page.index) == self.newest_page(),
+            CustomError::TaxLotPagesMissing
+        );
+        require!(
+            page.lots.len() < TAX_LOTS_PER_PAGE,
+            CustomError::TaxLotPageFull
+        );
+        page.lots.push(lot);
+        Ok(())
+    }
+
+    /// Consume `amount` from `pages`, given in consumption order, at `now`;
+    /// returns the disposals and the amount no lot covered
+    pub fn dispose(
+        &mut self,
+        pages: &mut [TaxLotPage],
+        amount: u64,
+        now: i64,
+    ) -> Result<(Vec<LotDisposal>, u64)> {
+        let mut remaining = amount;
+        let mut disposals = Vec::new();
+        let mut visited = 0;
+        let mut expected = match self.method {
+            LotMethod::Fifo => Some(self.first_page).filter(|_| self.newest_page().is_some()),
+            LotMethod::Lifo => self.newest_page(),
+        };
+
+        for page in pages.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            require_keys_eq!(page.user, self.user, CustomError::TaxLotMismatch);
+            require!(
+                Some(page.index) == expected,
+                CustomError::TaxLotPagesMissing
+            );
+            remaining = page.consume(self.method, remaining, now, &mut disposals);
+            expected = self.page_after(page.index);
+            visited += 1;
+        }
+        // Lots may remain on pages that were not passed
+        require!(
+            remaining == 0 || expected.is_none(),
+            CustomError::TaxLotPagesMissing
+        );
+
+        if self.method == LotMethod::Fifo {
+            let newest = self.newest_page().unwrap_or(self.first_page);
+            let emptied = pages[..visited]
+                .iter()
+                .take_while(|page| page.lots.is_empty() && page.index < newest)
+                .count() as u32;
+            self.first_page += emptied; // The newest page stays live for new lots
+        }
+        Ok((disposals, remaining))
+    }
+
+    /// Page consumed after `index`, or None past the last live page
+    fn page_after(&self, index: u32) -> Option<u32> {
+        match self.method {
+            LotMethod::Fifo => Some(index + 1).filter(|next| *next < self.next_page),
+            LotMethod::Lifo => index.checked_sub(1).filter(|prev| *prev >= self.first_page),
+        }
+    }
+}
+
+/// One page of a user's lots, oldest first
+#[account]
+pub struct TaxLotPage {
+    pub user: Pubkey,      // Owning user account (32 bytes)
+    pub index: u32,        // Page index (4 bytes)
+    pub lots: Vec<TaxLot>, // Lots held (4 + 48 * N bytes)
+    pub bump: u8,          // PDA bump seed (1 byte)
+}
+
+impl TaxLotPage {
+    /// Space required for a full page including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        4 +                      // index: u32
+        4 + TaxLot::LEN * TAX_LOTS_PER_PAGE + // lots: Vec<TaxLot>
+        1; // bump: u8
+
+    /// Consume up to `amount` of this page's lots in `method` order, noting
+    /// each in `disposals`; returns what is left to consume
+    fn consume(
+        &mut self,
+        method: LotMethod,
+        mut amount: u64,
+        now: i64,
+        disposals: &mut Vec<LotDisposal>,
+    ) -> u64 {
+        while amount > 0 {
+            let slot = match method {
+                LotMethod::Fifo => 0,
+                LotMethod::Lifo => match self.lots.len().checked_sub(1) {
+                    Some(last) => last,
+                    None => break,
+                },
+            };
+            let Some(lot) = self.lots.get_mut(slot) else {
+                break; // Page exhausted
+            };
+            let taken = lot.amount.min(amount);
+            lot.amount -= taken;
+            amount -= taken;
+            disposals.push(LotDisposal {
+                amount: taken,
+                acquired_at: lot.acquired_at,
+                source: lot.source,
+                holding_period: now.saturating_sub(lot.acquired_at),
+            });
+            if lot.amount == 0 {
+                self.lots.remove(slot); // Keeps the page oldest first
+            }
+        }
+        amount
+    }
+}
+
+/// Lot-relevant facts of one transfer
+pub struct LotTransfer {
+    pub sender: Pubkey,   // Sender's user account
+    pub receiver: Pubkey, // Receiver's user account
+    pub sent: u64,        // Debited from the sender
+    pub received: u64,    // Credited to the receiver, after the fee
+    pub timestamp: i64,   // When the transfer happened
+}
+
+/// Update the books of whichever side of `transfer` passed one, taking their
+/// pages from the non-signer accounts of `remaining`
+pub fn track_tax_lots(
+    remaining: &[AccountInfo],
+    sender_book: Option<&mut Account<TaxLotBook>>,
+    receiver_book: Option<&mut Account<TaxLotBook>>,
+    transfer: LotTransfer,
+    tag: InstructionTag,
+) -> Result<()> {
+    if sender_book.is_none() && receiver_book.is_none() {
+        return Ok(()); // Neither side tracks lots
+    }
+
+    let mut validator = RemainingAccountsValidator::default();
+    let mut sender_pages = Vec::new();
+    let mut receiver_pages = Vec::new();
+    for info in remaining.iter().filter(|info| !info.is_signer) {
+        let page: TaxLotPage = validator.load(info, |page: &TaxLotPage| {
+            vec![
+                b"tax_lot_page".to_vec(),
+                page.user.to_bytes().to_vec(),
+                page.index.to_le_bytes().to_vec(),
+            ]
+        })?;
+        match page.user {
+            user if user == transfer.sender => sender_pages.push((page, info)),
+            user if user == transfer.receiver => receiver_pages.push((page, info)),
+            _ => return err!(CustomError::TaxLotMismatch),
+        }
+    }
+
+    if let Some(book) = sender_book {
+        require_keys_eq!(book.user, transfer.sender, CustomError::TaxLotMismatch);
+        match book.method {
+            LotMethod::Fifo => sender_pages.sort_by_key(|(page, _)| page.index),
+            LotMethod::Lifo => sender_pages.sort_by_key(|(page, _)| std::cmp::Reverse(page.index)),
+        }
+        let (mut pages, infos): (Vec<_>, Vec<_>) = sender_pages.into_iter().unzip();
+        let (disposals, untracked) = book.dispose(&mut pages, transfer.sent, transfer.timestamp)?;
+        for (page, info) in pages.iter().zip(infos) {
+            page.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+
+        emit!(TaxLotsRealizedEvent {
+            user: transfer.sender,
+            amount: transfer.sent,
+            disposals,
+            untracked,
+            timestamp: transfer.timestamp,
+            tag,
+        });
+    }
+
+    if let Some(book) = receiver_book {
+        require_keys_eq!(book.user, transfer.receiver, CustomError::TaxLotMismatch);
+        if transfer.received > 0 {
+            let (page, info) = receiver_pages
+                .iter_mut()
+                .find(|(page, _)| Some(page.index) == book.newest_page())
+                .ok_or(CustomError::TaxLotPagesMissing)?;
+            book.acquire(
+                page,
+                TaxLot {
+                    amount: transfer.received,
+                    acquired_at: transfer.timestamp,
+                    source: transfer.sender,
+                },
+            )?;
+            page.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+    }
+    Ok(())
+}
+
+/// Context for starting lot tracking on the caller's account
+#[derive(Accounts)]
+pub struct InitTaxLots<'info> {
+    /// Book being created
+    #[account(
+        init,
+        payer = authority,
+        space = TaxLotBook::LEN,
+        seeds = [b"tax_lots", user.key().as_ref()], // One book per user
+        bump
+    )]
+    pub book: Account<'info, TaxLotBook>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for adding a page after the book's newest
+#[derive(Accounts)]
+pub struct AddTaxLotPage<'info> {
+    /// Book gaining the page
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [b"tax_lots", user.key().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Account<'info, TaxLotBook>,
+
+    /// Page being created
+    #[account(
+        init,
+        payer = authority,
+        space = TaxLotPage::LEN,
+        seeds = [b"tax_lot_page", user.key().as_ref(), book.next_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub page: Account<'info, TaxLotPage>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for changing the book's consumption method
+#[derive(Accounts)]
+pub struct SetLotMethod<'info> {
+    /// Book being changed
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [b"tax_lots", user.key().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Account<'info, TaxLotBook>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for closing a page the book has moved past
+#[derive(Accounts)]
+pub struct CloseTaxLotPage<'info> {
+    /// Book the page belonged to
+    #[account(
+        has_one = user,
+        seeds = [b"tax_lots", user.key().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Account<'info, TaxLotBook>,
+
+    /// Emptied page being closed, rent returned to the authority
+    #[account(
+        mut,
+        has_one = user,
+        close = authority,
+        constraint = page.index < book.first_page && page.lots.is_empty() @ CustomError::TaxLotPageInUse,
+        seeds = [b"tax_lot_page", user.key().as_ref(), page.index.to_le_bytes().as_ref()],
+        bump = page.bump
+    )]
+    pub page: Account<'info, TaxLotPage>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, receives the page's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event reporting the lots an outgoing transfer consumed
+#[event]
+pub struct TaxLotsRealizedEvent {
+    pub user: Pubkey,                // Sender's user account
+    pub amount: u64,                 // Amount sent
+    pub disposals: Vec<LotDisposal>, // Lots consumed, in consumption order
+    pub untracked: u64,              // Part of `amount` no lot covered
+    pub timestamp: i64,              // When the transfer happened
+    pub tag: InstructionTag,         // Emitting instruction
+}
+
+/// Start tracking lots on the caller's account with `method`
+pub fn handle_init_tax_lots(ctx: Context<InitTaxLots>, method: LotMethod) -> Result<()> {
+    let book = &mut ctx.accounts.book;
+    book.user = ctx.accounts.user.key();
+    book.method = method;
+    book.first_page = 0;
+    book.next_page = 0;
+    book.bump = ctx.bumps.book;
+
+    msg!("Tax lot book created for {}", book.user);
+    Ok(())
+}
+
+/// Create the book's next page
+pub fn handle_add_tax_lot_page(ctx: Context<AddTaxLotPage>) -> Result<()> {
+    let book = &mut ctx.accounts.book;
+    let page = &mut ctx.accounts.page;
+    page.user = book.user;
+    page.index = book.next_page;
+    page.lots = Vec::new();
+    page.bump = ctx.bumps.page;
+    book.next_page = book
+        .next_page
+        .checked_add(1)
+        .ok_or(CustomError::MathOverflow)?;
+
+    msg!("Tax lot page {} created for {}", page.index, book.user);
+    Ok(())
+}
+
+/// Change the order lots are consumed in from now on
+pub fn handle_set_lot_method(ctx: Context<SetLotMethod>, method: LotMethod) -> Result<()> {
+    ctx.accounts.book.method = method;
+
+    msg!("Tax lot method set to {:?}", method);
+    Ok(())
+}
+
+/// Close an emptied page, refunding its rent
+pub fn handle_close_tax_lot_page(ctx: Context<CloseTaxLotPage>) -> Result<()> {
+    msg!("Tax lot page {} closed", ctx.accounts.page.index);
+    Ok(())
+}
@@ -0,0 +1,168 @@
+/*
+ * ============================================================================
+ * FEE TREASURY
+ * ============================================================================
+ *
+ * `transfer_tokens` charges the namespace's protocol fee (`GlobalConfig::
+ * fee_at`) on every transfer: the sender is debited the full amount, the
+ * receiver credited the rest, and the fee accrues in the `FeeTreasury` PDA
+ * (`[b"fee_treasury", config]`). The admin claims collected fees with
+ * `withdraw_treasury`, which credits them to a user account of the
+ * namespace; from there they can leave through `withdraw_tokens`.
+ *
+ * Fees are internal units, like balances, and live in the ledger's Fees
+ * account while uncollected. The treasury is separate from the lamport
+ * treasury at `[b"treasury", config]`, which only receives swept rent.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::math;
+use crate::{
+    log_activity, ActivityEntry, ActivityKind, CustomError, GlobalConfig, InstructionTag, Ledger,
+    LedgerAccount, UserAccount,
+};
+
+/// Transfer fees collected in a namespace and not yet withdrawn
+#[account]
+pub struct FeeTreasury {
+    pub balance: u64,         // Fees available to withdraw (8 bytes)
+    pub total_collected: u64, // Fees collected since creation (8 bytes)
+    pub bump: u8,             // PDA bump seed (1 byte)
+}
+
+impl FeeTreasury {
+    /// Space required for the treasury including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        8 +                      // balance: u64
+        8 +                      // total_collected: u64
+        1; // bump: u8
+
+    /// Accrue a collected `fee`
+    pub fn collect(&mut self, fee: u64) -> Result<()> {
+        self.balance = math::add(self.balance, fee)?;
+        self.total_collected = math::add(self.total_collected, fee)?;
+        Ok(())
+    }
+}
+
+/// Context for creating the fee treasury
+#[derive(Accounts)]
+pub struct InitializeFeeTreasury<'info> {
+    /// Treasury being created
+    #[account(
+        init,
+        payer = admin,
+        space = FeeTreasury::LEN,
+        seeds = [b"fee_treasury", config.key().as_ref()], // Singleton PDA
+        bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for claiming collected fees
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    /// Treasury being drawn down
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// User account credited with the fees
+    #[account(
+        mut,
+        constraint = recipient.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), recipient.authority.as_ref()],
+        bump
+    )]
+    pub recipient: Account<'info, UserAccount>,
+
+    /// Double-entry ledger receiving the withdrawal
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+
+    /// Recipient's activity log address, appended to when the recipient keeps one
+    /// CHECK: address is the recipient's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", recipient.key().as_ref()], bump)]
+    pub recipient_activity: UncheckedAccount<'info>,
+}
+
+/// Event emitted when collected fees are withdrawn
+#[event]
+pub struct TreasuryWithdrawnEvent {
+    pub recipient: Pubkey,   // Credited user account
+    pub amount: u64,         // Fees withdrawn
+    pub remaining: u64,      // Treasury balance afterwards
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Create the fee treasury
+pub fn handle_initialize_fee_treasury(ctx: Context<InitializeFeeTreasury>) -> Result<()> {
+    let treasury = &mut ctx.accounts.fee_treasury;
+    treasury.balance = 0;
+    treasury.total_collected = 0;
+    treasury.bump = ctx.bumps.fee_treasury;
+
+    msg!("Fee treasury initialized");
+    Ok(())
+}
+
+/// Credit `amount` of collected fees to the recipient's balance
+pub fn handle_withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::InvalidAmount);
+
+    let treasury = &mut ctx.accounts.fee_treasury;
+    treasury.balance = treasury
+        .balance
+        .checked_sub(amount)
+        .ok_or(CustomError::InsufficientFunds)?;
+
+    let recipient = &mut ctx.accounts.recipient;
+    recipient.balance = math::add(recipient.balance, amount)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Fees, LedgerAccount::Users, amount)?; // Fees owed become user balance
+    let entry = ActivityEntry::new(
+        ActivityKind::Received,
+        InstructionTag::WithdrawTreasury,
+        Pubkey::default(), // Paid by the treasury
+        amount,
+        Clock::get()?.unix_timestamp,
+    );
+    log_activity(&ctx.accounts.recipient_activity, recipient.key(), entry)?;
+
+    emit!(TreasuryWithdrawnEvent {
+        recipient: recipient.key(),
+        amount,
+        remaining: treasury.balance,
+        tag: InstructionTag::WithdrawTreasury,
+    });
+
+    msg!("Withdrew {} in fees to {}", amount, recipient.authority);
+    Ok(())
+}
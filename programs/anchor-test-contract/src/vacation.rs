@@ -0,0 +1,82 @@
+/*
+ * ============================================================================
+ * SCHEDULED DEACTIVATION (VACATION MODE)
+ * ============================================================================
+ *
+ * Lets an authority block outbound transfers during a future time window.
+ * The window is evaluated against the Clock at transfer time, so the account
+ * restores itself once the window ends without any further transactions.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount,
+};
+
+/// Context for scheduling or clearing a vacation window
+#[derive(Accounts)]
+pub struct ScheduleDeactivation<'info> {
+    /// The user account receiving the schedule
+    #[account(
+        mut,                                     // Window fields will change
+        has_one = authority,                     // Verify ownership
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()], // Verify PDA
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>, // Must sign to schedule
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a vacation window is scheduled or cleared
+#[event]
+pub struct DeactivationScheduledEvent {
+    pub user: Pubkey,        // Affected user account
+    pub start: i64,          // Window start (0 when cleared)
+    pub end: i64,            // Window end (0 when cleared)
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Schedule a window `[start, end)` during which outbound transfers are blocked
+/// Passing `start == end == 0` clears any existing schedule
+pub fn handle_schedule_deactivation(
+    ctx: Context<ScheduleDeactivation>,
+    start: i64,
+    end: i64,
+) -> Result<()> {
+    require_instruction_enabled(
+        &ctx.accounts.config,
+        instruction_flags::SCHEDULE_DEACTIVATION,
+    )?;
+
+    let user = &mut ctx.accounts.user;
+
+    // A zeroed window cancels the schedule
+    if start != 0 || end != 0 {
+        let now = Clock::get()?.unix_timestamp;
+        require!(user.is_active(), CustomError::AccountInactive); // Must be active
+        require!(start < end, CustomError::InvalidVacationWindow); // Non-empty window
+        require!(end > now, CustomError::InvalidVacationWindow); // Must not be in the past
+    }
+
+    user.vacation_start = start;
+    user.vacation_end = end;
+
+    emit!(DeactivationScheduledEvent {
+        user: user.key(),
+        start,
+        end,
+        tag: InstructionTag::ScheduleDeactivation,
+    });
+
+    msg!("Vacation window set: {} - {}", start, end);
+    Ok(())
+}
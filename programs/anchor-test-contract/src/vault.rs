@@ -0,0 +1,537 @@
+/*
+ * ============================================================================
+ * TOKEN VAULTS
+ * ============================================================================
+ *
+ * Internal balances are backed by real SPL tokens. Each listed mint has a
+ * program-owned vault token account (`[b"vault", config, mint]`) whose
+ * authority is the namespace's vault authority PDA, so only this program can
+ * move tokens out of it.
+ *
+ * `deposit_tokens` CPIs a transfer from the user's token account into the
+ * vault and credits the internal equivalent; `withdraw_tokens` debits an
+ * internal amount and pays the raw equivalent back out, signing for the
+ * vault with the authority's seeds. Conversions follow `decimals`: both
+ * directions round down, so any dust stays in the vault. Withdrawals from
+ * `LARGE_WITHDRAWAL_THRESHOLD` must carry their operation digest.
+ *
+ * Internal balances are fungible across mints, but vaults are not: each
+ * user's `VaultPosition` (`[b"vault_position", user, mint]`, opened with
+ * `open_vault_position`) counts what they deposited of one mint and not yet
+ * withdrawn. A withdrawal debits both the balance and the position of the
+ * mint paid out, so a deposit of one mint can never be taken out as another
+ * and each vault only ever pays back what was put into it. Balance received
+ * through transfers stays internal.
+ *
+ * The vault is fully reserved: it holds every position in full and lends
+ * nothing out, so it never runs short of liquidity. Withdrawals are
+ * therefore all or nothing with no partial fills or remainder queue; what a
+ * user can take out is read from their position, not probed for. Nor does
+ * it take third-party liquidity: there is no shortfall for providers to
+ * cover and no withdrawal fee for them to share, and a pool any user could
+ * withdraw from would reopen the cross-mint drain positions close. For the
+ * same reason utilization is always zero, so fees follow the namespace's fee
+ * strategy rather than a utilization curve.
+ *
+ * The mint's `MintConfig` switches and cap apply, and every movement is
+ * posted to the ledger between Vault and Users.
+ *
+ * Nothing reads the vault's token amount to price anything: what users are
+ * owed is the booked `total_deposited`, whose raw value, rounded up, is the
+ * vault's accounted balance. Tokens sent to the vault directly sit above it
+ * and belong to no one until the admin `skim_donations` them, which books
+ * the surplus as fees in the `FeeTreasury` without moving any token.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::decimals::{self, InternalUnits, RawTokenUnits};
+use crate::guards::{self, Guard};
+use crate::math;
+use crate::{
+    instruction_flags, load_policy, log_activity, require_digest, require_instruction_enabled,
+    require_transfer_modes, signer_keys, withdrawal_summary, AccountMode, ActivityEntry,
+    ActivityKind, CustomError, FeeTreasury, GlobalConfig, InstructionTag, Ledger, LedgerAccount,
+    MintConfig, PolicyInput, UserAccount, LARGE_WITHDRAWAL_THRESHOLD,
+};
+
+/// Seed of the PDA that owns every vault of a namespace
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+
+/// A user's deposits of one mint, the most they may withdraw of it
+#[account]
+pub struct VaultPosition {
+    pub user: Pubkey,   // Owning user account (32 bytes)
+    pub mint: Pubkey,   // Mint deposited (32 bytes)
+    pub deposited: u64, // Internal units deposited and not withdrawn (8 bytes)
+    pub bump: u8,       // PDA bump seed (1 byte)
+}
+
+impl VaultPosition {
+    /// Space required for the account including the discriminator
+    pub const LEN: usize = 8 +  // Anchor discriminator
+        32 +                     // user: Pubkey
+        32 +                     // mint: Pubkey
+        8 +                      // deposited: u64
+        1; // bump: u8
+
+    /// Count `amount` internal units deposited
+    pub fn credit(&mut self, amount: u64) -> Result<()> {
+        self.deposited = math::add(self.deposited, amount)?;
+        Ok(())
+    }
+
+    /// Take `amount` internal units of `mint` out of the position
+    pub fn debit(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        require_keys_eq!(*mint, self.mint, CustomError::CrossMintWithdrawal);
+        self.deposited = self
+            .deposited
+            .checked_sub(amount)
+            .ok_or(CustomError::CrossMintWithdrawal)?; // Deposited as another mint, or not at all
+        Ok(())
+    }
+}
+
+/// Context for creating the vault of a listed mint
+#[derive(Accounts)]
+pub struct CreateVault<'info> {
+    /// Vault token account being created
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"vault", config.key().as_ref(), mint.key().as_ref()], // One vault per mint
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Namespace vault authority
+    /// CHECK: seeds are verified; holds no data and only signs CPIs
+    #[account(seeds = [VAULT_AUTHORITY_SEED, config.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Registry entry proving the mint is listed
+    #[account(
+        has_one = mint,
+        seeds = [b"mint_config", config.key().as_ref(), mint.key().as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// The vault's mint
+    pub mint: Account<'info, Mint>,
+
+    /// Namespace config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Namespace admin, pays rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Token program owning the vault
+    pub token_program: Program<'info, Token>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for opening a user's position in a mint's vault
+#[derive(Accounts)]
+pub struct OpenVaultPosition<'info> {
+    /// Position being created
+    #[account(
+        init,
+        payer = authority,
+        space = VaultPosition::LEN,
+        seeds = [b"vault_position", user.key().as_ref(), mint_config.mint.as_ref()], // One per user and mint
+        bump
+    )]
+    pub position: Account<'info, VaultPosition>,
+
+    /// Owning user account
+    #[account(
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, pays rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Registry entry proving the mint is listed
+    #[account(
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Namespace config the accounts belong to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Context shared by deposits and withdrawals
+#[derive(Accounts)]
+pub struct MoveTokens<'info> {
+    /// The user account credited or debited
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority, owns `user_token`
+    pub authority: Signer<'info>,
+
+    /// The authority's token account of the vault's mint
+    #[account(
+        mut,
+        token::mint = mint_config.mint,
+        token::authority = authority
+    )]
+    pub user_token: Account<'info, TokenAccount>,
+
+    /// Vault holding the mint's deposits
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Namespace vault authority, signs withdrawals
+    /// CHECK: seeds are verified; holds no data and only signs CPIs
+    #[account(seeds = [VAULT_AUTHORITY_SEED, config.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Registry entry of the mint, tracks outstanding deposits
+    #[account(
+        mut,
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Double-entry ledger receiving the movement
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Token program performing the transfer
+    pub token_program: Program<'info, Token>,
+
+    /// User's transfer policy address, enforced on withdrawals when it holds a policy
+    /// CHECK: address is the user's policy PDA; contents are decoded in the handler
+    #[account(seeds = [b"policy", user.key().as_ref()], bump)]
+    pub user_policy: UncheckedAccount<'info>,
+
+    /// User's activity log address, appended to when the user keeps one
+    /// CHECK: address is the user's log PDA; contents are checked in `log_activity`
+    #[account(mut, seeds = [b"activity_log", user.key().as_ref()], bump)]
+    pub user_activity: UncheckedAccount<'info>,
+
+    /// User's deposits of the vault's mint
+    #[account(
+        mut,
+        seeds = [b"vault_position", user.key().as_ref(), mint_config.mint.as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, VaultPosition>,
+}
+
+/// Context for booking tokens donated to a vault as fees
+#[derive(Accounts)]
+pub struct SkimDonations<'info> {
+    /// Vault the donations were sent to
+    #[account(seeds = [b"vault", config.key().as_ref(), mint_config.mint.as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Registry entry of the mint, books the skimmed units
+    #[account(
+        mut,
+        seeds = [b"mint_config", config.key().as_ref(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Treasury credited with the skimmed units
+    #[account(mut, seeds = [b"fee_treasury", config.key().as_ref()], bump = fee_treasury.bump)]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+
+    /// Double-entry ledger receiving the skim
+    #[account(mut, seeds = [b"ledger", config.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, Ledger>,
+
+    /// Global config, proves admin authority
+    #[account(
+        has_one = admin @ CustomError::Unauthorized,
+        seeds = [b"config", config.namespace.to_le_bytes().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Config admin
+    pub admin: Signer<'info>,
+}
+
+/// Event emitted when tokens are deposited into a vault
+#[event]
+pub struct TokensDepositedEvent {
+    pub user: Pubkey,        // Credited user account
+    pub mint: Pubkey,        // Deposited mint
+    pub raw_amount: u64,     // Tokens moved, in the mint's decimals
+    pub credited: u64,       // Internal units credited
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when tokens are withdrawn from a vault
+#[event]
+pub struct TokensWithdrawnEvent {
+    pub user: Pubkey,        // Debited user account
+    pub mint: Pubkey,        // Withdrawn mint
+    pub debited: u64,        // Internal units debited
+    pub raw_amount: u64,     // Tokens paid out, in the mint's decimals
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when donated tokens are booked as fees
+#[event]
+pub struct DonationsSkimmedEvent {
+    pub mint: Pubkey,        // Vault's mint
+    pub raw_surplus: u64,    // Tokens above the accounted balance
+    pub credited: u64,       // Internal units booked to the fee treasury
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Raw tokens the vault must hold to pay out every outstanding deposit
+pub fn accounted_balance(mint_config: &MintConfig) -> Result<u64> {
+    // Rounded up, so a skim never eats into what users can withdraw
+    Ok(decimals::owed_to_raw(
+        InternalUnits::new(mint_config.total_deposited),
+        mint_config.decimals,
+    )?
+    .get())
+}
+
+/// Raw tokens `vault_amount` holds above the accounted balance, sent
+/// straight to the vault and owed to no one
+pub fn donated_surplus(vault_amount: u64, mint_config: &MintConfig) -> Result<u64> {
+    Ok(vault_amount.saturating_sub(accounted_balance(mint_config)?))
+}
+
+/// Create the vault of a listed mint
+pub fn handle_create_vault(ctx: Context<CreateVault>) -> Result<()> {
+    msg!("Vault created for mint {}", ctx.accounts.mint.key());
+    Ok(())
+}
+
+/// Open an empty position for the caller in the mint's vault
+pub fn handle_open_vault_position(ctx: Context<OpenVaultPosition>) -> Result<()> {
+    let position = &mut ctx.accounts.position;
+    position.user = ctx.accounts.user.key();
+    position.mint = ctx.accounts.mint_config.mint;
+    position.bump = ctx.bumps.position;
+
+    msg!(
+        "Vault position opened for {} in mint {}",
+        position.user,
+        position.mint
+    );
+    Ok(())
+}
+
+/// Move `amount` raw tokens into the vault and credit their internal value
+pub fn handle_deposit_tokens(ctx: Context<MoveTokens>, amount: u64) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::DEPOSIT_TOKENS)?;
+    require!(amount > 0, CustomError::InvalidAmount);
+    // Tokens arrive from outside, which may always send
+    require_transfer_modes(AccountMode::Normal, ctx.accounts.user.mode)?;
+
+    let mint_config = &mut ctx.accounts.mint_config;
+    require!(mint_config.deposit_enabled, CustomError::DepositsDisabled);
+    let credited =
+        decimals::deposit_to_internal(RawTokenUnits::new(amount), mint_config.decimals)?.get();
+    require!(credited > 0, CustomError::InvalidAmount); // Dust would credit nothing
+    require!(
+        mint_config.has_capacity_for(credited),
+        CustomError::DepositCapExceeded
+    );
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    mint_config.total_deposited = math::add(mint_config.total_deposited, credited)?;
+    ctx.accounts.position.credit(credited)?;
+    let user = &mut ctx.accounts.user;
+    user.balance = math::add(user.balance, credited)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Vault, LedgerAccount::Users, credited)?; // Vault up, owed to users up
+    let entry = ActivityEntry::new(
+        ActivityKind::Received,
+        InstructionTag::DepositTokens,
+        Pubkey::default(), // From outside the program
+        credited,
+        Clock::get()?.unix_timestamp,
+    );
+    log_activity(&ctx.accounts.user_activity, user.key(), entry)?;
+
+    emit!(TokensDepositedEvent {
+        user: user.key(),
+        mint: mint_config.mint,
+        raw_amount: amount,
+        credited,
+        tag: InstructionTag::DepositTokens,
+    });
+
+    msg!("Deposited {} tokens, credited {}", amount, credited);
+    Ok(())
+}
+
+/// Debit `amount` internal units and pay their raw value out of the vault
+pub fn handle_withdraw_tokens(
+    ctx: Context<MoveTokens>,
+    amount: u64,
+    digest: Option<[u8; 32]>,
+) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::WITHDRAW_TOKENS)?;
+    require!(amount > 0, CustomError::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    let user = &ctx.accounts.user;
+    let policy = load_policy(&ctx.accounts.user_policy)?;
+    let policy_input = PolicyInput {
+        amount,
+        receiver: user.authority, // Paid to the authority's own token account
+        now,
+    };
+    guards::run(&[
+        Guard::Modes(user.mode, AccountMode::Normal), // Tokens leave for outside, which may always receive
+        Guard::NotJoint(user),
+        Guard::NotFrozen(user, now),
+        Guard::Funded(user, amount),
+        Guard::GuardianApproved(user, amount, &signer_keys(ctx.remaining_accounts)),
+        Guard::DailyLimit(user, amount, now),
+        Guard::Policy(policy.as_ref(), policy_input),
+    ])?;
+
+    let user = &mut ctx.accounts.user;
+    user.record_daily_spend(amount, now)?;
+
+    let mint_config = &mut ctx.accounts.mint_config;
+    require!(
+        mint_config.withdraw_enabled,
+        CustomError::WithdrawalsDisabled
+    );
+    let raw_amount =
+        decimals::withdrawal_to_raw(InternalUnits::new(amount), mint_config.decimals)?.get();
+    require!(raw_amount > 0, CustomError::InvalidAmount); // Dust would pay nothing
+    if amount >= LARGE_WITHDRAWAL_THRESHOLD {
+        let summary = withdrawal_summary(
+            ctx.accounts.config.namespace,
+            &mint_config.mint,
+            &ctx.accounts.user_token.key(),
+            raw_amount,
+            mint_config.decimals,
+        );
+        require_digest(&summary, digest)?;
+    }
+    ctx.accounts.position.debit(&mint_config.mint, amount)?;
+    mint_config.total_deposited = mint_config
+        .total_deposited
+        .checked_sub(amount)
+        .ok_or(CustomError::VaultInsufficient)?;
+
+    let config_key = ctx.accounts.config.key();
+    let bump = ctx.bumps.vault_authority;
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, config_key.as_ref(), &[bump]]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.user_token.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        raw_amount,
+    )?;
+
+    let user = &mut ctx.accounts.user;
+    user.balance = math::sub(user.balance, amount)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Users, LedgerAccount::Vault, amount)?; // Owed to users down, vault down
+    let tag = InstructionTag::WithdrawTokens;
+    let entry = ActivityEntry::new(ActivityKind::Sent, tag, Pubkey::default(), amount, now); // To outside the program
+    log_activity(&ctx.accounts.user_activity, user.key(), entry)?;
+
+    emit!(TokensWithdrawnEvent {
+        user: user.key(),
+        mint: mint_config.mint,
+        debited: amount,
+        raw_amount,
+        tag: InstructionTag::WithdrawTokens,
+    });
+
+    msg!("Withdrew {} tokens, debited {}", raw_amount, amount);
+    Ok(())
+}
+
+/// Book the vault's tokens above its accounted balance as treasury fees
+pub fn handle_skim_donations(ctx: Context<SkimDonations>) -> Result<()> {
+    let mint_config = &mut ctx.accounts.mint_config;
+    let raw_surplus = donated_surplus(ctx.accounts.vault.amount, mint_config)?;
+    let credited =
+        decimals::deposit_to_internal(RawTokenUnits::new(raw_surplus), mint_config.decimals)?.get();
+    require!(credited > 0, CustomError::InvalidAmount); // Nothing above dust to skim
+
+    mint_config.total_deposited = math::add(mint_config.total_deposited, credited)?;
+    ctx.accounts.fee_treasury.collect(credited)?;
+    ctx.accounts
+        .ledger
+        .post(LedgerAccount::Vault, LedgerAccount::Fees, credited)?; // Vault up, fees earned up
+
+    emit!(DonationsSkimmedEvent {
+        mint: mint_config.mint,
+        raw_surplus,
+        credited,
+        tag: InstructionTag::SkimDonations,
+    });
+
+    msg!(
+        "Skimmed {} donated tokens, credited {}",
+        raw_surplus,
+        credited
+    );
+    Ok(())
+}
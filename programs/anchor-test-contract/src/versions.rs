@@ -0,0 +1,139 @@
+/*
+ * ============================================================================
+ * INSTRUCTION VERSIONS
+ * ============================================================================
+ *
+ * When an instruction's signature changes, the new form ships as a new
+ * entrypoint and the old one keeps working, so deployed clients do not
+ * break on upgrade. `INSTRUCTION_VERSIONS` lists every versioned
+ * entrypoint with its version and, once superseded, the one to use
+ * instead.
+ *
+ * Each namespace's admin retires a superseded entrypoint with
+ * `set_instruction_sunset`, which stores a date in the config slot of its
+ * registry entry. Until then a call succeeds and logs a deprecation notice.
+ * From then on it fails with `InstructionDeprecated` and emits an
+ * `InstructionDeprecatedEvent` naming the replacement. A failed
+ * transaction keeps its logs, so clients can decode the event from the
+ * error and migrate without hardcoding the mapping. The sunset is a
+ * deadline and honors the namespace's clock tolerance.
+ *
+ * Registered today:
+ * - `transfer_tokens` (v1), superseded by `transfer_tokens_v2`, which adds
+ *   a memo
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{CustomError, GlobalConfig, InstructionTag, MAX_VERSIONED_INSTRUCTIONS};
+
+/// Version of every versioned entrypoint
+pub mod instruction_versions {
+    pub const TRANSFER_TOKENS: u8 = 1; // Amount and optional idempotency key
+    pub const TRANSFER_TOKENS_V2: u8 = 2; // Adds a memo
+}
+
+/// One entrypoint in the version registry
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InstructionVersion {
+    pub instruction: InstructionTag,         // Entrypoint
+    pub version: u8,                         // Its `instruction_versions` constant
+    pub use_instead: Option<InstructionTag>, // Replacement, `None` while current
+}
+
+/// Every versioned entrypoint; the index is the entry's sunset slot in
+/// `GlobalConfig::instruction_sunsets`, so entries are append-only
+pub const INSTRUCTION_VERSIONS: [InstructionVersion; 2] = [
+    InstructionVersion {
+        instruction: InstructionTag::TransferTokens,
+        version: instruction_versions::TRANSFER_TOKENS,
+        use_instead: Some(InstructionTag::TransferTokensV2),
+    },
+    InstructionVersion {
+        instruction: InstructionTag::TransferTokensV2,
+        version: instruction_versions::TRANSFER_TOKENS_V2,
+        use_instead: None,
+    },
+];
+
+const _: () = assert!(INSTRUCTION_VERSIONS.len() <= MAX_VERSIONED_INSTRUCTIONS);
+
+/// Registry slot and entry of `instruction`, if it is versioned
+pub fn instruction_version(instruction: InstructionTag) -> Option<(usize, InstructionVersion)> {
+    INSTRUCTION_VERSIONS
+        .iter()
+        .position(|entry| entry.instruction == instruction)
+        .map(|slot| (slot, INSTRUCTION_VERSIONS[slot]))
+}
+
+/// Event emitted when a call reaches a sunset entrypoint
+#[event]
+pub struct InstructionDeprecatedEvent {
+    pub instruction: InstructionTag, // Entrypoint called
+    pub version: u8,                 // Its version
+    pub use_instead: InstructionTag, // Entrypoint to call instead
+    pub sunset_at: i64,              // When it stopped being served
+}
+
+/// Event carrying a transfer's memo, emitted after its `TokenTransferEvent`
+#[event]
+pub struct TransferMemoEvent {
+    pub from: Pubkey,        // Sender's authority
+    pub to: Pubkey,          // Receiver's authority
+    pub memo: Vec<u8>,       // Client-supplied memo
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Fail once `instruction`'s sunset has passed at `now`
+///
+/// Current entrypoints, and superseded ones without a sunset, always pass.
+pub fn require_instruction_supported(
+    config: &GlobalConfig,
+    instruction: InstructionTag,
+    now: i64,
+) -> Result<()> {
+    let Some((slot, entry)) = instruction_version(instruction) else {
+        return Ok(()); // Unversioned
+    };
+    let Some(use_instead) = entry.use_instead else {
+        return Ok(()); // Current
+    };
+    let sunset_at = config.instruction_sunsets[slot];
+    if sunset_at == 0 {
+        msg!(
+            "{:?} v{} is deprecated, use {:?}",
+            instruction,
+            entry.version,
+            use_instead
+        );
+        return Ok(());
+    }
+    if config.clock_tolerance().before(now, sunset_at) {
+        msg!(
+            "{:?} v{} is deprecated, use {:?} before {}",
+            instruction,
+            entry.version,
+            use_instead,
+            sunset_at
+        );
+        return Ok(());
+    }
+
+    emit!(InstructionDeprecatedEvent {
+        instruction,
+        version: entry.version,
+        use_instead,
+        sunset_at,
+    });
+    err!(CustomError::InstructionDeprecated)
+}
+
+/// Check a sunset for `instruction` before it is stored
+pub fn validate_sunset(instruction: InstructionTag, sunset_at: i64) -> Result<usize> {
+    let (slot, entry) = instruction_version(instruction).ok_or(CustomError::InvalidSunset)?;
+    require!(
+        entry.use_instead.is_some() && sunset_at >= 0,
+        CustomError::InvalidSunset
+    ); // Current entrypoints cannot be retired
+    Ok(slot)
+}
@@ -0,0 +1,186 @@
+/*
+ * ============================================================================
+ * WATCHTOWERS
+ * ============================================================================
+ *
+ * A user may register a watchtower: the key of an off-chain monitoring
+ * service watching the account for suspicious signing activity. The
+ * watchtower can `raise_alert`, which freezes every outbound transfer from
+ * the account for `WATCHTOWER_ALERT_DURATION`, long enough for the user to
+ * react but short enough that a faulty service cannot lock the account for
+ * good. Raising again extends the freeze.
+ *
+ * The authority ends an alert early with `dismiss_alert`, and removes the
+ * watchtower by registering the default key. Inbound transfers are never
+ * affected.
+ */
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction_flags, require_instruction_enabled, CustomError, GlobalConfig, InstructionTag,
+    UserAccount, WATCHTOWER_ALERT_DURATION,
+};
+
+/// Why a watchtower raised an alert
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertReason {
+    SuspiciousSigning, // Signatures from an unfamiliar device or location
+    UnusualVolume,     // Outflows far above the account's habits
+    KeyCompromise,     // Evidence the authority key has leaked
+    Other,             // Anything else
+}
+
+impl UserAccount {
+    /// Returns true while a watchtower alert freezes outbound transfers
+    pub fn is_alerted(&self, now: i64) -> bool {
+        now < self.alert_until
+    }
+
+    /// True if `watchtower` is the account's registered watchtower
+    pub fn is_watchtower(&self, watchtower: &Pubkey) -> bool {
+        self.watchtower != Pubkey::default() && self.watchtower == *watchtower
+    }
+}
+
+/// Context for registering or removing a watchtower
+#[derive(Accounts)]
+pub struct SetWatchtower<'info> {
+    /// Account being watched
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for a watchtower raising an alert
+#[derive(Accounts)]
+pub struct RaiseAlert<'info> {
+    /// Account being frozen
+    #[account(
+        mut,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        constraint = user.is_watchtower(&watchtower.key()) @ CustomError::Unauthorized,
+        seeds = [b"user", config.key().as_ref(), user.authority.as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// The account's registered watchtower
+    pub watchtower: Signer<'info>,
+
+    /// Global config, checked for the instruction kill switch
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Context for the authority dismissing an alert
+#[derive(Accounts)]
+pub struct DismissAlert<'info> {
+    /// Account being unfrozen
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = user.is_current() @ CustomError::MigrationRequired,
+        seeds = [b"user", config.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, UserAccount>,
+
+    /// Account authority
+    pub authority: Signer<'info>,
+
+    /// Namespace config the account belongs to
+    #[account(seeds = [b"config", config.namespace.to_le_bytes().as_ref()], bump = config.bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+/// Event emitted when a watchtower is registered or removed
+#[event]
+pub struct WatchtowerSetEvent {
+    pub user: Pubkey,        // Watched user account
+    pub watchtower: Pubkey,  // New watchtower, default when removed
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when a watchtower freezes an account
+#[event]
+pub struct AlertRaisedEvent {
+    pub user: Pubkey,        // Frozen user account
+    pub watchtower: Pubkey,  // Watchtower that raised the alert
+    pub reason: AlertReason, // Why
+    pub until: i64,          // When outbound transfers resume
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Event emitted when the authority dismisses an alert
+#[event]
+pub struct AlertDismissedEvent {
+    pub user: Pubkey,        // Unfrozen user account
+    pub tag: InstructionTag, // Emitting instruction
+}
+
+/// Register `watchtower` for the caller's account, or remove it with the default key
+pub fn handle_set_watchtower(ctx: Context<SetWatchtower>, watchtower: Pubkey) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::WATCHTOWER)?;
+
+    let user = &mut ctx.accounts.user;
+    user.watchtower = watchtower;
+
+    emit!(WatchtowerSetEvent {
+        user: user.key(),
+        watchtower,
+        tag: InstructionTag::SetWatchtower,
+    });
+
+    msg!("Watchtower set to {}", watchtower);
+    Ok(())
+}
+
+/// Freeze the account's outbound transfers for `WATCHTOWER_ALERT_DURATION`
+pub fn handle_raise_alert(ctx: Context<RaiseAlert>, reason: AlertReason) -> Result<()> {
+    require_instruction_enabled(&ctx.accounts.config, instruction_flags::WATCHTOWER)?;
+
+    let user = &mut ctx.accounts.user;
+    user.alert_until = WATCHTOWER_ALERT_DURATION.after(Clock::get()?.unix_timestamp);
+
+    emit!(AlertRaisedEvent {
+        user: user.key(),
+        watchtower: ctx.accounts.watchtower.key(),
+        reason,
+        until: user.alert_until,
+        tag: InstructionTag::RaiseAlert,
+    });
+
+    msg!("Alert raised on {} until {}", user.key(), user.alert_until);
+    Ok(())
+}
+
+/// Lift the account's alert; always available, whatever the kill switches
+pub fn handle_dismiss_alert(ctx: Context<DismissAlert>) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    require!(
+        user.is_alerted(Clock::get()?.unix_timestamp),
+        CustomError::NoActiveAlert
+    );
+    user.alert_until = 0;
+
+    emit!(AlertDismissedEvent {
+        user: user.key(),
+        tag: InstructionTag::DismissAlert,
+    });
+
+    msg!("Alert dismissed on {}", user.key());
+    Ok(())
+}
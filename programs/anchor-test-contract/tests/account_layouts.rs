@@ -0,0 +1,692 @@
+//! Golden-byte snapshots of every account layout
+//!
+//! Each account is serialized with fixed field values and compared against
+//! `tests/snapshots/<name>.hex`. A mismatch means existing on-chain data would
+//! no longer deserialize: add a migration instead of editing the snapshot.
+//! For intentional new layouts, regenerate with `UPDATE_SNAPSHOTS=1 cargo test`.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, ZeroCopy};
+use anchor_test_contract::{
+    AccountMode, ActivityEntry, ActivityKind, ActivityLog, Allowance, Attachment, AttachmentPage,
+    Attestation, Auction, AuctionStatus, Bps, CapabilityToken, CashbackMerchant, CashbackProgram,
+    ChaosConfig, ChaosParams, Commitment, CpiProgramRegistry, CrankMetrics, CreditLine,
+    DocumentKind, EmissionCurve, EmissionSchedule, EpochStats, Escrow, EscrowStatus, FeeSchedule,
+    FeeStrategy, FeeTier, FeeTreasury, GlobalConfig, InstructionTag, Invoice, InvoiceStatus,
+    InvoiceTemplate, IssuerRecord, JointAccount, JointMode, Ledger, LotMethod, ManagerGrant,
+    MintConfig, NamespaceBridgeConfig, NamespaceMetadata, Obligation, ProfileParams, PromoClaim,
+    PromoCode, PromoReward, RampedParam, RecentOperations, ReservesAttestation, SealedBid, Seconds,
+    ShadowFees, SpendGuard, TaxLot, TaxLotBook, TaxLotPage, TransferPolicy, UserAccount,
+    UserProfile, VaultPosition, MAX_CPI_PROGRAMS, RECENT_OPERATIONS_CAPACITY,
+};
+use common::key;
+
+/// Pubkey filled with a single repeated byte
+/// Hex dump, 32 bytes per line so diffs point at the moved field
+fn to_hex(data: &[u8]) -> String {
+    data.chunks(32)
+        .map(|chunk| chunk.iter().map(|b| format!("{b:02x}")).collect::<String>() + "\n")
+        .collect()
+}
+
+fn assert_snapshot<T: AccountSerialize>(name: &str, account: &T, space: usize) {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    assert_snapshot_bytes(name, &data, space);
+}
+
+/// Zero-copy accounts are stored as their raw bytes after the discriminator
+fn assert_zero_copy_snapshot<T: ZeroCopy>(name: &str, account: &T, space: usize) {
+    let data = [&T::DISCRIMINATOR[..], bytemuck::bytes_of(account)].concat();
+    assert_snapshot_bytes(name, &data, space);
+}
+
+fn assert_snapshot_bytes(name: &str, data: &[u8], space: usize) {
+    assert!(
+        data.len() <= space,
+        "{name}: serialized {} bytes but LEN is {space}",
+        data.len()
+    );
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.hex"));
+    let actual = to_hex(data);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("{name}: missing snapshot {}", path.display()));
+    assert_eq!(
+        actual, expected,
+        "{name}: account layout changed; existing on-chain data would break"
+    );
+}
+
+#[test]
+fn user_account_layout() {
+    let account = UserAccount {
+        authority: key(1),
+        name: "alice".to_string(),
+        age: 30,
+        balance: 1_000,
+        mode: AccountMode::Normal,
+        created_at: 1_700_000_000,
+        is_joint: false,
+        vacation_start: 1_700_000_100,
+        vacation_end: 1_700_000_200,
+        compliance_hold: false,
+        deactivated_at: 0,
+        guardians: [key(2), key(3), Pubkey::default()],
+        guardian_threshold: 2,
+        guardian_limit: 5_000,
+        version: 2,
+        daily_limit: 20_000,
+        spent_today: 7_500,
+        window_start: 1_700_003_600,
+        pending_daily_limit: 50_000,
+        pending_limit_at: 1_700_090_000,
+        watchtower: key(68),
+        alert_until: 1_700_021_600,
+        open_obligations: 2,
+        sequence: 17,
+        uncounted_obligations: true,
+    };
+    assert_snapshot("user_account", &account, UserAccount::LEN);
+}
+
+#[test]
+fn global_config_layout() {
+    let account = GlobalConfig {
+        admin: key(2),
+        disabled_instructions: 0b101,
+        bump: 254,
+        fee_rate_bps: RampedParam {
+            old: 100,
+            new: 50,
+            start: 1_700_000_000,
+            end: 1_700_086_400,
+        },
+        circuit_breaker_multiple_bps: 30_000,
+        max_price_deviation_bps: 500,
+        price_deviation_window_secs: 3_600,
+        lookup_table: key(3),
+        namespace: 7,
+        fee_strategy: FeeStrategy::Tiered {
+            tiers: [
+                FeeTier {
+                    min_amount: 0,
+                    fee_bps: 100,
+                },
+                FeeTier {
+                    min_amount: 1_000_000,
+                    fee_bps: 50,
+                },
+                FeeTier {
+                    min_amount: 1_000_000_000,
+                    fee_bps: 10,
+                },
+                FeeTier::UNUSED,
+            ],
+        },
+        emission_schedule: EmissionSchedule {
+            curve: EmissionCurve::Halving {
+                initial: 1_000_000,
+                halving_periods: 52,
+            },
+            supply_cap: 100_000_000,
+        },
+        compliance: key(4),
+        paused: false,
+        reactivation_cooldown_secs: 86_400,
+        clock_tolerance_secs: 30,
+        instruction_sunsets: [1_710_000_000, 0, 0, 0, 0, 0, 0, 0],
+    };
+    assert_snapshot("global_config", &account, GlobalConfig::LEN);
+}
+
+#[test]
+fn epoch_stats_layout() {
+    let account = EpochStats {
+        epoch: 42,
+        volume: 5_000,
+        trailing_average: 4_000,
+        tripped: true,
+        tripped_at: 1_700_000_000,
+        bump: 253,
+    };
+    assert_snapshot("epoch_stats", &account, EpochStats::LEN);
+}
+
+#[test]
+fn ledger_layout() {
+    let account = Ledger {
+        debits: [1, 2, 3, 4],
+        credits: [5, 6, 7, 8],
+        entries: 9,
+        bump: 252,
+    };
+    assert_snapshot("ledger", &account, Ledger::LEN);
+}
+
+#[test]
+fn joint_account_layout() {
+    let account = JointAccount {
+        user: key(4),
+        primary: key(5),
+        secondary: key(6),
+        threshold: 10_000,
+        mode: JointMode::EitherParty,
+        bump: 251,
+    };
+    assert_snapshot("joint_account", &account, JointAccount::LEN);
+}
+
+#[test]
+fn recent_operations_layout() {
+    let mut account = RecentOperations {
+        user: key(7),
+        head: 2,
+        keys: [[0; 16]; RECENT_OPERATIONS_CAPACITY],
+        recorded_at: [0; RECENT_OPERATIONS_CAPACITY],
+        bump: 250,
+    };
+    account.keys[0] = [0xaa; 16];
+    account.keys[1] = [0xbb; 16];
+    account.recorded_at[0] = 1_700_000_000;
+    account.recorded_at[1] = 1_700_000_001;
+    assert_snapshot("recent_operations", &account, RecentOperations::LEN);
+}
+
+#[test]
+fn mint_config_layout() {
+    let account = MintConfig {
+        mint: key(8),
+        decimals: 6,
+        deposit_enabled: true,
+        withdraw_enabled: false,
+        max_total_deposits: 1_000_000,
+        total_deposited: 250_000,
+        oracle_feed: key(9),
+        bump: 249,
+        last_price: 1_000_000,
+        last_price_at: 1_700_000_000,
+    };
+    assert_snapshot("mint_config", &account, MintConfig::LEN);
+}
+
+#[test]
+fn commitment_layout() {
+    let account = Commitment {
+        owner: key(10),
+        domain: 7,
+        hash: [0xcc; 32],
+        committed_at: 1_700_000_000,
+        reveal_after: 1_700_000_600,
+        expires_at: 1_700_001_200,
+        revealed: false,
+        bump: 248,
+    };
+    assert_snapshot("commitment", &account, Commitment::LEN);
+}
+
+#[test]
+fn auction_layout() {
+    let account = Auction {
+        seller: key(11),
+        auction_id: 3,
+        commit_end: 1_700_000_600,
+        reveal_end: 1_700_001_200,
+        reserve_price: 500,
+        non_reveal_penalty_bps: 1_000,
+        highest_bidder: key(12),
+        highest_bid: 900,
+        second_bid: 700,
+        bid_count: 4,
+        revealed_count: 3,
+        status: AuctionStatus::Settled,
+        winner: key(12),
+        price: 700,
+        bump: 247,
+    };
+    assert_snapshot("auction", &account, Auction::LEN);
+}
+
+#[test]
+fn sealed_bid_layout() {
+    let account = SealedBid {
+        auction: key(13),
+        bidder: key(14),
+        deposit: 1_000,
+        revealed: true,
+        amount: 900,
+        bump: 246,
+    };
+    assert_snapshot("sealed_bid", &account, SealedBid::LEN);
+}
+
+#[test]
+fn attachment_page_layout() {
+    let account = AttachmentPage {
+        user: key(15),
+        page: 1,
+        entries: vec![Attachment {
+            kind: DocumentKind::Invoice,
+            cid_hash: [0xdd; 32],
+            attached_at: 1_700_000_000,
+        }],
+        bump: 245,
+    };
+    assert_snapshot("attachment_page", &account, AttachmentPage::LEN);
+}
+
+#[test]
+fn issuer_record_layout() {
+    let account = IssuerRecord {
+        issuer: key(16),
+        active: true,
+        bump: 244,
+    };
+    assert_snapshot("issuer_record", &account, IssuerRecord::LEN);
+}
+
+#[test]
+fn attestation_layout() {
+    let account = Attestation {
+        issuer: key(16),
+        user: key(17),
+        claim_type: 3,
+        data: [0xee; 32],
+        issued_at: 1_700_000_000,
+        expires_at: 1_731_536_000,
+        bump: 243,
+    };
+    assert_snapshot("attestation", &account, Attestation::LEN);
+}
+
+#[test]
+fn namespace_metadata_layout() {
+    let account = NamespaceMetadata {
+        config: key(18),
+        name: "operator".to_string(),
+        uri_hash: [0xdd; 32],
+        default_fees: FeeSchedule {
+            fee_rate_bps: 30,
+            min_fee: 5,
+            max_fee: 10_000,
+        },
+        updated_at: 1_700_000_000,
+        bump: 242,
+    };
+    assert_snapshot("namespace_metadata", &account, NamespaceMetadata::LEN);
+}
+
+#[test]
+fn namespace_bridge_config_layout() {
+    let account = NamespaceBridgeConfig {
+        config_a: key(19),
+        config_b: key(20),
+        fee_bps: 25,
+        source_share_bps: 4_000,
+        approved_a: true,
+        approved_b: false,
+        payer: key(21),
+        bump: 241,
+    };
+    assert_snapshot(
+        "namespace_bridge_config",
+        &account,
+        NamespaceBridgeConfig::LEN,
+    );
+}
+
+#[test]
+fn cpi_program_registry_layout() {
+    let account = CpiProgramRegistry {
+        config: key(22),
+        programs: (23..23 + MAX_CPI_PROGRAMS as u8).map(key).collect(),
+        bump: 240,
+    };
+    assert_snapshot("cpi_program_registry", &account, CpiProgramRegistry::LEN);
+}
+
+#[test]
+fn manager_grant_layout() {
+    let account = ManagerGrant {
+        user: key(40),
+        authority: key(41),
+        manager: key(42),
+        permissions: 0b1,
+        expires_at: 1_700_086_400,
+        granted_at: 1_700_000_000,
+        bump: 239,
+    };
+    assert_snapshot("manager_grant", &account, ManagerGrant::LEN);
+}
+
+#[test]
+fn allowance_layout() {
+    let account = Allowance {
+        owner: key(43),
+        authority: key(44),
+        delegate: key(45),
+        amount: 25_000,
+        bump: 238,
+    };
+    assert_snapshot("allowance", &account, Allowance::LEN);
+}
+
+#[test]
+fn fee_treasury_layout() {
+    let account = FeeTreasury {
+        balance: 4_200,
+        total_collected: 9_900,
+        bump: 237,
+    };
+    assert_snapshot("fee_treasury", &account, FeeTreasury::LEN);
+}
+
+#[test]
+fn spend_guard_layout() {
+    let account = SpendGuard {
+        opening_balance: 80_000,
+        max_outflow: 5_000,
+        bump: 236,
+    };
+    assert_snapshot("spend_guard", &account, SpendGuard::LEN);
+}
+
+#[test]
+fn obligation_layout() {
+    let account = Obligation {
+        party_a: key(46),
+        party_b: key(47),
+        a_owes_b: 12_000,
+        b_owes_a: 9_500,
+        settlements: 3,
+        bump: 235,
+    };
+    assert_snapshot("obligation", &account, Obligation::LEN);
+}
+
+#[test]
+fn invoice_template_layout() {
+    let account = InvoiceTemplate {
+        merchant: key(48),
+        payer: key(49),
+        amount: 2_500,
+        interval: Seconds::DAY,
+        next_issue_at: 1_700_086_400,
+        issued: 4,
+        unpaid: 1,
+        active: true,
+        bump: 234,
+        version: 6,
+    };
+    assert_snapshot("invoice_template", &account, InvoiceTemplate::LEN);
+}
+
+#[test]
+fn invoice_layout() {
+    let account = Invoice {
+        template: key(50),
+        merchant: key(48),
+        payer: key(49),
+        amount: 2_500,
+        period: 3,
+        issued_at: 1_700_000_000,
+        rent_payer: key(51),
+        owner: key(65),
+        price: 2_250,
+        status: InvoiceStatus::Issued,
+        bump: 233,
+    };
+    assert_snapshot("invoice", &account, Invoice::LEN);
+}
+
+#[test]
+fn promo_code_layout() {
+    let account = PromoCode {
+        code_hash: [52; 32],
+        reward: PromoReward::BonusCredit { amount: 750 },
+        max_uses: 100,
+        uses: 17,
+        expires_at: 1_700_604_800,
+        creator: key(53),
+        bump: 232,
+    };
+    assert_snapshot("promo_code", &account, PromoCode::LEN);
+}
+
+#[test]
+fn promo_claim_layout() {
+    let account = PromoClaim {
+        promo_code: key(54),
+        user: key(55),
+        claimed_at: 1_700_000_000,
+        bump: 231,
+    };
+    assert_snapshot("promo_claim", &account, PromoClaim::LEN);
+}
+
+#[test]
+fn escrow_layout() {
+    let account = Escrow {
+        creator: key(56),
+        creator_authority: key(57),
+        beneficiary: key(58),
+        amount: 40_000,
+        unlock_at: 1_731_536_000,
+        status: EscrowStatus::Locked,
+        bump: 230,
+    };
+    assert_snapshot("escrow", &account, Escrow::LEN);
+}
+
+#[test]
+fn cashback_program_layout() {
+    let account = CashbackProgram {
+        rate: Bps(150),
+        epoch_budget: 500_000,
+        epoch: 612,
+        spent: 120_000,
+        suspended: false,
+        total_paid: 3_400_000,
+        bump: 229,
+    };
+    assert_snapshot("cashback_program", &account, CashbackProgram::LEN);
+}
+
+#[test]
+fn cashback_merchant_layout() {
+    let account = CashbackMerchant {
+        merchant: key(59),
+        registered_at: 1_700_000_000,
+        bump: 228,
+    };
+    assert_snapshot("cashback_merchant", &account, CashbackMerchant::LEN);
+}
+
+#[test]
+fn activity_log_layout() {
+    let mut account: ActivityLog = bytemuck::Zeroable::zeroed();
+    account.user = key(60);
+    account.bump = 227;
+    account.append(ActivityEntry::new(
+        ActivityKind::Sent,
+        InstructionTag::TransferTokens,
+        key(61),
+        7_500,
+        1_700_000_000,
+    ));
+    assert_zero_copy_snapshot("activity_log", &account, ActivityLog::LEN);
+}
+
+#[test]
+fn credit_line_layout() {
+    let account = CreditLine {
+        lender: key(62),
+        borrower: key(63),
+        limit: 250_000,
+        rate: Bps(1_200),
+        drawn: 90_000,
+        interest: 1_337,
+        accrued_at: 1_700_000_000,
+        frozen: true,
+        bump: 226,
+    };
+    assert_snapshot("credit_line", &account, CreditLine::LEN);
+}
+
+#[test]
+fn user_profile_layout() {
+    let params = ProfileParams {
+        avatar_uri: Some("ipfs://bafy".to_string()),
+        email_hash: Some([0xee; 32]),
+        metadata: "tz=UTC".to_string(),
+    };
+    let account = UserProfile {
+        user: key(64),
+        avatar_uri: params.avatar_uri.clone(),
+        email_hash: params.email_hash,
+        metadata: params.metadata.clone(),
+        updated_at: 1_700_000_000,
+        bump: 225,
+    };
+    assert_snapshot("user_profile", &account, UserProfile::space(&params));
+}
+
+#[test]
+fn tax_lot_book_layout() {
+    let account = TaxLotBook {
+        user: key(66),
+        method: LotMethod::Lifo,
+        first_page: 2,
+        next_page: 5,
+        bump: 224,
+    };
+    assert_snapshot("tax_lot_book", &account, TaxLotBook::LEN);
+}
+
+#[test]
+fn tax_lot_page_layout() {
+    let account = TaxLotPage {
+        user: key(66),
+        index: 3,
+        lots: vec![TaxLot {
+            amount: 1_250,
+            acquired_at: 1_700_000_000,
+            source: key(67),
+        }],
+        bump: 223,
+    };
+    assert_snapshot("tax_lot_page", &account, TaxLotPage::LEN);
+}
+
+#[test]
+fn shadow_fees_layout() {
+    let account = ShadowFees {
+        config: key(69),
+        candidate: FeeStrategy::Discounted {
+            discount_bps: 2_500,
+        },
+        transfers: 40,
+        live_total: 4_000,
+        shadow_total: 3_000,
+        diverged: 38,
+        bump: 222,
+    };
+    assert_snapshot("shadow_fees", &account, ShadowFees::LEN);
+}
+
+#[test]
+fn chaos_config_layout() {
+    let account = ChaosConfig {
+        config: key(70),
+        params: ChaosParams {
+            fail_on_call: 3,
+            clock_skew_secs: -90,
+            oracle_delay_secs: 600,
+        },
+        calls: 2,
+        bump: 221,
+    };
+    assert_snapshot("chaos_config", &account, ChaosConfig::LEN);
+}
+
+#[test]
+fn transfer_policy_layout() {
+    let mut code = vec![0x01];
+    code.extend_from_slice(&500u64.to_le_bytes());
+    code.extend_from_slice(&[0x03, 0x11]);
+    let account = TransferPolicy {
+        user: key(71),
+        code,
+        allowlist: vec![key(72)],
+        bump: 220,
+    };
+    assert_snapshot("transfer_policy", &account, TransferPolicy::LEN);
+}
+
+#[test]
+fn crank_metrics_layout() {
+    let account = CrankMetrics {
+        config: key(73),
+        cranks: 12,
+        total_delay_secs: 340,
+        max_delay_secs: 95,
+        last_crank_at: 1_700_001_000,
+        bump: 219,
+    };
+    assert_snapshot("crank_metrics", &account, CrankMetrics::LEN);
+}
+
+#[test]
+fn capability_token_layout() {
+    let account = CapabilityToken {
+        user: key(74),
+        authority: key(75),
+        service: key(76),
+        name: "billing-svc".to_string(),
+        scopes: 0b11,
+        uses: 5,
+        max_uses: 100,
+        expires_at: 1_702_600_000,
+        issued_at: 1_700_000_000,
+        last_used_at: 1_700_500_000,
+        bump: 218,
+    };
+    assert_snapshot("capability_token", &account, CapabilityToken::LEN);
+}
+
+#[test]
+fn vault_position_layout() {
+    let account = VaultPosition {
+        user: key(77),
+        mint: key(78),
+        deposited: 42_000,
+        bump: 217,
+    };
+    assert_snapshot("vault_position", &account, VaultPosition::LEN);
+}
+
+#[test]
+fn reserves_attestation_layout() {
+    let account = ReservesAttestation {
+        mint: key(79),
+        epoch: 512,
+        vault_balance: 1_250_000,
+        accounted: 1_000_000,
+        coverage_bps: 12_500,
+        attested_at: 1_700_000_000,
+        bump: 216,
+    };
+    assert_snapshot("reserves_attestation", &account, ReservesAttestation::LEN);
+}
@@ -0,0 +1,77 @@
+//! Transfer permissions for every pair of sender and receiver modes
+//!
+//! Each rejected pair must name the party and the mode that blocked it,
+//! checking the sender first.
+
+use anchor_test_contract::{require_transfer_modes, AccountMode, CustomError};
+
+const MODES: [AccountMode; 4] = [
+    AccountMode::Locked,
+    AccountMode::Normal,
+    AccountMode::ReceiveOnly,
+    AccountMode::SendOnly,
+];
+
+/// Error expected for a transfer from `sender` to `receiver`, if any
+fn expected(sender: AccountMode, receiver: AccountMode) -> Option<CustomError> {
+    match (sender, receiver) {
+        (AccountMode::Locked, _) => Some(CustomError::SenderLocked),
+        (AccountMode::ReceiveOnly, _) => Some(CustomError::SenderReceiveOnly),
+        (_, AccountMode::Locked) => Some(CustomError::ReceiverLocked),
+        (_, AccountMode::SendOnly) => Some(CustomError::ReceiverSendOnly),
+        _ => None,
+    }
+}
+
+#[test]
+fn every_mode_pair_gets_its_own_verdict() {
+    let mut allowed = 0;
+    for sender in MODES {
+        for receiver in MODES {
+            match (
+                require_transfer_modes(sender, receiver),
+                expected(sender, receiver),
+            ) {
+                (Ok(()), None) => allowed += 1,
+                (Err(err), Some(error)) => {
+                    assert_eq!(err, error.into(), "{sender:?} -> {receiver:?}")
+                }
+                (result, expected) => {
+                    panic!("{sender:?} -> {receiver:?}: got {result:?}, expected {expected:?}")
+                }
+            }
+        }
+    }
+    // Normal and SendOnly senders, to Normal and ReceiveOnly receivers
+    assert_eq!(allowed, 4);
+}
+
+#[test]
+fn directions_match_the_mode_names() {
+    assert!(AccountMode::Normal.can_send() && AccountMode::Normal.can_receive());
+    assert!(!AccountMode::ReceiveOnly.can_send() && AccountMode::ReceiveOnly.can_receive());
+    assert!(AccountMode::SendOnly.can_send() && !AccountMode::SendOnly.can_receive());
+    assert!(!AccountMode::Locked.can_send() && !AccountMode::Locked.can_receive());
+
+    for sender in MODES {
+        for receiver in MODES {
+            assert_eq!(
+                require_transfer_modes(sender, receiver).is_ok(),
+                sender.can_send() && receiver.can_receive()
+            );
+        }
+    }
+}
+
+#[test]
+fn locked_and_normal_keep_the_old_active_flag_encoding() {
+    use anchor_lang::AnchorSerialize;
+    assert_eq!(
+        AccountMode::Locked.try_to_vec().unwrap(),
+        false.try_to_vec().unwrap()
+    );
+    assert_eq!(
+        AccountMode::Normal.try_to_vec().unwrap(),
+        true.try_to_vec().unwrap()
+    );
+}
@@ -0,0 +1,103 @@
+//! Activity log ring buffer ordering, wraparound and appends
+
+mod common;
+
+use anchor_lang::prelude::{AccountInfo, Pubkey, Result};
+use anchor_lang::Discriminator;
+use anchor_test_contract::{
+    log_activity, ActivityEntry, ActivityKind, ActivityLog, CustomError, InstructionTag,
+    ACTIVITY_LOG_CAPACITY,
+};
+use common::log;
+
+fn sent(amount: u64) -> ActivityEntry {
+    ActivityEntry::new(
+        ActivityKind::Sent,
+        InstructionTag::TransferTokens,
+        Pubkey::new_unique(),
+        amount,
+        1_700_000_000 + amount as i64,
+    )
+}
+
+#[test]
+fn empty_log_has_no_entries() {
+    let log = log();
+    assert_eq!(log.recent().count(), 0);
+    assert_eq!(ActivityKind::from_u8(log.entries[0].kind), None);
+}
+
+#[test]
+fn recent_entries_are_newest_first() {
+    let mut log = log();
+    for amount in 1..=3 {
+        log.append(sent(amount));
+    }
+    let amounts: Vec<u64> = log.recent().map(|entry| entry.amount).collect();
+    assert_eq!(amounts, vec![3, 2, 1]);
+    assert_eq!(
+        ActivityKind::from_u8(log.entries[0].kind),
+        Some(ActivityKind::Sent)
+    );
+}
+
+#[test]
+fn full_log_overwrites_oldest_entry() {
+    let mut log = log();
+    let total = ACTIVITY_LOG_CAPACITY as u64 + 5;
+    for amount in 1..=total {
+        log.append(sent(amount));
+    }
+    assert_eq!(log.count, total);
+    assert_eq!(log.recent().count(), ACTIVITY_LOG_CAPACITY);
+    assert_eq!(log.recent().next().unwrap().amount, total);
+    assert_eq!(log.recent().last().unwrap().amount, 6); // 1..=5 were overwritten
+}
+
+/// Append `entry` for `user` to a log account holding `data`, returning the
+/// data afterwards
+fn append_at(mut data: Vec<u8>, user: Pubkey, entry: ActivityEntry) -> Result<Vec<u8>> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 1_000_000;
+    let info = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut data,
+        &anchor_test_contract::ID,
+        false,
+        0,
+    );
+    log_activity(&info, user, entry)?;
+    Ok(data)
+}
+
+fn stored(log: &ActivityLog) -> Vec<u8> {
+    let mut data = ActivityLog::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(log));
+    data
+}
+
+#[test]
+fn logging_to_an_empty_address_is_a_no_op() {
+    assert!(append_at(Vec::new(), Pubkey::new_unique(), sent(1))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn logging_appends_to_the_users_log() {
+    let log = log();
+    let data = append_at(stored(&log), log.user, sent(7)).unwrap();
+    let appended: &ActivityLog = bytemuck::from_bytes(&data[8..ActivityLog::LEN]);
+    assert_eq!(appended.count, 1);
+    assert_eq!(appended.recent().next().unwrap().amount, 7);
+}
+
+#[test]
+fn logging_to_another_users_log_fails() {
+    let log = log();
+    let err = append_at(stored(&log), Pubkey::new_unique(), sent(7)).unwrap_err();
+    assert_eq!(err, CustomError::ActivityLogMismatch.into());
+}
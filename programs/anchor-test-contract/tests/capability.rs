@@ -0,0 +1,105 @@
+//! Capability token scopes, expiry and usage caps
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use anchor_test_contract::{
+    capability_scopes, expiry_handler, validate_capability, CapabilityToken, ClockTolerance,
+    CustomError,
+};
+use common::START;
+
+const EXPIRES: i64 = START + 86_400;
+
+fn token(scopes: u64, max_uses: u64) -> CapabilityToken {
+    CapabilityToken {
+        user: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+        service: Pubkey::new_unique(),
+        name: "billing".to_string(),
+        scopes,
+        uses: 0,
+        max_uses,
+        expires_at: EXPIRES,
+        issued_at: START,
+        last_used_at: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn tokens_only_cover_their_scopes() {
+    let mut token = token(capability_scopes::HEARTBEAT, 0);
+    token
+        .use_scope(capability_scopes::HEARTBEAT, START, ClockTolerance::NONE)
+        .unwrap();
+    assert_eq!(
+        token
+            .use_scope(
+                capability_scopes::CREATE_INVOICE_TEMPLATE,
+                START,
+                ClockTolerance::NONE
+            )
+            .unwrap_err(),
+        CustomError::CapabilityNotPermitted.into()
+    );
+    assert_eq!(token.uses, 1); // Refused uses are not counted
+    assert_eq!(token.last_used_at, START);
+}
+
+#[test]
+fn tokens_lapse_at_expiry_and_after_their_last_use() {
+    let mut token = token(capability_scopes::ALL, 2);
+    assert_eq!(
+        token
+            .use_scope(capability_scopes::HEARTBEAT, EXPIRES, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::CapabilityExpired.into()
+    );
+
+    token
+        .use_scope(capability_scopes::HEARTBEAT, START, ClockTolerance::NONE)
+        .unwrap();
+    token
+        .use_scope(
+            capability_scopes::HEARTBEAT,
+            START + 1,
+            ClockTolerance::NONE,
+        )
+        .unwrap();
+    assert_eq!(
+        token
+            .use_scope(
+                capability_scopes::HEARTBEAT,
+                START + 2,
+                ClockTolerance::NONE
+            )
+            .unwrap_err(),
+        CustomError::CapabilityExhausted.into()
+    );
+}
+
+#[test]
+fn tokens_need_a_label_known_scopes_and_a_future_expiry() {
+    validate_capability("billing", capability_scopes::ALL, EXPIRES, START).unwrap();
+    for (name, scopes) in [
+        ("", capability_scopes::HEARTBEAT),
+        ("billing", 0),
+        ("billing", 1 << 63),
+    ] {
+        assert_eq!(
+            validate_capability(name, scopes, EXPIRES, START).unwrap_err(),
+            CustomError::InvalidCapability.into()
+        );
+    }
+    assert_eq!(
+        validate_capability("billing", capability_scopes::HEARTBEAT, START, START).unwrap_err(),
+        CustomError::CapabilityExpired.into()
+    );
+}
+
+#[test]
+fn expired_tokens_can_be_swept() {
+    assert!(expiry_handler(&CapabilityToken::DISCRIMINATOR).is_some());
+}
@@ -0,0 +1,50 @@
+//! Merchant cashback budgeting and suspension
+
+use anchor_test_contract::{Bps, CashbackProgram};
+
+fn program(epoch_budget: u64) -> CashbackProgram {
+    CashbackProgram {
+        rate: Bps(200), // 2%
+        epoch_budget,
+        epoch: 10,
+        spent: 0,
+        suspended: false,
+        total_paid: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn cashback_is_rate_of_payment() {
+    let mut program = program(1_000);
+    let grant = program.grant(10_000, 10, u64::MAX).unwrap();
+    assert_eq!(grant.amount, 200);
+    assert!(!grant.suspended);
+    assert_eq!(program.spent, 200);
+}
+
+#[test]
+fn exhausting_budget_suspends_until_next_epoch() {
+    let mut program = program(300);
+    assert_eq!(program.grant(10_000, 10, u64::MAX).unwrap().amount, 200);
+
+    // Only the remaining budget is paid, and payouts stop
+    let grant = program.grant(10_000, 10, u64::MAX).unwrap();
+    assert_eq!(grant.amount, 100);
+    assert!(grant.suspended);
+    assert_eq!(program.grant(10_000, 10, u64::MAX).unwrap().amount, 0);
+
+    let grant = program.grant(10_000, 11, u64::MAX).unwrap();
+    assert!(grant.resumed);
+    assert_eq!(grant.amount, 200);
+    assert_eq!(program.spent, 200);
+    assert_eq!(program.total_paid, 500);
+}
+
+#[test]
+fn cashback_never_exceeds_treasury() {
+    let mut program = program(1_000);
+    let grant = program.grant(10_000, 10, 50).unwrap();
+    assert_eq!(grant.amount, 50);
+    assert!(!grant.suspended); // A short treasury is not an exhausted budget
+}
@@ -0,0 +1,58 @@
+//! Deterministic failure injection knobs
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{ChaosConfig, ChaosParams, CustomError, MintConfig};
+
+fn chaos(params: ChaosParams) -> ChaosConfig {
+    ChaosConfig {
+        config: Pubkey::new_unique(),
+        params,
+        calls: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn only_the_configured_call_fails() {
+    let mut chaos = chaos(ChaosParams {
+        fail_on_call: 3,
+        ..ChaosParams::default()
+    });
+    chaos.record_call().unwrap();
+    chaos.record_call().unwrap();
+    assert_eq!(chaos.record_call(), Err(CustomError::ChaosInjected.into()));
+    chaos.record_call().unwrap();
+    assert_eq!(chaos.calls, 4);
+
+    let mut calm = self::chaos(ChaosParams::default());
+    for _ in 0..10 {
+        calm.record_call().unwrap();
+    }
+}
+
+#[test]
+fn clock_skew_and_oracle_delay_shift_timestamps() {
+    let chaos = chaos(ChaosParams {
+        fail_on_call: 0,
+        clock_skew_secs: -90,
+        oracle_delay_secs: 600,
+    });
+    assert_eq!(chaos.skewed(1_700_000_000), 1_699_999_910);
+    assert_eq!(chaos.skewed(i64::MIN), i64::MIN);
+
+    let mut mint_config = MintConfig {
+        mint: Pubkey::new_unique(),
+        decimals: 6,
+        deposit_enabled: true,
+        withdraw_enabled: true,
+        max_total_deposits: 0,
+        total_deposited: 0,
+        oracle_feed: Pubkey::default(),
+        bump: 255,
+        last_price: 1_000,
+        last_price_at: 1_700_000_000,
+    };
+    chaos.delay_oracle(&mut mint_config);
+    assert_eq!(mint_config.last_price_at, 1_699_999_400);
+    assert_eq!(mint_config.last_price, 1_000);
+}
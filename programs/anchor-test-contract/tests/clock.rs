@@ -0,0 +1,103 @@
+//! Clock tolerance on deadlines around cluster-clock drift
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    Attestation, ClockTolerance, Commitment, CustomError, ManagerGrant, Seconds,
+    MAX_CLOCK_TOLERANCE,
+};
+
+const DEADLINE: i64 = 1_700_000_000;
+const DRIFT: ClockTolerance = ClockTolerance(Seconds(30));
+
+fn grant(expires_at: i64) -> ManagerGrant {
+    ManagerGrant {
+        user: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+        manager: Pubkey::new_unique(),
+        permissions: 1,
+        expires_at,
+        granted_at: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn deadlines_fall_due_exactly_once_per_instant() {
+    for tolerance in [ClockTolerance::NONE, DRIFT] {
+        let end = tolerance.0.after(DEADLINE);
+        assert!(tolerance.before(end - 1, DEADLINE));
+        assert!(!tolerance.passed(end - 1, DEADLINE));
+        assert!(!tolerance.before(end, DEADLINE));
+        assert!(tolerance.passed(end, DEADLINE)); // No instant is both
+    }
+}
+
+#[test]
+fn a_transaction_landing_just_past_its_deadline_still_counts() {
+    let grant = grant(DEADLINE);
+    assert_eq!(
+        grant
+            .require_permission(1, DEADLINE, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::ManagerGrantExpired.into()
+    );
+    grant.require_permission(1, DEADLINE, DRIFT).unwrap();
+    grant.require_permission(1, DEADLINE + 29, DRIFT).unwrap();
+    assert_eq!(
+        grant
+            .require_permission(1, DEADLINE + 30, DRIFT)
+            .unwrap_err(),
+        CustomError::ManagerGrantExpired.into()
+    );
+}
+
+#[test]
+fn attestations_stay_valid_through_the_tolerance() {
+    let user = Pubkey::new_unique();
+    let attestation = Attestation {
+        issuer: Pubkey::new_unique(),
+        user,
+        claim_type: 1,
+        data: [0; 32],
+        issued_at: 0,
+        expires_at: DEADLINE,
+        bump: 255,
+    };
+    assert!(!attestation.is_valid(&user, 1, DEADLINE, ClockTolerance::NONE));
+    assert!(attestation.is_valid(&user, 1, DEADLINE + 29, DRIFT));
+    assert!(!attestation.is_valid(&user, 1, DEADLINE + 30, DRIFT));
+}
+
+#[test]
+fn reveals_open_only_once_late_commits_are_over() {
+    let owner = Pubkey::new_unique();
+    let mut commitment = Commitment {
+        owner,
+        domain: 7,
+        hash: Commitment::hash_for(&owner, 7, b"bid"),
+        committed_at: 0,
+        reveal_after: DEADLINE,
+        expires_at: DEADLINE + 600,
+        revealed: false,
+        bump: 255,
+    };
+    // Bidding against `reveal_after` is still in time here
+    assert!(DRIFT.before(DEADLINE + 29, DEADLINE));
+    assert_eq!(
+        commitment
+            .verify_reveal(b"bid", DEADLINE + 29, DRIFT)
+            .unwrap_err(),
+        CustomError::RevealTooEarly.into()
+    );
+    // And the reveal deadline moves with it
+    commitment
+        .verify_reveal(b"bid", DEADLINE + 600 + 29, DRIFT)
+        .unwrap();
+}
+
+#[test]
+fn tolerance_saturates_at_the_end_of_time() {
+    let max = ClockTolerance(MAX_CLOCK_TOLERANCE);
+    assert!(max.before(i64::MAX - 1, i64::MAX));
+    assert!(max.passed(i64::MAX, i64::MAX));
+}
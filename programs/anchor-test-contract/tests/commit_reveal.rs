@@ -0,0 +1,77 @@
+//! Reveal rules of `Commitment::verify_reveal`
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{ClockTolerance, Commitment, CustomError};
+
+const OWNER: Pubkey = Pubkey::new_from_array([1; 32]);
+
+fn commitment(preimage: &[u8]) -> Commitment {
+    Commitment {
+        owner: OWNER,
+        domain: 42,
+        hash: Commitment::hash_for(&OWNER, 42, preimage),
+        committed_at: 100,
+        reveal_after: 200,
+        expires_at: 300,
+        revealed: false,
+        bump: 255,
+    }
+}
+
+#[test]
+fn reveal_inside_window_succeeds_once() {
+    let mut c = commitment(b"bid:1000");
+    c.verify_reveal(b"bid:1000", 200, ClockTolerance::NONE)
+        .unwrap();
+    assert!(c.revealed);
+    assert_eq!(
+        c.verify_reveal(b"bid:1000", 250, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::CommitmentAlreadyRevealed.into()
+    );
+}
+
+#[test]
+fn early_reveal_rejected() {
+    let mut c = commitment(b"bid:1000");
+    assert_eq!(
+        c.verify_reveal(b"bid:1000", 199, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::RevealTooEarly.into()
+    );
+    assert!(!c.revealed);
+}
+
+#[test]
+fn expired_reveal_rejected() {
+    let mut c = commitment(b"bid:1000");
+    assert_eq!(
+        c.verify_reveal(b"bid:1000", 300, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::CommitmentExpired.into()
+    );
+}
+
+#[test]
+fn wrong_preimage_rejected() {
+    let mut c = commitment(b"bid:1000");
+    assert_eq!(
+        c.verify_reveal(b"bid:999", 250, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::CommitmentMismatch.into()
+    );
+}
+
+#[test]
+fn hash_binds_owner_and_domain() {
+    let preimage = b"bid:1000";
+    let other = Pubkey::new_from_array([2; 32]);
+    assert_ne!(
+        Commitment::hash_for(&OWNER, 42, preimage),
+        Commitment::hash_for(&other, 42, preimage)
+    );
+    assert_ne!(
+        Commitment::hash_for(&OWNER, 42, preimage),
+        Commitment::hash_for(&OWNER, 43, preimage)
+    );
+}
@@ -0,0 +1,87 @@
+//! Helpers shared by the integration tests
+//!
+//! Each test binary compiles its own copy and uses only part of it.
+#![allow(dead_code)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    AccountMode, ActivityEntry, ActivityKind, ActivityLog, InstructionTag, InvoiceTemplate,
+    Seconds, UserAccount, MAX_GUARDIANS, USER_ACCOUNT_VERSION,
+};
+
+/// Timestamp the tests treat as the start of time
+pub const START: i64 = 1_700_000_000;
+
+/// A key of 32 `byte`s, recognizable in snapshots and failures
+pub fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+/// A current, unguarded account in normal mode holding `balance`
+///
+/// Tests override the fields they exercise with struct update syntax.
+pub fn user(balance: u64) -> UserAccount {
+    UserAccount {
+        authority: Pubkey::new_unique(),
+        name: "alice".to_string(),
+        age: 30,
+        balance,
+        mode: AccountMode::Normal,
+        created_at: 0,
+        is_joint: false,
+        vacation_start: 0,
+        vacation_end: 0,
+        compliance_hold: false,
+        deactivated_at: 0,
+        guardians: [Pubkey::default(); MAX_GUARDIANS],
+        guardian_threshold: 0,
+        guardian_limit: 0,
+        version: USER_ACCOUNT_VERSION,
+        daily_limit: 0,
+        spent_today: 0,
+        window_start: 0,
+        pending_daily_limit: 0,
+        pending_limit_at: 0,
+        watchtower: Pubkey::default(),
+        alert_until: 0,
+        open_obligations: 0,
+        sequence: 0,
+        uncounted_obligations: false,
+    }
+}
+
+/// An empty activity log of a new user
+pub fn log() -> ActivityLog {
+    let mut log: ActivityLog = bytemuck::Zeroable::zeroed();
+    log.user = Pubkey::new_unique();
+    log
+}
+
+/// An activity log whose only entry opened it at `timestamp`
+pub fn opened_at(timestamp: i64) -> ActivityLog {
+    let mut log = log();
+    log.append(ActivityEntry::new(
+        ActivityKind::Opened,
+        InstructionTag::InitActivityLog,
+        Pubkey::default(),
+        0,
+        timestamp,
+    ));
+    log
+}
+
+/// An active daily template of 2_500, first due at `START`
+pub fn template() -> InvoiceTemplate {
+    InvoiceTemplate {
+        merchant: Pubkey::new_unique(),
+        payer: Pubkey::new_unique(),
+        amount: 2_500,
+        interval: Seconds::DAY,
+        next_issue_at: START,
+        issued: 0,
+        unpaid: 0,
+        active: true,
+        bump: 255,
+        version: 0,
+    }
+}
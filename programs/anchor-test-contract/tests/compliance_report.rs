@@ -0,0 +1,132 @@
+//! Compliance report aggregation, flags and digests
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    report_flags, ActivityEntry, ActivityKind, ActivityLog, ComplianceReport, InstructionTag,
+    UserAccount, ACTIVITY_LOG_CAPACITY, REPORTABLE_TRANSFER_THRESHOLD,
+};
+use common::{opened_at, user, START};
+
+const END: i64 = START + 86_400;
+
+fn account(compliance_hold: bool) -> UserAccount {
+    UserAccount {
+        compliance_hold,
+        ..user(1_000_000)
+    }
+}
+
+/// Log opened `OPENED_BEFORE` seconds before the range
+fn log_from_yesterday() -> ActivityLog {
+    opened_at(START - OPENED_BEFORE)
+}
+
+const OPENED_BEFORE: i64 = 86_400;
+
+fn entry(kind: ActivityKind, counterparty: Pubkey, amount: u64, timestamp: i64) -> ActivityEntry {
+    ActivityEntry::new(
+        kind,
+        InstructionTag::TransferTokens,
+        counterparty,
+        amount,
+        timestamp,
+    )
+}
+
+#[test]
+fn reports_aggregate_the_range() {
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut log = log_from_yesterday();
+    log.append(entry(ActivityKind::Received, bob, 1_000, START - 1)); // Before the range
+    log.append(entry(ActivityKind::Received, alice, 300, START));
+    log.append(entry(ActivityKind::Sent, alice, 100, START + 60));
+    log.append(entry(ActivityKind::Sent, bob, 50, START + 120));
+    log.append(entry(
+        ActivityKind::Updated,
+        Pubkey::default(),
+        0,
+        START + 180,
+    ));
+    log.append(entry(ActivityKind::Sent, bob, 9_000, END)); // After the range
+
+    let report = ComplianceReport::compile(&log, &account(false), START, END).unwrap();
+    assert_eq!(report.user, log.user);
+    assert_eq!(report.counterparties, 2);
+    assert_eq!(report.transfers, 3);
+    assert_eq!((report.total_in, report.total_out), (300, 150));
+    assert_eq!(report.flags, 0);
+}
+
+#[test]
+fn reports_flag_what_auditors_ask_about() {
+    let mut log = log_from_yesterday();
+    let counterparty = Pubkey::new_unique();
+    log.append(entry(
+        ActivityKind::Sent,
+        counterparty,
+        REPORTABLE_TRANSFER_THRESHOLD,
+        START,
+    ));
+    log.append(entry(
+        ActivityKind::Deactivated,
+        Pubkey::default(),
+        0,
+        START + 1,
+    ));
+
+    let report = ComplianceReport::compile(&log, &account(true), START, END).unwrap();
+    assert_eq!(
+        report.flags,
+        report_flags::LARGE_TRANSFER | report_flags::DEACTIVATED | report_flags::COMPLIANCE_HOLD
+    );
+
+    // Overwritten history inside the range
+    let mut log = log_from_yesterday();
+    for i in 0..=ACTIVITY_LOG_CAPACITY as i64 {
+        log.append(entry(ActivityKind::Received, counterparty, 1, START + i));
+    }
+    let report = ComplianceReport::compile(&log, &account(false), START, END).unwrap();
+    assert_eq!(report.flags, report_flags::INCOMPLETE_HISTORY);
+    assert_eq!(report.counterparties, 1);
+
+    // History from before the log was opened
+    let report = ComplianceReport::compile(&opened_at(START), &account(false), START, END).unwrap();
+    assert_eq!(report.flags, report_flags::INCOMPLETE_HISTORY);
+}
+
+#[test]
+fn deposits_and_withdrawals_count_without_a_counterparty() {
+    let mut log = log_from_yesterday();
+    log.append(entry(ActivityKind::Received, Pubkey::default(), 500, START));
+    log.append(entry(
+        ActivityKind::Sent,
+        Pubkey::default(),
+        200,
+        START + 60,
+    ));
+    log.append(entry(
+        ActivityKind::Sent,
+        Pubkey::new_unique(),
+        100,
+        START + 120,
+    ));
+
+    let report = ComplianceReport::compile(&log, &account(false), START, END).unwrap();
+    assert_eq!(report.counterparties, 1);
+    assert_eq!(report.transfers, 3);
+    assert_eq!((report.total_in, report.total_out), (500, 300));
+}
+
+#[test]
+fn digests_commit_to_the_flags() {
+    let report =
+        ComplianceReport::compile(&log_from_yesterday(), &account(false), START, END).unwrap();
+    let flagged = ComplianceReport {
+        flags: report_flags::COMPLIANCE_HOLD,
+        ..report.clone()
+    };
+    assert_ne!(report.digest(), flagged.digest());
+    assert_eq!(report.digest(), report.clone().digest());
+}
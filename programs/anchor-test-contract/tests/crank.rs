@@ -0,0 +1,50 @@
+//! Optimistic locking of crank items and crank metrics
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{next_version, CrankMetrics};
+use common::{template, START};
+
+fn metrics() -> CrankMetrics {
+    CrankMetrics {
+        config: Pubkey::new_unique(),
+        cranks: 0,
+        total_delay_secs: 0,
+        max_delay_secs: 0,
+        last_crank_at: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn every_template_write_bumps_the_version() {
+    let mut template = template();
+    let read = template.version;
+
+    template.record_issued().unwrap();
+    assert_ne!(template.version, read); // A crank still holding `read` is stale
+    let issued = template.version;
+
+    template.record_paid().unwrap();
+    assert_ne!(template.version, issued);
+}
+
+#[test]
+fn versions_cannot_wrap_around() {
+    assert_eq!(next_version(7).unwrap(), 8);
+    assert!(next_version(u64::MAX).is_err());
+}
+
+#[test]
+fn metrics_track_how_late_winning_cranks_land() {
+    let mut metrics = metrics();
+    metrics.record(START, START + 30).unwrap();
+    metrics.record(START + 100, START + 190).unwrap();
+    metrics.record(START + 200, START + 195).unwrap(); // Early clocks count as on time
+
+    assert_eq!(metrics.cranks, 3);
+    assert_eq!(metrics.total_delay_secs, 120);
+    assert_eq!(metrics.max_delay_secs, 90);
+    assert_eq!(metrics.last_crank_at, START + 195);
+}
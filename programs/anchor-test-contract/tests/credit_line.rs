@@ -0,0 +1,176 @@
+//! Credit line limits, interest accrual and repayment order
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    draw_guards, guards, open_guards, policy_op, repay_guards, AccountMode, Bps, CreditLine,
+    CustomError, Seconds, TransferPolicy, UserAccount, MAX_GUARDIANS,
+};
+use common::{user, START};
+
+fn credit_line() -> CreditLine {
+    CreditLine {
+        lender: Pubkey::new_unique(),
+        borrower: Pubkey::new_unique(),
+        limit: 100_000,
+        rate: Bps(1_000), // 10% a year
+        drawn: 0,
+        interest: 0,
+        accrued_at: START,
+        frozen: false,
+        bump: 255,
+    }
+}
+
+#[test]
+fn draws_stay_within_the_limit() {
+    let mut line = credit_line();
+    line.draw(60_000, START).unwrap();
+    assert!(line.draw(40_001, START).is_err());
+    line.draw(40_000, START).unwrap();
+    assert_eq!(line.utilization().unwrap(), Bps(10_000));
+}
+
+#[test]
+fn interest_accrues_on_drawn_principal() {
+    let mut line = credit_line();
+    line.draw(50_000, START).unwrap();
+    line.accrue(START + Seconds::YEAR.get()).unwrap();
+    assert_eq!(line.interest, 5_000);
+    assert_eq!(line.outstanding().unwrap(), 55_000);
+}
+
+#[test]
+fn repayments_pay_interest_first() {
+    let mut line = credit_line();
+    line.draw(50_000, START).unwrap();
+    let (interest, principal) = line.repay(20_000, START + Seconds::YEAR.get()).unwrap();
+    assert_eq!((interest, principal), (5_000, 15_000));
+    assert_eq!(line.drawn, 35_000);
+    assert!(line.repay(35_001, START + Seconds::YEAR.get()).is_err());
+}
+
+#[test]
+fn frozen_lines_accept_repayments_only() {
+    let mut line = credit_line();
+    line.draw(10_000, START).unwrap();
+    line.frozen = true;
+    assert!(line.draw(1, START).is_err());
+    line.repay(10_000, START).unwrap();
+    assert_eq!(line.outstanding().unwrap(), 0);
+}
+
+/// A lender needing one of its guardians to move more than 5,000
+fn guarded_lender(guardian: Pubkey) -> UserAccount {
+    let mut guardians = [Pubkey::default(); MAX_GUARDIANS];
+    guardians[0] = guardian;
+    UserAccount {
+        guardians,
+        guardian_threshold: 1,
+        guardian_limit: 5_000,
+        ..user(100_000)
+    }
+}
+
+#[test]
+fn limits_and_draws_above_the_guardian_limit_need_guardians() {
+    let guardian = Pubkey::new_unique();
+    let lender = guarded_lender(guardian);
+    let borrower = user(0);
+
+    assert_eq!(
+        guards::run(&open_guards(&lender, &borrower, 50_000, &[], START)),
+        Err(CustomError::GuardianApprovalRequired.into())
+    );
+    guards::run(&open_guards(&lender, &borrower, 50_000, &[guardian], START)).unwrap();
+
+    guards::run(&draw_guards(&lender, &borrower, 5_000, &[], None, START)).unwrap();
+    assert_eq!(
+        guards::run(&draw_guards(&lender, &borrower, 5_001, &[], None, START)),
+        Err(CustomError::GuardianApprovalRequired.into())
+    );
+    guards::run(&draw_guards(
+        &lender,
+        &borrower,
+        5_001,
+        &[guardian],
+        None,
+        START,
+    ))
+    .unwrap();
+}
+
+#[test]
+fn draws_count_against_the_lenders_daily_limit() {
+    let mut lender = UserAccount {
+        daily_limit: 10_000,
+        ..user(100_000)
+    };
+    let borrower = user(0);
+    guards::run(&draw_guards(&lender, &borrower, 8_000, &[], None, START)).unwrap();
+    lender.record_daily_spend(8_000, START).unwrap();
+
+    assert_eq!(
+        guards::run(&draw_guards(&lender, &borrower, 2_001, &[], None, START)),
+        Err(CustomError::DailyLimitExceeded.into())
+    );
+    guards::run(&draw_guards(&lender, &borrower, 2_000, &[], None, START)).unwrap();
+}
+
+#[test]
+fn deactivating_borrowers_repay_but_do_not_draw() {
+    let lender = user(100_000);
+    let borrower = UserAccount {
+        mode: AccountMode::Deactivating,
+        ..user(10_000)
+    };
+    assert!(guards::run(&draw_guards(&lender, &borrower, 1_000, &[], None, START)).is_err());
+    guards::run(&repay_guards(&borrower, &lender, 1_000, &[], None, START)).unwrap();
+}
+
+#[test]
+fn the_paying_sides_policy_binds_draws_and_repayments() {
+    let mut code = vec![policy_op::MAX_AMOUNT];
+    code.extend_from_slice(&1_000u64.to_le_bytes());
+    let policy = TransferPolicy {
+        user: Pubkey::new_unique(),
+        code,
+        allowlist: vec![],
+        bump: 255,
+    };
+    let lender = user(100_000);
+    let borrower = user(10_000);
+
+    guards::run(&draw_guards(
+        &lender,
+        &borrower,
+        1_000,
+        &[],
+        Some(&policy),
+        START,
+    ))
+    .unwrap();
+    assert_eq!(
+        guards::run(&draw_guards(
+            &lender,
+            &borrower,
+            1_001,
+            &[],
+            Some(&policy),
+            START
+        )),
+        Err(CustomError::PolicyRejected.into())
+    );
+    assert_eq!(
+        guards::run(&repay_guards(
+            &borrower,
+            &lender,
+            1_001,
+            &[],
+            Some(&policy),
+            START
+        )),
+        Err(CustomError::PolicyRejected.into())
+    );
+}
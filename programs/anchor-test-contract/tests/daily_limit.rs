@@ -0,0 +1,62 @@
+//! Daily spending windows and delayed limit increases
+
+mod common;
+
+use anchor_test_contract::{UserAccount, DAILY_LIMIT_INCREASE_DELAY, DAILY_LIMIT_WINDOW};
+use common::{user, START};
+
+fn account(daily_limit: u64) -> UserAccount {
+    UserAccount {
+        daily_limit,
+        ..user(1_000_000)
+    }
+}
+
+#[test]
+fn spending_resets_when_the_window_rolls() {
+    let mut user = account(1_000);
+    user.record_daily_spend(600, START).unwrap();
+    user.record_daily_spend(400, START + 10).unwrap();
+    assert!(user.record_daily_spend(1, START + 20).is_err());
+
+    let next_window = DAILY_LIMIT_WINDOW.after(START);
+    user.record_daily_spend(1_000, next_window).unwrap();
+    assert_eq!(user.window_start, next_window);
+    assert_eq!(user.spent_today, 1_000);
+}
+
+#[test]
+fn lowering_the_limit_applies_at_once() {
+    let mut user = account(1_000);
+    assert_eq!(user.set_daily_limit(100, START), START);
+    assert!(user.record_daily_spend(101, START).is_err());
+
+    // A first limit on an unlimited account is also immediate
+    let mut unlimited = account(0);
+    assert_eq!(unlimited.set_daily_limit(500, START), START);
+    assert_eq!(unlimited.daily_limit, 500);
+}
+
+#[test]
+fn raising_the_limit_waits_out_the_delay() {
+    let mut user = account(1_000);
+    let effective_at = user.set_daily_limit(5_000, START);
+    assert_eq!(effective_at, DAILY_LIMIT_INCREASE_DELAY.after(START));
+    assert!(user.record_daily_spend(2_000, START).is_err());
+
+    user.record_daily_spend(5_000, effective_at).unwrap();
+    assert_eq!(user.daily_limit, 5_000);
+    assert_eq!(user.pending_limit_at, 0);
+}
+
+#[test]
+fn removing_the_limit_is_an_increase() {
+    let mut user = account(1_000);
+    user.set_daily_limit(0, START);
+    assert_eq!(user.daily_limit, 1_000);
+
+    // Setting a lower limit cancels the pending removal
+    user.set_daily_limit(800, START + 60);
+    user.apply_pending_daily_limit(DAILY_LIMIT_INCREASE_DELAY.after(START));
+    assert_eq!(user.daily_limit, 800);
+}
@@ -0,0 +1,61 @@
+//! Deactivation grace period while obligations settle
+
+mod common;
+
+use anchor_test_contract::{require_transfer_modes, AccountMode, CustomError};
+use common::{user, START};
+
+#[test]
+fn accounts_with_nothing_open_lock_at_once() {
+    let mut user = user(1_000);
+    user.begin_deactivation(START);
+
+    assert_eq!(user.mode, AccountMode::Locked);
+    assert_eq!(user.deactivated_at, START);
+    assert!(!user.is_active());
+}
+
+#[test]
+fn the_last_settled_obligation_finalizes_deactivation() {
+    let mut user = user(1_000);
+    user.open_obligation().unwrap();
+    user.open_obligation().unwrap();
+    user.begin_deactivation(START);
+
+    assert!(user.is_deactivating());
+    assert!(!user.is_active());
+    assert!(user.open_obligation().is_err()); // Nothing new while deactivating
+    assert!(!user.close_obligation().unwrap());
+    assert!(user.is_deactivating());
+    assert!(user.close_obligation().unwrap());
+    assert_eq!(user.mode, AccountMode::Locked);
+    assert_eq!(user.deactivated_at, START); // Cooldown runs from the start
+}
+
+#[test]
+fn deactivating_accounts_settle_but_start_nothing_new() {
+    let deactivating = AccountMode::Deactivating;
+    assert!(require_transfer_modes(deactivating, AccountMode::Normal).is_err());
+    assert!(require_transfer_modes(AccountMode::Normal, deactivating).is_err());
+    assert!(require_transfer_modes(
+        deactivating.for_settlement(),
+        AccountMode::Normal.for_settlement()
+    )
+    .is_ok());
+    assert_eq!(
+        AccountMode::Locked.for_settlement(),
+        AccountMode::Locked // Locked accounts settle nothing
+    );
+}
+
+#[test]
+fn settling_more_obligations_than_were_opened_fails() {
+    let mut user = user(1_000);
+    user.open_obligation().unwrap();
+    assert!(!user.close_obligation().unwrap());
+    assert_eq!(
+        user.close_obligation(),
+        Err(CustomError::NoOpenObligation.into())
+    );
+    assert_eq!(user.open_obligations, 0);
+}
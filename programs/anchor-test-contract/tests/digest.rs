@@ -0,0 +1,66 @@
+//! Canonical operation summaries and their digests
+//!
+//! Signing devices rebuild these strings independently, so their exact bytes
+//! are pinned here; any format change must bump `DIGEST_DOMAIN`.
+
+mod common;
+
+use anchor_test_contract::{
+    authority_change_summary, format_amount, operation_digest, require_digest, withdrawal_summary,
+    CustomError,
+};
+use common::key;
+
+#[test]
+fn amounts_are_written_in_whole_tokens() {
+    assert_eq!(format_amount(0, 6), "0");
+    assert_eq!(format_amount(1, 6), "0.000001");
+    assert_eq!(format_amount(1_500_250_000, 6), "1500.25");
+    assert_eq!(format_amount(2_000_000, 6), "2");
+    assert_eq!(format_amount(42, 0), "42");
+    assert_eq!(format_amount(u64::MAX, 9), "18446744073.709551615");
+    assert_eq!(
+        format_amount(u64::MAX, 30),
+        "0.000000000018446744073709551615"
+    );
+}
+
+#[test]
+fn summaries_have_a_fixed_format() {
+    let mint = key(1);
+    let destination = key(2);
+    assert_eq!(
+        withdrawal_summary(7, &mint, &destination, 1_500_250_000, 6),
+        format!("Withdraw 1500.25 tokens of mint {mint} to {destination} (namespace 7)")
+    );
+    assert_eq!(
+        authority_change_summary(7, "compliance", &key(3), &key(4)),
+        format!(
+            "Change compliance authority from {} to {} (namespace 7)",
+            key(3),
+            key(4)
+        )
+    );
+}
+
+#[test]
+fn only_the_matching_digest_is_accepted() {
+    let summary = withdrawal_summary(7, &key(1), &key(2), 1_000, 6);
+    require_digest(&summary, Some(operation_digest(&summary))).unwrap();
+
+    // A different amount, destination or namespace changes the digest
+    for other in [
+        withdrawal_summary(7, &key(1), &key(2), 1_001, 6),
+        withdrawal_summary(7, &key(1), &key(9), 1_000, 6),
+        withdrawal_summary(8, &key(1), &key(2), 1_000, 6),
+    ] {
+        assert_eq!(
+            require_digest(&summary, Some(operation_digest(&other))).unwrap_err(),
+            CustomError::DigestMismatch.into()
+        );
+    }
+    assert_eq!(
+        require_digest(&summary, None).unwrap_err(),
+        CustomError::DigestMismatch.into()
+    );
+}
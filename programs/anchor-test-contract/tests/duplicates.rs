@@ -0,0 +1,148 @@
+//! Property tests over adversarial account lists
+//!
+//! Lists are drawn from a small pool of keys so repeats are common, with a
+//! deterministic generator so any failure reproduces from its case number.
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::solana_program::hash::hash;
+use anchor_test_contract::rand::Rng;
+use anchor_test_contract::{
+    find_duplicate, require_distinct_accounts, require_distinct_writable, CustomError,
+};
+
+/// Generated lists per property
+const CASES: u64 = 500;
+
+/// Longest generated list, a full legacy transaction's worth of accounts
+const MAX_ACCOUNTS: u64 = 64;
+
+fn rng(case: u64) -> Rng {
+    Rng::from_seed(hash(&case.to_le_bytes()).to_bytes())
+}
+
+/// List of up to `MAX_ACCOUNTS` keys drawn from a pool of `pool` keys
+fn adversarial_keys(rng: &mut Rng, pool: &[Pubkey]) -> Vec<Pubkey> {
+    let len = rng.below(MAX_ACCOUNTS + 1).unwrap();
+    (0..len)
+        .map(|_| pool[rng.below(pool.len() as u64).unwrap() as usize])
+        .collect()
+}
+
+/// Reference check: some pair of positions holds the same key
+fn has_repeat(keys: &[Pubkey]) -> bool {
+    (0..keys.len()).any(|i| (i + 1..keys.len()).any(|j| keys[i] == keys[j]))
+}
+
+fn pool(size: usize) -> Vec<Pubkey> {
+    (0..size).map(|_| Pubkey::new_unique()).collect()
+}
+
+#[test]
+fn rejects_exactly_the_lists_with_a_repeat() {
+    for case in 0..CASES {
+        let mut rng = rng(case);
+        let pool = pool(1 + rng.below(MAX_ACCOUNTS).unwrap() as usize);
+        let keys = adversarial_keys(&mut rng, &pool);
+
+        match require_distinct_accounts(&keys) {
+            Ok(()) => assert!(!has_repeat(&keys), "case {case}: repeat accepted"),
+            Err(err) => {
+                assert!(has_repeat(&keys), "case {case}: distinct list rejected");
+                assert_eq!(err, CustomError::DuplicateAccount.into());
+            }
+        }
+    }
+}
+
+#[test]
+fn reported_duplicate_really_repeats() {
+    for case in 0..CASES {
+        let mut rng = rng(case);
+        let keys = adversarial_keys(&mut rng, &pool(8));
+        if let Some(key) = find_duplicate(&keys) {
+            assert!(
+                keys.iter().filter(|k| **k == key).count() >= 2,
+                "case {case}: {key} reported but appears once"
+            );
+        }
+    }
+}
+
+#[test]
+fn verdict_does_not_depend_on_order() {
+    for case in 0..CASES {
+        let mut rng = rng(case);
+        let mut keys = adversarial_keys(&mut rng, &pool(16));
+        let verdict = find_duplicate(&keys).is_some();
+
+        // Fisher-Yates shuffle, then reverse
+        for i in (1..keys.len()).rev() {
+            let j = rng.below(i as u64 + 1).unwrap() as usize;
+            keys.swap(i, j);
+        }
+        assert_eq!(find_duplicate(&keys).is_some(), verdict, "case {case}");
+        keys.reverse();
+        assert_eq!(find_duplicate(&keys).is_some(), verdict, "case {case}");
+    }
+}
+
+#[test]
+fn a_single_repeat_anywhere_is_caught() {
+    for case in 0..CASES {
+        let mut rng = rng(case);
+        let mut keys = pool(2 + rng.below(MAX_ACCOUNTS - 1).unwrap() as usize);
+        assert_eq!(find_duplicate(&keys), None);
+
+        // Smuggle one key into a second position, e.g. the same receiver twice
+        let from = rng.below(keys.len() as u64).unwrap() as usize;
+        let mut to = rng.below(keys.len() as u64).unwrap() as usize;
+        if to == from {
+            to = (to + 1) % keys.len();
+        }
+        keys[to] = keys[from];
+        assert_eq!(find_duplicate(&keys), Some(keys[from]), "case {case}");
+    }
+}
+
+#[test]
+fn only_writable_repeats_are_rejected() {
+    let owner = Pubkey::new_unique();
+    for case in 0..CASES {
+        let mut rng = rng(case);
+        let pool = pool(8);
+        let keys = adversarial_keys(&mut rng, &pool);
+        let writable: Vec<bool> = pool.iter().map(|_| rng.below(2).unwrap() == 1).collect();
+
+        // A transaction marks every occurrence of an account alike
+        let mut lamports = vec![0u64; keys.len()];
+        let mut data: Vec<Vec<u8>> = vec![Vec::new(); keys.len()];
+        let infos: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut().zip(data.iter_mut()))
+            .map(|(key, (lamports, data))| {
+                let index = pool.iter().position(|k| k == key).unwrap();
+                AccountInfo::new(
+                    key,
+                    false,
+                    writable[index],
+                    lamports,
+                    data,
+                    &owner,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+
+        let writable_keys: Vec<Pubkey> = keys
+            .iter()
+            .filter(|key| writable[pool.iter().position(|k| k == *key).unwrap()])
+            .copied()
+            .collect();
+        assert_eq!(
+            require_distinct_writable(&infos).is_ok(),
+            !has_repeat(&writable_keys),
+            "case {case}"
+        );
+    }
+}
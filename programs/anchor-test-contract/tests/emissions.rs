@@ -0,0 +1,220 @@
+//! Emission curves at their boundaries, and the supply cap over whole runs
+//!
+//! Every curve is evaluated at the first and last periods it changes at and
+//! at the ends of the u64 range; a simulated run must emit exactly the cap,
+//! never more, whatever the curve.
+
+use anchor_test_contract::{
+    emissions_for, CustomError, EmissionCurve, EmissionInputs, EmissionSchedule, BPS_DENOMINATOR,
+};
+
+/// Periods at and around the ends of the u64 range
+const PERIODS: [u64; 6] = [0, 1, 2, 63, u64::MAX - 1, u64::MAX];
+
+fn at(curve: EmissionCurve, period: u64) -> u64 {
+    curve.emission_at(period, 0).unwrap()
+}
+
+fn uncapped(curve: EmissionCurve) -> EmissionSchedule {
+    EmissionSchedule {
+        curve,
+        supply_cap: u64::MAX,
+    }
+}
+
+/// Emit period after period until the schedule stops; returns (total, periods)
+fn run(schedule: &EmissionSchedule, staked: u64, max_periods: u64) -> (u64, u64) {
+    let mut inputs = EmissionInputs { emitted: 0, staked };
+    for period in 0..max_periods {
+        let amount = emissions_for(schedule, period, &inputs).unwrap();
+        if amount == 0 && inputs.emitted == schedule.supply_cap {
+            return (inputs.emitted, period);
+        }
+        inputs.emitted = inputs
+            .emitted
+            .checked_add(amount)
+            .expect("cap exceeded u64");
+        assert!(
+            inputs.emitted <= schedule.supply_cap,
+            "period {period} broke the cap"
+        );
+    }
+    (inputs.emitted, max_periods)
+}
+
+#[test]
+fn constant_emits_the_same_amount_forever() {
+    let curve = EmissionCurve::Constant { per_period: 500 };
+    for period in PERIODS {
+        assert_eq!(at(curve, period), 500);
+    }
+}
+
+#[test]
+fn linear_decay_reaches_zero_on_schedule_and_stays_there() {
+    let curve = EmissionCurve::LinearDecay {
+        initial: 1_000,
+        decrement: 100,
+    };
+    assert_eq!(at(curve, 0), 1_000);
+    assert_eq!(at(curve, 9), 100);
+    assert_eq!(at(curve, 10), 0);
+    assert_eq!(at(curve, 11), 0);
+    assert_eq!(at(curve, u64::MAX), 0); // decrement * period saturates
+
+    let flat = EmissionCurve::LinearDecay {
+        initial: 1_000,
+        decrement: 0,
+    };
+    assert_eq!(at(flat, u64::MAX), 1_000);
+}
+
+#[test]
+fn halving_steps_exactly_at_each_boundary() {
+    let curve = EmissionCurve::Halving {
+        initial: 1 << 20,
+        halving_periods: 4,
+    };
+    assert_eq!(at(curve, 0), 1 << 20);
+    assert_eq!(at(curve, 3), 1 << 20);
+    assert_eq!(at(curve, 4), 1 << 19);
+    assert_eq!(at(curve, 7), 1 << 19);
+    assert_eq!(at(curve, 8), 1 << 18);
+    assert_eq!(at(curve, 4 * 20), 1);
+    assert_eq!(at(curve, 4 * 21), 0);
+
+    // 63 halvings leave one token of u64::MAX, 64 or more leave none
+    let every_period = EmissionCurve::Halving {
+        initial: u64::MAX,
+        halving_periods: 1,
+    };
+    assert_eq!(at(every_period, 63), 1);
+    assert_eq!(at(every_period, 64), 0);
+    assert_eq!(at(every_period, u64::MAX), 0);
+}
+
+#[test]
+fn target_apr_pays_the_rate_on_the_stake() {
+    let curve = EmissionCurve::TargetApr {
+        apr_bps: 1_000,
+        periods_per_year: 365,
+    };
+    assert_eq!(curve.emission_at(0, 365_000).unwrap(), 100); // 10% / 365
+    assert_eq!(curve.emission_at(u64::MAX, 365_000).unwrap(), 100); // Period-independent
+    assert_eq!(curve.emission_at(0, 0).unwrap(), 0);
+    assert_eq!(curve.emission_at(0, 3_649).unwrap(), 0); // Rounds down
+
+    // A 100% APR on the whole supply fits, above it overflows
+    let full = EmissionCurve::TargetApr {
+        apr_bps: BPS_DENOMINATOR,
+        periods_per_year: 1,
+    };
+    assert_eq!(full.emission_at(0, u64::MAX).unwrap(), u64::MAX);
+    let double = EmissionCurve::TargetApr {
+        apr_bps: 2 * BPS_DENOMINATOR,
+        periods_per_year: 1,
+    };
+    assert_eq!(
+        double.emission_at(0, u64::MAX).unwrap_err(),
+        CustomError::MathOverflow.into()
+    );
+}
+
+#[test]
+fn the_last_period_is_trimmed_to_the_cap() {
+    let schedule = EmissionSchedule {
+        curve: EmissionCurve::Constant { per_period: 300 },
+        supply_cap: 1_000,
+    };
+    let after = |emitted| emissions_for(&schedule, 3, &EmissionInputs { emitted, staked: 0 });
+    assert_eq!(after(600).unwrap(), 300);
+    assert_eq!(after(900).unwrap(), 100);
+    assert_eq!(after(1_000).unwrap(), 0);
+    assert_eq!(after(u64::MAX).unwrap(), 0); // Already past the cap emits nothing
+}
+
+#[test]
+fn runs_emit_exactly_the_cap_and_then_stop() {
+    let curves = [
+        EmissionCurve::Constant { per_period: 7 },
+        EmissionCurve::LinearDecay {
+            initial: 1_000,
+            decrement: 3,
+        },
+        EmissionCurve::Halving {
+            initial: 4_096,
+            halving_periods: 10,
+        },
+        EmissionCurve::TargetApr {
+            apr_bps: 500,
+            periods_per_year: 12,
+        },
+    ];
+    for curve in curves {
+        let schedule = EmissionSchedule {
+            curve,
+            supply_cap: 10_000,
+        };
+        schedule.validate().unwrap();
+        let (total, periods) = run(&schedule, 1_000_000, 100_000);
+        assert_eq!(
+            total, 10_000,
+            "{curve:?} stopped short after {periods} periods"
+        );
+    }
+
+    // A cap of zero emits nothing from the start
+    let closed = EmissionSchedule {
+        curve: EmissionCurve::Constant { per_period: 7 },
+        supply_cap: 0,
+    };
+    assert_eq!(run(&closed, 0, 10), (0, 0));
+}
+
+#[test]
+fn decaying_curves_below_the_cap_stop_at_their_own_total() {
+    // 1000 + 900 + ... + 100 = 5500, well under an unbounded cap
+    let decay = uncapped(EmissionCurve::LinearDecay {
+        initial: 1_000,
+        decrement: 100,
+    });
+    let (total, _) = run(&decay, 0, 1_000);
+    assert_eq!(total, 5_500);
+
+    // Two periods at each power of two from 2^10 down to 1 sum to 2^12 - 2
+    let halving = uncapped(EmissionCurve::Halving {
+        initial: 1 << 10,
+        halving_periods: 2,
+    });
+    let (total, _) = run(&halving, 0, 1_000);
+    assert_eq!(total, (1 << 12) - 2);
+}
+
+#[test]
+fn degenerate_curves_are_rejected() {
+    for curve in [
+        EmissionCurve::Halving {
+            initial: 1,
+            halving_periods: 0,
+        },
+        EmissionCurve::TargetApr {
+            apr_bps: 1,
+            periods_per_year: 0,
+        },
+    ] {
+        let schedule = uncapped(curve);
+        assert_eq!(
+            schedule.validate().unwrap_err(),
+            CustomError::InvalidEmissionSchedule.into()
+        );
+        assert_eq!(
+            curve.emission_at(1, 1).unwrap_err(),
+            CustomError::InvalidEmissionSchedule.into()
+        );
+    }
+    EmissionSchedule::default().validate().unwrap();
+    assert_eq!(
+        emissions_for(&EmissionSchedule::default(), 0, &EmissionInputs::default()).unwrap(),
+        0
+    );
+}
@@ -0,0 +1,195 @@
+//! Each fee strategy checked on its own, then the invariants they share
+//!
+//! Every strategy must price the amounts at the ends of the u64 range
+//! without panicking, and no fee may exceed the amount it is charged on.
+
+use anchor_test_contract::{
+    fee_for, utilization, Bps, CustomError, FeeInputs, FeeStrategy, FeeTier, BPS_DENOMINATOR,
+    FEE_TIERS,
+};
+
+/// Amounts at and around the ends of the u64 range
+const EDGES: [u64; 6] = [0, 1, 99, BPS_DENOMINATOR, u64::MAX - 1, u64::MAX];
+
+fn inputs(amount: u64, base_bps: u64, utilization_bps: u64) -> FeeInputs {
+    FeeInputs {
+        amount,
+        base_rate: Bps(base_bps),
+        utilization: Bps(utilization_bps),
+    }
+}
+
+fn fee(strategy: FeeStrategy, amount: u64) -> u64 {
+    fee_for(&strategy, &inputs(amount, 100, 0)).unwrap()
+}
+
+/// 1% up to 10k, 0.5% from 10k, 0.1% from 1M
+fn tiered() -> FeeStrategy {
+    FeeStrategy::Tiered {
+        tiers: [
+            FeeTier {
+                min_amount: 0,
+                fee_bps: 100,
+            },
+            FeeTier {
+                min_amount: 10_000,
+                fee_bps: 50,
+            },
+            FeeTier {
+                min_amount: 1_000_000,
+                fee_bps: 10,
+            },
+            FeeTier::UNUSED,
+        ],
+    }
+}
+
+#[test]
+fn proportional_charges_the_config_rate() {
+    assert_eq!(fee(FeeStrategy::Proportional, 10_000), 100);
+    assert_eq!(fee(FeeStrategy::Proportional, 99), 0); // Rounds down
+    assert_eq!(
+        fee_for(&FeeStrategy::Proportional, &inputs(10_000, 250, 0)).unwrap(),
+        250
+    );
+}
+
+#[test]
+fn flat_charges_the_same_fee_capped_at_the_amount() {
+    let flat = FeeStrategy::Flat { fee: 500 };
+    assert_eq!(fee(flat, 1_000_000), 500);
+    assert_eq!(fee(flat, u64::MAX), 500);
+    assert_eq!(fee(flat, 200), 200);
+    assert_eq!(fee(flat, 0), 0);
+}
+
+#[test]
+fn tiered_applies_the_highest_tier_reached() {
+    assert_eq!(fee(tiered(), 9_999), 99);
+    assert_eq!(fee(tiered(), 10_000), 50);
+    assert_eq!(fee(tiered(), 999_999), 4_999);
+    assert_eq!(fee(tiered(), 1_000_000), 1_000);
+
+    // Unused tiers never apply, even to the largest amount
+    assert_eq!(fee(tiered(), u64::MAX), u64::MAX / 1_000);
+
+    // Amounts below the first threshold are free
+    let mut tiers = [FeeTier::UNUSED; FEE_TIERS];
+    tiers[0] = FeeTier {
+        min_amount: 100,
+        fee_bps: 100,
+    };
+    assert_eq!(fee(FeeStrategy::Tiered { tiers }, 99), 0);
+}
+
+#[test]
+fn utilization_interpolates_between_its_bounds() {
+    let strategy = FeeStrategy::Utilization {
+        min_bps: 10,
+        max_bps: 110,
+    };
+    let at = |utilization_bps| fee_for(&strategy, &inputs(1_000_000, 0, utilization_bps)).unwrap();
+    assert_eq!(at(0), 1_000);
+    assert_eq!(at(5_000), 6_000);
+    assert_eq!(at(BPS_DENOMINATOR), 11_000);
+    assert_eq!(at(u64::MAX), 11_000); // Utilization is capped at 100%
+}
+
+#[test]
+fn utilization_is_capped_and_idle_without_capacity() {
+    assert_eq!(utilization(0, 0), Bps(0));
+    assert_eq!(utilization(u64::MAX, 0), Bps(0));
+    assert_eq!(utilization(50, 200), Bps(2_500));
+    assert_eq!(utilization(u64::MAX, 1), Bps::ONE_HUNDRED_PERCENT);
+}
+
+#[test]
+fn discounted_waives_a_share_of_the_config_rate() {
+    let half = FeeStrategy::Discounted {
+        discount_bps: 5_000,
+    };
+    assert_eq!(fee(half, 10_000), 50);
+
+    let free = FeeStrategy::Discounted {
+        discount_bps: BPS_DENOMINATOR,
+    };
+    assert_eq!(fee(free, u64::MAX), 0);
+
+    let none = FeeStrategy::Discounted { discount_bps: 0 };
+    assert_eq!(fee(none, 10_000), fee(FeeStrategy::Proportional, 10_000));
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let mut descending = [FeeTier::UNUSED; FEE_TIERS];
+    descending[0].min_amount = 10;
+    descending[1] = FeeTier {
+        min_amount: 5,
+        fee_bps: 0,
+    };
+    let mut above_100_percent = [FeeTier::UNUSED; FEE_TIERS];
+    above_100_percent[0] = FeeTier {
+        min_amount: 0,
+        fee_bps: BPS_DENOMINATOR + 1,
+    };
+
+    for strategy in [
+        FeeStrategy::Tiered { tiers: descending },
+        FeeStrategy::Tiered {
+            tiers: above_100_percent,
+        },
+        FeeStrategy::Utilization {
+            min_bps: 20,
+            max_bps: 10,
+        },
+        FeeStrategy::Utilization {
+            min_bps: 0,
+            max_bps: BPS_DENOMINATOR + 1,
+        },
+        FeeStrategy::Discounted {
+            discount_bps: BPS_DENOMINATOR + 1,
+        },
+    ] {
+        assert_eq!(
+            strategy.validate().unwrap_err(),
+            CustomError::InvalidFeeStrategy.into(),
+            "{strategy:?}"
+        );
+    }
+    for strategy in [
+        FeeStrategy::Proportional,
+        FeeStrategy::Flat { fee: u64::MAX },
+        tiered(),
+    ] {
+        strategy.validate().unwrap();
+    }
+}
+
+#[test]
+fn valid_strategies_never_charge_more_than_the_amount() {
+    let strategies = [
+        FeeStrategy::Proportional,
+        FeeStrategy::Flat { fee: u64::MAX },
+        tiered(),
+        FeeStrategy::Utilization {
+            min_bps: 0,
+            max_bps: BPS_DENOMINATOR,
+        },
+        FeeStrategy::Discounted { discount_bps: 1 },
+    ];
+    for strategy in strategies {
+        strategy.validate().unwrap();
+        for amount in EDGES {
+            for base_bps in [0, 1, BPS_DENOMINATOR] {
+                for utilization_bps in [0, BPS_DENOMINATOR, u64::MAX] {
+                    let charged =
+                        fee_for(&strategy, &inputs(amount, base_bps, utilization_bps)).unwrap();
+                    assert!(
+                        charged <= amount,
+                        "{strategy:?} charged {charged} on {amount}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Configuration fingerprints integrators pin against
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    build_features, config_fingerprint, instruction_flags, EmissionCurve, EmissionSchedule,
+    FeeStrategy, GlobalConfig, RampedParam,
+};
+
+fn config() -> GlobalConfig {
+    GlobalConfig {
+        admin: Pubkey::new_from_array([2; 32]),
+        disabled_instructions: 0,
+        bump: 254,
+        fee_rate_bps: RampedParam {
+            old: 100,
+            new: 100,
+            start: 0,
+            end: 0,
+        },
+        circuit_breaker_multiple_bps: 30_000,
+        max_price_deviation_bps: 500,
+        price_deviation_window_secs: 3_600,
+        lookup_table: Pubkey::default(),
+        namespace: 7,
+        fee_strategy: FeeStrategy::Proportional,
+        emission_schedule: EmissionSchedule {
+            curve: EmissionCurve::Constant { per_period: 1_000 },
+            supply_cap: 1_000_000,
+        },
+        compliance: Pubkey::new_from_array([4; 32]),
+        paused: false,
+        reactivation_cooldown_secs: 86_400,
+        clock_tolerance_secs: 30,
+        instruction_sunsets: [0; 8],
+    }
+}
+
+#[test]
+fn every_governed_setting_moves_the_fingerprint() {
+    let pinned = config_fingerprint(&config(), 0, Some(&[]));
+    assert_eq!(config_fingerprint(&config(), 0, Some(&[])), pinned);
+
+    let mut changed = config();
+    changed.disabled_instructions = instruction_flags::TRANSFER_TOKENS;
+    assert_ne!(config_fingerprint(&changed, 0, Some(&[])), pinned);
+
+    let mut changed = config();
+    changed.paused = true;
+    assert_ne!(config_fingerprint(&changed, 0, Some(&[])), pinned);
+
+    let mut changed = config();
+    changed.fee_strategy = FeeStrategy::Flat { fee: 1 };
+    assert_ne!(config_fingerprint(&changed, 0, Some(&[])), pinned);
+
+    let features = build_features::SHADOW_FEES;
+    assert_ne!(config_fingerprint(&config(), features, Some(&[])), pinned);
+
+    let program = Pubkey::new_from_array([9; 32]);
+    assert_ne!(config_fingerprint(&config(), 0, Some(&[program])), pinned);
+}
+
+#[test]
+fn the_registry_counts_by_contents_not_order() {
+    let (a, b) = (
+        Pubkey::new_from_array([1; 32]),
+        Pubkey::new_from_array([2; 32]),
+    );
+    assert_eq!(
+        config_fingerprint(&config(), 0, Some(&[a, b])),
+        config_fingerprint(&config(), 0, Some(&[b, a]))
+    );
+    // A namespace without a registry never matches one with an empty registry
+    assert_ne!(
+        config_fingerprint(&config(), 0, None),
+        config_fingerprint(&config(), 0, Some(&[]))
+    );
+}
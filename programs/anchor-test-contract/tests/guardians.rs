@@ -0,0 +1,91 @@
+//! Guardian sets and the approvals they require
+//!
+//! Approvals count guardians, not signatures: a guardian signing twice, or a
+//! signer who is not a guardian, adds nothing towards the threshold.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    guardian_approvals, validate_guardians, CustomError, UserAccount, MAX_GUARDIANS,
+};
+use common::{key, user};
+
+fn guarded(guardians: &[Pubkey], threshold: u8, limit: u64) -> UserAccount {
+    let mut slots = [Pubkey::default(); MAX_GUARDIANS];
+    slots[..guardians.len()].copy_from_slice(guardians);
+    UserAccount {
+        authority: key(1),
+        guardians: slots,
+        guardian_threshold: threshold,
+        guardian_limit: limit,
+        ..user(1_000_000)
+    }
+}
+
+#[test]
+fn repeated_and_foreign_signers_do_not_count() {
+    let guardians = [key(2), key(3), key(4)];
+    assert_eq!(guardian_approvals(&guardians, &[key(2), key(2), key(2)]), 1);
+    assert_eq!(guardian_approvals(&guardians, &[key(9), key(1)]), 0);
+    assert_eq!(guardian_approvals(&guardians, &[key(4), key(2), key(9)]), 2);
+}
+
+#[test]
+fn only_transfers_above_the_limit_need_guardians() {
+    let user = guarded(&[key(2), key(3), key(4)], 2, 5_000);
+    assert_eq!(user.guardian_keys(), &[key(2), key(3), key(4)]);
+
+    user.require_guardian_approval(5_000, &[]).unwrap();
+    assert_eq!(
+        user.require_guardian_approval(5_001, &[key(2), key(2)])
+            .unwrap_err(),
+        CustomError::GuardianApprovalRequired.into()
+    );
+    user.require_guardian_approval(5_001, &[key(2), key(3)])
+        .unwrap();
+
+    // Unguarded accounts never need approval
+    guarded(&[], 0, 0)
+        .require_guardian_approval(u64::MAX, &[])
+        .unwrap();
+}
+
+#[test]
+fn guarded_accounts_reactivate_only_with_a_quorum() {
+    // The authority alone cannot undo a guardian lock
+    let user = guarded(&[key(2), key(3), key(4)], 2, 5_000);
+    assert_eq!(
+        user.require_reactivation_approval(&[key(1), key(2)])
+            .unwrap_err(),
+        CustomError::GuardianApprovalRequired.into()
+    );
+    user.require_reactivation_approval(&[key(3), key(4)])
+        .unwrap();
+
+    guarded(&[], 0, 0)
+        .require_reactivation_approval(&[])
+        .unwrap();
+}
+
+#[test]
+fn malformed_guardian_sets_are_rejected() {
+    let authority = key(1);
+    validate_guardians(&authority, &[], 0).unwrap();
+    validate_guardians(&authority, &[key(2), key(3)], 2).unwrap();
+
+    for (guardians, threshold) in [
+        (vec![key(2), key(2)], 1),                 // Repeated guardian
+        (vec![key(1), key(2)], 1),                 // Authority as its own guardian
+        (vec![Pubkey::default()], 1),              // Unused-slot marker
+        (vec![key(2)], 2),                         // Threshold above the set
+        (vec![key(2)], 0),                         // Guardians without a threshold
+        (vec![], 1),                               // Threshold without guardians
+        (vec![key(2), key(3), key(4), key(5)], 2), // More than MAX_GUARDIANS
+    ] {
+        assert_eq!(
+            validate_guardians(&authority, &guardians, threshold).unwrap_err(),
+            CustomError::InvalidGuardianSet.into()
+        );
+    }
+}
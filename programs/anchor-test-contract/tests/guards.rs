@@ -0,0 +1,65 @@
+//! Guard lists checked in order with their original errors
+
+mod common;
+
+use anchor_test_contract::{guards, CustomError, Guard};
+use common::{user, START};
+
+#[test]
+fn the_first_failing_guard_decides_the_error() {
+    let mut user = user(100);
+    user.is_joint = true;
+    let guards = [
+        Guard::Positive(500),
+        Guard::Funded(&user, 500),
+        Guard::NotJoint(&user),
+    ];
+    assert_eq!(
+        guards::run(&guards),
+        Err(CustomError::InsufficientFunds.into())
+    );
+    assert_eq!(
+        guards::run(&guards[2..]),
+        Err(CustomError::JointTransferRequired.into())
+    );
+    assert_eq!(guards::run(&[]), Ok(()));
+}
+
+#[test]
+fn frozen_accounts_report_why() {
+    let mut user = user(100);
+    user.vacation_start = START - 10;
+    user.vacation_end = START + 10;
+    user.alert_until = START + 10;
+    assert_eq!(
+        Guard::NotFrozen(&user, START).check(),
+        Err(CustomError::VacationModeActive.into())
+    );
+
+    user.vacation_end = START;
+    assert_eq!(
+        Guard::NotFrozen(&user, START).check(),
+        Err(CustomError::WatchtowerAlertActive.into())
+    );
+    assert_eq!(Guard::NotFrozen(&user, START + 10).check(), Ok(()));
+}
+
+#[test]
+fn daily_limit_guards_leave_the_count_alone() {
+    let mut user = user(1_000);
+    user.daily_limit = 300;
+    user.record_daily_spend(200, START).unwrap();
+
+    Guard::DailyLimit(&user, 100, START).check().unwrap();
+    assert_eq!(
+        Guard::DailyLimit(&user, 101, START).check(),
+        Err(CustomError::DailyLimitExceeded.into())
+    );
+    assert_eq!(user.spent_today, 200);
+
+    // A due increase and a finished window both count as the next spend would
+    user.set_daily_limit(1_000, START);
+    let later = START + 2 * 86_400;
+    Guard::DailyLimit(&user, 1_000, later).check().unwrap();
+    user.record_daily_spend(1_000, later).unwrap();
+}
@@ -0,0 +1,76 @@
+//! Account health view
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{health_flags, AccountHealth, Bps, CreditLine, Seconds};
+use common::{user, START};
+
+const COOLDOWN: Seconds = Seconds::DAY;
+
+fn line(lender: Pubkey, borrower: Pubkey, drawn: u64) -> CreditLine {
+    CreditLine {
+        lender,
+        borrower,
+        limit: 10_000,
+        rate: Bps(0),
+        drawn,
+        interest: 25,
+        accrued_at: START,
+        frozen: false,
+        bump: 255,
+    }
+}
+
+#[test]
+fn a_fresh_account_is_active_and_unlimited() {
+    let health = AccountHealth::of(&user(1_000), Pubkey::new_unique(), COOLDOWN, START);
+
+    assert_eq!(health.flags, health_flags::ACTIVE);
+    assert_eq!(health.daily_remaining, None);
+    assert_eq!(health.reactivation_at, 0);
+    assert_eq!(health.vacation_end, 0);
+}
+
+#[test]
+fn status_limits_and_timers_are_reported() {
+    let mut account = user(1_000);
+    account.daily_limit = 500;
+    account.spent_today = 200;
+    account.window_start = START - 60;
+    account.alert_until = START + 3_600;
+    account.open_obligations = 1;
+    account.begin_deactivation(START - 10);
+
+    let health = AccountHealth::of(&account, Pubkey::new_unique(), COOLDOWN, START);
+    assert!(health.has(health_flags::DEACTIVATING));
+    assert!(health.has(health_flags::ALERTED));
+    assert!(!health.has(health_flags::ACTIVE));
+    assert!(!health.has(health_flags::CAN_REACTIVATE));
+    assert_eq!(health.open_obligations, 1);
+    assert_eq!(health.daily_remaining, Some(300));
+    assert_eq!(health.alert_until, START + 3_600);
+    assert_eq!(health.reactivation_at, COOLDOWN.after(START - 10));
+
+    let later = AccountHealth::of(&account, health.user, COOLDOWN, health.reactivation_at);
+    assert!(later.has(health_flags::CAN_REACTIVATE));
+    assert_eq!(later.alert_until, 0); // Alert over
+}
+
+#[test]
+fn credit_lines_count_on_the_accounts_side() {
+    let key = Pubkey::new_unique();
+    let mut health = AccountHealth::of(&user(1_000), key, COOLDOWN, START);
+
+    health
+        .add_credit_line(&line(Pubkey::new_unique(), key, 1_000))
+        .unwrap();
+    health
+        .add_credit_line(&line(key, Pubkey::new_unique(), 400))
+        .unwrap();
+    assert_eq!(health.borrowed, 1_025);
+    assert_eq!(health.lent, 425);
+
+    let unrelated = line(Pubkey::new_unique(), Pubkey::new_unique(), 1);
+    assert!(health.add_credit_line(&unrelated).is_err());
+}
@@ -0,0 +1,59 @@
+//! Interface constants and encodings third parties build against
+//!
+//! The transfer hook is also checked end to end against the reference hook
+//! program; the price adapter has no reference program, so its encoding is
+//! pinned here.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_test_contract::price_adapter::{self, PriceReport, GET_PRICE};
+use anchor_test_contract::transfer_hook::ON_TRANSFER;
+use anchor_test_contract::CustomError;
+use common::key;
+
+fn anchor_discriminator(name: &str) -> [u8; 8] {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn discriminators_are_those_of_the_instruction_names() {
+    assert_eq!(ON_TRANSFER, anchor_discriminator("on_transfer"));
+    assert_eq!(GET_PRICE, anchor_discriminator("get_price"));
+}
+
+#[test]
+fn get_price_reads_only_the_feed() {
+    let ix = price_adapter::get_price(key(1), key(2));
+    assert_eq!(ix.program_id, key(1));
+    assert_eq!(ix.accounts, vec![AccountMeta::new_readonly(key(2), false)]);
+    assert_eq!(ix.data, GET_PRICE);
+}
+
+#[test]
+fn reports_are_accepted_only_from_the_adapter() {
+    let report = PriceReport {
+        price: 12_345,
+        expo: -2,
+        published_at: 1_700_000_000,
+    };
+    let data = report.try_to_vec().unwrap();
+    assert_eq!(data.len(), 8 + 4 + 8);
+
+    let adapter = key(1);
+    assert_eq!(
+        price_adapter::read_report(&adapter, &(adapter, data.clone())).unwrap(),
+        report
+    );
+    assert_eq!(
+        price_adapter::read_report(&adapter, &(key(9), data.clone())).unwrap_err(),
+        CustomError::InvalidPriceReport.into()
+    );
+    assert_eq!(
+        price_adapter::read_report(&adapter, &(adapter, data[..4].to_vec())).unwrap_err(),
+        CustomError::InvalidPriceReport.into()
+    );
+}
@@ -0,0 +1,130 @@
+//! Recurring invoice template scheduling, auto-cancellation and factoring
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use anchor_test_contract::{
+    expiry_handler, CustomError, Invoice, InvoiceStatus, Seconds, INVOICE_PAYMENT_WINDOW,
+    MAX_UNPAID_INVOICES,
+};
+use common::{template, START};
+
+#[test]
+fn invoices_fall_due_once_per_interval() {
+    let mut template = template();
+    assert!(template.is_due(START));
+
+    template.record_issued().unwrap();
+    assert!(!template.is_due(START));
+    assert!(!template.is_due(START + Seconds::DAY.get() - 1));
+    assert!(template.is_due(START + Seconds::DAY.get()));
+}
+
+#[test]
+fn late_cranks_keep_the_schedule() {
+    let mut template = template();
+    template.record_issued().unwrap();
+    template.record_paid().unwrap();
+
+    // Two periods late: both remaining invoices are due at once
+    let now = START + 2 * Seconds::DAY.get();
+    template.record_issued().unwrap();
+    assert!(template.is_due(now));
+    template.record_issued().unwrap();
+    assert!(!template.is_due(now));
+    assert_eq!(template.issued, 3);
+}
+
+#[test]
+fn templates_cancel_after_too_many_unpaid_invoices() {
+    let mut template = template();
+    for _ in 1..MAX_UNPAID_INVOICES {
+        assert!(!template.record_issued().unwrap());
+    }
+    assert!(template.active);
+
+    assert!(template.record_issued().unwrap());
+    assert!(!template.active);
+    assert!(!template.is_due(i64::MAX));
+    assert_eq!(template.unpaid, MAX_UNPAID_INVOICES);
+
+    // Paying afterwards settles the debt but does not revive the template
+    template.record_paid().unwrap();
+    assert!(!template.active);
+}
+
+fn invoice() -> Invoice {
+    let merchant = Pubkey::new_unique();
+    Invoice {
+        template: Pubkey::new_unique(),
+        merchant,
+        payer: Pubkey::new_unique(),
+        amount: 2_500,
+        period: 0,
+        issued_at: START,
+        rent_payer: Pubkey::new_unique(),
+        owner: merchant,
+        price: 0,
+        status: InvoiceStatus::Issued,
+        bump: 255,
+    }
+}
+
+#[test]
+fn invoices_list_at_a_discount_only() {
+    let mut invoice = invoice();
+    assert_eq!(
+        invoice.list(2_501).unwrap_err(),
+        CustomError::InvalidInvoicePrice.into()
+    );
+
+    invoice.list(2_500).unwrap();
+    invoice.list(2_000).unwrap();
+    assert_eq!(invoice.price, 2_000);
+
+    // Delisted invoices cannot be bought
+    invoice.list(0).unwrap();
+    assert_eq!(
+        invoice.sell_to(Pubkey::new_unique(), 0).unwrap_err(),
+        CustomError::InvoiceNotForSale.into()
+    );
+}
+
+#[test]
+fn sales_move_the_right_to_payment() {
+    let mut invoice = invoice();
+    invoice.list(2_000).unwrap();
+
+    // Neither the owner nor the payer can buy it
+    for buyer in [invoice.owner, invoice.payer] {
+        assert_eq!(
+            invoice.sell_to(buyer, 2_000).unwrap_err(),
+            CustomError::InvoiceNotForSale.into()
+        );
+    }
+    // Buyers pay exactly the price they saw
+    let buyer = Pubkey::new_unique();
+    assert_eq!(
+        invoice.sell_to(buyer, 1_900).unwrap_err(),
+        CustomError::InvalidInvoicePrice.into()
+    );
+
+    invoice.sell_to(buyer, 2_000).unwrap();
+    assert_eq!(invoice.owner, buyer);
+    assert_eq!(invoice.price, 0); // No longer listed
+    assert_ne!(invoice.merchant, buyer); // Provenance kept
+
+    // The new owner can resell it
+    invoice.list(2_400).unwrap();
+    let next = Pubkey::new_unique();
+    invoice.sell_to(next, 2_400).unwrap();
+    assert_eq!(invoice.owner, next);
+}
+
+#[test]
+fn unpaid_invoices_can_be_swept_after_the_payment_window() {
+    let invoice = invoice();
+    assert_eq!(invoice.expires_at(), START + INVOICE_PAYMENT_WINDOW.get());
+    assert!(expiry_handler(&Invoice::DISCRIMINATOR).is_some());
+}
@@ -0,0 +1,267 @@
+//! Overflow matrix: every arithmetic helper fed values at the edges of u64
+//!
+//! Each case either returns the exact result a u128 reference computes or
+//! fails with `MathOverflow`; nothing panics, wraps or truncates.
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_test_contract::decimals::{self, InternalUnits, RawTokenUnits, Rounding};
+use anchor_test_contract::math::{add, bps_of, mul_div, sub};
+use anchor_test_contract::{
+    calculate_fee, price_deviation_bps, rent_delta, CustomError, EpochStats, Ledger, LedgerAccount,
+    NamespaceBridgeConfig, RampedParam, RentDelta, Seconds, BPS_DENOMINATOR, LEDGER_ACCOUNT_COUNT,
+};
+
+/// Amounts at and around the ends of the u64 range
+const EDGES: [u64; 8] = [
+    0,
+    1,
+    2,
+    BPS_DENOMINATOR,
+    u64::MAX / 2,
+    u64::MAX / 2 + 1,
+    u64::MAX - 1,
+    u64::MAX,
+];
+
+/// Basis-point rates, including ones above 100%
+const RATES: [u64; 6] = [0, 1, 5_000, 9_999, BPS_DENOMINATOR, BPS_DENOMINATOR + 1];
+
+/// Timestamps at and around the ends of the i64 range
+const TIMES: [i64; 7] = [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX - 1, i64::MAX];
+
+fn overflow() -> anchor_lang::error::Error {
+    CustomError::MathOverflow.into()
+}
+
+#[test]
+fn mul_div_matches_a_u128_reference() {
+    for value in EDGES {
+        for numerator in EDGES {
+            for denominator in EDGES {
+                for rounding in [Rounding::Down, Rounding::Up] {
+                    let result = mul_div(value, numerator, denominator, rounding);
+                    if denominator == 0 {
+                        assert_eq!(result.unwrap_err(), overflow());
+                        continue;
+                    }
+                    let product = value as u128 * numerator as u128;
+                    let expected = match rounding {
+                        Rounding::Down => product / denominator as u128,
+                        Rounding::Up => product.div_ceil(denominator as u128),
+                    };
+                    match u64::try_from(expected) {
+                        Ok(expected) => assert_eq!(result.unwrap(), expected),
+                        Err(_) => assert_eq!(
+                            result.unwrap_err(),
+                            overflow(),
+                            "{value} * {numerator} / {denominator} should overflow"
+                        ),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn bps_of_never_exceeds_the_amount_at_or_below_100_percent() {
+    for amount in EDGES {
+        for bps in RATES.into_iter().filter(|bps| *bps <= BPS_DENOMINATOR) {
+            let down = bps_of(amount, bps, Rounding::Down).unwrap();
+            let up = bps_of(amount, bps, Rounding::Up).unwrap();
+            assert!(down <= up && up <= amount);
+            assert!(up - down <= 1);
+        }
+        assert_eq!(
+            bps_of(amount, BPS_DENOMINATOR, Rounding::Up).unwrap(),
+            amount
+        );
+    }
+
+    // Above 100% only the largest amounts overflow
+    assert_eq!(
+        bps_of(u64::MAX, BPS_DENOMINATOR + 1, Rounding::Down).unwrap_err(),
+        overflow()
+    );
+}
+
+#[test]
+fn add_and_sub_match_checked_ops() {
+    for a in EDGES {
+        for b in EDGES {
+            match a.checked_add(b) {
+                Some(sum) => assert_eq!(add(a, b).unwrap(), sum),
+                None => assert_eq!(add(a, b).unwrap_err(), overflow()),
+            }
+            match a.checked_sub(b) {
+                Some(difference) => assert_eq!(sub(a, b).unwrap(), difference),
+                None => assert_eq!(sub(a, b).unwrap_err(), overflow()),
+            }
+        }
+    }
+}
+
+#[test]
+fn calculate_fee_is_one_percent_rounded_down() {
+    for amount in EDGES {
+        assert_eq!(calculate_fee(amount).unwrap(), amount / 100);
+    }
+}
+
+#[test]
+fn bridge_splits_conserve_the_amount() {
+    let mut bridge = NamespaceBridgeConfig {
+        config_a: Pubkey::new_unique(),
+        config_b: Pubkey::new_unique(),
+        fee_bps: 0,
+        source_share_bps: 0,
+        approved_a: true,
+        approved_b: true,
+        payer: Pubkey::new_unique(),
+        bump: 255,
+    };
+
+    for amount in EDGES {
+        for fee_bps in RATES.into_iter().filter(|bps| *bps <= BPS_DENOMINATOR) {
+            for source_share_bps in [0, 1, 5_000, BPS_DENOMINATOR] {
+                bridge.fee_bps = fee_bps;
+                bridge.source_share_bps = source_share_bps;
+                let split = bridge.split(amount).unwrap();
+                let total =
+                    split.net_amount as u128 + split.source_fee as u128 + split.dest_fee as u128;
+                assert_eq!(total, amount as u128, "{amount} at {fee_bps} bps");
+            }
+        }
+    }
+}
+
+#[test]
+fn ramped_values_stay_between_their_endpoints() {
+    for old in [0, 1, u64::MAX - 1, u64::MAX] {
+        for new in [0, 1, u64::MAX - 1, u64::MAX] {
+            for start in TIMES {
+                for end in TIMES {
+                    let param = RampedParam {
+                        old,
+                        new,
+                        start,
+                        end,
+                    };
+                    for now in TIMES {
+                        let value = param.value_at(now);
+                        assert!(
+                            old.min(new) <= value && value <= old.max(new),
+                            "{old}->{new} over [{start}, {end}] at {now} gave {value}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn ramping_from_extreme_timestamps_saturates_the_end() {
+    let mut param = RampedParam::fixed(0);
+    param.ramp_to(u64::MAX, i64::MAX - 1, Seconds(i64::MAX));
+    assert_eq!(param.end, i64::MAX);
+    assert_eq!(param.value_at(i64::MAX), u64::MAX);
+}
+
+#[test]
+fn price_deviation_is_capped_instead_of_wrapping() {
+    for reference in EDGES {
+        for price in EDGES {
+            let bps = price_deviation_bps(reference, price);
+            if reference == price || reference == 0 {
+                assert_eq!(bps, 0);
+            }
+        }
+    }
+    assert_eq!(price_deviation_bps(1, u64::MAX), u64::MAX);
+}
+
+#[test]
+fn decimal_conversions_report_overflow() {
+    for amount in EDGES {
+        for mint_decimals in [0, 6, 9, 12, 18, 19, 20, u8::MAX] {
+            let raw = RawTokenUnits::new(amount);
+            for convert in [decimals::deposit_to_internal, decimals::owed_to_internal] {
+                if let Err(err) = convert(raw, mint_decimals) {
+                    assert_eq!(err, overflow());
+                }
+            }
+            let internal = InternalUnits::new(amount);
+            for convert in [decimals::withdrawal_to_raw, decimals::owed_to_raw] {
+                if let Err(err) = convert(internal, mint_decimals) {
+                    assert_eq!(err, overflow());
+                }
+            }
+        }
+    }
+    assert_eq!(
+        decimals::deposit_to_internal(RawTokenUnits::new(u64::MAX), 0).unwrap_err(),
+        overflow()
+    );
+    assert_eq!(
+        decimals::owed_to_internal(RawTokenUnits::new(u64::MAX), 18).unwrap(),
+        InternalUnits::new(18_446_744_074)
+    );
+}
+
+#[test]
+fn ledger_posts_refuse_to_wrap() {
+    let mut ledger = Ledger {
+        debits: [0; LEDGER_ACCOUNT_COUNT],
+        credits: [0; LEDGER_ACCOUNT_COUNT],
+        entries: 0,
+        bump: 255,
+    };
+    ledger
+        .post(LedgerAccount::Vault, LedgerAccount::Users, u64::MAX)
+        .unwrap();
+    assert_eq!(
+        ledger
+            .post(LedgerAccount::Vault, LedgerAccount::Fees, 1)
+            .unwrap_err(),
+        overflow()
+    );
+    assert_eq!(ledger.balance(LedgerAccount::Users), u64::MAX as i128);
+}
+
+#[test]
+fn volume_limits_fit_for_any_average_and_multiple() {
+    for trailing_average in EDGES {
+        for multiple_bps in EDGES {
+            let stats = EpochStats {
+                epoch: u64::MAX,
+                volume: u64::MAX,
+                trailing_average,
+                tripped: false,
+                tripped_at: 0,
+                bump: 255,
+            };
+            let limit = stats.volume_limit(multiple_bps);
+            if trailing_average == 0 || multiple_bps == 0 {
+                assert_eq!(limit, None);
+            } else {
+                let expected =
+                    trailing_average as u128 * multiple_bps as u128 / BPS_DENOMINATOR as u128;
+                assert_eq!(limit, Some(expected));
+            }
+        }
+    }
+}
+
+#[test]
+fn rent_deltas_at_balance_extremes() {
+    let rent = Rent::default();
+    for len in [0, 1, 10 * 1024 * 1024] {
+        let required = rent.minimum_balance(len);
+        assert_eq!(rent_delta(&rent, 0, len), RentDelta::TopUp(required));
+        assert_eq!(
+            rent_delta(&rent, u64::MAX, len),
+            RentDelta::Refund(u64::MAX - required)
+        );
+    }
+}
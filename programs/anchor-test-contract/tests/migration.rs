@@ -0,0 +1,200 @@
+//! User accounts written under older layouts, decoded for migration
+
+mod common;
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::{AccountSerialize, AnchorSerialize, Discriminator};
+use anchor_test_contract::{
+    decode_user_account, migrate_step, AccountMode, UserAccount, MAX_NAME_LEN, USER_ACCOUNT_VERSION,
+};
+use common::START;
+
+/// Bytes of the daily limit fields version 2 appended
+const V2_FIELDS_LEN: usize = 5 * 8;
+
+/// Bytes of the watchtower fields version 3 appended
+const V3_FIELDS_LEN: usize = 32 + 8;
+
+/// Bytes of the obligation counter version 4 appended
+const V4_FIELDS_LEN: usize = 4;
+
+/// Bytes of the sequence counter version 5 appended
+const V5_FIELDS_LEN: usize = 8;
+
+/// Bytes of the uncounted obligations flag version 6 appended
+const V6_FIELDS_LEN: usize = 1;
+
+fn user(name: &str) -> UserAccount {
+    UserAccount {
+        authority: Pubkey::new_from_array([1; 32]),
+        name: name.to_string(),
+        created_at: START,
+        ..common::user(1_000)
+    }
+}
+
+/// Decode `data` as a program-owned account
+fn decode(mut data: Vec<u8>) -> UserAccount {
+    let key = Pubkey::new_unique();
+    let mut lamports = 1_000_000;
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &anchor_test_contract::ID,
+        false,
+        0,
+    );
+    decode_user_account(&info).unwrap()
+}
+
+#[test]
+fn accounts_without_a_version_byte_decode_as_version_zero() {
+    // A full-length name leaves no zero padding, so dropping the version
+    // byte gives exactly the size of an account from before versioning
+    let current = user(&"n".repeat(MAX_NAME_LEN));
+    let mut data = Vec::new();
+    current.try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), UserAccount::LEN);
+    data.truncate(
+        UserAccount::LEN
+            - V6_FIELDS_LEN
+            - V5_FIELDS_LEN
+            - V4_FIELDS_LEN
+            - V3_FIELDS_LEN
+            - V2_FIELDS_LEN
+            - 1,
+    ); // Drop the version byte too
+
+    let legacy = decode(data);
+    assert_eq!(legacy.version, 0);
+    assert!(!legacy.is_current());
+    assert_eq!(legacy.name, current.name);
+    assert_eq!(legacy.guardian_limit, current.guardian_limit);
+}
+
+#[test]
+fn original_layout_decodes_with_default_fields() {
+    // authority, name, age, balance, is_active, created_at
+    let mut data = UserAccount::DISCRIMINATOR.to_vec();
+    let fields = (
+        Pubkey::new_from_array([1; 32]),
+        "alice".to_string(),
+        30u8,
+        1_000u64,
+        true,
+        START,
+    );
+    fields.serialize(&mut data).unwrap();
+
+    let legacy = decode(data);
+    assert_eq!(legacy.name, "alice");
+    assert_eq!(legacy.balance, 1_000);
+    assert_eq!(legacy.mode, AccountMode::Normal); // `is_active = true`
+    assert_eq!(legacy.created_at, START);
+    assert!(!legacy.is_joint);
+    assert_eq!(legacy.guardian_count(), 0);
+    assert_eq!(legacy.version, 0);
+}
+
+#[test]
+fn migration_steps_end_at_the_current_version() {
+    let mut legacy = user("alice");
+    legacy.version = 0;
+    while legacy.version < USER_ACCOUNT_VERSION {
+        migrate_step(&mut legacy);
+    }
+
+    assert!(legacy.is_current());
+    assert_eq!(legacy.balance, 1_000);
+}
+
+#[test]
+fn version_one_accounts_migrate_without_a_daily_limit() {
+    let mut current = user(&"n".repeat(MAX_NAME_LEN));
+    current.version = 1;
+    let mut data = Vec::new();
+    current.try_serialize(&mut data).unwrap();
+    data.truncate(
+        UserAccount::LEN
+            - V6_FIELDS_LEN
+            - V5_FIELDS_LEN
+            - V4_FIELDS_LEN
+            - V3_FIELDS_LEN
+            - V2_FIELDS_LEN,
+    );
+
+    let mut legacy = decode(data);
+    assert_eq!(legacy.version, 1);
+    while legacy.version < USER_ACCOUNT_VERSION {
+        migrate_step(&mut legacy);
+    }
+    assert!(legacy.is_current());
+    assert_eq!(legacy.daily_limit, 0); // Unlimited, as before
+    legacy.record_daily_spend(u64::MAX, START).unwrap();
+}
+
+#[test]
+fn version_two_accounts_migrate_without_a_watchtower() {
+    let mut current = user(&"n".repeat(MAX_NAME_LEN));
+    current.version = 2;
+    current.daily_limit = 500;
+    let mut data = Vec::new();
+    current.try_serialize(&mut data).unwrap();
+    data.truncate(UserAccount::LEN - V6_FIELDS_LEN - V5_FIELDS_LEN - V4_FIELDS_LEN - V3_FIELDS_LEN);
+
+    let mut legacy = decode(data);
+    assert_eq!(legacy.version, 2);
+    assert_eq!(legacy.daily_limit, 500);
+    while legacy.version < USER_ACCOUNT_VERSION {
+        migrate_step(&mut legacy);
+    }
+    assert!(legacy.is_current());
+    assert!(!legacy.is_watchtower(&Pubkey::default()));
+    assert!(!legacy.is_alerted(START));
+}
+
+#[test]
+fn version_three_accounts_migrate_with_nothing_open() {
+    let mut current = user(&"n".repeat(MAX_NAME_LEN));
+    current.version = 3;
+    current.watchtower = Pubkey::new_from_array([2; 32]);
+    let mut data = Vec::new();
+    current.try_serialize(&mut data).unwrap();
+    data.truncate(UserAccount::LEN - V6_FIELDS_LEN - V5_FIELDS_LEN - V4_FIELDS_LEN);
+
+    let mut legacy = decode(data);
+    assert_eq!(legacy.version, 3);
+    assert_eq!(legacy.watchtower, current.watchtower);
+    while legacy.version < USER_ACCOUNT_VERSION {
+        migrate_step(&mut legacy);
+    }
+    assert!(legacy.is_current());
+    assert_eq!(legacy.open_obligations, 0);
+    assert!(legacy.uncounted_obligations);
+    assert!(!legacy.close_obligation().unwrap()); // An escrow from before version 4
+    assert_eq!(legacy.open_obligations, 0);
+}
+
+#[test]
+fn version_four_accounts_migrate_with_no_transfers_counted() {
+    let mut current = user(&"n".repeat(MAX_NAME_LEN));
+    current.version = 4;
+    current.open_obligations = 2;
+    let mut data = Vec::new();
+    current.try_serialize(&mut data).unwrap();
+    data.truncate(UserAccount::LEN - V6_FIELDS_LEN - V5_FIELDS_LEN);
+
+    let mut legacy = decode(data);
+    assert_eq!(legacy.version, 4);
+    assert_eq!(legacy.open_obligations, 2);
+    while legacy.version < USER_ACCOUNT_VERSION {
+        migrate_step(&mut legacy);
+    }
+    assert!(legacy.is_current());
+    assert_eq!(legacy.sequence, 0);
+    assert_eq!(legacy.next_sequence().unwrap(), 1);
+    assert!(!legacy.uncounted_obligations); // Counted since version 4
+}
@@ -0,0 +1,55 @@
+//! Netting of bilateral obligations
+//!
+//! Only the difference of the two gross amounts ever moves, from whichever
+//! side owes more.
+
+mod common;
+
+use anchor_test_contract::{NetPosition, Obligation};
+use common::key;
+
+fn obligation() -> Obligation {
+    Obligation {
+        party_a: key(1),
+        party_b: key(2),
+        a_owes_b: 0,
+        b_owes_a: 0,
+        settlements: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn the_side_owing_more_pays_the_difference() {
+    let mut pair = obligation();
+    pair.record(&key(1), 700).unwrap();
+    pair.record(&key(2), 1_000).unwrap();
+    assert_eq!(pair.record(&key(1), 100).unwrap(), 800); // Gross keeps accumulating
+
+    assert_eq!(
+        pair.net(),
+        Some(NetPosition {
+            debtor: key(2),
+            creditor: key(1),
+            amount: 200,
+        })
+    );
+}
+
+#[test]
+fn offsetting_obligations_move_nothing() {
+    let mut pair = obligation();
+    assert_eq!(pair.net(), None);
+    pair.record(&key(1), 500).unwrap();
+    pair.record(&key(2), 500).unwrap();
+    assert_eq!(pair.net(), None);
+}
+
+#[test]
+fn pairs_connect_in_either_order() {
+    let pair = obligation();
+    assert!(pair.connects(&key(1), &key(2)));
+    assert!(pair.connects(&key(2), &key(1)));
+    assert!(!pair.connects(&key(1), &key(3)));
+    assert!(!pair.connects(&key(1), &key(1)));
+}
@@ -0,0 +1,146 @@
+//! Transfer policy bytecode: predicates, combinators and malformed programs
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    evaluate_policy, policy_op, validate_policy, CustomError, PolicyInput, MAX_POLICY_ALLOWLIST,
+    MAX_POLICY_CODE_LEN, MAX_POLICY_STACK,
+};
+
+const MIDNIGHT: i64 = 1_699_920_000; // 2023-11-14 00:00 UTC
+
+fn max_amount(max: u64) -> Vec<u8> {
+    let mut code = vec![policy_op::MAX_AMOUNT];
+    code.extend_from_slice(&max.to_le_bytes());
+    code
+}
+
+fn input(amount: u64, receiver: Pubkey, hour: i64) -> PolicyInput {
+    PolicyInput {
+        amount,
+        receiver,
+        now: MIDNIGHT + hour * 3_600 + 59,
+    }
+}
+
+fn eval(code: &[u8], allowlist: &[Pubkey], input: PolicyInput) -> bool {
+    validate_policy(code, allowlist).unwrap();
+    evaluate_policy(code, allowlist, &input).unwrap()
+}
+
+fn invalid(code: &[u8]) {
+    assert_eq!(
+        validate_policy(code, &[]),
+        Err(CustomError::InvalidPolicy.into()),
+        "{code:?}"
+    );
+}
+
+#[test]
+fn max_amount_is_inclusive() {
+    let code = max_amount(500);
+    let anyone = Pubkey::new_unique();
+    assert!(eval(&code, &[], input(500, anyone, 0)));
+    assert!(!eval(&code, &[], input(501, anyone, 0)));
+    assert!(eval(&max_amount(u64::MAX), &[], input(u64::MAX, anyone, 0)));
+}
+
+#[test]
+fn hours_windows_are_half_open_and_may_wrap() {
+    let business = [policy_op::HOURS, 9, 17];
+    let night = [policy_op::HOURS, 22, 6];
+    let anyone = Pubkey::new_unique();
+    for hour in 0..24 {
+        let at = input(1, anyone, hour);
+        assert_eq!(eval(&business, &[], at), (9..17).contains(&hour), "{hour}");
+        assert_eq!(eval(&night, &[], at), !(6..22).contains(&hour), "{hour}");
+    }
+    assert!(!eval(&[policy_op::HOURS, 5, 5], &[], input(1, anyone, 5)));
+    assert!(eval(&[policy_op::HOURS, 0, 24], &[], input(1, anyone, 23)));
+
+    // Times before 1970 still map to the right hour
+    let before_epoch = PolicyInput {
+        amount: 1,
+        receiver: anyone,
+        now: -3_600, // 23:00 UTC on 1969-12-31
+    };
+    assert!(eval(&night, &[], before_epoch));
+}
+
+#[test]
+fn allowlists_match_receiver_authorities() {
+    let friend = Pubkey::new_unique();
+    let code = [policy_op::ALLOWLISTED];
+    assert!(eval(&code, &[friend], input(1, friend, 0)));
+    assert!(!eval(&code, &[friend], input(1, Pubkey::new_unique(), 0)));
+    assert!(!eval(&code, &[], input(1, friend, 0)));
+}
+
+#[test]
+fn combinators_compose_predicates() {
+    let friend = Pubkey::new_unique();
+    let stranger = Pubkey::new_unique();
+
+    // Up to 500, or any amount to the allowlist
+    let mut small_or_friend = max_amount(500);
+    small_or_friend.extend_from_slice(&[policy_op::ALLOWLISTED, policy_op::OR]);
+    assert!(eval(&small_or_friend, &[friend], input(10_000, friend, 3)));
+    assert!(eval(&small_or_friend, &[friend], input(500, stranger, 3)));
+    assert!(!eval(&small_or_friend, &[friend], input(501, stranger, 3)));
+
+    // Up to 500 during business hours, never to the allowlist
+    let mut strict = max_amount(500);
+    strict.extend_from_slice(&[
+        policy_op::HOURS,
+        9,
+        17,
+        policy_op::AND,
+        policy_op::ALLOWLISTED,
+        policy_op::NOT,
+        policy_op::AND,
+    ]);
+    assert!(eval(&strict, &[friend], input(100, stranger, 10)));
+    assert!(!eval(&strict, &[friend], input(100, stranger, 20)));
+    assert!(!eval(&strict, &[friend], input(100, friend, 10)));
+    assert!(!eval(&strict, &[friend], input(501, stranger, 10)));
+}
+
+#[test]
+fn malformed_programs_are_rejected() {
+    invalid(&[]); // No verdict
+    invalid(&[0xff]); // Unknown opcode
+    invalid(&max_amount(1)[..8]); // Truncated operand
+    invalid(&[policy_op::HOURS, 9]); // Truncated operand
+    invalid(&[policy_op::HOURS, 24, 1]); // Start out of range
+    invalid(&[policy_op::HOURS, 1, 25]); // End out of range
+    invalid(&[policy_op::NOT]); // Underflow
+    invalid(&[policy_op::ALLOWLISTED, policy_op::AND]); // Underflow
+    invalid(&[policy_op::ALLOWLISTED, policy_op::ALLOWLISTED]); // Two verdicts
+}
+
+#[test]
+fn size_limits_are_enforced() {
+    // The deepest stack allowed, folded back to one verdict
+    let mut deep = vec![policy_op::ALLOWLISTED; MAX_POLICY_STACK];
+    deep.extend(vec![policy_op::OR; MAX_POLICY_STACK - 1]);
+    validate_policy(&deep, &[]).unwrap();
+
+    let mut too_deep = vec![policy_op::ALLOWLISTED; MAX_POLICY_STACK + 1];
+    too_deep.extend(vec![policy_op::OR; MAX_POLICY_STACK]);
+    invalid(&too_deep);
+
+    // The longest program allowed, then one byte more
+    let mut longest = vec![policy_op::ALLOWLISTED];
+    longest.extend(vec![policy_op::NOT; MAX_POLICY_CODE_LEN - 1]);
+    validate_policy(&longest, &[]).unwrap();
+    longest.push(policy_op::NOT);
+    invalid(&longest);
+
+    let full: Vec<Pubkey> = (0..MAX_POLICY_ALLOWLIST)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    validate_policy(&[policy_op::ALLOWLISTED], &full).unwrap();
+    let mut over = full.clone();
+    over.push(Pubkey::new_unique());
+    assert!(validate_policy(&[policy_op::ALLOWLISTED], &over).is_err());
+    assert!(validate_policy(&[policy_op::ALLOWLISTED], &[Pubkey::default()]).is_err());
+}
@@ -0,0 +1,58 @@
+//! Profile validation and content-sized allocation
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountSerialize;
+use anchor_test_contract::{ProfileParams, UserProfile, MAX_AVATAR_URI_LEN};
+
+fn params(avatar_uri: &str) -> ProfileParams {
+    ProfileParams {
+        avatar_uri: Some(avatar_uri.to_string()),
+        email_hash: Some([7; 32]),
+        metadata: "{\"lang\":\"en\"}".to_string(),
+    }
+}
+
+#[test]
+fn avatar_uris_need_an_allowed_scheme() {
+    assert!(params("https://example.com/a.png").validate().is_ok());
+    assert!(params("ipfs://bafybeigdyrzt").validate().is_ok());
+    assert!(params("http://example.com/a.png").validate().is_err());
+    assert!(params("javascript:alert(1)").validate().is_err());
+    assert!(params("https://").validate().is_err()); // Scheme alone
+    assert!(ProfileParams::default().validate().is_ok()); // No avatar at all
+}
+
+#[test]
+fn lengths_are_checked_in_bytes() {
+    let prefix = "https://";
+    let fits = format!("{prefix}{}", "a".repeat(MAX_AVATAR_URI_LEN - prefix.len()));
+    assert!(params(&fits).validate().is_ok());
+
+    // Same character count, but each 'é' takes two bytes
+    let multibyte = format!("{prefix}{}", "é".repeat(MAX_AVATAR_URI_LEN - prefix.len()));
+    assert!(params(&multibyte).validate().is_err());
+
+    let mut long_metadata = params("ar://tx");
+    long_metadata.metadata = "m".repeat(257);
+    assert!(long_metadata.validate().is_err());
+}
+
+#[test]
+fn space_matches_the_serialized_profile() {
+    for params in [
+        params("https://example.com/a.png"),
+        ProfileParams::default(),
+    ] {
+        let profile = UserProfile {
+            user: Pubkey::new_unique(),
+            avatar_uri: params.avatar_uri.clone(),
+            email_hash: params.email_hash,
+            metadata: params.metadata.clone(),
+            updated_at: 1_700_000_000,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        profile.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), UserProfile::space(&params));
+    }
+}
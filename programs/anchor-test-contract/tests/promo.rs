@@ -0,0 +1,54 @@
+//! Promo code hashing and redemption limits
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{promo_code_hash, ClockTolerance, CustomError, PromoCode, PromoReward};
+use common::START;
+
+fn promo(max_uses: u32) -> PromoCode {
+    PromoCode {
+        code_hash: promo_code_hash("SPRING24"),
+        reward: PromoReward::FeeWaiver,
+        max_uses,
+        uses: 0,
+        expires_at: START + 3_600,
+        creator: Pubkey::new_unique(),
+        bump: 255,
+    }
+}
+
+#[test]
+fn codes_hash_exactly() {
+    assert_eq!(promo_code_hash("SPRING24"), promo_code_hash("SPRING24"));
+    assert_ne!(promo_code_hash("SPRING24"), promo_code_hash("spring24"));
+    assert_ne!(promo_code_hash("SPRING24"), promo_code_hash("SPRING24 "));
+}
+
+#[test]
+fn redemptions_stop_at_the_cap() {
+    let mut promo = promo(2);
+    promo.redeem(START, ClockTolerance::NONE).unwrap();
+    promo.redeem(START, ClockTolerance::NONE).unwrap();
+    assert_eq!(promo.uses, 2);
+
+    assert_eq!(
+        promo.redeem(START, ClockTolerance::NONE).unwrap_err(),
+        CustomError::PromoCodeExhausted.into()
+    );
+    assert_eq!(promo.uses, 2);
+}
+
+#[test]
+fn expired_codes_cannot_be_redeemed() {
+    let mut promo = promo(10);
+    promo
+        .redeem(promo.expires_at - 1, ClockTolerance::NONE)
+        .unwrap();
+    assert_eq!(
+        promo
+            .redeem(promo.expires_at, ClockTolerance::NONE)
+            .unwrap_err(),
+        CustomError::PromoCodeExpired.into()
+    );
+}
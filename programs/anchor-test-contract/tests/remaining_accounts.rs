@@ -0,0 +1,231 @@
+//! Look-alike accounts fed through the remaining-accounts validator
+//!
+//! A rogue program can mint accounts carrying this program's exact bytes,
+//! and this program's own accounts of one type can be passed where another
+//! is expected. Only a genuine account at its own PDA may be decoded.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::AccountSerialize;
+use anchor_test_contract::{
+    derive_address, CustomError, MintConfig, RemainingAccountsValidator, UserAccount,
+};
+use common::user;
+
+/// Owner of every look-alike account
+const ROGUE_PROGRAM: Pubkey = Pubkey::new_from_array([0xee; 32]);
+
+/// An account ready to be wrapped in an `AccountInfo`
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, owner: Pubkey, account: &impl AccountSerialize) -> Self {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        Self {
+            key,
+            owner,
+            lamports: 1_000_000,
+            data,
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            false,
+            false,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+fn lookalike(authority: Pubkey, balance: u64) -> UserAccount {
+    UserAccount {
+        authority,
+        name: "lookalike".to_string(),
+        ..user(balance)
+    }
+}
+
+fn user_seeds(config: Pubkey) -> impl FnOnce(&UserAccount) -> Vec<Vec<u8>> {
+    move |user| {
+        vec![
+            b"user".to_vec(),
+            config.to_bytes().to_vec(),
+            user.authority.to_bytes().to_vec(),
+        ]
+    }
+}
+
+fn user_address(config: Pubkey, authority: Pubkey) -> Pubkey {
+    derive_address(&[
+        b"user".to_vec(),
+        config.to_bytes().to_vec(),
+        authority.to_bytes().to_vec(),
+    ])
+}
+
+/// Load `account` as a user of `config` through a fresh validator
+fn load_user(config: Pubkey, account: &mut TestAccount) -> anchor_lang::Result<UserAccount> {
+    RemainingAccountsValidator::default().load(&account.info(), user_seeds(config))
+}
+
+/// Error `account` is rejected with when loaded as a user of `config`
+fn rejection(config: Pubkey, account: &mut TestAccount) -> anchor_lang::error::Error {
+    match load_user(config, account) {
+        Ok(_) => panic!("look-alike account {} was accepted", account.key),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn genuine_accounts_are_decoded() {
+    let config = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let mut account = TestAccount::new(
+        user_address(config, authority),
+        anchor_test_contract::ID,
+        &lookalike(authority, 42),
+    );
+
+    let user = load_user(config, &mut account).unwrap();
+    assert_eq!((user.authority, user.balance), (authority, 42));
+}
+
+#[test]
+fn rogue_program_copies_are_rejected_before_decoding() {
+    let config = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let genuine = user_address(config, authority);
+    let rogue_pda = Pubkey::find_program_address(
+        &[b"user", config.as_ref(), authority.as_ref()],
+        &ROGUE_PROGRAM,
+    )
+    .0;
+
+    // Byte-identical data, including our discriminator, at either address
+    for key in [genuine, rogue_pda] {
+        let mut account = TestAccount::new(key, ROGUE_PROGRAM, &lookalike(authority, u64::MAX));
+        assert_eq!(
+            rejection(config, &mut account),
+            ErrorCode::AccountOwnedByWrongProgram.into()
+        );
+    }
+
+    // Garbage from a rogue program is rejected without being parsed
+    let mut account = TestAccount::new(genuine, ROGUE_PROGRAM, &lookalike(authority, 0));
+    account.data = vec![0xff; 3];
+    assert_eq!(
+        rejection(config, &mut account),
+        ErrorCode::AccountOwnedByWrongProgram.into()
+    );
+}
+
+#[test]
+fn other_account_types_cannot_cosplay() {
+    let config = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let mint_config = MintConfig {
+        mint,
+        decimals: 6,
+        deposit_enabled: true,
+        withdraw_enabled: true,
+        max_total_deposits: 0,
+        total_deposited: 0,
+        oracle_feed: Pubkey::default(),
+        bump: 255,
+        last_price: 0,
+        last_price_at: 0,
+    };
+
+    // A real program-owned MintConfig, whose leading pubkey reads as an authority
+    let mut account = TestAccount::new(
+        user_address(config, mint),
+        anchor_test_contract::ID,
+        &mint_config,
+    );
+    assert_eq!(
+        rejection(config, &mut account),
+        ErrorCode::AccountDiscriminatorMismatch.into()
+    );
+
+    // User bytes with the discriminator stripped
+    let mut account = TestAccount::new(
+        user_address(config, mint),
+        anchor_test_contract::ID,
+        &lookalike(mint, 1),
+    );
+    account.data.drain(..8);
+    assert_eq!(
+        rejection(config, &mut account),
+        ErrorCode::AccountDiscriminatorMismatch.into()
+    );
+
+    account.data.truncate(7);
+    assert_eq!(
+        rejection(config, &mut account),
+        ErrorCode::AccountDiscriminatorNotFound.into()
+    );
+}
+
+#[test]
+fn accounts_off_their_pda_are_rejected() {
+    let config = Pubkey::new_unique();
+    let other_config = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    // A user of another namespace, and one claiming another authority's address
+    for key in [
+        user_address(other_config, authority),
+        user_address(config, Pubkey::new_unique()),
+        Pubkey::new_unique(),
+    ] {
+        let mut account = TestAccount::new(key, anchor_test_contract::ID, &lookalike(authority, 7));
+        assert_eq!(
+            rejection(config, &mut account),
+            ErrorCode::ConstraintSeeds.into()
+        );
+    }
+}
+
+#[test]
+fn each_account_is_accepted_once() {
+    let config = Pubkey::new_unique();
+    let mut validator = RemainingAccountsValidator::with_capacity(2);
+    let mut accounts: Vec<TestAccount> = (0..2)
+        .map(|_| {
+            let authority = Pubkey::new_unique();
+            TestAccount::new(
+                user_address(config, authority),
+                anchor_test_contract::ID,
+                &lookalike(authority, 1),
+            )
+        })
+        .collect();
+
+    for account in accounts.iter_mut() {
+        validator.load(&account.info(), user_seeds(config)).unwrap();
+    }
+    assert_eq!(validator.count(), 2);
+
+    assert_eq!(
+        validator
+            .load(&accounts[0].info(), user_seeds(config))
+            .err()
+            .expect("duplicate was accepted"),
+        CustomError::DuplicateAccount.into()
+    );
+    assert_eq!(validator.count(), 2);
+}
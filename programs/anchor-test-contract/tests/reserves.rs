@@ -0,0 +1,31 @@
+//! Proof of reserves coverage and its once-per-epoch cadence
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{coverage_bps, ReservesAttestation, BPS_DENOMINATOR};
+
+#[test]
+fn coverage_compares_the_vault_with_what_it_owes() {
+    assert_eq!(coverage_bps(1_000, 1_000), BPS_DENOMINATOR);
+    assert_eq!(coverage_bps(1_250, 1_000), 12_500); // Donations on top
+    assert_eq!(coverage_bps(999, 1_000), 9_990); // Shortfall, rounded down
+    assert_eq!(coverage_bps(0, 0), u64::MAX); // Nothing owed
+    assert_eq!(coverage_bps(u64::MAX, 1), u64::MAX);
+}
+
+#[test]
+fn reserves_are_attested_once_per_epoch() {
+    let mut attestation = ReservesAttestation {
+        mint: Pubkey::new_unique(),
+        epoch: 0,
+        vault_balance: 0,
+        accounted: 0,
+        coverage_bps: 0,
+        attested_at: 0,
+        bump: 255,
+    };
+    assert!(attestation.is_due(0)); // Never attested
+
+    attestation.attested_at = 1_700_000_000;
+    assert!(!attestation.is_due(0));
+    assert!(attestation.is_due(1));
+}
@@ -0,0 +1,72 @@
+//! Exact lamport deltas computed for rent-aware resizing
+
+use anchor_lang::prelude::Rent;
+use anchor_test_contract::{
+    rent_delta, Attachment, AttachmentPage, RentDelta, ATTACHMENTS_PER_PAGE,
+};
+
+/// Lamports per byte under the default rent (3480 lamports/byte-year, 2-year exemption)
+const LAMPORTS_PER_BYTE: u64 = 6_960;
+
+/// Rent carried by one attachment entry
+const ENTRY_RENT: u64 = Attachment::LEN as u64 * LAMPORTS_PER_BYTE;
+
+#[test]
+fn growing_tops_up_exactly_the_added_bytes() {
+    let rent = Rent::default();
+    let old_len = AttachmentPage::space(0);
+    let lamports = rent.minimum_balance(old_len);
+
+    assert_eq!(
+        rent_delta(&rent, lamports, AttachmentPage::space(1)),
+        RentDelta::TopUp(ENTRY_RENT)
+    );
+    assert_eq!(
+        rent_delta(&rent, lamports, AttachmentPage::space(3)),
+        RentDelta::TopUp(3 * ENTRY_RENT)
+    );
+}
+
+#[test]
+fn shrinking_refunds_exactly_the_freed_bytes() {
+    let rent = Rent::default();
+    let lamports = rent.minimum_balance(AttachmentPage::space(5));
+
+    assert_eq!(
+        rent_delta(&rent, lamports, AttachmentPage::space(4)),
+        RentDelta::Refund(ENTRY_RENT)
+    );
+    assert_eq!(
+        rent_delta(&rent, lamports, AttachmentPage::space(0)),
+        RentDelta::Refund(5 * ENTRY_RENT)
+    );
+}
+
+#[test]
+fn an_exactly_funded_account_needs_nothing() {
+    let rent = Rent::default();
+    let len = AttachmentPage::space(2);
+    assert_eq!(
+        rent_delta(&rent, rent.minimum_balance(len), len),
+        RentDelta::Balanced
+    );
+}
+
+#[test]
+fn underfunded_accounts_are_topped_up_to_exemption() {
+    let rent = Rent::default();
+    let len = AttachmentPage::space(1);
+    assert_eq!(
+        rent_delta(&rent, 1_000, len),
+        RentDelta::TopUp(rent.minimum_balance(len) - 1_000)
+    );
+}
+
+#[test]
+fn full_page_space_matches_len() {
+    assert_eq!(
+        AttachmentPage::space(ATTACHMENTS_PER_PAGE),
+        AttachmentPage::LEN
+    );
+    assert_eq!(AttachmentPage::space(0), 8 + 32 + 2 + 4 + 1);
+}
@@ -0,0 +1,64 @@
+//! Route planning: what each hop sends, pays and keeps
+//!
+//! Value is conserved along a route: everything the sender is debited ends
+//! up as a fee, a commission or the final receiver's credit.
+
+use anchor_lang::prelude::Result;
+use anchor_test_contract::{
+    calculate_fee, plan_route, CustomError, RouteHop, RouteLeg, MAX_ROUTE_HOPS,
+};
+
+fn hop(commission_bps: u64) -> RouteHop {
+    RouteHop { commission_bps }
+}
+
+fn no_fee(_: u64) -> Result<u64> {
+    Ok(0)
+}
+
+#[test]
+fn a_direct_route_delivers_everything_but_the_fee() {
+    let legs = plan_route(10_000, &[], calculate_fee).unwrap();
+    assert_eq!(
+        legs,
+        vec![RouteLeg {
+            sent: 10_000,
+            fee: 100,
+            retained: 9_900,
+        }]
+    );
+}
+
+#[test]
+fn intermediates_keep_their_commission_and_forward_the_rest() {
+    let legs = plan_route(10_000, &[hop(500), hop(0)], no_fee).unwrap();
+    let sent: Vec<u64> = legs.iter().map(|leg| leg.sent).collect();
+    let retained: Vec<u64> = legs.iter().map(|leg| leg.retained).collect();
+    assert_eq!(sent, vec![10_000, 9_500, 9_500]);
+    assert_eq!(retained, vec![500, 0, 9_500]);
+}
+
+#[test]
+fn routes_conserve_value() {
+    for amount in [1, 99, 10_000, 123_456_789] {
+        let legs = plan_route(amount, &[hop(250), hop(1_000), hop(10_000)], calculate_fee).unwrap();
+        let fees: u64 = legs.iter().map(|leg| leg.fee).sum();
+        let kept: u64 = legs.iter().map(|leg| leg.retained).sum();
+        assert_eq!(fees + kept, amount);
+        for pair in legs.windows(2) {
+            assert_eq!(pair[1].sent, pair[0].sent - pair[0].fee - pair[0].retained);
+        }
+    }
+}
+
+#[test]
+fn invalid_paths_are_rejected() {
+    assert_eq!(
+        plan_route(1_000, &[hop(10_001)], no_fee).unwrap_err(),
+        CustomError::InvalidRoute.into()
+    );
+    assert_eq!(
+        plan_route(1_000, &[hop(0); MAX_ROUTE_HOPS + 1], no_fee).unwrap_err(),
+        CustomError::RouteTooLong.into()
+    );
+}
@@ -0,0 +1,55 @@
+//! Candidate fee strategies rehearsed alongside the live one
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{fee_for, Bps, FeeInputs, FeeStrategy, ShadowFees};
+
+fn shadow(candidate: FeeStrategy) -> ShadowFees {
+    ShadowFees {
+        config: Pubkey::new_unique(),
+        candidate,
+        transfers: 0,
+        live_total: 0,
+        shadow_total: 0,
+        diverged: 0,
+        bump: 255,
+    }
+}
+
+fn inputs(amount: u64) -> FeeInputs {
+    FeeInputs {
+        amount,
+        base_rate: Bps(100),
+        utilization: Bps(0),
+    }
+}
+
+#[test]
+fn rehearsals_total_both_strategies() {
+    let mut shadow = shadow(FeeStrategy::Discounted {
+        discount_bps: 5_000,
+    });
+    for amount in [10_000, 20_000] {
+        let live = fee_for(&FeeStrategy::Proportional, &inputs(amount)).unwrap();
+        let candidate = shadow.rehearse(&inputs(amount), live).unwrap();
+        assert_eq!(candidate, live / 2);
+    }
+
+    assert_eq!(shadow.transfers, 2);
+    assert_eq!(shadow.live_total, 300);
+    assert_eq!(shadow.shadow_total, 150);
+    assert_eq!(shadow.diverged, 2);
+}
+
+#[test]
+fn matching_fees_do_not_count_as_divergent() {
+    let mut shadow = shadow(FeeStrategy::Proportional);
+    shadow.rehearse(&inputs(10_000), 100).unwrap();
+    assert_eq!(shadow.diverged, 0);
+
+    shadow.reset(FeeStrategy::Flat { fee: 7 });
+    assert_eq!(shadow.candidate, FeeStrategy::Flat { fee: 7 });
+    assert_eq!(
+        (shadow.transfers, shadow.live_total, shadow.shadow_total),
+        (0, 0, 0)
+    );
+}
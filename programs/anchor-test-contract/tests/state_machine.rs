@@ -0,0 +1,94 @@
+//! Exhaustive checks of the status state machines
+
+use anchor_test_contract::{
+    auction_transition, escrow_transition, invoice_transition, AuctionAction, AuctionStatus,
+    CustomError, EscrowAction, EscrowStatus, InvoiceAction, InvoiceStatus,
+};
+
+#[test]
+fn auction_transitions_are_exhaustive() {
+    for from in AuctionStatus::ALL {
+        for action in AuctionAction::ALL {
+            let expected = match (from, action) {
+                (AuctionStatus::Open, AuctionAction::Settle) => Some(AuctionStatus::Settled),
+                (AuctionStatus::Open, AuctionAction::Cancel) => Some(AuctionStatus::Cancelled),
+                _ => None,
+            };
+            match expected {
+                Some(to) => assert_eq!(auction_transition(from, action).unwrap(), to),
+                None => assert_eq!(
+                    auction_transition(from, action).unwrap_err(),
+                    CustomError::InvalidStatusTransition.into(),
+                    "{from:?} --{action:?}--> should be rejected"
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn terminal_auction_statuses_have_no_exits() {
+    for from in [AuctionStatus::Settled, AuctionStatus::Cancelled] {
+        for action in AuctionAction::ALL {
+            assert!(auction_transition(from, action).is_err());
+        }
+    }
+}
+
+#[test]
+fn invoice_transitions_are_exhaustive() {
+    for from in InvoiceStatus::ALL {
+        for action in InvoiceAction::ALL {
+            let expected = match (from, action) {
+                (InvoiceStatus::Pending, InvoiceAction::Issue) => Some(InvoiceStatus::Issued),
+                (InvoiceStatus::Issued, InvoiceAction::Pay) => Some(InvoiceStatus::Paid),
+                (InvoiceStatus::Issued, InvoiceAction::Reject) => Some(InvoiceStatus::Rejected),
+                (InvoiceStatus::Issued, InvoiceAction::Expire) => Some(InvoiceStatus::Expired),
+                _ => None,
+            };
+            match expected {
+                Some(to) => assert_eq!(invoice_transition(from, action).unwrap(), to),
+                None => assert_eq!(
+                    invoice_transition(from, action).unwrap_err(),
+                    CustomError::InvalidStatusTransition.into(),
+                    "{from:?} --{action:?}--> should be rejected"
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn settled_invoices_are_settled_once() {
+    for from in [
+        InvoiceStatus::Paid,
+        InvoiceStatus::Rejected,
+        InvoiceStatus::Expired,
+    ] {
+        for action in InvoiceAction::ALL {
+            assert!(invoice_transition(from, action).is_err());
+        }
+    }
+}
+
+#[test]
+fn escrow_transitions_are_exhaustive() {
+    for from in EscrowStatus::ALL {
+        for action in EscrowAction::ALL {
+            let expected = match (from, action) {
+                (EscrowStatus::Locked, EscrowAction::Claim) => Some(EscrowStatus::Claimed),
+                (EscrowStatus::Locked, EscrowAction::Cancel) => Some(EscrowStatus::Cancelled),
+                (EscrowStatus::Locked, EscrowAction::Expire) => Some(EscrowStatus::Expired),
+                _ => None,
+            };
+            match expected {
+                Some(to) => assert_eq!(escrow_transition(from, action).unwrap(), to),
+                None => assert_eq!(
+                    escrow_transition(from, action).unwrap_err(),
+                    CustomError::InvalidStatusTransition.into(),
+                    "{from:?} --{action:?}--> should be rejected"
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Account statement aggregation, completeness and digests
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    AccountStatement, ActivityEntry, ActivityKind, ActivityLog, InstructionTag,
+    ACTIVITY_LOG_CAPACITY, STATEMENT_PERIOD,
+};
+use common::opened_at;
+
+const EPOCH: u64 = 656; // Period starting 2023-11-19
+
+/// Log opened in the period before `EPOCH`
+fn log_from_last_period() -> ActivityLog {
+    opened_at(at(EPOCH - 1, 0))
+}
+
+fn at(epoch: u64, offset: i64) -> i64 {
+    epoch as i64 * STATEMENT_PERIOD.get() + offset
+}
+
+fn entry(kind: ActivityKind, amount: u64, timestamp: i64) -> ActivityEntry {
+    ActivityEntry::new(
+        kind,
+        InstructionTag::TransferTokens,
+        Pubkey::new_unique(),
+        amount,
+        timestamp,
+    )
+}
+
+#[test]
+fn statements_only_count_their_period() {
+    let mut log = log_from_last_period();
+    log.append(entry(ActivityKind::Sent, 1_000, at(EPOCH, -1))); // Previous period
+    log.append(entry(ActivityKind::Sent, 200, at(EPOCH, 0)));
+    log.append(entry(ActivityKind::Received, 750, at(EPOCH, 60)));
+    log.append(entry(ActivityKind::Sent, 50, at(EPOCH, 120)));
+    log.append(entry(ActivityKind::Updated, 0, at(EPOCH, 180)));
+    log.append(entry(ActivityKind::Received, 9_000, at(EPOCH + 1, 0))); // Next period
+
+    let statement = AccountStatement::from_log(&log, EPOCH).unwrap();
+    assert_eq!(statement.user, log.user);
+    assert_eq!(statement.period_start, at(EPOCH, 0));
+    assert_eq!(statement.period_end, at(EPOCH + 1, 0));
+    assert_eq!((statement.sent, statement.sent_count), (250, 2));
+    assert_eq!((statement.received, statement.received_count), (750, 1));
+    assert_eq!(statement.other_count, 1);
+    assert!(statement.complete);
+}
+
+#[test]
+fn overwritten_history_marks_statements_incomplete() {
+    let mut log = log_from_last_period();
+    for i in 0..=ACTIVITY_LOG_CAPACITY as i64 {
+        log.append(entry(ActivityKind::Sent, 1, at(EPOCH, i)));
+    }
+    // The period's first entry was overwritten
+    let statement = AccountStatement::from_log(&log, EPOCH).unwrap();
+    assert_eq!(statement.sent_count, ACTIVITY_LOG_CAPACITY as u32);
+    assert!(!statement.complete);
+
+    // Later periods are unaffected by what the log dropped
+    log.append(entry(ActivityKind::Received, 5, at(EPOCH + 1, 0)));
+    let next = AccountStatement::from_log(&log, EPOCH + 1).unwrap();
+    assert_eq!(next.received, 5);
+    assert!(next.complete);
+}
+
+#[test]
+fn periods_before_the_log_was_opened_are_incomplete() {
+    let log = opened_at(at(EPOCH, 60));
+    let statement = AccountStatement::from_log(&log, EPOCH).unwrap();
+    assert_eq!(statement.other_count, 0); // The opening entry is not activity
+    assert!(!statement.complete);
+    assert!(
+        AccountStatement::from_log(&log, EPOCH + 1)
+            .unwrap()
+            .complete
+    );
+}
+
+#[test]
+fn digests_commit_to_every_field() {
+    let mut log = log_from_last_period();
+    log.append(entry(ActivityKind::Sent, 200, at(EPOCH, 0)));
+    let statement = AccountStatement::from_log(&log, EPOCH).unwrap();
+    assert_eq!(statement.digest(), statement.clone().digest());
+
+    let altered = [
+        AccountStatement {
+            sent: 201,
+            ..statement.clone()
+        },
+        AccountStatement {
+            epoch: EPOCH + 1,
+            ..statement.clone()
+        },
+        AccountStatement {
+            complete: false,
+            ..statement.clone()
+        },
+    ];
+    for altered in altered {
+        assert_ne!(altered.digest(), statement.digest());
+    }
+}
@@ -0,0 +1,125 @@
+//! Tax lot acquisition, FIFO/LIFO consumption across pages and page bookkeeping
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    CustomError, LotMethod, TaxLot, TaxLotBook, TaxLotPage, TAX_LOTS_PER_PAGE,
+};
+use common::START;
+
+fn book(method: LotMethod, pages: u32) -> TaxLotBook {
+    TaxLotBook {
+        user: Pubkey::new_unique(),
+        method,
+        first_page: 0,
+        next_page: pages,
+        bump: 255,
+    }
+}
+
+/// Page `index` of `book` holding lots of `amounts`, acquired a day apart
+fn page(book: &TaxLotBook, index: u32, amounts: &[u64]) -> TaxLotPage {
+    TaxLotPage {
+        user: book.user,
+        index,
+        lots: amounts
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| TaxLot {
+                amount: *amount,
+                acquired_at: START + (index as i64 * 10 + i as i64) * 86_400,
+                source: Pubkey::new_unique(),
+            })
+            .collect(),
+        bump: 255,
+    }
+}
+
+fn lot(amount: u64) -> TaxLot {
+    TaxLot {
+        amount,
+        acquired_at: START,
+        source: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn fifo_consumes_oldest_lots_across_pages() {
+    let mut book = book(LotMethod::Fifo, 2);
+    let mut pages = [page(&book, 0, &[100, 50]), page(&book, 1, &[70])];
+    let now = START + 30 * 86_400;
+
+    let (disposals, untracked) = book.dispose(&mut pages, 170, now).unwrap();
+    let taken: Vec<u64> = disposals.iter().map(|d| d.amount).collect();
+    assert_eq!(taken, vec![100, 50, 20]);
+    assert_eq!(untracked, 0);
+    assert_eq!(disposals[0].holding_period, 30 * 86_400);
+    assert_eq!(disposals[2].holding_period, 20 * 86_400);
+
+    // The emptied first page is left behind, the partly used one stays live
+    assert!(pages[0].lots.is_empty());
+    assert_eq!(pages[1].lots[0].amount, 50);
+    assert_eq!(book.first_page, 1);
+}
+
+#[test]
+fn lifo_consumes_newest_lots_and_reports_the_untracked_rest() {
+    let mut book = book(LotMethod::Lifo, 2);
+    let mut pages = [page(&book, 1, &[30, 40]), page(&book, 0, &[10])];
+
+    let (disposals, untracked) = book.dispose(&mut pages, 100, START).unwrap();
+    let taken: Vec<u64> = disposals.iter().map(|d| d.amount).collect();
+    assert_eq!(taken, vec![40, 30, 10]);
+    assert_eq!(untracked, 20); // Balance from before tracking began
+    assert_eq!(book.first_page, 0);
+
+    // A book without pages covers nothing
+    let mut empty = self::book(LotMethod::Lifo, 0);
+    assert_eq!(empty.dispose(&mut [], 5, START).unwrap(), (vec![], 5));
+}
+
+#[test]
+fn consumption_needs_every_page_in_order() {
+    let mut book = book(LotMethod::Fifo, 2);
+
+    // Skipping the oldest page would misreport holding periods
+    let mut skipped = [page(&book, 1, &[70])];
+    assert_eq!(
+        book.dispose(&mut skipped, 10, START).unwrap_err(),
+        CustomError::TaxLotPagesMissing.into()
+    );
+
+    // Stopping short while lots remain on later pages
+    let mut short = [page(&book, 0, &[5])];
+    assert_eq!(
+        book.dispose(&mut short, 10, START).unwrap_err(),
+        CustomError::TaxLotPagesMissing.into()
+    );
+
+    // Another user's page
+    let mut foreign = [page(&self::book(LotMethod::Fifo, 1), 0, &[5])];
+    assert_eq!(
+        book.dispose(&mut foreign, 1, START).unwrap_err(),
+        CustomError::TaxLotMismatch.into()
+    );
+}
+
+#[test]
+fn lots_are_acquired_on_the_newest_page_only() {
+    let book = book(LotMethod::Fifo, 2);
+    let mut old = page(&book, 0, &[]);
+    assert_eq!(
+        book.acquire(&mut old, lot(1)).unwrap_err(),
+        CustomError::TaxLotPagesMissing.into()
+    );
+
+    let mut newest = page(&book, 1, &[]);
+    for _ in 0..TAX_LOTS_PER_PAGE {
+        book.acquire(&mut newest, lot(1)).unwrap();
+    }
+    assert_eq!(
+        book.acquire(&mut newest, lot(1)).unwrap_err(),
+        CustomError::TaxLotPageFull.into()
+    );
+}
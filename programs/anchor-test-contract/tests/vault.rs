@@ -0,0 +1,66 @@
+//! Vault accounted balance, the floor a donation skim never goes below, and
+//! the per-mint positions withdrawals are debited from
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{
+    accounted_balance, donated_surplus, CustomError, MintConfig, VaultPosition,
+};
+
+fn mint_config(decimals: u8, total_deposited: u64) -> MintConfig {
+    MintConfig {
+        mint: Pubkey::new_unique(),
+        decimals,
+        deposit_enabled: true,
+        withdraw_enabled: true,
+        max_total_deposits: 0,
+        total_deposited,
+        oracle_feed: Pubkey::default(),
+        bump: 255,
+        last_price: 0,
+        last_price_at: 0,
+    }
+}
+
+#[test]
+fn accounted_balance_covers_every_withdrawal() {
+    assert_eq!(accounted_balance(&mint_config(9, 1_234)).unwrap(), 1_234);
+    assert_eq!(accounted_balance(&mint_config(12, 5)).unwrap(), 5_000);
+    // A partial raw token is still owed in full
+    assert_eq!(accounted_balance(&mint_config(6, 1_001)).unwrap(), 2);
+    assert_eq!(accounted_balance(&mint_config(6, 0)).unwrap(), 0);
+}
+
+#[test]
+fn donations_sit_above_what_depositors_are_owed() {
+    let config = mint_config(6, 1_001); // Owes 2 raw tokens
+    assert_eq!(donated_surplus(2, &config).unwrap(), 0);
+    assert_eq!(donated_surplus(10, &config).unwrap(), 8);
+    assert_eq!(donated_surplus(1, &config).unwrap(), 0); // A shortfall is not a surplus
+}
+
+fn position(mint: Pubkey, deposited: u64) -> VaultPosition {
+    VaultPosition {
+        user: Pubkey::new_unique(),
+        mint,
+        deposited,
+        bump: 255,
+    }
+}
+
+#[test]
+fn withdrawals_come_out_of_the_deposited_mint() {
+    let (cheap, valuable) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut deposited = position(cheap, 0);
+    deposited.credit(1_000).unwrap();
+
+    // The balance a cheap deposit credits cannot be taken out as another mint
+    let err = deposited.debit(&valuable, 1_000).unwrap_err();
+    assert_eq!(err, CustomError::CrossMintWithdrawal.into());
+    let err = position(valuable, 0).debit(&valuable, 1_000).unwrap_err();
+    assert_eq!(err, CustomError::CrossMintWithdrawal.into());
+
+    let err = deposited.debit(&cheap, 1_001).unwrap_err();
+    assert_eq!(err, CustomError::CrossMintWithdrawal.into());
+    deposited.debit(&cheap, 1_000).unwrap();
+    assert_eq!(deposited.deposited, 0);
+}
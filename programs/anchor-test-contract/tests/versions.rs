@@ -0,0 +1,49 @@
+//! Instruction version registry and sunset validation
+
+mod common;
+
+use anchor_test_contract::{
+    instruction_version, instruction_versions, validate_sunset, CustomError, InstructionTag,
+    INSTRUCTION_VERSIONS,
+};
+use common::START;
+
+#[test]
+fn superseded_entrypoints_name_a_current_replacement() {
+    for entry in INSTRUCTION_VERSIONS {
+        if let Some(use_instead) = entry.use_instead {
+            let (_, replacement) = instruction_version(use_instead).unwrap();
+            assert_eq!(replacement.use_instead, None);
+            assert!(replacement.version > entry.version);
+        }
+    }
+    let (slot, v1) = instruction_version(InstructionTag::TransferTokens).unwrap();
+    assert_eq!(slot, 0); // Sunset slots are append-only
+    assert_eq!(v1.version, instruction_versions::TRANSFER_TOKENS);
+    assert_eq!(v1.use_instead, Some(InstructionTag::TransferTokensV2));
+    assert!(instruction_version(InstructionTag::DepositTokens).is_none());
+}
+
+#[test]
+fn only_superseded_entrypoints_take_a_sunset() {
+    assert_eq!(
+        validate_sunset(InstructionTag::TransferTokens, START).unwrap(),
+        0
+    );
+    assert_eq!(
+        validate_sunset(InstructionTag::TransferTokens, 0).unwrap(),
+        0
+    ); // Lifted
+    assert_eq!(
+        validate_sunset(InstructionTag::TransferTokens, -1).unwrap_err(),
+        CustomError::InvalidSunset.into()
+    );
+    assert_eq!(
+        validate_sunset(InstructionTag::TransferTokensV2, START).unwrap_err(),
+        CustomError::InvalidSunset.into()
+    );
+    assert_eq!(
+        validate_sunset(InstructionTag::DepositTokens, START).unwrap_err(),
+        CustomError::InvalidSunset.into()
+    );
+}
@@ -0,0 +1,43 @@
+//! Watchtower registration and alert windows
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_test_contract::{UserAccount, WATCHTOWER_ALERT_DURATION};
+use common::{user, START};
+
+fn account(watchtower: Pubkey) -> UserAccount {
+    UserAccount {
+        watchtower,
+        ..user(1_000_000)
+    }
+}
+
+#[test]
+fn only_the_registered_watchtower_may_raise_alerts() {
+    let watchtower = Pubkey::new_unique();
+    let user = account(watchtower);
+    assert!(user.is_watchtower(&watchtower));
+    assert!(!user.is_watchtower(&user.authority));
+    assert!(!user.is_watchtower(&Pubkey::new_unique()));
+}
+
+#[test]
+fn the_default_key_is_never_a_watchtower() {
+    let user = account(Pubkey::default());
+    assert!(!user.is_watchtower(&Pubkey::default()));
+}
+
+#[test]
+fn alerts_lapse_after_their_duration() {
+    let mut user = account(Pubkey::new_unique());
+    assert!(!user.is_alerted(START));
+
+    user.alert_until = WATCHTOWER_ALERT_DURATION.after(START);
+    assert!(user.is_alerted(START));
+    assert!(user.is_alerted(user.alert_until - 1));
+    assert!(!user.is_alerted(user.alert_until));
+
+    user.alert_until = 0; // Dismissed
+    assert!(!user.is_alerted(START));
+}
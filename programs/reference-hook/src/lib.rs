@@ -0,0 +1,78 @@
+/*
+ * ============================================================================
+ * REFERENCE TRANSFER HOOK
+ * ============================================================================
+ *
+ * A minimal program implementing `anchor_test_contract::transfer_hook`, for
+ * third parties to copy when writing their own hooks. It vetoes transfers
+ * larger than `MAX_HOOKED_AMOUNT` and between the same authority, and
+ * accepts everything else.
+ *
+ * The interface is matched by name, argument order and account order; the
+ * conformance tests in `tests/conformance.rs` check all three against the
+ * interface definition.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_test_contract::{transfer_hook::OnTransferArgs, InstructionTag};
+
+declare_id!("E7nR6dMyJraKi8DcRPMB71TdDMUmUwU5Ny7EXHUYvt8q");
+
+/// Largest transfer the reference hook lets through
+pub const MAX_HOOKED_AMOUNT: u64 = 1_000_000_000_000;
+
+#[program]
+pub mod reference_hook {
+    use super::*;
+
+    /// `transfer_hook::ON_TRANSFER`: fail to veto the transfer
+    pub fn on_transfer(
+        _ctx: Context<OnTransfer>,
+        from: Pubkey,
+        to: Pubkey,
+        amount: u64,
+        tag: InstructionTag,
+    ) -> Result<()> {
+        check_transfer(&OnTransferArgs {
+            from,
+            to,
+            amount,
+            tag,
+        })?;
+        msg!("Transfer of {} approved by hook", amount);
+        Ok(())
+    }
+}
+
+/// Accounts of `transfer_hook::ON_TRANSFER`, in interface order
+#[derive(Accounts)]
+pub struct OnTransfer<'info> {
+    /// Namespace config of the transfer
+    /// CHECK: only identifies the namespace; never read
+    pub config: UncheckedAccount<'info>,
+
+    /// Sender user account
+    /// CHECK: only identifies the sender; never read
+    pub sender: UncheckedAccount<'info>,
+
+    /// Receiver user account
+    /// CHECK: only identifies the receiver; never read
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// The hook's policy, separate from the entrypoint so it can be tested alone
+pub fn check_transfer(args: &OnTransferArgs) -> Result<()> {
+    require!(args.amount <= MAX_HOOKED_AMOUNT, HookError::AmountTooLarge);
+    require_keys_neq!(args.from, args.to, HookError::SelfTransfer);
+    Ok(())
+}
+
+/// Reasons the reference hook vetoes a transfer
+#[error_code]
+pub enum HookError {
+    #[msg("The transfer exceeds the hook's limit.")]
+    AmountTooLarge, // Error code: 6000
+
+    #[msg("The hook rejects transfers to the same authority.")]
+    SelfTransfer, // Error code: 6001
+}
@@ -0,0 +1,83 @@
+//! The reference hook against the `transfer_hook` interface definition
+//!
+//! A hook conforms when its instruction data and account order are exactly
+//! what `transfer_hook::on_transfer` builds. These checks are the template
+//! for third-party hooks: swap in the hook crate and keep the assertions.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, InstructionData};
+use anchor_test_contract::transfer_hook::{self, OnTransferArgs, ON_TRANSFER};
+use anchor_test_contract::InstructionTag;
+use reference_hook::{check_transfer, HookError, MAX_HOOKED_AMOUNT};
+
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+fn args(amount: u64) -> OnTransferArgs {
+    OnTransferArgs {
+        from: key(1),
+        to: key(2),
+        amount,
+        tag: InstructionTag::TransferTokens,
+    }
+}
+
+#[test]
+fn discriminator_matches_the_interface() {
+    assert_eq!(
+        reference_hook::instruction::OnTransfer::DISCRIMINATOR,
+        ON_TRANSFER
+    );
+}
+
+#[test]
+fn instruction_data_matches_the_interface() {
+    let args = args(500);
+    let ix = transfer_hook::on_transfer(reference_hook::ID, key(10), key(11), key(12), &args, &[]);
+    let data = reference_hook::instruction::OnTransfer {
+        from: args.from,
+        to: args.to,
+        amount: args.amount,
+        tag: args.tag,
+    }
+    .data();
+    assert_eq!(ix.data, data);
+}
+
+#[test]
+fn account_order_matches_the_interface() {
+    let extra = AccountMeta::new_readonly(key(13), false);
+    let ix = transfer_hook::on_transfer(
+        reference_hook::ID,
+        key(10),
+        key(11),
+        key(12),
+        &args(500),
+        std::slice::from_ref(&extra),
+    );
+    let mut metas = reference_hook::accounts::OnTransfer {
+        config: key(10),
+        sender: key(11),
+        receiver: key(12),
+    }
+    .to_account_metas(None);
+    metas.push(extra); // Extra accounts pass through after the fixed ones
+    assert_eq!(ix.accounts, metas);
+}
+
+#[test]
+fn the_reference_policy_vetoes_what_it_documents() {
+    check_transfer(&args(MAX_HOOKED_AMOUNT)).unwrap();
+    assert_eq!(
+        check_transfer(&args(MAX_HOOKED_AMOUNT + 1)).unwrap_err(),
+        HookError::AmountTooLarge.into()
+    );
+
+    let mut to_self = args(1);
+    to_self.to = to_self.from;
+    assert_eq!(
+        check_transfer(&to_self).unwrap_err(),
+        HookError::SelfTransfer.into()
+    );
+}
@@ -0,0 +1,165 @@
+//! Decoders for raw account data fetched over RPC
+
+use anchor_lang::{AccountDeserialize, Discriminator, ZeroCopy};
+use anchor_test_contract::{
+    ActivityLog, Allowance, AttachmentPage, Attestation, Auction, CapabilityToken,
+    CashbackMerchant, CashbackProgram, ChaosConfig, Commitment, CpiProgramRegistry, CrankMetrics,
+    CreditLine, EpochStats, Escrow, FeeTreasury, GlobalConfig, Invoice, InvoiceTemplate,
+    IssuerRecord, JointAccount, Ledger, ManagerGrant, MintConfig, NamespaceBridgeConfig,
+    NamespaceMetadata, Obligation, PromoClaim, PromoCode, RecentOperations, ReservesAttestation,
+    SealedBid, ShadowFees, SpendGuard, TaxLotBook, TaxLotPage, TransferPolicy, UserAccount,
+    UserProfile, VaultPosition,
+};
+
+/// Error returned when account data cannot be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Data is shorter than the 8-byte discriminator
+    TooShort,
+    /// Discriminator does not match the requested type
+    WrongDiscriminator,
+    /// Borsh deserialization of the body failed
+    InvalidData(String),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::TooShort => write!(f, "account data too short"),
+            DecodeError::WrongDiscriminator => write!(f, "account discriminator mismatch"),
+            DecodeError::InvalidData(err) => write!(f, "invalid account data: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decode account data of type `T`, checking its discriminator first
+pub fn decode<T: AccountDeserialize + Discriminator>(data: &[u8]) -> Result<T, DecodeError> {
+    let prefix = data.get(..8).ok_or(DecodeError::TooShort)?;
+    if prefix != T::DISCRIMINATOR {
+        return Err(DecodeError::WrongDiscriminator);
+    }
+    let mut slice = data;
+    T::try_deserialize(&mut slice).map_err(|err| DecodeError::InvalidData(err.to_string()))
+}
+
+/// Decode zero-copy account data of type `T`, checking its discriminator first
+pub fn decode_zero_copy<T: ZeroCopy>(data: &[u8]) -> Result<T, DecodeError> {
+    let prefix = data.get(..8).ok_or(DecodeError::TooShort)?;
+    if prefix != T::DISCRIMINATOR {
+        return Err(DecodeError::WrongDiscriminator);
+    }
+    let body = data
+        .get(8..8 + std::mem::size_of::<T>())
+        .ok_or(DecodeError::TooShort)?;
+    bytemuck::try_pod_read_unaligned(body).map_err(|err| DecodeError::InvalidData(err.to_string()))
+}
+
+/// Any account owned by the program, decoded by discriminator
+#[derive(Clone)]
+pub enum ProgramAccount {
+    ReservesAttestation(ReservesAttestation),
+    VaultPosition(VaultPosition),
+    CapabilityToken(CapabilityToken),
+    CrankMetrics(CrankMetrics),
+    TransferPolicy(TransferPolicy),
+    ChaosConfig(ChaosConfig),
+    ShadowFees(ShadowFees),
+    TaxLotPage(TaxLotPage),
+    TaxLotBook(TaxLotBook),
+    UserProfile(UserProfile),
+    CreditLine(CreditLine),
+    ActivityLog(Box<ActivityLog>),
+    CashbackMerchant(CashbackMerchant),
+    CashbackProgram(CashbackProgram),
+    Escrow(Escrow),
+    PromoClaim(PromoClaim),
+    PromoCode(PromoCode),
+    Invoice(Invoice),
+    InvoiceTemplate(InvoiceTemplate),
+    Obligation(Obligation),
+    SpendGuard(SpendGuard),
+    FeeTreasury(FeeTreasury),
+    Allowance(Allowance),
+    ManagerGrant(ManagerGrant),
+    CpiProgramRegistry(CpiProgramRegistry),
+    NamespaceBridgeConfig(NamespaceBridgeConfig),
+    NamespaceMetadata(NamespaceMetadata),
+    IssuerRecord(IssuerRecord),
+    Attestation(Attestation),
+    AttachmentPage(AttachmentPage),
+    SealedBid(SealedBid),
+    Auction(Auction),
+    Commitment(Commitment),
+    EpochStats(EpochStats),
+    GlobalConfig(GlobalConfig),
+    JointAccount(JointAccount),
+    Ledger(Ledger),
+    MintConfig(MintConfig),
+    RecentOperations(RecentOperations),
+    UserAccount(UserAccount),
+}
+
+/// Decode any program account by looking at its discriminator
+pub fn decode_any(data: &[u8]) -> Result<ProgramAccount, DecodeError> {
+    let prefix = data.get(..8).ok_or(DecodeError::TooShort)?;
+    let account = match prefix {
+        p if p == EpochStats::DISCRIMINATOR => ProgramAccount::EpochStats(decode(data)?),
+        p if p == GlobalConfig::DISCRIMINATOR => ProgramAccount::GlobalConfig(decode(data)?),
+        p if p == JointAccount::DISCRIMINATOR => ProgramAccount::JointAccount(decode(data)?),
+        p if p == Ledger::DISCRIMINATOR => ProgramAccount::Ledger(decode(data)?),
+        p if p == MintConfig::DISCRIMINATOR => ProgramAccount::MintConfig(decode(data)?),
+        p if p == RecentOperations::DISCRIMINATOR => {
+            ProgramAccount::RecentOperations(decode(data)?)
+        }
+        p if p == UserAccount::DISCRIMINATOR => ProgramAccount::UserAccount(decode(data)?),
+        p if p == Commitment::DISCRIMINATOR => ProgramAccount::Commitment(decode(data)?),
+        p if p == Auction::DISCRIMINATOR => ProgramAccount::Auction(decode(data)?),
+        p if p == SealedBid::DISCRIMINATOR => ProgramAccount::SealedBid(decode(data)?),
+        p if p == AttachmentPage::DISCRIMINATOR => ProgramAccount::AttachmentPage(decode(data)?),
+        p if p == Attestation::DISCRIMINATOR => ProgramAccount::Attestation(decode(data)?),
+        p if p == IssuerRecord::DISCRIMINATOR => ProgramAccount::IssuerRecord(decode(data)?),
+        p if p == NamespaceMetadata::DISCRIMINATOR => {
+            ProgramAccount::NamespaceMetadata(decode(data)?)
+        }
+        p if p == NamespaceBridgeConfig::DISCRIMINATOR => {
+            ProgramAccount::NamespaceBridgeConfig(decode(data)?)
+        }
+        p if p == CpiProgramRegistry::DISCRIMINATOR => {
+            ProgramAccount::CpiProgramRegistry(decode(data)?)
+        }
+        p if p == ManagerGrant::DISCRIMINATOR => ProgramAccount::ManagerGrant(decode(data)?),
+        p if p == Allowance::DISCRIMINATOR => ProgramAccount::Allowance(decode(data)?),
+        p if p == FeeTreasury::DISCRIMINATOR => ProgramAccount::FeeTreasury(decode(data)?),
+        p if p == SpendGuard::DISCRIMINATOR => ProgramAccount::SpendGuard(decode(data)?),
+        p if p == Obligation::DISCRIMINATOR => ProgramAccount::Obligation(decode(data)?),
+        p if p == InvoiceTemplate::DISCRIMINATOR => ProgramAccount::InvoiceTemplate(decode(data)?),
+        p if p == Invoice::DISCRIMINATOR => ProgramAccount::Invoice(decode(data)?),
+        p if p == PromoCode::DISCRIMINATOR => ProgramAccount::PromoCode(decode(data)?),
+        p if p == PromoClaim::DISCRIMINATOR => ProgramAccount::PromoClaim(decode(data)?),
+        p if p == Escrow::DISCRIMINATOR => ProgramAccount::Escrow(decode(data)?),
+        p if p == CashbackProgram::DISCRIMINATOR => ProgramAccount::CashbackProgram(decode(data)?),
+        p if p == CashbackMerchant::DISCRIMINATOR => {
+            ProgramAccount::CashbackMerchant(decode(data)?)
+        }
+        p if p == ActivityLog::DISCRIMINATOR => {
+            ProgramAccount::ActivityLog(Box::new(decode_zero_copy(data)?))
+        }
+        p if p == CreditLine::DISCRIMINATOR => ProgramAccount::CreditLine(decode(data)?),
+        p if p == UserProfile::DISCRIMINATOR => ProgramAccount::UserProfile(decode(data)?),
+        p if p == TaxLotBook::DISCRIMINATOR => ProgramAccount::TaxLotBook(decode(data)?),
+        p if p == TaxLotPage::DISCRIMINATOR => ProgramAccount::TaxLotPage(decode(data)?),
+        p if p == ShadowFees::DISCRIMINATOR => ProgramAccount::ShadowFees(decode(data)?),
+        p if p == ChaosConfig::DISCRIMINATOR => ProgramAccount::ChaosConfig(decode(data)?),
+        p if p == TransferPolicy::DISCRIMINATOR => ProgramAccount::TransferPolicy(decode(data)?),
+        p if p == CrankMetrics::DISCRIMINATOR => ProgramAccount::CrankMetrics(decode(data)?),
+        p if p == CapabilityToken::DISCRIMINATOR => ProgramAccount::CapabilityToken(decode(data)?),
+        p if p == VaultPosition::DISCRIMINATOR => ProgramAccount::VaultPosition(decode(data)?),
+        p if p == ReservesAttestation::DISCRIMINATOR => {
+            ProgramAccount::ReservesAttestation(decode(data)?)
+        }
+        _ => return Err(DecodeError::WrongDiscriminator),
+    };
+    Ok(account)
+}
@@ -0,0 +1,361 @@
+//! Decoders for events emitted by the program
+//!
+//! Events reach clients in two shapes:
+//! - `emit!` writes `Program data: <base64>` log lines
+//! - `emit_cpi!` self-invokes the program with the event as instruction data,
+//!   prefixed by Anchor's event instruction tag
+//!
+//! Both carry the 8-byte event discriminator followed by the Borsh body.
+//!
+//! Versioning: event layouts only ever grow by appending fields. Decoding
+//! reads the fields this SDK knows about and ignores trailing bytes, so an
+//! older SDK keeps working against a newer program; `DecodedEvent::version`
+//! tells the caller whether unknown trailing data was present.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anchor_test_contract::{
+    AccountCompactedEvent, AccountExpiredEvent, AccountModeChangedEvent, AlertDismissedEvent,
+    AlertRaisedEvent, AllowanceChangedEvent, AttestationChangedEvent, AuctionSettledEvent,
+    BatchTransferEvent, CapabilityIssuedEvent, CapabilityRevokedEvent, CapabilityUsedEvent,
+    CashbackConfiguredEvent, CashbackMerchantChangedEvent, CashbackPaidEvent,
+    CashbackSuspensionEvent, ChaosSetEvent, CircuitBreakerTrippedEvent, CommitmentRevealedEvent,
+    ComplianceAuthorityChangedEvent, ComplianceReportCompiledEvent, ConfigSnapshotEvent,
+    CpiProgramUpdatedEvent, CrankMetricsInitializedEvent, CreditLineFrozenEvent,
+    CreditLineOpenedEvent, CreditLineUtilizationEvent, CrossNamespaceTransferEvent,
+    DailyLimitChangedEvent, DeactivationFinalizedEvent, DeactivationScheduledEvent,
+    DocumentAttachedEvent, DocumentRemovedEvent, DonationsSkimmedEvent,
+    EmissionScheduleChangedEvent, EscrowCancelledEvent, EscrowClaimedEvent, EscrowCreatedEvent,
+    FeeStrategyChangedEvent, GuardiansChangedEvent, InstructionDeprecatedEvent,
+    InstructionsToggledEvent, InvariantsAttestedEvent, InvoiceIssuedEvent, InvoiceListedEvent,
+    InvoicePaidEvent, InvoiceRejectedEvent, InvoiceSoldEvent, InvoiceTemplateCancelledEvent,
+    LamportsSweptEvent, LedgerVerifiedEvent, LookupTableRegisteredEvent, ManagerActionEvent,
+    ManagerGrantedEvent, ManagerRevokedEvent, MintConfigChangedEvent, NamespaceBridgeChangedEvent,
+    NamespaceMetadataChangedEvent, NetSettledEvent, ObligationRecordedEvent,
+    OraclePriceOverriddenEvent, ParameterRampScheduledEvent, PauseToggledEvent, PolicyClosedEvent,
+    PolicySetEvent, ProfileUpdatedEvent, ProgramFingerprintEvent, PromoRedeemedEvent,
+    ProofOfReservesEvent, RoutedTransferEvent, ShadowFeeEvent, ShadowFeeStrategySetEvent,
+    StatementGeneratedEvent, TaxLotsRealizedEvent, TokenTransferEvent, TokensDepositedEvent,
+    TokensWithdrawnEvent, TransferMemoEvent, TreasuryWithdrawnEvent, UserConsistencyReportEvent,
+    UserMigratedEvent, WatchtowerSetEvent, ID,
+};
+use base64::Engine;
+
+use crate::accounts::DecodeError;
+
+/// Log prefix Anchor uses for `emit!` payloads
+pub const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Any event emitted by the program, decoded by discriminator
+pub enum ProgramEvent {
+    ProofOfReserves(ProofOfReservesEvent),
+    InvoiceRejected(InvoiceRejectedEvent),
+    DonationsSkimmed(DonationsSkimmedEvent),
+    ProgramFingerprint(ProgramFingerprintEvent),
+    TransferMemo(TransferMemoEvent),
+    InstructionDeprecated(InstructionDeprecatedEvent),
+    CapabilityUsed(CapabilityUsedEvent),
+    CapabilityRevoked(CapabilityRevokedEvent),
+    CapabilityIssued(CapabilityIssuedEvent),
+    CrankMetricsInitialized(CrankMetricsInitializedEvent),
+    DeactivationFinalized(DeactivationFinalizedEvent),
+    ConfigSnapshot(ConfigSnapshotEvent),
+    PolicyClosed(PolicyClosedEvent),
+    PolicySet(PolicySetEvent),
+    ChaosSet(ChaosSetEvent),
+    ShadowFeeStrategySet(ShadowFeeStrategySetEvent),
+    ShadowFee(ShadowFeeEvent),
+    AlertDismissed(AlertDismissedEvent),
+    AlertRaised(AlertRaisedEvent),
+    WatchtowerSet(WatchtowerSetEvent),
+    ComplianceReportCompiled(ComplianceReportCompiledEvent),
+    TaxLotsRealized(TaxLotsRealizedEvent),
+    StatementGenerated(StatementGeneratedEvent),
+    InvoiceSold(InvoiceSoldEvent),
+    InvoiceListed(InvoiceListedEvent),
+    ProfileUpdated(ProfileUpdatedEvent),
+    DailyLimitChanged(DailyLimitChangedEvent),
+    CreditLineFrozen(CreditLineFrozenEvent),
+    CreditLineUtilization(CreditLineUtilizationEvent),
+    CreditLineOpened(CreditLineOpenedEvent),
+    CashbackSuspension(CashbackSuspensionEvent),
+    CashbackPaid(CashbackPaidEvent),
+    CashbackMerchantChanged(CashbackMerchantChangedEvent),
+    CashbackConfigured(CashbackConfiguredEvent),
+    EscrowCancelled(EscrowCancelledEvent),
+    EscrowClaimed(EscrowClaimedEvent),
+    EscrowCreated(EscrowCreatedEvent),
+    PromoRedeemed(PromoRedeemedEvent),
+    InvoiceTemplateCancelled(InvoiceTemplateCancelledEvent),
+    InvoicePaid(InvoicePaidEvent),
+    InvoiceIssued(InvoiceIssuedEvent),
+    UserMigrated(UserMigratedEvent),
+    NetSettled(NetSettledEvent),
+    ObligationRecorded(ObligationRecordedEvent),
+    GuardiansChanged(GuardiansChangedEvent),
+    RoutedTransfer(RoutedTransferEvent),
+    BatchTransfer(BatchTransferEvent),
+    TreasuryWithdrawn(TreasuryWithdrawnEvent),
+    AllowanceChanged(AllowanceChangedEvent),
+    PauseToggled(PauseToggledEvent),
+    TokensWithdrawn(TokensWithdrawnEvent),
+    TokensDeposited(TokensDepositedEvent),
+    ManagerAction(ManagerActionEvent),
+    ManagerRevoked(ManagerRevokedEvent),
+    ManagerGranted(ManagerGrantedEvent),
+    ComplianceAuthorityChanged(ComplianceAuthorityChangedEvent),
+    AccountModeChanged(AccountModeChangedEvent),
+    EmissionScheduleChanged(EmissionScheduleChangedEvent),
+    FeeStrategyChanged(FeeStrategyChangedEvent),
+    CpiProgramUpdated(CpiProgramUpdatedEvent),
+    AccountExpired(AccountExpiredEvent),
+    LamportsSwept(LamportsSweptEvent),
+    AccountCompacted(AccountCompactedEvent),
+    CrossNamespaceTransfer(CrossNamespaceTransferEvent),
+    NamespaceBridgeChanged(NamespaceBridgeChangedEvent),
+    NamespaceMetadataChanged(NamespaceMetadataChangedEvent),
+    UserConsistencyReport(UserConsistencyReportEvent),
+    AttestationChanged(AttestationChangedEvent),
+    DocumentRemoved(DocumentRemovedEvent),
+    DocumentAttached(DocumentAttachedEvent),
+    AuctionSettled(AuctionSettledEvent),
+    CommitmentRevealed(CommitmentRevealedEvent),
+    CircuitBreakerTripped(CircuitBreakerTrippedEvent),
+    DeactivationScheduled(DeactivationScheduledEvent),
+    InstructionsToggled(InstructionsToggledEvent),
+    InvariantsAttested(InvariantsAttestedEvent),
+    LedgerVerified(LedgerVerifiedEvent),
+    LookupTableRegistered(LookupTableRegisteredEvent),
+    MintConfigChanged(MintConfigChangedEvent),
+    OraclePriceOverridden(OraclePriceOverriddenEvent),
+    ParameterRampScheduled(ParameterRampScheduledEvent),
+    TokenTransfer(TokenTransferEvent),
+}
+
+/// Layout version of a decoded event relative to this SDK
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventVersion {
+    /// Layout matches exactly
+    Current,
+    /// A newer program appended fields this SDK does not know yet
+    Newer { unknown_bytes: usize },
+}
+
+/// A decoded event together with its layout version
+pub struct DecodedEvent {
+    pub event: ProgramEvent,
+    pub version: EventVersion,
+}
+
+/// Decode the body of event `T`, tolerating appended fields
+fn decode_body<T: AnchorDeserialize>(body: &[u8]) -> Result<(T, EventVersion), DecodeError> {
+    let mut slice = body;
+    let event =
+        T::deserialize(&mut slice).map_err(|err| DecodeError::InvalidData(err.to_string()))?;
+    let version = match slice.len() {
+        0 => EventVersion::Current,
+        unknown_bytes => EventVersion::Newer { unknown_bytes },
+    };
+    Ok((event, version))
+}
+
+/// Decode raw event bytes (discriminator followed by the Borsh body)
+pub fn decode_event(data: &[u8]) -> Result<DecodedEvent, DecodeError> {
+    if data.len() < 8 {
+        return Err(DecodeError::TooShort);
+    }
+    let (prefix, body) = data.split_at(8);
+
+    macro_rules! decode_as {
+        ($variant:ident) => {{
+            let (event, version) = decode_body(body)?;
+            (ProgramEvent::$variant(event), version)
+        }};
+    }
+
+    let (event, version) = match prefix {
+        p if p == CircuitBreakerTrippedEvent::DISCRIMINATOR => {
+            decode_as!(CircuitBreakerTripped)
+        }
+        p if p == DeactivationScheduledEvent::DISCRIMINATOR => {
+            decode_as!(DeactivationScheduled)
+        }
+        p if p == InstructionsToggledEvent::DISCRIMINATOR => decode_as!(InstructionsToggled),
+        p if p == InvariantsAttestedEvent::DISCRIMINATOR => decode_as!(InvariantsAttested),
+        p if p == LedgerVerifiedEvent::DISCRIMINATOR => decode_as!(LedgerVerified),
+        p if p == LookupTableRegisteredEvent::DISCRIMINATOR => {
+            decode_as!(LookupTableRegistered)
+        }
+        p if p == MintConfigChangedEvent::DISCRIMINATOR => decode_as!(MintConfigChanged),
+        p if p == OraclePriceOverriddenEvent::DISCRIMINATOR => {
+            decode_as!(OraclePriceOverridden)
+        }
+        p if p == ParameterRampScheduledEvent::DISCRIMINATOR => {
+            decode_as!(ParameterRampScheduled)
+        }
+        p if p == TokenTransferEvent::DISCRIMINATOR => decode_as!(TokenTransfer),
+        p if p == CommitmentRevealedEvent::DISCRIMINATOR => decode_as!(CommitmentRevealed),
+        p if p == AuctionSettledEvent::DISCRIMINATOR => decode_as!(AuctionSettled),
+        p if p == DocumentAttachedEvent::DISCRIMINATOR => decode_as!(DocumentAttached),
+        p if p == DocumentRemovedEvent::DISCRIMINATOR => decode_as!(DocumentRemoved),
+        p if p == AttestationChangedEvent::DISCRIMINATOR => decode_as!(AttestationChanged),
+        p if p == UserConsistencyReportEvent::DISCRIMINATOR => decode_as!(UserConsistencyReport),
+        p if p == NamespaceMetadataChangedEvent::DISCRIMINATOR => {
+            decode_as!(NamespaceMetadataChanged)
+        }
+        p if p == NamespaceBridgeChangedEvent::DISCRIMINATOR => decode_as!(NamespaceBridgeChanged),
+        p if p == CrossNamespaceTransferEvent::DISCRIMINATOR => decode_as!(CrossNamespaceTransfer),
+        p if p == AccountCompactedEvent::DISCRIMINATOR => decode_as!(AccountCompacted),
+        p if p == LamportsSweptEvent::DISCRIMINATOR => decode_as!(LamportsSwept),
+        p if p == AccountExpiredEvent::DISCRIMINATOR => decode_as!(AccountExpired),
+        p if p == CpiProgramUpdatedEvent::DISCRIMINATOR => decode_as!(CpiProgramUpdated),
+        p if p == FeeStrategyChangedEvent::DISCRIMINATOR => decode_as!(FeeStrategyChanged),
+        p if p == EmissionScheduleChangedEvent::DISCRIMINATOR => {
+            decode_as!(EmissionScheduleChanged)
+        }
+        p if p == AccountModeChangedEvent::DISCRIMINATOR => decode_as!(AccountModeChanged),
+        p if p == ComplianceAuthorityChangedEvent::DISCRIMINATOR => {
+            decode_as!(ComplianceAuthorityChanged)
+        }
+        p if p == ManagerGrantedEvent::DISCRIMINATOR => decode_as!(ManagerGranted),
+        p if p == ManagerRevokedEvent::DISCRIMINATOR => decode_as!(ManagerRevoked),
+        p if p == ManagerActionEvent::DISCRIMINATOR => decode_as!(ManagerAction),
+        p if p == TokensDepositedEvent::DISCRIMINATOR => decode_as!(TokensDeposited),
+        p if p == TokensWithdrawnEvent::DISCRIMINATOR => decode_as!(TokensWithdrawn),
+        p if p == PauseToggledEvent::DISCRIMINATOR => decode_as!(PauseToggled),
+        p if p == AllowanceChangedEvent::DISCRIMINATOR => decode_as!(AllowanceChanged),
+        p if p == TreasuryWithdrawnEvent::DISCRIMINATOR => decode_as!(TreasuryWithdrawn),
+        p if p == BatchTransferEvent::DISCRIMINATOR => decode_as!(BatchTransfer),
+        p if p == RoutedTransferEvent::DISCRIMINATOR => decode_as!(RoutedTransfer),
+        p if p == GuardiansChangedEvent::DISCRIMINATOR => decode_as!(GuardiansChanged),
+        p if p == ObligationRecordedEvent::DISCRIMINATOR => decode_as!(ObligationRecorded),
+        p if p == NetSettledEvent::DISCRIMINATOR => decode_as!(NetSettled),
+        p if p == UserMigratedEvent::DISCRIMINATOR => decode_as!(UserMigrated),
+        p if p == InvoiceIssuedEvent::DISCRIMINATOR => decode_as!(InvoiceIssued),
+        p if p == InvoicePaidEvent::DISCRIMINATOR => decode_as!(InvoicePaid),
+        p if p == InvoiceTemplateCancelledEvent::DISCRIMINATOR => {
+            decode_as!(InvoiceTemplateCancelled)
+        }
+        p if p == PromoRedeemedEvent::DISCRIMINATOR => decode_as!(PromoRedeemed),
+        p if p == EscrowCreatedEvent::DISCRIMINATOR => decode_as!(EscrowCreated),
+        p if p == EscrowClaimedEvent::DISCRIMINATOR => decode_as!(EscrowClaimed),
+        p if p == EscrowCancelledEvent::DISCRIMINATOR => decode_as!(EscrowCancelled),
+        p if p == CashbackConfiguredEvent::DISCRIMINATOR => decode_as!(CashbackConfigured),
+        p if p == CashbackMerchantChangedEvent::DISCRIMINATOR => {
+            decode_as!(CashbackMerchantChanged)
+        }
+        p if p == CashbackPaidEvent::DISCRIMINATOR => decode_as!(CashbackPaid),
+        p if p == CashbackSuspensionEvent::DISCRIMINATOR => decode_as!(CashbackSuspension),
+        p if p == CreditLineOpenedEvent::DISCRIMINATOR => decode_as!(CreditLineOpened),
+        p if p == CreditLineUtilizationEvent::DISCRIMINATOR => decode_as!(CreditLineUtilization),
+        p if p == CreditLineFrozenEvent::DISCRIMINATOR => decode_as!(CreditLineFrozen),
+        p if p == DailyLimitChangedEvent::DISCRIMINATOR => decode_as!(DailyLimitChanged),
+        p if p == ProfileUpdatedEvent::DISCRIMINATOR => decode_as!(ProfileUpdated),
+        p if p == InvoiceListedEvent::DISCRIMINATOR => decode_as!(InvoiceListed),
+        p if p == InvoiceSoldEvent::DISCRIMINATOR => decode_as!(InvoiceSold),
+        p if p == StatementGeneratedEvent::DISCRIMINATOR => decode_as!(StatementGenerated),
+        p if p == TaxLotsRealizedEvent::DISCRIMINATOR => decode_as!(TaxLotsRealized),
+        p if p == ComplianceReportCompiledEvent::DISCRIMINATOR => {
+            decode_as!(ComplianceReportCompiled)
+        }
+        p if p == WatchtowerSetEvent::DISCRIMINATOR => decode_as!(WatchtowerSet),
+        p if p == AlertRaisedEvent::DISCRIMINATOR => decode_as!(AlertRaised),
+        p if p == AlertDismissedEvent::DISCRIMINATOR => decode_as!(AlertDismissed),
+        p if p == ShadowFeeEvent::DISCRIMINATOR => decode_as!(ShadowFee),
+        p if p == ShadowFeeStrategySetEvent::DISCRIMINATOR => decode_as!(ShadowFeeStrategySet),
+        p if p == ChaosSetEvent::DISCRIMINATOR => decode_as!(ChaosSet),
+        p if p == PolicySetEvent::DISCRIMINATOR => decode_as!(PolicySet),
+        p if p == PolicyClosedEvent::DISCRIMINATOR => decode_as!(PolicyClosed),
+        p if p == ConfigSnapshotEvent::DISCRIMINATOR => decode_as!(ConfigSnapshot),
+        p if p == DeactivationFinalizedEvent::DISCRIMINATOR => decode_as!(DeactivationFinalized),
+        p if p == CrankMetricsInitializedEvent::DISCRIMINATOR => {
+            decode_as!(CrankMetricsInitialized)
+        }
+        p if p == CapabilityIssuedEvent::DISCRIMINATOR => decode_as!(CapabilityIssued),
+        p if p == CapabilityRevokedEvent::DISCRIMINATOR => decode_as!(CapabilityRevoked),
+        p if p == CapabilityUsedEvent::DISCRIMINATOR => decode_as!(CapabilityUsed),
+        p if p == InstructionDeprecatedEvent::DISCRIMINATOR => decode_as!(InstructionDeprecated),
+        p if p == TransferMemoEvent::DISCRIMINATOR => decode_as!(TransferMemo),
+        p if p == ProgramFingerprintEvent::DISCRIMINATOR => decode_as!(ProgramFingerprint),
+        p if p == DonationsSkimmedEvent::DISCRIMINATOR => decode_as!(DonationsSkimmed),
+        p if p == InvoiceRejectedEvent::DISCRIMINATOR => decode_as!(InvoiceRejected),
+        p if p == ProofOfReservesEvent::DISCRIMINATOR => decode_as!(ProofOfReserves),
+        _ => return Err(DecodeError::WrongDiscriminator),
+    };
+    Ok(DecodedEvent { event, version })
+}
+
+/// Decode the payload of a single `Program data:` log line
+/// Returns `None` for lines that are not event payloads
+pub fn decode_log_line(line: &str) -> Option<Result<DecodedEvent, DecodeError>> {
+    let encoded = line.strip_prefix(PROGRAM_DATA_PREFIX)?;
+    let data = match base64::engine::general_purpose::STANDARD.decode(encoded) {
+        Ok(data) => data,
+        Err(err) => return Some(Err(DecodeError::InvalidData(err.to_string()))),
+    };
+    Some(decode_event(&data))
+}
+
+/// Decode every event emitted by this program in a transaction's logs
+///
+/// Log lines are attributed by tracking the `invoke`/`success`/`failed`
+/// frames, so payloads written by other programs in the same transaction
+/// (including ones this program CPIs into) are skipped.
+pub fn decode_logs<'a>(
+    logs: impl IntoIterator<Item = &'a str>,
+) -> Vec<Result<DecodedEvent, DecodeError>> {
+    let program_id = ID.to_string();
+    let mut stack: Vec<bool> = Vec::new(); // One entry per frame: is it this program?
+    let mut events = Vec::new();
+
+    for line in logs {
+        if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let key = words.next().unwrap_or_default();
+            match words.next() {
+                Some("invoke") => {
+                    stack.push(key == program_id);
+                    continue;
+                }
+                Some("success") | Some("failed:") => {
+                    stack.pop();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if stack.last() == Some(&true) {
+            if let Some(event) = decode_log_line(line) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+/// Decode an event carried as `emit_cpi!` instruction data
+/// Returns `None` when the instruction is not an event self-invocation
+pub fn decode_cpi_event(ix_data: &[u8]) -> Option<Result<DecodedEvent, DecodeError>> {
+    let payload = ix_data.strip_prefix(EVENT_IX_TAG_LE.as_slice())?;
+    Some(decode_event(payload))
+}
+
+/*
+ * Websocket subscriptions
+ */
+
+/// JSON-RPC `logsSubscribe` request filtered to transactions mentioning the program
+///
+/// Feed each notification's `logs` array to `decode_logs`.
+pub fn logs_subscribe_request(request_id: u64, commitment: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","id":{request_id},"method":"logsSubscribe","params":[{{"mentions":["{ID}"]}},{{"commitment":"{commitment}"}}]}}"#
+    )
+}
+
+/// JSON-RPC `logsUnsubscribe` request for a subscription id returned by the node
+pub fn logs_unsubscribe_request(request_id: u64, subscription: u64) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","id":{request_id},"method":"logsUnsubscribe","params":[{subscription}]}}"#
+    )
+}
@@ -0,0 +1,3192 @@
+//! Typed instruction builders
+//!
+//! Each builder derives the PDAs an instruction needs and returns a ready
+//! `Instruction`. Accounts and argument encoding come from the program's
+//! generated `accounts` and `instruction` modules.
+
+use anchor_lang::solana_program::address_lookup_table;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token;
+use anchor_test_contract::{
+    accounts as ix_accounts, instruction as ix_data, promo_code_hash, AccountMode, AlertReason,
+    Auction, Bps, ChaosParams, Commitment, DocumentKind, EmissionSchedule, FeeStrategy,
+    IdempotencyKey, InstructionTag, JointMode, LotMethod, MintConfigParams,
+    NamespaceMetadataParams, ProfileParams, PromoReward, RouteHop, Seconds, ID,
+};
+
+use crate::pda;
+
+/// Assemble an instruction from generated account and data types
+fn build(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining: &[AccountMeta],
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend_from_slice(remaining);
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/*
+ * Configuration (admin)
+ */
+
+/// `create_namespace`: `admin` becomes the config admin and pays rent
+pub fn create_namespace(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CreateNamespace {
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateNamespace { namespace },
+        &[],
+    )
+}
+
+fn admin_config(namespace: u64, admin: Pubkey) -> ix_accounts::AdminConfig {
+    let config = pda::config(namespace).0;
+    ix_accounts::AdminConfig { config, admin }
+}
+
+/// `set_disabled_instructions`
+pub fn set_disabled_instructions(namespace: u64, admin: Pubkey, mask: u64) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetDisabledInstructions { mask },
+        &[],
+    )
+}
+
+/// `ramp_fee_rate`
+pub fn ramp_fee_rate(namespace: u64, admin: Pubkey, new_bps: u64, duration: i64) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::RampFeeRate { new_bps, duration },
+        &[],
+    )
+}
+
+/// `set_fee_strategy`
+pub fn set_fee_strategy(namespace: u64, admin: Pubkey, strategy: FeeStrategy) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetFeeStrategy { strategy },
+        &[],
+    )
+}
+
+/// `set_emission_schedule`
+pub fn set_emission_schedule(
+    namespace: u64,
+    admin: Pubkey,
+    schedule: EmissionSchedule,
+) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetEmissionSchedule { schedule },
+        &[],
+    )
+}
+
+/// `set_compliance_authority`; `digest` covers `authority_change_summary`
+pub fn set_compliance_authority(
+    namespace: u64,
+    admin: Pubkey,
+    compliance: Pubkey,
+    digest: [u8; 32],
+) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetComplianceAuthority { compliance, digest },
+        &[],
+    )
+}
+
+/// `set_circuit_breaker_multiple`
+pub fn set_circuit_breaker_multiple(
+    namespace: u64,
+    admin: Pubkey,
+    multiple_bps: u64,
+) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetCircuitBreakerMultiple { multiple_bps },
+        &[],
+    )
+}
+
+/// `set_price_deviation_limits`
+pub fn set_price_deviation_limits(
+    namespace: u64,
+    admin: Pubkey,
+    max_deviation_bps: u64,
+    window_secs: i64,
+) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetPriceDeviationLimits {
+            max_deviation_bps,
+            window_secs,
+        },
+        &[],
+    )
+}
+
+/// `initialize_epoch_stats`
+pub fn initialize_epoch_stats(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitializeEpochStats {
+            epoch_stats: pda::epoch_stats(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitializeEpochStats {},
+        &[],
+    )
+}
+
+/// `reset_circuit_breaker`
+pub fn reset_circuit_breaker(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ResetCircuitBreaker {
+            epoch_stats: pda::epoch_stats(&config).0,
+            config,
+            admin,
+        },
+        ix_data::ResetCircuitBreaker {},
+        &[],
+    )
+}
+
+/// `initialize_ledger`
+pub fn initialize_ledger(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitializeLedger {
+            ledger: pda::ledger(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitializeLedger {},
+        &[],
+    )
+}
+
+/// `initialize_fee_treasury`
+pub fn initialize_fee_treasury(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitializeFeeTreasury {
+            fee_treasury: pda::fee_treasury(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitializeFeeTreasury {},
+        &[],
+    )
+}
+
+/// `withdraw_treasury` crediting `amount` in fees to `recipient_authority`'s account
+pub fn withdraw_treasury(
+    namespace: u64,
+    admin: Pubkey,
+    recipient_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let recipient = pda::user(&config, &recipient_authority).0;
+    build(
+        ix_accounts::WithdrawTreasury {
+            fee_treasury: pda::fee_treasury(&config).0,
+            recipient,
+            ledger: pda::ledger(&config).0,
+            config,
+            admin,
+            recipient_activity: pda::activity_log(&recipient).0,
+        },
+        ix_data::WithdrawTreasury { amount },
+        &[],
+    )
+}
+
+/// `list_mint`
+pub fn list_mint(
+    namespace: u64,
+    admin: Pubkey,
+    mint: Pubkey,
+    params: MintConfigParams,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ListMint {
+            mint_config: pda::mint_config(&config, &mint).0,
+            mint,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::ListMint { params },
+        &[],
+    )
+}
+
+/// `update_mint_config`
+pub fn update_mint_config(
+    namespace: u64,
+    admin: Pubkey,
+    mint: Pubkey,
+    params: MintConfigParams,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ManageMint {
+            mint_config: pda::mint_config(&config, &mint).0,
+            config,
+            admin,
+        },
+        ix_data::UpdateMintConfig { params },
+        &[],
+    )
+}
+
+/// `delist_mint`
+pub fn delist_mint(namespace: u64, admin: Pubkey, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::DelistMint {
+            mint_config: pda::mint_config(&config, &mint).0,
+            config,
+            admin,
+        },
+        ix_data::DelistMint {},
+        &[],
+    )
+}
+
+/// `override_oracle_price`
+pub fn override_oracle_price(
+    namespace: u64,
+    admin: Pubkey,
+    mint: Pubkey,
+    price: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ManageMint {
+            mint_config: pda::mint_config(&config, &mint).0,
+            config,
+            admin,
+        },
+        ix_data::OverrideOraclePrice { price },
+        &[],
+    )
+}
+
+fn manage_lookup_table(
+    namespace: u64,
+    admin: Pubkey,
+    lookup_table: Pubkey,
+) -> ix_accounts::ManageLookupTable {
+    let config = pda::config(namespace).0;
+    ix_accounts::ManageLookupTable {
+        config,
+        admin,
+        lookup_table,
+        alt_authority: pda::alt_authority(&config).0,
+        address_lookup_table_program: address_lookup_table::program::ID,
+        system_program: system_program::ID,
+    }
+}
+
+/// `create_lookup_table`; returns the instruction and the table address
+pub fn create_lookup_table(
+    namespace: u64,
+    admin: Pubkey,
+    recent_slot: u64,
+    mint_configs: &[Pubkey],
+) -> (Instruction, Pubkey) {
+    let config = pda::config(namespace).0;
+    let (table, _) = address_lookup_table::instruction::derive_lookup_table_address(
+        &pda::alt_authority(&config).0,
+        recent_slot,
+    );
+    let remaining: Vec<AccountMeta> = mint_configs
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect();
+    let ix = build(
+        manage_lookup_table(namespace, admin, table),
+        ix_data::CreateLookupTable { recent_slot },
+        &remaining,
+    );
+    (ix, table)
+}
+
+/// `extend_lookup_table` with additional program-owned `addresses`
+pub fn extend_lookup_table(
+    namespace: u64,
+    admin: Pubkey,
+    lookup_table: Pubkey,
+    addresses: &[Pubkey],
+) -> Instruction {
+    let remaining: Vec<AccountMeta> = addresses
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect();
+    build(
+        manage_lookup_table(namespace, admin, lookup_table),
+        ix_data::ExtendLookupTable {},
+        &remaining,
+    )
+}
+
+/*
+ * Monitoring (permissionless)
+ */
+
+/// `verify_ledger`
+pub fn verify_ledger(namespace: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::VerifyLedger {
+            ledger: pda::ledger(&config).0,
+            config,
+        },
+        ix_data::VerifyLedger {},
+        &[],
+    )
+}
+
+/// `assert_invariants` over the user accounts of `authorities`
+pub fn assert_invariants(namespace: u64, authorities: &[Pubkey], complete: bool) -> Instruction {
+    let config = pda::config(namespace).0;
+    let remaining: Vec<AccountMeta> = authorities
+        .iter()
+        .map(|authority| AccountMeta::new_readonly(pda::user(&config, authority).0, false))
+        .collect();
+    build(
+        ix_accounts::AssertInvariants {
+            ledger: pda::ledger(&config).0,
+            epoch_stats: pda::epoch_stats(&config).0,
+            config,
+        },
+        ix_data::AssertInvariants { complete },
+        &remaining,
+    )
+}
+
+/// `assert_balance` on the account of `authority`
+pub fn assert_balance(namespace: u64, authority: Pubkey, min: u64, max: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::AssertBalance {
+            user: pda::user(&config, &authority).0,
+            config,
+        },
+        ix_data::AssertBalance { min, max },
+        &[],
+    )
+}
+
+/// `assert_vault_solvency` for the vault of `mint`
+pub fn assert_vault_solvency(namespace: u64, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::AssertVaultSolvency {
+            ledger: pda::ledger(&config).0,
+            mint_config: pda::mint_config(&config, &mint).0,
+            vault: pda::vault(&config, &mint).0,
+            config,
+        },
+        ix_data::AssertVaultSolvency {},
+        &[],
+    )
+}
+
+/// `sweep_excess_lamports` from the program-owned `account` to the treasury
+pub fn sweep_excess_lamports(namespace: u64, account: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SweepExcessLamports {
+            account,
+            config,
+            treasury: pda::treasury(&config).0,
+        },
+        ix_data::SweepExcessLamports {},
+        &[],
+    )
+}
+
+/// `expire` a time-bound `account`, returning its rent to `rent_recipient`;
+/// `settled` are the writable accounts its handler updates, in order
+pub fn expire(
+    namespace: u64,
+    account: Pubkey,
+    rent_recipient: Pubkey,
+    settled: &[Pubkey],
+) -> Instruction {
+    let settled: Vec<AccountMeta> = settled
+        .iter()
+        .map(|&settled| AccountMeta::new(settled, false))
+        .collect();
+    build(
+        ix_accounts::Expire {
+            account,
+            config: pda::config(namespace).0,
+            rent_recipient,
+        },
+        ix_data::Expire {},
+        &settled,
+    )
+}
+
+/*
+ * User instructions
+ */
+
+/// `initialize_user`
+pub fn initialize_user(namespace: u64, authority: Pubkey, name: String, age: u8) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitializeUser {
+            user: pda::user(&config, &authority).0,
+            authority,
+            system_program: system_program::ID,
+            config,
+        },
+        ix_data::InitializeUser { name, age },
+        &[],
+    )
+}
+
+/// `update_user`
+pub fn update_user(
+    namespace: u64,
+    authority: Pubkey,
+    new_name: Option<String>,
+    new_age: Option<u8>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::UpdateUser {
+            user,
+            authority,
+            config,
+            activity_log: pda::activity_log(&user).0,
+        },
+        ix_data::UpdateUser { new_name, new_age },
+        &[],
+    )
+}
+
+/// `transfer_tokens`; the recent-operations account is included when a key is
+/// given
+pub fn transfer_tokens(
+    namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+    idempotency_key: Option<IdempotencyKey>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    build(
+        ix_accounts::TransferTokens {
+            sender,
+            receiver,
+            authority,
+            config,
+            recent_ops: idempotency_key.map(|_| pda::recent_operations(&sender).0),
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            cashback: None,
+            merchant: None,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_lots: None,
+            receiver_lots: None,
+            shadow_fees: None,
+            chaos: None,
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::TransferTokens {
+            amount,
+            idempotency_key,
+        },
+        &[],
+    )
+}
+
+/// `transfer_tokens_v2`: `transfer_tokens` with a memo
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_tokens_v2(
+    namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+    idempotency_key: Option<IdempotencyKey>,
+    memo: Vec<u8>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    build(
+        ix_accounts::TransferTokens {
+            sender,
+            receiver,
+            authority,
+            config,
+            recent_ops: idempotency_key.map(|_| pda::recent_operations(&sender).0),
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            cashback: None,
+            merchant: None,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_lots: None,
+            receiver_lots: None,
+            shadow_fees: None,
+            chaos: None,
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::TransferTokensV2 {
+            amount,
+            idempotency_key,
+            memo,
+        },
+        &[],
+    )
+}
+
+/// `transfer_tokens` to a registered cashback merchant, crediting the
+/// sender cashback
+pub fn merchant_transfer(
+    namespace: u64,
+    authority: Pubkey,
+    merchant_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &merchant_authority).0;
+    build(
+        ix_accounts::TransferTokens {
+            sender,
+            receiver,
+            authority,
+            config,
+            recent_ops: None,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            cashback: Some(pda::cashback(&config).0),
+            merchant: Some(pda::cashback_merchant(&receiver).0),
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_lots: None,
+            receiver_lots: None,
+            shadow_fees: None,
+            chaos: None,
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::TransferTokens {
+            amount,
+            idempotency_key: None,
+        },
+        &[],
+    )
+}
+
+/// `transfer_tokens` keeping tax lots: the sender's book with its pages
+/// `sender_pages` when given, and the receiver's book with its newest page
+/// `receiver_page` when given
+pub fn tax_lot_transfer(
+    namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+    sender_pages: Option<&[u32]>,
+    receiver_page: Option<u32>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    let pages: Vec<AccountMeta> = sender_pages
+        .unwrap_or_default()
+        .iter()
+        .map(|index| pda::tax_lot_page(&sender, *index).0)
+        .chain(receiver_page.map(|index| pda::tax_lot_page(&receiver, index).0))
+        .map(|page| AccountMeta::new(page, false))
+        .collect();
+    build(
+        ix_accounts::TransferTokens {
+            sender,
+            receiver,
+            authority,
+            config,
+            recent_ops: None,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            cashback: None,
+            merchant: None,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_lots: sender_pages.map(|_| pda::tax_lots(&sender).0),
+            receiver_lots: receiver_page.map(|_| pda::tax_lots(&receiver).0),
+            shadow_fees: None,
+            chaos: None,
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::TransferTokens {
+            amount,
+            idempotency_key: None,
+        },
+        &pages,
+    )
+}
+
+/// `transfer_tokens` also pricing the transfer under the namespace's
+/// rehearsed fee strategy
+pub fn shadow_transfer(
+    namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    build(
+        ix_accounts::TransferTokens {
+            sender,
+            receiver,
+            authority,
+            config,
+            recent_ops: None,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            cashback: None,
+            merchant: None,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_lots: None,
+            receiver_lots: None,
+            shadow_fees: Some(pda::shadow_fees(&config).0),
+            chaos: None,
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::TransferTokens {
+            amount,
+            idempotency_key: None,
+        },
+        &[],
+    )
+}
+
+/// `transfer_tokens` hooked by the namespace's chaos configuration
+pub fn chaos_transfer(
+    namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    build(
+        ix_accounts::TransferTokens {
+            sender,
+            receiver,
+            authority,
+            config,
+            recent_ops: None,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            cashback: None,
+            merchant: None,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_lots: None,
+            receiver_lots: None,
+            shadow_fees: None,
+            chaos: Some(pda::chaos(&config).0),
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::TransferTokens {
+            amount,
+            idempotency_key: None,
+        },
+        &[],
+    )
+}
+
+/// `batch_transfer` paying each `(receiver_authority, amount)` leg in order
+pub fn batch_transfer(namespace: u64, authority: Pubkey, legs: &[(Pubkey, u64)]) -> Instruction {
+    let config = pda::config(namespace).0;
+    let receivers: Vec<Pubkey> = legs
+        .iter()
+        .map(|(receiver, _)| pda::user(&config, receiver).0)
+        .collect();
+    let logs = receivers
+        .iter()
+        .map(|receiver| pda::activity_log(receiver).0);
+    let remaining: Vec<AccountMeta> = receivers
+        .iter()
+        .copied()
+        .chain(logs)
+        .map(|account| AccountMeta::new(account, false))
+        .collect();
+    let sender = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::BatchTransfer {
+            sender,
+            authority,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            sender_policy: pda::policy(&sender).0,
+            sender_activity: pda::activity_log(&sender).0,
+        },
+        ix_data::BatchTransfer {
+            amounts: legs.iter().map(|(_, amount)| *amount).collect(),
+        },
+        &remaining,
+    )
+}
+
+/// `routed_transfer` of `amount` through each `(authority, policy)` hop to `receiver_authority`
+pub fn routed_transfer(
+    namespace: u64,
+    authority: Pubkey,
+    hops: &[(Pubkey, RouteHop)],
+    receiver_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let accounts: Vec<Pubkey> = hops
+        .iter()
+        .map(|(hop, _)| hop)
+        .chain([&receiver_authority])
+        .map(|hop| pda::user(&config, hop).0)
+        .collect();
+    let logs = accounts.iter().map(|account| pda::activity_log(account).0);
+    let route: Vec<AccountMeta> = accounts
+        .iter()
+        .copied()
+        .chain(logs)
+        .map(|account| AccountMeta::new(account, false))
+        .collect();
+    let sender = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::RoutedTransfer {
+            sender,
+            authority,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            sender_policy: pda::policy(&sender).0,
+            sender_activity: pda::activity_log(&sender).0,
+        },
+        ix_data::RoutedTransfer {
+            path: hops.iter().map(|(_, policy)| *policy).collect(),
+            amount,
+        },
+        &route,
+    )
+}
+
+/// `set_account_mode` for the caller's own account
+pub fn set_account_mode(namespace: u64, authority: Pubkey, mode: AccountMode) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetAccountMode {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::SetAccountMode { mode },
+        &[],
+    )
+}
+
+/// `compliance_set_account_mode` on the account of `user_authority`
+pub fn compliance_set_account_mode(
+    namespace: u64,
+    compliance: Pubkey,
+    user_authority: Pubkey,
+    mode: AccountMode,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ComplianceSetAccountMode {
+            user: pda::user(&config, &user_authority).0,
+            config,
+            compliance,
+        },
+        ix_data::ComplianceSetAccountMode { mode },
+        &[],
+    )
+}
+
+/// `grant_manager`: `authority` lets `manager` act until `expires_at` and pays rent
+pub fn grant_manager(
+    namespace: u64,
+    authority: Pubkey,
+    manager: Pubkey,
+    permissions: u64,
+    expires_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::GrantManager {
+            grant: pda::manager_grant(&user, &manager).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::GrantManager {
+            manager,
+            permissions,
+            expires_at,
+        },
+        &[],
+    )
+}
+
+/// `revoke_manager`: rent returns to `authority`
+pub fn revoke_manager(namespace: u64, authority: Pubkey, manager: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::RevokeManager {
+            grant: pda::manager_grant(&user, &manager).0,
+            user,
+            authority,
+            config,
+        },
+        ix_data::RevokeManager {},
+        &[],
+    )
+}
+
+/// `manager_update_user` on the account of `user_authority`
+pub fn manager_update_user(
+    namespace: u64,
+    manager: Pubkey,
+    user_authority: Pubkey,
+    new_name: Option<String>,
+    new_age: Option<u8>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &user_authority).0;
+    build(
+        ix_accounts::ManagerUpdateUser {
+            grant: pda::manager_grant(&user, &manager).0,
+            user,
+            manager,
+            config,
+        },
+        ix_data::ManagerUpdateUser { new_name, new_age },
+        &[],
+    )
+}
+
+/// `set_paused`
+pub fn set_paused(namespace: u64, admin: Pubkey, paused: bool) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetPaused { paused },
+        &[],
+    )
+}
+
+/// `create_vault` for a listed `mint`
+pub fn create_vault(namespace: u64, admin: Pubkey, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CreateVault {
+            vault: pda::vault(&config, &mint).0,
+            vault_authority: pda::vault_authority(&config).0,
+            mint_config: pda::mint_config(&config, &mint).0,
+            mint,
+            config,
+            admin,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateVault {},
+        &[],
+    )
+}
+
+/// `open_vault_position`: `authority` opens its position in the `mint` vault
+pub fn open_vault_position(namespace: u64, authority: Pubkey, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::OpenVaultPosition {
+            position: pda::vault_position(&user, &mint).0,
+            user,
+            authority,
+            mint_config: pda::mint_config(&config, &mint).0,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::OpenVaultPosition {},
+        &[],
+    )
+}
+
+/// Accounts shared by `deposit_tokens` and `withdraw_tokens`
+fn move_tokens(
+    namespace: u64,
+    authority: Pubkey,
+    mint: Pubkey,
+    user_token: Pubkey,
+) -> ix_accounts::MoveTokens {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    ix_accounts::MoveTokens {
+        user,
+        authority,
+        user_token,
+        vault: pda::vault(&config, &mint).0,
+        vault_authority: pda::vault_authority(&config).0,
+        mint_config: pda::mint_config(&config, &mint).0,
+        config,
+        ledger: pda::ledger(&config).0,
+        token_program: token::ID,
+        user_policy: pda::policy(&user).0,
+        user_activity: pda::activity_log(&user).0,
+        position: pda::vault_position(&user, &mint).0,
+    }
+}
+
+/// `deposit_tokens`: `amount` raw tokens from `user_token` into the vault
+pub fn deposit_tokens(
+    namespace: u64,
+    authority: Pubkey,
+    mint: Pubkey,
+    user_token: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        move_tokens(namespace, authority, mint, user_token),
+        ix_data::DepositTokens { amount },
+        &[],
+    )
+}
+
+/// `withdraw_tokens`: debit `amount` internal units, paid out to `user_token`
+///
+/// Large withdrawals need `digest`, covering `withdrawal_summary`.
+pub fn withdraw_tokens(
+    namespace: u64,
+    authority: Pubkey,
+    mint: Pubkey,
+    user_token: Pubkey,
+    amount: u64,
+    digest: Option<[u8; 32]>,
+) -> Instruction {
+    build(
+        move_tokens(namespace, authority, mint, user_token),
+        ix_data::WithdrawTokens { amount, digest },
+        &[],
+    )
+}
+
+/// `skim_donations`: book tokens sent straight to the `mint` vault as fees
+pub fn skim_donations(namespace: u64, admin: Pubkey, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SkimDonations {
+            vault: pda::vault(&config, &mint).0,
+            mint_config: pda::mint_config(&config, &mint).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            ledger: pda::ledger(&config).0,
+            config,
+            admin,
+        },
+        ix_data::SkimDonations {},
+        &[],
+    )
+}
+
+/// `init_reserves_attestation` for a listed `mint`
+pub fn init_reserves_attestation(namespace: u64, admin: Pubkey, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let mint_config = pda::mint_config(&config, &mint).0;
+    build(
+        ix_accounts::InitReservesAttestation {
+            attestation: pda::reserves(&mint_config).0,
+            mint_config,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitReservesAttestation {},
+        &[],
+    )
+}
+
+/// `attest_reserves` of the `mint` vault, cranked by anyone once per epoch
+pub fn attest_reserves(namespace: u64, mint: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let mint_config = pda::mint_config(&config, &mint).0;
+    build(
+        ix_accounts::AttestReserves {
+            attestation: pda::reserves(&mint_config).0,
+            vault: pda::vault(&config, &mint).0,
+            mint_config,
+            config,
+        },
+        ix_data::AttestReserves {},
+        &[],
+    )
+}
+
+/// `approve_delegate`: `authority` lets `delegate` spend `amount` and pays rent
+pub fn approve_delegate(
+    namespace: u64,
+    authority: Pubkey,
+    delegate: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let owner = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::ApproveDelegate {
+            allowance: pda::allowance(&owner, &delegate).0,
+            owner,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::ApproveDelegate { delegate, amount },
+        &[],
+    )
+}
+
+/// `revoke_delegate`: rent returns to `authority`
+pub fn revoke_delegate(namespace: u64, authority: Pubkey, delegate: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let owner = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::RevokeDelegate {
+            allowance: pda::allowance(&owner, &delegate).0,
+            owner,
+            authority,
+            config,
+        },
+        ix_data::RevokeDelegate {},
+        &[],
+    )
+}
+
+/// `transfer_from` the account of `owner_authority`, signed by `delegate`
+pub fn transfer_from(
+    namespace: u64,
+    delegate: Pubkey,
+    owner_authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let owner = pda::user(&config, &owner_authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    build(
+        ix_accounts::TransferFrom {
+            allowance: pda::allowance(&owner, &delegate).0,
+            owner,
+            receiver,
+            delegate,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            owner_policy: pda::policy(&owner).0,
+            owner_activity: pda::activity_log(&owner).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+        },
+        ix_data::TransferFrom { amount },
+        &[],
+    )
+}
+
+/// `reactivate_user`, once the namespace's cooldown has elapsed
+pub fn reactivate_user(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ReactivateUser {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::ReactivateUser {},
+        &[],
+    )
+}
+
+/// `close_user`: rent returns to `authority`
+pub fn close_user(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CloseUser {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::CloseUser {},
+        &[],
+    )
+}
+
+/// `set_reactivation_cooldown`
+pub fn set_reactivation_cooldown(namespace: u64, admin: Pubkey, cooldown_secs: i64) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetReactivationCooldown { cooldown_secs },
+        &[],
+    )
+}
+
+/// `set_clock_tolerance`
+pub fn set_clock_tolerance(namespace: u64, admin: Pubkey, tolerance_secs: i64) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetClockTolerance { tolerance_secs },
+        &[],
+    )
+}
+
+/// `set_instruction_sunset`
+pub fn set_instruction_sunset(
+    namespace: u64,
+    admin: Pubkey,
+    instruction: InstructionTag,
+    sunset_at: i64,
+) -> Instruction {
+    build(
+        admin_config(namespace, admin),
+        ix_data::SetInstructionSunset {
+            instruction,
+            sunset_at,
+        },
+        &[],
+    )
+}
+
+/// `begin_guard` for the caller's own account; pair with `end_guard`
+pub fn begin_guard(namespace: u64, authority: Pubkey, max_outflow: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::BeginGuard {
+            guard: pda::spend_guard(&user).0,
+            user,
+            config,
+            authority,
+            instructions: instructions_sysvar::ID,
+            system_program: system_program::ID,
+        },
+        ix_data::BeginGuard { max_outflow },
+        &[],
+    )
+}
+
+/// `end_guard` for `authority`'s account
+pub fn end_guard(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::EndGuard {
+            guard: pda::spend_guard(&user).0,
+            user,
+            config,
+            authority,
+        },
+        ix_data::EndGuard {},
+        &[],
+    )
+}
+
+/// `instructions` bracketed by `begin_guard` and `end_guard`, so the
+/// transaction reverts if more than `max_outflow` leaves the account
+pub fn guarded(
+    namespace: u64,
+    authority: Pubkey,
+    max_outflow: u64,
+    instructions: Vec<Instruction>,
+) -> Vec<Instruction> {
+    let mut bracketed = vec![begin_guard(namespace, authority, max_outflow)];
+    bracketed.extend(instructions);
+    bracketed.push(end_guard(namespace, authority));
+    bracketed
+}
+
+/// `set_guardians` for the caller's own account
+///
+/// Once guardians are set, add the current ones with `with_guardians`.
+pub fn set_guardians(
+    namespace: u64,
+    authority: Pubkey,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    limit: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetGuardians {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::SetGuardians {
+            guardians,
+            threshold,
+            limit,
+        },
+        &[],
+    )
+}
+
+/// `guardian_deactivate_user` locking `authority`'s account, signed by `guardians`
+pub fn guardian_deactivate_user(
+    namespace: u64,
+    authority: Pubkey,
+    guardians: &[Pubkey],
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let ix = build(
+        ix_accounts::GuardianDeactivateUser {
+            user: pda::user(&config, &authority).0,
+            config,
+        },
+        ix_data::GuardianDeactivateUser {},
+        &[],
+    );
+    with_guardians(ix, guardians)
+}
+
+/// `ix` with `guardians` appended as signers, for operations above the
+/// account's guardian limit and for reactivating a guarded account
+///
+/// Guardian signers go last, after any other remaining accounts.
+pub fn with_guardians(mut ix: Instruction, guardians: &[Pubkey]) -> Instruction {
+    ix.accounts.extend(
+        guardians
+            .iter()
+            .map(|guardian| AccountMeta::new_readonly(*guardian, true)),
+    );
+    ix
+}
+
+/// `deactivate_user`
+pub fn deactivate_user(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::DeactivateUser {
+            user,
+            authority,
+            config,
+            activity_log: pda::activity_log(&user).0,
+        },
+        ix_data::DeactivateUser {},
+        &[],
+    )
+}
+
+/// `convert_to_joint`
+pub fn convert_to_joint(
+    namespace: u64,
+    authority: Pubkey,
+    co_authority: Pubkey,
+    threshold: u64,
+    mode: JointMode,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::ConvertToJoint {
+            user,
+            joint: pda::joint(&user).0,
+            authority,
+            co_authority,
+            system_program: system_program::ID,
+            config,
+        },
+        ix_data::ConvertToJoint { threshold, mode },
+        &[],
+    )
+}
+
+/// `joint_transfer`; `primary` is the original authority of the joint account
+pub fn joint_transfer(
+    namespace: u64,
+    primary: Pubkey,
+    receiver_authority: Pubkey,
+    signer: Pubkey,
+    co_signer: Option<Pubkey>,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &primary).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    build(
+        ix_accounts::JointTransfer {
+            sender,
+            joint: pda::joint(&sender).0,
+            receiver,
+            signer,
+            co_signer,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+            sender_policy: pda::policy(&sender).0,
+        },
+        ix_data::JointTransfer { amount },
+        &[],
+    )
+}
+
+/// `schedule_deactivation`; pass zeros to clear the window
+pub fn schedule_deactivation(
+    namespace: u64,
+    authority: Pubkey,
+    start: i64,
+    end: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ScheduleDeactivation {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::ScheduleDeactivation { start, end },
+        &[],
+    )
+}
+
+/// `init_recent_operations`
+pub fn init_recent_operations(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::InitRecentOperations {
+            recent_ops: pda::recent_operations(&user).0,
+            user,
+            authority,
+            system_program: system_program::ID,
+            config,
+        },
+        ix_data::InitRecentOperations {},
+        &[],
+    )
+}
+
+/// `init_activity_log` for the caller's account
+pub fn init_activity_log(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::InitActivityLog {
+            activity_log: pda::activity_log(&user).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::InitActivityLog {},
+        &[],
+    )
+}
+
+/*
+ * Localnet
+ */
+
+/// `bootstrap_demo` creating `users` demo accounts; needs a `bootstrap-demo` build
+pub fn bootstrap_demo(namespace: u64, admin: Pubkey, users: u8) -> Instruction {
+    let config = pda::config(namespace).0;
+    let remaining: Vec<AccountMeta> = (0..users)
+        .map(|index| {
+            let authority = anchor_test_contract::demo_authority(index);
+            AccountMeta::new(pda::user(&config, &authority).0, false)
+        })
+        .collect();
+    build(
+        ix_accounts::BootstrapDemo {
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::BootstrapDemo { namespace, users },
+        &remaining,
+    )
+}
+
+/*
+ * Commit-reveal
+ */
+
+/// `commit` of `hash` under `domain`; see `Commitment::hash_for`
+pub fn commit(
+    namespace: u64,
+    owner: Pubkey,
+    domain: u64,
+    hash: [u8; 32],
+    reveal_after: i64,
+    expires_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::Commit {
+            commitment: pda::commitment(&config, &owner, domain).0,
+            owner,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::Commit {
+            domain,
+            hash,
+            reveal_after,
+            expires_at,
+        },
+        &[],
+    )
+}
+
+/// `reveal` of the preimage committed under `domain`
+pub fn reveal(namespace: u64, owner: Pubkey, domain: u64, preimage: Vec<u8>) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::Reveal {
+            commitment: pda::commitment(&config, &owner, domain).0,
+            owner,
+            config,
+        },
+        ix_data::Reveal { preimage },
+        &[],
+    )
+}
+
+/*
+ * Sealed-bid auctions
+ */
+
+/// `create_auction`
+pub fn create_auction(
+    namespace: u64,
+    seller: Pubkey,
+    auction_id: u64,
+    commit_end: i64,
+    reveal_end: i64,
+    reserve_price: u64,
+    non_reveal_penalty_bps: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CreateAuction {
+            auction: pda::auction(&config, &seller, auction_id).0,
+            seller,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateAuction {
+            auction_id,
+            commit_end,
+            reveal_end,
+            reserve_price,
+            non_reveal_penalty_bps,
+        },
+        &[],
+    )
+}
+
+/// `commit` plus `place_bid` for a sealed bid of `amount` hidden by `salt`
+///
+/// `auction_state` is the decoded auction; its windows bound the commitment.
+pub fn place_bid(
+    namespace: u64,
+    auction_state: &Auction,
+    bidder: Pubkey,
+    amount: u64,
+    salt: &[u8; 32],
+    deposit: u64,
+) -> [Instruction; 2] {
+    let config = pda::config(namespace).0;
+    let auction = pda::auction(&config, &auction_state.seller, auction_state.auction_id).0;
+    let domain = Auction::commitment_domain(&auction);
+    let hash = Commitment::hash_for(&bidder, domain, &Auction::bid_preimage(amount, salt));
+    let bidder_account = pda::user(&config, &bidder).0;
+    let place = build(
+        ix_accounts::PlaceBid {
+            auction,
+            bid: pda::sealed_bid(&auction, &bidder).0,
+            commitment: pda::commitment(&config, &bidder, domain).0,
+            bidder,
+            bidder_account,
+            bidder_policy: pda::policy(&bidder_account).0,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::PlaceBid { deposit },
+        &[],
+    );
+    let commit = commit(
+        namespace,
+        bidder,
+        domain,
+        hash,
+        auction_state.commit_end,
+        auction_state.reveal_end,
+    );
+    [commit, place]
+}
+
+/// `reveal_bid`
+pub fn reveal_bid(
+    namespace: u64,
+    auction: Pubkey,
+    bidder: Pubkey,
+    amount: u64,
+    salt: [u8; 32],
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::RevealBid {
+            auction,
+            bid: pda::sealed_bid(&auction, &bidder).0,
+            commitment: pda::commitment(&config, &bidder, Auction::commitment_domain(&auction)).0,
+            bidder,
+            config,
+        },
+        ix_data::RevealBid { amount, salt },
+        &[],
+    )
+}
+
+/// `settle_auction` for `auction` in `namespace`
+pub fn settle_auction(namespace: u64, auction: Pubkey) -> Instruction {
+    build(
+        ix_accounts::SettleAuction {
+            auction,
+            config: pda::config(namespace).0,
+        },
+        ix_data::SettleAuction {},
+        &[],
+    )
+}
+
+/// `claim_bid`
+pub fn claim_bid(namespace: u64, auction: Pubkey, seller: Pubkey, bidder: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ClaimBid {
+            auction,
+            bid: pda::sealed_bid(&auction, &bidder).0,
+            bidder,
+            seller,
+            bidder_account: pda::user(&config, &bidder).0,
+            config,
+        },
+        ix_data::ClaimBid {},
+        &[],
+    )
+}
+
+/// `cancel_auction`
+pub fn cancel_auction(namespace: u64, seller: Pubkey, auction_id: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CancelAuction {
+            auction: pda::auction(&config, &seller, auction_id).0,
+            seller,
+        },
+        ix_data::CancelAuction {},
+        &[],
+    )
+}
+
+/*
+ * Profile attachments
+ */
+
+/// `create_attachment_page`
+pub fn create_attachment_page(namespace: u64, authority: Pubkey, page: u16) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CreateAttachmentPage {
+            attachments: pda::attachment_page(&user, page).0,
+            user,
+            authority,
+            system_program: system_program::ID,
+            config,
+        },
+        ix_data::CreateAttachmentPage { page },
+        &[],
+    )
+}
+
+fn manage_attachments(
+    namespace: u64,
+    authority: Pubkey,
+    page: u16,
+) -> ix_accounts::ManageAttachments {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    ix_accounts::ManageAttachments {
+        attachments: pda::attachment_page(&user, page).0,
+        user,
+        authority,
+        config,
+        system_program: system_program::ID,
+    }
+}
+
+/// `attach_document` on attachment page `page`
+pub fn attach_document(
+    namespace: u64,
+    authority: Pubkey,
+    page: u16,
+    kind: DocumentKind,
+    cid_hash: [u8; 32],
+) -> Instruction {
+    build(
+        manage_attachments(namespace, authority, page),
+        ix_data::AttachDocument { kind, cid_hash },
+        &[],
+    )
+}
+
+/// `remove_document` from attachment page `page`
+pub fn remove_document(
+    namespace: u64,
+    authority: Pubkey,
+    page: u16,
+    cid_hash: [u8; 32],
+) -> Instruction {
+    build(
+        manage_attachments(namespace, authority, page),
+        ix_data::RemoveDocument { cid_hash },
+        &[],
+    )
+}
+
+/// `compact_account` shrinking attachment page `page` to fit its entries
+pub fn compact_account(namespace: u64, authority: Pubkey, page: u16) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CompactAccount {
+            attachments: pda::attachment_page(&user, page).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::CompactAccount {},
+        &[],
+    )
+}
+
+/*
+ * Attestations
+ */
+
+/// `register_issuer`
+pub fn register_issuer(namespace: u64, admin: Pubkey, issuer: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::RegisterIssuer {
+            issuer_record: pda::issuer_record(&config, &issuer).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::RegisterIssuer { issuer },
+        &[],
+    )
+}
+
+/// `set_issuer_active`
+pub fn set_issuer_active(
+    namespace: u64,
+    admin: Pubkey,
+    issuer: Pubkey,
+    active: bool,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetIssuerActive {
+            issuer_record: pda::issuer_record(&config, &issuer).0,
+            config,
+            admin,
+        },
+        ix_data::SetIssuerActive { active },
+        &[],
+    )
+}
+
+/// `issue_attestation` about the user owned by `user_authority`
+pub fn issue_attestation(
+    namespace: u64,
+    issuer: Pubkey,
+    user_authority: Pubkey,
+    claim_type: u16,
+    data: [u8; 32],
+    expires_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &user_authority).0;
+    build(
+        ix_accounts::IssueAttestation {
+            attestation: pda::attestation(&issuer, &user, claim_type).0,
+            issuer_record: pda::issuer_record(&config, &issuer).0,
+            user,
+            issuer,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::IssueAttestation {
+            claim_type,
+            data,
+            expires_at,
+        },
+        &[],
+    )
+}
+
+/// `revoke_attestation`
+pub fn revoke_attestation(issuer: Pubkey, user: Pubkey, claim_type: u16) -> Instruction {
+    build(
+        ix_accounts::RevokeAttestation {
+            attestation: pda::attestation(&issuer, &user, claim_type).0,
+            issuer,
+        },
+        ix_data::RevokeAttestation {},
+        &[],
+    )
+}
+
+/// `verify_attestation`
+pub fn verify_attestation(
+    namespace: u64,
+    issuer: Pubkey,
+    user: Pubkey,
+    claim_type: u16,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::VerifyAttestation {
+            attestation: pda::attestation(&issuer, &user, claim_type).0,
+            issuer_record: pda::issuer_record(&config, &issuer).0,
+            config,
+        },
+        ix_data::VerifyAttestation { user, claim_type },
+        &[],
+    )
+}
+
+/// `check_user_consistency` for the user of `authority` over `attachment_pages`
+pub fn check_user_consistency(
+    namespace: u64,
+    authority: Pubkey,
+    attachment_pages: &[u16],
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    let remaining: Vec<AccountMeta> = attachment_pages
+        .iter()
+        .map(|page| AccountMeta::new_readonly(pda::attachment_page(&user, *page).0, false))
+        .collect();
+    build(
+        ix_accounts::CheckUserConsistency {
+            user,
+            joint: pda::joint(&user).0,
+            recent_ops: pda::recent_operations(&user).0,
+            config,
+        },
+        ix_data::CheckUserConsistency {},
+        &remaining,
+    )
+}
+
+/*
+ * Namespace metadata
+ */
+
+/// `create_namespace_metadata`
+pub fn create_namespace_metadata(
+    namespace: u64,
+    admin: Pubkey,
+    params: NamespaceMetadataParams,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CreateNamespaceMetadata {
+            metadata: pda::namespace_metadata(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateNamespaceMetadata { params },
+        &[],
+    )
+}
+
+/// `update_namespace_metadata`
+pub fn update_namespace_metadata(
+    namespace: u64,
+    admin: Pubkey,
+    params: NamespaceMetadataParams,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::UpdateNamespaceMetadata {
+            metadata: pda::namespace_metadata(&config).0,
+            config,
+            admin,
+        },
+        ix_data::UpdateNamespaceMetadata { params },
+        &[],
+    )
+}
+
+/// `create_cpi_registry`: `admin` pays rent
+pub fn create_cpi_registry(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CreateCpiRegistry {
+            registry: pda::cpi_registry(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateCpiRegistry {},
+        &[],
+    )
+}
+
+/// Accounts shared by `allow_cpi_program` and `revoke_cpi_program`
+fn update_cpi_registry(namespace: u64, admin: Pubkey) -> ix_accounts::UpdateCpiRegistry {
+    let config = pda::config(namespace).0;
+    ix_accounts::UpdateCpiRegistry {
+        registry: pda::cpi_registry(&config).0,
+        config,
+        admin,
+    }
+}
+
+/// `allow_cpi_program`
+pub fn allow_cpi_program(namespace: u64, admin: Pubkey, program_id: Pubkey) -> Instruction {
+    build(
+        update_cpi_registry(namespace, admin),
+        ix_data::AllowCpiProgram { program_id },
+        &[],
+    )
+}
+
+/// `revoke_cpi_program`
+pub fn revoke_cpi_program(namespace: u64, admin: Pubkey, program_id: Pubkey) -> Instruction {
+    build(
+        update_cpi_registry(namespace, admin),
+        ix_data::RevokeCpiProgram { program_id },
+        &[],
+    )
+}
+
+/*
+ * Cross-namespace bridges
+ */
+
+/// Ordered `(config_a, config_b, bridge)` for two namespaces
+fn bridge_accounts(namespace: u64, other_namespace: u64) -> (Pubkey, Pubkey, Pubkey) {
+    let config = pda::config(namespace).0;
+    let other = pda::config(other_namespace).0;
+    let (config_a, config_b) = if config < other {
+        (config, other)
+    } else {
+        (other, config)
+    };
+    (config_a, config_b, pda::namespace_bridge(&config, &other).0)
+}
+
+/// `propose_namespace_bridge` by the admin of `namespace`
+pub fn propose_namespace_bridge(
+    namespace: u64,
+    other_namespace: u64,
+    admin: Pubkey,
+    fee_bps: u64,
+    source_share_bps: u64,
+) -> Instruction {
+    let (config_a, config_b, bridge) = bridge_accounts(namespace, other_namespace);
+    build(
+        ix_accounts::ProposeNamespaceBridge {
+            bridge,
+            config_a,
+            config_b,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::ProposeNamespaceBridge {
+            fee_bps,
+            source_share_bps,
+        },
+        &[],
+    )
+}
+
+fn manage_namespace_bridge(
+    namespace: u64,
+    other_namespace: u64,
+    admin: Pubkey,
+    payer: Pubkey,
+) -> ix_accounts::ManageNamespaceBridge {
+    let (config_a, config_b, bridge) = bridge_accounts(namespace, other_namespace);
+    ix_accounts::ManageNamespaceBridge {
+        bridge,
+        config_a,
+        config_b,
+        admin,
+        payer,
+    }
+}
+
+/// `approve_namespace_bridge` by the admin of `namespace`; `payer` is the proposer
+pub fn approve_namespace_bridge(
+    namespace: u64,
+    other_namespace: u64,
+    admin: Pubkey,
+    payer: Pubkey,
+    fee_bps: u64,
+    source_share_bps: u64,
+) -> Instruction {
+    build(
+        manage_namespace_bridge(namespace, other_namespace, admin, payer),
+        ix_data::ApproveNamespaceBridge {
+            fee_bps,
+            source_share_bps,
+        },
+        &[],
+    )
+}
+
+/// `close_namespace_bridge` by the admin of `namespace`; `payer` is the proposer
+pub fn close_namespace_bridge(
+    namespace: u64,
+    other_namespace: u64,
+    admin: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    build(
+        manage_namespace_bridge(namespace, other_namespace, admin, payer),
+        ix_data::CloseNamespaceBridge {},
+        &[],
+    )
+}
+
+/// `cross_namespace_transfer` from `authority` in `namespace` to `receiver_authority` in `dest_namespace`
+pub fn cross_namespace_transfer(
+    namespace: u64,
+    dest_namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let source_config = pda::config(namespace).0;
+    let dest_config = pda::config(dest_namespace).0;
+    let sender = pda::user(&source_config, &authority).0;
+    let receiver = pda::user(&dest_config, &receiver_authority).0;
+    build(
+        ix_accounts::CrossNamespaceTransfer {
+            sender,
+            receiver,
+            authority,
+            bridge: pda::namespace_bridge(&source_config, &dest_config).0,
+            source_config,
+            dest_config,
+            source_epoch_stats: pda::epoch_stats(&source_config).0,
+            dest_epoch_stats: pda::epoch_stats(&dest_config).0,
+            source_ledger: pda::ledger(&source_config).0,
+            dest_ledger: pda::ledger(&dest_config).0,
+            source_fee_treasury: pda::fee_treasury(&source_config).0,
+            dest_fee_treasury: pda::fee_treasury(&dest_config).0,
+            sender_policy: pda::policy(&sender).0,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+        },
+        ix_data::CrossNamespaceTransfer { amount },
+        &[],
+    )
+}
+
+/*
+ * Bilateral netting
+ */
+
+/// `open_obligation` for `authority`'s and `counterparty_authority`'s accounts
+pub fn open_obligation(
+    namespace: u64,
+    authority: Pubkey,
+    counterparty_authority: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    let other = pda::user(&config, &counterparty_authority).0;
+    let (party_a, party_b) = if user < other {
+        (user, other)
+    } else {
+        (other, user)
+    };
+    build(
+        ix_accounts::OpenObligation {
+            obligation: pda::obligation(&user, &other).0,
+            party_a,
+            party_b,
+            payer: authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::OpenObligation {},
+        &[],
+    )
+}
+
+/// `record_obligation`: `authority` acknowledges owing `creditor_authority` `amount`
+pub fn record_obligation(
+    namespace: u64,
+    authority: Pubkey,
+    creditor_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let debtor = pda::user(&config, &authority).0;
+    let creditor = pda::user(&config, &creditor_authority).0;
+    build(
+        ix_accounts::RecordObligation {
+            obligation: pda::obligation(&debtor, &creditor).0,
+            debtor,
+            creditor,
+            authority,
+            config,
+        },
+        ix_data::RecordObligation { amount },
+        &[],
+    )
+}
+
+/// `settle_net` by `authority` with `counterparty_authority`
+///
+/// Add the net debtor's guardians with `with_guardians` when required.
+pub fn settle_net(
+    namespace: u64,
+    authority: Pubkey,
+    counterparty_authority: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let party = pda::user(&config, &authority).0;
+    let counterparty = pda::user(&config, &counterparty_authority).0;
+    build(
+        ix_accounts::SettleNet {
+            obligation: pda::obligation(&party, &counterparty).0,
+            party,
+            counterparty,
+            authority,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            party_activity: pda::activity_log(&party).0,
+            counterparty_activity: pda::activity_log(&counterparty).0,
+            party_policy: pda::policy(&party).0,
+            counterparty_policy: pda::policy(&counterparty).0,
+        },
+        ix_data::SettleNet {},
+        &[],
+    )
+}
+
+/// `migrate_user` bringing `authority`'s account to the current layout,
+/// with `payer` covering the rent of the grown account
+pub fn migrate_user(namespace: u64, authority: Pubkey, payer: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::MigrateUser {
+            user: pda::user(&config, &authority).0,
+            payer,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::MigrateUser {},
+        &[],
+    )
+}
+
+/*
+ * Recurring invoices
+ */
+
+/// `create_invoice_template` billing `payer_authority`'s account `amount`
+/// every `interval`, signed by the merchant's `authority`
+pub fn create_invoice_template(
+    namespace: u64,
+    authority: Pubkey,
+    payer_authority: Pubkey,
+    amount: u64,
+    interval: Seconds,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let merchant = pda::user(&config, &authority).0;
+    let payer = pda::user(&config, &payer_authority).0;
+    build(
+        ix_accounts::CreateInvoiceTemplate {
+            template: pda::invoice_template(&merchant, &payer).0,
+            merchant,
+            payer,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateInvoiceTemplate { amount, interval },
+        &[],
+    )
+}
+
+/// `issue_invoice` for invoice `period` of the template billing
+/// `payer_authority` on behalf of `merchant_authority`, `cranker` paying rent
+///
+/// `expected_version` is the template version the crank read; `metered`
+/// counts the crank in the namespace's crank metrics, which must exist.
+pub fn issue_invoice(
+    namespace: u64,
+    merchant_authority: Pubkey,
+    payer_authority: Pubkey,
+    period: u64,
+    cranker: Pubkey,
+    expected_version: u64,
+    metered: bool,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let merchant = pda::user(&config, &merchant_authority).0;
+    let payer = pda::user(&config, &payer_authority).0;
+    let template = pda::invoice_template(&merchant, &payer).0;
+    build(
+        ix_accounts::IssueInvoice {
+            template,
+            invoice: pda::invoice(&template, period).0,
+            merchant,
+            payer,
+            cranker,
+            config,
+            crank_metrics: metered.then(|| pda::crank_metrics(&config).0),
+            system_program: system_program::ID,
+        },
+        ix_data::IssueInvoice { expected_version },
+        &[],
+    )
+}
+
+/// `pay_invoice` for invoice `period` from `merchant_authority`, signed by the
+/// payer's `authority`; `owner` is the user account the invoice pays, and
+/// `rent_payer` the crank that issued it
+///
+/// Add the payer's guardians with `with_guardians` when required.
+pub fn pay_invoice(
+    namespace: u64,
+    authority: Pubkey,
+    merchant_authority: Pubkey,
+    period: u64,
+    owner: Pubkey,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let payer = pda::user(&config, &authority).0;
+    let merchant = pda::user(&config, &merchant_authority).0;
+    let template = pda::invoice_template(&merchant, &payer).0;
+    build(
+        ix_accounts::PayInvoice {
+            invoice: pda::invoice(&template, period).0,
+            template,
+            payer,
+            owner,
+            authority,
+            rent_payer,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            payer_activity: pda::activity_log(&payer).0,
+            owner_activity: pda::activity_log(&owner).0,
+            payer_policy: pda::policy(&payer).0,
+        },
+        ix_data::PayInvoice {},
+        &[],
+    )
+}
+
+/// `reject_invoice` for invoice `period` from `merchant_authority`, signed by
+/// the payer's `authority`; `rent_payer` is the crank that issued it
+pub fn reject_invoice(
+    namespace: u64,
+    authority: Pubkey,
+    merchant_authority: Pubkey,
+    period: u64,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let payer = pda::user(&config, &authority).0;
+    let template = pda::invoice_template(&pda::user(&config, &merchant_authority).0, &payer).0;
+    build(
+        ix_accounts::RejectInvoice {
+            invoice: pda::invoice(&template, period).0,
+            template,
+            payer,
+            authority,
+            rent_payer,
+            config,
+        },
+        ix_data::RejectInvoice {},
+        &[],
+    )
+}
+
+/// `cancel_invoice_template` for the template billing `payer_authority`,
+/// signed by the merchant's `authority`
+pub fn cancel_invoice_template(
+    namespace: u64,
+    authority: Pubkey,
+    payer_authority: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let merchant = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CancelInvoiceTemplate {
+            template: pda::invoice_template(&merchant, &pda::user(&config, &payer_authority).0).0,
+            merchant,
+            authority,
+            config,
+        },
+        ix_data::CancelInvoiceTemplate {},
+        &[],
+    )
+}
+
+/// `sell_invoice` listing `invoice` at `price`, signed by its owner's
+/// `authority`
+pub fn sell_invoice(namespace: u64, authority: Pubkey, invoice: Pubkey, price: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SellInvoice {
+            invoice,
+            owner: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::SellInvoice { price },
+        &[],
+    )
+}
+
+/// `buy_invoice` buying `invoice` from its current `owner` at `price`,
+/// signed by the buyer's `authority`
+///
+/// Add the buyer's guardians with `with_guardians` when required.
+pub fn buy_invoice(
+    namespace: u64,
+    authority: Pubkey,
+    invoice: Pubkey,
+    owner: Pubkey,
+    price: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let buyer = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::BuyInvoice {
+            invoice,
+            buyer,
+            owner,
+            authority,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            buyer_activity: pda::activity_log(&buyer).0,
+            owner_activity: pda::activity_log(&owner).0,
+            buyer_policy: pda::policy(&buyer).0,
+        },
+        ix_data::BuyInvoice { price },
+        &[],
+    )
+}
+
+/*
+ * Promo codes
+ */
+
+/// `create_promo_code` for the plaintext `code`; only its hash is sent
+pub fn create_promo_code(
+    namespace: u64,
+    admin: Pubkey,
+    code: &str,
+    reward: PromoReward,
+    max_uses: u32,
+    expires_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::CreatePromoCode {
+            promo_code: pda::promo_code(&config, code).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::CreatePromoCode {
+            code_hash: promo_code_hash(code),
+            reward,
+            max_uses,
+            expires_at,
+        },
+        &[],
+    )
+}
+
+/// `promo_transfer` of `amount` from `authority` to `receiver_authority`,
+/// redeeming `code`
+///
+/// Add the sender's guardians with `with_guardians` when required.
+pub fn promo_transfer(
+    namespace: u64,
+    authority: Pubkey,
+    receiver_authority: Pubkey,
+    amount: u64,
+    code: &str,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let sender = pda::user(&config, &authority).0;
+    let receiver = pda::user(&config, &receiver_authority).0;
+    let promo_code = pda::promo_code(&config, code).0;
+    build(
+        ix_accounts::PromoTransfer {
+            sender,
+            receiver,
+            promo_code,
+            promo_claim: pda::promo_claim(&promo_code, &sender).0,
+            authority,
+            config,
+            epoch_stats: pda::epoch_stats(&config).0,
+            ledger: pda::ledger(&config).0,
+            fee_treasury: pda::fee_treasury(&config).0,
+            system_program: system_program::ID,
+            sender_policy: pda::policy(&sender).0,
+            sender_activity: pda::activity_log(&sender).0,
+            receiver_activity: pda::activity_log(&receiver).0,
+        },
+        ix_data::PromoTransfer {
+            amount,
+            code: code.to_string(),
+        },
+        &[],
+    )
+}
+
+/*
+ * Time-locked escrow
+ */
+
+/// `create_escrow` locking `amount` of `authority`'s balance for
+/// `beneficiary` until `unlock_at`
+///
+/// Add the creator's guardians with `with_guardians` when required.
+pub fn create_escrow(
+    namespace: u64,
+    authority: Pubkey,
+    amount: u64,
+    unlock_at: i64,
+    beneficiary: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let creator = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CreateEscrow {
+            escrow: pda::escrow(&config, &creator, &beneficiary, unlock_at).0,
+            creator,
+            authority,
+            config,
+            ledger: pda::ledger(&config).0,
+            system_program: system_program::ID,
+            creator_policy: pda::policy(&creator).0,
+            creator_activity: pda::activity_log(&creator).0,
+        },
+        ix_data::CreateEscrow {
+            amount,
+            unlock_at,
+            beneficiary,
+        },
+        &[],
+    )
+}
+
+/// `claim_escrow` of the escrow `creator_authority` locked for the signing
+/// `beneficiary` until `unlock_at`
+pub fn claim_escrow(
+    namespace: u64,
+    beneficiary: Pubkey,
+    creator_authority: Pubkey,
+    unlock_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let creator = pda::user(&config, &creator_authority).0;
+    let beneficiary_user = pda::user(&config, &beneficiary).0;
+    build(
+        ix_accounts::ClaimEscrow {
+            escrow: pda::escrow(&config, &creator, &beneficiary, unlock_at).0,
+            beneficiary: beneficiary_user,
+            authority: beneficiary,
+            creator_authority,
+            creator,
+            config,
+            ledger: pda::ledger(&config).0,
+            beneficiary_activity: pda::activity_log(&beneficiary_user).0,
+        },
+        ix_data::ClaimEscrow {},
+        &[],
+    )
+}
+
+/// `cancel_escrow` of the escrow `authority` locked for `beneficiary` until
+/// `unlock_at`
+pub fn cancel_escrow(
+    namespace: u64,
+    authority: Pubkey,
+    beneficiary: Pubkey,
+    unlock_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let creator = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CancelEscrow {
+            escrow: pda::escrow(&config, &creator, &beneficiary, unlock_at).0,
+            creator,
+            authority,
+            config,
+            ledger: pda::ledger(&config).0,
+            creator_activity: pda::activity_log(&creator).0,
+        },
+        ix_data::CancelEscrow {},
+        &[],
+    )
+}
+
+/*
+ * Cashback
+ */
+
+/// `initialize_cashback` crediting `rate` of merchant payments, up to
+/// `epoch_budget` per epoch
+pub fn initialize_cashback(
+    namespace: u64,
+    admin: Pubkey,
+    rate: Bps,
+    epoch_budget: u64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitializeCashback {
+            cashback: pda::cashback(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitializeCashback { rate, epoch_budget },
+        &[],
+    )
+}
+
+/// `set_cashback` changing the rate and per-epoch budget
+pub fn set_cashback(namespace: u64, admin: Pubkey, rate: Bps, epoch_budget: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetCashback {
+            cashback: pda::cashback(&config).0,
+            config,
+            admin,
+        },
+        ix_data::SetCashback { rate, epoch_budget },
+        &[],
+    )
+}
+
+/// `register_merchant` for `merchant_authority`'s user account
+pub fn register_merchant(namespace: u64, admin: Pubkey, merchant_authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let merchant = pda::user(&config, &merchant_authority).0;
+    build(
+        ix_accounts::RegisterMerchant {
+            registration: pda::cashback_merchant(&merchant).0,
+            merchant,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::RegisterMerchant {},
+        &[],
+    )
+}
+
+/// `deregister_merchant` for `merchant_authority`'s user account
+pub fn deregister_merchant(
+    namespace: u64,
+    admin: Pubkey,
+    merchant_authority: Pubkey,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let merchant = pda::user(&config, &merchant_authority).0;
+    build(
+        ix_accounts::DeregisterMerchant {
+            registration: pda::cashback_merchant(&merchant).0,
+            merchant,
+            config,
+            admin,
+        },
+        ix_data::DeregisterMerchant {},
+        &[],
+    )
+}
+
+/*
+ * Credit lines
+ */
+
+/// `open_credit_line` from `authority`'s account to `borrower_authority`'s
+pub fn open_credit_line(
+    namespace: u64,
+    authority: Pubkey,
+    borrower_authority: Pubkey,
+    limit: u64,
+    rate: Bps,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let lender = pda::user(&config, &authority).0;
+    let borrower = pda::user(&config, &borrower_authority).0;
+    build(
+        ix_accounts::OpenCreditLine {
+            credit_line: pda::credit_line(&lender, &borrower).0,
+            lender,
+            borrower,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::OpenCreditLine { limit, rate },
+        &[],
+    )
+}
+
+/// Accounts of the credit line `lender_authority` extended to the signing borrower
+fn use_credit_line(
+    namespace: u64,
+    authority: Pubkey,
+    lender_authority: Pubkey,
+) -> ix_accounts::UseCreditLine {
+    let config = pda::config(namespace).0;
+    let lender = pda::user(&config, &lender_authority).0;
+    let borrower = pda::user(&config, &authority).0;
+    ix_accounts::UseCreditLine {
+        credit_line: pda::credit_line(&lender, &borrower).0,
+        lender,
+        borrower,
+        authority,
+        config,
+        ledger: pda::ledger(&config).0,
+        lender_activity: pda::activity_log(&lender).0,
+        borrower_activity: pda::activity_log(&borrower).0,
+        lender_policy: pda::policy(&lender).0,
+        borrower_policy: pda::policy(&borrower).0,
+    }
+}
+
+/// `draw_credit` of `amount` on the line `lender_authority` extended to `authority`
+pub fn draw_credit(
+    namespace: u64,
+    authority: Pubkey,
+    lender_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        use_credit_line(namespace, authority, lender_authority),
+        ix_data::DrawCredit { amount },
+        &[],
+    )
+}
+
+/// `repay_credit` of `amount` on the line `lender_authority` extended to `authority`
+pub fn repay_credit(
+    namespace: u64,
+    authority: Pubkey,
+    lender_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        use_credit_line(namespace, authority, lender_authority),
+        ix_data::RepayCredit { amount },
+        &[],
+    )
+}
+
+/// `set_credit_line_frozen` on the line `authority` extended to `borrower_authority`
+pub fn set_credit_line_frozen(
+    namespace: u64,
+    authority: Pubkey,
+    borrower_authority: Pubkey,
+    frozen: bool,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let lender = pda::user(&config, &authority).0;
+    let borrower = pda::user(&config, &borrower_authority).0;
+    build(
+        ix_accounts::SetCreditLineFrozen {
+            credit_line: pda::credit_line(&lender, &borrower).0,
+            lender,
+            authority,
+            config,
+        },
+        ix_data::SetCreditLineFrozen { frozen },
+        &[],
+    )
+}
+
+/// `set_daily_limit` on the caller's account; 0 removes the limit
+pub fn set_daily_limit(namespace: u64, authority: Pubkey, daily_limit: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetDailyLimit {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::SetDailyLimit { daily_limit },
+        &[],
+    )
+}
+
+/*
+ * Profiles
+ */
+
+/// `create_profile` for the caller's account
+pub fn create_profile(namespace: u64, authority: Pubkey, params: ProfileParams) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CreateProfile {
+            profile: pda::profile(&user).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::CreateProfile { params },
+        &[],
+    )
+}
+
+/// `update_profile` replacing the caller's profile
+pub fn update_profile(namespace: u64, authority: Pubkey, params: ProfileParams) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::UpdateProfile {
+            profile: pda::profile(&user).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::UpdateProfile { params },
+        &[],
+    )
+}
+
+/*
+ * Statements and reports
+ */
+
+/// `generate_statement` for period `epoch` of `user_authority`'s account;
+/// the `AccountStatement` comes back as the transaction's return data
+pub fn generate_statement(namespace: u64, user_authority: Pubkey, epoch: u64) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &user_authority).0;
+    build(
+        ix_accounts::GenerateStatement {
+            user,
+            activity_log: pda::activity_log(&user).0,
+            config,
+        },
+        ix_data::GenerateStatement { epoch },
+        &[],
+    )
+}
+
+/// `compile_compliance_report` over `[from, to)` for `user_authority`'s
+/// account; the `ComplianceReport` comes back as the transaction's return data
+pub fn compile_compliance_report(
+    namespace: u64,
+    user_authority: Pubkey,
+    from: i64,
+    to: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &user_authority).0;
+    build(
+        ix_accounts::CompileComplianceReport {
+            user,
+            activity_log: pda::activity_log(&user).0,
+            config,
+        },
+        ix_data::CompileComplianceReport { from, to },
+        &[],
+    )
+}
+
+/// `get_account_health` for `user_authority`'s account, counting the
+/// `credit_lines` it lends or borrows on; the `AccountHealth` comes back as
+/// the transaction's return data
+pub fn get_account_health(
+    namespace: u64,
+    user_authority: Pubkey,
+    credit_lines: &[Pubkey],
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let remaining: Vec<AccountMeta> = credit_lines
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect();
+    build(
+        ix_accounts::GetAccountHealth {
+            user: pda::user(&config, &user_authority).0,
+            config,
+        },
+        ix_data::GetAccountHealth {},
+        &remaining,
+    )
+}
+
+/// `program_fingerprint` for the namespace, passing its CPI registry when
+/// `has_registry`; the digest comes back as the transaction's return data
+pub fn program_fingerprint(namespace: u64, has_registry: bool) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::ProgramFingerprint {
+            config,
+            registry: has_registry.then(|| pda::cpi_registry(&config).0),
+        },
+        ix_data::ProgramFingerprint {},
+        &[],
+    )
+}
+
+/*
+ * Tax lots
+ */
+
+/// `init_tax_lots` for the caller's account, consuming lots by `method`
+pub fn init_tax_lots(namespace: u64, authority: Pubkey, method: LotMethod) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::InitTaxLots {
+            book: pda::tax_lots(&user).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::InitTaxLots { method },
+        &[],
+    )
+}
+
+/// `add_tax_lot_page` creating page `index`, the book's `next_page`
+pub fn add_tax_lot_page(namespace: u64, authority: Pubkey, index: u32) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::AddTaxLotPage {
+            book: pda::tax_lots(&user).0,
+            page: pda::tax_lot_page(&user, index).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::AddTaxLotPage {},
+        &[],
+    )
+}
+
+/// `set_lot_method` for the caller's book
+pub fn set_lot_method(namespace: u64, authority: Pubkey, method: LotMethod) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::SetLotMethod {
+            book: pda::tax_lots(&user).0,
+            user,
+            authority,
+            config,
+        },
+        ix_data::SetLotMethod { method },
+        &[],
+    )
+}
+
+/// `close_tax_lot_page` for the caller's emptied page `index`
+pub fn close_tax_lot_page(namespace: u64, authority: Pubkey, index: u32) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CloseTaxLotPage {
+            book: pda::tax_lots(&user).0,
+            page: pda::tax_lot_page(&user, index).0,
+            user,
+            authority,
+            config,
+        },
+        ix_data::CloseTaxLotPage {},
+        &[],
+    )
+}
+
+/*
+ * Watchtowers
+ */
+
+/// `set_watchtower` for the caller's account; the default key removes it
+pub fn set_watchtower(namespace: u64, authority: Pubkey, watchtower: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetWatchtower {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::SetWatchtower { watchtower },
+        &[],
+    )
+}
+
+/// `raise_alert` on `user_authority`'s account, signed by its watchtower
+pub fn raise_alert(
+    namespace: u64,
+    user_authority: Pubkey,
+    watchtower: Pubkey,
+    reason: AlertReason,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::RaiseAlert {
+            user: pda::user(&config, &user_authority).0,
+            watchtower,
+            config,
+        },
+        ix_data::RaiseAlert { reason },
+        &[],
+    )
+}
+
+/// `dismiss_alert` for the caller's account
+pub fn dismiss_alert(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::DismissAlert {
+            user: pda::user(&config, &authority).0,
+            authority,
+            config,
+        },
+        ix_data::DismissAlert {},
+        &[],
+    )
+}
+
+/*
+ * Shadow fees
+ */
+
+/// `init_shadow_fees` rehearsing `candidate` on the namespace's transfers
+pub fn init_shadow_fees(namespace: u64, admin: Pubkey, candidate: FeeStrategy) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitShadowFees {
+            shadow_fees: pda::shadow_fees(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitShadowFees { candidate },
+        &[],
+    )
+}
+
+/// `set_shadow_fee_strategy` replacing the rehearsed strategy
+pub fn set_shadow_fee_strategy(
+    namespace: u64,
+    admin: Pubkey,
+    candidate: FeeStrategy,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetShadowFeeStrategy {
+            shadow_fees: pda::shadow_fees(&config).0,
+            config,
+            admin,
+        },
+        ix_data::SetShadowFeeStrategy { candidate },
+        &[],
+    )
+}
+
+/*
+ * Chaos injection
+ */
+
+/// `init_chaos` configuring failure injection for the namespace
+pub fn init_chaos(namespace: u64, admin: Pubkey, params: ChaosParams) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitChaos {
+            chaos: pda::chaos(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitChaos { params },
+        &[],
+    )
+}
+
+/// `set_chaos` retuning failure injection
+pub fn set_chaos(namespace: u64, admin: Pubkey, params: ChaosParams) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::SetChaos {
+            chaos: pda::chaos(&config).0,
+            config,
+            admin,
+        },
+        ix_data::SetChaos { params },
+        &[],
+    )
+}
+
+/*
+ * Transfer policies
+ */
+
+/// `create_policy` attaching `code` and `allowlist` to the caller's account
+pub fn create_policy(
+    namespace: u64,
+    authority: Pubkey,
+    code: Vec<u8>,
+    allowlist: Vec<Pubkey>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::CreatePolicy {
+            policy: pda::policy(&user).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::CreatePolicy { code, allowlist },
+        &[],
+    )
+}
+
+/// `update_policy` replacing the caller's policy
+pub fn update_policy(
+    namespace: u64,
+    authority: Pubkey,
+    code: Vec<u8>,
+    allowlist: Vec<Pubkey>,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::ManagePolicy {
+            policy: pda::policy(&user).0,
+            user,
+            authority,
+            config,
+        },
+        ix_data::UpdatePolicy { code, allowlist },
+        &[],
+    )
+}
+
+/// `close_policy` removing the caller's policy
+pub fn close_policy(namespace: u64, authority: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::ManagePolicy {
+            policy: pda::policy(&user).0,
+            user,
+            authority,
+            config,
+        },
+        ix_data::ClosePolicy {},
+        &[],
+    )
+}
+
+/*
+ * Crank contention
+ */
+
+/// `init_crank_metrics` counting the namespace's winning cranks
+pub fn init_crank_metrics(namespace: u64, admin: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    build(
+        ix_accounts::InitCrankMetrics {
+            crank_metrics: pda::crank_metrics(&config).0,
+            config,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix_data::InitCrankMetrics {},
+        &[],
+    )
+}
+
+/*
+ * Capability tokens
+ */
+
+/// `issue_capability`: `authority` lets `service`, labelled `name`, act within
+/// `scopes` until `expires_at` and pays rent
+pub fn issue_capability(
+    namespace: u64,
+    authority: Pubkey,
+    service: Pubkey,
+    name: String,
+    scopes: u64,
+    max_uses: u64,
+    expires_at: i64,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::IssueCapability {
+            token: pda::capability(&user, &service).0,
+            user,
+            authority,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::IssueCapability {
+            service,
+            name,
+            scopes,
+            max_uses,
+            expires_at,
+        },
+        &[],
+    )
+}
+
+/// `revoke_capability` closing `service`'s token on the caller's account
+pub fn revoke_capability(namespace: u64, authority: Pubkey, service: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &authority).0;
+    build(
+        ix_accounts::RevokeCapability {
+            token: pda::capability(&user, &service).0,
+            user,
+            authority,
+            config,
+        },
+        ix_data::RevokeCapability {},
+        &[],
+    )
+}
+
+/// `service_heartbeat` signed by `service` for `user_authority`'s account
+pub fn service_heartbeat(namespace: u64, user_authority: Pubkey, service: Pubkey) -> Instruction {
+    let config = pda::config(namespace).0;
+    let user = pda::user(&config, &user_authority).0;
+    build(
+        ix_accounts::ServiceHeartbeat {
+            token: pda::capability(&user, &service).0,
+            user,
+            service,
+            config,
+        },
+        ix_data::ServiceHeartbeat {},
+        &[],
+    )
+}
+
+/// `capability_create_invoice_template`: `service` bills `payer_authority`
+/// for `merchant_authority`, paying the template's rent
+pub fn capability_create_invoice_template(
+    namespace: u64,
+    merchant_authority: Pubkey,
+    payer_authority: Pubkey,
+    service: Pubkey,
+    amount: u64,
+    interval: Seconds,
+) -> Instruction {
+    let config = pda::config(namespace).0;
+    let merchant = pda::user(&config, &merchant_authority).0;
+    let payer = pda::user(&config, &payer_authority).0;
+    build(
+        ix_accounts::CapabilityCreateInvoiceTemplate {
+            token: pda::capability(&merchant, &service).0,
+            template: pda::invoice_template(&merchant, &payer).0,
+            merchant,
+            payer,
+            service,
+            config,
+            system_program: system_program::ID,
+        },
+        ix_data::CapabilityCreateInvoiceTemplate { amount, interval },
+        &[],
+    )
+}
@@ -0,0 +1,47 @@
+/*
+ * ============================================================================
+ * ANCHOR TEST CONTRACT - CLIENT SDK
+ * ============================================================================
+ *
+ * Lightweight client helpers for backend services that need to build
+ * transactions for the program without the full anchor-client stack:
+ * - `pda`: deterministic address derivation for every program account
+ * - `instructions`: typed builders returning plain `Instruction`s
+ * - `accounts`: decoders for raw account data
+ * - `events`: decoders for program logs and CPI event data
+ *
+ * All argument and account types are re-exported from the program crate, so
+ * a change to an instruction signature or account layout breaks this crate
+ * at compile time instead of at runtime.
+ *
+ * Builders take the target `namespace` first; every PDA is derived under
+ * that namespace's config account.
+ */
+
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+
+// Shared types straight from the program crate
+pub use anchor_test_contract::{
+    authority_change_summary, build_features, capability_scopes, claim_types, config_fingerprint,
+    consistency_checks, discriminators, format_amount, health_flags, instruction_flags,
+    instruction_versions, manager_permissions, operation_digest, policy_op, promo_code_hash,
+    report_flags, withdrawal_summary, AccountHealth, AccountMode, AccountStatement, ActivityEntry,
+    ActivityKind, ActivityLog, AlertReason, Allowance, AttachmentPage, Attestation, Auction,
+    AuctionStatus, Bps, CapabilityToken, CashbackMerchant, CashbackProgram, ChaosConfig,
+    ChaosParams, Commitment, ComplianceReport, CpiProgramRegistry, CrankMetrics, CreditLine,
+    DocumentKind, EmissionCurve, EmissionSchedule, EpochStats, Escrow, ExpirableKind, FeeSchedule,
+    FeeStrategy, FeeTier, FeeTreasury, GlobalConfig, IdempotencyKey, InstructionTag,
+    InstructionVersion, Invoice, InvoiceTemplate, IssuerRecord, JointAccount, JointMode, Ledger,
+    LedgerAccount, LotDisposal, LotMethod, ManagerGrant, MintConfig, MintConfigParams,
+    NamespaceMetadata, NamespaceMetadataParams, Obligation, ProfileParams, PromoClaim, PromoCode,
+    PromoReward, RecentOperations, RouteHop, SealedBid, Seconds, ShadowFees, SpendGuard, TaxLot,
+    TaxLotBook, TaxLotPage, TransferPolicy, UserAccount, UserProfile, ID as PROGRAM_ID,
+    INSTRUCTION_VERSIONS,
+};
+
+// Re-exported so callers do not need a direct solana-program dependency
+pub use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+pub use anchor_lang::solana_program::pubkey::Pubkey;
@@ -0,0 +1,280 @@
+//! PDA derivation helpers mirroring the seeds used by the program
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_test_contract::{
+    promo_code_hash, ALT_AUTHORITY_SEED, ID, TREASURY_SEED, VAULT_AUTHORITY_SEED,
+};
+
+/// `[b"config", namespace]`
+pub fn config(namespace: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config", &namespace.to_le_bytes()], &ID)
+}
+
+/// `[b"epoch_stats", config]`
+pub fn epoch_stats(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"epoch_stats", config.as_ref()], &ID)
+}
+
+/// `[b"ledger", config]`
+pub fn ledger(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"ledger", config.as_ref()], &ID)
+}
+
+/// `[b"user", config, authority]`
+pub fn user(config: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"user", config.as_ref(), authority.as_ref()], &ID)
+}
+
+/// `[b"activity_log", user]`
+pub fn activity_log(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"activity_log", user.as_ref()], &ID)
+}
+
+/// `[b"joint", user]`
+pub fn joint(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"joint", user.as_ref()], &ID)
+}
+
+/// `[b"recent_ops", user]`
+pub fn recent_operations(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"recent_ops", user.as_ref()], &ID)
+}
+
+/// `[b"mint_config", config, mint]`
+pub fn mint_config(config: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint_config", config.as_ref(), mint.as_ref()], &ID)
+}
+
+/// `[ALT_AUTHORITY_SEED, config]`
+pub fn alt_authority(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ALT_AUTHORITY_SEED, config.as_ref()], &ID)
+}
+
+/// `[b"commitment", config, owner, domain]`
+pub fn commitment(config: &Pubkey, owner: &Pubkey, domain: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"commitment",
+            config.as_ref(),
+            owner.as_ref(),
+            &domain.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// `[b"auction", config, seller, auction_id]`
+pub fn auction(config: &Pubkey, seller: &Pubkey, auction_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"auction",
+            config.as_ref(),
+            seller.as_ref(),
+            &auction_id.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// `[b"bid", auction, bidder]`
+pub fn sealed_bid(auction: &Pubkey, bidder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bid", auction.as_ref(), bidder.as_ref()], &ID)
+}
+
+/// `[b"attachments", user, page]`
+pub fn attachment_page(user: &Pubkey, page: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"attachments", user.as_ref(), &page.to_le_bytes()], &ID)
+}
+
+/// `[b"issuer", config, issuer]`
+pub fn issuer_record(config: &Pubkey, issuer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"issuer", config.as_ref(), issuer.as_ref()], &ID)
+}
+
+/// `[b"attestation", issuer, user, claim_type]`
+pub fn attestation(issuer: &Pubkey, user: &Pubkey, claim_type: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"attestation",
+            issuer.as_ref(),
+            user.as_ref(),
+            &claim_type.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// `[b"namespace_metadata", config]`
+pub fn namespace_metadata(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"namespace_metadata", config.as_ref()], &ID)
+}
+
+/// `[b"cpi_registry", config]`
+pub fn cpi_registry(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"cpi_registry", config.as_ref()], &ID)
+}
+
+/// `[b"manager_grant", user, manager]` for the user account PDA `user`
+pub fn manager_grant(user: &Pubkey, manager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"manager_grant", user.as_ref(), manager.as_ref()], &ID)
+}
+
+/// `[b"vault", config, mint]`
+pub fn vault(config: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", config.as_ref(), mint.as_ref()], &ID)
+}
+
+/// `[b"vault_position", user, mint]`
+pub fn vault_position(user: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_position", user.as_ref(), mint.as_ref()], &ID)
+}
+
+/// `[b"reserves", mint_config]`
+pub fn reserves(mint_config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reserves", mint_config.as_ref()], &ID)
+}
+
+/// `[VAULT_AUTHORITY_SEED, config]`
+pub fn vault_authority(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED, config.as_ref()], &ID)
+}
+
+/// `[b"allowance", owner, delegate]` for the owner's user account PDA `owner`
+pub fn allowance(owner: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"allowance", owner.as_ref(), delegate.as_ref()], &ID)
+}
+
+/// `[b"fee_treasury", config]`
+pub fn fee_treasury(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_treasury", config.as_ref()], &ID)
+}
+
+/// `[b"spend_guard", user]`
+pub fn spend_guard(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"spend_guard", user.as_ref()], &ID)
+}
+
+/// `[b"bridge", low, high]` for the two config addresses in either order
+pub fn namespace_bridge(config: &Pubkey, other: &Pubkey) -> (Pubkey, u8) {
+    let (low, high) = if config < other {
+        (config, other)
+    } else {
+        (other, config)
+    };
+    Pubkey::find_program_address(&[b"bridge", low.as_ref(), high.as_ref()], &ID)
+}
+
+/// `[b"obligation", low, high]` for the two user accounts in either order
+pub fn obligation(user: &Pubkey, other: &Pubkey) -> (Pubkey, u8) {
+    let (low, high) = if user < other {
+        (user, other)
+    } else {
+        (other, user)
+    };
+    Pubkey::find_program_address(&[b"obligation", low.as_ref(), high.as_ref()], &ID)
+}
+
+/// `[b"escrow", config, creator, beneficiary, unlock_at]`
+pub fn escrow(
+    config: &Pubkey,
+    creator: &Pubkey,
+    beneficiary: &Pubkey,
+    unlock_at: i64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"escrow",
+            config.as_ref(),
+            creator.as_ref(),
+            beneficiary.as_ref(),
+            &unlock_at.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// `[b"invoice_template", merchant, payer]` of two user accounts
+pub fn invoice_template(merchant: &Pubkey, payer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"invoice_template", merchant.as_ref(), payer.as_ref()],
+        &ID,
+    )
+}
+
+/// `[b"invoice", template, period]`
+pub fn invoice(template: &Pubkey, period: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"invoice", template.as_ref(), &period.to_le_bytes()], &ID)
+}
+
+/// `[b"promo_code", config, promo_code_hash(code)]`
+pub fn promo_code(config: &Pubkey, code: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"promo_code", config.as_ref(), &promo_code_hash(code)],
+        &ID,
+    )
+}
+
+/// `[b"promo_claim", promo_code, user]`
+pub fn promo_claim(promo_code: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"promo_claim", promo_code.as_ref(), user.as_ref()], &ID)
+}
+
+/// `[TREASURY_SEED, config]`
+pub fn treasury(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, config.as_ref()], &ID)
+}
+
+/// `[b"cashback", config]`
+pub fn cashback(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"cashback", config.as_ref()], &ID)
+}
+
+/// `[b"cashback_merchant", merchant]`, keyed by the merchant's user account
+pub fn cashback_merchant(merchant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"cashback_merchant", merchant.as_ref()], &ID)
+}
+
+/// `[b"credit_line", lender, borrower]`, keyed by the two user accounts
+pub fn credit_line(lender: &Pubkey, borrower: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"credit_line", lender.as_ref(), borrower.as_ref()], &ID)
+}
+
+/// `[b"profile", user]`
+pub fn profile(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"profile", user.as_ref()], &ID)
+}
+
+/// `[b"tax_lots", user]`
+pub fn tax_lots(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"tax_lots", user.as_ref()], &ID)
+}
+
+/// `[b"tax_lot_page", user, index]`
+pub fn tax_lot_page(user: &Pubkey, index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"tax_lot_page", user.as_ref(), &index.to_le_bytes()], &ID)
+}
+
+/// `[b"shadow_fees", config]`
+pub fn shadow_fees(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"shadow_fees", config.as_ref()], &ID)
+}
+
+/// `[b"chaos", config]`
+pub fn chaos(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"chaos", config.as_ref()], &ID)
+}
+
+/// `[b"policy", user]`
+pub fn policy(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"policy", user.as_ref()], &ID)
+}
+
+/// `[b"crank_metrics", config]`
+pub fn crank_metrics(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"crank_metrics", config.as_ref()], &ID)
+}
+
+/// `[b"capability", user, service]`
+pub fn capability(user: &Pubkey, service: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"capability", user.as_ref(), service.as_ref()], &ID)
+}